@@ -0,0 +1,485 @@
+use ethers::{
+    prelude::*,
+    types::{Address, U256},
+};
+use std::{
+    collections::{BTreeSet, HashMap},
+    sync::Arc,
+    time::{Duration, Instant},
+};
+use tokio::sync::{OnceCell, RwLock};
+
+use liquidation_bot::{
+    errors::{ProviderError, Result},
+    math::{mul_div, to_f64, BPS},
+    oracle::PriceSource,
+};
+
+// Pool functions missing from the bundled AavePool ABI. Structs made only of
+// static fields encode like flat return values, so they are declared that way.
+abigen!(
+    AaveReserves,
+    r#"[
+        function getReservesList() external view returns (address[])
+        function getUserConfiguration(address user) external view returns (uint256)
+        function getReserveData(address asset) external view returns (uint256 configuration, uint128 liquidityIndex, uint128 currentLiquidityRate, uint128 variableBorrowIndex, uint128 currentVariableBorrowRate, uint128 currentStableBorrowRate, uint40 lastUpdateTimestamp, uint16 id, address aTokenAddress, address stableDebtTokenAddress, address variableDebtTokenAddress, address interestRateStrategyAddress, uint128 accruedToTreasury, uint128 unbacked, uint128 isolationModeTotalDebt)
+        function getUserEMode(address user) external view returns (uint256)
+        function getEModeCategoryData(uint8 id) external view returns ((uint16,uint16,uint16,address,string))
+        function ADDRESSES_PROVIDER() external view returns (address)
+    ]"#
+);
+
+abigen!(
+    AaveAddressesProvider,
+    r#"[function getPriceOracle() external view returns (address)]"#
+);
+
+abigen!(
+    AaveOracle,
+    r#"[
+        function getAssetsPrices(address[] assets) external view returns (uint256[])
+        function getSourcesOfAssets(address[] assets) external view returns (address[])
+    ]"#
+);
+
+// Chainlink proxies forward to an aggregator, which is where reports are transmitted
+abigen!(
+    ChainlinkProxy,
+    r#"[function aggregator() external view returns (address)]"#
+);
+
+abigen!(
+    AavePriceSource,
+    r#"[function latestAnswer() external view returns (int256)]"#
+);
+
+abigen!(
+    AaveBalance,
+    r#"[function balanceOf(address owner) external view returns (uint256)]"#
+);
+
+// Governance rarely touches reserve parameters; re-read them this often
+const RESERVES_TTL: Duration = Duration::from_secs(600);
+// Aave's flash loan premium, in bps
+const FLASH_LOAN_FEE_BPS: u64 = 5;
+// Share of a reserve's debt one liquidation may repay, in bps. Below
+// CLOSE_FACTOR_HF_THRESHOLD the whole debt can go at once.
+const DEFAULT_CLOSE_FACTOR: u64 = 5_000;
+const MAX_CLOSE_FACTOR: u64 = 10_000;
+const CLOSE_FACTOR_HF_THRESHOLD: f64 = 0.95;
+
+// One listed reserve with the parameters a liquidation depends on
+#[derive(Debug, Clone)]
+pub struct Reserve {
+    pub asset: Address,
+    // Index into the user configuration bitmap
+    pub id: u16,
+    pub a_token: Address,
+    pub stable_debt: Address,
+    pub variable_debt: Address,
+    pub decimals: u8,
+    // Share of collateral value that backs debt, in bps
+    pub liquidation_threshold: u64,
+    // Collateral paid out per unit of debt repaid, in bps (10500 = 5% bonus)
+    pub liquidation_bonus: u64,
+    // Cut of the bonus the protocol keeps, in bps of the bonus
+    pub liquidation_protocol_fee: u64,
+    // Efficiency mode category the asset belongs to, 0 for none
+    pub emode_category: u8,
+    // Isolated collateral has a debt ceiling; zero otherwise
+    pub debt_ceiling: U256,
+}
+
+impl Reserve {
+    // Field layout from Aave's ReserveConfiguration library (V3.0 and V3.1,
+    // where eMode membership is still set per reserve)
+    fn from_config(asset: Address, id: u16, a_token: Address, stable_debt: Address, variable_debt: Address, config: U256) -> Self {
+        let field = |offset: usize, width: usize| (config >> offset) & ((U256::one() << width) - 1);
+        let bits = |offset: usize, width: usize| field(offset, width).low_u64();
+        Self {
+            asset,
+            id,
+            a_token,
+            stable_debt,
+            variable_debt,
+            decimals: bits(48, 8) as u8,
+            liquidation_threshold: bits(16, 16),
+            liquidation_bonus: bits(32, 16),
+            liquidation_protocol_fee: bits(152, 16),
+            emode_category: bits(168, 8) as u8,
+            debt_ceiling: field(212, 40),
+        }
+    }
+}
+
+// Efficiency mode: correlated assets (stablecoins, ETH and its LSTs) lent
+// against each other with a higher threshold and a smaller bonus
+#[derive(Debug, Clone)]
+pub struct EModeCategory {
+    pub liquidation_threshold: u64,
+    pub liquidation_bonus: u64,
+    // Prices every asset in the category when set, instead of the Aave oracle
+    pub price_source: Address,
+}
+
+// Listed reserves and the eMode categories they use
+#[derive(Debug, Clone, Default)]
+pub struct Reserves {
+    pub list: Vec<Reserve>,
+    pub emode_categories: HashMap<u8, EModeCategory>,
+}
+
+// A user's balance in one reserve, priced in the oracle's base currency
+#[derive(Debug, Clone)]
+pub struct Holding {
+    pub reserve: Reserve,
+    pub collateral: U256,
+    pub debt: U256,
+    // Base currency per whole token
+    pub price: U256,
+    // The reserve's parameters, or its eMode category's when the user is in it
+    pub liquidation_threshold: u64,
+    pub liquidation_bonus: u64,
+}
+
+impl Holding {
+    fn value(&self, amount: U256) -> U256 {
+        mul_div(amount, self.price, U256::exp10(self.reserve.decimals as usize)).unwrap_or_default()
+    }
+
+    fn amount(&self, value: U256) -> U256 {
+        if self.price.is_zero() {
+            return U256::zero();
+        }
+        mul_div(value, U256::exp10(self.reserve.decimals as usize), self.price).unwrap_or_default()
+    }
+}
+
+// Everything a user supplies and borrows, under the rules that apply to them
+#[derive(Debug, Clone, Default)]
+pub struct Position {
+    pub holdings: Vec<Holding>,
+    // The user's eMode category, 0 for none
+    pub emode: u8,
+    // Borrowing against a single isolated asset, which caps total debt
+    // against it and limits what can be borrowed
+    pub isolated: bool,
+}
+
+impl Position {
+    // Collateral value weighted by liquidation threshold over debt value,
+    // from the same prices the liquidation plan uses
+    pub fn health_factor(&self) -> f64 {
+        let bps = U256::from(BPS);
+        let (mut backing, mut debt) = (U256::zero(), U256::zero());
+        for holding in &self.holdings {
+            backing += holding.value(holding.collateral) * U256::from(holding.liquidation_threshold) / bps;
+            debt += holding.value(holding.debt);
+        }
+        if debt.is_zero() {
+            return f64::INFINITY;
+        }
+        to_f64(backing) / to_f64(debt)
+    }
+}
+
+// The collateral/debt pair to liquidate and how much of the debt to repay
+#[derive(Debug, Clone)]
+pub struct LiquidationPlan {
+    pub collateral_asset: Address,
+    pub debt_asset: Address,
+    // In debt asset units
+    pub debt_to_cover: U256,
+    // Share of the reserve's debt repayable, in bps
+    pub close_factor: u64,
+    // Collateral paid out over the debt repaid, in bps (500 = 5% bonus)
+    pub bonus_bps: u32,
+    // The user's whole balance of the collateral, in base currency
+    pub collateral_value: U256,
+    // Bonus collateral less the flash loan fee, in base currency
+    pub gross_value: U256,
+    // Debt asset price in base currency, for converting costs
+    pub debt_price: U256,
+    pub debt_decimals: u8,
+}
+
+impl LiquidationPlan {
+    // `debt_to_cover` in base currency
+    pub fn debt_value(&self) -> U256 {
+        mul_div(self.debt_to_cover, self.debt_price, U256::exp10(self.debt_decimals as usize)).unwrap_or_default()
+    }
+
+    // `value` in base currency as debt asset units
+    pub fn to_debt_units(&self, value: U256) -> U256 {
+        if self.debt_price.is_zero() {
+            return U256::zero();
+        }
+        mul_div(value, U256::exp10(self.debt_decimals as usize), self.debt_price).unwrap_or_default()
+    }
+}
+
+// Reads which reserves an Aave V3 user supplies and borrows, and plans the
+// most profitable liquidation across them
+pub struct AaveMarket<M> {
+    provider: Arc<M>,
+    pool: Address,
+    reserves: RwLock<Option<(Instant, Arc<Reserves>)>>,
+    oracle: OnceCell<Address>,
+    // Replaces the Aave oracle for markets priced by a pull oracle
+    price_source: Option<Arc<dyn PriceSource>>,
+    // Chainlink aggregator -> assets it prices; upgraded now and then, so kept with the reserves TTL
+    feeds: RwLock<Option<(Instant, Arc<HashMap<Address, Vec<Address>>>)>>,
+}
+
+impl<M: Middleware + 'static> AaveMarket<M> {
+    pub fn new(provider: Arc<M>, pool: Address) -> Self {
+        Self {
+            provider,
+            pool,
+            reserves: RwLock::new(None),
+            oracle: OnceCell::new(),
+            price_source: None,
+            feeds: RwLock::new(None),
+        }
+    }
+
+    pub fn with_price_source(mut self, source: Arc<dyn PriceSource>) -> Self {
+        self.price_source = Some(source);
+        self
+    }
+
+    pub async fn reserves(&self) -> Result<Arc<Reserves>> {
+        if let Some((loaded, reserves)) = self.reserves.read().await.as_ref() {
+            if loaded.elapsed() < RESERVES_TTL {
+                return Ok(reserves.clone());
+            }
+        }
+
+        let pool = AaveReserves::new(self.pool, self.provider.clone());
+        let assets = pool.get_reserves_list().call().await?;
+        let mut multicall = Multicall::new(self.provider.clone(), None)
+            .await
+            .map_err(|e| ProviderError::Rpc(e.to_string()))?;
+        for asset in &assets {
+            multicall.add_call(pool.get_reserve_data(*asset), false);
+        }
+        let data: Vec<GetReserveDataReturn> = multicall
+            .call_array()
+            .await
+            .map_err(|e| ProviderError::Rpc(e.to_string()))?;
+
+        let list: Vec<Reserve> = assets
+            .into_iter()
+            .zip(data)
+            .map(|(asset, d)| {
+                Reserve::from_config(asset, d.id, d.a_token_address, d.stable_debt_token_address, d.variable_debt_token_address, d.configuration)
+            })
+            .collect();
+
+        let mut emode_categories = HashMap::new();
+        let ids: BTreeSet<u8> = list.iter().map(|r| r.emode_category).filter(|c| *c != 0).collect();
+        for id in ids {
+            let (_, liquidation_threshold, liquidation_bonus, price_source, _) = pool.get_e_mode_category_data(id).call().await?;
+            emode_categories.insert(
+                id,
+                EModeCategory {
+                    liquidation_threshold: liquidation_threshold as u64,
+                    liquidation_bonus: liquidation_bonus as u64,
+                    price_source,
+                },
+            );
+        }
+
+        let reserves = Arc::new(Reserves { list, emode_categories });
+        *self.reserves.write().await = Some((Instant::now(), reserves.clone()));
+        Ok(reserves)
+    }
+
+    // Every reserve the user supplies as collateral or borrows, with balances,
+    // prices and the eMode or isolation rules that apply. `overrides` replaces
+    // oracle prices, e.g. with an update that hasn't landed yet.
+    pub async fn position(&self, user: Address, overrides: &HashMap<Address, U256>) -> Result<Position> {
+        let reserves = self.reserves().await?;
+        let pool = AaveReserves::new(self.pool, self.provider.clone());
+        // Two bits per reserve id: borrowing, then used as collateral
+        let config = pool.get_user_configuration(user).call().await?;
+        let flag = |id: u16, bit: usize| config.bit(id as usize * 2 + bit);
+
+        let used: Vec<&Reserve> = reserves.list.iter().filter(|r| flag(r.id, 0) || flag(r.id, 1)).collect();
+        if used.is_empty() {
+            return Ok(Position::default());
+        }
+        let emode = pool.get_user_e_mode(user).call().await?.low_u64() as u8;
+        let category = reserves.emode_categories.get(&emode).filter(|_| emode != 0);
+
+        let mut multicall = Multicall::new(self.provider.clone(), None)
+            .await
+            .map_err(|e| ProviderError::Rpc(e.to_string()))?;
+        for reserve in &used {
+            for token in [reserve.a_token, reserve.variable_debt, reserve.stable_debt] {
+                multicall.add_call(AaveBalance::new(token, self.provider.clone()).balance_of(user), false);
+            }
+        }
+        let balances: Vec<U256> = multicall
+            .call_array()
+            .await
+            .map_err(|e| ProviderError::Rpc(e.to_string()))?;
+
+        let mut prices = self.prices(used.iter().map(|r| r.asset).collect()).await?;
+        // A category price source replaces the oracle for its members
+        if let Some(source) = category.map(|c| c.price_source).filter(|s| !s.is_zero()) {
+            let answer = AavePriceSource::new(source, self.provider.clone()).latest_answer().call().await?;
+            let price = answer.max(I256::zero()).into_raw();
+            for (reserve, p) in used.iter().zip(prices.iter_mut()) {
+                if reserve.emode_category == emode {
+                    *p = price;
+                }
+            }
+        }
+        for (reserve, p) in used.iter().zip(prices.iter_mut()) {
+            if let Some(price) = overrides.get(&reserve.asset) {
+                *p = *price;
+            }
+        }
+
+        let holdings: Vec<Holding> = used
+            .into_iter()
+            .zip(balances.chunks(3))
+            .zip(prices)
+            .map(|((reserve, b), price)| {
+                let (liquidation_threshold, liquidation_bonus) = match category {
+                    Some(c) if reserve.emode_category == emode => (c.liquidation_threshold, c.liquidation_bonus),
+                    _ => (reserve.liquidation_threshold, reserve.liquidation_bonus),
+                };
+                Holding {
+                    collateral: if flag(reserve.id, 1) { b[0] } else { U256::zero() },
+                    debt: if flag(reserve.id, 0) { b[1] + b[2] } else { U256::zero() },
+                    reserve: reserve.clone(),
+                    price,
+                    liquidation_threshold,
+                    liquidation_bonus,
+                }
+            })
+            .collect();
+
+        let mut collateral = holdings.iter().filter(|h| !h.collateral.is_zero());
+        let isolated = matches!((collateral.next(), collateral.next()), (Some(h), None) if !h.reserve.debt_ceiling.is_zero());
+        Ok(Position {
+            holdings,
+            emode,
+            isolated,
+        })
+    }
+
+    async fn oracle(&self) -> Result<AaveOracle<M>> {
+        let oracle = self
+            .oracle
+            .get_or_try_init(|| async {
+                let pool = AaveReserves::new(self.pool, self.provider.clone());
+                let provider = pool.addresses_provider().call().await?;
+                AaveAddressesProvider::new(provider, self.provider.clone()).get_price_oracle().call().await
+            })
+            .await?;
+        Ok(AaveOracle::new(*oracle, self.provider.clone()))
+    }
+
+    // What a flash loan of `asset` can borrow right now: the underlying its
+    // aToken holds. Zero for assets Aave doesn't list.
+    pub async fn flash_liquidity(&self, asset: Address) -> Result<U256> {
+        let reserves = self.reserves().await?;
+        let Some(reserve) = reserves.list.iter().find(|r| r.asset == asset) else {
+            return Ok(U256::zero());
+        };
+        Ok(AaveBalance::new(asset, self.provider.clone()).balance_of(reserve.a_token).call().await?)
+    }
+
+    // Base currency price of each asset
+    pub async fn prices(&self, assets: Vec<Address>) -> Result<Vec<U256>> {
+        if let Some(source) = &self.price_source {
+            return source.prices(&assets).await;
+        }
+        Ok(self.oracle().await?.get_assets_prices(assets).call().await?)
+    }
+
+    // Assets priced by each Chainlink aggregator. The oracle reads a proxy's
+    // answer unchanged, so an aggregator's new answer is the assets' new price.
+    // Sources that aren't plain proxies (rate adapters, fixed prices) are left out.
+    pub async fn price_feeds(&self) -> Result<Arc<HashMap<Address, Vec<Address>>>> {
+        if let Some((loaded, feeds)) = self.feeds.read().await.as_ref() {
+            if loaded.elapsed() < RESERVES_TTL {
+                return Ok(feeds.clone());
+            }
+        }
+
+        let assets: Vec<Address> = self.reserves().await?.list.iter().map(|r| r.asset).collect();
+        let sources = self.oracle().await?.get_sources_of_assets(assets.clone()).call().await?;
+        let mut feeds: HashMap<Address, Vec<Address>> = HashMap::new();
+        for (asset, source) in assets.into_iter().zip(sources) {
+            if let Ok(aggregator) = ChainlinkProxy::new(source, self.provider.clone()).aggregator().call().await {
+                feeds.entry(aggregator).or_default().push(asset);
+            }
+        }
+
+        let feeds = Arc::new(feeds);
+        *self.feeds.write().await = Some((Instant::now(), feeds.clone()));
+        Ok(feeds)
+    }
+
+    // The collateral/debt pair paying the largest bonus, repaying as much
+    // debt as the close factor and the user's collateral allow
+    pub async fn plan(&self, user: Address, overrides: &HashMap<Address, U256>) -> Result<Option<(Position, LiquidationPlan)>> {
+        let position = self.position(user, overrides).await?;
+        let close_factor = close_factor(position.health_factor());
+        Ok(best_pair(&position.holdings, close_factor).map(|plan| (position, plan)))
+    }
+}
+
+fn close_factor(health_factor: f64) -> u64 {
+    if health_factor < CLOSE_FACTOR_HF_THRESHOLD {
+        MAX_CLOSE_FACTOR
+    } else {
+        DEFAULT_CLOSE_FACTOR
+    }
+}
+
+// Sized like Aave's LiquidationLogic: the repayable debt shrinks when the
+// collateral can't pay it back with the bonus on top
+fn best_pair(holdings: &[Holding], close_factor: u64) -> Option<LiquidationPlan> {
+    let bps = U256::from(BPS);
+    let mut best: Option<LiquidationPlan> = None;
+
+    for debt in holdings.iter().filter(|h| !h.debt.is_zero()) {
+        let repayable = debt.value(debt.debt) * U256::from(close_factor) / bps;
+        for collateral in holdings.iter().filter(|h| !h.collateral.is_zero()) {
+            let bonus = U256::from(collateral.liquidation_bonus);
+            if bonus <= bps {
+                continue;
+            }
+            // Collateral ceiling: debt whose repayment plus bonus it covers
+            let ceiling = collateral.value(collateral.collateral) * bps / bonus;
+            let cover = repayable.min(ceiling);
+
+            let bonus_value = cover * (bonus - bps) / bps;
+            let protocol_fee = bonus_value * U256::from(collateral.reserve.liquidation_protocol_fee) / bps;
+            let flash_fee = cover * U256::from(FLASH_LOAN_FEE_BPS) / bps;
+            let Some(gross) = bonus_value.checked_sub(protocol_fee + flash_fee).filter(|g| !g.is_zero()) else {
+                continue;
+            };
+            if best.as_ref().map_or(false, |b| b.gross_value >= gross) {
+                continue;
+            }
+            best = Some(LiquidationPlan {
+                collateral_asset: collateral.reserve.asset,
+                debt_asset: debt.reserve.asset,
+                debt_to_cover: debt.amount(cover),
+                close_factor,
+                bonus_bps: (collateral.liquidation_bonus - BPS as u64) as u32,
+                collateral_value: collateral.value(collateral.collateral),
+                gross_value: gross,
+                debt_price: debt.price,
+                debt_decimals: debt.reserve.decimals,
+            });
+        }
+    }
+    best
+}
@@ -0,0 +1,178 @@
+use ethers::types::{I256, U256};
+use std::{
+    collections::HashMap,
+    sync::Mutex,
+    time::{Duration, Instant},
+};
+
+#[derive(Debug, Clone)]
+pub struct AllocatorConfig {
+    // Total gas spend (wei) all strategies may use per period
+    pub gas_budget_per_period: U256,
+    // Total working capital (wei) that may be committed at once
+    pub capital_budget: U256,
+    pub period: Duration,
+    // Every strategy keeps at least this share so it can prove itself again
+    pub min_share_bps: u32,
+    // Weight of the latest period's return when updating a strategy's score
+    pub return_smoothing: f64,
+}
+
+impl Default for AllocatorConfig {
+    fn default() -> Self {
+        Self {
+            gas_budget_per_period: U256::exp10(18), // 1 ETH of gas per period
+            capital_budget: U256::from(10) * U256::exp10(18),
+            period: Duration::from_secs(3600),
+            min_share_bps: 1000,
+            return_smoothing: 0.3,
+        }
+    }
+}
+
+// Budget handed out by `try_reserve`; must be returned via `settle`
+#[derive(Debug, Clone)]
+pub struct Reservation {
+    pub strategy: String,
+    pub gas: U256,
+    pub capital: U256,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct StrategyUsage {
+    pub share_bps: u32,
+    pub gas_spent: U256,
+    pub gas_reserved: U256,
+    pub capital_committed: U256,
+    pub realized_profit: I256,
+    pub return_score: f64,
+}
+
+struct AllocatorState {
+    period_start: Instant,
+    strategies: HashMap<String, StrategyUsage>,
+}
+
+pub struct CapitalAllocator {
+    config: AllocatorConfig,
+    state: Mutex<AllocatorState>,
+}
+
+impl CapitalAllocator {
+    pub fn new(config: AllocatorConfig) -> Self {
+        Self {
+            config,
+            state: Mutex::new(AllocatorState {
+                period_start: Instant::now(),
+                strategies: HashMap::new(),
+            }),
+        }
+    }
+
+    // Strategies start with an equal share until they have a track record
+    pub fn register(&self, strategy: &str) {
+        let mut state = self.state.lock().unwrap();
+        state.strategies.entry(strategy.to_string()).or_default();
+        let equal = 10_000 / state.strategies.len() as u32;
+        for usage in state.strategies.values_mut() {
+            usage.share_bps = equal;
+        }
+    }
+
+    // Reserve gas and capital for one execution, or None if the strategy is over budget
+    pub fn try_reserve(&self, strategy: &str, gas: U256, capital: U256) -> Option<Reservation> {
+        let mut state = self.state.lock().unwrap();
+        self.roll_period(&mut state);
+
+        let total_capital: U256 = state
+            .strategies
+            .values()
+            .fold(U256::zero(), |acc, u| acc + u.capital_committed);
+
+        let usage = state.strategies.get_mut(strategy)?;
+        let gas_allowance = self.config.gas_budget_per_period * usage.share_bps / 10_000;
+        let capital_allowance = self.config.capital_budget * usage.share_bps / 10_000;
+
+        if usage.gas_spent + usage.gas_reserved + gas > gas_allowance {
+            return None;
+        }
+        if usage.capital_committed + capital > capital_allowance
+            || total_capital + capital > self.config.capital_budget
+        {
+            return None;
+        }
+
+        usage.gas_reserved += gas;
+        usage.capital_committed += capital;
+
+        Some(Reservation {
+            strategy: strategy.to_string(),
+            gas,
+            capital,
+        })
+    }
+
+    // Release a reservation and book what actually happened
+    pub fn settle(&self, reservation: Reservation, gas_spent: U256, profit: I256) {
+        let mut state = self.state.lock().unwrap();
+        if let Some(usage) = state.strategies.get_mut(&reservation.strategy) {
+            usage.gas_reserved = usage.gas_reserved.saturating_sub(reservation.gas);
+            usage.capital_committed = usage.capital_committed.saturating_sub(reservation.capital);
+            usage.gas_spent += gas_spent;
+            usage.realized_profit += profit;
+        }
+    }
+
+    pub fn snapshot(&self) -> HashMap<String, StrategyUsage> {
+        self.state.lock().unwrap().strategies.clone()
+    }
+
+    // At each period boundary, move share towards strategies with the best return on gas
+    fn roll_period(&self, state: &mut AllocatorState) {
+        if state.period_start.elapsed() < self.config.period {
+            return;
+        }
+        state.period_start = Instant::now();
+
+        let alpha = self.config.return_smoothing;
+        for usage in state.strategies.values_mut() {
+            let period_return = if usage.gas_spent.is_zero() {
+                0.0
+            } else {
+                i256_to_f64(usage.realized_profit) / u256_to_f64(usage.gas_spent)
+            };
+            usage.return_score = (1.0 - alpha) * usage.return_score + alpha * period_return;
+            usage.gas_spent = U256::zero();
+            usage.realized_profit = I256::zero();
+        }
+
+        let n = state.strategies.len() as u32;
+        if n == 0 {
+            return;
+        }
+        let floor = self.config.min_share_bps.min(10_000 / n);
+        let distributable = 10_000 - floor * n;
+        let positive_total: f64 = state
+            .strategies
+            .values()
+            .map(|u| u.return_score.max(0.0))
+            .sum();
+
+        for usage in state.strategies.values_mut() {
+            let weight = if positive_total > 0.0 {
+                usage.return_score.max(0.0) / positive_total
+            } else {
+                1.0 / n as f64
+            };
+            usage.share_bps = floor + (distributable as f64 * weight) as u32;
+        }
+    }
+}
+
+fn u256_to_f64(value: U256) -> f64 {
+    value.to_string().parse().unwrap_or(f64::MAX)
+}
+
+fn i256_to_f64(value: I256) -> f64 {
+    value.to_string().parse().unwrap_or(0.0)
+}
@@ -0,0 +1,121 @@
+// Property tests for the constant-product quote, checked against an
+// arbitrary-precision reference, and edge cases of the stableswap quote.
+//
+// Run with:
+//   cargo test --lib amm_tests
+
+use crate::{
+    dex::{curve::get_dy, DexManager},
+    models::CurveState,
+};
+use ethers::types::U256;
+use num_bigint::BigUint;
+use proptest::prelude::*;
+
+fn big(value: U256) -> BigUint {
+    let mut bytes = [0u8; 32];
+    value.to_big_endian(&mut bytes);
+    BigUint::from_bytes_be(&bytes)
+}
+
+fn reference(amount_in: U256, reserve_in: U256, reserve_out: U256, fee_bps: u32) -> BigUint {
+    let with_fee = big(amount_in) * BigUint::from(10_000 - fee_bps);
+    (&with_fee * big(reserve_out)) / (big(reserve_in) * BigUint::from(10_000u32) + with_fee)
+}
+
+fn u256() -> impl Strategy<Value = U256> {
+    any::<[u8; 32]>().prop_map(|bytes| U256::from_big_endian(&bytes))
+}
+
+// Up to 2^bits, so products land on both sides of 2^256
+fn up_to(bits: usize) -> impl Strategy<Value = U256> {
+    u256().prop_map(move |v| if bits >= 256 { v } else { v >> (256 - bits) })
+}
+
+proptest! {
+    #[test]
+    fn matches_reference_when_it_fits(
+        amount_in in up_to(200),
+        reserve_in in up_to(200).prop_filter("non-zero", |r| !r.is_zero()),
+        reserve_out in up_to(200),
+        fee_bps in 0u32..=10_000,
+    ) {
+        let out = DexManager::calculate_output_amount(amount_in, reserve_in, reserve_out, fee_bps);
+        let expected = if amount_in.is_zero() || reserve_out.is_zero() {
+            BigUint::from(0u8)
+        } else {
+            reference(amount_in, reserve_in, reserve_out, fee_bps)
+        };
+        prop_assert_eq!(big(out), expected);
+    }
+
+    #[test]
+    fn never_panics_and_stays_below_reserve(
+        amount_in in u256(),
+        reserve_in in u256(),
+        reserve_out in u256(),
+        fee_bps in 0u32..=20_000,
+    ) {
+        let out = DexManager::calculate_output_amount(amount_in, reserve_in, reserve_out, fee_bps);
+        prop_assert!(out < reserve_out || out.is_zero());
+    }
+
+    #[test]
+    fn monotonic_in_amount_in(
+        a in up_to(128),
+        b in up_to(128),
+        reserve_in in up_to(128).prop_filter("non-zero", |r| !r.is_zero()),
+        reserve_out in up_to(128),
+        fee_bps in 0u32..10_000,
+    ) {
+        let (small, large) = if a <= b { (a, b) } else { (b, a) };
+        prop_assert!(
+            DexManager::calculate_output_amount(small, reserve_in, reserve_out, fee_bps)
+                <= DexManager::calculate_output_amount(large, reserve_in, reserve_out, fee_bps)
+        );
+    }
+}
+
+#[test]
+fn huge_reserves_no_longer_overflow() {
+    // 1e30 units of an 18-decimal token on each side; amount_in * reserve_out
+    // alone passes 2^256 when amount_in is also large
+    let reserve = U256::exp10(48);
+    let amount_in = U256::exp10(40);
+    let out = DexManager::calculate_output_amount(amount_in, reserve, reserve, 30);
+    assert_eq!(big(out), reference(amount_in, reserve, reserve, 30));
+}
+
+fn curve_pool(balances: [u64; 3], amplification: u64) -> CurveState {
+    CurveState {
+        balances: balances.iter().map(|b| U256::from(*b) * U256::exp10(18)).collect(),
+        decimals: vec![18; 3],
+        amplification: U256::from(amplification),
+        fee: U256::from(4_000_000),
+        index0: 0,
+        index1: 1,
+    }
+}
+
+#[test]
+fn curve_quotes_a_balanced_pool() {
+    let state = curve_pool([1_000_000; 3], 2_000);
+    let out = get_dy(&state, 0, 1, U256::exp10(18)).unwrap();
+    // Near 1:1 less the 0.04% fee
+    assert!(out > U256::exp10(18) * 999 / 1000 && out < U256::exp10(18));
+}
+
+#[test]
+fn curve_declines_a_pool_with_an_empty_coin() {
+    let state = curve_pool([1_000_000, 0, 1_000_000], 2_000);
+    assert_eq!(get_dy(&state, 0, 2, U256::exp10(18)), None);
+    assert_eq!(get_dy(&curve_pool([1_000_000; 3], 0), 0, 1, U256::exp10(18)), None);
+}
+
+#[test]
+fn curve_declines_out_of_range_indices() {
+    let state = curve_pool([1_000_000; 3], 2_000);
+    assert_eq!(get_dy(&state, 3, 1, U256::exp10(18)), None);
+    assert_eq!(get_dy(&state, 0, 7, U256::exp10(18)), None);
+    assert_eq!(get_dy(&state, 1, 1, U256::exp10(18)), None);
+}
@@ -0,0 +1,210 @@
+use async_trait::async_trait;
+use ethers::{
+    providers::{Http, JsonRpcClient, Middleware, Provider},
+    types::{Address, U256, U64},
+    utils::format_ether,
+};
+use serde::{de::DeserializeOwned, Serialize};
+use serde_json::Value;
+use std::{
+    collections::{HashMap, HashSet},
+    fmt::Debug,
+    path::PathBuf,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc,
+    },
+};
+use url::Url;
+use tracing::{info, warn};
+
+use crate::{
+    errors::{BotError, Result},
+    Config,
+};
+use liquidation_bot::{
+    dex::{DexManager, TokenFilter},
+    models::ArbitrageOpportunity,
+    price::UsdPricer,
+    replay::{Recorder, Replayer},
+    strategy::arbitrage::{actionable, liquid},
+};
+
+// Tip assumed on top of the historical base fee
+const PRIORITY_FEE_GWEI: u64 = 1;
+
+// State reads that default to "latest" and are re-pointed at the replayed block
+const PINNED_METHODS: &[&str] = &["eth_call", "eth_getStorageAt", "eth_getBalance", "eth_getCode"];
+
+// HTTP client that answers every state read as of one block, so the DEX
+// handlers replay history without knowing they are
+#[derive(Debug)]
+struct PinnedClient<P> {
+    inner: P,
+    block: Arc<AtomicU64>,
+}
+
+#[async_trait]
+impl<P: JsonRpcClient> JsonRpcClient for PinnedClient<P> {
+    type Error = P::Error;
+
+    async fn request<T, R>(&self, method: &str, params: T) -> std::result::Result<R, Self::Error>
+    where
+        T: Debug + Serialize + Send + Sync,
+        R: DeserializeOwned + Send,
+    {
+        if !PINNED_METHODS.contains(&method) {
+            return self.inner.request(method, params).await;
+        }
+
+        // Block tags are plain strings, so this can't fail for the methods above
+        let mut params = serde_json::to_value(&params).expect("rpc params serialize");
+        if let Some(Value::String(tag)) = params.as_array_mut().and_then(|p| p.last_mut()) {
+            if tag == "latest" {
+                *tag = format!("{:#x}", self.block.load(Ordering::Relaxed));
+            }
+        }
+        self.inner.request(method, params).await
+    }
+}
+
+#[derive(Default)]
+struct PairStats {
+    found: usize,
+    net_profit: U256,
+}
+
+// Where a backtest reads chain state from
+pub enum Source {
+    Archive(String),
+    // Archive node, with every response also written to a file
+    Record { archive_url: String, path: PathBuf },
+    // A previous recording; no network access
+    Replay(PathBuf),
+}
+
+// Replay `from_block..=to_block` against an archive node: read pool state at
+// each block, run the arbitrage scan with the live thresholds, and report what
+// would have been found and earned.
+pub async fn run(config: Config, source: Source, from_block: u64, to_block: u64) -> Result<()> {
+    if from_block > to_block {
+        return Err(BotError::Config(format!("empty block range {}..{}", from_block, to_block)));
+    }
+    info!(from_block, to_block, "⏪ Backtesting");
+
+    match source {
+        Source::Archive(url) => scan(config, http(&url)?, from_block, to_block).await,
+        Source::Record { archive_url, path } => {
+            let recorder = Recorder::create(http(&archive_url)?, &path).map_err(|e| BotError::Config(e.to_string()))?;
+            info!(path = %path.display(), "⏺️ Recording RPC responses");
+            scan(config, recorder, from_block, to_block).await
+        }
+        Source::Replay(path) => {
+            let replayer = Replayer::open(&path).map_err(|e| BotError::Config(e.to_string()))?;
+            info!(path = %path.display(), "▶️ Replaying RPC responses");
+            scan(config, replayer, from_block, to_block).await
+        }
+    }
+}
+
+fn http(url: &str) -> Result<Http> {
+    let parsed = Url::parse(url).map_err(|e| BotError::Config(format!("invalid archive url {}: {}", url, e)))?;
+    Ok(Http::new(parsed))
+}
+
+async fn scan<P: JsonRpcClient + 'static>(config: Config, client: P, from_block: u64, to_block: u64) -> Result<()> {
+    let pinned = Arc::new(AtomicU64::new(from_block));
+    let provider = Arc::new(Provider::new(PinnedClient {
+        inner: client,
+        block: pinned.clone(),
+    }));
+
+    let mut dex = DexManager::new();
+    dex.set_token_filter(TokenFilter::new(config.token_allowlist.clone(), config.token_denylist.clone()));
+    config.chain.register_venues(&mut dex, provider.clone());
+
+    // Pools that existed at the start of the range
+    let tokens = config.target_tokens.resolve(provider.clone(), None).await?;
+    let mut pools = dex.get_all_pools(&tokens).await;
+    dex.apply_transfer_taxes(&mut pools).await;
+    if config.exclude_taxed_tokens {
+        pools.retain(|p| p.token0.transfer_tax_bps == 0 && p.token1.transfer_tax_bps == 0);
+    }
+    info!(pools = pools.len(), block = from_block, "🏊 Pools loaded");
+
+    let pricer = UsdPricer::from_env(provider.clone(), &config.chain)?;
+    let mut found: Vec<ArbitrageOpportunity> = Vec::new();
+    let mut previous = HashSet::new();
+    for number in from_block..=to_block {
+        pinned.store(number, Ordering::Relaxed);
+
+        let Some(block) = provider.get_block(number).await? else {
+            return Err(BotError::Config(format!("archive node has no block {}", number)));
+        };
+        let gas_price = block.base_fee_per_gas.unwrap_or_default() + U256::exp10(9) * PRIORITY_FEE_GWEI;
+
+        for pool in pools.iter_mut() {
+            if let Err(e) = dex.refresh_pool(pool, U64::from(number)).await {
+                warn!(pool = ?pool.address, block = number, error = %e, "⚠️ Refresh failed");
+            }
+        }
+
+        let l1_fee_per_byte = config.chain.gas_model.l1_fee_per_byte(provider.clone()).await?;
+        let prices = pricer.prices(&pools, U64::from(number), block.timestamp).await;
+        let scanned = liquid(pools.clone(), &prices, config.min_liquidity_usd);
+        let opportunities = dex
+            .find_arbitrage_opportunities(&scanned, gas_price, l1_fee_per_byte, U64::from(number))
+            .await;
+        let opportunities = actionable(opportunities, config.chain.wrapped_native, config.min_profit_usd, &prices);
+
+        // A spread still open from the previous block would already have been taken
+        let current: HashSet<_> = opportunities.iter().map(route_key).collect();
+        let new: Vec<_> = opportunities.into_iter().filter(|o| !previous.contains(&route_key(o))).collect();
+        if !new.is_empty() {
+            info!(block = number, count = new.len(), "💰 New opportunities");
+        }
+        found.extend(new);
+        previous = current;
+    }
+
+    report(&found, to_block - from_block + 1);
+    Ok(())
+}
+
+fn route_key(o: &ArbitrageOpportunity) -> (Address, Address, Address) {
+    (o.buy_pool.address, o.sell_pool.address, o.token_in)
+}
+
+fn report(found: &[ArbitrageOpportunity], blocks: u64) {
+    let mut by_route: HashMap<String, PairStats> = HashMap::new();
+    let mut total = U256::zero();
+
+    for o in found {
+        let net = o.gross_profit.saturating_sub(o.gas_cost);
+        total += net;
+        let route = format!(
+            "{}/{} {} -> {}",
+            o.buy_pool.token0.symbol, o.buy_pool.token1.symbol, o.buy_pool.dex, o.sell_pool.dex
+        );
+        let stats = by_route.entry(route).or_default();
+        stats.found += 1;
+        stats.net_profit += net;
+    }
+
+    let mut routes: Vec<_> = by_route.into_iter().collect();
+    routes.sort_by(|a, b| b.1.net_profit.cmp(&a.1.net_profit));
+
+    println!();
+    println!("{:<40} {:>8} {:>20}", "route", "found", "net profit (ETH)");
+    for (route, stats) in &routes {
+        println!("{:<40} {:>8} {:>20}", route, stats.found, format_ether(stats.net_profit));
+    }
+
+    println!();
+    println!(
+        "📊 {} blocks | {} opportunities | would have earned {} ETH after gas",
+        blocks,
+        found.len(),
+        format_ether(total),
+    );
+}
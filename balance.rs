@@ -0,0 +1,168 @@
+use ethers::{
+    prelude::*,
+    providers::{Provider, Ws},
+    types::{Address, U256},
+};
+use std::sync::Arc;
+use tracing::{error, info};
+
+use liquidation_bot::{policy::PolicySigner, signer::WalletSigner};
+
+use crate::{
+    errors::Result,
+    monitoring::{AlertLevel, AlertManager, Incident},
+};
+
+abigen!(
+    Weth,
+    r#"[
+        function deposit() external payable
+        function withdraw(uint256 wad) external
+        function balanceOf(address owner) external view returns (uint256)
+    ]"#
+);
+
+type Client = SignerMiddleware<Arc<Provider<Ws>>, PolicySigner<WalletSigner>>;
+
+#[derive(Debug, Clone)]
+pub struct BalanceConfig {
+    pub weth: Address,
+    // Below this much ETH executions risk failing for gas
+    pub min_eth: U256,
+    // Level to restore ETH to when topping up
+    pub target_eth: U256,
+    // Working WETH kept for non-flash-loan paths
+    pub min_weth: U256,
+    pub target_weth: U256,
+    // Wrap/unwrap between ETH and WETH to restore targets
+    pub auto_wrap: bool,
+    // Optional treasury key that tops up the hot wallet when both balances are low
+    pub treasury_key: Option<String>,
+}
+
+impl BalanceConfig {
+    pub fn from_env() -> Self {
+        let eth = |name: &str, default: &str| {
+            ethers::utils::parse_ether(std::env::var(name).unwrap_or_else(|_| default.to_string()))
+                .unwrap_or_default()
+        };
+
+        Self {
+            weth: "0xC02aaA39b223FE8D0A0e5C4F27eAD9083C756Cc2".parse().unwrap(),
+            min_eth: eth("MIN_ETH_BALANCE", "0.2"),
+            target_eth: eth("TARGET_ETH_BALANCE", "0.5"),
+            min_weth: eth("MIN_WETH_BALANCE", "0"),
+            target_weth: eth("TARGET_WETH_BALANCE", "0"),
+            auto_wrap: std::env::var("AUTO_WRAP").map(|v| v == "true").unwrap_or(true),
+            treasury_key: std::env::var("TREASURY_PRIVATE_KEY").ok(),
+        }
+    }
+}
+
+pub struct BalanceMonitor {
+    config: BalanceConfig,
+    provider: Arc<Provider<Ws>>,
+    weth: Weth<Client>,
+    wallet: Address,
+    alerts: Arc<AlertManager>,
+}
+
+impl BalanceMonitor {
+    pub fn new(
+        config: BalanceConfig,
+        provider: Arc<Provider<Ws>>,
+        wallet: PolicySigner<WalletSigner>,
+        alerts: Arc<AlertManager>,
+    ) -> Self {
+        let address = wallet.address();
+        let client = Arc::new(SignerMiddleware::new(provider.clone(), wallet));
+        let weth = Weth::new(config.weth, client);
+
+        Self {
+            config,
+            provider,
+            weth,
+            wallet: address,
+            alerts,
+        }
+    }
+
+    // One pass: rebalance ETH/WETH if allowed, then alert on anything still short
+    pub async fn check(&self) -> Result<()> {
+        let mut eth = self.provider.get_balance(self.wallet, None).await?;
+        let mut weth = self.weth.balance_of(self.wallet).call().await?;
+
+        if self.config.auto_wrap {
+            if eth < self.config.min_eth && weth > self.config.target_weth {
+                // Gas comes first: unwrap any WETH above its target
+                let amount = (self.config.target_eth - eth).min(weth - self.config.target_weth);
+                info!(amount = %ethers::utils::format_ether(amount), "🔄 Unwrapping WETH for gas");
+                self.weth.withdraw(amount).send().await?.await?;
+                eth += amount;
+                weth -= amount;
+            } else if weth < self.config.min_weth && eth > self.config.target_eth {
+                let amount = (self.config.target_weth - weth).min(eth - self.config.target_eth);
+                info!(amount = %ethers::utils::format_ether(amount), "🔄 Wrapping ETH into WETH");
+                self.weth.deposit().value(amount).send().await?.await?;
+                eth -= amount;
+                weth += amount;
+            }
+        }
+
+        if eth < self.config.min_eth {
+            if let Some(key) = &self.config.treasury_key {
+                let amount = self.config.target_eth - eth;
+                match self.top_up_from_treasury(key, amount).await {
+                    Ok(()) => eth += amount,
+                    Err(e) => error!(error = %e, "❌ Treasury top-up failed"),
+                }
+            }
+        }
+
+        if eth < self.config.min_eth {
+            self.alerts
+                .page(
+                    Incident::GasReserve,
+                    &format!(
+                        "Executor ETH balance {} below gas floor {}",
+                        ethers::utils::format_ether(eth),
+                        ethers::utils::format_ether(self.config.min_eth)
+                    ),
+                )
+                .await;
+        } else {
+            self.alerts.resolve(Incident::GasReserve).await;
+        }
+
+        if weth < self.config.min_weth {
+            self.alerts
+                .send_alert(
+                    AlertLevel::Warning,
+                    &format!(
+                        "Executor WETH balance {} below working capital floor {}",
+                        ethers::utils::format_ether(weth),
+                        ethers::utils::format_ether(self.config.min_weth)
+                    ),
+                )
+                .await;
+        }
+
+        Ok(())
+    }
+
+    async fn top_up_from_treasury(&self, key: &str, amount: U256) -> Result<()> {
+        let chain_id = self.provider.get_chainid().await?.as_u64();
+        let treasury = key.parse::<LocalWallet>()?.with_chain_id(chain_id);
+        let client = SignerMiddleware::new(self.provider.clone(), treasury);
+
+        let tx = TransactionRequest::new().to(self.wallet).value(amount);
+        client
+            .send_transaction(tx, None)
+            .await
+            .map_err(|e| crate::errors::ExecutionError::Reverted(e.to_string()))?
+            .await?;
+
+        info!(amount = %ethers::utils::format_ether(amount), "💸 Topped up ETH from treasury");
+        Ok(())
+    }
+}
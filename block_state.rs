@@ -0,0 +1,201 @@
+use ethers::{
+    prelude::*,
+    providers::{Provider, Ws},
+    types::{Address, BlockId, Filter, H256, U256, U64},
+    utils::keccak256,
+};
+use std::{
+    collections::{HashMap, HashSet},
+    sync::Arc,
+};
+use tokio::sync::watch;
+use tracing::warn;
+
+use crate::{
+    chain_tracker::{ChainTracker, Reorg, REORG_DEPTH},
+    errors::Result,
+    gas::{estimate_fees, Confidence, FeeEstimate, GasModel},
+};
+
+abigen!(
+    ChainlinkAggregator,
+    r#"[function latestRoundData() external view returns (uint80, int256, uint256, uint256, uint80)]"#
+);
+
+// Everything strategies need about one block, fetched once and pinned to its hash
+#[derive(Debug, Clone, Default)]
+pub struct BlockSnapshot {
+    pub number: U64,
+    pub hash: H256,
+    pub timestamp: U256,
+    pub base_fee: U256,
+    // Fee-history estimate for the next block; `gas_price` is its normal price
+    pub fees: FeeEstimate,
+    pub gas_price: U256,
+    // L1 data fee per transaction byte (wei); zero off rollups
+    pub l1_fee_per_byte: U256,
+    // Chainlink answer per feed, as reported (feed decimals, usually 8)
+    pub prices: HashMap<Address, I256>,
+    // Lending-pool users whose position changed in this block
+    pub positions_touched: HashSet<Address>,
+    // Set when this block replaced blocks already published; state read at
+    // the orphaned heights is no longer on chain
+    pub reorg: Option<Reorg>,
+}
+
+impl BlockSnapshot {
+    pub fn is_empty(&self) -> bool {
+        self.number.is_zero()
+    }
+}
+
+pub struct BlockStateService {
+    provider: Arc<Provider<Ws>>,
+    lending_pool: Address,
+    price_feeds: Vec<Address>,
+    gas_model: GasModel,
+    sender: watch::Sender<Arc<BlockSnapshot>>,
+}
+
+impl BlockStateService {
+    pub fn new(provider: Arc<Provider<Ws>>, lending_pool: Address, price_feeds: Vec<Address>) -> Self {
+        let (sender, _) = watch::channel(Arc::new(BlockSnapshot::default()));
+        Self {
+            provider,
+            lending_pool,
+            price_feeds,
+            gas_model: GasModel::default(),
+            sender,
+        }
+    }
+
+    // How the chain prices L1 data, on rollups
+    pub fn with_gas_model(mut self, gas_model: GasModel) -> Self {
+        self.gas_model = gas_model;
+        self
+    }
+
+    pub fn subscribe(&self) -> watch::Receiver<Arc<BlockSnapshot>> {
+        self.sender.subscribe()
+    }
+
+    // Latest published snapshot (empty until the first block arrives)
+    pub fn latest(&self) -> Arc<BlockSnapshot> {
+        self.sender.borrow().clone()
+    }
+
+    pub async fn run(self: Arc<Self>) -> Result<()> {
+        let mut blocks = self.provider.subscribe_blocks().await?;
+        let mut chain = ChainTracker::new(REORG_DEPTH);
+        // A reorg whose own snapshot failed, announced with the next one
+        let mut unannounced: Option<Reorg> = None;
+
+        while let Some(block) = blocks.next().await {
+            let (Some(number), Some(hash)) = (block.number, block.hash) else {
+                continue;
+            };
+            let reorg = match chain.observe(&self.provider, number, hash, block.parent_hash).await {
+                Ok(reorg) => reorg,
+                Err(e) => {
+                    warn!(block = %number, error = %e, "⚠️ Reorg check failed");
+                    None
+                }
+            };
+            if let Some(reorg) = &reorg {
+                warn!(
+                    block = %number,
+                    depth = reorg.depth(),
+                    ancestor = %reorg.common_ancestor,
+                    "🔀 Chain reorganised"
+                );
+            }
+
+            let reorg = match (reorg, unannounced.take()) {
+                (Some(new), Some(old)) if old.common_ancestor < new.common_ancestor => Some(old),
+                (new, old) => new.or(old),
+            };
+
+            match self.build_snapshot(number, hash, &block).await {
+                Ok(mut snapshot) => {
+                    snapshot.reorg = reorg;
+                    self.sender.send_replace(Arc::new(snapshot));
+                }
+                Err(e) => {
+                    warn!(block = %number, error = %e, "⚠️ Snapshot failed");
+                    unannounced = reorg;
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    async fn build_snapshot(&self, number: U64, hash: H256, block: &Block<H256>) -> Result<BlockSnapshot> {
+        let at = BlockId::Hash(hash);
+
+        let (fees, l1_fee_per_byte, prices, positions_touched) = tokio::try_join!(
+            estimate_fees(self.provider.as_ref()),
+            self.gas_model.l1_fee_per_byte(self.provider.clone()),
+            self.fetch_prices(at),
+            self.fetch_touched_positions(hash),
+        )?;
+
+        Ok(BlockSnapshot {
+            number,
+            hash,
+            timestamp: block.timestamp,
+            base_fee: block.base_fee_per_gas.unwrap_or_default(),
+            fees,
+            gas_price: fees.gas_price(Confidence::Normal),
+            l1_fee_per_byte,
+            prices,
+            positions_touched,
+            reorg: None,
+        })
+    }
+
+    async fn fetch_prices(&self, at: BlockId) -> Result<HashMap<Address, I256>> {
+        let mut prices = HashMap::new();
+        for feed in &self.price_feeds {
+            let aggregator = ChainlinkAggregator::new(*feed, self.provider.clone());
+            let (_, answer, _, _, _) = aggregator.latest_round_data().block(at).call().await?;
+            prices.insert(*feed, answer);
+        }
+        Ok(prices)
+    }
+
+    async fn fetch_touched_positions(&self, hash: H256) -> Result<HashSet<Address>> {
+        let filter = Filter::new().address(self.lending_pool).at_block_hash(hash);
+        let logs = self.provider.get_logs(&filter).await?;
+
+        let liquidation_topic = H256::from(keccak256(
+            "LiquidationCall(address,address,address,uint256,uint256,address,bool)",
+        ));
+        let user_topics: HashSet<H256> = [
+            "Supply(address,address,address,uint256,uint16)",
+            "Withdraw(address,address,address,uint256)",
+            "Borrow(address,address,address,uint256,uint8,uint256,uint16)",
+            "Repay(address,address,address,uint256,bool)",
+        ]
+        .iter()
+        .map(|sig| H256::from(keccak256(sig)))
+        .collect();
+
+        let mut touched = HashSet::new();
+        for log in logs {
+            let Some(topic0) = log.topics.first() else { continue };
+            let index = if *topic0 == liquidation_topic {
+                3
+            } else if user_topics.contains(topic0) {
+                2
+            } else {
+                continue;
+            };
+            if let Some(topic) = log.topics.get(index) {
+                touched.insert(Address::from(*topic));
+            }
+        }
+
+        Ok(touched)
+    }
+}
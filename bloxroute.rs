@@ -0,0 +1,245 @@
+use ethers::types::{Bytes, Transaction, H256, U64};
+use futures::{SinkExt, StreamExt};
+use serde_json::{json, Value};
+use std::{collections::HashMap, fmt, str::FromStr, sync::Arc, time::Duration};
+use tokio::sync::mpsc;
+use tokio_tungstenite::{
+    connect_async,
+    tungstenite::{client::IntoClientRequest, Message},
+};
+use tracing::{info, warn};
+
+use crate::errors::{BotError, ExecutionError, ProviderError, Result};
+
+pub const BLOXROUTE_WS: &str = "wss://api.blxrbdn.com/ws";
+pub const BLOXROUTE_API: &str = "https://api.blxrbdn.com";
+
+const RECONNECT_DELAY: Duration = Duration::from_secs(5);
+// Pending transactions buffered for the engine before new ones are dropped
+const STREAM_BUFFER: usize = 4096;
+
+// The BDN's transaction streams. `newTxs` delivers a transaction as soon as
+// any gateway sees it; `pendingTxs` only once it has been validated.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BloxrouteStream {
+    NewTxs,
+    PendingTxs,
+}
+
+impl FromStr for BloxrouteStream {
+    type Err = String;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s.trim() {
+            "newTxs" => Ok(BloxrouteStream::NewTxs),
+            "pendingTxs" => Ok(BloxrouteStream::PendingTxs),
+            other => Err(format!("unknown stream {}, expected newTxs or pendingTxs", other)),
+        }
+    }
+}
+
+impl fmt::Display for BloxrouteStream {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            BloxrouteStream::NewTxs => write!(f, "newTxs"),
+            BloxrouteStream::PendingTxs => write!(f, "pendingTxs"),
+        }
+    }
+}
+
+// Where a bundle goes: the builders in MEV_RELAYS, bloXroute, or both
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum BundleRoute {
+    #[default]
+    Builders,
+    Bloxroute,
+    Both,
+}
+
+impl BundleRoute {
+    pub fn builders(self) -> bool {
+        self != BundleRoute::Bloxroute
+    }
+
+    pub fn bloxroute(self) -> bool {
+        self != BundleRoute::Builders
+    }
+}
+
+impl FromStr for BundleRoute {
+    type Err = String;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s.trim() {
+            "builders" => Ok(BundleRoute::Builders),
+            "bloxroute" => Ok(BundleRoute::Bloxroute),
+            "both" => Ok(BundleRoute::Both),
+            other => Err(format!("unknown route {}, expected builders, bloxroute or both", other)),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct BloxrouteConfig {
+    // Cloud API authorization header; nothing is used without it
+    pub auth: Option<String>,
+    // Read pending transactions from this stream instead of the node's txpool
+    pub stream: Option<BloxrouteStream>,
+    // Route per opportunity kind (`liquidation`, `oracle_backrun`); unlisted
+    // kinds go to the builders
+    pub routes: HashMap<String, BundleRoute>,
+    pub ws_url: String,
+    pub api_url: String,
+}
+
+impl BloxrouteConfig {
+    pub fn from_env() -> Result<Self> {
+        let stream = match std::env::var("BLOXROUTE_STREAM") {
+            Ok(s) if !s.trim().is_empty() => {
+                Some(s.parse().map_err(|e| BotError::Config(format!("BLOXROUTE_STREAM: {}", e)))?)
+            }
+            _ => None,
+        };
+        let mut routes = HashMap::new();
+        for entry in std::env::var("BLOXROUTE_ROUTES").unwrap_or_default().split(',') {
+            if entry.trim().is_empty() {
+                continue;
+            }
+            let Some((kind, route)) = entry.split_once('=') else {
+                return Err(BotError::Config(format!("BLOXROUTE_ROUTES: expected kind=route, got {}", entry.trim())));
+            };
+            let route = route.parse().map_err(|e| BotError::Config(format!("BLOXROUTE_ROUTES: {}", e)))?;
+            routes.insert(kind.trim().to_string(), route);
+        }
+
+        Ok(Self {
+            auth: std::env::var("BLOXROUTE_AUTH").ok().filter(|a| !a.trim().is_empty()),
+            stream,
+            routes,
+            ws_url: std::env::var("BLOXROUTE_WS").unwrap_or_else(|_| BLOXROUTE_WS.to_string()),
+            api_url: std::env::var("BLOXROUTE_API").unwrap_or_else(|_| BLOXROUTE_API.to_string()),
+        })
+    }
+
+    // How an opportunity of `kind` is submitted; bloXroute routes fall back
+    // to the builders without an auth header
+    pub fn route(&self, kind: &str) -> BundleRoute {
+        if self.auth.is_none() {
+            return BundleRoute::Builders;
+        }
+        self.routes.get(kind).copied().unwrap_or_default()
+    }
+}
+
+// bloXroute's Cloud API: the BDN's pending-transaction streams, which see
+// transactions before most nodes' txpools do, and bundle submission to the
+// builders bloXroute forwards to
+pub struct BloxrouteClient {
+    http: reqwest::Client,
+    config: BloxrouteConfig,
+    auth: String,
+}
+
+impl BloxrouteClient {
+    // None without an auth header
+    pub fn new(config: BloxrouteConfig) -> Option<Self> {
+        Some(Self {
+            http: reqwest::Client::new(),
+            auth: config.auth.clone()?,
+            config,
+        })
+    }
+
+    // Pending transactions from `stream`, reconnecting whenever it drops.
+    // When the receiver falls behind, transactions are dropped, not queued.
+    pub fn subscribe(self: &Arc<Self>, stream: BloxrouteStream) -> mpsc::Receiver<Transaction> {
+        let (sender, receiver) = mpsc::channel(STREAM_BUFFER);
+        let client = self.clone();
+        tokio::spawn(async move {
+            while !sender.is_closed() {
+                match client.stream(stream, &sender).await {
+                    Ok(()) => warn!(%stream, "⚠️ bloXroute stream closed, reconnecting"),
+                    Err(e) => warn!(%stream, error = %e, "⚠️ bloXroute stream failed, reconnecting"),
+                }
+                tokio::time::sleep(RECONNECT_DELAY).await;
+            }
+        });
+        receiver
+    }
+
+    async fn stream(&self, stream: BloxrouteStream, sender: &mpsc::Sender<Transaction>) -> Result<()> {
+        let connection = |e: tokio_tungstenite::tungstenite::Error| ProviderError::Connection(e.to_string());
+        let mut request = self.config.ws_url.as_str().into_client_request().map_err(connection)?;
+        request.headers_mut().insert(
+            "Authorization",
+            self.auth.parse().map_err(|_| BotError::Config("BLOXROUTE_AUTH is not a valid header".to_string()))?,
+        );
+        let (mut ws, _) = connect_async(request).await.map_err(connection)?;
+
+        let subscribe = json!({
+            "jsonrpc": "2.0",
+            "id": 1,
+            "method": "subscribe",
+            "params": [stream.to_string(), {"include": ["tx_hash", "tx_contents"]}],
+        });
+        ws.send(Message::Text(subscribe.to_string())).await.map_err(connection)?;
+        info!(%stream, "📡 bloXroute stream connected");
+
+        while let Some(message) = ws.next().await {
+            let Message::Text(text) = message.map_err(connection)? else {
+                continue;
+            };
+            let Ok(value) = serde_json::from_str::<Value>(&text) else {
+                continue;
+            };
+            if let Some(err) = value.get("error") {
+                return Err(ProviderError::Rpc(err.to_string()).into());
+            }
+            let contents = &value["params"]["result"]["txContents"];
+            let Ok(tx) = serde_json::from_value::<Transaction>(contents.clone()) else {
+                continue;
+            };
+            if sender.try_send(tx).is_err() && sender.is_closed() {
+                return Ok(());
+            }
+        }
+        Ok(())
+    }
+
+    // Submit a bundle for `block` through `blxr_submit_bundle`, which bloXroute
+    // forwards to every builder it works with
+    pub async fn send_bundle(&self, txs: &[Bytes], block: U64) -> Result<H256> {
+        // Raw transactions go without the 0x prefix
+        let transactions: Vec<String> = txs.iter().map(hex::encode).collect();
+        let body = json!({
+            "jsonrpc": "2.0",
+            "id": 1,
+            "method": "blxr_submit_bundle",
+            "params": {
+                "transaction": transactions,
+                "block_number": format!("{:#x}", block),
+                "mev_builders": {"all": ""},
+            },
+        });
+
+        let response: Value = self
+            .http
+            .post(&self.config.api_url)
+            .header("Authorization", &self.auth)
+            .json(&body)
+            .send()
+            .await
+            .map_err(|e| ProviderError::Connection(e.to_string()))?
+            .json()
+            .await
+            .map_err(|e| ProviderError::Rpc(e.to_string()))?;
+
+        if let Some(err) = response.get("error") {
+            return Err(ExecutionError::BundleRejected(err.to_string()).into());
+        }
+        response["result"]["bundleHash"]
+            .as_str()
+            .and_then(|h| h.parse().ok())
+            .ok_or_else(|| ExecutionError::BundleRejected(format!("no bundle hash in {}", response)).into())
+    }
+}
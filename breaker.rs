@@ -0,0 +1,229 @@
+use chrono::{DateTime, Duration, Utc};
+use ethers::types::{I256, U256};
+use redis::{AsyncCommands, Client as RedisClient};
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::VecDeque,
+    sync::{
+        atomic::{AtomicU32, Ordering},
+        Arc, Mutex, RwLock,
+    },
+};
+use tracing::{error, info, warn};
+
+use liquidation_bot::errors::{BotError, Result};
+
+use crate::{
+    monitoring::{AlertManager, Incident},
+    reporting::ExecutionRecord,
+};
+
+// Shared by every process on the key, so a trip, halt or re-arm reaches them all
+const TRIPPED_KEY: &str = "breaker:tripped";
+// When the breaker was last re-armed; trips from before then are cleared
+const REARMED_KEY: &str = "breaker:rearmed_at";
+
+#[derive(Debug, Clone)]
+pub struct BreakerConfig {
+    // Failed executions in a row that trip the breaker
+    pub max_consecutive_failures: u32,
+    // Realized loss within `loss_window` that trips it, in wei
+    pub max_loss: U256,
+    pub loss_window: Duration,
+    // Wallet ETH below this trips it
+    pub min_balance: U256,
+}
+
+impl BreakerConfig {
+    pub fn from_env() -> Self {
+        let eth = |name: &str, default: &str| {
+            ethers::utils::parse_ether(std::env::var(name).unwrap_or_else(|_| default.to_string()))
+                .unwrap_or_default()
+        };
+
+        Self {
+            max_consecutive_failures: std::env::var("BREAKER_MAX_CONSECUTIVE_FAILURES")
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(5),
+            max_loss: eth("BREAKER_MAX_LOSS_ETH", "0.5"),
+            loss_window: Duration::hours(
+                std::env::var("BREAKER_LOSS_WINDOW_HOURS").ok().and_then(|v| v.parse().ok()).unwrap_or(24),
+            ),
+            min_balance: eth("BREAKER_MIN_BALANCE_ETH", "0.05"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Trip {
+    pub reason: String,
+    pub at: DateTime<Utc>,
+}
+
+// Halts execution, but not scanning, when the bot starts losing money: too
+// many failures in a row, too much realized loss over a window, or the wallet
+// running low. Once tripped it stays tripped, across restarts too, until
+// re-armed by hand with `liquidation-bot rearm` or POST /breaker/rearm.
+pub struct CircuitBreaker {
+    config: BreakerConfig,
+    redis: Arc<RedisClient>,
+    alerts: Arc<AlertManager>,
+    tripped: RwLock<Option<Trip>>,
+    consecutive_failures: AtomicU32,
+    // (time, profit) of executions within the loss window, oldest first
+    realized: Mutex<VecDeque<(DateTime<Utc>, I256)>>,
+}
+
+impl CircuitBreaker {
+    pub fn new(config: BreakerConfig, redis: Arc<RedisClient>, alerts: Arc<AlertManager>) -> Self {
+        Self {
+            config,
+            redis,
+            alerts,
+            tripped: RwLock::new(None),
+            consecutive_failures: AtomicU32::new(0),
+            realized: Mutex::new(VecDeque::new()),
+        }
+    }
+
+    pub fn is_tripped(&self) -> bool {
+        self.tripped.read().unwrap().is_some()
+    }
+
+    pub fn status(&self) -> Option<Trip> {
+        self.tripped.read().unwrap().clone()
+    }
+
+    // Count an execution that reached submission
+    pub async fn record(&self, record: &ExecutionRecord) {
+        let failures = if record.success {
+            self.consecutive_failures.store(0, Ordering::Relaxed);
+            0
+        } else {
+            self.consecutive_failures.fetch_add(1, Ordering::Relaxed) + 1
+        };
+
+        let loss = {
+            let mut realized = self.realized.lock().unwrap();
+            realized.push_back((record.timestamp, record.profit));
+            let cutoff = Utc::now() - self.config.loss_window;
+            while realized.front().map_or(false, |(at, _)| *at < cutoff) {
+                realized.pop_front();
+            }
+            -realized.iter().fold(I256::zero(), |total, (_, profit)| total + *profit)
+        };
+
+        if failures >= self.config.max_consecutive_failures {
+            self.trip(format!("{} consecutive failed executions", failures)).await;
+        } else if loss > I256::from_raw(self.config.max_loss) {
+            self.trip(format!(
+                "realized loss {} ETH within {}h",
+                ethers::utils::format_ether(loss.into_raw()),
+                self.config.loss_window.num_hours()
+            ))
+            .await;
+        }
+    }
+
+    pub async fn check_balance(&self, eth: U256) {
+        if eth < self.config.min_balance {
+            self.trip(format!(
+                "wallet balance {} ETH below floor {}",
+                ethers::utils::format_ether(eth),
+                ethers::utils::format_ether(self.config.min_balance)
+            ))
+            .await;
+        }
+    }
+
+    // Halt execution until re-armed; a no-op when already tripped
+    pub async fn trip(&self, reason: String) {
+        let trip = {
+            let mut tripped = self.tripped.write().unwrap();
+            if tripped.is_some() {
+                return;
+            }
+            let trip = Trip { reason, at: Utc::now() };
+            *tripped = Some(trip.clone());
+            trip
+        };
+        error!(reason = %trip.reason, "🛑 Circuit breaker tripped, execution halted");
+        if let Err(e) = store(&self.redis, &trip).await {
+            warn!(error = %e, "⚠️ Persisting circuit breaker state failed");
+        }
+        self.alerts
+            .page(Incident::CircuitBreaker, &format!("Circuit breaker tripped: {}", trip.reason))
+            .await;
+    }
+
+    pub async fn halt(&self, reason: &str) -> Result<()> {
+        halt(&self.redis, reason).await?;
+        self.sync().await
+    }
+
+    pub async fn rearm(&self) -> Result<()> {
+        rearm(&self.redis).await?;
+        self.sync().await
+    }
+
+    // Pick up halts and re-arms made elsewhere, and persist a trip whose store failed
+    pub async fn sync(&self) -> Result<()> {
+        let mut conn = self.redis.get_async_connection().await?;
+        let (stored, rearmed_at): (Option<String>, Option<String>) =
+            redis::pipe().get(TRIPPED_KEY).get(REARMED_KEY).query_async(&mut conn).await?;
+        let stored = stored.and_then(|json| serde_json::from_str::<Trip>(&json).ok());
+        let rearmed_at = rearmed_at.and_then(|at| DateTime::parse_from_rfc3339(&at).ok());
+        let rearmed_after = |trip: &Trip| rearmed_at.map_or(false, |at| at >= trip.at);
+
+        match (self.status(), stored) {
+            (Some(local), _) if rearmed_after(&local) => {
+                *self.tripped.write().unwrap() = None;
+                self.consecutive_failures.store(0, Ordering::Relaxed);
+                self.realized.lock().unwrap().clear();
+                info!("✅ Circuit breaker re-armed, execution resumed");
+                self.alerts.resolve(Incident::CircuitBreaker).await;
+            }
+            (Some(local), None) => store(&self.redis, &local).await?,
+            (None, Some(trip)) if !rearmed_after(&trip) => {
+                error!(reason = %trip.reason, "🛑 Execution halted");
+                *self.tripped.write().unwrap() = Some(trip.clone());
+                self.alerts
+                    .page(Incident::CircuitBreaker, &format!("Execution halted: {}", trip.reason))
+                    .await;
+            }
+            _ => {}
+        }
+        Ok(())
+    }
+}
+
+async fn store(redis: &RedisClient, trip: &Trip) -> Result<()> {
+    let json = serde_json::to_string(trip).map_err(|e| BotError::Config(e.to_string()))?;
+    let mut conn = redis.get_async_connection().await?;
+    let _: () = conn.set(TRIPPED_KEY, json).await?;
+    Ok(())
+}
+
+// Manual kill switch: every running instance halts within a sync interval
+pub async fn halt(redis: &RedisClient, reason: &str) -> Result<()> {
+    store(
+        redis,
+        &Trip {
+            reason: format!("manual halt: {}", reason),
+            at: Utc::now(),
+        },
+    )
+    .await
+}
+
+// Every running instance resumes execution within a sync interval
+pub async fn rearm(redis: &RedisClient) -> Result<()> {
+    let mut conn = redis.get_async_connection().await?;
+    let _: () = redis::pipe()
+        .set(REARMED_KEY, Utc::now().to_rfc3339())
+        .del(TRIPPED_KEY)
+        .query_async(&mut conn)
+        .await?;
+    Ok(())
+}
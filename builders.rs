@@ -0,0 +1,251 @@
+use ethers::{
+    signers::Signer,
+    types::{Bytes, H256, U256, U64},
+    utils::keccak256,
+};
+use futures::future::join_all;
+use serde::Serialize;
+use serde_json::{json, Value};
+
+use crate::{
+    errors::{BotError, ExecutionError, ProviderError, Result, SimulationError},
+    signer::WalletSigner,
+};
+
+// Builders that take eth_sendBundle directly: name, endpoint, and the marker
+// each writes into the extraData of the blocks it builds
+pub const KNOWN_BUILDERS: [(&str, &str, &str); 5] = [
+    ("flashbots", "https://relay.flashbots.net", "Illuminate Dmocratize Dstribute"),
+    ("titan", "https://rpc.titanbuilder.xyz", "Titan"),
+    ("beaverbuild", "https://rpc.beaverbuild.org", "beaverbuild"),
+    ("rsync", "https://rsync-builder.xyz", "rsync"),
+    ("builder0x69", "https://builder0x69.io", "builder0x69"),
+];
+
+#[derive(Debug, Clone, Serialize)]
+pub struct Builder {
+    pub name: String,
+    pub url: String,
+    // Unknown builders can't be told apart on chain
+    #[serde(skip)]
+    extra_data: Option<String>,
+}
+
+// eth_callBundle result: what the bundle would do on top of the latest block
+#[derive(Debug, Clone, Default)]
+pub struct BundleSimulation {
+    // What the block builder is paid per unit of gas, the price bundles are ranked by
+    pub gas_price: U256,
+    pub gas_used: U256,
+    pub coinbase_diff: U256,
+    pub transactions: Vec<SimulatedTx>,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct SimulatedTx {
+    pub hash: H256,
+    pub gas_used: U256,
+    // gasUsed * gasPrice, in wei
+    pub gas_fees: U256,
+    // Decoded revert reason, or the error when there's none
+    pub revert: Option<String>,
+}
+
+impl BundleSimulation {
+    pub fn first_revert(&self) -> Option<&SimulatedTx> {
+        self.transactions.iter().find(|tx| tx.revert.is_some())
+    }
+}
+
+// The builders a bundle is sent to. Each only sees bundles sent to it, so a
+// bundle submitted to one builder is lost whenever another wins the block.
+pub struct BuilderRegistry {
+    http: reqwest::Client,
+    signer: WalletSigner,
+    builders: Vec<Builder>,
+    // Where bundles are simulated before they go out
+    simulation_endpoint: Option<String>,
+}
+
+impl BuilderRegistry {
+    // `entries` are names from KNOWN_BUILDERS or bundle endpoint URLs;
+    // requests are signed with `signer`, the searcher reputation key
+    pub fn new(entries: &[String], signer: WalletSigner) -> Self {
+        let builders = entries
+            .iter()
+            .map(|entry| {
+                let known = KNOWN_BUILDERS
+                    .iter()
+                    .find(|(name, url, _)| entry == name || entry.trim_end_matches('/') == *url);
+                match known {
+                    Some((name, url, extra_data)) => Builder {
+                        name: name.to_string(),
+                        url: url.to_string(),
+                        extra_data: Some(extra_data.to_string()),
+                    },
+                    None => Builder {
+                        name: url::Url::parse(entry)
+                            .ok()
+                            .and_then(|u| u.host_str().map(str::to_string))
+                            .unwrap_or_else(|| entry.clone()),
+                        url: entry.clone(),
+                        extra_data: None,
+                    },
+                }
+            })
+            .collect();
+
+        Self {
+            http: reqwest::Client::new(),
+            signer,
+            builders,
+            simulation_endpoint: None,
+        }
+    }
+
+    // Simulate with eth_callBundle at `url`, normally the Flashbots relay
+    pub fn with_simulation_endpoint(mut self, url: &str) -> Self {
+        self.simulation_endpoint = Some(url.to_string());
+        self
+    }
+
+    pub fn builders(&self) -> &[Builder] {
+        &self.builders
+    }
+
+    pub fn names(&self) -> Vec<String> {
+        self.builders.iter().map(|b| b.name.clone()).collect()
+    }
+
+    // Which registered builder produced a block, from its extraData
+    pub fn identify(&self, extra_data: &[u8]) -> Option<&str> {
+        let extra_data = String::from_utf8_lossy(extra_data);
+        self.builders
+            .iter()
+            .find(|b| b.extra_data.as_deref().map_or(false, |m| extra_data.contains(m)))
+            .map(|b| b.name.as_str())
+    }
+
+    // Send the bundle to every builder in `names` at once. Results come back
+    // per builder, in the order given.
+    pub async fn send_bundle(&self, names: &[String], txs: &[Bytes], block: U64) -> Vec<(String, Result<H256>)> {
+        let params = json!([{
+            "txs": txs,
+            "blockNumber": block,
+        }]);
+        // Builders compute the hash the same way; it's the fallback when one doesn't return it
+        let local_hash = bundle_hash(txs);
+
+        let sends = names
+            .iter()
+            .filter_map(|name| self.builders.iter().find(|b| &b.name == name))
+            .map(|builder| {
+                let params = params.clone();
+                async move {
+                    let result = self
+                        .signed_request(&builder.url, "eth_sendBundle", params)
+                        .await
+                        .map(|v| {
+                            v.get("bundleHash")
+                                .and_then(Value::as_str)
+                                .and_then(|h| h.parse().ok())
+                                .unwrap_or(local_hash)
+                        });
+                    (builder.name.clone(), result)
+                }
+            });
+        join_all(sends).await
+    }
+
+    // Run the bundle against the latest state as if it were in `block`
+    pub async fn simulate(&self, txs: &[Bytes], block: U64) -> Result<BundleSimulation> {
+        let Some(url) = &self.simulation_endpoint else {
+            return Err(SimulationError::Backend("no bundle simulation endpoint".to_string()).into());
+        };
+        let params = json!([{
+            "txs": txs,
+            "blockNumber": block,
+            "stateBlockNumber": "latest",
+        }]);
+        let result = self
+            .signed_request(url, "eth_callBundle", params)
+            .await
+            .map_err(|e| SimulationError::Backend(e.to_string()))?;
+
+        let transactions = result["results"]
+            .as_array()
+            .map(|results| {
+                results
+                    .iter()
+                    .map(|r| SimulatedTx {
+                        hash: r["txHash"].as_str().and_then(|h| h.parse().ok()).unwrap_or_default(),
+                        gas_used: quantity(&r["gasUsed"]),
+                        gas_fees: quantity(&r["gasFees"]),
+                        revert: r["revert"]
+                            .as_str()
+                            .or_else(|| r["error"].as_str())
+                            .map(str::to_string),
+                    })
+                    .collect()
+            })
+            .unwrap_or_default();
+        Ok(BundleSimulation {
+            gas_price: quantity(&result["bundleGasPrice"]),
+            gas_used: quantity(&result["totalGasUsed"]),
+            coinbase_diff: quantity(&result["coinbaseDiff"]),
+            transactions,
+        })
+    }
+
+    async fn signed_request(&self, url: &str, method: &str, params: Value) -> Result<Value> {
+        let body = json!({
+            "jsonrpc": "2.0",
+            "id": 1,
+            "method": method,
+            "params": params,
+        })
+        .to_string();
+
+        let response: Value = self
+            .http
+            .post(url)
+            .header("Content-Type", "application/json")
+            .header("X-Flashbots-Signature", flashbots_signature(&self.signer, &body).await?)
+            .body(body)
+            .send()
+            .await
+            .map_err(|e| ProviderError::Connection(e.to_string()))?
+            .json()
+            .await
+            .map_err(|e| ProviderError::Rpc(e.to_string()))?;
+
+        if let Some(err) = response.get("error") {
+            return Err(ExecutionError::BundleRejected(err.to_string()).into());
+        }
+        Ok(response.get("result").cloned().unwrap_or(Value::Null))
+    }
+}
+
+// X-Flashbots-Signature header for a request body: the signer's address and
+// its signature over the body hash
+pub async fn flashbots_signature(signer: &WalletSigner, body: &str) -> Result<String> {
+    let digest = format!("0x{}", hex::encode(keccak256(body.as_bytes())));
+    let signature = signer.sign_message(digest).await.map_err(BotError::from)?;
+    Ok(format!("{:?}:0x{}", signer.address(), signature))
+}
+
+// Relays mix JSON numbers, decimal strings and hex quantities
+fn quantity(v: &Value) -> U256 {
+    match v {
+        Value::Number(n) => n.as_u64().map(U256::from).unwrap_or_default(),
+        Value::String(s) if s.starts_with("0x") => U256::from_str_radix(&s[2..], 16).unwrap_or_default(),
+        Value::String(s) => U256::from_dec_str(s).unwrap_or_default(),
+        _ => U256::zero(),
+    }
+}
+
+// keccak256 over the concatenated transaction hashes
+fn bundle_hash(txs: &[Bytes]) -> H256 {
+    let hashes: Vec<u8> = txs.iter().flat_map(|tx| keccak256(tx)).collect();
+    H256::from(keccak256(hashes))
+}
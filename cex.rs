@@ -0,0 +1,321 @@
+use dashmap::DashMap;
+use ethers::types::{Address, U64};
+use futures::{SinkExt, StreamExt};
+use serde_json::{json, Value};
+use std::{
+    fmt,
+    str::FromStr,
+    sync::Arc,
+    time::{Duration, Instant},
+};
+use tokio_tungstenite::{connect_async, tungstenite::Message};
+use tracing::{info, warn};
+
+use crate::{
+    chains::ChainProfile,
+    errors::{BotError, ProviderError, Result},
+    math,
+    models::{CexDexOpportunity, DexPool},
+    pathfinder::spot_rate,
+};
+
+pub const BINANCE_WS: &str = "wss://stream.binance.com:9443/stream";
+pub const COINBASE_WS: &str = "wss://ws-feed.exchange.coinbase.com";
+
+const RECONNECT_DELAY: Duration = Duration::from_secs(5);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum CexVenue {
+    Binance,
+    Coinbase,
+}
+
+impl FromStr for CexVenue {
+    type Err = String;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s.trim() {
+            "binance" => Ok(CexVenue::Binance),
+            "coinbase" => Ok(CexVenue::Coinbase),
+            other => Err(format!("unknown exchange {}", other)),
+        }
+    }
+}
+
+impl fmt::Display for CexVenue {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            CexVenue::Binance => write!(f, "binance"),
+            CexVenue::Coinbase => write!(f, "coinbase"),
+        }
+    }
+}
+
+// A market on the exchanges and the tokens it maps to on chain, from
+// `BASE-QUOTE:base_address:quote_address`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CexPair {
+    pub base_symbol: String,
+    pub quote_symbol: String,
+    pub base: Address,
+    pub quote: Address,
+}
+
+impl CexPair {
+    pub fn name(&self) -> String {
+        format!("{}-{}", self.base_symbol, self.quote_symbol)
+    }
+
+    // The market's id on `venue`: ETHUSDC on Binance, ETH-USDC on Coinbase
+    fn market(&self, venue: CexVenue) -> String {
+        match venue {
+            CexVenue::Binance => format!("{}{}", self.base_symbol, self.quote_symbol),
+            CexVenue::Coinbase => self.name(),
+        }
+    }
+}
+
+impl FromStr for CexPair {
+    type Err = String;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        let mut parts = s.trim().split(':');
+        let (Some(market), Some(base), Some(quote), None) = (parts.next(), parts.next(), parts.next(), parts.next()) else {
+            return Err(format!("expected BASE-QUOTE:base_address:quote_address, got {}", s.trim()));
+        };
+        let Some((base_symbol, quote_symbol)) = market.split_once('-') else {
+            return Err(format!("market {} is not BASE-QUOTE", market));
+        };
+        Ok(Self {
+            base_symbol: base_symbol.to_uppercase(),
+            quote_symbol: quote_symbol.to_uppercase(),
+            base: base.parse().map_err(|_| format!("invalid address {}", base))?,
+            quote: quote.parse().map_err(|_| format!("invalid address {}", quote))?,
+        })
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct CexConfig {
+    pub enabled: bool,
+    pub venues: Vec<CexVenue>,
+    pub pairs: Vec<CexPair>,
+    // Taker fee paid on the exchange leg
+    pub fee_bps: u32,
+    // Quotes older than this are ignored
+    pub max_quote_age: Duration,
+}
+
+impl CexConfig {
+    // Pairs default to the chain's wrapped native token against USDC
+    pub fn from_env(chain: &ChainProfile) -> Result<Self> {
+        let native = match chain.chain_id {
+            56 => "BNB",
+            137 => "POL",
+            43114 => "AVAX",
+            _ => "ETH",
+        };
+        let pairs = match std::env::var("CEX_PAIRS") {
+            Ok(pairs) => pairs
+                .split(',')
+                .filter(|p| !p.trim().is_empty())
+                .map(|p| p.parse().map_err(|e| BotError::Config(format!("CEX_PAIRS: {}", e))))
+                .collect::<Result<_>>()?,
+            Err(_) => vec![CexPair {
+                base_symbol: native.to_string(),
+                quote_symbol: "USDC".to_string(),
+                base: chain.wrapped_native,
+                quote: chain.usdc,
+            }],
+        };
+        let venues = std::env::var("CEX_VENUES")
+            .unwrap_or_else(|_| "binance,coinbase".to_string())
+            .split(',')
+            .map(|v| v.parse().map_err(|e| BotError::Config(format!("CEX_VENUES: {}", e))))
+            .collect::<Result<_>>()?;
+
+        Ok(Self {
+            enabled: std::env::var("ENABLE_CEX_DEX").map(|v| v == "true").unwrap_or(false),
+            venues,
+            pairs,
+            fee_bps: std::env::var("CEX_FEE_BPS").ok().and_then(|v| v.parse().ok()).unwrap_or(10),
+            max_quote_age: Duration::from_millis(
+                std::env::var("CEX_MAX_QUOTE_AGE_MS").ok().and_then(|v| v.parse().ok()).unwrap_or(2_000),
+            ),
+        })
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Quote {
+    bid: f64,
+    ask: f64,
+    at: Instant,
+}
+
+// Best bid and ask for each configured pair, streamed from the exchanges'
+// public WebSocket tickers. Each venue reconnects on its own when dropped.
+pub struct CexFeed {
+    config: CexConfig,
+    // (pair name, venue) -> latest top of book
+    quotes: DashMap<(String, CexVenue), Quote>,
+}
+
+impl CexFeed {
+    pub fn new(config: CexConfig) -> Self {
+        Self {
+            config,
+            quotes: DashMap::new(),
+        }
+    }
+
+    pub fn spawn(self: &Arc<Self>) {
+        for venue in self.config.venues.clone() {
+            tokio::spawn(self.clone().run(venue));
+        }
+    }
+
+    async fn run(self: Arc<Self>, venue: CexVenue) {
+        loop {
+            match self.stream(venue).await {
+                Ok(()) => warn!(%venue, "⚠️ Exchange feed closed, reconnecting"),
+                Err(e) => warn!(%venue, error = %e, "⚠️ Exchange feed failed, reconnecting"),
+            }
+            tokio::time::sleep(RECONNECT_DELAY).await;
+        }
+    }
+
+    async fn stream(&self, venue: CexVenue) -> Result<()> {
+        let connection = |e: tokio_tungstenite::tungstenite::Error| ProviderError::Connection(e.to_string());
+        let markets: Vec<String> = self.config.pairs.iter().map(|p| p.market(venue)).collect();
+
+        let mut ws = match venue {
+            CexVenue::Binance => {
+                let streams: Vec<String> = markets.iter().map(|m| format!("{}@bookTicker", m.to_lowercase())).collect();
+                let (ws, _) = connect_async(format!("{}?streams={}", BINANCE_WS, streams.join("/")))
+                    .await
+                    .map_err(connection)?;
+                ws
+            }
+            CexVenue::Coinbase => {
+                let (mut ws, _) = connect_async(COINBASE_WS).await.map_err(connection)?;
+                let subscribe = json!({
+                    "type": "subscribe",
+                    "product_ids": markets,
+                    "channels": ["ticker"],
+                });
+                ws.send(Message::Text(subscribe.to_string())).await.map_err(connection)?;
+                ws
+            }
+        };
+        info!(%venue, markets = markets.len(), "📈 Exchange feed connected");
+
+        while let Some(message) = ws.next().await {
+            let Message::Text(text) = message.map_err(connection)? else {
+                continue;
+            };
+            let Ok(value) = serde_json::from_str::<Value>(&text) else {
+                continue;
+            };
+            let Some((market, bid, ask)) = parse_ticker(venue, &value) else {
+                continue;
+            };
+            if let Some(pair) = self.config.pairs.iter().find(|p| p.market(venue) == market) {
+                self.quotes.insert((pair.name(), venue), Quote { bid, ask, at: Instant::now() });
+            }
+        }
+        Ok(())
+    }
+
+    // Mid-price of `pair` across the venues with a fresh quote, quote per base
+    pub fn mid(&self, pair: &CexPair) -> Option<f64> {
+        let mids: Vec<f64> = self
+            .config
+            .venues
+            .iter()
+            .filter_map(|venue| self.quotes.get(&(pair.name(), *venue)).map(|q| *q))
+            .filter(|q| q.at.elapsed() <= self.config.max_quote_age && q.bid > 0.0 && q.ask >= q.bid)
+            .map(|q| (q.bid + q.ask) / 2.0)
+            .collect();
+        (!mids.is_empty()).then(|| mids.iter().sum::<f64>() / mids.len() as f64)
+    }
+
+    // Pools trading a configured pair whose marginal price, after the pool's
+    // fee, is further from the exchange mid than the exchange fee
+    pub fn deviations(&self, pools: &[DexPool], block_number: U64) -> Vec<CexDexOpportunity> {
+        let mut found = Vec::new();
+        for pair in &self.config.pairs {
+            let Some(mid) = self.mid(pair) else {
+                continue;
+            };
+            let hedge = 1.0 - self.config.fee_bps as f64 / math::BPS as f64;
+
+            for pool in pools.iter().filter(|p| p.has_token(pair.base) && p.has_token(pair.quote)) {
+                let (Some(base), Some(quote)) = (pool.other(pair.quote), pool.other(pair.base)) else {
+                    continue;
+                };
+                // Raw units to whole tokens of quote per base
+                let scale = 10f64.powi(base.decimals as i32 - quote.decimals as i32);
+
+                // Selling base into the pool and buying it back on the exchange
+                if let Some(rate) = spot_rate(pool, pair.base) {
+                    let dex_price = rate * scale;
+                    let edge = dex_price * hedge / mid - 1.0;
+                    if edge > 0.0 {
+                        found.push(opportunity(pair, pool, false, dex_price, mid, edge, block_number));
+                    }
+                }
+                // Buying base on the pool and selling it on the exchange
+                if let Some(rate) = spot_rate(pool, pair.quote) {
+                    let dex_price = scale / rate;
+                    let edge = mid * hedge / dex_price - 1.0;
+                    if edge > 0.0 {
+                        found.push(opportunity(pair, pool, true, dex_price, mid, edge, block_number));
+                    }
+                }
+            }
+        }
+        found
+    }
+}
+
+fn opportunity(
+    pair: &CexPair,
+    pool: &DexPool,
+    buy_on_dex: bool,
+    dex_price: f64,
+    cex_mid: f64,
+    edge: f64,
+    block_number: U64,
+) -> CexDexOpportunity {
+    CexDexOpportunity {
+        pair: pair.name(),
+        pool: pool.clone(),
+        buy_on_dex,
+        dex_price,
+        cex_mid,
+        edge_bps: (edge * math::BPS as f64) as u32,
+        block_number,
+    }
+}
+
+// Market, best bid and best ask from a Binance bookTicker or Coinbase ticker message
+fn parse_ticker(venue: CexVenue, value: &Value) -> Option<(String, f64, f64)> {
+    let price = |v: &Value| v.as_str()?.parse::<f64>().ok();
+    match venue {
+        CexVenue::Binance => {
+            let data = &value["data"];
+            Some((data["s"].as_str()?.to_string(), price(&data["b"])?, price(&data["a"])?))
+        }
+        CexVenue::Coinbase => {
+            if value["type"] != "ticker" {
+                return None;
+            }
+            Some((
+                value["product_id"].as_str()?.to_string(),
+                price(&value["best_bid"])?,
+                price(&value["best_ask"])?,
+            ))
+        }
+    }
+}
@@ -0,0 +1,95 @@
+use ethers::{
+    prelude::*,
+    providers::{Provider, Ws},
+    types::{H256, U64},
+};
+use std::collections::BTreeMap;
+
+use crate::errors::{ProviderError, Result};
+
+// Blocks of history kept to compare new heads against; deeper reorgs are only
+// seen as far back as this
+pub const REORG_DEPTH: usize = 64;
+
+// The chain switching branches under blocks already published
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Reorg {
+    // Last block both branches share
+    pub common_ancestor: U64,
+    // Blocks of the abandoned branch, oldest first
+    pub orphaned: Vec<(U64, H256)>,
+}
+
+impl Reorg {
+    pub fn depth(&self) -> usize {
+        self.orphaned.len()
+    }
+
+    // First height whose state may have changed
+    pub fn first_orphaned(&self) -> U64 {
+        self.common_ancestor + 1
+    }
+}
+
+// Hashes of the recent canonical chain by height. Each new head is checked
+// against them: a different hash at a height already seen, or a parent that
+// isn't the block held one below, means the blocks since the fork were orphaned.
+#[derive(Debug, Default)]
+pub struct ChainTracker {
+    hashes: BTreeMap<U64, H256>,
+    depth: usize,
+}
+
+impl ChainTracker {
+    pub fn new(depth: usize) -> Self {
+        Self {
+            hashes: BTreeMap::new(),
+            depth: depth.max(1),
+        }
+    }
+
+    // Record a new head. When it replaces blocks already seen, the branch is
+    // walked back through `provider` to where it meets the one held, and the
+    // orphaned blocks are returned.
+    pub async fn observe(&mut self, provider: &Provider<Ws>, number: U64, hash: H256, parent: H256) -> Result<Option<Reorg>> {
+        if self.hashes.get(&number) == Some(&hash) {
+            return Ok(None);
+        }
+
+        // Everything at or above the new head belongs to the old branch
+        let mut orphaned: Vec<(U64, H256)> = self.hashes.split_off(&number).into_iter().collect();
+        let mut height = number;
+        let mut canonical = parent;
+        while height > U64::zero() {
+            let below = height - 1;
+            match self.hashes.get(&below) {
+                Some(seen) if *seen != canonical => {
+                    orphaned.push((below, *seen));
+                    self.hashes.insert(below, canonical);
+                    let block = provider
+                        .get_block(canonical)
+                        .await?
+                        .ok_or_else(|| ProviderError::Rpc(format!("block {:?} not found", canonical)))?;
+                    canonical = block.parent_hash;
+                    height = below;
+                }
+                // The branches meet here, or the fork is older than we keep
+                _ => break,
+            }
+        }
+
+        self.hashes.insert(number, hash);
+        while self.hashes.len() > self.depth {
+            self.hashes.pop_first();
+        }
+
+        if orphaned.is_empty() {
+            return Ok(None);
+        }
+        orphaned.sort();
+        Ok(Some(Reorg {
+            common_ancestor: height.saturating_sub(U64::one()),
+            orphaned,
+        }))
+    }
+}
@@ -0,0 +1,63 @@
+use ethers::{
+    abi::{self, ParamType, Token},
+    types::I256,
+    utils::id,
+};
+
+// OffchainAggregator (OCR1): transmit(report, rs, ss, rawVs), where the
+// report is (rawReportContext, rawObservers, int192[] observations)
+const OCR1_TRANSMIT: &str = "transmit(bytes,bytes32[],bytes32[],bytes32)";
+// OCR2Aggregator: transmit(reportContext, report, rs, ss, rawVs), where the
+// report is (observationsTimestamp, rawObservers, int192[] observations, juelsPerFeeCoin)
+const OCR2_TRANSMIT: &str = "transmit(bytes32[3],bytes,bytes32[],bytes32[],bytes32)";
+
+// The answer a pending Chainlink `transmit` will write, decoded from its
+// calldata. Observations arrive sorted and the aggregator stores the median,
+// so the new price is known before the transaction lands.
+pub fn decode_transmit(input: &[u8]) -> Option<I256> {
+    if input.len() < 4 {
+        return None;
+    }
+    let (selector, args) = input.split_at(4);
+
+    let report = if selector == &id(OCR1_TRANSMIT)[..] {
+        let params = [
+            ParamType::Bytes,
+            ParamType::Array(Box::new(ParamType::FixedBytes(32))),
+            ParamType::Array(Box::new(ParamType::FixedBytes(32))),
+            ParamType::FixedBytes(32),
+        ];
+        abi::decode(&params, args).ok()?.into_iter().next()?
+    } else if selector == &id(OCR2_TRANSMIT)[..] {
+        let params = [
+            ParamType::FixedArray(Box::new(ParamType::FixedBytes(32)), 3),
+            ParamType::Bytes,
+            ParamType::Array(Box::new(ParamType::FixedBytes(32))),
+            ParamType::Array(Box::new(ParamType::FixedBytes(32))),
+            ParamType::FixedBytes(32),
+        ];
+        abi::decode(&params, args).ok()?.into_iter().nth(1)?
+    } else {
+        return None;
+    };
+    let Token::Bytes(report) = report else {
+        return None;
+    };
+
+    // Both report layouts put the observations third behind two 32-byte
+    // words; the trailing OCR2 field is left undecoded
+    let layout = [
+        ParamType::FixedBytes(32),
+        ParamType::FixedBytes(32),
+        ParamType::Array(Box::new(ParamType::Int(192))),
+    ];
+    let fields = abi::decode(&layout, &report).ok()?;
+    let Some(Token::Array(observations)) = fields.into_iter().nth(2) else {
+        return None;
+    };
+    match observations.get(observations.len() / 2)? {
+        // int192 is sign-extended to 256 bits in the encoding
+        Token::Int(raw) => Some(I256::from_raw(*raw)),
+        _ => None,
+    }
+}
@@ -0,0 +1,595 @@
+use ethers::{
+    providers::Middleware,
+    types::{Address, U64},
+};
+use std::sync::Arc;
+use tracing::warn;
+
+use crate::{
+    dex::{
+        balancer::BALANCER_VAULT,
+        curve::CURVE_REGISTRY,
+        maverick::{MAVERICK_V2_FACTORY, MAVERICK_V2_QUOTER},
+        solidly::{AERODROME_FACTORY, VELODROME_FACTORY},
+        trader_joe::LB_FACTORY,
+        uniswap_v2::{V2Fork, PANCAKESWAP_V2_FACTORY, SUSHISWAP_FACTORY, UNISWAP_V2_FACTORY},
+        uniswap_v3::{Quoter, PANCAKESWAP_QUOTER_V2, PANCAKESWAP_V3_FACTORY, QUOTER_V2, UNISWAP_V3_FACTORY},
+        uniswap_v4::{UNISWAP_V4_POOL_MANAGER, UNISWAP_V4_QUOTER, UNISWAP_V4_STATE_VIEW},
+        BalancerHandler, CurveHandler, DexHandler, DexManager, GenericV2Handler, HookPolicy, MaverickHandler,
+        SolidlyHandler, TraderJoeHandler, UniswapV2Handler, UniswapV3Handler, UniswapV4Handler,
+    },
+    errors::{BotError, Result},
+    gas::GasModel,
+    models::FlashLoanProvider,
+    safety::UNISWAP_V2_ROUTER,
+    token_sets::WETH,
+};
+
+// Networks with a built-in profile, by CHAIN name
+pub const CHAINS: &[&str] = &["mainnet", "arbitrum", "base", "optimism", "polygon", "bsc", "avalanche"];
+
+// Everything that differs between networks: the wrapped native token and its
+// USD feed, the lending pool, the DEX deployments, the default flash loan
+// providers and how gas is charged. Venues a chain doesn't have are None and never registered. RPC
+// endpoints come from the config file's `[chains.<name>]` section.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ChainProfile {
+    pub name: String,
+    pub chain_id: u64,
+    // WETH, WBNB or WPOL; gas is priced through it and arbitrage loops start in it
+    pub wrapped_native: Address,
+    // Chainlink <native>/USD
+    pub native_usd_feed: Address,
+    pub usdc: Address,
+    // What the "bluechips" and "stables" target presets expand to
+    pub bluechips: Vec<Address>,
+    pub stables: Vec<Address>,
+    pub aave_pool: Address,
+    // Where position backfill starts; 0 scans from genesis
+    pub aave_pool_deployed_at: u64,
+    pub uniswap_v2_factory: Option<Address>,
+    // V2-style router the treasury and the honeypot check swap through
+    pub uniswap_v2_router: Option<Address>,
+    pub sushiswap_factory: Option<Address>,
+    pub uniswap_v3_factory: Option<Address>,
+    pub uniswap_v3_quoter: Option<Address>,
+    pub pancakeswap_v2_factory: Option<Address>,
+    pub pancakeswap_v3_factory: Option<Address>,
+    pub pancakeswap_v3_quoter: Option<Address>,
+    pub velodrome_factory: Option<Address>,
+    pub aerodrome_factory: Option<Address>,
+    pub trader_joe_lb_factory: Option<Address>,
+    pub maverick_v2_factory: Option<Address>,
+    pub maverick_v2_quoter: Option<Address>,
+    // V4 needs both the PoolManager and StateView; the quoter is optional
+    pub uniswap_v4_pool_manager: Option<Address>,
+    pub uniswap_v4_state_view: Option<Address>,
+    pub uniswap_v4_quoter: Option<Address>,
+    // Which hooked V4 pools to trade through
+    pub v4_hook_policy: HookPolicy,
+    // Further Uniswap V2 clones from V2_FORKS; none are built in
+    pub v2_forks: Vec<V2Fork>,
+    pub curve_registry: Option<Address>,
+    // Balancer pool ids are only listed for mainnet, so the Vault is too
+    pub balancer_vault: Option<Address>,
+    pub flash_loan_providers: Vec<FlashLoanProvider>,
+    // Whether transactions also pay an L1 data fee, and how it's priced
+    pub gas_model: GasModel,
+}
+
+impl ChainProfile {
+    pub fn mainnet() -> Self {
+        Self {
+            name: "mainnet".to_string(),
+            chain_id: 1,
+            wrapped_native: addr(WETH),
+            native_usd_feed: addr("0x5f4eC3Df9cbd43714FE2740f5E3616155c5b8419"),
+            usdc: addr("0xA0b86991c6218b36c1d19D4a2e9Eb0cE3606eB48"),
+            bluechips: addrs(&[
+                WETH,
+                "0xA0b86991c6218b36c1d19D4a2e9Eb0cE3606eB48", // USDC
+                "0xdAC17F958D2ee523a2206206994597C13D831ec7", // USDT
+                "0x6B175474E89094C44Da98b954EedeAC495271d0F", // DAI
+                "0x2260FAC5E5542a773Aa44fBCfeDf7C193bc2C599", // WBTC
+            ]),
+            stables: addrs(&[
+                "0xA0b86991c6218b36c1d19D4a2e9Eb0cE3606eB48", // USDC
+                "0xdAC17F958D2ee523a2206206994597C13D831ec7", // USDT
+                "0x6B175474E89094C44Da98b954EedeAC495271d0F", // DAI
+                "0x853d955aCEf822Db058eb8505911ED77F175b99e", // FRAX
+                "0x5f98805A4E8be255a32880FDeC7F6728C6568bA0", // LUSD
+                "0x0000000000085d4780B73119b644AE5ecd22b376", // TUSD
+            ]),
+            aave_pool: addr("0x87870Bca3F3fD6335C3F4ce8392D69350B4fA4E2"),
+            aave_pool_deployed_at: 16_291_127,
+            uniswap_v2_factory: Some(addr(UNISWAP_V2_FACTORY)),
+            uniswap_v2_router: Some(addr(UNISWAP_V2_ROUTER)),
+            sushiswap_factory: Some(addr(SUSHISWAP_FACTORY)),
+            uniswap_v3_factory: Some(addr(UNISWAP_V3_FACTORY)),
+            uniswap_v3_quoter: Some(addr(QUOTER_V2)),
+            pancakeswap_v2_factory: None,
+            pancakeswap_v3_factory: None,
+            pancakeswap_v3_quoter: None,
+            velodrome_factory: None,
+            aerodrome_factory: None,
+            trader_joe_lb_factory: None,
+            maverick_v2_factory: Some(addr(MAVERICK_V2_FACTORY)),
+            maverick_v2_quoter: Some(addr(MAVERICK_V2_QUOTER)),
+            uniswap_v4_pool_manager: Some(addr(UNISWAP_V4_POOL_MANAGER)),
+            uniswap_v4_state_view: Some(addr(UNISWAP_V4_STATE_VIEW)),
+            uniswap_v4_quoter: Some(addr(UNISWAP_V4_QUOTER)),
+            v4_hook_policy: HookPolicy::default(),
+            v2_forks: Vec::new(),
+            curve_registry: Some(addr(CURVE_REGISTRY)),
+            balancer_vault: Some(addr(BALANCER_VAULT)),
+            flash_loan_providers: vec![FlashLoanProvider::Aave],
+            gas_model: GasModel::Execution,
+        }
+    }
+
+    pub fn arbitrum() -> Self {
+        let weth = "0x82aF49447D8a07e3bd95BD0d56f35241523fBab1";
+        let usdc = "0xaf88d065e77c8cC2239327C5EDb3A432268e5831";
+        let usdt = "0xFd086bC7CD5C481DCC9C85ebE478A1C0b69FCbb9";
+        let dai = "0xDA10009cBd5D07dd0CeCc66161FC93D7c9000da1";
+        Self {
+            name: "arbitrum".to_string(),
+            chain_id: 42161,
+            wrapped_native: addr(weth),
+            native_usd_feed: addr("0x639Fe6ab55C921f74e7fac1ee960C0B6293ba612"),
+            usdc: addr(usdc),
+            bluechips: addrs(&[weth, usdc, usdt, dai, "0x2f2a2543B76A4166549F7aaB2e75Bef0aefC5B0f"]),
+            stables: addrs(&[usdc, usdt, dai]),
+            aave_pool: addr("0x794a61358D6845594F94dc1DB02A252b5b4814aD"),
+            aave_pool_deployed_at: 7_742_429,
+            uniswap_v2_factory: Some(addr("0xf1D7CC64Fb4452F05c498126312eBE29f30Fbcf9")),
+            uniswap_v2_router: Some(addr("0x4752ba5DBc23f44D87826276BF6Fd6b1C372aD24")),
+            sushiswap_factory: Some(addr("0xc35DADB65012eC5796536bD9864eD8773aBc74C4")),
+            uniswap_v3_factory: Some(addr(UNISWAP_V3_FACTORY)),
+            uniswap_v3_quoter: Some(addr(QUOTER_V2)),
+            pancakeswap_v2_factory: None,
+            pancakeswap_v3_factory: None,
+            pancakeswap_v3_quoter: None,
+            velodrome_factory: None,
+            aerodrome_factory: None,
+            trader_joe_lb_factory: Some(addr(LB_FACTORY)),
+            maverick_v2_factory: Some(addr(MAVERICK_V2_FACTORY)),
+            maverick_v2_quoter: Some(addr(MAVERICK_V2_QUOTER)),
+            uniswap_v4_pool_manager: None,
+            uniswap_v4_state_view: None,
+            uniswap_v4_quoter: None,
+            v4_hook_policy: HookPolicy::default(),
+            v2_forks: Vec::new(),
+            curve_registry: None,
+            balancer_vault: None,
+            flash_loan_providers: vec![FlashLoanProvider::Aave],
+            gas_model: GasModel::Arbitrum,
+        }
+    }
+
+    pub fn base() -> Self {
+        let weth = "0x4200000000000000000000000000000000000006";
+        let usdc = "0x833589fCD6eDb6E08f4c7C32D4f71b54bdA02913";
+        let dai = "0x50c5725949A6F0c72E6C4a641F24049A917DB0Cb";
+        Self {
+            name: "base".to_string(),
+            chain_id: 8453,
+            wrapped_native: addr(weth),
+            native_usd_feed: addr("0x71041dddad3595F9CEd3DcCFBe3D1F4b0a16Bb70"),
+            usdc: addr(usdc),
+            bluechips: addrs(&[weth, usdc, dai]),
+            stables: addrs(&[usdc, dai]),
+            aave_pool: addr("0xA238Dd80C259a72e81d7e4664a9801593F98d1c5"),
+            aave_pool_deployed_at: 0,
+            uniswap_v2_factory: Some(addr("0x8909Dc15e40173Ff4699343b6eB8132c65e18eC6")),
+            uniswap_v2_router: Some(addr("0x4752ba5DBc23f44D87826276BF6Fd6b1C372aD24")),
+            sushiswap_factory: None,
+            uniswap_v3_factory: Some(addr("0x33128a8fC17869897dcE68Ed026d694621f6FDfD")),
+            uniswap_v3_quoter: Some(addr("0x3d4e44Eb1374240CE5F1B871ab261CD16335B76a")),
+            pancakeswap_v2_factory: None,
+            pancakeswap_v3_factory: None,
+            pancakeswap_v3_quoter: None,
+            velodrome_factory: None,
+            aerodrome_factory: Some(addr(AERODROME_FACTORY)),
+            trader_joe_lb_factory: None,
+            maverick_v2_factory: Some(addr(MAVERICK_V2_FACTORY)),
+            maverick_v2_quoter: Some(addr(MAVERICK_V2_QUOTER)),
+            uniswap_v4_pool_manager: None,
+            uniswap_v4_state_view: None,
+            uniswap_v4_quoter: None,
+            v4_hook_policy: HookPolicy::default(),
+            v2_forks: Vec::new(),
+            curve_registry: None,
+            balancer_vault: None,
+            flash_loan_providers: vec![FlashLoanProvider::Aave],
+            gas_model: GasModel::OpStack,
+        }
+    }
+
+    pub fn optimism() -> Self {
+        let weth = "0x4200000000000000000000000000000000000006";
+        let usdc = "0x0b2C639c533813f4Aa9D7837CAf62653d097Ff85";
+        let usdt = "0x94b008aA00579c1307B0EF2c499aD98a8ce58e58";
+        let dai = "0xDA10009cBd5D07dd0CeCc66161FC93D7c9000da1";
+        Self {
+            name: "optimism".to_string(),
+            chain_id: 10,
+            wrapped_native: addr(weth),
+            native_usd_feed: addr("0x13e3Ee699D1909E989722E753853AE30b17e08c5"),
+            usdc: addr(usdc),
+            bluechips: addrs(&[weth, usdc, usdt, dai, "0x68f180fcCe6836688e9084f035309E29Bf0A2095"]),
+            stables: addrs(&[usdc, usdt, dai]),
+            aave_pool: addr("0x794a61358D6845594F94dc1DB02A252b5b4814aD"),
+            aave_pool_deployed_at: 4_365_693,
+            uniswap_v2_factory: Some(addr("0x0c3c1c532F1e39EdF36BE9Fe0bE1410313E074Bf")),
+            uniswap_v2_router: Some(addr("0x4A7b5Da61326A6379179b40d00F57E5bbDC962c2")),
+            sushiswap_factory: None,
+            uniswap_v3_factory: Some(addr(UNISWAP_V3_FACTORY)),
+            uniswap_v3_quoter: Some(addr(QUOTER_V2)),
+            pancakeswap_v2_factory: None,
+            pancakeswap_v3_factory: None,
+            pancakeswap_v3_quoter: None,
+            velodrome_factory: Some(addr(VELODROME_FACTORY)),
+            aerodrome_factory: None,
+            trader_joe_lb_factory: None,
+            maverick_v2_factory: None,
+            maverick_v2_quoter: None,
+            uniswap_v4_pool_manager: None,
+            uniswap_v4_state_view: None,
+            uniswap_v4_quoter: None,
+            v4_hook_policy: HookPolicy::default(),
+            v2_forks: Vec::new(),
+            curve_registry: None,
+            balancer_vault: None,
+            flash_loan_providers: vec![FlashLoanProvider::Aave],
+            gas_model: GasModel::OpStack,
+        }
+    }
+
+    pub fn polygon() -> Self {
+        let wpol = "0x0d500B1d8E8eF31E21C99d1Db9A6444d3ADf1270";
+        let usdc = "0x3c499c542cEF5E3811e1192ce70d8cC03d5c3359";
+        let usdt = "0xc2132D05D31c914a87C6611C10748AEb04B58e8F";
+        let dai = "0x8f3Cf7ad23Cd3CaDbD9735AFf958023239c6A063";
+        Self {
+            name: "polygon".to_string(),
+            chain_id: 137,
+            wrapped_native: addr(wpol),
+            native_usd_feed: addr("0xAB594600376Ec9fD91F8e885dADF0CE036862dE0"),
+            usdc: addr(usdc),
+            bluechips: addrs(&[
+                wpol,
+                "0x7ceB23fD6bC0adD59E62ac25578270cFf1b9f619", // WETH
+                usdc,
+                usdt,
+                dai,
+                "0x1BFD67037B42Cf73acF2047067bd4F2C47D9BfD6", // WBTC
+            ]),
+            stables: addrs(&[usdc, usdt, dai]),
+            aave_pool: addr("0x794a61358D6845594F94dc1DB02A252b5b4814aD"),
+            aave_pool_deployed_at: 25_826_028,
+            uniswap_v2_factory: Some(addr("0x9e5A52f57b3038F1B8EeE45F28b3C1967e22799C")),
+            uniswap_v2_router: Some(addr("0xedf6066a2b290C185783862C7F4776A2C8077AD1")),
+            sushiswap_factory: Some(addr("0xc35DADB65012eC5796536bD9864eD8773aBc74C4")),
+            uniswap_v3_factory: Some(addr(UNISWAP_V3_FACTORY)),
+            uniswap_v3_quoter: Some(addr(QUOTER_V2)),
+            pancakeswap_v2_factory: None,
+            pancakeswap_v3_factory: None,
+            pancakeswap_v3_quoter: None,
+            velodrome_factory: None,
+            aerodrome_factory: None,
+            trader_joe_lb_factory: None,
+            maverick_v2_factory: None,
+            maverick_v2_quoter: None,
+            uniswap_v4_pool_manager: None,
+            uniswap_v4_state_view: None,
+            uniswap_v4_quoter: None,
+            v4_hook_policy: HookPolicy::default(),
+            v2_forks: Vec::new(),
+            curve_registry: None,
+            balancer_vault: None,
+            flash_loan_providers: vec![FlashLoanProvider::Aave],
+            gas_model: GasModel::Execution,
+        }
+    }
+
+    pub fn bsc() -> Self {
+        let wbnb = "0xbb4CdB9CBd36B01bD1cBaEBF2De08d9173bc095c";
+        let usdc = "0x8AC76a51cc950d9822D68b83fE1Ad97B32Cd580d";
+        let usdt = "0x55d398326f99059fF775485246999027B3197955";
+        Self {
+            name: "bsc".to_string(),
+            chain_id: 56,
+            wrapped_native: addr(wbnb),
+            native_usd_feed: addr("0x0567F2323251f0Aab15c8dFb1967E4e8A7D42aeE"),
+            usdc: addr(usdc),
+            bluechips: addrs(&[
+                wbnb,
+                "0x2170Ed0880ac9A755fd29B2688956BD959F933F8", // ETH
+                usdc,
+                usdt,
+                "0x7130d2A12B9BCbFAe4f2634d864A1Ee1Ce3Ead9c", // BTCB
+            ]),
+            stables: addrs(&[usdc, usdt]),
+            aave_pool: addr("0x6807dc923806fE8Fd134338EABCA509979a7e0cB"),
+            aave_pool_deployed_at: 0,
+            uniswap_v2_factory: Some(addr("0x8909Dc15e40173Ff4699343b6eB8132c65e18eC6")),
+            // PancakeSwap's, where the liquidity is
+            uniswap_v2_router: Some(addr("0x10ED43C718714eb63d5aA57B78B54704E256024E")),
+            sushiswap_factory: Some(addr("0xc35DADB65012eC5796536bD9864eD8773aBc74C4")),
+            uniswap_v3_factory: Some(addr("0xdB1d10011AD0Ff90774D0C6Bb92e5C5c8b4461F7")),
+            uniswap_v3_quoter: Some(addr("0x78D78E420Da98ad378D7799bE8f4AF69033EB077")),
+            pancakeswap_v2_factory: Some(addr(PANCAKESWAP_V2_FACTORY)),
+            pancakeswap_v3_factory: Some(addr(PANCAKESWAP_V3_FACTORY)),
+            pancakeswap_v3_quoter: Some(addr(PANCAKESWAP_QUOTER_V2)),
+            velodrome_factory: None,
+            aerodrome_factory: None,
+            trader_joe_lb_factory: None,
+            maverick_v2_factory: Some(addr(MAVERICK_V2_FACTORY)),
+            maverick_v2_quoter: Some(addr(MAVERICK_V2_QUOTER)),
+            uniswap_v4_pool_manager: None,
+            uniswap_v4_state_view: None,
+            uniswap_v4_quoter: None,
+            v4_hook_policy: HookPolicy::default(),
+            v2_forks: Vec::new(),
+            curve_registry: None,
+            balancer_vault: None,
+            flash_loan_providers: vec![FlashLoanProvider::Aave],
+            gas_model: GasModel::Execution,
+        }
+    }
+
+    pub fn avalanche() -> Self {
+        let wavax = "0xB31f66AA3C1e785363F0875A1B74E27b85FD66c7";
+        let usdc = "0xB97EF9Ef8734C71904D8002F8b6Bc66Dd9c48a6E";
+        let usdt = "0x9702230A8Ea53601f5cD2dc00fDBc13d4dF4A8c7";
+        Self {
+            name: "avalanche".to_string(),
+            chain_id: 43114,
+            wrapped_native: addr(wavax),
+            native_usd_feed: addr("0x0A77230d17318075983913bC2145DB16C7366156"),
+            usdc: addr(usdc),
+            bluechips: addrs(&[
+                wavax,
+                "0x49D5c2BdFfac6CE2BFdB6640F4F80f226bc10bAB", // WETH.e
+                usdc,
+                usdt,
+                "0x152b9d0FdC40C096757F570A51E494bd4b943E50", // BTC.b
+            ]),
+            stables: addrs(&[usdc, usdt]),
+            aave_pool: addr("0x794a61358D6845594F94dc1DB02A252b5b4814aD"),
+            aave_pool_deployed_at: 0,
+            uniswap_v2_factory: Some(addr("0x9e5A52f57b3038F1B8EeE45F28b3C1967e22799C")),
+            uniswap_v2_router: Some(addr("0x4752ba5DBc23f44D87826276BF6Fd6b1C372aD24")),
+            sushiswap_factory: Some(addr("0xc35DADB65012eC5796536bD9864eD8773aBc74C4")),
+            uniswap_v3_factory: Some(addr("0x740b1c1de25031C31FF4fC9A62f554A55cdC1baD")),
+            uniswap_v3_quoter: Some(addr("0xbe0F5544EC67e9B3b2D979aaA43f18Fd87E6257F")),
+            pancakeswap_v2_factory: None,
+            pancakeswap_v3_factory: None,
+            pancakeswap_v3_quoter: None,
+            velodrome_factory: None,
+            aerodrome_factory: None,
+            trader_joe_lb_factory: Some(addr(LB_FACTORY)),
+            maverick_v2_factory: None,
+            maverick_v2_quoter: None,
+            uniswap_v4_pool_manager: None,
+            uniswap_v4_state_view: None,
+            uniswap_v4_quoter: None,
+            v4_hook_policy: HookPolicy::default(),
+            v2_forks: Vec::new(),
+            curve_registry: None,
+            balancer_vault: None,
+            flash_loan_providers: vec![FlashLoanProvider::Aave],
+            gas_model: GasModel::Execution,
+        }
+    }
+
+    pub fn by_name(name: &str) -> Option<Self> {
+        match name {
+            "mainnet" | "ethereum" => Some(Self::mainnet()),
+            "arbitrum" => Some(Self::arbitrum()),
+            "base" => Some(Self::base()),
+            "optimism" => Some(Self::optimism()),
+            "polygon" => Some(Self::polygon()),
+            "bsc" => Some(Self::bsc()),
+            "avalanche" => Some(Self::avalanche()),
+            _ => None,
+        }
+    }
+
+    pub fn by_id(chain_id: u64) -> Option<Self> {
+        CHAINS.iter().filter_map(|name| Self::by_name(name)).find(|p| p.chain_id == chain_id)
+    }
+
+    // The built-in profile named by CHAIN, or else matching CHAIN_ID (mainnet
+    // when neither is set), with any address overridden from the environment.
+    // An address set to "none" drops that venue. A CHAIN_ID without a profile
+    // (a local fork, say) keeps the named profile's addresses and signs for
+    // that id.
+    pub fn from_env() -> Result<Self> {
+        let chain_id: Option<u64> = std::env::var("CHAIN_ID")
+            .ok()
+            .map(|v| v.parse().map_err(|_| BotError::Config(format!("CHAIN_ID: invalid chain id {}", v))))
+            .transpose()?;
+        let name = std::env::var("CHAIN").ok();
+
+        let mut profile = match name.as_deref().and_then(Self::by_name).or_else(|| chain_id.and_then(Self::by_id)) {
+            Some(profile) => profile,
+            None => {
+                if name.is_some() || chain_id.is_some() {
+                    warn!(chain = ?name, ?chain_id, "⚠️ No built-in chain profile, using mainnet addresses");
+                }
+                Self::mainnet()
+            }
+        };
+        if let Some(chain_id) = chain_id {
+            profile.chain_id = chain_id;
+        }
+
+        profile.wrapped_native = required("WRAPPED_NATIVE", profile.wrapped_native)?;
+        profile.native_usd_feed = required("NATIVE_USD_FEED", profile.native_usd_feed)?;
+        profile.usdc = required("USDC", profile.usdc)?;
+        profile.aave_pool = required("AAVE_V3_POOL", profile.aave_pool)?;
+        if let Ok(block) = std::env::var("AAVE_V3_DEPLOYED_AT") {
+            profile.aave_pool_deployed_at = block
+                .parse()
+                .map_err(|_| BotError::Config(format!("AAVE_V3_DEPLOYED_AT: invalid block {}", block)))?;
+        }
+        profile.uniswap_v2_factory = optional("UNISWAP_V2_FACTORY", profile.uniswap_v2_factory)?;
+        profile.uniswap_v2_router = optional("UNISWAP_V2_ROUTER", profile.uniswap_v2_router)?;
+        profile.sushiswap_factory = optional("SUSHISWAP_FACTORY", profile.sushiswap_factory)?;
+        profile.uniswap_v3_factory = optional("UNISWAP_V3_FACTORY", profile.uniswap_v3_factory)?;
+        profile.uniswap_v3_quoter = optional("UNISWAP_V3_QUOTER", profile.uniswap_v3_quoter)?;
+        profile.pancakeswap_v2_factory = optional("PANCAKESWAP_V2_FACTORY", profile.pancakeswap_v2_factory)?;
+        profile.pancakeswap_v3_factory = optional("PANCAKESWAP_V3_FACTORY", profile.pancakeswap_v3_factory)?;
+        profile.pancakeswap_v3_quoter = optional("PANCAKESWAP_V3_QUOTER", profile.pancakeswap_v3_quoter)?;
+        profile.velodrome_factory = optional("VELODROME_FACTORY", profile.velodrome_factory)?;
+        profile.aerodrome_factory = optional("AERODROME_FACTORY", profile.aerodrome_factory)?;
+        profile.trader_joe_lb_factory = optional("TRADER_JOE_LB_FACTORY", profile.trader_joe_lb_factory)?;
+        profile.maverick_v2_factory = optional("MAVERICK_V2_FACTORY", profile.maverick_v2_factory)?;
+        profile.maverick_v2_quoter = optional("MAVERICK_V2_QUOTER", profile.maverick_v2_quoter)?;
+        profile.uniswap_v4_pool_manager = optional("UNISWAP_V4_POOL_MANAGER", profile.uniswap_v4_pool_manager)?;
+        profile.uniswap_v4_state_view = optional("UNISWAP_V4_STATE_VIEW", profile.uniswap_v4_state_view)?;
+        profile.uniswap_v4_quoter = optional("UNISWAP_V4_QUOTER", profile.uniswap_v4_quoter)?;
+        if let Ok(policy) = std::env::var("UNISWAP_V4_HOOKS") {
+            profile.v4_hook_policy = policy
+                .parse()
+                .map_err(|e| BotError::Config(format!("UNISWAP_V4_HOOKS: {}", e)))?;
+        }
+        if let Ok(forks) = std::env::var("V2_FORKS") {
+            profile.v2_forks = forks
+                .split(',')
+                .filter(|f| !f.trim().is_empty())
+                .map(|f| f.parse().map_err(|e| BotError::Config(format!("V2_FORKS: {}", e))))
+                .collect::<Result<_>>()?;
+        }
+        profile.curve_registry = optional("CURVE_REGISTRY", profile.curve_registry)?;
+        profile.balancer_vault = optional("BALANCER_VAULT", profile.balancer_vault)?;
+        if let Ok(providers) = std::env::var("FLASH_LOAN_PROVIDER") {
+            profile.flash_loan_providers = providers
+                .split(',')
+                .map(|p| p.trim().parse().map_err(|e| BotError::Config(format!("FLASH_LOAN_PROVIDER: {}", e))))
+                .collect::<Result<_>>()?;
+        }
+        Ok(profile)
+    }
+
+    // Register a handler for every venue deployed on this chain
+    pub fn register_venues<M: Middleware + 'static>(&self, dex: &mut DexManager, provider: Arc<M>) {
+        // Some factories share their address across chains, where a deployment
+        // block from the venue's home chain means nothing
+        let deployed_on = |home: u64, handler_block: U64| if self.chain_id == home { handler_block.as_u64() } else { 0 };
+
+        if let Some(factory) = self.uniswap_v2_factory {
+            let handler = UniswapV2Handler::uniswap(provider.clone()).with_factory(factory);
+            let block = deployed_on(1, handler.factory_deployed_at());
+            dex.register_handler(Arc::new(handler.with_deployment_block(block)));
+        }
+        if let Some(factory) = self.sushiswap_factory {
+            let handler = UniswapV2Handler::sushiswap(provider.clone()).with_factory(factory);
+            let block = deployed_on(1, handler.factory_deployed_at());
+            dex.register_handler(Arc::new(handler.with_deployment_block(block)));
+        }
+        if let Some(factory) = self.uniswap_v3_factory {
+            let handler = match self.uniswap_v3_quoter {
+                Some(quoter) => UniswapV3Handler::mainnet(provider.clone())
+                    .with_factory(factory)
+                    .with_quoter(Quoter::new(provider.clone(), quoter)),
+                None => UniswapV3Handler::new(provider.clone(), factory),
+            };
+            let block = deployed_on(1, handler.factory_deployed_at());
+            dex.register_handler(Arc::new(handler.with_deployment_block(block)));
+        }
+        if let Some(factory) = self.pancakeswap_v2_factory {
+            let handler = UniswapV2Handler::pancakeswap(provider.clone()).with_factory(factory);
+            let block = deployed_on(56, handler.factory_deployed_at());
+            dex.register_handler(Arc::new(handler.with_deployment_block(block)));
+        }
+        if let Some(factory) = self.pancakeswap_v3_factory {
+            let handler = match self.pancakeswap_v3_quoter {
+                Some(quoter) => UniswapV3Handler::pancakeswap(provider.clone())
+                    .with_factory(factory)
+                    .with_quoter(Quoter::new(provider.clone(), quoter)),
+                None => UniswapV3Handler::pancakeswap(provider.clone()).with_factory(factory).without_quoter(),
+            };
+            let block = deployed_on(56, handler.factory_deployed_at());
+            dex.register_handler(Arc::new(handler.with_deployment_block(block)));
+        }
+        if let Some(factory) = self.velodrome_factory {
+            let handler = SolidlyHandler::velodrome(provider.clone()).with_factory(factory);
+            let block = deployed_on(10, handler.factory_deployed_at());
+            dex.register_handler(Arc::new(handler.with_deployment_block(block)));
+        }
+        if let Some(factory) = self.aerodrome_factory {
+            let handler = SolidlyHandler::aerodrome(provider.clone()).with_factory(factory);
+            let block = deployed_on(8453, handler.factory_deployed_at());
+            dex.register_handler(Arc::new(handler.with_deployment_block(block)));
+        }
+        if let Some(factory) = self.trader_joe_lb_factory {
+            dex.register_handler(Arc::new(TraderJoeHandler::new(provider.clone(), factory)));
+        }
+        if let Some(factory) = self.maverick_v2_factory {
+            let handler = MaverickHandler::new(provider.clone(), factory);
+            let handler = match self.maverick_v2_quoter {
+                Some(quoter) => handler.with_quoter(quoter),
+                None => handler,
+            };
+            dex.register_handler(Arc::new(handler));
+        }
+        if let (Some(pool_manager), Some(state_view)) = (self.uniswap_v4_pool_manager, self.uniswap_v4_state_view) {
+            let handler = UniswapV4Handler::mainnet(provider.clone())
+                .with_contracts(pool_manager, state_view)
+                .with_hook_policy(self.v4_hook_policy.clone());
+            let handler = match self.uniswap_v4_quoter {
+                Some(quoter) => handler.with_quoter(quoter),
+                None => handler.without_quoter(),
+            };
+            let block = deployed_on(1, handler.factory_deployed_at());
+            dex.register_handler(Arc::new(handler.with_deployment_block(block)));
+        }
+        for fork in &self.v2_forks {
+            dex.register_handler(Arc::new(GenericV2Handler::from_fork(provider.clone(), fork)));
+        }
+        if let Some(registry) = self.curve_registry {
+            dex.register_handler(Arc::new(CurveHandler::new(provider.clone(), registry)));
+        }
+        if let Some(vault) = self.balancer_vault {
+            dex.register_handler(Arc::new(BalancerHandler::mainnet(provider).with_vault(vault)));
+        }
+    }
+}
+
+impl Default for ChainProfile {
+    fn default() -> Self {
+        Self::mainnet()
+    }
+}
+
+fn addr(address: &str) -> Address {
+    address.parse().unwrap()
+}
+
+fn addrs(list: &[&str]) -> Vec<Address> {
+    list.iter().map(|a| addr(a)).collect()
+}
+
+fn parse_address(var: &str, value: &str) -> Result<Address> {
+    value
+        .parse()
+        .map_err(|_| BotError::Config(format!("{}: invalid address {}", var, value)))
+}
+
+fn required(var: &str, default: Address) -> Result<Address> {
+    match std::env::var(var) {
+        Ok(value) => parse_address(var, &value),
+        Err(_) => Ok(default),
+    }
+}
+
+fn optional(var: &str, default: Option<Address>) -> Result<Option<Address>> {
+    match std::env::var(var).as_deref() {
+        Ok("none") => Ok(None),
+        Ok(value) => parse_address(var, value).map(Some),
+        Err(_) => Ok(default),
+    }
+}
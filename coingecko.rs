@@ -0,0 +1,268 @@
+use dashmap::DashMap;
+use ethers::{
+    types::{Address, U256},
+    utils::parse_units,
+};
+use serde_json::Value;
+use std::{
+    collections::HashMap,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+    time::{Duration, Instant},
+};
+use tokio::sync::Mutex;
+use tracing::{debug, warn};
+
+use crate::{
+    chains::ChainProfile,
+    errors::{ProviderError, Result},
+};
+
+pub const COINGECKO_API: &str = "https://api.coingecko.com/api/v3";
+
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(5);
+// Contract addresses asked for in one /simple/token_price request
+const BATCH_SIZE: usize = 50;
+// Wait after a 429 that carries no Retry-After
+const DEFAULT_RETRY_AFTER: Duration = Duration::from_secs(60);
+
+#[derive(Debug, Clone)]
+pub struct CoinGeckoConfig {
+    pub api: String,
+    // Sent as a Pro key when `api` is pro-api.coingecko.com, as a Demo key otherwise
+    pub api_key: Option<String>,
+    // How long an answer, or a token CoinGecko doesn't list, is served from cache
+    pub ttl: Duration,
+    // Least time between two requests
+    pub min_interval: Duration,
+}
+
+impl CoinGeckoConfig {
+    pub fn from_env() -> Self {
+        let api_key = std::env::var("COINGECKO_API_KEY").ok().filter(|k| !k.trim().is_empty());
+        // The public API allows about 5 calls a minute without a key, 30 with one
+        let default_interval = if api_key.is_some() { 2_000 } else { 12_000 };
+        let millis = |name: &str, default: u64| {
+            Duration::from_millis(std::env::var(name).ok().and_then(|v| v.parse().ok()).unwrap_or(default))
+        };
+        Self {
+            api: std::env::var("COINGECKO_API").unwrap_or_else(|_| COINGECKO_API.to_string()),
+            api_key,
+            ttl: millis("COINGECKO_TTL_MS", 60_000),
+            min_interval: millis("COINGECKO_MIN_INTERVAL_MS", default_interval),
+        }
+    }
+}
+
+// One CoinGecko price: USD per whole token with 18 decimals, and when
+// CoinGecko last updated it
+#[derive(Debug, Clone, Copy)]
+pub struct CoinGeckoPrice {
+    pub usd: U256,
+    pub updated_at: u64,
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Cached {
+    // None for a token CoinGecko doesn't list, so it isn't asked again every call
+    price: Option<CoinGeckoPrice>,
+    fetched_at: Instant,
+}
+
+// CoinGecko behind a request spacer and a TTL cache, shared by everything that
+// prices off chain. Token prices are asked for in batches. After a 429 the
+// client stops asking until Retry-After has passed and fails fast meanwhile,
+// so callers fall back to pricing on chain instead of waiting.
+pub struct CoinGeckoClient {
+    http: reqwest::Client,
+    config: CoinGeckoConfig,
+    // CoinGecko asset platform of the chain, for token prices
+    platform: Option<&'static str>,
+    // CoinGecko id of the chain's native token
+    native_id: Option<&'static str>,
+    native: Mutex<Option<Cached>>,
+    tokens: DashMap<Address, Cached>,
+    // When the next request may go out; held while waiting for it
+    next_request: Mutex<Instant>,
+    // A background token refresh is running
+    refreshing: AtomicBool,
+}
+
+impl CoinGeckoClient {
+    pub fn new(chain: &ChainProfile, config: CoinGeckoConfig) -> Self {
+        let (platform, native_id) = match chain.chain_id {
+            1 => (Some("ethereum"), Some("ethereum")),
+            10 => (Some("optimistic-ethereum"), Some("ethereum")),
+            56 => (Some("binance-smart-chain"), Some("binancecoin")),
+            137 => (Some("polygon-pos"), Some("polygon-ecosystem-token")),
+            8453 => (Some("base"), Some("ethereum")),
+            42161 => (Some("arbitrum-one"), Some("ethereum")),
+            43114 => (Some("avalanche"), Some("avalanche-2")),
+            _ => (None, None),
+        };
+        Self {
+            http: reqwest::Client::builder().timeout(REQUEST_TIMEOUT).build().unwrap_or_default(),
+            config,
+            platform,
+            native_id,
+            native: Mutex::new(None),
+            tokens: DashMap::new(),
+            next_request: Mutex::new(Instant::now()),
+            refreshing: AtomicBool::new(false),
+        }
+    }
+
+    // CoinGecko id of the native token, None on chains it isn't set for
+    pub fn native_id(&self) -> Option<&'static str> {
+        self.native_id
+    }
+
+    // The native token in USD, from cache while it's within the TTL
+    pub async fn native_price(&self) -> Result<Option<CoinGeckoPrice>> {
+        let Some(id) = self.native_id else {
+            return Ok(None);
+        };
+        let mut native = self.native.lock().await;
+        if let Some(cached) = *native {
+            if cached.fetched_at.elapsed() < self.config.ttl {
+                return Ok(cached.price);
+            }
+        }
+
+        let body = self
+            .get("simple/price", &[("ids", id), ("vs_currencies", "usd"), ("include_last_updated_at", "true")])
+            .await?;
+        let price = quote(&body[id]);
+        *native = Some(Cached { price, fetched_at: Instant::now() });
+        Ok(price)
+    }
+
+    // Prices of `tokens` on this chain, cached ones as they are and the rest
+    // asked for in batches. A failed batch is logged, and it and the batches
+    // after it are left out of the answer rather than failing it.
+    pub async fn token_prices(&self, tokens: &[Address]) -> HashMap<Address, CoinGeckoPrice> {
+        let (mut prices, missing) = self.cached(tokens);
+        let Some(platform) = self.platform else {
+            return prices;
+        };
+
+        for batch in missing.chunks(BATCH_SIZE) {
+            let addresses = batch.iter().map(|t| format!("{:?}", t)).collect::<Vec<_>>().join(",");
+            let path = format!("simple/token_price/{}", platform);
+            let query = [
+                ("contract_addresses", addresses.as_str()),
+                ("vs_currencies", "usd"),
+                ("include_last_updated_at", "true"),
+            ];
+            let body = match self.get(&path, &query).await {
+                Ok(body) => body,
+                Err(e) => {
+                    warn!(tokens = batch.len(), error = %e, "⚠️ CoinGecko token prices failed");
+                    // The rest would only be refused as well
+                    break;
+                }
+            };
+
+            let fetched_at = Instant::now();
+            for token in batch {
+                // Keys come back as lowercase addresses
+                let price = quote(&body[format!("{:?}", token)]);
+                self.tokens.insert(*token, Cached { price, fetched_at });
+                if let Some(price) = price {
+                    prices.insert(*token, price);
+                }
+            }
+        }
+        prices
+    }
+
+    // Cached prices of `tokens` without waiting on CoinGecko. Tokens missing
+    // from the cache or past the TTL are asked for in the background, ready for
+    // a later call; only one such refresh runs at a time.
+    pub fn token_prices_now(self: &Arc<Self>, tokens: &[Address]) -> HashMap<Address, CoinGeckoPrice> {
+        let (prices, missing) = self.cached(tokens);
+        if !missing.is_empty() && self.platform.is_some() && !self.refreshing.swap(true, Ordering::AcqRel) {
+            let client = self.clone();
+            tokio::spawn(async move {
+                client.token_prices(&missing).await;
+                client.refreshing.store(false, Ordering::Release);
+            });
+        }
+        prices
+    }
+
+    // Fresh cached prices among `tokens`, and the tokens that need asking for
+    fn cached(&self, tokens: &[Address]) -> (HashMap<Address, CoinGeckoPrice>, Vec<Address>) {
+        let mut prices = HashMap::new();
+        let mut missing = Vec::new();
+        for token in tokens {
+            match self.tokens.get(token).map(|c| *c) {
+                Some(cached) if cached.fetched_at.elapsed() < self.config.ttl => {
+                    if let Some(price) = cached.price {
+                        prices.insert(*token, price);
+                    }
+                }
+                _ => missing.push(*token),
+            }
+        }
+        (prices, missing)
+    }
+
+    // One request, spaced `min_interval` after the last. While a 429's
+    // Retry-After runs this fails at once rather than waiting it out.
+    async fn get(&self, path: &str, query: &[(&str, &str)]) -> Result<Value> {
+        {
+            let mut next = self.next_request.lock().await;
+            let now = Instant::now();
+            if *next > now + self.config.min_interval {
+                return Err(ProviderError::RateLimited("coingecko: backing off after 429".to_string()).into());
+            }
+            tokio::time::sleep_until((*next).into()).await;
+            *next = Instant::now() + self.config.min_interval;
+        }
+
+        let mut request = self
+            .http
+            .get(format!("{}/{}", self.config.api.trim_end_matches('/'), path))
+            .query(query);
+        if let Some(key) = &self.config.api_key {
+            let header = if self.config.api.contains("pro-api") { "x-cg-pro-api-key" } else { "x-cg-demo-api-key" };
+            request = request.header(header, key);
+        }
+
+        let response = request
+            .send()
+            .await
+            .map_err(|e| ProviderError::Rpc(format!("coingecko: {}", e)))?;
+        if response.status() == reqwest::StatusCode::TOO_MANY_REQUESTS {
+            let retry_after = response
+                .headers()
+                .get(reqwest::header::RETRY_AFTER)
+                .and_then(|v| v.to_str().ok()?.parse().ok())
+                .map(Duration::from_secs)
+                .unwrap_or(DEFAULT_RETRY_AFTER);
+            *self.next_request.lock().await = Instant::now() + retry_after;
+            debug!(retry_after_secs = retry_after.as_secs(), "🐢 CoinGecko rate limited");
+            return Err(ProviderError::RateLimited(format!("coingecko: retry after {}s", retry_after.as_secs())).into());
+        }
+        response
+            .error_for_status()
+            .map_err(|e| ProviderError::Rpc(format!("coingecko: {}", e)))?
+            .json()
+            .await
+            .map_err(|e| ProviderError::Rpc(format!("coingecko: {}", e)).into())
+    }
+}
+
+// `{"usd": .., "last_updated_at": ..}` as a price; None when absent or not positive
+fn quote(entry: &Value) -> Option<CoinGeckoPrice> {
+    let usd = entry["usd"].as_f64().filter(|p| *p > 0.0)?;
+    // Fixed notation, since tiny prices print in exponent form otherwise
+    let usd = parse_units(format!("{:.18}", usd), 18).map(U256::from).ok()?;
+    Some(CoinGeckoPrice {
+        usd,
+        updated_at: entry["last_updated_at"].as_u64().unwrap_or_default(),
+    })
+}
@@ -0,0 +1,234 @@
+use ethers::{
+    prelude::*,
+    types::{Address, U256},
+};
+use std::sync::Arc;
+use tokio::sync::OnceCell;
+
+use liquidation_bot::{
+    errors::{BotError, ProviderError, Result},
+    math::{mul_div, BPS},
+};
+
+abigen!(
+    Comptroller,
+    r#"[
+        function getAllMarkets() external view returns (address[])
+        function getAssetsIn(address account) external view returns (address[])
+        function getAccountLiquidity(address account) external view returns (uint256, uint256, uint256)
+        function closeFactorMantissa() external view returns (uint256)
+        function liquidationIncentiveMantissa() external view returns (uint256)
+        function oracle() external view returns (address)
+    ]"#
+);
+
+abigen!(
+    CToken,
+    r#"[
+        function underlying() external view returns (address)
+        function getAccountSnapshot(address account) external view returns (uint256, uint256, uint256, uint256)
+    ]"#
+);
+
+abigen!(
+    CompoundOracle,
+    r#"[function getUnderlyingPrice(address cToken) external view returns (uint256)]"#
+);
+
+pub const COMPOUND_V2_COMPTROLLER: &str = "0x3d9819210A31b4961b30EF54bE2aeD79B9c9Cd3B";
+// Repays and seizes native ETH, which the executor doesn't handle; also the
+// market whose price is the ETH price
+const CETH: &str = "0x4Ddc2D193948926D02f9B1fE9e1daa0718270ED5";
+// Seized collateral the cToken keeps as reserves
+const PROTOCOL_SEIZE_SHARE_BPS: u64 = 280;
+const FLASH_LOAN_FEE_BPS: u64 = 5;
+
+// One market the user has entered. Values are USD with 18 decimals: the
+// oracle scales each price by 1e(36 - underlying decimals).
+#[derive(Debug, Clone)]
+pub struct Market {
+    pub c_token: Address,
+    pub underlying: Address,
+    // Underlying units
+    pub supplied: U256,
+    pub borrowed: U256,
+    pub price: U256,
+}
+
+impl Market {
+    fn value(&self, amount: U256) -> U256 {
+        mul_div(amount, self.price, U256::exp10(18)).unwrap_or_default()
+    }
+
+    fn amount(&self, value: U256) -> U256 {
+        if self.price.is_zero() {
+            return U256::zero();
+        }
+        mul_div(value, U256::exp10(18), self.price).unwrap_or_default()
+    }
+}
+
+// Which borrow to repay and which cToken to seize
+#[derive(Debug, Clone)]
+pub struct CompoundPlan {
+    pub c_token_collateral: Address,
+    pub debt_asset: Address,
+    // Underlying units of the borrowed market
+    pub repay_amount: U256,
+    // The same in USD
+    pub repay_value: U256,
+    // Incentive over the debt repaid, in bps
+    pub bonus_bps: u32,
+    // The borrower's whole balance of the seized cToken, in USD
+    pub collateral_value: U256,
+    // Incentive less the protocol's seize share and the flash loan fee, in USD
+    pub gross_value: U256,
+    debt_price: U256,
+}
+
+impl CompoundPlan {
+    // `value` in USD as debt asset units
+    pub fn to_debt_units(&self, value: U256) -> U256 {
+        if self.debt_price.is_zero() {
+            return U256::zero();
+        }
+        mul_div(value, U256::exp10(18), self.debt_price).unwrap_or_default()
+    }
+}
+
+// Legacy Compound (V2): per-asset cToken markets under one comptroller.
+// Liquidators repay part of a borrow and seize the borrower's cTokens.
+pub struct CompoundV2<M> {
+    provider: Arc<M>,
+    comptroller: Address,
+    // cToken -> underlying, for every market but cETH
+    markets: OnceCell<Vec<(Address, Address)>>,
+}
+
+impl<M: Middleware + 'static> CompoundV2<M> {
+    pub fn new(provider: Arc<M>, comptroller: Address) -> Self {
+        Self {
+            provider,
+            comptroller,
+            markets: OnceCell::new(),
+        }
+    }
+
+    // cTokens and their underlying tokens; listings are permanent, so read once
+    pub async fn markets(&self) -> Result<&[(Address, Address)]> {
+        let markets = self
+            .markets
+            .get_or_try_init(|| async {
+                let ceth: Address = CETH.parse().unwrap();
+                let comptroller = Comptroller::new(self.comptroller, self.provider.clone());
+                let c_tokens: Vec<Address> = comptroller
+                    .get_all_markets()
+                    .call()
+                    .await?
+                    .into_iter()
+                    .filter(|c| *c != ceth)
+                    .collect();
+
+                let mut multicall = Multicall::new(self.provider.clone(), None)
+                    .await
+                    .map_err(|e| ProviderError::Rpc(e.to_string()))?;
+                for c_token in &c_tokens {
+                    multicall.add_call(CToken::new(*c_token, self.provider.clone()).underlying(), false);
+                }
+                let underlying: Vec<Address> = multicall
+                    .call_array()
+                    .await
+                    .map_err(|e| ProviderError::Rpc(e.to_string()))?;
+                Ok::<_, BotError>(c_tokens.into_iter().zip(underlying).collect())
+            })
+            .await?;
+        Ok(markets)
+    }
+
+    // Amount by which the user's borrows exceed their borrowing power, in USD;
+    // positive means liquidatable
+    pub async fn shortfall(&self, user: Address) -> Result<U256> {
+        let comptroller = Comptroller::new(self.comptroller, self.provider.clone());
+        let (_, _, shortfall) = comptroller.get_account_liquidity(user).call().await?;
+        Ok(shortfall)
+    }
+
+    // Every market the user has entered, with balances and prices
+    pub async fn positions(&self, user: Address) -> Result<Vec<Market>> {
+        let markets = self.markets().await?;
+        let comptroller = Comptroller::new(self.comptroller, self.provider.clone());
+        let oracle = CompoundOracle::new(comptroller.oracle().call().await?, self.provider.clone());
+        let entered = comptroller.get_assets_in(user).call().await?;
+
+        let mut positions = Vec::new();
+        for (c_token, underlying) in markets.iter().filter(|(c, _)| entered.contains(c)) {
+            let (_, c_balance, borrowed, exchange_rate) =
+                CToken::new(*c_token, self.provider.clone()).get_account_snapshot(user).call().await?;
+            positions.push(Market {
+                c_token: *c_token,
+                underlying: *underlying,
+                supplied: mul_div(c_balance, exchange_rate, U256::exp10(18)).unwrap_or_default(),
+                borrowed,
+                price: oracle.get_underlying_price(*c_token).call().await?,
+            });
+        }
+        Ok(positions)
+    }
+
+    // ETH price in USD with 18 decimals, for pricing gas
+    pub async fn eth_price(&self) -> Result<U256> {
+        let comptroller = Comptroller::new(self.comptroller, self.provider.clone());
+        let oracle = CompoundOracle::new(comptroller.oracle().call().await?, self.provider.clone());
+        Ok(oracle.get_underlying_price(CETH.parse().unwrap()).call().await?)
+    }
+
+    // The borrow/collateral pair paying the most incentive, repaying as much
+    // as the close factor and the collateral allow
+    pub async fn plan(&self, user: Address) -> Result<Option<CompoundPlan>> {
+        let positions = self.positions(user).await?;
+        let comptroller = Comptroller::new(self.comptroller, self.provider.clone());
+        let close_factor = comptroller.close_factor_mantissa().call().await?;
+        let incentive = comptroller.liquidation_incentive_mantissa().call().await?;
+        Ok(best_pair(&positions, close_factor, incentive))
+    }
+}
+
+fn best_pair(positions: &[Market], close_factor: U256, incentive: U256) -> Option<CompoundPlan> {
+    let one = U256::exp10(18);
+    let bps = U256::from(BPS);
+    if incentive <= one {
+        return None;
+    }
+    let bonus_bps = ((incentive - one) * bps / one).as_u32();
+    let mut best: Option<CompoundPlan> = None;
+
+    for debt in positions.iter().filter(|m| !m.borrowed.is_zero()) {
+        let repayable = debt.value(debt.borrowed) * close_factor / one;
+        for collateral in positions.iter().filter(|m| !m.supplied.is_zero()) {
+            // Seizing takes repay * incentive of collateral, which must exist
+            let ceiling = collateral.value(collateral.supplied) * one / incentive;
+            let repay = repayable.min(ceiling);
+
+            let seized = repay * incentive / one;
+            let protocol_share = seized * U256::from(PROTOCOL_SEIZE_SHARE_BPS) / bps;
+            let flash_fee = repay * U256::from(FLASH_LOAN_FEE_BPS) / bps;
+            let Some(gross) = (seized - repay).checked_sub(protocol_share + flash_fee).filter(|g| !g.is_zero()) else {
+                continue;
+            };
+            if best.as_ref().map_or(false, |b| b.gross_value >= gross) {
+                continue;
+            }
+            best = Some(CompoundPlan {
+                c_token_collateral: collateral.c_token,
+                debt_asset: debt.underlying,
+                repay_amount: debt.amount(repay),
+                repay_value: repay,
+                bonus_bps,
+                collateral_value: collateral.value(collateral.supplied),
+                gross_value: gross,
+                debt_price: debt.price,
+            });
+        }
+    }
+    best
+}
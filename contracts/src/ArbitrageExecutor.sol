@@ -0,0 +1,390 @@
+// SPDX-License-Identifier: MIT
+pragma solidity ^0.8.19;
+
+import "@aave/core-v3/contracts/flashloan/base/FlashLoanSimpleReceiverBase.sol";
+import "@aave/core-v3/contracts/interfaces/IPoolAddressesProvider.sol";
+import "@openzeppelin/contracts/access/Ownable.sol";
+import "@openzeppelin/contracts/token/ERC20/IERC20.sol";
+import "@openzeppelin/contracts/token/ERC20/utils/SafeERC20.sol";
+
+interface IUniswapV2Pair {
+    function token0() external view returns (address);
+    function getReserves() external view returns (uint112 reserve0, uint112 reserve1, uint32 blockTimestampLast);
+    function swap(uint256 amount0Out, uint256 amount1Out, address to, bytes calldata data) external;
+}
+
+interface IUniswapV3Pool {
+    function token0() external view returns (address);
+    function flash(address recipient, uint256 amount0, uint256 amount1, bytes calldata data) external;
+    function swap(
+        address recipient,
+        bool zeroForOne,
+        int256 amountSpecified,
+        uint160 sqrtPriceLimitX96,
+        bytes calldata data
+    ) external returns (int256 amount0, int256 amount1);
+}
+
+interface ILBPair {
+    function swap(bool swapForY, address to) external returns (bytes32 amountsOut);
+}
+
+interface IMaverickV2Pool {
+    struct SwapParams {
+        uint256 amount;
+        bool tokenAIn;
+        bool exactOutput;
+        int32 tickLimit;
+    }
+    function swap(address recipient, SwapParams calldata params, bytes calldata data)
+        external
+        returns (uint256 amountIn, uint256 amountOut);
+}
+
+interface IPoolManager {
+    struct PoolKey {
+        address currency0;
+        address currency1;
+        uint24 fee;
+        int24 tickSpacing;
+        address hooks;
+    }
+    struct SwapParams {
+        bool zeroForOne;
+        int256 amountSpecified;
+        uint160 sqrtPriceLimitX96;
+    }
+    function unlock(bytes calldata data) external returns (bytes memory);
+    function swap(PoolKey memory key, SwapParams memory params, bytes calldata hookData) external returns (int256);
+    function sync(address currency) external;
+    function settle() external payable returns (uint256);
+    function take(address currency, address to, uint256 amount) external;
+}
+
+// Runs the bot's two-pool arbitrage: borrows from Aave, a V2 pair's flash swap
+// or a V3 pool's flash, runs each `SwapLeg` against its venue and reverts
+// unless the loan is repaid with `minProfit` to spare. A leg's calldata is built off chain by the
+// venue's handler; how the input is paid follows from the function it calls:
+// - V2/Solidly pair and Trader Joe swaps: the input is sent to the pool first
+// - Uniswap/PancakeSwap V3 and Maverick V2 swaps: the pool is paid in its callback
+// - Uniswap V4 swaps: run inside PoolManager.unlock, then settled and taken
+// - anything else (Balancer Vault, Curve): the target is approved to pull the input
+// Each leg's output is the next leg's input token, the last leg's the token the
+// lender is repaid in, and must reach the leg's `minOut`.
+contract ArbitrageExecutor is FlashLoanSimpleReceiverBase, Ownable {
+    using SafeERC20 for IERC20;
+
+    struct SwapLeg {
+        address target;
+        address tokenIn;
+        uint256 amountIn;
+        uint256 minOut;
+        bytes data;
+    }
+
+    // Pair or pool lending through a flash swap or flash, the only caller
+    // uniswapV2Call and uniswapV3FlashCallback accept
+    address private activeLender;
+    // Pool or PoolManager of the leg in flight, the only caller a swap callback accepts
+    address private activeSwap;
+    // Its input token, paid from the callback
+    address private activeToken;
+
+    mapping(address => bool) public authorizedCallers;
+    bool public emergencyStop = false;
+
+    uint256 public totalProfit;
+    uint256 public totalArbitrages;
+
+    event ArbitrageExecuted(address indexed token, uint256 borrowed, uint256 profit);
+    event EmergencyStopToggled(bool stopped);
+
+    modifier onlyAuthorized() {
+        require(authorizedCallers[msg.sender] || msg.sender == owner(), "Unauthorized");
+        _;
+    }
+
+    modifier notStopped() {
+        require(!emergencyStop, "Emergency stop active");
+        _;
+    }
+
+    constructor(address _addressProvider)
+        FlashLoanSimpleReceiverBase(IPoolAddressesProvider(_addressProvider))
+    {
+        authorizedCallers[msg.sender] = true;
+    }
+
+    // Flash-borrow `flashAmount` of `flashToken` from Aave and run `legs` with it
+    function executeArbitrage(
+        address flashToken,
+        uint256 flashAmount,
+        SwapLeg[] calldata legs,
+        uint256 minProfit
+    ) external onlyAuthorized notStopped {
+        require(legs.length > 0, "No legs");
+        POOL.flashLoanSimple(address(this), flashToken, flashAmount, abi.encode(legs, minProfit), 0);
+    }
+
+    // Aave flash loan callback
+    function executeOperation(
+        address asset,
+        uint256 amount,
+        uint256 premium,
+        address initiator,
+        bytes calldata params
+    ) external override returns (bool) {
+        require(msg.sender == address(POOL), "Invalid caller");
+        require(initiator == address(this), "Invalid initiator");
+        (SwapLeg[] memory legs, uint256 minProfit) = abi.decode(params, (SwapLeg[], uint256));
+
+        uint256 profit = _runLegs(legs, asset, amount, amount + premium, minProfit);
+        IERC20(asset).forceApprove(address(POOL), amount + premium);
+        emit ArbitrageExecuted(asset, amount, profit);
+        return true;
+    }
+
+    // Take `borrowAmount` of `borrowToken` out of the V2 `pair`, run `legs` in
+    // `uniswapV2Call` and repay the pair in `repayToken`. `repayAmount` is the
+    // bot's quote for the buy side; the pair is paid at least what its 0.3%
+    // fee invariant asks for.
+    function executeFlashSwap(
+        address pair,
+        address borrowToken,
+        uint256 borrowAmount,
+        address repayToken,
+        uint256 repayAmount,
+        SwapLeg[] calldata legs,
+        uint256 minProfit
+    ) external onlyAuthorized notStopped {
+        require(legs.length > 0, "No legs");
+        bool borrowToken0 = IUniswapV2Pair(pair).token0() == borrowToken;
+        bytes memory data = abi.encode(borrowToken, borrowAmount, repayToken, repayAmount, legs, minProfit);
+
+        activeLender = pair;
+        IUniswapV2Pair(pair).swap(
+            borrowToken0 ? borrowAmount : 0,
+            borrowToken0 ? 0 : borrowAmount,
+            address(this),
+            data
+        );
+        activeLender = address(0);
+    }
+
+    // Uniswap V2 flash swap callback; SushiSwap and other forks call the same name
+    function uniswapV2Call(address sender, uint256, uint256, bytes calldata data) external {
+        require(msg.sender == activeLender && activeLender != address(0), "Invalid caller");
+        require(sender == address(this), "Invalid initiator");
+        (
+            address borrowToken,
+            uint256 borrowAmount,
+            address repayToken,
+            uint256 repayAmount,
+            SwapLeg[] memory legs,
+            uint256 minProfit
+        ) = abi.decode(data, (address, uint256, address, uint256, SwapLeg[], uint256));
+
+        uint256 owed = _flashSwapOwed(msg.sender, borrowToken, borrowAmount, repayToken, repayAmount);
+        uint256 borrowed = repayToken == borrowToken ? borrowAmount : 0;
+        uint256 profit = _runLegs(legs, repayToken, borrowed, owed, minProfit);
+        IERC20(repayToken).safeTransfer(msg.sender, owed);
+        emit ArbitrageExecuted(repayToken, borrowAmount, profit);
+    }
+
+    // Borrow `borrowAmount` of `borrowToken` through the V3 `pool`'s flash and
+    // run `legs` in `uniswapV3FlashCallback`, which repays it plus the pool's fee
+    function executeV3Flash(
+        address pool,
+        address borrowToken,
+        uint256 borrowAmount,
+        SwapLeg[] calldata legs,
+        uint256 minProfit
+    ) external onlyAuthorized notStopped {
+        require(legs.length > 0, "No legs");
+        bool borrowToken0 = IUniswapV3Pool(pool).token0() == borrowToken;
+        bytes memory data = abi.encode(borrowToken, borrowAmount, legs, minProfit);
+
+        activeLender = pool;
+        IUniswapV3Pool(pool).flash(
+            address(this),
+            borrowToken0 ? borrowAmount : 0,
+            borrowToken0 ? 0 : borrowAmount,
+            data
+        );
+        activeLender = address(0);
+    }
+
+    // Uniswap V3 flash callback; only the borrowed side carries a fee
+    function uniswapV3FlashCallback(uint256 fee0, uint256 fee1, bytes calldata data) external {
+        require(msg.sender == activeLender && activeLender != address(0), "Invalid caller");
+        (address borrowToken, uint256 borrowAmount, SwapLeg[] memory legs, uint256 minProfit) =
+            abi.decode(data, (address, uint256, SwapLeg[], uint256));
+
+        uint256 owed = borrowAmount + fee0 + fee1;
+        uint256 profit = _runLegs(legs, borrowToken, borrowAmount, owed, minProfit);
+        IERC20(borrowToken).safeTransfer(msg.sender, owed);
+        emit ArbitrageExecuted(borrowToken, borrowAmount, profit);
+    }
+
+    // What a pair takes back for `borrowAmount`: the borrowed token plus its
+    // 0.3% fee, or the other token at the pre-swap reserves' price
+    function _flashSwapOwed(
+        address pair,
+        address borrowToken,
+        uint256 borrowAmount,
+        address repayToken,
+        uint256 repayAmount
+    ) internal view returns (uint256) {
+        if (repayToken == borrowToken) {
+            return (borrowAmount * 1000) / 997 + 1;
+        }
+        // Reserves aren't updated until the callback returns
+        (uint112 reserve0, uint112 reserve1, ) = IUniswapV2Pair(pair).getReserves();
+        (uint256 reserveIn, uint256 reserveOut) = IUniswapV2Pair(pair).token0() == repayToken
+            ? (uint256(reserve0), uint256(reserve1))
+            : (uint256(reserve1), uint256(reserve0));
+        uint256 amountIn = (reserveIn * borrowAmount * 1000) / ((reserveOut - borrowAmount) * 997) + 1;
+        return amountIn > repayAmount ? amountIn : repayAmount;
+    }
+
+    // Run every leg and return the profit in `token`: what the legs leave on
+    // top of the balance held before them, less `owed` to the lender.
+    // `borrowed` is the part of that balance the lender sent, so it counts
+    // towards repaying `owed`. Reverts unless the profit reaches `minProfit`.
+    function _runLegs(
+        SwapLeg[] memory legs,
+        address token,
+        uint256 borrowed,
+        uint256 owed,
+        uint256 minProfit
+    ) internal returns (uint256 profit) {
+        uint256 before = IERC20(token).balanceOf(address(this));
+        for (uint256 i = 0; i < legs.length; i++) {
+            address tokenOut = i + 1 < legs.length ? legs[i + 1].tokenIn : token;
+            _runLeg(legs[i], tokenOut);
+        }
+        uint256 available = IERC20(token).balanceOf(address(this)) + borrowed;
+        require(available >= before + owed + minProfit, "Below minimum profit");
+        profit = available - before - owed;
+        totalProfit += profit;
+        totalArbitrages++;
+    }
+
+    function _runLeg(SwapLeg memory leg, address tokenOut) internal {
+        uint256 outBefore = IERC20(tokenOut).balanceOf(address(this));
+        bytes4 selector = _selector(leg.data);
+
+        if (selector == IUniswapV2Pair.swap.selector || selector == ILBPair.swap.selector) {
+            IERC20(leg.tokenIn).safeTransfer(leg.target, leg.amountIn);
+            _call(leg.target, leg.data);
+        } else if (selector == IUniswapV3Pool.swap.selector || selector == IMaverickV2Pool.swap.selector) {
+            activeSwap = leg.target;
+            activeToken = leg.tokenIn;
+            _call(leg.target, leg.data);
+            activeSwap = address(0);
+            activeToken = address(0);
+        } else if (selector == IPoolManager.swap.selector) {
+            activeSwap = leg.target;
+            IPoolManager(leg.target).unlock(abi.encode(leg));
+            activeSwap = address(0);
+        } else {
+            IERC20(leg.tokenIn).forceApprove(leg.target, leg.amountIn);
+            _call(leg.target, leg.data);
+            IERC20(leg.tokenIn).forceApprove(leg.target, 0);
+        }
+
+        require(IERC20(tokenOut).balanceOf(address(this)) - outBefore >= leg.minOut, "Leg below minimum out");
+    }
+
+    // Uniswap V3: pay whichever side the pool is owed
+    function uniswapV3SwapCallback(int256 amount0Delta, int256 amount1Delta, bytes calldata) external {
+        _paySwap(amount0Delta > 0 ? uint256(amount0Delta) : uint256(amount1Delta));
+    }
+
+    // PancakeSwap V3 calls back under its own name
+    function pancakeV3SwapCallback(int256 amount0Delta, int256 amount1Delta, bytes calldata) external {
+        _paySwap(amount0Delta > 0 ? uint256(amount0Delta) : uint256(amount1Delta));
+    }
+
+    function maverickV2SwapCallback(address, uint256 amountIn, uint256, bytes calldata) external {
+        _paySwap(amountIn);
+    }
+
+    function _paySwap(uint256 amount) internal {
+        require(msg.sender == activeSwap && activeSwap != address(0), "Invalid caller");
+        IERC20(activeToken).safeTransfer(msg.sender, amount);
+    }
+
+    // Uniswap V4: swap, pay what the pool is owed and take what it owes.
+    // Native-currency pools aren't supported.
+    function unlockCallback(bytes calldata data) external returns (bytes memory) {
+        require(msg.sender == activeSwap && activeSwap != address(0), "Invalid caller");
+        SwapLeg memory leg = abi.decode(data, (SwapLeg));
+        (IPoolManager.PoolKey memory key, , ) = abi.decode(
+            _args(leg.data),
+            (IPoolManager.PoolKey, IPoolManager.SwapParams, bytes)
+        );
+        require(key.currency0 != address(0), "Native V4 pool");
+
+        bytes memory result = _call(msg.sender, leg.data);
+        int256 delta = abi.decode(result, (int256));
+        int128 amount0 = int128(delta >> 128);
+        int128 amount1 = int128(delta);
+        _settleV4(key.currency0, amount0);
+        _settleV4(key.currency1, amount1);
+        return "";
+    }
+
+    // Negative deltas are owed to the PoolManager, positive ones owed by it
+    function _settleV4(address currency, int128 amount) internal {
+        IPoolManager manager = IPoolManager(msg.sender);
+        if (amount < 0) {
+            manager.sync(currency);
+            IERC20(currency).safeTransfer(address(manager), uint256(uint128(-amount)));
+            manager.settle();
+        } else if (amount > 0) {
+            manager.take(currency, address(this), uint256(uint128(amount)));
+        }
+    }
+
+    function _call(address target, bytes memory data) internal returns (bytes memory) {
+        (bool ok, bytes memory result) = target.call(data);
+        if (!ok) {
+            assembly {
+                revert(add(result, 32), mload(result))
+            }
+        }
+        return result;
+    }
+
+    function _selector(bytes memory data) internal pure returns (bytes4 selector) {
+        require(data.length >= 4, "Short calldata");
+        assembly {
+            selector := mload(add(data, 32))
+        }
+    }
+
+    // Calldata without its selector
+    function _args(bytes memory data) internal pure returns (bytes memory args) {
+        args = new bytes(data.length - 4);
+        for (uint256 i = 0; i < args.length; i++) {
+            args[i] = data[i + 4];
+        }
+    }
+
+    // Admin functions
+    function toggleEmergencyStop() external onlyOwner {
+        emergencyStop = !emergencyStop;
+        emit EmergencyStopToggled(emergencyStop);
+    }
+
+    function authorizeAddress(address addr, bool authorized) external onlyOwner {
+        authorizedCallers[addr] = authorized;
+    }
+
+    function withdrawToken(address token) external onlyOwner {
+        IERC20(token).safeTransfer(owner(), IERC20(token).balanceOf(address(this)));
+    }
+
+    receive() external payable {}
+}
@@ -7,20 +7,75 @@ import "@aave/core-v3/contracts/interfaces/IPool.sol";
 import "@openzeppelin/contracts/access/Ownable.sol";
 import "@openzeppelin/contracts/token/ERC20/IERC20.sol";
 
-interface IDYdXFlashLoan {
-    function flashLoan(address token, uint256 amount, bytes calldata data) external;
-}
-
 interface ICompoundV3 {
     function absorb(address absorber, address[] calldata accounts) external;
     function isLiquidatable(address account) external view returns (bool);
 }
 
+interface ICToken {
+    function underlying() external view returns (address);
+    function liquidateBorrow(address borrower, uint256 repayAmount, address cTokenCollateral) external returns (uint256);
+    function redeem(uint256 redeemTokens) external returns (uint256);
+    function balanceOf(address owner) external view returns (uint256);
+}
+
+interface IComptroller {
+    function getAllMarkets() external view returns (address[] memory);
+}
+
+interface IClipper {
+    function take(uint256 id, uint256 amt, uint256 max, address who, bytes calldata data) external;
+}
+
+interface IVat {
+    function hope(address usr) external;
+}
+
+interface IGemJoin {
+    function gem() external view returns (address);
+    function dec() external view returns (uint256);
+    function exit(address usr, uint256 amt) external;
+}
+
+interface IDaiJoin {
+    function join(address usr, uint256 wad) external;
+}
+
+interface ISwapRouter {
+    struct ExactInputSingleParams {
+        address tokenIn;
+        address tokenOut;
+        uint24 fee;
+        address recipient;
+        uint256 deadline;
+        uint256 amountIn;
+        uint256 amountOutMinimum;
+        uint160 sqrtPriceLimitX96;
+    }
+    function exactInputSingle(ExactInputSingleParams calldata params) external payable returns (uint256);
+}
+
+interface IWETH {
+    function withdraw(uint256 amount) external;
+}
+
 interface IEulerV2 {
     function liquidate(address violator, address collateral, uint256 repayAmount) external;
 }
 
 contract LiquidationExecutor is FlashLoanSimpleReceiverBase, Ownable {
+    // Maker core and the router auction collateral is sold through
+    address constant MAKER_VAT = 0x35D1b3F3D7966A1DFe207aa4514C12a259A0492B;
+    address constant MAKER_DAI_JOIN = 0x9759A6Ac90977b93B58547b4A71c78317f391A28;
+    address constant DAI = 0x6B175474E89094C44Da98b954EedeAC495271d0F;
+    address constant UNISWAP_V3_ROUTER = 0xE592427A0AEce92De3Edee1F18E0157C05861564;
+    uint256 constant RAY = 1e27;
+    // Builder tips beyond the ETH balance are unwrapped from it
+    address constant WETH = 0xC02aaA39b223FE8D0A0e5C4F27eAD9083C756Cc2;
+    
+    // Clipper allowed to call back during a take
+    address private activeClipper;
+    
     // Protocol addresses
     mapping(string => address) public protocols;
     mapping(address => uint256) public liquidationBonuses; // basis points
@@ -64,12 +119,13 @@ contract LiquidationExecutor is FlashLoanSimpleReceiverBase, Ownable {
         // Initialize protocol addresses (mainnet)
         protocols["AAVE_V3"] = 0x87870Bca3F3fD6335C3F4ce8392D69350B4fA4E2;
         protocols["COMPOUND_V3"] = 0xc3d688B66703497DAA19211EEdff47f25384cdc3;
+        protocols["COMPOUND_V2"] = 0x3d9819210A31b4961b30EF54bE2aeD79B9c9Cd3B; // Comptroller
         protocols["EULER_V2"] = 0x0000000000000000000000000000000000000000; // Update with V2 address
-        protocols["DYDX"] = 0x1E0447b19BB6EcFdAe1e4AE1694b0C3659614e4e;
         
         // Set default liquidation bonuses
         liquidationBonuses[protocols["AAVE_V3"]] = 500; // 5%
         liquidationBonuses[protocols["COMPOUND_V3"]] = 700; // 7%
+        liquidationBonuses[protocols["COMPOUND_V2"]] = 800; // 8%
     }
     
     // Main liquidation entry point
@@ -81,15 +137,42 @@ contract LiquidationExecutor is FlashLoanSimpleReceiverBase, Ownable {
         uint256 debtToCover,
         bool useFlashLoan
     ) external onlyAuthorized notStopped {
-        if (useFlashLoan) {
-            // Determine best flash loan source
-            if (debtAsset == address(0xEeeeeEeeeEeEeeEeEeEeeEEEeeeeEeeeeeeeEEeE)) {
-                // Use dYdX for ETH (free)
-                _executeDYdXFlashLoan(user, collateralAsset, debtAsset, debtToCover);
-            } else {
-                // Use Aave for other assets
-                _executeAaveFlashLoan(protocol, user, collateralAsset, debtAsset, debtToCover);
+        _liquidate(protocol, user, collateralAsset, debtAsset, debtToCover, useFlashLoan);
+    }
+    
+    // Liquidate, then pay the block builder `coinbaseTip` wei from the contract's
+    // ETH, unwrapping WETH for any shortfall, so the bid scales with profit
+    // rather than with the gas price
+    function liquidateWithTip(
+        string memory protocol,
+        address user,
+        address collateralAsset,
+        address debtAsset,
+        uint256 debtToCover,
+        bool useFlashLoan,
+        uint256 coinbaseTip
+    ) external onlyAuthorized notStopped {
+        _liquidate(protocol, user, collateralAsset, debtAsset, debtToCover, useFlashLoan);
+        if (coinbaseTip > 0) {
+            uint256 balance = address(this).balance;
+            if (balance < coinbaseTip) {
+                IWETH(WETH).withdraw(coinbaseTip - balance);
             }
+            (bool paid, ) = block.coinbase.call{value: coinbaseTip}("");
+            require(paid, "Coinbase payment failed");
+        }
+    }
+    
+    function _liquidate(
+        string memory protocol,
+        address user,
+        address collateralAsset,
+        address debtAsset,
+        uint256 debtToCover,
+        bool useFlashLoan
+    ) internal {
+        if (useFlashLoan) {
+            _executeAaveFlashLoan(protocol, user, collateralAsset, debtAsset, debtToCover);
         } else {
             // Direct liquidation with bot's funds
             _performLiquidation(protocol, user, collateralAsset, debtAsset, debtToCover);
@@ -108,17 +191,6 @@ contract LiquidationExecutor is FlashLoanSimpleReceiverBase, Ownable {
         POOL.flashLoanSimple(address(this), debtAsset, amount, params, 0);
     }
     
-    // dYdX flash loan execution (free for ETH, DAI, USDC)
-    function _executeDYdXFlashLoan(
-        address user,
-        address collateralAsset,
-        address debtAsset,
-        uint256 amount
-    ) internal {
-        bytes memory data = abi.encode(user, collateralAsset, debtAsset, amount);
-        IDYdXFlashLoan(protocols["DYDX"]).flashLoan(debtAsset, amount, data);
-    }
-    
     // Flash loan callback from Aave
     function executeOperation(
         address asset,
@@ -182,6 +254,8 @@ contract LiquidationExecutor is FlashLoanSimpleReceiverBase, Ownable {
             return _liquidateAave(user, collateralAsset, debtAsset, debtToCover);
         } else if (keccak256(bytes(protocol)) == keccak256(bytes("COMPOUND_V3"))) {
             return _liquidateCompound(user);
+        } else if (keccak256(bytes(protocol)) == keccak256(bytes("COMPOUND_V2"))) {
+            return _liquidateCompoundV2(user, collateralAsset, debtAsset, debtToCover);
         } else if (keccak256(bytes(protocol)) == keccak256(bytes("EULER_V2"))) {
             return _liquidateEuler(user, collateralAsset, debtToCover);
         }
@@ -227,6 +301,44 @@ contract LiquidationExecutor is FlashLoanSimpleReceiverBase, Ownable {
         return 0; // Placeholder
     }
     
+    // Compound V2 liquidation: collateralAsset is the cToken to seize, debtAsset
+    // the underlying of the borrow being repaid
+    function _liquidateCompoundV2(
+        address borrower,
+        address cTokenCollateral,
+        address debtAsset,
+        uint256 repayAmount
+    ) internal returns (uint256) {
+        address cTokenBorrowed = _compoundMarket(debtAsset);
+        IERC20(debtAsset).approve(cTokenBorrowed, repayAmount);
+        require(
+            ICToken(cTokenBorrowed).liquidateBorrow(borrower, repayAmount, cTokenCollateral) == 0,
+            "Compound liquidation failed"
+        );
+        
+        // Redeem the seized cTokens for the underlying
+        address collateral = ICToken(cTokenCollateral).underlying();
+        uint256 balanceBefore = IERC20(collateral).balanceOf(address(this));
+        require(
+            ICToken(cTokenCollateral).redeem(ICToken(cTokenCollateral).balanceOf(address(this))) == 0,
+            "Compound redeem failed"
+        );
+        return IERC20(collateral).balanceOf(address(this)) - balanceBefore;
+    }
+    
+    // cToken for an underlying; cETH has no underlying() and is skipped
+    function _compoundMarket(address underlying) internal view returns (address) {
+        address[] memory markets = IComptroller(protocols["COMPOUND_V2"]).getAllMarkets();
+        for (uint256 i = 0; i < markets.length; i++) {
+            try ICToken(markets[i]).underlying() returns (address asset) {
+                if (asset == underlying) {
+                    return markets[i];
+                }
+            } catch {}
+        }
+        revert("No Compound market");
+    }
+    
     // Euler V2 liquidation
     function _liquidateEuler(
         address violator,
@@ -240,6 +352,63 @@ contract LiquidationExecutor is FlashLoanSimpleReceiverBase, Ownable {
         return 0; // Placeholder
     }
     
+    // Maker Clipper auction: the Clipper sends the collateral and calls back
+    // before collecting DAI, so the lot is paid for by selling it
+    function takeAuction(
+        address clipper,
+        uint256 id,
+        uint256 amt,
+        uint256 maxPrice,
+        address gemJoin,
+        uint24 poolFee
+    ) external onlyAuthorized notStopped {
+        uint256 daiBefore = IERC20(DAI).balanceOf(address(this));
+        
+        // The Clipper collects payment from our Vat balance
+        IVat(MAKER_VAT).hope(clipper);
+        activeClipper = clipper;
+        IClipper(clipper).take(id, amt, maxPrice, address(this), abi.encode(gemJoin, poolFee));
+        activeClipper = address(0);
+        
+        uint256 daiAfter = IERC20(DAI).balanceOf(address(this));
+        require(daiAfter > daiBefore, "Unprofitable take");
+        
+        uint256 profit = daiAfter - daiBefore;
+        require(profit >= minProfitThreshold, "Below minimum profit");
+        totalProfit += profit;
+        totalLiquidations++;
+        
+        emit LiquidationExecuted(clipper, address(0), IGemJoin(gemJoin).gem(), 0, amt, profit);
+    }
+    
+    // Clipper callback: `slice` collateral (Vat units) is ours, `owe` DAI (rad) is due
+    function clipperCall(address sender, uint256 owe, uint256 slice, bytes calldata data) external {
+        require(msg.sender == activeClipper && sender == address(this), "Invalid caller");
+        (address gemJoin, uint24 poolFee) = abi.decode(data, (address, uint24));
+        
+        address gem = IGemJoin(gemJoin).gem();
+        uint256 amount = slice / 10 ** (18 - IGemJoin(gemJoin).dec());
+        IGemJoin(gemJoin).exit(address(this), amount);
+        
+        uint256 daiOwed = owe / RAY + 1;
+        IERC20(gem).approve(UNISWAP_V3_ROUTER, amount);
+        ISwapRouter(UNISWAP_V3_ROUTER).exactInputSingle(
+            ISwapRouter.ExactInputSingleParams({
+                tokenIn: gem,
+                tokenOut: DAI,
+                fee: poolFee,
+                recipient: address(this),
+                deadline: block.timestamp,
+                amountIn: amount,
+                amountOutMinimum: daiOwed,
+                sqrtPriceLimitX96: 0
+            })
+        );
+        
+        IERC20(DAI).approve(MAKER_DAI_JOIN, daiOwed);
+        IDaiJoin(MAKER_DAI_JOIN).join(address(this), daiOwed);
+    }
+    
     // Multi-liquidation batch execution
     function batchLiquidate(
         string[] memory protocols,
@@ -257,7 +426,7 @@ contract LiquidationExecutor is FlashLoanSimpleReceiverBase, Ownable {
         );
         
         for (uint256 i = 0; i < protocols.length; i++) {
-            liquidate(
+            _liquidate(
                 protocols[i],
                 users[i],
                 collateralAssets[i],
@@ -0,0 +1,179 @@
+// SPDX-License-Identifier: UNLICENSED
+pragma solidity ^0.8.19;
+
+import {Test} from "forge-std/Test.sol";
+import {IERC20} from "@openzeppelin/contracts/token/ERC20/IERC20.sol";
+import {ArbitrageExecutor, IUniswapV2Pair, IUniswapV3Pool} from "../src/ArbitrageExecutor.sol";
+
+interface IReserves {
+    function getReserves() external view returns (uint112, uint112, uint32);
+}
+
+// Runs against a mainnet fork: FORK_URL=<rpc> forge test --match-contract ArbitrageExecutor
+contract ArbitrageExecutorTest is Test {
+    address constant AAVE_PROVIDER = 0x2f39d218133AFaB8F2B819B1066c7E434Ad94E9e;
+    address constant WETH = 0xC02aaA39b223FE8D0A0e5C4F27eAD9083C756Cc2;
+    address constant USDC = 0xA0b86991c6218b36c1d19D4a2e9Eb0cE3606eB48;
+    // Uniswap V2 USDC/WETH and V3 USDC/WETH 0.05%, USDC is token0 in both
+    address constant V2_PAIR = 0xB4e16d0168e52d35CaCD2c6185b44281Ec28C9Dc;
+    address constant V3_POOL = 0x88e6A0c2dDD26FEEb64F039a2c41296FcB3f5640;
+    // Uniswap V3 WETH/USDT 0.05%, lending WETH outside the trade
+    address constant V3_LENDER = 0x11b815efB8f581194ae79006d24E0d814B7697F6;
+    uint160 constant MIN_SQRT_RATIO = 4295128739;
+    uint160 constant MAX_SQRT_RATIO = 1461446703485210103287273052203988822378723970342;
+
+    ArbitrageExecutor public executor;
+
+    function setUp() public {
+        string memory forkUrl = vm.envOr("FORK_URL", string(""));
+        if (bytes(forkUrl).length == 0) {
+            vm.skip(true);
+            return;
+        }
+        vm.createSelectFork(forkUrl);
+        executor = new ArbitrageExecutor(AAVE_PROVIDER);
+    }
+
+    function test_ArbitrageAcrossV2AndV3() public {
+        _skewV2(true);
+        uint256 borrow = 10 ether;
+
+        executor.executeArbitrage(WETH, borrow, _legs(borrow), 0.01 ether);
+
+        // Whatever is left after repaying Aave is profit
+        assertGt(IERC20(WETH).balanceOf(address(executor)), 0.01 ether);
+        assertEq(executor.totalArbitrages(), 1);
+        assertEq(executor.totalProfit(), IERC20(WETH).balanceOf(address(executor)));
+    }
+
+    function test_RevertsBelowMinProfit() public {
+        uint256 borrow = 10 ether;
+        ArbitrageExecutor.SwapLeg[] memory legs = _legs(borrow);
+
+        // Without a spread the round trip loses both pool fees and the premium
+        vm.expectRevert();
+        executor.executeArbitrage(WETH, borrow, legs, 0);
+    }
+
+    function test_RejectsUnauthorizedCaller() public {
+        ArbitrageExecutor.SwapLeg[] memory legs = _legs(1 ether);
+        vm.prank(address(0xBEEF));
+        vm.expectRevert(bytes("Unauthorized"));
+        executor.executeArbitrage(WETH, 1 ether, legs, 0);
+    }
+
+    function test_FlashSwapFromV2Pair() public {
+        // WETH cheap on V2: borrow it from the pair, sell it on V3 and repay in USDC
+        _skewV2(false);
+        uint256 borrow = 10 ether;
+        (uint112 usdcReserve, uint112 wethReserve, ) = IReserves(V2_PAIR).getReserves();
+        uint256 repay = _amountIn(borrow, usdcReserve, wethReserve);
+
+        ArbitrageExecutor.SwapLeg[] memory legs = new ArbitrageExecutor.SwapLeg[](1);
+        legs[0] = ArbitrageExecutor.SwapLeg({
+            target: V3_POOL,
+            tokenIn: WETH,
+            amountIn: borrow,
+            minOut: repay,
+            data: abi.encodeWithSelector(
+                IUniswapV3Pool.swap.selector, address(executor), false, int256(borrow), MAX_SQRT_RATIO - 1, ""
+            )
+        });
+        executor.executeFlashSwap(V2_PAIR, WETH, borrow, USDC, repay, legs, 1e6);
+
+        assertGt(IERC20(USDC).balanceOf(address(executor)), 1e6);
+        assertEq(IERC20(WETH).balanceOf(address(executor)), 0);
+    }
+
+    function test_FlashSwapRepaysAtLeastThePairsPrice() public {
+        // Repaying less than the pair's fee-inclusive price is raised to it,
+        // which the round trip without a spread can't cover
+        uint256 borrow = 10 ether;
+        ArbitrageExecutor.SwapLeg[] memory legs = new ArbitrageExecutor.SwapLeg[](1);
+        legs[0] = ArbitrageExecutor.SwapLeg({
+            target: V3_POOL,
+            tokenIn: WETH,
+            amountIn: borrow,
+            minOut: 0,
+            data: abi.encodeWithSelector(
+                IUniswapV3Pool.swap.selector, address(executor), false, int256(borrow), MAX_SQRT_RATIO - 1, ""
+            )
+        });
+        vm.expectRevert();
+        executor.executeFlashSwap(V2_PAIR, WETH, borrow, USDC, 1, legs, 0);
+    }
+
+    function test_FlashFromV3Pool() public {
+        _skewV2(true);
+        uint256 borrow = 10 ether;
+
+        executor.executeV3Flash(V3_LENDER, WETH, borrow, _legs(borrow), 0.01 ether);
+
+        // The lender took back its 0.05% fee with the loan
+        assertGt(IERC20(WETH).balanceOf(address(executor)), 0.01 ether);
+        assertEq(executor.totalArbitrages(), 1);
+    }
+
+    function test_RejectsStrayFlashCallback() public {
+        vm.expectRevert(bytes("Invalid caller"));
+        executor.uniswapV3FlashCallback(1, 0, "");
+    }
+
+    function test_RejectsStrayFlashSwapCallback() public {
+        vm.expectRevert(bytes("Invalid caller"));
+        executor.uniswapV2Call(address(executor), 1, 0, "");
+    }
+
+    function test_RejectsStraySwapCallback() public {
+        vm.expectRevert(bytes("Invalid caller"));
+        executor.uniswapV3SwapCallback(1, 0, "");
+    }
+
+    // Sell WETH for USDC on V2, buy it back on V3, as the bot builds them
+    function _legs(uint256 borrow) internal view returns (ArbitrageExecutor.SwapLeg[] memory legs) {
+        (uint112 usdcReserve, uint112 wethReserve, ) = IReserves(V2_PAIR).getReserves();
+        uint256 usdcOut = _amountOut(borrow, wethReserve, usdcReserve);
+
+        legs = new ArbitrageExecutor.SwapLeg[](2);
+        legs[0] = ArbitrageExecutor.SwapLeg({
+            target: V2_PAIR,
+            tokenIn: WETH,
+            amountIn: borrow,
+            minOut: usdcOut,
+            data: abi.encodeWithSelector(IUniswapV2Pair.swap.selector, usdcOut, 0, address(executor), "")
+        });
+        legs[1] = ArbitrageExecutor.SwapLeg({
+            target: V3_POOL,
+            tokenIn: USDC,
+            amountIn: usdcOut,
+            minOut: 0,
+            data: abi.encodeWithSelector(
+                IUniswapV3Pool.swap.selector, address(executor), true, int256(usdcOut), MIN_SQRT_RATIO + 1, ""
+            )
+        });
+    }
+
+    // Buy WETH out of the V2 pair until it trades well above V3, or sell it in
+    // until it trades well below
+    function _skewV2(bool wethUp) internal {
+        (uint112 usdcReserve, uint112 wethReserve, ) = IReserves(V2_PAIR).getReserves();
+        if (wethUp) {
+            uint256 usdcIn = uint256(usdcReserve) / 2;
+            deal(USDC, V2_PAIR, uint256(usdcReserve) + usdcIn);
+            IUniswapV2Pair(V2_PAIR).swap(0, _amountOut(usdcIn, usdcReserve, wethReserve), address(this), "");
+        } else {
+            uint256 wethIn = uint256(wethReserve) / 2;
+            deal(WETH, V2_PAIR, uint256(wethReserve) + wethIn);
+            IUniswapV2Pair(V2_PAIR).swap(_amountOut(wethIn, wethReserve, usdcReserve), 0, address(this), "");
+        }
+    }
+
+    function _amountOut(uint256 amountIn, uint256 reserveIn, uint256 reserveOut) internal pure returns (uint256) {
+        uint256 amountInWithFee = amountIn * 997;
+        return (amountInWithFee * reserveOut) / (reserveIn * 1000 + amountInWithFee);
+    }
+
+    function _amountIn(uint256 amountOut, uint256 reserveIn, uint256 reserveOut) internal pure returns (uint256) {
+        return (reserveIn * amountOut * 1000) / ((reserveOut - amountOut) * 997) + 1;
+    }
+}
@@ -0,0 +1,330 @@
+use ethers::{
+    abi::{self, Token as AbiToken},
+    contract::abigen,
+    providers::Middleware,
+    signers::Signer,
+    types::{
+        transaction::eip712::{EIP712Domain, Eip712},
+        Address, U256,
+    },
+    utils::keccak256,
+};
+use serde_json::{json, Value};
+use std::{convert::Infallible, sync::Arc};
+
+use crate::{
+    errors::{BotError, ExecutionError, ProviderError, Result},
+    math,
+    models::ArbitrageOpportunity,
+    signer::WalletSigner,
+};
+
+abigen!(
+    CowSellToken,
+    r#"[
+        function balanceOf(address owner) external view returns (uint256)
+        function allowance(address owner, address spender) external view returns (uint256)
+    ]"#
+);
+
+// GPv2Settlement, which orders are signed for, and the relayer that pulls the
+// sell token; the same on every chain CoW Protocol runs on
+pub const COW_SETTLEMENT: &str = "0x9008D19f58AAbD9eD0D60971565AA8510560ab41";
+pub const COW_VAULT_RELAYER: &str = "0xC92E8bdf79f0507f65a392b0ab4667716BFE0110";
+
+const ORDER_TYPE: &str = "Order(address sellToken,address buyToken,address receiver,uint256 sellAmount,uint256 buyAmount,uint32 validTo,bytes32 appData,uint256 feeAmount,string kind,bool partiallyFillable,string sellTokenBalance,string buyTokenBalance)";
+const CANCELLATIONS_TYPE: &str = "OrderCancellations(bytes[] orderUids)";
+// Empty app data, which the order book knows without it being uploaded
+const APP_DATA: &str = "{}";
+
+// Order book API by chain id
+pub fn api_url(chain_id: u64) -> Option<&'static str> {
+    match chain_id {
+        1 => Some("https://api.cow.fi/mainnet"),
+        100 => Some("https://api.cow.fi/xdai"),
+        8453 => Some("https://api.cow.fi/base"),
+        42161 => Some("https://api.cow.fi/arbitrum_one"),
+        _ => None,
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct CowConfig {
+    pub enabled: bool,
+    // Opportunities below this spread go to the flash-loan executor, since the
+    // price has to hold until the next batch settles
+    pub min_spread_bps: u32,
+    // Buy limits sit this far under the pools' quotes
+    pub slippage_bps: u32,
+    // Seconds an order stays valid
+    pub validity_secs: u32,
+}
+
+impl CowConfig {
+    pub fn from_env() -> Self {
+        let var = |name: &str, default: u32| std::env::var(name).ok().and_then(|v| v.parse().ok()).unwrap_or(default);
+
+        Self {
+            enabled: std::env::var("ENABLE_COW").map(|v| v == "true").unwrap_or(false),
+            min_spread_bps: var("COW_MIN_SPREAD_BPS", 50),
+            slippage_bps: var("COW_SLIPPAGE_BPS", 20),
+            validity_secs: var("COW_ORDER_VALIDITY_SECS", 120),
+        }
+    }
+}
+
+// A fill-or-kill sell order paid out of, and back into, the signer's ERC20
+// balances. The fee is taken from surplus, so the signed fee is always zero.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CowOrder {
+    pub sell_token: Address,
+    pub buy_token: Address,
+    pub receiver: Address,
+    pub sell_amount: U256,
+    // Minimum received; solvers keep any surplus above it within the batch
+    pub buy_amount: U256,
+    pub valid_to: u32,
+}
+
+// The order as the settlement contract hashes it on a given chain
+struct TypedOrder<'a> {
+    order: &'a CowOrder,
+    chain_id: u64,
+}
+
+// Orders and cancellations are both signed for the settlement contract
+fn settlement_domain(chain_id: u64) -> EIP712Domain {
+    EIP712Domain {
+        name: Some("Gnosis Protocol".to_string()),
+        version: Some("v2".to_string()),
+        chain_id: Some(chain_id.into()),
+        verifying_contract: Some(COW_SETTLEMENT.parse().unwrap()),
+        salt: None,
+    }
+}
+
+impl Eip712 for TypedOrder<'_> {
+    type Error = Infallible;
+
+    fn domain(&self) -> std::result::Result<EIP712Domain, Self::Error> {
+        Ok(settlement_domain(self.chain_id))
+    }
+
+    fn type_hash() -> std::result::Result<[u8; 32], Self::Error> {
+        Ok(keccak256(ORDER_TYPE))
+    }
+
+    fn struct_hash(&self) -> std::result::Result<[u8; 32], Self::Error> {
+        let order = self.order;
+        Ok(keccak256(abi::encode(&[
+            AbiToken::FixedBytes(Self::type_hash()?.to_vec()),
+            AbiToken::Address(order.sell_token),
+            AbiToken::Address(order.buy_token),
+            AbiToken::Address(order.receiver),
+            AbiToken::Uint(order.sell_amount),
+            AbiToken::Uint(order.buy_amount),
+            AbiToken::Uint(order.valid_to.into()),
+            AbiToken::FixedBytes(keccak256(APP_DATA).to_vec()),
+            AbiToken::Uint(U256::zero()),
+            AbiToken::FixedBytes(keccak256("sell").to_vec()),
+            AbiToken::Bool(false),
+            AbiToken::FixedBytes(keccak256("erc20").to_vec()),
+            AbiToken::FixedBytes(keccak256("erc20").to_vec()),
+        ])))
+    }
+}
+
+// Cancellation of open orders by uid, as the order book's DELETE expects it signed
+struct TypedCancellations<'a> {
+    uids: &'a [Vec<u8>],
+    chain_id: u64,
+}
+
+impl Eip712 for TypedCancellations<'_> {
+    type Error = Infallible;
+
+    fn domain(&self) -> std::result::Result<EIP712Domain, Self::Error> {
+        Ok(settlement_domain(self.chain_id))
+    }
+
+    fn type_hash() -> std::result::Result<[u8; 32], Self::Error> {
+        Ok(keccak256(CANCELLATIONS_TYPE))
+    }
+
+    fn struct_hash(&self) -> std::result::Result<[u8; 32], Self::Error> {
+        // A bytes[] member hashes as the hash of its elements' hashes
+        let uids: Vec<u8> = self.uids.iter().flat_map(keccak256).collect();
+        Ok(keccak256(abi::encode(&[
+            AbiToken::FixedBytes(Self::type_hash()?.to_vec()),
+            AbiToken::FixedBytes(keccak256(uids).to_vec()),
+        ])))
+    }
+}
+
+// Executes arbitrage as a pair of CoW Protocol orders instead of a flash-loaned
+// transaction: sell the input for the intermediate token, as on the cheap pool,
+// and sell the intermediate back, as on the expensive one. Both are paid from
+// the wallet's own balances, cost no gas and can't be sandwiched, but only fill
+// at the next batch auction, seconds later.
+pub struct CowClient {
+    http: reqwest::Client,
+    api: String,
+    chain_id: u64,
+    signer: WalletSigner,
+    config: CowConfig,
+}
+
+impl CowClient {
+    // None on chains CoW Protocol doesn't run on
+    pub fn new(chain_id: u64, signer: WalletSigner, config: CowConfig) -> Option<Self> {
+        Some(Self {
+            http: reqwest::Client::new(),
+            api: api_url(chain_id)?.to_string(),
+            chain_id,
+            signer,
+            config,
+        })
+    }
+
+    // Another order book, a staging one say
+    pub fn with_api(mut self, url: &str) -> Self {
+        self.api = url.trim_end_matches('/').to_string();
+        self
+    }
+
+    pub fn owner(&self) -> Address {
+        self.signer.address()
+    }
+
+    // The two legs of `opportunity`, valid from `now` (unix seconds). None when
+    // the spread is too thin to wait for a batch, or the limits leave no profit.
+    pub fn orders_for(&self, opportunity: &ArbitrageOpportunity, now: u64) -> Option<[CowOrder; 2]> {
+        if opportunity.spread_bps < self.config.min_spread_bps {
+            return None;
+        }
+        let keep = U256::from(math::BPS - self.config.slippage_bps.min(math::BPS));
+        let intermediate_min = math::mul_div(opportunity.intermediate_amount, keep, U256::from(math::BPS))?;
+        let out_min = math::mul_div(opportunity.amount_out, keep, U256::from(math::BPS))?;
+        if out_min <= opportunity.amount_in {
+            return None;
+        }
+
+        let valid_to = u32::try_from(now + self.config.validity_secs as u64).ok()?;
+        let owner = self.owner();
+        Some([
+            CowOrder {
+                sell_token: opportunity.token_in,
+                buy_token: opportunity.token_out,
+                receiver: owner,
+                sell_amount: opportunity.amount_in,
+                buy_amount: intermediate_min,
+                valid_to,
+            },
+            CowOrder {
+                sell_token: opportunity.token_out,
+                buy_token: opportunity.token_in,
+                receiver: owner,
+                sell_amount: opportunity.intermediate_amount,
+                buy_amount: out_min,
+                valid_to,
+            },
+        ])
+    }
+
+    // Whether the wallet holds, and has approved the relayer for, every sell amount
+    pub async fn funded<M: Middleware + 'static>(&self, provider: Arc<M>, orders: &[CowOrder]) -> Result<bool> {
+        let owner = self.owner();
+        let relayer: Address = COW_VAULT_RELAYER.parse().unwrap();
+        for order in orders {
+            let token = CowSellToken::new(order.sell_token, provider.clone());
+            let balance = token.balance_of(owner).call().await?;
+            let allowance = token.allowance(owner, relayer).call().await?;
+            if balance < order.sell_amount || allowance < order.sell_amount {
+                return Ok(false);
+            }
+        }
+        Ok(true)
+    }
+
+    // Sign and submit `order`, returning its uid. The legs are independent
+    // orders: one filling alone still trades at its limit, better than the pool,
+    // but one left without the other is cancelled.
+    pub async fn place(&self, order: &CowOrder) -> Result<String> {
+        let typed = TypedOrder {
+            order,
+            chain_id: self.chain_id,
+        };
+        let signature = self.signer.sign_typed_data(&typed).await.map_err(BotError::from)?;
+        let body = json!({
+            "sellToken": order.sell_token,
+            "buyToken": order.buy_token,
+            "receiver": order.receiver,
+            "sellAmount": order.sell_amount.to_string(),
+            "buyAmount": order.buy_amount.to_string(),
+            "validTo": order.valid_to,
+            "appData": APP_DATA,
+            "appDataHash": format!("0x{}", hex::encode(keccak256(APP_DATA))),
+            "feeAmount": "0",
+            "kind": "sell",
+            "partiallyFillable": false,
+            "sellTokenBalance": "erc20",
+            "buyTokenBalance": "erc20",
+            "signingScheme": "eip712",
+            "signature": format!("0x{}", signature),
+            "from": self.owner(),
+        });
+
+        let response = self
+            .http
+            .post(format!("{}/api/v1/orders", self.api))
+            .json(&body)
+            .send()
+            .await
+            .map_err(|e| ProviderError::Connection(e.to_string()))?;
+        let status = response.status();
+        let result: Value = response.json().await.map_err(|e| ProviderError::Rpc(e.to_string()))?;
+
+        match (status.is_success(), result.as_str()) {
+            (true, Some(uid)) => Ok(uid.to_string()),
+            _ => Err(ExecutionError::OrderRejected(
+                result["description"].as_str().map(str::to_string).unwrap_or_else(|| result.to_string()),
+            )
+            .into()),
+        }
+    }
+    // Sign and send a cancellation of the open orders `uids` (0x-prefixed hex).
+    // An order already being settled in a batch can still fill.
+    pub async fn cancel(&self, uids: &[String]) -> Result<()> {
+        let raw = uids
+            .iter()
+            .map(|uid| hex::decode(uid.trim_start_matches("0x")))
+            .collect::<std::result::Result<Vec<_>, _>>()
+            .map_err(|e| ExecutionError::OrderRejected(format!("invalid order uid: {}", e)))?;
+        let typed = TypedCancellations {
+            uids: &raw,
+            chain_id: self.chain_id,
+        };
+        let signature = self.signer.sign_typed_data(&typed).await.map_err(BotError::from)?;
+        let body = json!({
+            "orderUids": uids,
+            "signature": format!("0x{}", signature),
+            "signingScheme": "eip712",
+        });
+
+        let response = self
+            .http
+            .delete(format!("{}/api/v1/orders", self.api))
+            .json(&body)
+            .send()
+            .await
+            .map_err(|e| ProviderError::Connection(e.to_string()))?;
+        if response.status().is_success() {
+            return Ok(());
+        }
+        let result: Value = response.json().await.unwrap_or_default();
+        Err(ExecutionError::OrderRejected(
+            result["description"].as_str().map(str::to_string).unwrap_or_else(|| result.to_string()),
+        )
+        .into())
+    }
+}
@@ -0,0 +1,242 @@
+use async_trait::async_trait;
+use ethers::{
+    abi::AbiEncode,
+    prelude::*,
+    types::{Address, Bytes, U256, U64},
+};
+use std::sync::Arc;
+
+use super::{fetch_token, DexHandler};
+use crate::{
+    errors::{DexError, Result},
+    math::to_f64,
+    models::{BalancerState, DexPool, DexType, PoolState},
+};
+
+abigen!(
+    BalancerVault,
+    r#"[
+        struct SingleSwap { bytes32 poolId; uint8 kind; address assetIn; address assetOut; uint256 amount; bytes userData; }
+        struct FundManagement { address sender; bool fromInternalBalance; address recipient; bool toInternalBalance; }
+        function getPoolTokens(bytes32 poolId) external view returns (address[], uint256[], uint256)
+        function swap(SingleSwap singleSwap, FundManagement funds, uint256 limit, uint256 deadline) external returns (uint256)
+    ]"#
+);
+
+abigen!(
+    WeightedPool,
+    r#"[
+        function getNormalizedWeights() external view returns (uint256[])
+        function getSwapFeePercentage() external view returns (uint256)
+    ]"#
+);
+
+pub const BALANCER_VAULT: &str = "0xBA12222222228d8Ba445958a75a0704d566BF2C8";
+
+// The Vault can't be searched by token pair, so pools are listed up front
+pub const DEFAULT_POOL_IDS: &[&str] = &[
+    // 50 WBTC / 50 WETH
+    "0xa6f548df93de924d73be7d25dc02554c6bd66db500020000000000000000000e",
+    // 50 USDC / 50 WETH
+    "0x96646936b91d6b9d7d0c47c496afbf3d6ec7b6f8000200000000000000000019",
+    // 80 BAL / 20 WETH
+    "0x5c6ee304399dbdb9c8ef030ab642b10820db8f56000200000000000000000014",
+];
+
+const ONE: u64 = 1_000_000_000_000_000_000;
+// SwapKind.GIVEN_IN
+const GIVEN_IN: u8 = 0;
+
+pub struct BalancerHandler<M> {
+    provider: Arc<M>,
+    vault: Address,
+    pool_ids: Vec<[u8; 32]>,
+}
+
+impl<M: Middleware + 'static> BalancerHandler<M> {
+    pub fn new(provider: Arc<M>, vault: Address, pool_ids: Vec<[u8; 32]>) -> Self {
+        Self { provider, vault, pool_ids }
+    }
+
+    pub fn mainnet(provider: Arc<M>) -> Self {
+        let pool_ids = DEFAULT_POOL_IDS
+            .iter()
+            .map(|id| id.parse::<H256>().unwrap().0)
+            .collect();
+        Self::new(provider, BALANCER_VAULT.parse().unwrap(), pool_ids)
+    }
+
+    pub fn with_vault(mut self, vault: Address) -> Self {
+        self.vault = vault;
+        self
+    }
+
+    fn vault(&self) -> BalancerVault<M> {
+        BalancerVault::new(self.vault, self.provider.clone())
+    }
+
+    // One DexPool per pair of `tokens` held by the pool
+    async fn load_pools(&self, pool_id: [u8; 32], tokens: &[Address]) -> Result<Vec<DexPool>> {
+        // The pool address is the first 20 bytes of its id
+        let address = Address::from_slice(&pool_id[..20]);
+        let (pool_tokens, balances, _) = self.vault().get_pool_tokens(pool_id).call().await?;
+
+        let pool = WeightedPool::new(address, self.provider.clone());
+        let weights = pool.get_normalized_weights().call().await?;
+        let swap_fee = pool.get_swap_fee_percentage().call().await?;
+
+        let mut pools = Vec::new();
+        for i in 0..pool_tokens.len() {
+            for j in i + 1..pool_tokens.len() {
+                if !tokens.contains(&pool_tokens[i]) || !tokens.contains(&pool_tokens[j]) {
+                    continue;
+                }
+                pools.push(DexPool {
+                    address,
+                    dex: DexType::Balancer,
+                    token0: fetch_token(self.provider.clone(), pool_tokens[i]).await?,
+                    token1: fetch_token(self.provider.clone(), pool_tokens[j]).await?,
+                    reserve0: balances[i],
+                    reserve1: balances[j],
+                    fee_bps: (swap_fee * 10_000 / ONE).as_u32(),
+                    block: U64::zero(),
+                    state: PoolState::Balancer(BalancerState {
+                        pool_id,
+                        weight0: weights[i],
+                        weight1: weights[j],
+                        swap_fee,
+                    }),
+                });
+            }
+        }
+        Ok(pools)
+    }
+}
+
+#[async_trait]
+impl<M: Middleware + 'static> DexHandler for BalancerHandler<M> {
+    fn dex_type(&self) -> DexType {
+        DexType::Balancer
+    }
+
+    async fn discover_pools(&self, tokens: &[Address]) -> Result<Vec<DexPool>> {
+        let mut pools = Vec::new();
+        for pool_id in &self.pool_ids {
+            pools.extend(self.load_pools(*pool_id, tokens).await?);
+        }
+        Ok(pools)
+    }
+
+    async fn refresh_state(&self, pool: &mut DexPool, block: U64) -> Result<()> {
+        let PoolState::Balancer(state) = &pool.state else {
+            return Err(DexError::Unsupported(format!("{:?} is not a Balancer pool", pool.address)).into());
+        };
+        let (tokens, balances, _) = self.vault().get_pool_tokens(state.pool_id).block(block).call().await?;
+
+        for (token, balance) in tokens.into_iter().zip(balances) {
+            if token == pool.token0.address {
+                pool.reserve0 = balance;
+            } else if token == pool.token1.address {
+                pool.reserve1 = balance;
+            }
+        }
+        Ok(())
+    }
+
+    async fn quote_out(&self, pool: &DexPool, token_in: Address, amount_in: U256) -> Result<U256> {
+        let PoolState::Balancer(state) = &pool.state else {
+            return Err(DexError::Unsupported(format!("{:?} is not a Balancer pool", pool.address)).into());
+        };
+        let (balance_in, balance_out) = pool
+            .reserves_for(token_in)
+            .ok_or(DexError::PoolNotFound(pool.address))?;
+        let (weight_in, weight_out) = if token_in == pool.token0.address {
+            (state.weight0, state.weight1)
+        } else {
+            (state.weight1, state.weight0)
+        };
+
+        Ok(calc_out_given_in(
+            balance_in,
+            weight_in,
+            balance_out,
+            weight_out,
+            amount_in,
+            state.swap_fee,
+        ))
+    }
+
+    fn swap_target(&self, _pool: &DexPool) -> Address {
+        self.vault
+    }
+
+    // Vault swap; the Vault pulls `amount_in` from the executor
+    fn build_swap_calldata(
+        &self,
+        pool: &DexPool,
+        token_in: Address,
+        amount_in: U256,
+        min_out: U256,
+        recipient: Address,
+    ) -> Result<Bytes> {
+        let PoolState::Balancer(state) = &pool.state else {
+            return Err(DexError::Unsupported(format!("{:?} is not a Balancer pool", pool.address)).into());
+        };
+        let token_out = pool
+            .other(token_in)
+            .ok_or(DexError::PoolNotFound(pool.address))?
+            .address;
+
+        Ok(SwapCall {
+            single_swap: SingleSwap {
+                pool_id: state.pool_id,
+                kind: GIVEN_IN,
+                asset_in: token_in,
+                asset_out: token_out,
+                amount: amount_in,
+                user_data: Bytes::new(),
+            },
+            funds: FundManagement {
+                sender: recipient,
+                from_internal_balance: false,
+                recipient,
+                to_internal_balance: false,
+            },
+            limit: min_out,
+            deadline: U256::MAX,
+        }
+        .encode()
+        .into())
+    }
+}
+
+// Weighted math: out = Bo * (1 - (Bi / (Bi + Ai)) ^ (Wi / Wo)), fee taken from the input.
+// The power runs in f64 via ln_1p/exp_m1, which stays accurate for small trades;
+// the result is rounded down so quotes never overstate the pool.
+pub fn calc_out_given_in(
+    balance_in: U256,
+    weight_in: U256,
+    balance_out: U256,
+    weight_out: U256,
+    amount_in: U256,
+    swap_fee: U256,
+) -> U256 {
+    if amount_in.is_zero() || balance_in.is_zero() || balance_out.is_zero() || weight_out.is_zero() {
+        return U256::zero();
+    }
+    let fee = swap_fee.min(U256::from(ONE));
+    let amount_in = amount_in - amount_in * fee / ONE;
+
+    let ratio = to_f64(amount_in) / to_f64(balance_in);
+    let exponent = to_f64(weight_in) / to_f64(weight_out);
+    // 1 - (1 + ratio)^-exponent
+    let fraction = -(-exponent * ratio.ln_1p()).exp_m1();
+    if !fraction.is_finite() || fraction <= 0.0 {
+        return U256::zero();
+    }
+
+    // Keep 15 significant digits and shave one part in 1e12 for f64 error
+    let scaled = U256::from((fraction * 1e15) as u64);
+    let out = balance_out.saturating_mul(scaled) / U256::exp10(15);
+    (out - out / U256::exp10(12)).min(balance_out)
+}
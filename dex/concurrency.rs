@@ -0,0 +1,86 @@
+use ethers::types::Address;
+use futures::{stream, Future, StreamExt};
+use std::sync::{
+    atomic::{AtomicUsize, Ordering},
+    OnceLock,
+};
+use tracing::debug;
+
+use crate::errors::{BotError, ProviderError, Result};
+
+// Pool reads in flight at once when nothing else is configured
+pub const DEFAULT_SCAN_CONCURRENCY: usize = 16;
+
+// How many pool reads a scan keeps in flight, shared by every handler. It
+// starts at the ceiling; a batch the node throttled halves it, and each clean
+// batch adds one back, up to the ceiling.
+pub struct ScanConcurrency {
+    limit: AtomicUsize,
+    ceiling: AtomicUsize,
+}
+
+impl ScanConcurrency {
+    pub fn global() -> &'static ScanConcurrency {
+        static LIMIT: OnceLock<ScanConcurrency> = OnceLock::new();
+        LIMIT.get_or_init(|| ScanConcurrency {
+            limit: AtomicUsize::new(DEFAULT_SCAN_CONCURRENCY),
+            ceiling: AtomicUsize::new(DEFAULT_SCAN_CONCURRENCY),
+        })
+    }
+
+    // Most reads ever in flight; 1 makes scans sequential
+    pub fn set_ceiling(&self, ceiling: usize) {
+        let ceiling = ceiling.max(1);
+        self.ceiling.store(ceiling, Ordering::Relaxed);
+        self.limit.store(ceiling, Ordering::Relaxed);
+    }
+
+    pub fn limit(&self) -> usize {
+        self.limit.load(Ordering::Relaxed).max(1)
+    }
+
+    fn record(&self, throttled: bool) {
+        let ceiling = self.ceiling.load(Ordering::Relaxed);
+        let current = self.limit();
+        let next = if throttled { (current / 2).max(1) } else { (current + 1).min(ceiling) };
+        if next != current {
+            self.limit.store(next, Ordering::Relaxed);
+            debug!(limit = next, throttled, "🚦 Scan concurrency adjusted");
+        }
+    }
+}
+
+fn throttled(err: &BotError) -> bool {
+    matches!(err, BotError::Provider(ProviderError::RateLimited(_) | ProviderError::Timeout))
+}
+
+// Run `read` over every item with up to the current limit in flight, results
+// in completion order. Whether the node throttled any of them sets the limit
+// for the next batch.
+pub async fn read_concurrently<I, T, F, Fut>(items: I, read: F) -> Vec<Result<T>>
+where
+    I: IntoIterator,
+    F: FnMut(I::Item) -> Fut,
+    Fut: Future<Output = Result<T>>,
+{
+    let limiter = ScanConcurrency::global();
+    let results: Vec<Result<T>> = stream::iter(items).map(read).buffer_unordered(limiter.limit()).collect().await;
+    limiter.record(results.iter().any(|r| r.as_ref().is_err_and(throttled)));
+    results
+}
+
+// Pools `lookup` finds for each of `pairs`, read concurrently. Any failure
+// fails the whole discovery, as one failed lookup did when pairs were read one
+// by one.
+pub async fn discover_pairs<P, T, F, Fut>(pairs: P, lookup: F) -> Result<Vec<T>>
+where
+    P: IntoIterator<Item = (Address, Address)>,
+    F: FnMut((Address, Address)) -> Fut,
+    Fut: Future<Output = Result<Vec<T>>>,
+{
+    let mut found = Vec::new();
+    for result in read_concurrently(pairs, lookup).await {
+        found.extend(result?);
+    }
+    Ok(found)
+}
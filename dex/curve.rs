@@ -0,0 +1,286 @@
+use async_trait::async_trait;
+use ethers::{
+    abi::AbiEncode,
+    prelude::*,
+    types::{Address, Bytes, I256, U256, U64},
+};
+use std::sync::Arc;
+
+use super::{concurrency::discover_pairs, fetch_token, token_pairs, DexHandler};
+use crate::{
+    errors::{DexError, Result},
+    models::{CurveState, DexPool, DexType, PoolState},
+};
+
+abigen!(
+    CurveRegistry,
+    r#"[
+        function find_pool_for_coins(address from, address to, uint256 i) external view returns (address)
+        function get_coin_indices(address pool, address from, address to) external view returns (int128, int128, bool)
+        function get_n_coins(address pool) external view returns (uint256[2])
+        function get_balances(address pool) external view returns (uint256[8])
+        function get_decimals(address pool) external view returns (uint256[8])
+    ]"#
+);
+
+abigen!(
+    CurvePool,
+    r#"[
+        function A() external view returns (uint256)
+        function fee() external view returns (uint256)
+        function exchange(int128 i, int128 j, uint256 dx, uint256 min_dy) external
+    ]"#
+);
+
+// Main Curve registry on mainnet
+pub const CURVE_REGISTRY: &str = "0x90E00ACe148ca3b23Ac1bC8C240C2a7Dd9c2d7f5";
+
+const FEE_DENOMINATOR: u64 = 10_000_000_000;
+// Pools may return several matches for the same pair; look at the first few
+const MAX_POOLS_PER_PAIR: u64 = 4;
+
+pub struct CurveHandler<M> {
+    provider: Arc<M>,
+    registry: Address,
+}
+
+impl<M: Middleware + 'static> CurveHandler<M> {
+    pub fn new(provider: Arc<M>, registry: Address) -> Self {
+        Self { provider, registry }
+    }
+
+    pub fn mainnet(provider: Arc<M>) -> Self {
+        Self::new(provider, CURVE_REGISTRY.parse().unwrap())
+    }
+
+    fn registry(&self) -> CurveRegistry<M> {
+        CurveRegistry::new(self.registry, self.provider.clone())
+    }
+
+    async fn load_pair(&self, address: Address, token_a: Address, token_b: Address) -> Result<Option<DexPool>> {
+        let registry = self.registry();
+        let (i, j, underlying) = registry.get_coin_indices(address, token_a, token_b).call().await?;
+        // Underlying (lending/meta) routes need exchange_underlying; not supported here
+        if underlying {
+            return Ok(None);
+        }
+
+        let [n_coins, _] = registry.get_n_coins(address).call().await?;
+        let n = n_coins.as_usize();
+        let balances = registry.get_balances(address).call().await?;
+        let decimals = registry.get_decimals(address).call().await?;
+
+        let pool = CurvePool::new(address, self.provider.clone());
+        let amplification = pool.a().call().await?;
+        let fee = pool.fee().call().await?;
+
+        let index0 = i.low_usize();
+        let index1 = j.low_usize();
+        let state = CurveState {
+            balances: balances[..n].to_vec(),
+            decimals: decimals[..n].iter().map(|d| d.as_u32() as u8).collect(),
+            amplification,
+            fee,
+            index0,
+            index1,
+        };
+
+        Ok(Some(DexPool {
+            address,
+            dex: DexType::Curve,
+            token0: fetch_token(self.provider.clone(), token_a).await?,
+            token1: fetch_token(self.provider.clone(), token_b).await?,
+            reserve0: state.balances[index0],
+            reserve1: state.balances[index1],
+            fee_bps: (fee * 10_000 / FEE_DENOMINATOR).as_u32(),
+            block: U64::zero(),
+            state: PoolState::Curve(state),
+        }))
+    }
+}
+
+#[async_trait]
+impl<M: Middleware + 'static> DexHandler for CurveHandler<M> {
+    fn dex_type(&self) -> DexType {
+        DexType::Curve
+    }
+
+    async fn discover_pools(&self, tokens: &[Address]) -> Result<Vec<DexPool>> {
+        let registry = &self.registry();
+
+        discover_pairs(token_pairs(tokens), |(a, b)| async move {
+            let mut pools = Vec::new();
+            for k in 0..MAX_POOLS_PER_PAIR {
+                let address = registry.find_pool_for_coins(a, b, k.into()).call().await?;
+                if address.is_zero() {
+                    break;
+                }
+                if let Some(pool) = self.load_pair(address, a, b).await? {
+                    pools.push(pool);
+                }
+            }
+            Ok(pools)
+        })
+        .await
+    }
+
+    async fn refresh_state(&self, pool: &mut DexPool, block: U64) -> Result<()> {
+        let balances = self.registry().get_balances(pool.address).block(block).call().await?;
+        // A ramps over time, so it has to be re-read too
+        let amplification = CurvePool::new(pool.address, self.provider.clone()).a().block(block).call().await?;
+
+        let PoolState::Curve(state) = &mut pool.state else {
+            return Err(DexError::Unsupported(format!("{:?} is not a Curve pool", pool.address)).into());
+        };
+        let n = state.balances.len();
+        state.balances = balances[..n].to_vec();
+        state.amplification = amplification;
+        pool.reserve0 = state.balances[state.index0];
+        pool.reserve1 = state.balances[state.index1];
+        Ok(())
+    }
+
+    async fn quote_out(&self, pool: &DexPool, token_in: Address, amount_in: U256) -> Result<U256> {
+        let PoolState::Curve(state) = &pool.state else {
+            return Err(DexError::Unsupported(format!("{:?} is not a Curve pool", pool.address)).into());
+        };
+        let (i, j) = if token_in == pool.token0.address {
+            (state.index0, state.index1)
+        } else if token_in == pool.token1.address {
+            (state.index1, state.index0)
+        } else {
+            return Err(DexError::PoolNotFound(pool.address).into());
+        };
+
+        get_dy(state, i, j, amount_in).ok_or_else(|| DexError::Overflow("curve get_dy").into())
+    }
+
+    fn build_swap_calldata(
+        &self,
+        pool: &DexPool,
+        token_in: Address,
+        amount_in: U256,
+        min_out: U256,
+        _recipient: Address,
+    ) -> Result<Bytes> {
+        let PoolState::Curve(state) = &pool.state else {
+            return Err(DexError::Unsupported(format!("{:?} is not a Curve pool", pool.address)).into());
+        };
+        let (i, j) = if token_in == pool.token0.address {
+            (state.index0, state.index1)
+        } else {
+            (state.index1, state.index0)
+        };
+
+        // Curve sends output to msg.sender; the executor forwards it
+        Ok(ExchangeCall {
+            i: I256::from(i as i64),
+            j: I256::from(j as i64),
+            dx: amount_in,
+            min_dy: min_out,
+        }
+        .encode()
+        .into())
+    }
+}
+
+// Output of swapping `dx` of coin `i` for coin `j`, mirroring the pool's get_dy
+pub fn get_dy(state: &CurveState, i: usize, j: usize, dx: U256) -> Option<U256> {
+    let rates: Vec<U256> = state
+        .decimals
+        .iter()
+        .map(|d| U256::exp10(18usize.saturating_sub(*d as usize)))
+        .collect();
+    let xp: Vec<U256> = state
+        .balances
+        .iter()
+        .zip(&rates)
+        .map(|(b, r)| b.checked_mul(*r))
+        .collect::<Option<_>>()?;
+    if i == j || i >= xp.len() || j >= xp.len() {
+        return None;
+    }
+    // A drained or unseeded pool has no invariant to price against
+    if state.amplification.is_zero() || xp.iter().any(|x| x.is_zero()) {
+        return None;
+    }
+
+    let x = xp[i].checked_add(dx.checked_mul(rates[i])?)?;
+    let y = get_y(i, j, x, &xp, state.amplification)?;
+    let dy = xp[j].checked_sub(y)?.checked_sub(U256::one())?;
+    let fee = dy.checked_mul(state.fee)? / FEE_DENOMINATOR;
+
+    dy.checked_sub(fee)?.checked_div(rates[j])
+}
+
+// Stableswap invariant D for normalised balances
+fn get_d(xp: &[U256], amp: U256) -> Option<U256> {
+    let n = U256::from(xp.len());
+    let s = xp.iter().try_fold(U256::zero(), |acc, x| acc.checked_add(*x))?;
+    if s.is_zero() {
+        return Some(U256::zero());
+    }
+
+    let ann = amp.checked_mul(n)?;
+    let mut d = s;
+    for _ in 0..255 {
+        let mut d_p = d;
+        for x in xp {
+            d_p = d_p.checked_mul(d)?.checked_div(x.checked_mul(n)?)?;
+        }
+        let d_prev = d;
+        let numerator = ann.checked_mul(s)?.checked_add(d_p.checked_mul(n)?)?.checked_mul(d)?;
+        let denominator = ann.checked_sub(U256::one())?.checked_mul(d)?.checked_add((n + 1).checked_mul(d_p)?)?;
+        d = numerator.checked_div(denominator)?;
+
+        if abs_diff(d, d_prev) <= U256::one() {
+            return Some(d);
+        }
+    }
+    None
+}
+
+// New balance of coin `j` after coin `i` is set to `x`, keeping D constant;
+// `get_dy` has checked the indices
+fn get_y(i: usize, j: usize, x: U256, xp: &[U256], amp: U256) -> Option<U256> {
+    let n = U256::from(xp.len());
+    let d = get_d(xp, amp)?;
+    let ann = amp.checked_mul(n)?;
+
+    let mut c = d;
+    let mut s = U256::zero();
+    for (k, balance) in xp.iter().enumerate() {
+        let x_k = if k == i {
+            x
+        } else if k != j {
+            *balance
+        } else {
+            continue;
+        };
+        s = s.checked_add(x_k)?;
+        c = c.checked_mul(d)?.checked_div(x_k.checked_mul(n)?)?;
+    }
+    c = c.checked_mul(d)?.checked_div(ann.checked_mul(n)?)?;
+    let b = s.checked_add(d.checked_div(ann)?)?;
+
+    let mut y = d;
+    for _ in 0..255 {
+        let y_prev = y;
+        let numerator = y.checked_mul(y)?.checked_add(c)?;
+        let denominator = y.checked_mul(U256::from(2))?.checked_add(b)?.checked_sub(d)?;
+        y = numerator.checked_div(denominator)?;
+
+        if abs_diff(y, y_prev) <= U256::one() {
+            return Some(y);
+        }
+    }
+    None
+}
+
+fn abs_diff(a: U256, b: U256) -> U256 {
+    if a > b {
+        a - b
+    } else {
+        b - a
+    }
+}
@@ -0,0 +1,329 @@
+use async_trait::async_trait;
+use ethers::{
+    abi::AbiEncode,
+    prelude::*,
+    types::{Address, Bytes, U256, U64},
+};
+use std::{collections::BTreeMap, sync::Arc};
+
+use super::{concurrency::discover_pairs, fetch_token, prefetch_tokens, token_pairs, uniswap_v3::sqrt_ratio_at_tick, DexHandler};
+use crate::{
+    errors::{DexError, Result},
+    math::{self, mul_div, mul_div_up, q96},
+    models::{DexPool, DexType, MaverickState, PoolState},
+};
+
+abigen!(
+    MaverickV2Factory,
+    r#"[
+        function lookup(address tokenA, address tokenB, uint256 startIndex, uint256 endIndex) external view returns (address[])
+    ]"#
+);
+
+abigen!(
+    MaverickV2Pool,
+    r#"[
+        function tokenA() external view returns (address)
+        function tokenB() external view returns (address)
+        function tickSpacing() external view returns (uint256)
+        function fee(bool tokenAIn) external view returns (uint256)
+        function getState() external view returns ((uint128,uint128,int64,int64,uint40,int32,bool,uint32,uint8))
+        function getTick(int32 tick) external view returns ((uint128,uint128,uint128,uint32[4]))
+        function swap(address recipient, (uint256,bool,bool,int32) params, bytes data) external returns (uint256, uint256)
+    ]"#
+);
+
+abigen!(
+    MaverickV2Quoter,
+    r#"[
+        function calculateSwap(address pool, uint128 amount, bool tokenAIn, bool exactOutput, int32 tickLimit) external returns (uint256, uint256, uint256)
+    ]"#
+);
+
+// Maverick V2, at the same addresses on Ethereum, Base, Arbitrum and BNB Chain
+pub const MAVERICK_V2_FACTORY: &str = "0x0A7e848Aca42d879EF06507Fca0E7b33A0a63c1e";
+pub const MAVERICK_V2_QUOTER: &str = "0xb40AfdB85a07f37aE217E7D6462e609900dD8D7A";
+
+// Pools looked up per pair; they differ by fee, tick spacing and bin kinds
+const MAX_POOLS_PER_PAIR: u64 = 8;
+// Ticks read on each side of the active one
+const TICKS_PER_SIDE: i32 = 16;
+
+// Maverick V2: liquidity sits in ticks, each a constant-liquidity price range
+// like a Uniswap V3 position, and bins move it between ticks as the price
+// moves. A quote only needs the tick reserves at the time of the swap, since
+// bins move once it's done. Pools keep reserves in 18-decimal units.
+pub struct MaverickHandler<M> {
+    provider: Arc<M>,
+    factory: Address,
+    quoter: Option<Address>,
+}
+
+impl<M: Middleware + 'static> MaverickHandler<M> {
+    pub fn new(provider: Arc<M>, factory: Address) -> Self {
+        Self {
+            provider,
+            factory,
+            quoter: None,
+        }
+    }
+
+    pub fn v2(provider: Arc<M>) -> Self {
+        Self::new(provider, MAVERICK_V2_FACTORY.parse().unwrap()).with_quoter(MAVERICK_V2_QUOTER.parse().unwrap())
+    }
+
+    pub fn with_quoter(mut self, quoter: Address) -> Self {
+        self.quoter = Some(quoter);
+        self
+    }
+
+    async fn load_maverick_pool(&self, address: Address) -> Result<DexPool> {
+        let pool = MaverickV2Pool::new(address, self.provider.clone());
+        let token_a = pool.token_a().call().await?;
+        let token_b = pool.token_b().call().await?;
+        let tick_spacing = pool.tick_spacing().call().await?.as_u32();
+        let fee_a_in = pool.fee(true).call().await?;
+        let fee_b_in = pool.fee(false).call().await?;
+
+        let mut dex_pool = DexPool {
+            address,
+            dex: DexType::MaverickV2,
+            token0: fetch_token(self.provider.clone(), token_a).await?,
+            token1: fetch_token(self.provider.clone(), token_b).await?,
+            reserve0: U256::zero(),
+            reserve1: U256::zero(),
+            fee_bps: math::bps(fee_a_in.max(fee_b_in), U256::exp10(18)),
+            block: U64::zero(),
+            state: PoolState::Maverick(MaverickState {
+                active_tick: 0,
+                tick_spacing,
+                fee_a_in,
+                fee_b_in,
+                ticks: BTreeMap::new(),
+            }),
+        };
+        self.load_state(&mut dex_pool, BlockNumber::Latest.into()).await?;
+        Ok(dex_pool)
+    }
+
+    // Active tick and the reserves of the ticks around it, as of `block`
+    async fn load_state(&self, dex_pool: &mut DexPool, block: BlockId) -> Result<()> {
+        let pool = MaverickV2Pool::new(dex_pool.address, self.provider.clone());
+        let (reserve_a, reserve_b, _, _, _, active_tick, ..) = pool.get_state().block(block).call().await?;
+
+        let mut ticks = BTreeMap::new();
+        for tick in active_tick - TICKS_PER_SIDE..=active_tick + TICKS_PER_SIDE {
+            let (a, b, ..) = pool.get_tick(tick).block(block).call().await?;
+            if a > 0 || b > 0 {
+                ticks.insert(tick, (U256::from(a), U256::from(b)));
+            }
+        }
+
+        let PoolState::Maverick(state) = &mut dex_pool.state else {
+            return Err(DexError::Unsupported(format!("{:?} is not a Maverick pool", dex_pool.address)).into());
+        };
+        state.active_tick = active_tick;
+        state.ticks = ticks;
+        dex_pool.reserve0 = from_d18(U256::from(reserve_a), dex_pool.token0.decimals);
+        dex_pool.reserve1 = from_d18(U256::from(reserve_b), dex_pool.token1.decimals);
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl<M: Middleware + 'static> DexHandler for MaverickHandler<M> {
+    fn dex_type(&self) -> DexType {
+        DexType::MaverickV2
+    }
+
+    async fn discover_pools(&self, tokens: &[Address]) -> Result<Vec<DexPool>> {
+        let factory = &MaverickV2Factory::new(self.factory, self.provider.clone());
+
+        prefetch_tokens(self.provider.clone(), tokens).await;
+
+        discover_pairs(token_pairs(tokens), |(a, b)| async move {
+            // The factory indexes pairs by sorted tokens
+            let (token_a, token_b) = if a < b { (a, b) } else { (b, a) };
+            let found = factory
+                .lookup(token_a, token_b, U256::zero(), U256::from(MAX_POOLS_PER_PAIR))
+                .call()
+                .await?;
+            let mut pools = Vec::new();
+            for address in found.into_iter().filter(|p| !p.is_zero()) {
+                pools.push(self.load_maverick_pool(address).await?);
+            }
+            Ok(pools)
+        })
+        .await
+    }
+
+    async fn load_pool(&self, address: Address) -> Result<DexPool> {
+        self.load_maverick_pool(address).await
+    }
+
+    async fn refresh_state(&self, pool: &mut DexPool, block: U64) -> Result<()> {
+        self.load_state(pool, block.into()).await
+    }
+
+    async fn quote_out(&self, pool: &DexPool, token_in: Address, amount_in: U256) -> Result<U256> {
+        let PoolState::Maverick(state) = &pool.state else {
+            return Err(DexError::Unsupported(format!("{:?} is not a Maverick pool", pool.address)).into());
+        };
+        let (token_a_in, decimals_in, decimals_out) = if token_in == pool.token0.address {
+            (true, pool.token0.decimals, pool.token1.decimals)
+        } else if token_in == pool.token1.address {
+            (false, pool.token1.decimals, pool.token0.decimals)
+        } else {
+            return Err(DexError::PoolNotFound(pool.address).into());
+        };
+
+        swap_exact_in(state, token_a_in, to_d18(amount_in, decimals_in))
+            .map(|out| from_d18(out, decimals_out))
+            .ok_or_else(|| DexError::InsufficientLiquidity(pool.address).into())
+    }
+
+    // The quoter simulates the swap, bin movement included
+    async fn quote_onchain(&self, pool: &DexPool, token_in: Address, amount_in: U256) -> Result<Option<U256>> {
+        let Some(quoter) = self.quoter else {
+            return Ok(None);
+        };
+        let amount: u128 = amount_in
+            .try_into()
+            .map_err(|_| DexError::InsufficientLiquidity(pool.address))?;
+        let token_a_in = token_in == pool.token0.address;
+        let (_, amount_out, _) = MaverickV2Quoter::new(quoter, self.provider.clone())
+            .calculate_swap(pool.address, amount, token_a_in, false, tick_limit(token_a_in))
+            .call()
+            .await?;
+        Ok(Some(amount_out))
+    }
+
+    // Pool swap; the executor pays in maverickV2SwapCallback and checks `min_out` itself
+    fn build_swap_calldata(
+        &self,
+        pool: &DexPool,
+        token_in: Address,
+        amount_in: U256,
+        _min_out: U256,
+        recipient: Address,
+    ) -> Result<Bytes> {
+        if !pool.has_token(token_in) {
+            return Err(DexError::PoolNotFound(pool.address).into());
+        }
+        let token_a_in = token_in == pool.token0.address;
+
+        Ok(SwapCall {
+            recipient,
+            params: (amount_in, token_a_in, false, tick_limit(token_a_in)),
+            data: Bytes::new(),
+        }
+        .encode()
+        .into())
+    }
+}
+
+// Furthest tick a swap may reach; selling A moves the price down
+fn tick_limit(token_a_in: bool) -> i32 {
+    if token_a_in {
+        i32::MIN
+    } else {
+        i32::MAX
+    }
+}
+
+fn to_d18(amount: U256, decimals: u8) -> U256 {
+    if decimals <= 18 {
+        amount.saturating_mul(U256::exp10(18 - decimals as usize))
+    } else {
+        amount / U256::exp10(decimals as usize - 18)
+    }
+}
+
+fn from_d18(amount: U256, decimals: u8) -> U256 {
+    if decimals <= 18 {
+        amount / U256::exp10(18 - decimals as usize)
+    } else {
+        amount.saturating_mul(U256::exp10(decimals as usize - 18))
+    }
+}
+
+// Square-root prices (Q96) bounding `tick`
+fn tick_bounds(tick: i32, tick_spacing: u32) -> (U256, U256) {
+    let spacing = tick_spacing as i32;
+    (sqrt_ratio_at_tick(tick * spacing), sqrt_ratio_at_tick((tick + 1) * spacing))
+}
+
+// Liquidity of a tick holding `reserve_a` and `reserve_b` between the square-root
+// prices `lower` and `upper`: the positive root of
+// (a + L/upper)(b + L*lower) = L^2, as Maverick's getTickL
+fn tick_liquidity(reserve_a: U256, reserve_b: U256, lower: U256, upper: U256) -> Option<U256> {
+    let q96 = q96();
+    // L^2 * (1 - lower/upper) - L * (a*lower + b/upper) - a*b = 0, coefficients in Q96
+    let quadratic = q96.checked_sub(mul_div(lower, q96, upper)?)?;
+    let linear = mul_div(reserve_a, lower, q96)?.checked_add(mul_div(reserve_b, q96, upper)?)?;
+    let constant = reserve_a.checked_mul(reserve_b)?;
+    let discriminant = linear
+        .checked_mul(linear)?
+        .checked_add(mul_div(quadratic.checked_mul(U256::from(4))?, constant, q96)?)?;
+    mul_div(linear.checked_add(discriminant.integer_sqrt())?, q96, quadratic.checked_mul(U256::from(2))?)
+}
+
+// Price of A in B at the active tick, in raw units as Q128
+pub fn active_price_x128(state: &MaverickState, decimals_a: u8, decimals_b: u8) -> Option<U256> {
+    let &(reserve_a, reserve_b) = state.ticks.get(&state.active_tick)?;
+    let (lower, upper) = tick_bounds(state.active_tick, state.tick_spacing);
+    let liquidity = tick_liquidity(reserve_a, reserve_b, lower, upper)?;
+    let x = reserve_a.checked_add(mul_div(liquidity, q96(), upper)?)?;
+    let y = reserve_b.checked_add(mul_div(liquidity, lower, q96())?)?;
+    mul_div(math::ratio_x128(y, x)?, math::decimals_x128(decimals_a, decimals_b), math::q128())
+}
+
+// Output, in 18-decimal units, for `amount_in` walking the loaded ticks away
+// from the active one with the fee taken from each tick's input. None when it
+// would run past them.
+pub fn swap_exact_in(state: &MaverickState, token_a_in: bool, amount_in: U256) -> Option<U256> {
+    let precision = U256::exp10(18);
+    let fee = if token_a_in { state.fee_a_in } else { state.fee_b_in };
+    let ticks: Vec<i32> = if token_a_in {
+        state.ticks.range(..=state.active_tick).rev().map(|(t, _)| *t).collect()
+    } else {
+        state.ticks.range(state.active_tick..).map(|(t, _)| *t).collect()
+    };
+
+    let mut remaining = amount_in;
+    let mut amount_out = U256::zero();
+    for tick in ticks {
+        let (reserve_a, reserve_b) = state.ticks[&tick];
+        let reserve_out = if token_a_in { reserve_b } else { reserve_a };
+        if reserve_out.is_zero() {
+            continue;
+        }
+        let (lower, upper) = tick_bounds(tick, state.tick_spacing);
+        let liquidity = tick_liquidity(reserve_a, reserve_b, lower, upper)?;
+        // Virtual reserves, whose product is L^2 across the tick
+        let x = reserve_a.checked_add(mul_div(liquidity, q96(), upper)?)?;
+        let y = reserve_b.checked_add(mul_div(liquidity, lower, q96())?)?;
+        let (virtual_in, virtual_out) = if token_a_in { (x, y) } else { (y, x) };
+
+        // Net input that takes the price to the tick's edge and empties it
+        let edge = if token_a_in {
+            mul_div(liquidity, q96(), lower)?
+        } else {
+            mul_div(liquidity, upper, q96())?
+        };
+        let max_net = edge.saturating_sub(virtual_in);
+        let max_in = mul_div_up(max_net, precision, precision.checked_sub(fee)?)?;
+
+        if remaining < max_in {
+            let net = mul_div(remaining, precision - fee, precision)?;
+            let out = virtual_out.saturating_sub(mul_div_up(liquidity, liquidity, virtual_in.checked_add(net)?)?);
+            return amount_out.checked_add(out.min(reserve_out));
+        }
+        remaining -= max_in;
+        amount_out = amount_out.checked_add(reserve_out)?;
+        if remaining.is_zero() {
+            return Some(amount_out);
+        }
+    }
+    None
+}
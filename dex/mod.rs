@@ -0,0 +1,534 @@
+use arc_swap::ArcSwap;
+use async_trait::async_trait;
+use ethers::{
+    prelude::*,
+    types::{Address, Bytes, Filter, Log, I256, U256, U512, U64},
+};
+use futures::future::join_all;
+use std::{collections::HashMap, sync::Arc};
+use tracing::{info, instrument, warn};
+
+use self::concurrency::read_concurrently;
+use crate::{
+    errors::{DexError, Result},
+    math,
+    models::{ArbitrageOpportunity, DexPool, DexType, FlashLoanProvider, PoolState, Token},
+    pathfinder::Hop,
+};
+
+pub mod balancer;
+pub mod concurrency;
+pub mod curve;
+pub mod maverick;
+pub mod solidly;
+pub mod tokens;
+pub mod trader_joe;
+pub mod uniswap_v2;
+pub mod uniswap_v3;
+pub mod uniswap_v4;
+
+pub use balancer::BalancerHandler;
+pub use concurrency::ScanConcurrency;
+pub use curve::CurveHandler;
+pub use maverick::MaverickHandler;
+pub use solidly::SolidlyHandler;
+pub use tokens::{TokenCache, TokenFilter};
+pub use trader_joe::TraderJoeHandler;
+pub use uniswap_v2::{GenericV2Handler, UniswapV2Handler, V2Fork};
+pub use uniswap_v3::UniswapV3Handler;
+pub use uniswap_v4::{HookPolicy, UniswapV4Handler};
+
+abigen!(
+    Erc20Metadata,
+    r#"[
+        function symbol() external view returns (string)
+        function decimals() external view returns (uint8)
+    ]"#
+);
+
+// Gas for a flash-loaned two-leg arbitrage through the executor contract
+const ARBITRAGE_GAS: u64 = 250_000;
+// Signed size of an arbitrage transaction, what a rollup's L1 data fee is charged on
+const ARBITRAGE_TX_BYTES: u64 = 600;
+// Each round shrinks the search interval to two thirds
+const OPTIMAL_SEARCH_ROUNDS: usize = 96;
+
+// Everything the scanner needs from a venue. Built-in venues implement this,
+// and library users can register their own with `DexManager::register_handler`.
+// A new built-in venue is one module here plus its entry in
+// `ChainProfile::register_venues`; nothing else dispatches on the concrete handler.
+#[async_trait]
+pub trait DexHandler: Send + Sync {
+    fn dex_type(&self) -> DexType;
+
+    // Pools on this venue trading any two of `tokens`
+    async fn discover_pools(&self, tokens: &[Address]) -> Result<Vec<DexPool>>;
+
+    // Factory event announcing new pools on this venue, if it has one
+    fn pool_created_filter(&self) -> Option<Filter> {
+        None
+    }
+
+    // Block the factory was deployed at, where a backfill of its events starts
+    fn factory_deployed_at(&self) -> U64 {
+        U64::zero()
+    }
+
+    // Pool address carried by a log matching `pool_created_filter`
+    fn decode_pool_created(&self, _log: &Log) -> Option<Address> {
+        None
+    }
+
+    // Full pool description for a known address
+    async fn load_pool(&self, address: Address) -> Result<DexPool> {
+        Err(DexError::Unsupported(format!("{} can't load {:?} by address", self.dex_type(), address)).into())
+    }
+
+    // Re-read on-chain state (reserves, prices) into `pool`, every read made
+    // at `block` so pools refreshed together describe the same chain state
+    async fn refresh_state(&self, pool: &mut DexPool, block: U64) -> Result<()>;
+
+    // Amount of the other token received for `amount_in` of `token_in`
+    async fn quote_out(&self, pool: &DexPool, token_in: Address, amount_in: U256) -> Result<U256>;
+
+    // Authoritative quote from an on-chain quoter, used to confirm opportunities
+    // before they are reported. Venues whose local math is exact need not override.
+    async fn quote_onchain(&self, _pool: &DexPool, _token_in: Address, _amount_in: U256) -> Result<Option<U256>> {
+        Ok(None)
+    }
+
+    // Share of a transfer out of `pool` that `token` withholds, in bps (BPS when
+    // the transfer reverts). None where the venue can't probe it.
+    async fn transfer_tax(&self, _pool: &DexPool, _token: Address) -> Result<Option<u32>> {
+        Ok(None)
+    }
+
+    // Contract the swap calldata is sent to
+    fn swap_target(&self, pool: &DexPool) -> Address {
+        pool.address
+    }
+
+    // Calldata for the executor to perform this leg, sending output to `recipient`
+    fn build_swap_calldata(
+        &self,
+        pool: &DexPool,
+        token_in: Address,
+        amount_in: U256,
+        min_out: U256,
+        recipient: Address,
+    ) -> Result<Bytes>;
+}
+
+// Every unordered pair of `tokens`, the candidates `discover_pools` looks up
+pub fn token_pairs(tokens: &[Address]) -> impl Iterator<Item = (Address, Address)> + '_ {
+    tokens
+        .iter()
+        .enumerate()
+        .flat_map(move |(i, a)| tokens[i + 1..].iter().map(move |b| (*a, *b)))
+}
+
+// Read metadata for all of `tokens` in one multicall before discovery, instead
+// of two calls per pool side. A failure only means slower, per-token reads.
+pub(crate) async fn prefetch_tokens<M: Middleware + 'static>(provider: Arc<M>, tokens: &[Address]) {
+    if let Err(e) = TokenCache::global().prefetch(provider, tokens).await {
+        warn!(error = %e, "⚠️ Token metadata prefetch failed");
+    }
+}
+
+// ERC20 metadata shared by every handler, read from chain on first use
+pub(crate) async fn fetch_token<M: Middleware + 'static>(provider: Arc<M>, address: Address) -> Result<Token> {
+    if let Some(token) = TokenCache::global().get(&address) {
+        return Ok(token);
+    }
+    let erc20 = Erc20Metadata::new(address, provider);
+    let decimals = erc20.decimals().call().await?;
+    // Some tokens (MKR) return bytes32 symbols; don't fail the pool over it
+    let symbol = erc20.symbol().call().await.unwrap_or_else(|_| format!("{:?}", address));
+    let token = Token {
+        address,
+        symbol,
+        decimals,
+        transfer_tax_bps: TokenCache::global().transfer_tax(&address).unwrap_or(0),
+    };
+    TokenCache::global().insert(token.clone());
+    Ok(token)
+}
+
+#[derive(Default)]
+pub struct DexManager {
+    handlers: Vec<Arc<dyn DexHandler>>,
+    // Swappable so allow/deny lists can be reloaded while scanning
+    filter: ArcSwap<TokenFilter>,
+    // Sources of arbitrage capital in order of preference; opportunities are
+    // priced with the first that can fund their buy pool, Aave otherwise
+    flash_loans: Vec<FlashLoanProvider>,
+}
+
+impl DexManager {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn register_handler(&mut self, handler: Arc<dyn DexHandler>) {
+        // Re-registering a venue replaces the previous handler
+        let dex = handler.dex_type();
+        self.handlers.retain(|h| h.dex_type() != dex);
+        self.handlers.push(handler);
+    }
+
+    pub fn set_token_filter(&self, filter: TokenFilter) {
+        self.filter.store(Arc::new(filter));
+    }
+
+    pub fn set_flash_loan_providers(&mut self, providers: Vec<FlashLoanProvider>) {
+        self.flash_loans = providers;
+    }
+
+    // Cheapest V3 pool holding `token` outside the trade, when V3 flashes are
+    // enabled; a pool can't be swapped through while it's lending
+    fn v3_lender<'a>(&self, pools: &'a [DexPool], token: Address, trade: [&DexPool; 2]) -> Option<&'a DexPool> {
+        if !self.flash_loans.contains(&FlashLoanProvider::UniswapV3Flash) {
+            return None;
+        }
+        pools
+            .iter()
+            .filter(|p| p.dex == DexType::UniswapV3 && p.other(token).is_some())
+            .filter(|p| trade.iter().all(|t| t.address != p.address))
+            .min_by_key(|p| p.fee_bps)
+    }
+
+    pub fn handler_for(&self, dex: &DexType) -> Result<&Arc<dyn DexHandler>> {
+        self.handlers
+            .iter()
+            .find(|h| &h.dex_type() == dex)
+            .ok_or_else(|| DexError::Unsupported(dex.to_string()).into())
+    }
+
+    pub fn handlers(&self) -> &[Arc<dyn DexHandler>] {
+        &self.handlers
+    }
+
+    // Pools for `tokens` across every registered venue, searched side by side;
+    // a failing venue is skipped
+    // Tokens outside the filter are neither discovered nor returned
+    #[instrument(name = "pool_fetch", skip_all, fields(tokens = tokens.len()))]
+    pub async fn get_all_pools(&self, tokens: &[Address]) -> Vec<DexPool> {
+        let filter = self.filter.load_full();
+        let tokens: Vec<Address> = tokens.iter().filter(|t| filter.permits(t)).copied().collect();
+        let tokens = &tokens;
+        let found = join_all(self.handlers.iter().map(|handler| async move {
+            (handler.dex_type(), handler.discover_pools(tokens).await)
+        }))
+        .await;
+
+        let mut pools = Vec::new();
+        for (dex, result) in found {
+            match result {
+                Ok(found) => pools.extend(found.into_iter().filter(|p| filter.permits_pool(p))),
+                Err(e) => warn!(%dex, error = %e, "⚠️ Pool discovery failed"),
+            }
+        }
+        pools
+    }
+
+    // Pools at known addresses, read concurrently; ones on venues without a
+    // handler or that fail to load are skipped
+    pub async fn load_pools(&self, addresses: &[(DexType, Address)]) -> Vec<DexPool> {
+        let filter = self.filter.load_full();
+        let loaded = read_concurrently(addresses.iter(), |(dex, address)| async move {
+            self.handler_for(dex)?.load_pool(*address).await
+        })
+        .await;
+
+        let mut pools = Vec::new();
+        for result in loaded {
+            match result {
+                Ok(pool) if filter.permits_pool(&pool) => pools.push(pool),
+                Ok(_) => {}
+                Err(e) => warn!(error = %e, "⚠️ Pool load failed"),
+            }
+        }
+        pools
+    }
+
+    pub fn permits(&self, pool: &DexPool) -> bool {
+        self.filter.load().permits_pool(pool)
+    }
+
+    pub fn token_filter(&self) -> Arc<TokenFilter> {
+        self.filter.load_full()
+    }
+
+    // Re-read `pool` as of `block` and record that block on it
+    pub async fn refresh_pool(&self, pool: &mut DexPool, block: U64) -> Result<()> {
+        self.handler_for(&pool.dex)?.refresh_state(pool, block).await?;
+        pool.block = block;
+        Ok(())
+    }
+
+    pub async fn refresh_pools(&self, pools: &mut [DexPool], block: U64) -> Result<()> {
+        let results = read_concurrently(pools.iter_mut(), |pool| self.refresh_pool(pool, block)).await;
+        results.into_iter().collect()
+    }
+
+    pub async fn quote_out(&self, pool: &DexPool, token_in: Address, amount_in: U256) -> Result<U256> {
+        self.handler_for(&pool.dex)?.quote_out(pool, token_in, amount_in).await
+    }
+
+    // Output of running `amount_in` through every hop in order
+    pub async fn quote_path(&self, hops: &[Hop], amount_in: U256) -> Result<U256> {
+        let mut amount = amount_in;
+        for hop in hops {
+            amount = self.quote_out(&hop.pool, hop.token_in, amount).await?;
+        }
+        Ok(amount)
+    }
+
+    // Constant-product output with the fee taken from the input. Intermediates
+    // are U512, so huge reserves of high-decimal tokens can't overflow; an input
+    // too large even for that quotes zero rather than panicking.
+    pub fn calculate_output_amount(
+        amount_in: U256,
+        reserve_in: U256,
+        reserve_out: U256,
+        fee_bps: u32,
+    ) -> U256 {
+        if amount_in.is_zero() || reserve_in.is_zero() || reserve_out.is_zero() {
+            return U256::zero();
+        }
+        let amount_in_with_fee = amount_in.full_mul(U256::from(math::BPS - fee_bps.min(math::BPS)));
+        let Some(numerator) = amount_in_with_fee.checked_mul(U512::from(reserve_out)) else {
+            return U256::zero();
+        };
+        let denominator = reserve_in.full_mul(U256::from(math::BPS)) + amount_in_with_fee;
+        // Always below reserve_out, so it fits
+        U256::try_from(numerator / denominator).unwrap_or_default()
+    }
+
+    // Profit-maximising input for two constant-product pools. Composing the legs
+    // gives out(x) = Kx / (C + Dx), so the optimum is x = (sqrt(KC) - C) / D with
+    // K = g1*g2*b1*a2, C = a1*b2, D = g1*(b2 + g2*b1) (g = 1 - fee, a/b = reserves).
+    // Zero when the pools aren't mispriced.
+    pub fn calculate_optimal_borrow(buy_pool: &DexPool, sell_pool: &DexPool, token_in: Address) -> U256 {
+        let (Some((a1, b1)), Some(token_out)) = (buy_pool.reserves_for(token_in), buy_pool.other(token_in)) else {
+            return U256::zero();
+        };
+        let Some((b2, a2)) = sell_pool.reserves_for(token_out.address) else {
+            return U256::zero();
+        };
+        if a1.is_zero() || b1.is_zero() || a2.is_zero() || b2.is_zero() {
+            return U256::zero();
+        }
+
+        // Fees stay in basis points, so everything is scaled by 1e4 per factor
+        let bps = U512::from(10_000u64);
+        let g1 = U512::from(10_000 - buy_pool.fee_bps.min(10_000));
+        let g2 = U512::from(10_000 - sell_pool.fee_bps.min(10_000));
+        let c = a1.full_mul(b2);
+        let root = (g1 * g2 * c * b1.full_mul(a2)).integer_sqrt();
+        if root <= c * bps {
+            return U256::zero();
+        }
+        let numerator = (root - c * bps) * bps;
+        let denominator = g1 * (U512::from(b2) * bps + g2 * U512::from(b1));
+
+        U256::try_from(numerator / denominator).unwrap_or(a1)
+    }
+
+    // Input size for any pair of pools: the closed form when both are constant
+    // product, otherwise a ternary search over the (concave) quoted profit
+    pub async fn optimal_borrow(&self, buy_pool: &DexPool, sell_pool: &DexPool, token_in: Address) -> U256 {
+        let constant_product = |p: &DexPool| matches!(p.state, PoolState::ConstantProduct);
+        if constant_product(buy_pool) && constant_product(sell_pool) {
+            return Self::calculate_optimal_borrow(buy_pool, sell_pool, token_in);
+        }
+
+        let (Some((reserve_in, _)), Some(token_out)) = (buy_pool.reserves_for(token_in), buy_pool.other(token_in)) else {
+            return U256::zero();
+        };
+        let token_out = token_out.address;
+        let profit = |amount: U256| async move {
+            let intermediate = self.quote_out(buy_pool, token_in, amount).await.ok()?;
+            let out = self.quote_out(sell_pool, token_out, intermediate).await.ok()?;
+            Some(I256::from_raw(out) - I256::from_raw(amount))
+        };
+
+        let (mut lo, mut hi) = (U256::zero(), reserve_in / 2);
+        for _ in 0..OPTIMAL_SEARCH_ROUNDS {
+            if hi - lo <= U256::from(2) {
+                break;
+            }
+            let third = (hi - lo) / 3;
+            let (m1, m2) = (lo + third, hi - third);
+            // A failed quote means the size is past what the pool can fill
+            let p1 = profit(m1).await.unwrap_or(I256::MIN);
+            let p2 = profit(m2).await.unwrap_or(I256::MIN);
+            if p1 < p2 {
+                lo = m1;
+            } else {
+                hi = m2;
+            }
+        }
+
+        let best = (lo + hi) / 2;
+        match profit(best).await {
+            Some(p) if p > I256::zero() => best,
+            _ => U256::zero(),
+        }
+    }
+
+    // Set each token's transfer tax, probing every token once per process
+    // through the first of its pools whose venue supports it
+    pub async fn apply_transfer_taxes(&self, pools: &mut [DexPool]) {
+        let cache = TokenCache::global();
+        for pool in pools.iter_mut() {
+            for token in [pool.token0.clone(), pool.token1.clone()] {
+                if cache.transfer_tax(&token.address).is_some() {
+                    continue;
+                }
+                let Ok(handler) = self.handler_for(&pool.dex) else {
+                    continue;
+                };
+                match handler.transfer_tax(pool, token.address).await {
+                    Ok(Some(tax_bps)) => {
+                        if tax_bps > 0 {
+                            info!(token = %token.symbol, tax_bps, "🧾 Token withholds a transfer tax");
+                        }
+                        cache.set_transfer_tax(token.address, tax_bps);
+                    }
+                    Ok(None) => {}
+                    Err(e) => warn!(token = %token.symbol, error = %e, "⚠️ Transfer tax probe failed"),
+                }
+            }
+            pool.token0.transfer_tax_bps = cache.transfer_tax(&pool.token0.address).unwrap_or(0);
+            pool.token1.transfer_tax_bps = cache.transfer_tax(&pool.token1.address).unwrap_or(0);
+        }
+    }
+
+    // Compare every pair of pools trading the same tokens, in both directions
+    #[instrument(name = "profit_calc", skip_all, fields(pools = pools.len(), block = %block_number))]
+    pub async fn find_arbitrage_opportunities(
+        &self,
+        pools: &[DexPool],
+        gas_price: U256,
+        l1_fee_per_byte: U256,
+        block_number: U64,
+    ) -> Vec<ArbitrageOpportunity> {
+        let mut by_pair: HashMap<(Address, Address), Vec<&DexPool>> = HashMap::new();
+        for pool in pools {
+            by_pair.entry(pool.pair_key()).or_default().push(pool);
+        }
+
+        let gas_cost = gas_price * ARBITRAGE_GAS + l1_fee_per_byte * ARBITRAGE_TX_BYTES;
+        let mut opportunities = Vec::new();
+
+        for ((token_a, token_b), group) in by_pair {
+            for (i, first) in group.iter().enumerate() {
+                for second in group.iter().skip(i + 1) {
+                    // A spread between reads from different blocks may only be
+                    // the time between them
+                    if !first.block.is_zero() && !second.block.is_zero() && first.block != second.block {
+                        continue;
+                    }
+                    for (buy, sell) in [(*first, *second), (*second, *first)] {
+                        for token_in in [token_a, token_b] {
+                            let lender = self.v3_lender(pools, token_in, [buy, sell]);
+                            match self
+                                .evaluate_pair(buy, sell, token_in, lender, gas_cost, block_number)
+                                .await
+                            {
+                                Ok(Some(opportunity)) => opportunities.push(opportunity),
+                                Ok(None) => {}
+                                Err(e) => warn!(
+                                    buy = ?buy.address,
+                                    sell = ?sell.address,
+                                    error = %e,
+                                    "⚠️ Quote failed"
+                                ),
+                            }
+                        }
+                    }
+                }
+            }
+        }
+
+        opportunities.sort_by(|a, b| b.gross_profit.cmp(&a.gross_profit));
+        opportunities
+    }
+
+    async fn evaluate_pair(
+        &self,
+        buy_pool: &DexPool,
+        sell_pool: &DexPool,
+        token_in: Address,
+        flash_pool: Option<&DexPool>,
+        gas_cost: U256,
+        block_number: U64,
+    ) -> Result<Option<ArbitrageOpportunity>> {
+        let token_out = match buy_pool.other(token_in) {
+            Some(t) => t.address,
+            None => return Ok(None),
+        };
+
+        let amount_in = self.optimal_borrow(buy_pool, sell_pool, token_in).await;
+        if amount_in.is_zero() {
+            return Ok(None);
+        }
+
+        // Fee-on-transfer tokens withhold a share on every move: the intermediate
+        // token out of the buy pool and into the sell pool, then the input token
+        // out of the sell pool
+        let tax_out = buy_pool.other(token_in).map_or(0, |t| t.transfer_tax_bps);
+        let tax_in = buy_pool.other(token_out).map_or(0, |t| t.transfer_tax_bps);
+        let delivered = |amount: U256| math::less_fee(math::less_fee(amount, tax_out), tax_out);
+
+        let intermediate = self.quote_out(buy_pool, token_in, amount_in).await?;
+        let amount_out = self.quote_out(sell_pool, token_out, delivered(intermediate)).await?;
+        let amount_out = math::less_fee(amount_out, tax_in);
+        if amount_out <= amount_in {
+            return Ok(None);
+        }
+
+        // Re-quote both legs on-chain where the venue supports it
+        let handler = self.handler_for(&buy_pool.dex)?;
+        let intermediate = match handler.quote_onchain(buy_pool, token_in, amount_in).await? {
+            Some(quoted) => quoted,
+            None => intermediate,
+        };
+        let handler = self.handler_for(&sell_pool.dex)?;
+        let amount_out = match handler.quote_onchain(sell_pool, token_out, delivered(intermediate)).await? {
+            Some(quoted) => quoted,
+            None => handler.quote_out(sell_pool, token_out, delivered(intermediate)).await?,
+        };
+        let amount_out = math::less_fee(amount_out, tax_in);
+        if amount_out <= amount_in {
+            return Ok(None);
+        }
+
+        let gross_profit = amount_out - amount_in;
+        let opportunity = ArbitrageOpportunity {
+            token_in,
+            token_out,
+            buy_pool: buy_pool.clone(),
+            sell_pool: sell_pool.clone(),
+            amount_in,
+            intermediate_amount: intermediate,
+            amount_out,
+            gross_profit,
+            gas_cost,
+            spread_bps: math::bps(gross_profit, amount_in),
+            block_number,
+            flash_loan: FlashLoanProvider::Aave,
+            flash_pool: flash_pool.cloned(),
+        };
+
+        // Priced with the first provider, in order of preference, that can fund it
+        let funded = self
+            .flash_loans
+            .iter()
+            .filter(|provider| provider.supports(buy_pool))
+            .find_map(|provider| opportunity.clone().with_flash_loan(*provider))
+            .or_else(|| opportunity.with_flash_loan(FlashLoanProvider::Aave));
+        Ok(funded.filter(|o| !o.gross_profit.is_zero()))
+    }
+}
@@ -0,0 +1,318 @@
+use async_trait::async_trait;
+use ethers::{
+    abi::AbiEncode,
+    prelude::*,
+    types::{Address, Bytes, Filter, Log, U256, U64},
+};
+use std::sync::Arc;
+
+use super::{concurrency::discover_pairs, fetch_token, prefetch_tokens, token_pairs, tokens::probe_transfer_tax, DexHandler};
+use crate::{
+    errors::{DexError, Result},
+    math::{self, mul_div},
+    models::{DexPool, DexType, PoolState},
+};
+
+abigen!(
+    SolidlyFactory,
+    r#"[
+        function getPool(address tokenA, address tokenB, bool stable) external view returns (address)
+        function getFee(address pool, bool stable) external view returns (uint256)
+    ]"#
+);
+
+abigen!(
+    SolidlyPool,
+    r#"[
+        function metadata() external view returns (uint256, uint256, uint256, uint256, bool, address, address)
+        function getReserves() external view returns (uint256, uint256, uint256)
+        function getAmountOut(uint256 amountIn, address tokenIn) external view returns (uint256)
+        function swap(uint256 amount0Out, uint256 amount1Out, address to, bytes data) external
+    ]"#
+);
+
+// Velodrome V2 on Optimism and Aerodrome on Base
+pub const VELODROME_FACTORY: &str = "0xF1046053aa5682b4F9a81b5481394DA16BE5FF5a";
+pub const AERODROME_FACTORY: &str = "0x420DD381b31aEf6683db6B902084cB0FFECe40Da";
+// A little before each factory's deployment on its chain
+const VELODROME_DEPLOYED_AT: u64 = 105_235_063;
+const AERODROME_DEPLOYED_AT: u64 = 3_000_000;
+// Transfer tax probes move reserve / this out of the pool
+const TAX_PROBE_DIVISOR: u64 = 1_000;
+// Newton steps the pool allows itself in _get_y before reverting
+const MAX_NEWTON_STEPS: usize = 255;
+
+// Solidly-style AMMs: every pair can have a volatile (x*y=k) pool and a stable
+// (x^3*y + x*y^3 = k) pool, each with its own fee from the factory
+pub struct SolidlyHandler<M> {
+    provider: Arc<M>,
+    dex: DexType,
+    factory: Address,
+    deployed_at: U64,
+}
+
+impl<M: Middleware + 'static> SolidlyHandler<M> {
+    pub fn new(provider: Arc<M>, dex: DexType, factory: Address) -> Self {
+        Self {
+            provider,
+            dex,
+            factory,
+            deployed_at: U64::zero(),
+        }
+    }
+
+    pub fn velodrome(provider: Arc<M>) -> Self {
+        Self::new(provider, DexType::Velodrome, VELODROME_FACTORY.parse().unwrap())
+            .with_deployment_block(VELODROME_DEPLOYED_AT)
+    }
+
+    pub fn aerodrome(provider: Arc<M>) -> Self {
+        Self::new(provider, DexType::Aerodrome, AERODROME_FACTORY.parse().unwrap())
+            .with_deployment_block(AERODROME_DEPLOYED_AT)
+    }
+
+    // The same venue behind another factory (another chain or a fork). Its
+    // deployment block isn't known, so a backfill starts from genesis.
+    pub fn with_factory(mut self, factory: Address) -> Self {
+        if factory != self.factory {
+            self.factory = factory;
+            self.deployed_at = U64::zero();
+        }
+        self
+    }
+
+    // Skip the blocks before the factory existed when backfilling
+    pub fn with_deployment_block(mut self, block: u64) -> Self {
+        self.deployed_at = U64::from(block);
+        self
+    }
+
+    async fn load_solidly_pool(&self, address: Address) -> Result<DexPool> {
+        let pool = SolidlyPool::new(address, self.provider.clone());
+        let (_, _, reserve0, reserve1, stable, token0, token1) = pool.metadata().call().await?;
+        // Fees are per pool (or per pool type) and in basis points
+        let fee = SolidlyFactory::new(self.factory, self.provider.clone())
+            .get_fee(address, stable)
+            .call()
+            .await?;
+
+        Ok(DexPool {
+            address,
+            dex: self.dex.clone(),
+            token0: fetch_token(self.provider.clone(), token0).await?,
+            token1: fetch_token(self.provider.clone(), token1).await?,
+            reserve0,
+            reserve1,
+            fee_bps: fee.min(U256::from(math::BPS)).as_u32(),
+            block: U64::zero(),
+            state: if stable { PoolState::SolidlyStable } else { PoolState::ConstantProduct },
+        })
+    }
+}
+
+#[async_trait]
+impl<M: Middleware + 'static> DexHandler for SolidlyHandler<M> {
+    fn dex_type(&self) -> DexType {
+        self.dex.clone()
+    }
+
+    async fn discover_pools(&self, tokens: &[Address]) -> Result<Vec<DexPool>> {
+        let factory = &SolidlyFactory::new(self.factory, self.provider.clone());
+
+        prefetch_tokens(self.provider.clone(), tokens).await;
+
+        discover_pairs(token_pairs(tokens), |(a, b)| async move {
+            let mut pools = Vec::new();
+            for stable in [false, true] {
+                let address = factory.get_pool(a, b, stable).call().await?;
+                if address.is_zero() {
+                    continue;
+                }
+                pools.push(self.load_solidly_pool(address).await?);
+            }
+            Ok(pools)
+        })
+        .await
+    }
+
+    fn pool_created_filter(&self) -> Option<Filter> {
+        Some(
+            Filter::new()
+                .address(self.factory)
+                .event("PoolCreated(address,address,bool,address,uint256)"),
+        )
+    }
+
+    fn factory_deployed_at(&self) -> U64 {
+        self.deployed_at
+    }
+
+    // PoolCreated(token0 indexed, token1 indexed, stable indexed, pool, allPoolsLength)
+    fn decode_pool_created(&self, log: &Log) -> Option<Address> {
+        (log.address == self.factory && log.data.len() >= 32).then(|| Address::from_slice(&log.data[12..32]))
+    }
+
+    async fn load_pool(&self, address: Address) -> Result<DexPool> {
+        self.load_solidly_pool(address).await
+    }
+
+    async fn refresh_state(&self, pool: &mut DexPool, block: U64) -> Result<()> {
+        let (reserve0, reserve1, _) = SolidlyPool::new(pool.address, self.provider.clone())
+            .get_reserves()
+            .block(block)
+            .call()
+            .await?;
+        pool.reserve0 = reserve0;
+        pool.reserve1 = reserve1;
+        Ok(())
+    }
+
+    async fn quote_out(&self, pool: &DexPool, token_in: Address, amount_in: U256) -> Result<U256> {
+        get_amount_out(pool, token_in, amount_in).ok_or_else(|| DexError::Overflow("solidly getAmountOut").into())
+    }
+
+    // The pool's own getAmountOut, against live reserves
+    async fn quote_onchain(&self, pool: &DexPool, token_in: Address, amount_in: U256) -> Result<Option<U256>> {
+        let amount_out = SolidlyPool::new(pool.address, self.provider.clone())
+            .get_amount_out(amount_in, token_in)
+            .call()
+            .await?;
+        Ok(Some(amount_out))
+    }
+
+    // Transfers a thousandth of the pool's current reserve out of it
+    async fn transfer_tax(&self, pool: &DexPool, token: Address) -> Result<Option<u32>> {
+        let Some((reserve, _)) = pool.reserves_for(token) else {
+            return Ok(None);
+        };
+        if reserve.is_zero() {
+            return Ok(None);
+        }
+        let amount = reserve / TAX_PROBE_DIVISOR;
+        Ok(Some(probe_transfer_tax(self.provider.clone(), token, pool.address, amount.max(U256::one())).await?))
+    }
+
+    // Direct pool swap, as on a V2 pair; the executor transfers `amount_in` to the pool first
+    fn build_swap_calldata(
+        &self,
+        pool: &DexPool,
+        token_in: Address,
+        _amount_in: U256,
+        min_out: U256,
+        recipient: Address,
+    ) -> Result<Bytes> {
+        let (amount0_out, amount1_out) = if token_in == pool.token0.address {
+            (U256::zero(), min_out)
+        } else if token_in == pool.token1.address {
+            (min_out, U256::zero())
+        } else {
+            return Err(DexError::PoolNotFound(pool.address).into());
+        };
+
+        Ok(SwapCall {
+            amount_0_out: amount0_out,
+            amount_1_out: amount1_out,
+            to: recipient,
+            data: Bytes::new(),
+        }
+        .encode()
+        .into())
+    }
+}
+
+// Output for `amount_in` of `token_in`, mirroring the pool's getAmountOut: the
+// fee comes off the input, then volatile pools trade on x*y=k and stable pools
+// on x^3*y + x*y^3 in 18-decimal units
+pub fn get_amount_out(pool: &DexPool, token_in: Address, amount_in: U256) -> Option<U256> {
+    let (reserve_in, reserve_out) = pool.reserves_for(token_in)?;
+    let amount_in = amount_in.checked_sub(amount_in.checked_mul(U256::from(pool.fee_bps))? / math::BPS)?;
+
+    if !matches!(pool.state, PoolState::SolidlyStable) {
+        return mul_div(amount_in, reserve_out, reserve_in.checked_add(amount_in)?);
+    }
+
+    let (decimals_in, decimals_out) = if token_in == pool.token0.address {
+        (pool.token0.decimals, pool.token1.decimals)
+    } else {
+        (pool.token1.decimals, pool.token0.decimals)
+    };
+    let (unit_in, unit_out) = (U256::exp10(decimals_in as usize), U256::exp10(decimals_out as usize));
+    let one = U256::exp10(18);
+
+    let x = mul_div(reserve_in, one, unit_in)?;
+    let y = mul_div(reserve_out, one, unit_out)?;
+    let xy = stable_k(x, y)?;
+    let x_after = mul_div(amount_in, one, unit_in)?.checked_add(x)?;
+    let dy = y.checked_sub(get_y(x_after, xy, y)?)?;
+    mul_div(dy, unit_out, one)
+}
+
+// Marginal stable-pool rate before fees, in raw units as Q128. With r = y/x in
+// 18-decimal units, dy/dx = r(3 + r^2) / (1 + 3r^2).
+pub fn stable_rate_x128(reserve_in: U256, reserve_out: U256, decimals_in: u8, decimals_out: u8) -> Option<U256> {
+    let q128 = math::q128();
+    let r = mul_div(
+        math::ratio_x128(reserve_out, reserve_in)?,
+        U256::exp10(decimals_in as usize),
+        U256::exp10(decimals_out as usize),
+    )?;
+    let r2 = mul_div(r, r, q128)?;
+    let numerator = mul_div(r, q128.checked_mul(U256::from(3))?.checked_add(r2)?, q128)?;
+    let denominator = q128.checked_add(r2.checked_mul(U256::from(3))?)?;
+    let rate = mul_div(numerator, q128, denominator)?;
+    mul_div(rate, math::decimals_x128(decimals_in, decimals_out), q128)
+}
+
+// x^3*y + x*y^3, each product scaled back to 18 decimals
+fn stable_k(x: U256, y: U256) -> Option<U256> {
+    let one = U256::exp10(18);
+    let a = mul_div(x, y, one)?;
+    let b = mul_div(x, x, one)?.checked_add(mul_div(y, y, one)?)?;
+    mul_div(a, b, one)
+}
+
+// The same invariant with x fixed, as the pool's _f
+fn f(x0: U256, y: U256) -> Option<U256> {
+    let one = U256::exp10(18);
+    let y3 = mul_div(mul_div(y, y, one)?, y, one)?;
+    let x3 = mul_div(mul_div(x0, x0, one)?, x0, one)?;
+    mul_div(x0, y3, one)?.checked_add(mul_div(x3, y, one)?)
+}
+
+// df/dy, as the pool's _d
+fn d(x0: U256, y: U256) -> Option<U256> {
+    let one = U256::exp10(18);
+    let y2 = mul_div(y, y, one)?;
+    let x3 = mul_div(mul_div(x0, x0, one)?, x0, one)?;
+    mul_div(x0.checked_mul(U256::from(3))?, y2, one)?.checked_add(x3)
+}
+
+// Balance of the output side keeping the invariant at `xy` once the input side
+// is `x0`, by Newton's method from `y` as the pool's _get_y does
+fn get_y(x0: U256, xy: U256, mut y: U256) -> Option<U256> {
+    let one = U256::exp10(18);
+    for _ in 0..MAX_NEWTON_STEPS {
+        let k = f(x0, y)?;
+        let slope = d(x0, y)?;
+        if k < xy {
+            let mut dy = mul_div(xy - k, one, slope)?;
+            if dy.is_zero() {
+                if f(x0, y + 1)? > xy {
+                    return Some(y + 1);
+                }
+                dy = U256::one();
+            }
+            y = y.checked_add(dy)?;
+        } else {
+            let mut dy = mul_div(k - xy, one, slope)?;
+            if dy.is_zero() {
+                if k == xy || f(x0, y.checked_sub(U256::one())?)? < xy {
+                    return Some(y);
+                }
+                dy = U256::one();
+            }
+            y = y.checked_sub(dy)?;
+        }
+    }
+    None
+}
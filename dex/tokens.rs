@@ -0,0 +1,179 @@
+use dashmap::DashMap;
+use ethers::{
+    abi::{AbiEncode, Token as AbiToken},
+    prelude::*,
+    providers::{call_raw::RawCall, spoof, RpcError},
+    types::{transaction::eip2718::TypedTransaction, Address, Bytes, U256},
+};
+use std::{
+    collections::HashSet,
+    sync::{Arc, OnceLock},
+};
+
+use super::Erc20Metadata;
+use crate::{
+    errors::{ProviderError, Result},
+    math::{self, BPS},
+    models::{DexPool, Token},
+};
+
+// Tokens per multicall; two calls each
+const BATCH_SIZE: usize = 100;
+
+// Runtime code swapped in at a pool's address for one eth_call. Given
+// abi.encode(token, to, amount) it transfers `amount` of `token` out of the
+// pool's balance, then returns `to`'s balance, bubbling up any revert.
+const TRANSFER_PROBE: &str = "0x63a9059cbb60e01b600052602035600452604035602452600060006044600060006000355af1156051576370a0823160e01b60005260203560045260206000602460006000355afa1560515760206000f35b3d600060003e3d6000fd";
+// Receives probe transfers; assumed to hold nothing beforehand
+const PROBE_RECIPIENT: &str = "0x000000000000000000000000000000000000fee7";
+
+// ERC20 metadata never changes, so every token is read from chain once per
+// process and shared by all handlers. The pool registry persists it across
+// restarts.
+#[derive(Default)]
+pub struct TokenCache {
+    tokens: DashMap<Address, Token>,
+    // Probed transfer tax per token, in bps
+    taxes: DashMap<Address, u32>,
+}
+
+impl TokenCache {
+    pub fn global() -> &'static TokenCache {
+        static CACHE: OnceLock<TokenCache> = OnceLock::new();
+        CACHE.get_or_init(TokenCache::default)
+    }
+
+    pub fn get(&self, address: &Address) -> Option<Token> {
+        self.tokens.get(address).map(|t| t.clone())
+    }
+
+    pub fn insert(&self, token: Token) {
+        self.tokens.insert(token.address, token);
+    }
+
+    // Metadata loaded from storage
+    pub fn seed(&self, tokens: impl IntoIterator<Item = Token>) {
+        for token in tokens {
+            self.insert(token);
+        }
+    }
+
+    pub fn transfer_tax(&self, address: &Address) -> Option<u32> {
+        self.taxes.get(address).map(|t| *t)
+    }
+
+    pub fn set_transfer_tax(&self, address: Address, tax_bps: u32) {
+        self.taxes.insert(address, tax_bps);
+        if let Some(mut token) = self.tokens.get_mut(&address) {
+            token.transfer_tax_bps = tax_bps;
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.tokens.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.tokens.is_empty()
+    }
+
+    // Read every uncached token in `addresses` through Multicall3. A batch
+    // that can't be decoded (bytes32 symbols, non-ERC20s) is left for
+    // `fetch_token` to read one by one.
+    pub async fn prefetch<M: Middleware + 'static>(&self, provider: Arc<M>, addresses: &[Address]) -> Result<()> {
+        let mut missing: Vec<Address> = addresses
+            .iter()
+            .filter(|a| !self.tokens.contains_key(*a))
+            .copied()
+            .collect();
+        missing.sort();
+        missing.dedup();
+
+        for batch in missing.chunks(BATCH_SIZE) {
+            let mut multicall = Multicall::new(provider.clone(), None)
+                .await
+                .map_err(|e| ProviderError::Rpc(e.to_string()))?;
+            for address in batch {
+                let erc20 = Erc20Metadata::new(*address, provider.clone());
+                multicall.add_call(erc20.decimals(), true).add_call(erc20.symbol(), true);
+            }
+
+            let Ok(results) = multicall.call_raw().await else {
+                continue;
+            };
+            for (address, pair) in batch.iter().zip(results.chunks(2)) {
+                let Some(Ok(AbiToken::Uint(decimals))) = pair.first() else {
+                    continue;
+                };
+                let symbol = match pair.get(1) {
+                    Some(Ok(AbiToken::String(symbol))) => symbol.clone(),
+                    _ => format!("{:?}", address),
+                };
+                self.insert(Token {
+                    address: *address,
+                    symbol,
+                    decimals: decimals.low_u32() as u8,
+                    transfer_tax_bps: self.transfer_tax(address).unwrap_or(0),
+                });
+            }
+        }
+
+        Ok(())
+    }
+}
+
+// Operator restrictions on which tokens are traded. With an allowlist only
+// those tokens are scanned; denied tokens are never scanned.
+#[derive(Debug, Clone, Default)]
+pub struct TokenFilter {
+    allow: Option<HashSet<Address>>,
+    deny: HashSet<Address>,
+}
+
+impl TokenFilter {
+    pub fn new(allow: Option<Vec<Address>>, deny: Vec<Address>) -> Self {
+        Self {
+            allow: allow.map(|a| a.into_iter().collect()),
+            deny: deny.into_iter().collect(),
+        }
+    }
+
+    pub fn permits(&self, token: &Address) -> bool {
+        !self.deny.contains(token) && self.allow.as_ref().map_or(true, |a| a.contains(token))
+    }
+
+    pub fn permits_pool(&self, pool: &DexPool) -> bool {
+        self.permits(&pool.token0.address) && self.permits(&pool.token1.address)
+    }
+}
+
+// Share of `amount` that fails to arrive when `holder` transfers it, in bps,
+// simulated with eth_call. A transfer that reverts counts as a full tax: the
+// token can't be bought out of that pool.
+pub async fn probe_transfer_tax<M: Middleware + 'static>(
+    provider: Arc<M>,
+    token: Address,
+    holder: Address,
+    amount: U256,
+) -> Result<u32> {
+    if amount.is_zero() {
+        return Ok(0);
+    }
+    let recipient: Address = PROBE_RECIPIENT.parse().unwrap();
+    let mut state = spoof::state();
+    state.account(holder).code(TRANSFER_PROBE.parse::<Bytes>().unwrap());
+
+    let tx: TypedTransaction = TransactionRequest::new()
+        .to(holder)
+        .data((token, recipient, amount).encode())
+        .into();
+    match provider.provider().call_raw(&tx).state(&state).await {
+        Ok(output) if output.len() >= 32 => {
+            let received = U256::from_big_endian(&output[..32]);
+            Ok(math::bps(amount.saturating_sub(received), amount))
+        }
+        Ok(_) => Ok(BPS),
+        Err(e) if e.as_error_response().is_some() => Ok(BPS),
+        Err(e) => Err(e.into()),
+    }
+}
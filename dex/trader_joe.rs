@@ -0,0 +1,309 @@
+use async_trait::async_trait;
+use ethers::{
+    abi::AbiEncode,
+    prelude::*,
+    types::{Address, Bytes, Filter, Log, U256, U64},
+};
+use std::{collections::BTreeMap, sync::Arc};
+
+use super::{concurrency::discover_pairs, fetch_token, prefetch_tokens, token_pairs, DexHandler};
+use crate::{
+    errors::{DexError, Result},
+    math::{self, mul_div, mul_div_up},
+    models::{DexPool, DexType, LiquidityBookState, PoolState},
+};
+
+abigen!(
+    LbFactory,
+    r#"[
+        function getAllLBPairs(address tokenX, address tokenY) external view returns ((uint16,address,bool,bool)[])
+    ]"#
+);
+
+abigen!(
+    LbPair,
+    r#"[
+        function getTokenX() external view returns (address)
+        function getTokenY() external view returns (address)
+        function getBinStep() external view returns (uint16)
+        function getActiveId() external view returns (uint24)
+        function getReserves() external view returns (uint128, uint128)
+        function getBin(uint24 id) external view returns (uint128, uint128)
+        function getNextNonEmptyBin(bool swapForY, uint24 id) external view returns (uint24)
+        function getStaticFeeParameters() external view returns (uint16, uint16, uint16, uint16, uint24, uint16, uint24)
+        function getVariableFeeParameters() external view returns (uint24, uint24, uint24, uint40)
+        function getSwapOut(uint128 amountIn, bool swapForY) external view returns (uint128, uint128, uint128)
+        function swap(bool swapForY, address to) external returns (bytes32)
+    ]"#
+);
+
+// Liquidity Book v2.1 factory, at the same address on Avalanche and Arbitrum
+pub const LB_FACTORY: &str = "0x8e42f2F4101563bF679975178e880FD87d3eFd4e";
+
+// Bin id of price 1; prices are (1 + binStep / 10^4)^(id - this)
+const REAL_ID_SHIFT: i64 = 1 << 23;
+// Non-empty bins read on each side of the active one
+const BINS_PER_SIDE: usize = 8;
+// Sentinels getNextNonEmptyBin returns past the last bin
+const NO_BIN_BELOW: u32 = 0;
+const NO_BIN_ABOVE: u32 = (1 << 24) - 1;
+
+// Trader Joe's Liquidity Book: each pair is split into bins of constant price
+// (constant sum within a bin), and swaps walk bin by bin away from the active
+// one. Pairs exist per bin step, so one token pair can have several.
+pub struct TraderJoeHandler<M> {
+    provider: Arc<M>,
+    factory: Address,
+}
+
+impl<M: Middleware + 'static> TraderJoeHandler<M> {
+    pub fn new(provider: Arc<M>, factory: Address) -> Self {
+        Self { provider, factory }
+    }
+
+    pub fn liquidity_book(provider: Arc<M>) -> Self {
+        Self::new(provider, LB_FACTORY.parse().unwrap())
+    }
+
+    async fn load_lb_pair(&self, address: Address) -> Result<DexPool> {
+        let pair = LbPair::new(address, self.provider.clone());
+        let token_x = pair.get_token_x().call().await?;
+        let token_y = pair.get_token_y().call().await?;
+        let bin_step = pair.get_bin_step().call().await?;
+        let (state, reserve_x, reserve_y) = self.load_state(&pair, bin_step, BlockNumber::Latest.into()).await?;
+
+        Ok(DexPool {
+            address,
+            dex: DexType::TraderJoeV2,
+            token0: fetch_token(self.provider.clone(), token_x).await?,
+            token1: fetch_token(self.provider.clone(), token_y).await?,
+            reserve0: reserve_x,
+            reserve1: reserve_y,
+            fee_bps: math::bps(state.fee, U256::exp10(18)),
+            block: U64::zero(),
+            state: PoolState::LiquidityBook(state),
+        })
+    }
+
+    // Active bin, current fee and the nearest non-empty bins either side, plus
+    // the pair's total reserves, as of `block`
+    async fn load_state(&self, pair: &LbPair<M>, bin_step: u16, block: BlockId) -> Result<(LiquidityBookState, U256, U256)> {
+        let active_id = pair.get_active_id().block(block).call().await?;
+        let (reserve_x, reserve_y) = pair.get_reserves().block(block).call().await?;
+        let (base_factor, _, _, _, variable_fee_control, _, _) =
+            pair.get_static_fee_parameters().block(block).call().await?;
+        let (volatility_accumulator, ..) = pair.get_variable_fee_parameters().block(block).call().await?;
+
+        let mut bins = BTreeMap::new();
+        let (x, y) = pair.get_bin(active_id).block(block).call().await?;
+        bins.insert(active_id, (U256::from(x), U256::from(y)));
+        for swap_for_y in [true, false] {
+            let mut id = active_id;
+            for _ in 0..BINS_PER_SIDE {
+                id = pair.get_next_non_empty_bin(swap_for_y, id).block(block).call().await?;
+                if id == NO_BIN_BELOW || id == NO_BIN_ABOVE {
+                    break;
+                }
+                let (x, y) = pair.get_bin(id).block(block).call().await?;
+                bins.insert(id, (U256::from(x), U256::from(y)));
+            }
+        }
+
+        let state = LiquidityBookState {
+            active_id,
+            bin_step,
+            fee: total_fee(bin_step, base_factor, variable_fee_control, volatility_accumulator),
+            bins,
+        };
+        Ok((state, U256::from(reserve_x), U256::from(reserve_y)))
+    }
+}
+
+#[async_trait]
+impl<M: Middleware + 'static> DexHandler for TraderJoeHandler<M> {
+    fn dex_type(&self) -> DexType {
+        DexType::TraderJoeV2
+    }
+
+    async fn discover_pools(&self, tokens: &[Address]) -> Result<Vec<DexPool>> {
+        let factory = &LbFactory::new(self.factory, self.provider.clone());
+
+        prefetch_tokens(self.provider.clone(), tokens).await;
+
+        discover_pairs(token_pairs(tokens), |(a, b)| async move {
+            let mut pools = Vec::new();
+            // One pair per bin step; those the factory hides from routing are skipped
+            for (_, pair, _, ignored_for_routing) in factory.get_all_lb_pairs(a, b).call().await? {
+                if pair.is_zero() || ignored_for_routing {
+                    continue;
+                }
+                pools.push(self.load_lb_pair(pair).await?);
+            }
+            Ok(pools)
+        })
+        .await
+    }
+
+    fn pool_created_filter(&self) -> Option<Filter> {
+        Some(
+            Filter::new()
+                .address(self.factory)
+                .event("LBPairCreated(address,address,uint256,address,uint256)"),
+        )
+    }
+
+    // LBPairCreated(tokenX indexed, tokenY indexed, binStep indexed, LBPair, pid)
+    fn decode_pool_created(&self, log: &Log) -> Option<Address> {
+        (log.address == self.factory && log.data.len() >= 32).then(|| Address::from_slice(&log.data[12..32]))
+    }
+
+    async fn load_pool(&self, address: Address) -> Result<DexPool> {
+        self.load_lb_pair(address).await
+    }
+
+    async fn refresh_state(&self, pool: &mut DexPool, block: U64) -> Result<()> {
+        let PoolState::LiquidityBook(state) = &pool.state else {
+            return Err(DexError::Unsupported(format!("{:?} is not a Liquidity Book pair", pool.address)).into());
+        };
+        let pair = LbPair::new(pool.address, self.provider.clone());
+        let (state, reserve_x, reserve_y) = self.load_state(&pair, state.bin_step, block.into()).await?;
+
+        pool.reserve0 = reserve_x;
+        pool.reserve1 = reserve_y;
+        pool.fee_bps = math::bps(state.fee, U256::exp10(18));
+        pool.state = PoolState::LiquidityBook(state);
+        Ok(())
+    }
+
+    async fn quote_out(&self, pool: &DexPool, token_in: Address, amount_in: U256) -> Result<U256> {
+        let PoolState::LiquidityBook(state) = &pool.state else {
+            return Err(DexError::Unsupported(format!("{:?} is not a Liquidity Book pair", pool.address)).into());
+        };
+        let swap_for_y = if token_in == pool.token0.address {
+            true
+        } else if token_in == pool.token1.address {
+            false
+        } else {
+            return Err(DexError::PoolNotFound(pool.address).into());
+        };
+
+        swap_exact_in(state, swap_for_y, amount_in).ok_or_else(|| DexError::InsufficientLiquidity(pool.address).into())
+    }
+
+    // The pair's own getSwapOut, which also applies the volatility fee as it grows
+    async fn quote_onchain(&self, pool: &DexPool, token_in: Address, amount_in: U256) -> Result<Option<U256>> {
+        let amount_in: u128 = amount_in
+            .try_into()
+            .map_err(|_| DexError::InsufficientLiquidity(pool.address))?;
+        let (amount_in_left, amount_out, _) = LbPair::new(pool.address, self.provider.clone())
+            .get_swap_out(amount_in, token_in == pool.token0.address)
+            .call()
+            .await?;
+        if amount_in_left > 0 {
+            return Err(DexError::InsufficientLiquidity(pool.address).into());
+        }
+        Ok(Some(U256::from(amount_out)))
+    }
+
+    // Pair swap; the executor transfers `amount_in` to the pair first and checks
+    // `min_out` itself, since the pair has no minimum
+    fn build_swap_calldata(
+        &self,
+        pool: &DexPool,
+        token_in: Address,
+        _amount_in: U256,
+        _min_out: U256,
+        recipient: Address,
+    ) -> Result<Bytes> {
+        if !pool.has_token(token_in) {
+            return Err(DexError::PoolNotFound(pool.address).into());
+        }
+
+        Ok(SwapCall {
+            swap_for_y: token_in == pool.token0.address,
+            to: recipient,
+        }
+        .encode()
+        .into())
+    }
+}
+
+// Base fee plus the variable fee at the last recorded volatility, with 1e18 precision
+fn total_fee(bin_step: u16, base_factor: u16, variable_fee_control: u32, volatility_accumulator: u32) -> U256 {
+    let base = U256::from(base_factor) * U256::from(bin_step) * U256::exp10(10);
+    let step_volatility = U256::from(volatility_accumulator) * U256::from(bin_step);
+    let variable = (step_volatility * step_volatility * U256::from(variable_fee_control) + 99) / 100;
+    base + variable
+}
+
+// Price of bin `id`, Y per X in raw units as Q128
+pub fn bin_price_x128(id: u32, bin_step: u16) -> Option<U256> {
+    let q128 = math::q128();
+    let base = q128 + mul_div(q128, U256::from(bin_step), U256::from(math::BPS))?;
+    let exponent = id as i64 - REAL_ID_SHIFT;
+
+    let (mut price, mut power, mut remaining) = (q128, base, exponent.unsigned_abs());
+    while remaining > 0 {
+        if remaining & 1 == 1 {
+            price = mul_div(price, power, q128)?;
+        }
+        remaining >>= 1;
+        if remaining > 0 {
+            power = mul_div(power, power, q128)?;
+        }
+    }
+
+    if exponent < 0 {
+        mul_div(q128, q128, price)
+    } else {
+        Some(price)
+    }
+}
+
+// Output for `amount_in` through the loaded bins, mirroring the pair's swap: a
+// swap for Y walks down from the active bin and a swap for X walks up, with the
+// fee charged on each bin's input. None when it would run past the loaded bins.
+pub fn swap_exact_in(state: &LiquidityBookState, swap_for_y: bool, amount_in: U256) -> Option<U256> {
+    let q128 = math::q128();
+    let precision = U256::exp10(18);
+    let ids: Vec<u32> = if swap_for_y {
+        state.bins.range(..=state.active_id).rev().map(|(id, _)| *id).collect()
+    } else {
+        state.bins.range(state.active_id..).map(|(id, _)| *id).collect()
+    };
+
+    let mut remaining = amount_in;
+    let mut amount_out = U256::zero();
+    for id in ids {
+        let (reserve_x, reserve_y) = state.bins[&id];
+        let reserve_out = if swap_for_y { reserve_y } else { reserve_x };
+        if reserve_out.is_zero() {
+            continue;
+        }
+        let price = bin_price_x128(id, state.bin_step)?;
+
+        // Input that empties the bin, fee included
+        let max_in = if swap_for_y {
+            mul_div_up(reserve_out, q128, price)?
+        } else {
+            mul_div_up(reserve_out, price, q128)?
+        };
+        let max_in = max_in.checked_add(mul_div_up(max_in, state.fee, precision.checked_sub(state.fee)?)?)?;
+
+        if remaining < max_in {
+            let net = remaining - mul_div_up(remaining, state.fee, precision)?;
+            let out = if swap_for_y {
+                mul_div(net, price, q128)?
+            } else {
+                mul_div(net, q128, price)?
+            };
+            return amount_out.checked_add(out.min(reserve_out));
+        }
+        remaining -= max_in;
+        amount_out = amount_out.checked_add(reserve_out)?;
+        if remaining.is_zero() {
+            return Some(amount_out);
+        }
+    }
+    None
+}
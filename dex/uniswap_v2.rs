@@ -0,0 +1,277 @@
+use async_trait::async_trait;
+use ethers::{
+    abi::AbiEncode,
+    prelude::*,
+    types::{Address, Bytes, Filter, Log, U256, U64},
+};
+use std::sync::Arc;
+
+use super::{concurrency::discover_pairs, fetch_token, prefetch_tokens, token_pairs, tokens::probe_transfer_tax, DexHandler, DexManager};
+use crate::{
+    errors::{DexError, Result},
+    math,
+    models::{DexPool, DexType, PoolState},
+};
+
+abigen!(
+    UniswapV2Factory,
+    r#"[function getPair(address tokenA, address tokenB) external view returns (address)]"#
+);
+
+abigen!(
+    UniswapV2Pair,
+    r#"[
+        function token0() external view returns (address)
+        function token1() external view returns (address)
+        function getReserves() external view returns (uint112, uint112, uint32)
+        function swap(uint256 amount0Out, uint256 amount1Out, address to, bytes data) external
+    ]"#
+);
+
+pub const UNISWAP_V2_FACTORY: &str = "0x5C69bEe701ef814a2B6a3EDD4B1652CB9cc5aA6f";
+pub const SUSHISWAP_FACTORY: &str = "0xC0AEe478e3658e2610c5F7A4A2E1777cE9e4f2Ac";
+pub const PANCAKESWAP_V2_FACTORY: &str = "0xcA143Ce32Fe78f1f7019d7d551a6402fC5350c73";
+const UNISWAP_V2_DEPLOYED_AT: u64 = 10_000_835;
+const SUSHISWAP_DEPLOYED_AT: u64 = 10_794_229;
+// On BNB Chain
+const PANCAKESWAP_V2_DEPLOYED_AT: u64 = 6_809_737;
+// Transfer tax probes move reserve / this out of the pair
+const TAX_PROBE_DIVISOR: u64 = 1_000;
+// What most V2 clones charge
+const DEFAULT_FORK_FEE_BPS: u32 = 30;
+
+// The handler config-driven V2 clones (ShibaSwap, Fraxswap, L2 forks) run on;
+// built with `from_fork`
+pub type GenericV2Handler<M> = UniswapV2Handler<M>;
+
+// A Uniswap V2 clone described by config rather than code:
+// `name=factory[:fee_bps[:deployed_at]]`, e.g.
+// `shibaswap=0x115934131916C8b277DD010Ee02de363c09d037c:30`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct V2Fork {
+    pub name: String,
+    pub factory: Address,
+    pub fee_bps: u32,
+    // Where a backfill of its pairs starts; 0 scans from genesis
+    pub deployed_at: u64,
+}
+
+impl std::str::FromStr for V2Fork {
+    type Err = String;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        let (name, rest) = s
+            .split_once('=')
+            .ok_or_else(|| format!("expected name=factory[:fee_bps[:deployed_at]], got {}", s))?;
+        let name = name.trim();
+        if name.is_empty() {
+            return Err(format!("V2 fork without a name: {}", s));
+        }
+        let mut fields = rest.split(':').map(str::trim);
+        let factory = fields
+            .next()
+            .unwrap_or_default()
+            .parse()
+            .map_err(|e| format!("{}: invalid factory address: {}", name, e))?;
+        let fee_bps = match fields.next() {
+            Some(fee) => fee.parse().map_err(|_| format!("{}: invalid fee {}", name, fee))?,
+            None => DEFAULT_FORK_FEE_BPS,
+        };
+        if fee_bps >= math::BPS {
+            return Err(format!("{}: fee {} bps is not below 100%", name, fee_bps));
+        }
+        let deployed_at = match fields.next() {
+            Some(block) => block.parse().map_err(|_| format!("{}: invalid block {}", name, block))?,
+            None => 0,
+        };
+        Ok(Self {
+            name: name.to_string(),
+            factory,
+            fee_bps,
+            deployed_at,
+        })
+    }
+}
+
+// Any constant-product venue with the Uniswap V2 factory/pair ABI
+pub struct UniswapV2Handler<M> {
+    provider: Arc<M>,
+    dex: DexType,
+    factory: Address,
+    fee_bps: u32,
+    deployed_at: U64,
+}
+
+impl<M: Middleware + 'static> UniswapV2Handler<M> {
+    pub fn new(provider: Arc<M>, dex: DexType, factory: Address, fee_bps: u32) -> Self {
+        Self {
+            provider,
+            dex,
+            factory,
+            fee_bps,
+            deployed_at: U64::zero(),
+        }
+    }
+
+    pub fn uniswap(provider: Arc<M>) -> Self {
+        Self::new(provider, DexType::UniswapV2, UNISWAP_V2_FACTORY.parse().unwrap(), 30)
+            .with_deployment_block(UNISWAP_V2_DEPLOYED_AT)
+    }
+
+    pub fn sushiswap(provider: Arc<M>) -> Self {
+        Self::new(provider, DexType::SushiSwap, SUSHISWAP_FACTORY.parse().unwrap(), 30)
+            .with_deployment_block(SUSHISWAP_DEPLOYED_AT)
+    }
+
+    // A V2 clone named in config, registered as DexType::Custom(name)
+    pub fn from_fork(provider: Arc<M>, fork: &V2Fork) -> Self {
+        Self::new(provider, DexType::Custom(fork.name.clone()), fork.factory, fork.fee_bps)
+            .with_deployment_block(fork.deployed_at)
+    }
+
+    // BNB Chain deployment; pairs charge 0.25%
+    pub fn pancakeswap(provider: Arc<M>) -> Self {
+        Self::new(provider, DexType::PancakeSwapV2, PANCAKESWAP_V2_FACTORY.parse().unwrap(), 25)
+            .with_deployment_block(PANCAKESWAP_V2_DEPLOYED_AT)
+    }
+
+    // The same venue behind another factory (another chain or a fork). Its
+    // deployment block isn't known, so a backfill starts from genesis.
+    pub fn with_factory(mut self, factory: Address) -> Self {
+        if factory != self.factory {
+            self.factory = factory;
+            self.deployed_at = U64::zero();
+        }
+        self
+    }
+
+    // Skip the blocks before the factory existed when backfilling
+    pub fn with_deployment_block(mut self, block: u64) -> Self {
+        self.deployed_at = U64::from(block);
+        self
+    }
+
+    async fn load_pair(&self, address: Address) -> Result<DexPool> {
+        let pair = UniswapV2Pair::new(address, self.provider.clone());
+        let token0 = pair.token_0().call().await?;
+        let token1 = pair.token_1().call().await?;
+        let (reserve0, reserve1, _) = pair.get_reserves().call().await?;
+
+        Ok(DexPool {
+            address,
+            dex: self.dex.clone(),
+            token0: fetch_token(self.provider.clone(), token0).await?,
+            token1: fetch_token(self.provider.clone(), token1).await?,
+            reserve0: U256::from(reserve0),
+            reserve1: U256::from(reserve1),
+            fee_bps: self.fee_bps,
+            block: U64::zero(),
+            state: PoolState::ConstantProduct,
+        })
+    }
+}
+
+#[async_trait]
+impl<M: Middleware + 'static> DexHandler for UniswapV2Handler<M> {
+    fn dex_type(&self) -> DexType {
+        self.dex.clone()
+    }
+
+    async fn discover_pools(&self, tokens: &[Address]) -> Result<Vec<DexPool>> {
+        let factory = &UniswapV2Factory::new(self.factory, self.provider.clone());
+
+        prefetch_tokens(self.provider.clone(), tokens).await;
+
+        discover_pairs(token_pairs(tokens), |(a, b)| async move {
+            let pair = factory.get_pair(a, b).call().await?;
+            if pair.is_zero() {
+                return Ok(Vec::new());
+            }
+            Ok(vec![self.load_pair(pair).await?])
+        })
+        .await
+    }
+
+    fn pool_created_filter(&self) -> Option<Filter> {
+        Some(
+            Filter::new()
+                .address(self.factory)
+                .event("PairCreated(address,address,address,uint256)"),
+        )
+    }
+
+    fn factory_deployed_at(&self) -> U64 {
+        self.deployed_at
+    }
+
+    // PairCreated(token0 indexed, token1 indexed, pair, allPairsLength)
+    fn decode_pool_created(&self, log: &Log) -> Option<Address> {
+        (log.address == self.factory && log.data.len() >= 32).then(|| Address::from_slice(&log.data[12..32]))
+    }
+
+    async fn load_pool(&self, address: Address) -> Result<DexPool> {
+        self.load_pair(address).await
+    }
+
+    async fn refresh_state(&self, pool: &mut DexPool, block: U64) -> Result<()> {
+        let pair = UniswapV2Pair::new(pool.address, self.provider.clone());
+        let (reserve0, reserve1, _) = pair.get_reserves().block(block).call().await?;
+        pool.reserve0 = U256::from(reserve0);
+        pool.reserve1 = U256::from(reserve1);
+        Ok(())
+    }
+
+    async fn quote_out(&self, pool: &DexPool, token_in: Address, amount_in: U256) -> Result<U256> {
+        let (reserve_in, reserve_out) = pool
+            .reserves_for(token_in)
+            .ok_or(DexError::PoolNotFound(pool.address))?;
+        Ok(DexManager::calculate_output_amount(amount_in, reserve_in, reserve_out, pool.fee_bps))
+    }
+
+    // Transfers a thousandth of the pair's current reserve out of it
+    async fn transfer_tax(&self, pool: &DexPool, token: Address) -> Result<Option<u32>> {
+        let (reserve0, reserve1, _) = UniswapV2Pair::new(pool.address, self.provider.clone())
+            .get_reserves()
+            .call()
+            .await?;
+        let reserve = if token == pool.token0.address {
+            U256::from(reserve0)
+        } else if token == pool.token1.address {
+            U256::from(reserve1)
+        } else {
+            return Ok(None);
+        };
+        if reserve.is_zero() {
+            return Ok(None);
+        }
+        let amount = reserve / TAX_PROBE_DIVISOR;
+        Ok(Some(probe_transfer_tax(self.provider.clone(), token, pool.address, amount.max(U256::one())).await?))
+    }
+
+    // Direct pair swap; the executor transfers `amount_in` to the pair first
+    fn build_swap_calldata(
+        &self,
+        pool: &DexPool,
+        token_in: Address,
+        _amount_in: U256,
+        min_out: U256,
+        recipient: Address,
+    ) -> Result<Bytes> {
+        let (amount0_out, amount1_out) = if token_in == pool.token0.address {
+            (U256::zero(), min_out)
+        } else if token_in == pool.token1.address {
+            (min_out, U256::zero())
+        } else {
+            return Err(DexError::PoolNotFound(pool.address).into());
+        };
+
+        Ok(SwapCall {
+            amount_0_out: amount0_out,
+            amount_1_out: amount1_out,
+            to: recipient,
+            data: Bytes::new(),
+        }
+        .encode()
+        .into())
+    }
+}
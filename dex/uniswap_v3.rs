@@ -0,0 +1,575 @@
+use async_trait::async_trait;
+use ethers::{
+    abi::AbiEncode,
+    prelude::*,
+    types::{Address, Bytes, Filter, Log, I256, U256, U64},
+};
+use std::{collections::BTreeMap, sync::Arc};
+
+use super::{concurrency::discover_pairs, fetch_token, prefetch_tokens, token_pairs, DexHandler};
+use crate::{
+    errors::{DexError, Result},
+    math::{div_round_up, mul_div, mul_div_up, q96},
+    models::{DexPool, DexType, PoolState, UniswapV3State},
+};
+
+abigen!(
+    UniswapV3Factory,
+    r#"[function getPool(address tokenA, address tokenB, uint24 fee) external view returns (address)]"#
+);
+
+abigen!(
+    UniswapV3Pool,
+    r#"[
+        function token0() external view returns (address)
+        function token1() external view returns (address)
+        function fee() external view returns (uint24)
+        function tickSpacing() external view returns (int24)
+        function slot0() external view returns (uint160, int24, uint16, uint16, uint16, uint32, bool)
+        function liquidity() external view returns (uint128)
+        function tickBitmap(int16 wordPosition) external view returns (uint256)
+        function ticks(int24 tick) external view returns (uint128, int128, uint256, uint256, int56, uint160, uint32, bool)
+        function swap(address recipient, bool zeroForOne, int256 amountSpecified, uint160 sqrtPriceLimitX96, bytes data) external returns (int256, int256)
+    ]"#
+);
+
+abigen!(
+    QuoterV2,
+    r#"[
+        struct QuoteExactInputSingleParams { address tokenIn; address tokenOut; uint256 amountIn; uint24 fee; uint160 sqrtPriceLimitX96; }
+        function quoteExactInputSingle(QuoteExactInputSingleParams params) external returns (uint256, uint160, uint32, uint256)
+    ]"#
+);
+
+pub const UNISWAP_V3_FACTORY: &str = "0x1F98431c8aD98523631AE4a59f8409a71c3a6Be2";
+pub const QUOTER_V2: &str = "0x61fFE014bA17989E743c5F6cB21bF9697530B21e";
+const UNISWAP_V3_DEPLOYED_AT: u64 = 12_369_621;
+
+pub const FEE_TIERS: [u32; 4] = [100, 500, 3000, 10_000];
+
+// PancakeSwap V3 on BNB Chain: the Uniswap V3 pool design with a 0.25% tier
+// instead of 0.3%. Pools call back `pancakeV3SwapCallback`.
+pub const PANCAKESWAP_V3_FACTORY: &str = "0x0BFbCF9fa4f9C56B0F40a671Ad40E0805A091865";
+pub const PANCAKESWAP_QUOTER_V2: &str = "0xB048Bbc1Ee6b733FFfCFb9e9CeF7375518e25997";
+pub const PANCAKESWAP_FEE_TIERS: [u32; 4] = [100, 500, 2500, 10_000];
+// A little before the factory's deployment on BNB Chain
+const PANCAKESWAP_V3_DEPLOYED_AT: u64 = 26_800_000;
+
+// Bitmap words read on each side of the current one; each word covers 256 tick spacings
+pub(crate) const BITMAP_WORDS: i32 = 2;
+
+pub(crate) const MIN_TICK: i32 = -887_272;
+pub(crate) const MAX_TICK: i32 = 887_272;
+pub(crate) const MIN_SQRT_RATIO: u64 = 4_295_128_739;
+pub(crate) const MAX_SQRT_RATIO: &str = "1461446703485210103287273052203988822378723970342";
+const FEE_DENOMINATOR: u32 = 1_000_000;
+
+// QuoterV2 simulates the swap in the real pool (via eth_call), so its answer
+// includes every tick crossing, not just the ones loaded locally
+pub struct Quoter<M> {
+    contract: QuoterV2<M>,
+}
+
+impl<M: Middleware + 'static> Quoter<M> {
+    pub fn new(provider: Arc<M>, address: Address) -> Self {
+        Self { contract: QuoterV2::new(address, provider) }
+    }
+
+    pub async fn quote_exact_input_single(
+        &self,
+        token_in: Address,
+        token_out: Address,
+        fee_pips: u32,
+        amount_in: U256,
+    ) -> Result<U256> {
+        let params = QuoteExactInputSingleParams {
+            token_in,
+            token_out,
+            amount_in,
+            fee: fee_pips,
+            sqrt_price_limit_x96: U256::zero(),
+        };
+        let (amount_out, ..) = self.contract.quote_exact_input_single(params).call().await?;
+        Ok(amount_out)
+    }
+}
+
+pub struct UniswapV3Handler<M> {
+    provider: Arc<M>,
+    dex: DexType,
+    factory: Address,
+    fee_tiers: Vec<u32>,
+    quoter: Option<Quoter<M>>,
+    deployed_at: U64,
+}
+
+impl<M: Middleware + 'static> UniswapV3Handler<M> {
+    pub fn new(provider: Arc<M>, factory: Address) -> Self {
+        Self {
+            provider,
+            dex: DexType::UniswapV3,
+            factory,
+            fee_tiers: FEE_TIERS.to_vec(),
+            quoter: None,
+            deployed_at: U64::zero(),
+        }
+    }
+
+    pub fn mainnet(provider: Arc<M>) -> Self {
+        let quoter = Quoter::new(provider.clone(), QUOTER_V2.parse().unwrap());
+        Self::new(provider, UNISWAP_V3_FACTORY.parse().unwrap())
+            .with_quoter(quoter)
+            .with_deployment_block(UNISWAP_V3_DEPLOYED_AT)
+    }
+
+    pub fn pancakeswap(provider: Arc<M>) -> Self {
+        let quoter = Quoter::new(provider.clone(), PANCAKESWAP_QUOTER_V2.parse().unwrap());
+        Self {
+            dex: DexType::PancakeSwapV3,
+            fee_tiers: PANCAKESWAP_FEE_TIERS.to_vec(),
+            ..Self::new(provider, PANCAKESWAP_V3_FACTORY.parse().unwrap())
+        }
+        .with_quoter(quoter)
+        .with_deployment_block(PANCAKESWAP_V3_DEPLOYED_AT)
+    }
+
+    // The same venue behind another factory (another chain or a fork). Its
+    // deployment block isn't known, so a backfill starts from genesis.
+    pub fn with_factory(mut self, factory: Address) -> Self {
+        if factory != self.factory {
+            self.factory = factory;
+            self.deployed_at = U64::zero();
+        }
+        self
+    }
+
+    // Skip the blocks before the factory existed when backfilling
+    pub fn with_deployment_block(mut self, block: u64) -> Self {
+        self.deployed_at = U64::from(block);
+        self
+    }
+
+    pub fn with_quoter(mut self, quoter: Quoter<M>) -> Self {
+        self.quoter = Some(quoter);
+        self
+    }
+
+    // Price with the local tick math only, where no quoter is deployed
+    pub fn without_quoter(mut self) -> Self {
+        self.quoter = None;
+        self
+    }
+
+    async fn load_v3_pool(&self, address: Address) -> Result<DexPool> {
+        let pool = UniswapV3Pool::new(address, self.provider.clone());
+        let token0 = pool.token_0().call().await?;
+        let token1 = pool.token_1().call().await?;
+        let fee_pips = pool.fee().call().await?;
+        let tick_spacing = pool.tick_spacing().call().await?;
+        let state = self.load_state(&pool, fee_pips, tick_spacing, BlockNumber::Latest.into()).await?;
+
+        let mut pool = DexPool {
+            address,
+            dex: self.dex.clone(),
+            token0: fetch_token(self.provider.clone(), token0).await?,
+            token1: fetch_token(self.provider.clone(), token1).await?,
+            reserve0: U256::zero(),
+            reserve1: U256::zero(),
+            fee_bps: fee_pips / 100,
+            block: U64::zero(),
+            state: PoolState::UniswapV3(state),
+        };
+        update_virtual_reserves(&mut pool);
+        Ok(pool)
+    }
+
+    // Price, in-range liquidity and every initialized tick in the surrounding
+    // bitmap words, as of `block`
+    async fn load_state(
+        &self,
+        pool: &UniswapV3Pool<M>,
+        fee_pips: u32,
+        tick_spacing: i32,
+        block: BlockId,
+    ) -> Result<UniswapV3State> {
+        let (sqrt_price_x96, tick, ..) = pool.slot_0().block(block).call().await?;
+        let liquidity = pool.liquidity().block(block).call().await?;
+
+        let word = compress(tick, tick_spacing) >> 8;
+        let mut ticks = BTreeMap::new();
+        for position in word - BITMAP_WORDS..=word + BITMAP_WORDS {
+            let bitmap = pool.tick_bitmap(position as i16).block(block).call().await?;
+            for bit in 0..256 {
+                if !bitmap.bit(bit) {
+                    continue;
+                }
+                let initialized = (position * 256 + bit as i32) * tick_spacing;
+                let (_, liquidity_net, ..) = pool.ticks(initialized).block(block).call().await?;
+                ticks.insert(initialized, liquidity_net);
+            }
+        }
+
+        Ok(UniswapV3State {
+            sqrt_price_x96,
+            tick,
+            liquidity,
+            tick_spacing,
+            fee_pips,
+            ticks,
+            tick_lower_bound: ((word - BITMAP_WORDS) * 256 * tick_spacing).max(MIN_TICK),
+            tick_upper_bound: ((word + BITMAP_WORDS + 1) * 256 * tick_spacing).min(MAX_TICK),
+        })
+    }
+}
+
+#[async_trait]
+impl<M: Middleware + 'static> DexHandler for UniswapV3Handler<M> {
+    fn dex_type(&self) -> DexType {
+        self.dex.clone()
+    }
+
+    async fn discover_pools(&self, tokens: &[Address]) -> Result<Vec<DexPool>> {
+        let factory = &UniswapV3Factory::new(self.factory, self.provider.clone());
+
+        prefetch_tokens(self.provider.clone(), tokens).await;
+
+        discover_pairs(token_pairs(tokens), |(a, b)| async move {
+            let mut pools = Vec::new();
+            for &fee in &self.fee_tiers {
+                let address = factory.get_pool(a, b, fee).call().await?;
+                if address.is_zero() {
+                    continue;
+                }
+                pools.push(self.load_v3_pool(address).await?);
+            }
+            Ok(pools)
+        })
+        .await
+    }
+
+    fn pool_created_filter(&self) -> Option<Filter> {
+        Some(
+            Filter::new()
+                .address(self.factory)
+                .event("PoolCreated(address,address,uint24,int24,address)"),
+        )
+    }
+
+    fn factory_deployed_at(&self) -> U64 {
+        self.deployed_at
+    }
+
+    // PoolCreated(token0 indexed, token1 indexed, fee indexed, tickSpacing, pool)
+    fn decode_pool_created(&self, log: &Log) -> Option<Address> {
+        (log.address == self.factory && log.data.len() >= 64).then(|| Address::from_slice(&log.data[44..64]))
+    }
+
+    async fn load_pool(&self, address: Address) -> Result<DexPool> {
+        self.load_v3_pool(address).await
+    }
+
+    async fn refresh_state(&self, pool: &mut DexPool, block: U64) -> Result<()> {
+        let PoolState::UniswapV3(state) = &pool.state else {
+            return Err(DexError::Unsupported(format!("{:?} is not a Uniswap V3 pool", pool.address)).into());
+        };
+        let contract = UniswapV3Pool::new(pool.address, self.provider.clone());
+        let state = self.load_state(&contract, state.fee_pips, state.tick_spacing, block.into()).await?;
+
+        pool.state = PoolState::UniswapV3(state);
+        update_virtual_reserves(pool);
+        Ok(())
+    }
+
+    async fn quote_out(&self, pool: &DexPool, token_in: Address, amount_in: U256) -> Result<U256> {
+        let PoolState::UniswapV3(state) = &pool.state else {
+            return Err(DexError::Unsupported(format!("{:?} is not a Uniswap V3 pool", pool.address)).into());
+        };
+        let zero_for_one = if token_in == pool.token0.address {
+            true
+        } else if token_in == pool.token1.address {
+            false
+        } else {
+            return Err(DexError::PoolNotFound(pool.address).into());
+        };
+
+        swap_exact_in(state, zero_for_one, amount_in)
+            .ok_or_else(|| DexError::InsufficientLiquidity(pool.address).into())
+    }
+
+    async fn quote_onchain(&self, pool: &DexPool, token_in: Address, amount_in: U256) -> Result<Option<U256>> {
+        let (Some(quoter), PoolState::UniswapV3(state)) = (&self.quoter, &pool.state) else {
+            return Ok(None);
+        };
+        let token_out = pool
+            .other(token_in)
+            .ok_or(DexError::PoolNotFound(pool.address))?
+            .address;
+        let amount_out = quoter
+            .quote_exact_input_single(token_in, token_out, state.fee_pips, amount_in)
+            .await?;
+        Ok(Some(amount_out))
+    }
+
+    // Pool swap; the executor pays in uniswapV3SwapCallback (pancakeV3SwapCallback
+    // on PancakeSwap) and checks `min_out` itself
+    fn build_swap_calldata(
+        &self,
+        pool: &DexPool,
+        token_in: Address,
+        amount_in: U256,
+        _min_out: U256,
+        recipient: Address,
+    ) -> Result<Bytes> {
+        let zero_for_one = if token_in == pool.token0.address {
+            true
+        } else if token_in == pool.token1.address {
+            false
+        } else {
+            return Err(DexError::PoolNotFound(pool.address).into());
+        };
+        let limit = if zero_for_one {
+            U256::from(MIN_SQRT_RATIO) + 1
+        } else {
+            U256::from_dec_str(MAX_SQRT_RATIO).unwrap() - 1
+        };
+
+        Ok(SwapCall {
+            recipient,
+            zero_for_one,
+            amount_specified: I256::from_raw(amount_in),
+            sqrt_price_limit_x96: limit,
+            data: Bytes::new(),
+        }
+        .encode()
+        .into())
+    }
+}
+
+// Reserves a constant-product pool would need to match the in-range liquidity;
+// only used for sizing, quotes go through the tick math
+pub fn update_virtual_reserves(pool: &mut DexPool) {
+    let state = match &pool.state {
+        PoolState::UniswapV3(state) => state,
+        PoolState::UniswapV4(v4) => &v4.pool,
+        _ => return,
+    };
+    let liquidity = U256::from(state.liquidity);
+    if !state.sqrt_price_x96.is_zero() {
+        pool.reserve0 = mul_div(liquidity, q96(), state.sqrt_price_x96).unwrap_or_default();
+        pool.reserve1 = mul_div(liquidity, state.sqrt_price_x96, q96()).unwrap_or_default();
+    }
+}
+
+// Tick index divided by spacing, rounding towards negative infinity
+pub(crate) fn compress(tick: i32, tick_spacing: i32) -> i32 {
+    tick.div_euclid(tick_spacing)
+}
+
+// Exact-input swap across initialized ticks, as UniswapV3Pool.swap computes it.
+// None if the swap runs past the ticks loaded into `state`.
+pub fn swap_exact_in(state: &UniswapV3State, zero_for_one: bool, amount_in: U256) -> Option<U256> {
+    simulate_swap(state, zero_for_one, amount_in).map(|swap| swap.amount_out)
+}
+
+// The same swap, moving `state` to where the pool ends up. `tick` is kept in the
+// final initialized range rather than recomputed from the price, which is all
+// later swaps need.
+pub fn apply_swap_exact_in(state: &mut UniswapV3State, zero_for_one: bool, amount_in: U256) -> Option<U256> {
+    let swap = simulate_swap(state, zero_for_one, amount_in)?;
+    state.sqrt_price_x96 = swap.sqrt_price;
+    state.tick = swap.tick;
+    state.liquidity = swap.liquidity;
+    Some(swap.amount_out)
+}
+
+struct SwapResult {
+    amount_out: U256,
+    sqrt_price: U256,
+    tick: i32,
+    liquidity: u128,
+}
+
+fn simulate_swap(state: &UniswapV3State, zero_for_one: bool, amount_in: U256) -> Option<SwapResult> {
+    let mut sqrt_price = state.sqrt_price_x96;
+    let mut tick = state.tick;
+    let mut liquidity = state.liquidity;
+    let mut remaining = amount_in;
+    let mut amount_out = U256::zero();
+
+    while !remaining.is_zero() {
+        let next = if zero_for_one {
+            state.ticks.range(..=tick).next_back()
+        } else {
+            state.ticks.range(tick + 1..).next()
+        };
+        let target_tick = match next {
+            Some((t, _)) => *t,
+            None if zero_for_one => state.tick_lower_bound,
+            None => state.tick_upper_bound,
+        };
+        let sqrt_target = sqrt_ratio_at_tick(target_tick);
+
+        let step = compute_swap_step(sqrt_price, sqrt_target, liquidity, remaining, state.fee_pips)?;
+        remaining = remaining.checked_sub(step.amount_in.checked_add(step.fee_amount)?)?;
+        amount_out = amount_out.checked_add(step.amount_out)?;
+        sqrt_price = step.sqrt_price_next;
+
+        if sqrt_price != sqrt_target {
+            break;
+        }
+        // Reached the end of what we know about the pool
+        let (crossed, liquidity_net) = next?;
+        let delta = if zero_for_one { -*liquidity_net } else { *liquidity_net };
+        liquidity = if delta < 0 {
+            liquidity.checked_sub(delta.unsigned_abs())?
+        } else {
+            liquidity.checked_add(delta as u128)?
+        };
+        tick = if zero_for_one { crossed - 1 } else { *crossed };
+    }
+
+    Some(SwapResult {
+        amount_out,
+        sqrt_price,
+        tick,
+        liquidity,
+    })
+}
+
+struct SwapStep {
+    sqrt_price_next: U256,
+    amount_in: U256,
+    amount_out: U256,
+    fee_amount: U256,
+}
+
+// SwapMath.computeSwapStep for exact input
+fn compute_swap_step(
+    sqrt_current: U256,
+    sqrt_target: U256,
+    liquidity: u128,
+    remaining: U256,
+    fee_pips: u32,
+) -> Option<SwapStep> {
+    let zero_for_one = sqrt_current >= sqrt_target;
+    let remaining_less_fee = mul_div(remaining, U256::from(FEE_DENOMINATOR - fee_pips), U256::from(FEE_DENOMINATOR))?;
+
+    let max_in = if zero_for_one {
+        amount0_delta(sqrt_target, sqrt_current, liquidity, true)?
+    } else {
+        amount1_delta(sqrt_current, sqrt_target, liquidity, true)?
+    };
+    let sqrt_price_next = if remaining_less_fee >= max_in {
+        sqrt_target
+    } else {
+        next_sqrt_price_from_input(sqrt_current, liquidity, remaining_less_fee, zero_for_one)?
+    };
+    let reached_target = sqrt_price_next == sqrt_target;
+
+    let (amount_in, amount_out) = if zero_for_one {
+        (
+            if reached_target { max_in } else { amount0_delta(sqrt_price_next, sqrt_current, liquidity, true)? },
+            amount1_delta(sqrt_price_next, sqrt_current, liquidity, false)?,
+        )
+    } else {
+        (
+            if reached_target { max_in } else { amount1_delta(sqrt_current, sqrt_price_next, liquidity, true)? },
+            amount0_delta(sqrt_current, sqrt_price_next, liquidity, false)?,
+        )
+    };
+
+    let fee_amount = if reached_target {
+        mul_div_up(amount_in, U256::from(fee_pips), U256::from(FEE_DENOMINATOR - fee_pips))?
+    } else {
+        remaining.checked_sub(amount_in)?
+    };
+
+    Some(SwapStep { sqrt_price_next, amount_in, amount_out, fee_amount })
+}
+
+fn next_sqrt_price_from_input(sqrt_price: U256, liquidity: u128, amount_in: U256, zero_for_one: bool) -> Option<U256> {
+    if amount_in.is_zero() {
+        return Some(sqrt_price);
+    }
+    let liquidity = U256::from(liquidity);
+    if zero_for_one {
+        // L * sqrtP / (L + amount * sqrtP), rounding up
+        let numerator = liquidity << 96;
+        let denominator = numerator.checked_add(amount_in.checked_mul(sqrt_price)?);
+        match denominator {
+            Some(denominator) => mul_div_up(numerator, sqrt_price, denominator),
+            None => div_round_up(numerator, (numerator / sqrt_price).checked_add(amount_in)?),
+        }
+    } else {
+        // sqrtP + amount / L, rounding down
+        sqrt_price.checked_add(mul_div(amount_in, q96(), liquidity)?)
+    }
+}
+
+fn amount0_delta(sqrt_a: U256, sqrt_b: U256, liquidity: u128, round_up: bool) -> Option<U256> {
+    let (lower, upper) = if sqrt_a < sqrt_b { (sqrt_a, sqrt_b) } else { (sqrt_b, sqrt_a) };
+    if lower.is_zero() {
+        return None;
+    }
+    let numerator1 = U256::from(liquidity) << 96;
+    let numerator2 = upper - lower;
+    if round_up {
+        div_round_up(mul_div_up(numerator1, numerator2, upper)?, lower)
+    } else {
+        Some(mul_div(numerator1, numerator2, upper)? / lower)
+    }
+}
+
+fn amount1_delta(sqrt_a: U256, sqrt_b: U256, liquidity: u128, round_up: bool) -> Option<U256> {
+    let (lower, upper) = if sqrt_a < sqrt_b { (sqrt_a, sqrt_b) } else { (sqrt_b, sqrt_a) };
+    if round_up {
+        mul_div_up(U256::from(liquidity), upper - lower, q96())
+    } else {
+        mul_div(U256::from(liquidity), upper - lower, q96())
+    }
+}
+
+// TickMath.getSqrtRatioAtTick
+pub fn sqrt_ratio_at_tick(tick: i32) -> U256 {
+    const MULTIPLIERS: [(u32, u128); 19] = [
+        (0x2, 0xfff97272373d413259a46990580e213a),
+        (0x4, 0xfff2e50f5f656932ef12357cf3c7fdcc),
+        (0x8, 0xffe5caca7e10e4e61c3624eaa0941cd0),
+        (0x10, 0xffcb9843d60f6159c9db58835c926644),
+        (0x20, 0xff973b41fa98c081472e6896dfb254c0),
+        (0x40, 0xff2ea16466c96a3843ec78b326b52861),
+        (0x80, 0xfe5dee046a99a2a811c461f1969c3053),
+        (0x100, 0xfcbe86c7900a88aedcffc83b479aa3a4),
+        (0x200, 0xf987a7253ac413176f2b074cf7815e54),
+        (0x400, 0xf3392b0822b70005940c7a398e4b70f3),
+        (0x800, 0xe7159475a2c29b7443b29c7fa6e889d9),
+        (0x1000, 0xd097f3bdfd2022b8845ad8f792aa5825),
+        (0x2000, 0xa9f746462d870fdf8a65dc1f90e061e5),
+        (0x4000, 0x70d869a156d2a1b890bb3df62baf32f7),
+        (0x8000, 0x31be135f97d08fd981231505542fcfa6),
+        (0x10000, 0x9aa508b5b7a84e1c677de54f3e99bc9),
+        (0x20000, 0x5d6af8dedb81196699c329225ee604),
+        (0x40000, 0x2216e584f5fa1ea926041bedfe98),
+        (0x80000, 0x48a170391f7dc42444e8fa2),
+    ];
+
+    let tick = tick.clamp(MIN_TICK, MAX_TICK);
+    let abs_tick = tick.unsigned_abs();
+    let mut ratio = if abs_tick & 0x1 != 0 {
+        U256::from(0xfffcb933bd6fad37aa2d162d1a594001u128)
+    } else {
+        U256::one() << 128
+    };
+    for (bit, multiplier) in MULTIPLIERS {
+        if abs_tick & bit != 0 {
+            ratio = (ratio * U256::from(multiplier)) >> 128;
+        }
+    }
+    if tick > 0 {
+        ratio = U256::MAX / ratio;
+    }
+
+    // Q128.128 to Q64.96, rounding up
+    let round = if (ratio & U256::from(u32::MAX)).is_zero() { 0 } else { 1 };
+    (ratio >> 32) + round
+}
@@ -0,0 +1,445 @@
+use async_trait::async_trait;
+use dashmap::DashMap;
+use ethers::{
+    abi::{self, AbiEncode, Token as AbiToken},
+    prelude::*,
+    types::{Address, Bytes, Filter, Log, I256, U256, U64},
+    utils::keccak256,
+};
+use std::{collections::BTreeMap, sync::Arc};
+
+use super::{
+    concurrency::discover_pairs,
+    fetch_token, prefetch_tokens, token_pairs,
+    uniswap_v3::{
+        compress, swap_exact_in, update_virtual_reserves, BITMAP_WORDS, MAX_SQRT_RATIO, MAX_TICK, MIN_SQRT_RATIO,
+        MIN_TICK,
+    },
+    DexHandler,
+};
+use crate::{
+    errors::{DexError, Result},
+    models::{DexPool, DexType, PoolState, UniswapV3State, UniswapV4State},
+};
+
+abigen!(
+    StateView,
+    r#"[
+        function getSlot0(bytes32 poolId) external view returns (uint160, int24, uint24, uint24)
+        function getLiquidity(bytes32 poolId) external view returns (uint128)
+        function getTickBitmap(bytes32 poolId, int16 tick) external view returns (uint256)
+        function getTickLiquidity(bytes32 poolId, int24 tick) external view returns (uint128, int128)
+    ]"#;
+
+    V4Quoter,
+    r#"[
+        struct PoolKey { address currency0; address currency1; uint24 fee; int24 tickSpacing; address hooks; }
+        struct QuoteExactSingleParams { PoolKey poolKey; bool zeroForOne; uint128 exactAmount; bytes hookData; }
+        function quoteExactInputSingle(QuoteExactSingleParams params) external returns (uint256, uint256)
+    ]"#;
+
+    PoolManager,
+    r#"[
+        struct PoolKey { address currency0; address currency1; uint24 fee; int24 tickSpacing; address hooks; }
+        struct SwapParams { bool zeroForOne; int256 amountSpecified; uint160 sqrtPriceLimitX96; }
+        function swap(PoolKey key, SwapParams params, bytes hookData) external returns (int256)
+    ]"#;
+);
+
+// Mainnet singleton, its read-only lens and the V4 quoter
+pub const UNISWAP_V4_POOL_MANAGER: &str = "0x000000000004444c5dc75cB358380D2e3dE08A90";
+pub const UNISWAP_V4_STATE_VIEW: &str = "0x7fFE42C4a5DEeA5b0feC41C94C136Cf115597227";
+pub const UNISWAP_V4_QUOTER: &str = "0x52F0E24D1c21C8A0cB1e5a5dD6198556BD9E1203";
+// A little before the PoolManager's deployment on mainnet
+const UNISWAP_V4_DEPLOYED_AT: u64 = 21_600_000;
+
+// Fee and tick spacing pairs tried for each token pair; pools with other keys
+// are only found through their Initialize events
+const STANDARD_KEYS: [(u32, i32); 4] = [(100, 1), (500, 10), (3000, 60), (10_000, 200)];
+
+// A hook's permissions are the low bits of its address. These let it run code
+// on, or take a cut of, every swap.
+const BEFORE_SWAP: u16 = 1 << 7;
+const AFTER_SWAP: u16 = 1 << 6;
+const BEFORE_SWAP_RETURNS_DELTA: u16 = 1 << 3;
+const AFTER_SWAP_RETURNS_DELTA: u16 = 1 << 2;
+const SWAP_HOOK_FLAGS: u16 = BEFORE_SWAP | AFTER_SWAP | BEFORE_SWAP_RETURNS_DELTA | AFTER_SWAP_RETURNS_DELTA;
+
+// Whether a hook contract runs when the pool is swapped through
+pub fn hooks_swaps(hooks: Address) -> bool {
+    let bits = u16::from_be_bytes([hooks.0[18], hooks.0[19]]);
+    bits & SWAP_HOOK_FLAGS != 0
+}
+
+// Which hooked pools are scanned. A swap hook can change the price, fee or
+// output of a swap after it was quoted, so by default only pools without one
+// are, plus hook contracts trusted by address.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum HookPolicy {
+    ExcludeSwapHooks { trusted: Vec<Address> },
+    AllowAll,
+}
+
+impl Default for HookPolicy {
+    fn default() -> Self {
+        HookPolicy::ExcludeSwapHooks { trusted: Vec::new() }
+    }
+}
+
+impl HookPolicy {
+    pub fn permits(&self, hooks: Address) -> bool {
+        match self {
+            HookPolicy::AllowAll => true,
+            HookPolicy::ExcludeSwapHooks { trusted } => !hooks_swaps(hooks) || trusted.contains(&hooks),
+        }
+    }
+}
+
+// "exclude", "all", or the trusted hook addresses, comma separated
+impl std::str::FromStr for HookPolicy {
+    type Err = String;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s.trim() {
+            "" | "exclude" => Ok(HookPolicy::default()),
+            "all" => Ok(HookPolicy::AllowAll),
+            list => {
+                let trusted = list
+                    .split(',')
+                    .map(|a| a.trim().parse().map_err(|_| format!("invalid hook address {}", a.trim())))
+                    .collect::<std::result::Result<_, _>>()?;
+                Ok(HookPolicy::ExcludeSwapHooks { trusted })
+            }
+        }
+    }
+}
+
+// Uniswap V4: every pool lives in the PoolManager singleton, keyed by
+// keccak(PoolKey), and is read through StateView. Pools have no address of
+// their own, so the last 20 bytes of the pool id stand in for one. Pricing is
+// V3's tick math at the pool's current LP fee.
+pub struct UniswapV4Handler<M> {
+    provider: Arc<M>,
+    pool_manager: Address,
+    state_view: Address,
+    quoter: Option<Address>,
+    hook_policy: HookPolicy,
+    deployed_at: U64,
+    // Keys announced by Initialize events, until the pool is loaded
+    announced: DashMap<Address, PoolKey>,
+}
+
+impl<M: Middleware + 'static> UniswapV4Handler<M> {
+    pub fn new(provider: Arc<M>, pool_manager: Address, state_view: Address) -> Self {
+        Self {
+            provider,
+            pool_manager,
+            state_view,
+            quoter: None,
+            hook_policy: HookPolicy::default(),
+            deployed_at: U64::zero(),
+            announced: DashMap::new(),
+        }
+    }
+
+    pub fn mainnet(provider: Arc<M>) -> Self {
+        Self::new(provider, UNISWAP_V4_POOL_MANAGER.parse().unwrap(), UNISWAP_V4_STATE_VIEW.parse().unwrap())
+            .with_quoter(UNISWAP_V4_QUOTER.parse().unwrap())
+            .with_deployment_block(UNISWAP_V4_DEPLOYED_AT)
+    }
+
+    // The same contracts on another chain or a fork. Their deployment block
+    // isn't known, so a backfill starts from genesis.
+    pub fn with_contracts(mut self, pool_manager: Address, state_view: Address) -> Self {
+        if pool_manager != self.pool_manager {
+            self.pool_manager = pool_manager;
+            self.deployed_at = U64::zero();
+        }
+        self.state_view = state_view;
+        self
+    }
+
+    // Skip the blocks before the PoolManager existed when backfilling
+    pub fn with_deployment_block(mut self, block: u64) -> Self {
+        self.deployed_at = U64::from(block);
+        self
+    }
+
+    pub fn with_quoter(mut self, quoter: Address) -> Self {
+        self.quoter = Some(quoter);
+        self
+    }
+
+    pub fn without_quoter(mut self) -> Self {
+        self.quoter = None;
+        self
+    }
+
+    pub fn with_hook_policy(mut self, policy: HookPolicy) -> Self {
+        self.hook_policy = policy;
+        self
+    }
+
+    fn state_view(&self) -> StateView<M> {
+        StateView::new(self.state_view, self.provider.clone())
+    }
+
+    async fn load_v4_pool(&self, key: &PoolKey) -> Result<DexPool> {
+        let pool_id = pool_id(key);
+        let pool = self.load_state(pool_id, key.tick_spacing, BlockNumber::Latest.into()).await?;
+
+        let mut dex_pool = DexPool {
+            address: pool_address(pool_id),
+            dex: DexType::UniswapV4,
+            token0: fetch_token(self.provider.clone(), key.currency_0).await?,
+            token1: fetch_token(self.provider.clone(), key.currency_1).await?,
+            reserve0: U256::zero(),
+            reserve1: U256::zero(),
+            fee_bps: pool.fee_pips / 100,
+            block: U64::zero(),
+            state: PoolState::UniswapV4(UniswapV4State {
+                pool_id,
+                fee: key.fee,
+                tick_spacing: key.tick_spacing,
+                hooks: key.hooks,
+                pool,
+            }),
+        };
+        update_virtual_reserves(&mut dex_pool);
+        Ok(dex_pool)
+    }
+
+    // Price, LP fee, in-range liquidity and the initialized ticks in the
+    // surrounding bitmap words as of `block`, as for a V3 pool
+    async fn load_state(&self, pool_id: [u8; 32], tick_spacing: i32, block: BlockId) -> Result<UniswapV3State> {
+        let view = self.state_view();
+        let (sqrt_price_x96, tick, _, lp_fee) = view.get_slot_0(pool_id).block(block).call().await?;
+        if sqrt_price_x96.is_zero() {
+            return Err(DexError::Unsupported(format!("V4 pool {} is not initialized", hex::encode(pool_id))).into());
+        }
+        let liquidity = view.get_liquidity(pool_id).block(block).call().await?;
+
+        let word = compress(tick, tick_spacing) >> 8;
+        let mut ticks = BTreeMap::new();
+        for position in word - BITMAP_WORDS..=word + BITMAP_WORDS {
+            let bitmap = view.get_tick_bitmap(pool_id, position as i16).block(block).call().await?;
+            for bit in 0..256 {
+                if !bitmap.bit(bit) {
+                    continue;
+                }
+                let initialized = (position * 256 + bit as i32) * tick_spacing;
+                let (_, liquidity_net) = view.get_tick_liquidity(pool_id, initialized).block(block).call().await?;
+                ticks.insert(initialized, liquidity_net);
+            }
+        }
+
+        Ok(UniswapV3State {
+            sqrt_price_x96,
+            tick,
+            liquidity,
+            tick_spacing,
+            // The protocol fee is off on every pool so far, so the LP fee is the whole fee
+            fee_pips: lp_fee,
+            ticks,
+            tick_lower_bound: ((word - BITMAP_WORDS) * 256 * tick_spacing).max(MIN_TICK),
+            tick_upper_bound: ((word + BITMAP_WORDS + 1) * 256 * tick_spacing).min(MAX_TICK),
+        })
+    }
+}
+
+#[async_trait]
+impl<M: Middleware + 'static> DexHandler for UniswapV4Handler<M> {
+    fn dex_type(&self) -> DexType {
+        DexType::UniswapV4
+    }
+
+    // Standard keys without hooks, or with a trusted hook
+    async fn discover_pools(&self, tokens: &[Address]) -> Result<Vec<DexPool>> {
+        let view = self.state_view();
+        let mut hooks = vec![Address::zero()];
+        if let HookPolicy::ExcludeSwapHooks { trusted } = &self.hook_policy {
+            hooks.extend(trusted);
+        }
+        let (view, hooks) = (&view, &hooks);
+
+        prefetch_tokens(self.provider.clone(), tokens).await;
+
+        discover_pairs(token_pairs(tokens), |(a, b)| async move {
+            let (currency_0, currency_1) = if a < b { (a, b) } else { (b, a) };
+            let mut pools = Vec::new();
+            for &hook in hooks {
+                for (fee, tick_spacing) in STANDARD_KEYS {
+                    let key = PoolKey {
+                        currency_0,
+                        currency_1,
+                        fee,
+                        tick_spacing,
+                        hooks: hook,
+                    };
+                    let (sqrt_price_x96, ..) = view.get_slot_0(pool_id(&key)).call().await?;
+                    if sqrt_price_x96.is_zero() {
+                        continue;
+                    }
+                    pools.push(self.load_v4_pool(&key).await?);
+                }
+            }
+            Ok(pools)
+        })
+        .await
+    }
+
+    fn pool_created_filter(&self) -> Option<Filter> {
+        Some(
+            Filter::new()
+                .address(self.pool_manager)
+                .event("Initialize(bytes32,address,address,uint24,int24,address,uint160,int24)"),
+        )
+    }
+
+    fn factory_deployed_at(&self) -> U64 {
+        self.deployed_at
+    }
+
+    // Initialize(id indexed, currency0 indexed, currency1 indexed, fee, tickSpacing,
+    // hooks, sqrtPriceX96, tick). Pools of native ETH (currency 0) and pools the
+    // hook policy excludes are skipped.
+    fn decode_pool_created(&self, log: &Log) -> Option<Address> {
+        if log.address != self.pool_manager || log.topics.len() < 4 || log.data.len() < 96 {
+            return None;
+        }
+        let key = PoolKey {
+            currency_0: Address::from(log.topics[2]),
+            currency_1: Address::from(log.topics[3]),
+            fee: U256::from_big_endian(&log.data[0..32]).low_u32(),
+            tick_spacing: i32::from_be_bytes(log.data[60..64].try_into().ok()?),
+            hooks: Address::from_slice(&log.data[76..96]),
+        };
+        if key.currency_0.is_zero() || !self.hook_policy.permits(key.hooks) {
+            return None;
+        }
+        let address = pool_address(pool_id(&key));
+        self.announced.insert(address, key);
+        Some(address)
+    }
+
+    async fn load_pool(&self, address: Address) -> Result<DexPool> {
+        let Some((_, key)) = self.announced.remove(&address) else {
+            return Err(DexError::Unsupported(format!("V4 pool key for {:?} is unknown", address)).into());
+        };
+        self.load_v4_pool(&key).await
+    }
+
+    async fn refresh_state(&self, pool: &mut DexPool, block: U64) -> Result<()> {
+        let PoolState::UniswapV4(v4) = &pool.state else {
+            return Err(DexError::Unsupported(format!("{:?} is not a Uniswap V4 pool", pool.address)).into());
+        };
+        let state = self.load_state(v4.pool_id, v4.tick_spacing, block.into()).await?;
+
+        pool.fee_bps = state.fee_pips / 100;
+        if let PoolState::UniswapV4(v4) = &mut pool.state {
+            v4.pool = state;
+        }
+        update_virtual_reserves(pool);
+        Ok(())
+    }
+
+    async fn quote_out(&self, pool: &DexPool, token_in: Address, amount_in: U256) -> Result<U256> {
+        let PoolState::UniswapV4(v4) = &pool.state else {
+            return Err(DexError::Unsupported(format!("{:?} is not a Uniswap V4 pool", pool.address)).into());
+        };
+        if !pool.has_token(token_in) {
+            return Err(DexError::PoolNotFound(pool.address).into());
+        }
+
+        swap_exact_in(&v4.pool, token_in == pool.token0.address, amount_in)
+            .ok_or_else(|| DexError::InsufficientLiquidity(pool.address).into())
+    }
+
+    // The V4 quoter runs the swap through the PoolManager, hooks included
+    async fn quote_onchain(&self, pool: &DexPool, token_in: Address, amount_in: U256) -> Result<Option<U256>> {
+        let (Some(quoter), PoolState::UniswapV4(v4)) = (self.quoter, &pool.state) else {
+            return Ok(None);
+        };
+        let exact_amount: u128 = amount_in
+            .try_into()
+            .map_err(|_| DexError::InsufficientLiquidity(pool.address))?;
+        let params = QuoteExactSingleParams {
+            pool_key: pool_key(pool, v4),
+            zero_for_one: token_in == pool.token0.address,
+            exact_amount,
+            hook_data: Bytes::new(),
+        };
+        let (amount_out, _) = V4Quoter::new(quoter, self.provider.clone())
+            .quote_exact_input_single(params)
+            .call()
+            .await?;
+        Ok(Some(amount_out))
+    }
+
+    fn swap_target(&self, _pool: &DexPool) -> Address {
+        self.pool_manager
+    }
+
+    // PoolManager.swap; the executor makes this call from its unlockCallback,
+    // settles the input, takes the output and checks `min_out` itself
+    fn build_swap_calldata(
+        &self,
+        pool: &DexPool,
+        token_in: Address,
+        amount_in: U256,
+        _min_out: U256,
+        _recipient: Address,
+    ) -> Result<Bytes> {
+        let PoolState::UniswapV4(v4) = &pool.state else {
+            return Err(DexError::Unsupported(format!("{:?} is not a Uniswap V4 pool", pool.address)).into());
+        };
+        if !pool.has_token(token_in) {
+            return Err(DexError::PoolNotFound(pool.address).into());
+        }
+        let zero_for_one = token_in == pool.token0.address;
+        let limit = if zero_for_one {
+            U256::from(MIN_SQRT_RATIO) + 1
+        } else {
+            U256::from_dec_str(MAX_SQRT_RATIO).unwrap() - 1
+        };
+
+        Ok(SwapCall {
+            key: pool_key(pool, v4),
+            // Negative means exact input in V4
+            params: SwapParams {
+                zero_for_one,
+                amount_specified: -I256::from_raw(amount_in),
+                sqrt_price_limit_x96: limit,
+            },
+            hook_data: Bytes::new(),
+        }
+        .encode()
+        .into())
+    }
+}
+
+fn pool_key(pool: &DexPool, v4: &UniswapV4State) -> PoolKey {
+    PoolKey {
+        currency_0: pool.token0.address,
+        currency_1: pool.token1.address,
+        fee: v4.fee,
+        tick_spacing: v4.tick_spacing,
+        hooks: v4.hooks,
+    }
+}
+
+// keccak256(abi.encode(key)), as PoolIdLibrary.toId
+pub fn pool_id(key: &PoolKey) -> [u8; 32] {
+    keccak256(abi::encode(&[
+        AbiToken::Address(key.currency_0),
+        AbiToken::Address(key.currency_1),
+        AbiToken::Uint(key.fee.into()),
+        AbiToken::Int(I256::from(key.tick_spacing).into_raw()),
+        AbiToken::Address(key.hooks),
+    ]))
+}
+
+// Stand-in address for a pool id, what the registry and caches key pools by
+fn pool_address(pool_id: [u8; 32]) -> Address {
+    Address::from_slice(&pool_id[12..])
+}
@@ -0,0 +1,157 @@
+use ethers::{
+    providers::Middleware,
+    signers::Signer,
+    types::{Address, H256, I256, U256},
+};
+use tracing::info;
+
+use crate::{
+    errors::{BotError, Result},
+    fork::Fork,
+    Config, LiquidationBot, LiquidationTarget,
+};
+
+#[derive(Debug)]
+struct DryRunOutcome {
+    user: Address,
+    protocol: String,
+    predicted: U256,
+    realized: I256,
+    gas_used: U256,
+    tx_hash: Option<H256>,
+    error: Option<String>,
+}
+
+// Detect opportunities at `fork_block` on a local fork, execute each one there,
+// and report realized profit against the evaluator's prediction.
+pub async fn run(mut config: Config, fork_url: &str, fork_block: u64) -> Result<()> {
+    info!(fork_block, "🧪 Dry run on fork");
+
+    let fork = Fork::spawn(fork_url, Some(fork_block)).await?;
+    config.primary_rpc = fork.endpoint();
+    config.backup_rpc = fork.endpoint();
+    config.ws_endpoint = fork.ws_endpoint();
+
+    let bot = LiquidationBot::new(config).await?;
+
+    // Fund the signer so gas never masks a real failure
+    fork.set_balance(bot.wallet.address(), U256::exp10(20)).await?;
+
+    bot.scan_aave_positions().await?;
+    let targets = bot.rank_targets().await;
+    info!(candidates = targets.len(), block = fork_block, "🔍 Candidates found");
+
+    let mut outcomes = Vec::with_capacity(targets.len());
+    for (target, _) in targets {
+        outcomes.push(execute_on_fork(&bot, target).await);
+    }
+
+    report(&outcomes);
+    Ok(())
+}
+
+async fn execute_on_fork(bot: &LiquidationBot, target: LiquidationTarget) -> DryRunOutcome {
+    let mut outcome = DryRunOutcome {
+        user: target.user,
+        protocol: target.protocol.clone(),
+        predicted: target.expected_profit,
+        realized: I256::zero(),
+        gas_used: U256::zero(),
+        tx_hash: None,
+        error: None,
+    };
+
+    let profit_before = match bot.executor.total_profit().call().await {
+        Ok(p) => p,
+        Err(e) => {
+            outcome.error = Some(BotError::from(e).to_string());
+            return outcome;
+        }
+    };
+
+    match bot.execute_liquidation_standard(target).await {
+        Ok(tx_hash) => {
+            outcome.tx_hash = Some(tx_hash);
+
+            let gas_cost = match bot.provider.get_transaction_receipt(tx_hash).await {
+                Ok(Some(receipt)) => {
+                    let gas_used = receipt.gas_used.unwrap_or_default();
+                    outcome.gas_used = gas_used;
+                    gas_used * receipt.effective_gas_price.unwrap_or_default()
+                }
+                _ => U256::zero(),
+            };
+
+            let profit_after = bot
+                .executor
+                .total_profit()
+                .call()
+                .await
+                .unwrap_or(profit_before);
+
+            outcome.realized = I256::from_raw(profit_after - profit_before) - I256::from_raw(gas_cost);
+        }
+        Err(e) => outcome.error = Some(e.to_string()),
+    }
+
+    outcome
+}
+
+fn report(outcomes: &[DryRunOutcome]) {
+    println!();
+    println!(
+        "{:<44} {:<10} {:>14} {:>14} {:>10} {:>10}",
+        "user", "protocol", "predicted", "realized", "delta %", "gas"
+    );
+
+    let mut total_predicted = I256::zero();
+    let mut total_realized = I256::zero();
+
+    for o in outcomes {
+        let predicted = I256::from_raw(o.predicted);
+        total_predicted += predicted;
+
+        match &o.error {
+            Some(e) => println!(
+                "{:<44} {:<10} {:>14} {:>14} {:>10} {:>10}  ❌ {}",
+                format!("{:?}", o.user), o.protocol, format_units(predicted), "-", "-", "-", e
+            ),
+            None => {
+                total_realized += o.realized;
+                println!(
+                    "{:<44} {:<10} {:>14} {:>14} {:>10} {:>10}  {:?}",
+                    format!("{:?}", o.user),
+                    o.protocol,
+                    format_units(predicted),
+                    format_units(o.realized),
+                    delta_pct(predicted, o.realized),
+                    o.gas_used,
+                    o.tx_hash.unwrap_or_default(),
+                );
+            }
+        }
+    }
+
+    let executed = outcomes.iter().filter(|o| o.error.is_none()).count();
+    println!();
+    println!(
+        "📊 Executed {}/{} | predicted {} | realized {} | delta {}",
+        executed,
+        outcomes.len(),
+        format_units(total_predicted),
+        format_units(total_realized),
+        delta_pct(total_predicted, total_realized),
+    );
+}
+
+fn format_units(value: I256) -> String {
+    ethers::utils::format_units(value, 18).unwrap_or_else(|_| value.to_string())
+}
+
+fn delta_pct(predicted: I256, realized: I256) -> String {
+    if predicted.is_zero() {
+        return "-".to_string();
+    }
+    let bps = (realized - predicted) * I256::from(10_000) / predicted;
+    format!("{:.2}", bps.as_i64() as f64 / 100.0)
+}
@@ -0,0 +1,270 @@
+use ethers::{
+    contract::ContractError,
+    providers::{Middleware, ProviderError as RpcError},
+    types::{Address, TransactionReceipt, H256, U256},
+};
+use thiserror::Error;
+
+pub type Result<T> = std::result::Result<T, BotError>;
+
+// Top-level error for everything the bot does after startup. Each layer has
+// its own enum so callers can decide whether to retry, fall back, or halt
+// without string-matching on messages.
+#[derive(Debug, Error)]
+pub enum BotError {
+    #[error("provider: {0}")]
+    Provider(#[from] ProviderError),
+
+    #[error("dex: {0}")]
+    Dex(#[from] DexError),
+
+    #[error("simulation: {0}")]
+    Simulation(#[from] SimulationError),
+
+    #[error("execution: {0}")]
+    Execution(#[from] ExecutionError),
+
+    #[error("redis: {0}")]
+    Redis(#[from] redis::RedisError),
+
+    #[error("storage: {0}")]
+    Storage(#[from] sqlx::Error),
+
+    #[error("config: {0}")]
+    Config(String),
+
+    #[error("task: {0}")]
+    Task(#[from] tokio::task::JoinError),
+}
+
+#[derive(Debug, Error)]
+pub enum ProviderError {
+    #[error("request timed out")]
+    Timeout,
+
+    #[error("rate limited by {0}")]
+    RateLimited(String),
+
+    #[error("connection lost: {0}")]
+    Connection(String),
+
+    #[error("chain id mismatch: expected {expected}, got {actual}")]
+    ChainIdMismatch { expected: u64, actual: u64 },
+
+    #[error("rpc error: {0}")]
+    Rpc(String),
+}
+
+#[derive(Debug, Error)]
+pub enum DexError {
+    #[error("pool {0:?} not found")]
+    PoolNotFound(Address),
+
+    #[error("insufficient liquidity in pool {0:?}")]
+    InsufficientLiquidity(Address),
+
+    #[error("stale state for pool {0:?}")]
+    StaleState(Address),
+
+    #[error("math overflow in {0}")]
+    Overflow(&'static str),
+
+    #[error("unsupported dex: {0}")]
+    Unsupported(String),
+}
+
+#[derive(Debug, Error)]
+pub enum SimulationError {
+    #[error("reverted: {0}")]
+    Reverted(String),
+
+    #[error("unprofitable after simulation")]
+    Unprofitable,
+
+    #[error("backend unavailable: {0}")]
+    Backend(String),
+}
+
+#[derive(Debug, Error)]
+pub enum ExecutionError {
+    #[error("gas price {0} above cap")]
+    GasTooHigh(ethers::types::U256),
+
+    #[error("nonce too low")]
+    NonceTooLow,
+
+    #[error("replacement transaction underpriced")]
+    Underpriced,
+
+    #[error("insufficient funds for gas")]
+    InsufficientFunds,
+
+    #[error("transaction reverted: {0}")]
+    Reverted(String),
+
+    // Mined and reverted, with the gas it still paid for in wei
+    #[error("transaction {tx:?} reverted on chain")]
+    RevertedOnChain { tx: H256, gas_cost: U256 },
+
+    #[error("bundle rejected: {0}")]
+    BundleRejected(String),
+
+    #[error("order rejected: {0}")]
+    OrderRejected(String),
+
+    #[error("not included before deadline")]
+    NotIncluded,
+
+    #[error("public submission exposed to sandwiching, {0} at risk")]
+    SandwichExposed(ethers::types::U256),
+
+    #[error("signer: {0}")]
+    Signer(String),
+
+    #[error("refused by signing policy: {0}")]
+    Policy(String),
+}
+
+impl BotError {
+    // Transient failures where repeating the same call later can succeed
+    pub fn is_retryable(&self) -> bool {
+        match self {
+            BotError::Provider(e) => e.is_retryable(),
+            BotError::Dex(e) => e.is_retryable(),
+            BotError::Simulation(e) => e.is_retryable(),
+            BotError::Execution(e) => e.is_retryable(),
+            BotError::Redis(e) => e.is_timeout() || e.is_connection_dropped() || e.is_io_error(),
+            BotError::Storage(e) => matches!(e, sqlx::Error::PoolTimedOut | sqlx::Error::Io(_)),
+            BotError::Config(_) => false,
+            BotError::Task(_) => false,
+        }
+    }
+
+    // Failures that will keep happening until an operator intervenes
+    pub fn is_fatal(&self) -> bool {
+        match self {
+            BotError::Provider(e) => e.is_fatal(),
+            BotError::Dex(_) => false,
+            BotError::Simulation(_) => false,
+            BotError::Execution(e) => e.is_fatal(),
+            BotError::Redis(_) => false,
+            BotError::Storage(_) => false,
+            BotError::Config(_) => true,
+            BotError::Task(e) => e.is_panic(),
+        }
+    }
+}
+
+impl ProviderError {
+    pub fn is_retryable(&self) -> bool {
+        matches!(
+            self,
+            ProviderError::Timeout | ProviderError::RateLimited(_) | ProviderError::Connection(_)
+        )
+    }
+
+    pub fn is_fatal(&self) -> bool {
+        matches!(self, ProviderError::ChainIdMismatch { .. })
+    }
+}
+
+impl DexError {
+    pub fn is_retryable(&self) -> bool {
+        matches!(self, DexError::StaleState(_))
+    }
+}
+
+impl SimulationError {
+    pub fn is_retryable(&self) -> bool {
+        matches!(self, SimulationError::Backend(_))
+    }
+}
+
+impl ExecutionError {
+    pub fn is_retryable(&self) -> bool {
+        matches!(
+            self,
+            ExecutionError::NonceTooLow | ExecutionError::Underpriced | ExecutionError::NotIncluded
+        )
+    }
+
+    pub fn is_fatal(&self) -> bool {
+        matches!(self, ExecutionError::InsufficientFunds | ExecutionError::Signer(_))
+    }
+
+    pub fn reverted_on_chain(receipt: &TransactionReceipt) -> Self {
+        let gas_used = receipt.gas_used.unwrap_or_default();
+        let gas_price = receipt.effective_gas_price.unwrap_or_default();
+        ExecutionError::RevertedOnChain {
+            tx: receipt.transaction_hash,
+            gas_cost: gas_used.saturating_mul(gas_price),
+        }
+    }
+
+    // Node error messages are not standardised, so classify on the common substrings
+    fn from_message(msg: &str) -> Self {
+        let lower = msg.to_lowercase();
+        if lower.contains("signing policy") {
+            ExecutionError::Policy(msg.to_string())
+        } else if lower.contains("nonce too low") {
+            ExecutionError::NonceTooLow
+        } else if lower.contains("underpriced") {
+            ExecutionError::Underpriced
+        } else if lower.contains("insufficient funds") {
+            ExecutionError::InsufficientFunds
+        } else {
+            ExecutionError::Reverted(msg.to_string())
+        }
+    }
+}
+
+impl From<RpcError> for ProviderError {
+    fn from(err: RpcError) -> Self {
+        let msg = err.to_string();
+        let lower = msg.to_lowercase();
+
+        if lower.contains("timeout") || lower.contains("timed out") {
+            ProviderError::Timeout
+        } else if lower.contains("429") || lower.contains("rate limit") || lower.contains("too many requests") {
+            ProviderError::RateLimited(msg)
+        } else if lower.contains("connection") || lower.contains("websocket") || lower.contains("eof") {
+            ProviderError::Connection(msg)
+        } else {
+            ProviderError::Rpc(msg)
+        }
+    }
+}
+
+impl From<RpcError> for BotError {
+    fn from(err: RpcError) -> Self {
+        BotError::Provider(err.into())
+    }
+}
+
+impl<M: Middleware> From<ContractError<M>> for BotError {
+    fn from(err: ContractError<M>) -> Self {
+        if let Some(reason) = err.decode_revert::<String>() {
+            return ExecutionError::Reverted(reason).into();
+        }
+
+        match err {
+            ContractError::ProviderError { e } => BotError::Provider(e.into()),
+            ContractError::MiddlewareError { e } => {
+                BotError::Execution(ExecutionError::from_message(&e.to_string()))
+            }
+            other => BotError::Execution(ExecutionError::Reverted(other.to_string())),
+        }
+    }
+}
+
+impl From<ethers::signers::WalletError> for BotError {
+    fn from(err: ethers::signers::WalletError) -> Self {
+        BotError::Execution(ExecutionError::Signer(err.to_string()))
+    }
+}
+
+impl From<std::env::VarError> for BotError {
+    fn from(err: std::env::VarError) -> Self {
+        BotError::Config(err.to_string())
+    }
+}
@@ -0,0 +1,194 @@
+use ethers::{
+    prelude::*,
+    types::{Address, I256, U256},
+};
+use std::{
+    sync::Arc,
+    time::{Duration, Instant, SystemTime, UNIX_EPOCH},
+};
+use tokio::sync::Mutex;
+use tracing::{debug, warn};
+
+use crate::{
+    chains::ChainProfile,
+    coingecko::CoinGeckoClient,
+    etherscan::EtherscanClient,
+    errors::{ProviderError, Result},
+    price::UsdFeed,
+};
+
+#[derive(Debug, Clone)]
+pub struct EthPriceConfig {
+    // How long a median is served before the sources are asked again
+    pub ttl: Duration,
+    // Quotes a source last updated longer ago than this are left out
+    pub max_age: Duration,
+}
+
+impl EthPriceConfig {
+    pub fn from_env() -> Self {
+        let secs = |name: &str, default: u64| {
+            Duration::from_secs(std::env::var(name).ok().and_then(|v| v.parse().ok()).unwrap_or(default))
+        };
+        Self {
+            ttl: secs("ETH_PRICE_TTL_SECS", 30),
+            max_age: secs("ETH_PRICE_MAX_AGE_SECS", 7_200),
+        }
+    }
+}
+
+// One source's answer: USD per ETH with 18 decimals, and when the source
+// last updated it
+#[derive(Debug, Clone, Copy)]
+struct Quote {
+    price: U256,
+    updated_at: u64,
+}
+
+// The median the service last settled on
+#[derive(Debug, Clone, Copy)]
+pub struct EthPriceReading {
+    // USD per ETH, 18 decimals
+    pub price: U256,
+    // Fresh, non-zero quotes the median was taken over
+    pub sources: usize,
+    // Age of the oldest quote used when it was taken
+    pub oldest_quote: Duration,
+    pub fetched_at: Instant,
+}
+
+// ETH in USD as the median of Chainlink on chain, CoinGecko and Etherscan.
+// Zero, failed or stale quotes are dropped rather than averaged in, and with
+// none left the price is an error instead of a zero that would make every
+// profit look free of gas. On chains whose native token isn't ETH, that token
+// is priced instead, by Chainlink and CoinGecko alone.
+pub struct EthPriceService<M> {
+    provider: Arc<M>,
+    feed: Address,
+    // Shared with token pricing, so both count against one rate limit
+    coingecko: Option<Arc<CoinGeckoClient>>,
+    // Only on chains whose native token is ETH, the one Etherscan quotes
+    etherscan: Option<Arc<EtherscanClient>>,
+    eth_native: bool,
+    config: EthPriceConfig,
+    // Held while the sources are asked, so concurrent callers share one fetch
+    last: Mutex<Option<EthPriceReading>>,
+}
+
+impl<M: Middleware + 'static> EthPriceService<M> {
+    pub fn new(provider: Arc<M>, chain: &ChainProfile, config: EthPriceConfig) -> Self {
+        Self {
+            provider,
+            feed: chain.native_usd_feed,
+            coingecko: None,
+            etherscan: None,
+            eth_native: matches!(chain.chain_id, 1 | 10 | 8453 | 42161),
+            config,
+            last: Mutex::new(None),
+        }
+    }
+
+    pub fn with_coingecko(mut self, coingecko: Arc<CoinGeckoClient>) -> Self {
+        self.coingecko = Some(coingecko);
+        self
+    }
+
+    pub fn with_etherscan(mut self, etherscan: Arc<EtherscanClient>) -> Self {
+        self.etherscan = Some(etherscan).filter(|_| self.eth_native);
+        self
+    }
+
+    // USD per ETH with 18 decimals, from cache while it's within the TTL
+    pub async fn price(&self) -> Result<U256> {
+        Ok(self.reading().await?.price)
+    }
+
+    pub async fn reading(&self) -> Result<EthPriceReading> {
+        let mut last = self.last.lock().await;
+        if let Some(reading) = *last {
+            if reading.fetched_at.elapsed() < self.config.ttl {
+                return Ok(reading);
+            }
+        }
+
+        let now = SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or_default().as_secs();
+        let (chainlink, coingecko, etherscan) = tokio::join!(self.chainlink(), self.coingecko(), self.etherscan());
+        let mut quotes = Vec::new();
+        for (source, quote) in [("chainlink", chainlink), ("coingecko", coingecko), ("etherscan", etherscan)] {
+            match quote {
+                Ok(Some(quote)) if quote.price.is_zero() => warn!(source, "⚠️ ETH price source returned zero"),
+                Ok(Some(quote)) if now.saturating_sub(quote.updated_at) > self.config.max_age.as_secs() => {
+                    warn!(source, age_secs = now.saturating_sub(quote.updated_at), "⚠️ ETH price source is stale")
+                }
+                Ok(Some(quote)) => quotes.push(quote),
+                Ok(None) => {}
+                Err(e) => warn!(source, error = %e, "⚠️ ETH price source failed"),
+            }
+        }
+
+        // The last reading stays for `last`, so its age keeps showing
+        let Some(price) = median(quotes.iter().map(|q| q.price).collect()) else {
+            return Err(ProviderError::Rpc("no fresh ETH price from any source".to_string()).into());
+        };
+        let oldest = quotes.iter().map(|q| now.saturating_sub(q.updated_at)).max().unwrap_or_default();
+        let reading = EthPriceReading {
+            price,
+            sources: quotes.len(),
+            oldest_quote: Duration::from_secs(oldest),
+            fetched_at: Instant::now(),
+        };
+        debug!(price = %ethers::utils::format_ether(price), sources = reading.sources, "💲 ETH price");
+        *last = Some(reading);
+        Ok(reading)
+    }
+
+    // The last reading, however old, for reporting its freshness
+    pub async fn last(&self) -> Option<EthPriceReading> {
+        *self.last.lock().await
+    }
+
+    async fn chainlink(&self) -> Result<Option<Quote>> {
+        let feed = UsdFeed::new(self.feed, self.provider.clone());
+        let decimals = feed.decimals().call().await?;
+        let (_, answer, _, updated_at, _) = feed.latest_round_data().call().await?;
+        if answer <= I256::zero() {
+            return Ok(Some(Quote { price: U256::zero(), updated_at: updated_at.low_u64() }));
+        }
+        let price = answer.into_raw().saturating_mul(U256::exp10(18)) / U256::exp10(decimals as usize);
+        Ok(Some(Quote { price, updated_at: updated_at.low_u64() }))
+    }
+
+    async fn coingecko(&self) -> Result<Option<Quote>> {
+        let Some(coingecko) = &self.coingecko else {
+            return Ok(None);
+        };
+        // A listed coin with no usable price is a zero quote, reported as such
+        let price = coingecko.native_price().await?;
+        Ok(coingecko.native_id().map(|_| Quote {
+            price: price.map(|p| p.usd).unwrap_or_default(),
+            updated_at: price.map(|p| p.updated_at).unwrap_or_default(),
+        }))
+    }
+
+    async fn etherscan(&self) -> Result<Option<Quote>> {
+        let Some(etherscan) = &self.etherscan else {
+            return Ok(None);
+        };
+        let (price, updated_at) = etherscan.eth_price().await?;
+        Ok(Some(Quote { price, updated_at }))
+    }
+}
+
+// Middle value, or the mean of the two middle ones
+fn median(mut values: Vec<U256>) -> Option<U256> {
+    if values.is_empty() {
+        return None;
+    }
+    values.sort();
+    let mid = values.len() / 2;
+    if values.len() % 2 == 1 {
+        Some(values[mid])
+    } else {
+        Some((values[mid - 1] + values[mid]) / 2)
+    }
+}
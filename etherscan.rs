@@ -0,0 +1,334 @@
+use dashmap::DashMap;
+use ethers::{
+    types::{Address, U256},
+    utils::parse_units,
+};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use sqlx::{
+    sqlite::{SqliteConnectOptions, SqlitePool, SqlitePoolOptions},
+    Row,
+};
+use std::{
+    str::FromStr,
+    time::{Duration, Instant, SystemTime, UNIX_EPOCH},
+};
+use tokio::sync::Mutex;
+use tracing::debug;
+
+use crate::errors::{BotError, ProviderError, Result};
+
+pub const ETHERSCAN_API: &str = "https://api.etherscan.io/v2/api";
+
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(10);
+// Pause after Etherscan reports its rate limit reached
+const RATE_LIMIT_BACKOFF: Duration = Duration::from_secs(1);
+
+#[derive(Debug, Clone)]
+pub struct EtherscanConfig {
+    pub api: String,
+    // Etherscan V2 answers nothing without a key
+    pub api_key: Option<String>,
+    // SQLite file responses are kept in across restarts
+    pub cache_path: String,
+    // How long contract and token info is trusted; verified ABIs are kept for good
+    pub ttl: Duration,
+    // Requests per second the key allows
+    pub rate_per_sec: u32,
+}
+
+impl EtherscanConfig {
+    pub fn from_env() -> Self {
+        Self {
+            api: std::env::var("ETHERSCAN_API").unwrap_or_else(|_| ETHERSCAN_API.to_string()),
+            api_key: std::env::var("ETHERSCAN_API_KEY").ok().filter(|k| !k.trim().is_empty()),
+            cache_path: std::env::var("ETHERSCAN_CACHE").unwrap_or_else(|_| "etherscan.db".to_string()),
+            ttl: Duration::from_secs(
+                std::env::var("ETHERSCAN_CACHE_TTL_SECS")
+                    .ok()
+                    .and_then(|v| v.parse().ok())
+                    .unwrap_or(86_400),
+            ),
+            rate_per_sec: std::env::var("ETHERSCAN_RATE_PER_SEC")
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(5),
+        }
+    }
+}
+
+// What Etherscan knows about a contract's source
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ContractInfo {
+    pub name: String,
+    // Source published and matched on Etherscan
+    pub verified: bool,
+    // Etherscan detected a proxy
+    pub proxy: bool,
+    pub implementation: Option<Address>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct TokenInfo {
+    pub name: String,
+    pub symbol: String,
+    pub decimals: u8,
+    pub total_supply: U256,
+}
+
+#[derive(Debug, Clone)]
+struct Cached<T> {
+    value: T,
+    // Unix seconds
+    fetched_at: u64,
+}
+
+// Etherscan behind a rate limiter and a cache on disk, so ABI and
+// verification lookups are cheap enough for hot paths: after the first call a
+// contract is answered from memory, and after a restart from SQLite. Verified
+// ABIs never change and are kept for good; contract and token info is asked
+// again once it's older than the TTL.
+pub struct EtherscanClient {
+    http: reqwest::Client,
+    config: EtherscanConfig,
+    api_key: String,
+    chain_id: u64,
+    db: SqlitePool,
+    contracts: DashMap<Address, Cached<ContractInfo>>,
+    abis: DashMap<Address, String>,
+    tokens: DashMap<Address, Cached<Option<TokenInfo>>>,
+    // When the next request may go out; held while waiting for it
+    next_request: Mutex<Instant>,
+}
+
+impl EtherscanClient {
+    // None without an API key
+    pub async fn open(chain_id: u64, config: EtherscanConfig) -> Result<Option<Self>> {
+        let Some(api_key) = config.api_key.clone() else {
+            return Ok(None);
+        };
+        let options =
+            SqliteConnectOptions::from_str(&format!("sqlite://{}", config.cache_path))?.create_if_missing(true);
+        let db = SqlitePoolOptions::new().max_connections(2).connect_with(options).await?;
+
+        sqlx::query(
+            "CREATE TABLE IF NOT EXISTS contracts (
+                chain_id INTEGER NOT NULL,
+                address TEXT NOT NULL,
+                info TEXT NOT NULL,
+                abi TEXT,
+                fetched_at INTEGER NOT NULL,
+                PRIMARY KEY (chain_id, address)
+            )",
+        )
+        .execute(&db)
+        .await?;
+        // `info` is NULL for an address Etherscan has no token info for
+        sqlx::query(
+            "CREATE TABLE IF NOT EXISTS tokens (
+                chain_id INTEGER NOT NULL,
+                address TEXT NOT NULL,
+                info TEXT,
+                fetched_at INTEGER NOT NULL,
+                PRIMARY KEY (chain_id, address)
+            )",
+        )
+        .execute(&db)
+        .await?;
+
+        Ok(Some(Self {
+            http: reqwest::Client::builder().timeout(REQUEST_TIMEOUT).build().unwrap_or_default(),
+            config,
+            api_key,
+            chain_id,
+            db,
+            contracts: DashMap::new(),
+            abis: DashMap::new(),
+            tokens: DashMap::new(),
+            next_request: Mutex::new(Instant::now()),
+        }))
+    }
+
+    // Verification and proxy status of `address`
+    pub async fn contract(&self, address: Address) -> Result<ContractInfo> {
+        if let Some(cached) = self.contracts.get(&address).filter(|c| self.fresh(c.fetched_at)) {
+            return Ok(cached.value.clone());
+        }
+        let row = sqlx::query("SELECT info, abi, fetched_at FROM contracts WHERE chain_id = ? AND address = ?")
+            .bind(self.chain_id as i64)
+            .bind(format!("{:?}", address))
+            .fetch_optional(&self.db)
+            .await?;
+        if let Some(row) = row {
+            let fetched_at = row.try_get::<i64, _>("fetched_at")? as u64;
+            if let Some(abi) = row.try_get::<Option<String>, _>("abi")? {
+                self.abis.insert(address, abi);
+            }
+            if self.fresh(fetched_at) {
+                let info: ContractInfo = decode(row.try_get("info")?)?;
+                self.contracts.insert(address, Cached { value: info.clone(), fetched_at });
+                return Ok(info);
+            }
+        }
+
+        let body = self
+            .get(&[("module", "contract"), ("action", "getsourcecode"), ("address", &format!("{:?}", address))])
+            .await?;
+        let source = &body["result"][0];
+        let field = |name: &str| source[name].as_str().unwrap_or_default().to_string();
+        let abi = Some(field("ABI")).filter(|abi| abi.starts_with('['));
+        let info = ContractInfo {
+            name: field("ContractName"),
+            verified: !field("SourceCode").is_empty(),
+            proxy: field("Proxy") == "1",
+            implementation: field("Implementation").parse().ok().filter(|a: &Address| !a.is_zero()),
+        };
+
+        let fetched_at = now();
+        sqlx::query(
+            "INSERT INTO contracts (chain_id, address, info, abi, fetched_at) VALUES (?, ?, ?, ?, ?)
+             ON CONFLICT(chain_id, address) DO UPDATE SET
+                 info = excluded.info, abi = COALESCE(excluded.abi, contracts.abi), fetched_at = excluded.fetched_at",
+        )
+        .bind(self.chain_id as i64)
+        .bind(format!("{:?}", address))
+        .bind(encode(&info)?)
+        .bind(&abi)
+        .bind(fetched_at as i64)
+        .execute(&self.db)
+        .await?;
+        if let Some(abi) = abi {
+            self.abis.insert(address, abi);
+        }
+        debug!(contract = ?address, verified = info.verified, proxy = info.proxy, "📜 Contract source looked up");
+        self.contracts.insert(address, Cached { value: info.clone(), fetched_at });
+        Ok(info)
+    }
+
+    // The verified ABI of `address` as JSON; None for an unverified contract
+    pub async fn abi(&self, address: Address) -> Result<Option<String>> {
+        if let Some(abi) = self.abis.get(&address) {
+            return Ok(Some(abi.clone()));
+        }
+        // Loads a stored ABI, or fetches the source along with it
+        self.contract(address).await?;
+        Ok(self.abis.get(&address).map(|abi| abi.clone()))
+    }
+
+    // Name, symbol, decimals and supply of an ERC-20; None when Etherscan has no
+    // token at `address`
+    pub async fn token_info(&self, address: Address) -> Result<Option<TokenInfo>> {
+        if let Some(cached) = self.tokens.get(&address).filter(|c| self.fresh(c.fetched_at)) {
+            return Ok(cached.value.clone());
+        }
+        let row = sqlx::query("SELECT info, fetched_at FROM tokens WHERE chain_id = ? AND address = ?")
+            .bind(self.chain_id as i64)
+            .bind(format!("{:?}", address))
+            .fetch_optional(&self.db)
+            .await?;
+        if let Some(row) = row {
+            let fetched_at = row.try_get::<i64, _>("fetched_at")? as u64;
+            if self.fresh(fetched_at) {
+                let info = row
+                    .try_get::<Option<String>, _>("info")?
+                    .map(decode::<TokenInfo>)
+                    .transpose()?;
+                self.tokens.insert(address, Cached { value: info.clone(), fetched_at });
+                return Ok(info);
+            }
+        }
+
+        let body = self
+            .get(&[("module", "token"), ("action", "tokeninfo"), ("contractaddress", &format!("{:?}", address))])
+            .await?;
+        let info = body["result"][0].as_object().map(|token| {
+            let field = |name: &str| token.get(name).and_then(Value::as_str).unwrap_or_default().to_string();
+            TokenInfo {
+                name: field("tokenName"),
+                symbol: field("symbol"),
+                decimals: field("divisor").parse().unwrap_or_default(),
+                total_supply: U256::from_dec_str(&field("totalSupply")).unwrap_or_default(),
+            }
+        });
+
+        let fetched_at = now();
+        sqlx::query(
+            "INSERT INTO tokens (chain_id, address, info, fetched_at) VALUES (?, ?, ?, ?)
+             ON CONFLICT(chain_id, address) DO UPDATE SET info = excluded.info, fetched_at = excluded.fetched_at",
+        )
+        .bind(self.chain_id as i64)
+        .bind(format!("{:?}", address))
+        .bind(info.as_ref().map(encode).transpose()?)
+        .bind(fetched_at as i64)
+        .execute(&self.db)
+        .await?;
+        self.tokens.insert(address, Cached { value: info.clone(), fetched_at });
+        Ok(info)
+    }
+
+    // ETH in USD (18 decimals) and when Etherscan last updated it. Not cached;
+    // callers keep their own TTL.
+    pub async fn eth_price(&self) -> Result<(U256, u64)> {
+        let body = self.get_on(1, &[("module", "stats"), ("action", "ethprice")]).await?;
+        let field = |name: &str| body["result"][name].as_str().unwrap_or_default().to_string();
+        let price = parse_units(field("ethusd"), 18).map(U256::from).unwrap_or_default();
+        Ok((price, field("ethusd_timestamp").parse().unwrap_or_default()))
+    }
+
+    fn fresh(&self, fetched_at: u64) -> bool {
+        now().saturating_sub(fetched_at) < self.config.ttl.as_secs()
+    }
+
+    async fn get(&self, query: &[(&str, &str)]) -> Result<Value> {
+        self.get_on(self.chain_id, query).await
+    }
+
+    // One request on `chain_id`, spaced to stay within the key's rate
+    async fn get_on(&self, chain_id: u64, query: &[(&str, &str)]) -> Result<Value> {
+        {
+            let mut next = self.next_request.lock().await;
+            tokio::time::sleep_until((*next).into()).await;
+            *next = Instant::now() + Duration::from_secs(1) / self.config.rate_per_sec.max(1);
+        }
+
+        let chain_id = chain_id.to_string();
+        let body: Value = self
+            .http
+            .get(&self.config.api)
+            .query(&[("chainid", chain_id.as_str()), ("apikey", self.api_key.as_str())])
+            .query(query)
+            .send()
+            .await
+            .and_then(|r| r.error_for_status())
+            .map_err(|e| ProviderError::Rpc(format!("etherscan: {}", e)))?
+            .json()
+            .await
+            .map_err(|e| ProviderError::Rpc(format!("etherscan: {}", e)))?;
+
+        // Failures come back as 200 with status 0 and the reason in `result`
+        if body["status"].as_str() == Some("0") {
+            let reason = body["result"].as_str().unwrap_or_default().to_string();
+            if reason.to_lowercase().contains("rate limit") {
+                *self.next_request.lock().await = Instant::now() + RATE_LIMIT_BACKOFF;
+                return Err(ProviderError::RateLimited(format!("etherscan: {}", reason)).into());
+            }
+            // "No data found" and the like are answers, not errors
+            if !body["message"].as_str().unwrap_or_default().starts_with("No ") {
+                return Err(ProviderError::Rpc(format!("etherscan: {}", reason)).into());
+            }
+        }
+        Ok(body)
+    }
+}
+
+fn encode<T: Serialize>(value: &T) -> Result<String> {
+    serde_json::to_string(value).map_err(|e| BotError::Config(format!("etherscan cache encode: {}", e)))
+}
+
+fn decode<T: serde::de::DeserializeOwned>(value: String) -> Result<T> {
+    serde_json::from_str(&value).map_err(|e| BotError::Config(format!("etherscan cache decode: {}", e)))
+}
+
+fn now() -> u64 {
+    SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or_default().as_secs()
+}
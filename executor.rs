@@ -0,0 +1,397 @@
+use ethers::{
+    prelude::*,
+    types::{transaction::eip2718::TypedTransaction, Address, Bytes, H256, U256},
+};
+use ethers_flashbots::{BundleRequest, FlashbotsMiddleware};
+use std::sync::Arc;
+use tracing::{info, instrument, warn};
+use url::Url;
+
+use crate::{
+    bloxroute::BloxrouteClient,
+    builders::BuilderRegistry,
+    sandwich::{self, SandwichGuard},
+    dex::DexManager,
+    errors::{BotError, ExecutionError, ProviderError, Result, SimulationError},
+    gas::attach_access_list,
+    nonce::NonceManager,
+    models::{ArbitrageOpportunity, DexPool, FlashLoanProvider},
+    policy::PolicySigner,
+    signer::WalletSigner,
+    simulation::{SimulationRequest, Simulator},
+};
+
+abigen!(
+    ArbitrageExecutorContract,
+    r#"[
+        struct SwapLeg { address target; address tokenIn; uint256 amountIn; uint256 minOut; bytes data; }
+        function executeArbitrage(address flashToken, uint256 flashAmount, SwapLeg[] legs, uint256 minProfit) external
+        function executeFlashSwap(address pair, address borrowToken, uint256 borrowAmount, address repayToken, uint256 repayAmount, SwapLeg[] legs, uint256 minProfit) external
+        function executeV3Flash(address pool, address borrowToken, uint256 borrowAmount, SwapLeg[] legs, uint256 minProfit) external
+    ]"#
+);
+
+// Every transaction the executor signs passes the wallet's signing policy
+type Client<M> = SignerMiddleware<Arc<M>, PolicySigner<WalletSigner>>;
+
+// Tolerated drift per leg between quote and execution
+const DEFAULT_SLIPPAGE_BPS: u64 = 30;
+
+// Turns an ArbitrageOpportunity into one executor call: flash-borrow `amount_in`,
+// run both legs, repay and keep the rest. A V2 flash swap instead borrows the
+// intermediate token from the buy pool and runs only the sell leg before
+// repaying it; a V3 flash borrows the input from a third pool. Without `live` every call is only simulated with eth_call, so
+// the bot can run against mainnet without risk.
+pub struct ArbitrageExecutor<M: Middleware> {
+    contract: ArbitrageExecutorContract<Client<M>>,
+    dex: Arc<DexManager>,
+    live: bool,
+    slippage_bps: u64,
+    // Private submission; public mempool when unset
+    relay: Option<FlashbotsMiddleware<Arc<M>, WalletSigner>>,
+    // Public transactions take their nonce here, shared with other strategies
+    nonces: Option<Arc<NonceManager<Client<M>>>>,
+    // Other builders the same bundle goes to, so blocks they win aren't lost
+    builders: Option<Arc<BuilderRegistry>>,
+    // And bloXroute, which forwards to the builders it works with
+    bloxroute: Option<Arc<BloxrouteClient>>,
+    // Bundles the relay simulates paying builders less than this per gas are dropped
+    min_bundle_gas_price: U256,
+    // Full execution check after eth_call, measuring realised profit
+    simulator: Option<Arc<dyn Simulator>>,
+    // Without a relay, trades a sandwich could gut are not sent
+    sandwich_guard: Option<SandwichGuard>,
+}
+
+impl<M: Middleware + 'static> ArbitrageExecutor<M> {
+    pub fn new(
+        provider: Arc<M>,
+        wallet: PolicySigner<WalletSigner>,
+        address: Address,
+        dex: Arc<DexManager>,
+        live: bool,
+    ) -> Self {
+        let client = Arc::new(SignerMiddleware::new(provider, wallet));
+        Self {
+            contract: ArbitrageExecutorContract::new(address, client),
+            dex,
+            live,
+            slippage_bps: DEFAULT_SLIPPAGE_BPS,
+            relay: None,
+            nonces: None,
+            builders: None,
+            bloxroute: None,
+            min_bundle_gas_price: U256::zero(),
+            simulator: None,
+            sandwich_guard: None,
+        }
+    }
+
+    // Submit as Flashbots bundles to `relay_url`, authenticated with `bundle_signer`
+    // (a reputation key, not the wallet holding funds)
+    pub fn with_flashbots(mut self, provider: Arc<M>, relay_url: &str, bundle_signer: WalletSigner) -> Result<Self> {
+        let url = Url::parse(relay_url)
+            .map_err(|e| BotError::Config(format!("invalid relay url {}: {}", relay_url, e)))?;
+        self.relay = Some(FlashbotsMiddleware::new(provider, url, bundle_signer));
+        Ok(self)
+    }
+
+    pub fn with_nonce_manager(mut self, nonces: Arc<NonceManager<Client<M>>>) -> Self {
+        self.nonces = Some(nonces);
+        self
+    }
+
+    pub fn with_builders(mut self, builders: Arc<BuilderRegistry>) -> Self {
+        self.builders = Some(builders);
+        self
+    }
+
+    pub fn with_bloxroute(mut self, bloxroute: Arc<BloxrouteClient>) -> Self {
+        self.bloxroute = Some(bloxroute);
+        self
+    }
+
+    pub fn with_min_bundle_gas_price(mut self, gas_price: U256) -> Self {
+        self.min_bundle_gas_price = gas_price;
+        self
+    }
+
+    pub fn with_simulator(mut self, simulator: Arc<dyn Simulator>) -> Self {
+        self.simulator = Some(simulator);
+        self
+    }
+
+    pub fn with_sandwich_guard(mut self, guard: SandwichGuard) -> Self {
+        self.sandwich_guard = Some(guard);
+        self
+    }
+
+    pub fn with_slippage_bps(mut self, slippage_bps: u64) -> Self {
+        self.slippage_bps = slippage_bps;
+        self
+    }
+
+    pub fn is_live(&self) -> bool {
+        self.live
+    }
+
+    // The contract that holds flash-loaned funds and keeps the profit
+    pub fn address(&self) -> Address {
+        self.contract.address()
+    }
+
+    // Both swap legs, each with its own slippage floor. The sell leg spends
+    // only the buy leg's floor, the least the contract is sure to hold (and
+    // exactly what a V2 pair pays out), with its quote scaled down to match.
+    pub fn build_legs(&self, opportunity: &ArbitrageOpportunity) -> Result<Vec<SwapLeg>> {
+        let buy = self.leg(
+            &opportunity.buy_pool,
+            opportunity.token_in,
+            opportunity.amount_in,
+            opportunity.intermediate_amount,
+        )?;
+        let sell_out = if opportunity.intermediate_amount.is_zero() {
+            U256::zero()
+        } else {
+            opportunity.amount_out * buy.min_out / opportunity.intermediate_amount
+        };
+        let sell = self.leg(&opportunity.sell_pool, opportunity.token_out, buy.min_out, sell_out)?;
+        Ok(vec![buy, sell])
+    }
+
+    // One swap of `amount_in` on `pool`, expected to return `expected_out`
+    fn leg(&self, pool: &DexPool, token_in: Address, amount_in: U256, expected_out: U256) -> Result<SwapLeg> {
+        let handler = self.dex.handler_for(&pool.dex)?;
+        let min_out = expected_out * (10_000 - self.slippage_bps) / 10_000;
+        Ok(SwapLeg {
+            target: handler.swap_target(pool),
+            token_in,
+            amount_in,
+            min_out,
+            data: handler.build_swap_calldata(pool, token_in, amount_in, min_out, self.contract.address())?,
+        })
+    }
+
+    pub fn calldata(&self, opportunity: &ArbitrageOpportunity, min_profit: U256) -> Result<Bytes> {
+        let call = self.call(opportunity, min_profit)?;
+        Ok(call.calldata().unwrap_or_default())
+    }
+
+    fn call(&self, opportunity: &ArbitrageOpportunity, min_profit: U256) -> Result<ContractCall<Client<M>, ()>> {
+        match opportunity.flash_loan {
+            FlashLoanProvider::Aave => {
+                let legs = self.build_legs(opportunity)?;
+                Ok(self.contract.execute_arbitrage(opportunity.token_in, opportunity.amount_in, legs, min_profit))
+            }
+            FlashLoanProvider::UniswapV2Flash => {
+                // The pair's swap is the buy leg: the whole intermediate amount
+                // is borrowed from it, sold, and `amount_in` repaid
+                let sell = self.leg(
+                    &opportunity.sell_pool,
+                    opportunity.token_out,
+                    opportunity.intermediate_amount,
+                    opportunity.amount_out,
+                )?;
+                Ok(self.contract.execute_flash_swap(
+                    opportunity.buy_pool.address,
+                    opportunity.token_out,
+                    opportunity.intermediate_amount,
+                    opportunity.token_in,
+                    opportunity.amount_in,
+                    vec![sell],
+                    min_profit,
+                ))
+            }
+            FlashLoanProvider::UniswapV3Flash => {
+                let Some(pool) = &opportunity.flash_pool else {
+                    return Err(ExecutionError::Reverted("V3 flash without a lending pool".to_string()).into());
+                };
+                // `uniswapV3FlashCallback` is told the fee and repays amount_in plus it
+                let legs = self.build_legs(opportunity)?;
+                Ok(self.contract.execute_v3_flash(
+                    pool.address,
+                    opportunity.token_in,
+                    opportunity.amount_in,
+                    legs,
+                    min_profit,
+                ))
+            }
+        }
+    }
+
+    // eth_call against pending state, then the configured simulator. Ok means
+    // the trade would succeed and clear `min_profit` right now.
+    pub async fn validate(&self, opportunity: &ArbitrageOpportunity, min_profit: U256) -> Result<()> {
+        let call = self.call(opportunity, min_profit)?;
+        self.check(&call, opportunity.token_in, min_profit).await
+    }
+
+    #[instrument(name = "simulation", skip_all)]
+    async fn check(&self, call: &ContractCall<Client<M>, ()>, token: Address, min_profit: U256) -> Result<()> {
+        // The contract reverts unless repayment and min_profit are covered
+        if let Err(e) = call.clone().block(BlockNumber::Pending).call().await {
+            return Err(ExecutionError::Reverted(e.to_string()).into());
+        }
+        if let Some(simulator) = &self.simulator {
+            self.verify(simulator.as_ref(), call, token, min_profit).await?;
+        }
+        Ok(())
+    }
+
+    // Simulates, then submits when live. Returns the mined transaction hash, or
+    // None for a successful dry run.
+    pub async fn execute(
+        &self,
+        opportunity: &ArbitrageOpportunity,
+        min_profit: U256,
+        gas_price: U256,
+    ) -> Result<Option<H256>> {
+        let call = self.call(opportunity, min_profit)?.gas_price(gas_price);
+        self.check(&call, opportunity.token_in, min_profit).await?;
+        if !self.live {
+            info!(
+                buy = ?opportunity.buy_pool.address,
+                sell = ?opportunity.sell_pool.address,
+                "🧪 Dry run: arbitrage would execute (pass --execute to submit)"
+            );
+            return Ok(None);
+        }
+        // Bundles aren't seen before inclusion; a public trade is, with each
+        // leg's slippage floor for a sandwich to fill
+        if let (None, Some(guard)) = (&self.relay, &self.sandwich_guard) {
+            let loss = sandwich::arbitrage_loss(opportunity, self.slippage_bps as u32);
+            if guard.too_risky(loss, opportunity.gross_profit) {
+                warn!(loss = %loss, profit = %opportunity.gross_profit, "🥪 Too exposed to sandwiching to send publicly");
+                return Err(ExecutionError::SandwichExposed(loss).into());
+            }
+        }
+
+        self.submit(&call).await.map(Some)
+    }
+
+    #[instrument(name = "submission", skip_all, fields(bundle = self.relay.is_some()))]
+    async fn submit(&self, call: &ContractCall<Client<M>, ()>) -> Result<H256> {
+        if self.relay.is_some() {
+            return self.send_bundle(call).await;
+        }
+
+        let mut call = call.clone();
+        self.add_access_list(&mut call.tx).await;
+        let pending = match &self.nonces {
+            Some(nonces) => nonces.send(call.tx.clone()).await?,
+            None => call.send().await?,
+        };
+        let receipt = pending.await.map_err(BotError::from)?;
+        match receipt {
+            Some(r) if r.status == Some(U64::from(1)) => Ok(r.transaction_hash),
+            Some(r) => Err(ExecutionError::reverted_on_chain(&r).into()),
+            None => Err(ExecutionError::NotIncluded.into()),
+        }
+    }
+
+    // Best effort: without a list the trade still goes out, paying cold access
+    async fn add_access_list(&self, tx: &mut TypedTransaction) {
+        if let Err(e) = attach_access_list(self.contract.client().as_ref(), tx).await {
+            warn!(error = %e, "⚠️ Access list unavailable");
+        }
+    }
+
+    // Profit stays in the executor contract, so its `token` balance change is the trade's profit
+    async fn verify(
+        &self,
+        simulator: &dyn Simulator,
+        call: &ContractCall<Client<M>, ()>,
+        token: Address,
+        min_profit: U256,
+    ) -> Result<()> {
+        let request = SimulationRequest {
+            from: self.contract.client().address(),
+            to: self.contract.address(),
+            data: call.calldata().unwrap_or_default(),
+            value: U256::zero(),
+            profit: Some((token, self.contract.address())),
+            funding: Vec::new(),
+        };
+        let outcome = simulator.simulate(&request).await?;
+
+        // Backends that can't measure profit rely on the contract's minProfit check
+        if let Some(profit) = outcome.profit {
+            info!(simulator = simulator.name(), profit = %profit, gas = %outcome.gas_used, "🧪 Simulation passed");
+            if profit <= I256::from_raw(min_profit) {
+                return Err(SimulationError::Unprofitable.into());
+            }
+        }
+        Ok(())
+    }
+
+    // Single-transaction bundle for the next block. The relay only includes it
+    // if it doesn't revert, so a failed arbitrage costs no gas.
+    async fn send_bundle(&self, call: &ContractCall<Client<M>, ()>) -> Result<H256> {
+        let Some(relay) = &self.relay else {
+            return Err(ExecutionError::BundleRejected("no relay configured".to_string()).into());
+        };
+        let client = self.contract.client();
+
+        let mut tx = call.tx.clone();
+        self.add_access_list(&mut tx).await;
+        client
+            .fill_transaction(&mut tx, None)
+            .await
+            .map_err(|e| ExecutionError::Signer(e.to_string()))?;
+        let signature = client.signer().sign_transaction(&tx).await.map_err(BotError::from)?;
+
+        let block = client
+            .get_block_number()
+            .await
+            .map_err(|e| ProviderError::Rpc(e.to_string()))?
+            + 1;
+        let raw = tx.rlp_signed(&signature);
+        let bundle = BundleRequest::new()
+            .push_transaction(raw.clone())
+            .set_block(block)
+            .set_simulation_block(block - 1)
+            .set_simulation_timestamp(0);
+
+        // The contract enforces minProfit, so a bundle that simulates cleanly is profitable
+        let simulated = relay
+            .simulate_bundle(&bundle)
+            .await
+            .map_err(|e| SimulationError::Backend(e.to_string()))?;
+        if let Some(reverted) = simulated.transactions.iter().find(|t| t.revert.is_some() || t.error.is_some()) {
+            let reason = reverted.revert.clone().or_else(|| reverted.error.clone()).unwrap_or_default();
+            warn!(tx = ?reverted.hash, %reason, "❌ Bundle simulation reverted");
+            return Err(SimulationError::Reverted(reason).into());
+        }
+        let effective_gas_price = if simulated.gas_used.is_zero() {
+            U256::zero()
+        } else {
+            simulated.coinbase_diff / simulated.gas_used
+        };
+        if effective_gas_price < self.min_bundle_gas_price {
+            warn!(gas_price = %effective_gas_price, min = %self.min_bundle_gas_price, "❌ Bundle gas price too low");
+            return Err(SimulationError::Unprofitable.into());
+        }
+
+        let pending = relay
+            .send_bundle(&bundle)
+            .await
+            .map_err(|e| ExecutionError::BundleRejected(e.to_string()))?;
+        info!(block = %block, "📦 Arbitrage bundle submitted");
+
+        // Inclusion is still awaited through the relay; a copy it already has is deduplicated by hash
+        if let Some(builders) = &self.builders {
+            for (builder, result) in builders.send_bundle(&builders.names(), &[raw.clone()], block).await {
+                if let Err(e) = result {
+                    warn!(%builder, error = %e, "⚠️ Builder rejected bundle");
+                }
+            }
+        }
+        if let Some(bloxroute) = &self.bloxroute {
+            if let Err(e) = bloxroute.send_bundle(&[raw], block).await {
+                warn!(error = %e, "⚠️ bloXroute rejected bundle");
+            }
+        }
+
+        pending.await.map_err(|_| ExecutionError::NotIncluded.into())
+    }
+}
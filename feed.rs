@@ -0,0 +1,64 @@
+use futures::{stream, Stream};
+use tokio::sync::broadcast::{self, error::RecvError};
+use tracing::warn;
+
+use crate::models::{ArbitrageOpportunity, CexDexOpportunity, ExecutionReport};
+
+// Events buffered per subscriber; one that falls further behind skips ahead
+const CAPACITY: usize = 1024;
+
+// Actionable arbitrage, published the moment the scan finds it
+pub type OpportunityFeed = Feed<ArbitrageOpportunity>;
+// Arbitrage executions, by the bot or reported by consumers
+pub type ExecutionFeed = Feed<ExecutionReport>;
+// DEX pools priced away from the centralized exchanges
+pub type CexDexFeed = Feed<CexDexOpportunity>;
+
+// Fan-out of bot events to consumers outside the bot. Publishing never waits
+// on a subscriber.
+pub struct Feed<T> {
+    sender: broadcast::Sender<T>,
+}
+
+impl<T> Clone for Feed<T> {
+    fn clone(&self) -> Self {
+        Self {
+            sender: self.sender.clone(),
+        }
+    }
+}
+
+impl<T: Clone + Send + 'static> Default for Feed<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T: Clone + Send + 'static> Feed<T> {
+    pub fn new() -> Self {
+        let (sender, _) = broadcast::channel(CAPACITY);
+        Self { sender }
+    }
+
+    pub fn publish(&self, event: &T) {
+        // Fails only when nobody is subscribed
+        let _ = self.sender.send(event.clone());
+    }
+
+    pub fn subscribers(&self) -> usize {
+        self.sender.receiver_count()
+    }
+
+    // Events published from now on
+    pub fn subscribe(&self) -> impl Stream<Item = T> + Send + 'static {
+        stream::unfold(self.sender.subscribe(), |mut receiver| async move {
+            loop {
+                match receiver.recv().await {
+                    Ok(event) => return Some((event, receiver)),
+                    Err(RecvError::Lagged(skipped)) => warn!(skipped, "⚠️ Feed subscriber fell behind"),
+                    Err(RecvError::Closed) => return None,
+                }
+            }
+        })
+    }
+}
@@ -0,0 +1,91 @@
+use ethers::{
+    providers::{Middleware, Provider, Ws},
+    types::{Address, BlockNumber, H256, U256},
+    utils::{Anvil, AnvilInstance},
+};
+use std::{sync::Arc, time::Duration};
+
+use crate::errors::{BotError, Result};
+
+// UniswapV2Pair packs reserve0, reserve1 and blockTimestampLast into slot 8
+const V2_RESERVES_SLOT: u64 = 8;
+
+// A local anvil fork of mainnet plus the cheatcodes we use to seed state
+pub struct Fork {
+    anvil: AnvilInstance,
+    pub provider: Arc<Provider<Ws>>,
+}
+
+impl Fork {
+    pub async fn spawn(fork_url: &str, fork_block: Option<u64>) -> Result<Self> {
+        let mut anvil = Anvil::new().fork(fork_url);
+        if let Some(block) = fork_block {
+            anvil = anvil.fork_block_number(block);
+        }
+        let anvil = anvil.spawn();
+
+        let ws = Ws::connect(anvil.ws_endpoint()).await?;
+        let provider = Arc::new(Provider::new(ws).interval(Duration::from_millis(10)));
+
+        Ok(Self { anvil, provider })
+    }
+
+    pub fn endpoint(&self) -> String {
+        self.anvil.endpoint()
+    }
+
+    pub fn ws_endpoint(&self) -> String {
+        self.anvil.ws_endpoint()
+    }
+
+    // Hex private key of one of anvil's funded dev accounts
+    pub fn dev_key(&self) -> String {
+        hex::encode(self.anvil.keys()[0].to_bytes())
+    }
+
+    pub async fn set_balance(&self, address: Address, wei: U256) -> Result<()> {
+        self.provider
+            .request::<_, ()>("anvil_setBalance", (address, wei))
+            .await
+            .map_err(BotError::from)
+    }
+
+    pub async fn impersonate(&self, address: Address) -> Result<()> {
+        self.provider
+            .request::<_, ()>("anvil_impersonateAccount", [address])
+            .await
+            .map_err(BotError::from)
+    }
+
+    pub async fn set_storage(&self, address: Address, slot: U256, value: U256) -> Result<()> {
+        let mut slot_bytes = [0u8; 32];
+        slot.to_big_endian(&mut slot_bytes);
+        let mut value_bytes = [0u8; 32];
+        value.to_big_endian(&mut value_bytes);
+
+        self.provider
+            .request::<_, bool>(
+                "anvil_setStorageAt",
+                (address, H256::from(slot_bytes), H256::from(value_bytes)),
+            )
+            .await?;
+        Ok(())
+    }
+
+    // Overwrite a V2 pair's reserves so pricing code sees a known state
+    pub async fn set_v2_reserves(&self, pair: Address, reserve0: u128, reserve1: u128) -> Result<()> {
+        let block = self
+            .provider
+            .get_block(BlockNumber::Latest)
+            .await?
+            .ok_or_else(|| BotError::Config("fork has no latest block".to_string()))?;
+        let timestamp = block.timestamp.as_u32();
+
+        // Layout (low to high): reserve0 uint112 | reserve1 uint112 | blockTimestampLast uint32
+        let packed = U256::from(reserve0)
+            | (U256::from(reserve1) << 112)
+            | (U256::from(timestamp) << 224);
+
+        self.set_storage(pair, U256::from(V2_RESERVES_SLOT), packed).await
+    }
+}
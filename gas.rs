@@ -0,0 +1,195 @@
+use ethers::{
+    prelude::*,
+    types::{transaction::eip2718::TypedTransaction, Address, BlockNumber, Bytes, Eip1559TransactionRequest, U256},
+};
+use std::sync::Arc;
+
+use crate::errors::{ProviderError, Result};
+
+// OP Stack predeploy pricing a transaction's L1 data
+abigen!(
+    OpGasPriceOracle,
+    r#"[function getL1Fee(bytes data) external view returns (uint256)]"#
+);
+
+// Arbitrum precompile with the current L1 and L2 prices, in wei
+abigen!(
+    ArbGasInfo,
+    r#"[function getPricesInWei() external view returns (uint256, uint256, uint256, uint256, uint256, uint256)]"#
+);
+
+const OP_GAS_PRICE_ORACLE: &str = "0x420000000000000000000000000000000000000F";
+const ARB_GAS_INFO: &str = "0x000000000000000000000000000000000000006C";
+// Calldata an L1 fee per byte is sampled with. Non-zero bytes, so compression
+// on newer OP Stack versions doesn't flatter it.
+const L1_FEE_PROBE_BYTES: usize = 1_000;
+
+// Blocks of eth_feeHistory an estimate looks back over
+const FEE_HISTORY_BLOCKS: u64 = 20;
+// Priority fee percentiles within each block: what a typical transaction
+// paid, and what the ones racing for the top of the block paid
+const NORMAL_PERCENTILE: f64 = 50.0;
+const COMPETITIVE_PERCENTILE: f64 = 90.0;
+// Gas limit over the access-list estimate, for state moving before inclusion
+const GAS_LIMIT_MARGIN_PCT: u64 = 120;
+
+// How hard a transaction has to fight for its place in the block
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Confidence {
+    // Lands within a block or two alongside ordinary traffic
+    Normal,
+    // Outbids most of the block, for submissions racing other searchers
+    Competitive,
+}
+
+// How a chain charges for a transaction on top of its execution gas. Rollups
+// also post the transaction's bytes to L1, and that data fee is usually the
+// larger part of the cost.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum GasModel {
+    // Execution gas only (mainnet, Polygon, BSC)
+    #[default]
+    Execution,
+    // Optimism, Base: GasPriceOracle.getL1Fee on the calldata
+    OpStack,
+    // Arbitrum: ArbGasInfo's per-transaction and per-calldata-byte L1 prices
+    Arbitrum,
+}
+
+impl GasModel {
+    // Current L1 data fee per calldata byte, in wei; zero without an L1 fee
+    pub async fn l1_fee_per_byte<M: Middleware + 'static>(&self, provider: Arc<M>) -> Result<U256> {
+        match self {
+            GasModel::Execution => Ok(U256::zero()),
+            GasModel::OpStack => {
+                let oracle = OpGasPriceOracle::new(OP_GAS_PRICE_ORACLE.parse::<Address>().unwrap(), provider);
+                let probe = Bytes::from(vec![0xff; L1_FEE_PROBE_BYTES]);
+                Ok(oracle.get_l1_fee(probe).call().await? / L1_FEE_PROBE_BYTES)
+            }
+            GasModel::Arbitrum => {
+                let info = ArbGasInfo::new(ARB_GAS_INFO.parse::<Address>().unwrap(), provider);
+                let (_, per_calldata_byte, ..) = info.get_prices_in_wei().call().await?;
+                Ok(per_calldata_byte)
+            }
+        }
+    }
+
+    // L1 data fee for a transaction carrying `calldata`, in wei
+    pub async fn l1_fee<M: Middleware + 'static>(&self, provider: Arc<M>, calldata: &Bytes) -> Result<U256> {
+        match self {
+            GasModel::Execution => Ok(U256::zero()),
+            GasModel::OpStack => {
+                let oracle = OpGasPriceOracle::new(OP_GAS_PRICE_ORACLE.parse::<Address>().unwrap(), provider);
+                Ok(oracle.get_l1_fee(calldata.clone()).call().await?)
+            }
+            GasModel::Arbitrum => {
+                let info = ArbGasInfo::new(ARB_GAS_INFO.parse::<Address>().unwrap(), provider);
+                let (per_tx, per_calldata_byte, ..) = info.get_prices_in_wei().call().await?;
+                Ok(per_tx + per_calldata_byte * calldata.len())
+            }
+        }
+    }
+}
+
+// Fees for the next block, from recent history rather than the node's
+// single eth_gasPrice guess
+#[derive(Debug, Clone, Copy, Default)]
+pub struct FeeEstimate {
+    // Next block's base fee, known exactly from the last one
+    pub base_fee: U256,
+    // Median across recent blocks of each percentile's priority fee
+    pub priority_normal: U256,
+    pub priority_competitive: U256,
+}
+
+impl FeeEstimate {
+    pub fn priority_fee(&self, confidence: Confidence) -> U256 {
+        match confidence {
+            Confidence::Normal => self.priority_normal,
+            Confidence::Competitive => self.priority_competitive,
+        }
+    }
+
+    // Legacy gas price paying the next block's base fee plus the tip
+    pub fn gas_price(&self, confidence: Confidence) -> U256 {
+        self.base_fee + self.priority_fee(confidence)
+    }
+
+    // EIP-1559 cap that survives the base fee doubling before inclusion
+    pub fn max_fee(&self, confidence: Confidence) -> U256 {
+        self.base_fee * 2 + self.priority_fee(confidence)
+    }
+}
+
+pub async fn estimate_fees<M: Middleware>(provider: &M) -> Result<FeeEstimate> {
+    let history = provider
+        .fee_history(
+            FEE_HISTORY_BLOCKS,
+            BlockNumber::Latest,
+            &[NORMAL_PERCENTILE, COMPETITIVE_PERCENTILE],
+        )
+        .await
+        .map_err(|e| ProviderError::Rpc(e.to_string()))?;
+
+    // base_fee_per_gas has one more entry than the blocks asked for: the next block's
+    let base_fee = history.base_fee_per_gas.last().copied().unwrap_or_default();
+    Ok(FeeEstimate {
+        base_fee,
+        priority_normal: median_reward(&history.reward, 0),
+        priority_competitive: median_reward(&history.reward, 1),
+    })
+}
+
+// `tx` as an EIP-1559 (type-2) transaction paying at most `max_fee` per gas,
+// of which up to `priority_fee` goes to the block builder
+pub fn eip1559(tx: &TypedTransaction, max_fee: U256, priority_fee: U256) -> TypedTransaction {
+    let mut request = Eip1559TransactionRequest::new()
+        .data(tx.data().cloned().unwrap_or_default())
+        .value(tx.value().copied().unwrap_or_default())
+        .access_list(tx.access_list().cloned().unwrap_or_default())
+        .max_fee_per_gas(max_fee)
+        .max_priority_fee_per_gas(priority_fee.min(max_fee));
+    if let Some(to) = tx.to() {
+        request = request.to(to.clone());
+    }
+    if let Some(from) = tx.from() {
+        request = request.from(*from);
+    }
+    if let Some(gas) = tx.gas() {
+        request = request.gas(*gas);
+    }
+    if let Some(nonce) = tx.nonce() {
+        request = request.nonce(*nonce);
+    }
+    if let Some(chain_id) = tx.chain_id() {
+        request = request.chain_id(chain_id);
+    }
+    request.into()
+}
+
+// Attach an EIP-2930 access list to `tx`, so the pools, tokens and flash
+// lenders it touches are paid for up front at the warm rate rather than cold
+// on first touch, and set its gas limit from the gas used with the list
+pub async fn attach_access_list<M: Middleware>(provider: &M, tx: &mut TypedTransaction) -> Result<U256> {
+    let created = provider
+        .create_access_list(tx, None)
+        .await
+        .map_err(|e| ProviderError::Rpc(format!("eth_createAccessList: {}", e)))?;
+    tx.set_access_list(created.access_list);
+    tx.set_gas(created.gas_used * GAS_LIMIT_MARGIN_PCT / 100);
+    Ok(created.gas_used)
+}
+
+// Median of one percentile column, skipping empty blocks that report zero
+fn median_reward(rewards: &[Vec<U256>], column: usize) -> U256 {
+    let mut fees: Vec<U256> = rewards
+        .iter()
+        .filter_map(|block| block.get(column).copied())
+        .filter(|fee| !fee.is_zero())
+        .collect();
+    if fees.is_empty() {
+        return U256::zero();
+    }
+    fees.sort();
+    fees[fees.len() / 2]
+}
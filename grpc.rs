@@ -0,0 +1,165 @@
+use ethers::types::{Address, H256, I256, U64};
+use futures::{future, Stream, StreamExt};
+use std::{pin::Pin, sync::Arc};
+use tokio::sync::{mpsc, RwLock};
+use tonic::{Request, Response, Status};
+
+use crate::{
+    feed::OpportunityFeed,
+    models::{self, ExecutionReport},
+};
+
+pub mod proto {
+    tonic::include_proto!("arbitrage.v1");
+}
+
+use proto::arbitrage_server::{Arbitrage, ArbitrageServer};
+
+// gRPC counterpart of the HTTP opportunity stream, for consumers that also
+// want the pool set and report back what they executed
+pub struct ArbitrageService {
+    feed: OpportunityFeed,
+    pools: Arc<RwLock<Vec<models::DexPool>>>,
+    results: mpsc::Sender<ExecutionReport>,
+}
+
+impl ArbitrageService {
+    pub fn new(
+        feed: OpportunityFeed,
+        pools: Arc<RwLock<Vec<models::DexPool>>>,
+        results: mpsc::Sender<ExecutionReport>,
+    ) -> Self {
+        Self { feed, pools, results }
+    }
+
+    pub fn into_server(self) -> ArbitrageServer<Self> {
+        ArbitrageServer::new(self)
+    }
+}
+
+#[tonic::async_trait]
+impl Arbitrage for ArbitrageService {
+    type StreamOpportunitiesStream = Pin<Box<dyn Stream<Item = Result<proto::ArbitrageOpportunity, Status>> + Send>>;
+
+    async fn stream_opportunities(
+        &self,
+        request: Request<proto::StreamOpportunitiesRequest>,
+    ) -> Result<Response<Self::StreamOpportunitiesStream>, Status> {
+        let tokens = parse_addresses(&request.into_inner().tokens)?;
+        let stream = self
+            .feed
+            .subscribe()
+            .filter(move |o| future::ready(tokens.is_empty() || tokens.contains(&o.token_in)))
+            .map(|o| Ok(proto::ArbitrageOpportunity::from(&o)));
+        Ok(Response::new(Box::pin(stream)))
+    }
+
+    async fn get_pools(
+        &self,
+        request: Request<proto::GetPoolsRequest>,
+    ) -> Result<Response<proto::GetPoolsResponse>, Status> {
+        let tokens = parse_addresses(&request.into_inner().tokens)?;
+        let pools = self
+            .pools
+            .read()
+            .await
+            .iter()
+            .filter(|p| tokens.is_empty() || tokens.contains(&p.token0.address) || tokens.contains(&p.token1.address))
+            .map(proto::DexPool::from)
+            .collect();
+        Ok(Response::new(proto::GetPoolsResponse { pools }))
+    }
+
+    async fn submit_execution_result(
+        &self,
+        request: Request<proto::ExecutionResult>,
+    ) -> Result<Response<proto::SubmitExecutionResultResponse>, Status> {
+        let report = ExecutionReport::try_from(request.into_inner())?;
+        self.results
+            .send(report)
+            .await
+            .map_err(|_| Status::unavailable("execution results are not being recorded"))?;
+        Ok(Response::new(proto::SubmitExecutionResultResponse {}))
+    }
+}
+
+impl From<&models::Token> for proto::Token {
+    fn from(token: &models::Token) -> Self {
+        Self {
+            address: format!("{:?}", token.address),
+            symbol: token.symbol.clone(),
+            decimals: token.decimals as u32,
+            transfer_tax_bps: token.transfer_tax_bps,
+        }
+    }
+}
+
+impl From<&models::DexPool> for proto::DexPool {
+    fn from(pool: &models::DexPool) -> Self {
+        Self {
+            address: format!("{:?}", pool.address),
+            dex: pool.dex.to_string(),
+            token0: Some((&pool.token0).into()),
+            token1: Some((&pool.token1).into()),
+            reserve0: pool.reserve0.to_string(),
+            reserve1: pool.reserve1.to_string(),
+            fee_bps: pool.fee_bps,
+        }
+    }
+}
+
+impl From<&models::ArbitrageOpportunity> for proto::ArbitrageOpportunity {
+    fn from(o: &models::ArbitrageOpportunity) -> Self {
+        Self {
+            token_in: format!("{:?}", o.token_in),
+            token_out: format!("{:?}", o.token_out),
+            buy_pool: Some((&o.buy_pool).into()),
+            sell_pool: Some((&o.sell_pool).into()),
+            amount_in: o.amount_in.to_string(),
+            intermediate_amount: o.intermediate_amount.to_string(),
+            amount_out: o.amount_out.to_string(),
+            gross_profit: o.gross_profit.to_string(),
+            gas_cost: o.gas_cost.to_string(),
+            spread_bps: o.spread_bps,
+            block_number: o.block_number.as_u64(),
+            flash_loan: o.flash_loan.to_string(),
+        }
+    }
+}
+
+impl TryFrom<proto::ExecutionResult> for ExecutionReport {
+    type Error = Status;
+
+    fn try_from(result: proto::ExecutionResult) -> Result<Self, Status> {
+        let tx_hash = match result.tx_hash.as_str() {
+            "" => None,
+            hash => Some(hash.parse::<H256>().map_err(|_| invalid("tx_hash", hash))?),
+        };
+        let realized_profit = match result.realized_profit.as_str() {
+            "" => I256::zero(),
+            profit => I256::from_dec_str(profit).map_err(|_| invalid("realized_profit", profit))?,
+        };
+        Ok(Self {
+            block_number: U64::from(result.block_number),
+            token_in: parse_address("token_in", &result.token_in)?,
+            buy_pool: parse_address("buy_pool", &result.buy_pool)?,
+            sell_pool: parse_address("sell_pool", &result.sell_pool)?,
+            tx_hash,
+            success: result.success,
+            realized_profit,
+            error: Some(result.error).filter(|e| !e.is_empty()),
+        })
+    }
+}
+
+fn parse_address(field: &str, value: &str) -> Result<Address, Status> {
+    value.parse().map_err(|_| invalid(field, value))
+}
+
+fn parse_addresses(values: &[String]) -> Result<Vec<Address>, Status> {
+    values.iter().map(|v| parse_address("tokens", v)).collect()
+}
+
+fn invalid(field: &str, value: &str) -> Status {
+    Status::invalid_argument(format!("{}: invalid value {}", field, value))
+}
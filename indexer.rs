@@ -0,0 +1,186 @@
+use ethers::{
+    prelude::*,
+    providers::{JsonRpcClient, Provider},
+    types::{Address, Filter, U64},
+};
+use redis::{AsyncCommands, Client as RedisClient};
+use std::{collections::HashSet, sync::Arc};
+use tracing::info;
+
+use liquidation_bot::errors::Result;
+
+// Blocks per eth_getLogs request
+const LOG_CHUNK: u64 = 5_000;
+
+// Pool events that open, grow or close a position. The account is the third
+// topic in each: onBehalfOf for Supply and Borrow, user for Repay and Withdraw.
+const POSITION_EVENTS: [&str; 4] = [
+    "Supply(address,address,address,uint256,uint16)",
+    "Borrow(address,address,address,uint256,uint8,uint256,uint16)",
+    "Repay(address,address,address,uint256,bool)",
+    "Withdraw(address,address,address,uint256)",
+];
+
+// cToken borrows; nothing is indexed, the borrower is the first data word
+const COMPOUND_V2_EVENTS: [&str; 1] = ["Borrow(address,uint256,uint256,uint256)"];
+// Block the mainnet Comptroller was deployed at
+pub const COMPOUND_V2_DEPLOYED_AT: u64 = 7_710_671;
+
+// Where an event names the account
+#[derive(Debug, Clone, Copy)]
+enum AccountField {
+    Topic(usize),
+    // 32-byte word of the data
+    Word(usize),
+}
+
+// Every account that has ever held a position in an Aave V3 pool, or borrowed
+// from a Compound V2 market, kept in Redis so restarts don't re-read history.
+// History is backfilled in chunks from deployment, checkpointed so an
+// interrupted backfill resumes; live sync follows from wherever it first started.
+pub struct PositionIndex {
+    redis: Arc<RedisClient>,
+    // The Aave pool or Compound comptroller the index is keyed by
+    pool: Address,
+    // Contracts emitting the events: the pool itself, or every cToken
+    contracts: Vec<Address>,
+    events: &'static [&'static str],
+    account: AccountField,
+    deployed_at: U64,
+    // positions:<pool> holds the accounts; the other keys are block checkpoints
+    users_key: String,
+    live_key: String,
+    backfill_key: String,
+}
+
+impl PositionIndex {
+    pub fn new(redis: Arc<RedisClient>, pool: Address, deployed_at: u64) -> Self {
+        Self::for_events(redis, pool, vec![pool], &POSITION_EVENTS, AccountField::Topic(2), deployed_at)
+    }
+
+    // Borrowers across the Compound V2 `markets` under `comptroller`. Accounts
+    // stay listed after repaying; the scan skips any without a shortfall.
+    pub fn compound_v2(redis: Arc<RedisClient>, comptroller: Address, markets: Vec<Address>, deployed_at: u64) -> Self {
+        Self::for_events(redis, comptroller, markets, &COMPOUND_V2_EVENTS, AccountField::Word(0), deployed_at)
+    }
+
+    fn for_events(
+        redis: Arc<RedisClient>,
+        pool: Address,
+        contracts: Vec<Address>,
+        events: &'static [&'static str],
+        account: AccountField,
+        deployed_at: u64,
+    ) -> Self {
+        let prefix = format!("positions:{:?}", pool);
+        Self {
+            redis,
+            pool,
+            contracts,
+            events,
+            account,
+            deployed_at: deployed_at.into(),
+            users_key: prefix.clone(),
+            live_key: format!("{}:live", prefix),
+            backfill_key: format!("{}:backfill", prefix),
+        }
+    }
+
+    pub async fn users(&self) -> Result<Vec<Address>> {
+        let mut conn = self.redis.get_async_connection().await?;
+        let users: Vec<String> = conn.smembers(&self.users_key).await?;
+        Ok(users.iter().filter_map(|u| u.parse().ok()).collect())
+    }
+
+    // Accounts listed elsewhere, such as a subgraph; returns how many were new
+    pub async fn add(&self, users: &[Address]) -> Result<usize> {
+        if users.is_empty() {
+            return Ok(0);
+        }
+        let users: Vec<String> = users.iter().map(|u| format!("{:?}", u)).collect();
+        let mut conn = self.redis.get_async_connection().await?;
+        Ok(conn.sadd(&self.users_key, users).await?)
+    }
+
+    // Accounts with nothing left; a later event adds them back
+    pub async fn remove(&self, users: &[Address]) -> Result<()> {
+        if users.is_empty() {
+            return Ok(());
+        }
+        let users: Vec<String> = users.iter().map(|u| format!("{:?}", u)).collect();
+        let mut conn = self.redis.get_async_connection().await?;
+        let _: () = conn.srem(&self.users_key, users).await?;
+        Ok(())
+    }
+
+    // Apply events since the last sync, up to `to_block`. The first sync
+    // starts at `to_block`; earlier accounts come from `backfill`.
+    pub async fn sync<P: JsonRpcClient>(&self, provider: &Provider<P>, to_block: U64) -> Result<usize> {
+        let Some(last) = self.checkpoint(&self.live_key).await? else {
+            self.write_checkpoint(&self.live_key, to_block).await?;
+            return Ok(0);
+        };
+        self.index_range(provider, &self.live_key, last + 1, to_block).await
+    }
+
+    // Every account from the pool's deployment up to where live sync took
+    // over (or `to_block` if it hasn't started)
+    pub async fn backfill<P: JsonRpcClient>(&self, provider: &Provider<P>, to_block: U64) -> Result<usize> {
+        let from = match self.checkpoint(&self.backfill_key).await? {
+            Some(block) => block + 1,
+            None => self.deployed_at,
+        };
+        let to = self.checkpoint(&self.live_key).await?.unwrap_or(to_block).min(to_block);
+        if from > to {
+            return Ok(0);
+        }
+        info!(pool = ?self.pool, from = %from, to = %to, "🗂️ Backfilling positions");
+        self.index_range(provider, &self.backfill_key, from, to).await
+    }
+
+    // Chunked walk over the position events, checkpointing `key` after each chunk
+    async fn index_range<P: JsonRpcClient>(&self, provider: &Provider<P>, key: &str, from: U64, to: U64) -> Result<usize> {
+        let filter = Filter::new().address(self.contracts.clone()).events(self.events);
+        let mut added = 0;
+        let mut start = from;
+
+        while start <= to {
+            let end = (start + LOG_CHUNK - 1).min(to);
+            let logs = provider.get_logs(&filter.clone().from_block(start).to_block(end)).await?;
+
+            let users: HashSet<String> = logs
+                .iter()
+                .filter_map(|log| self.account_of(log))
+                .map(|account| format!("{:?}", account))
+                .collect();
+            if !users.is_empty() {
+                let mut conn = self.redis.get_async_connection().await?;
+                let new: usize = conn.sadd(&self.users_key, users.into_iter().collect::<Vec<_>>()).await?;
+                added += new;
+            }
+            self.write_checkpoint(key, end).await?;
+            start = end + 1;
+        }
+
+        Ok(added)
+    }
+
+    fn account_of(&self, log: &Log) -> Option<Address> {
+        match self.account {
+            AccountField::Topic(i) => log.topics.get(i).map(|topic| Address::from(*topic)),
+            AccountField::Word(i) => log.data.get(i * 32 + 12..(i + 1) * 32).map(Address::from_slice),
+        }
+    }
+
+    async fn checkpoint(&self, key: &str) -> Result<Option<U64>> {
+        let mut conn = self.redis.get_async_connection().await?;
+        let block: Option<u64> = conn.get(key).await?;
+        Ok(block.map(U64::from))
+    }
+
+    async fn write_checkpoint(&self, key: &str, block: U64) -> Result<()> {
+        let mut conn = self.redis.get_async_connection().await?;
+        let _: () = conn.set(key, block.as_u64()).await?;
+        Ok(())
+    }
+}
@@ -0,0 +1,59 @@
+use futures::{stream, StreamExt};
+use rdkafka::{
+    config::ClientConfig,
+    producer::{FutureProducer, FutureRecord},
+};
+use std::time::Duration;
+use tracing::warn;
+
+use crate::{
+    errors::{BotError, Result},
+    feed::{ExecutionFeed, OpportunityFeed},
+};
+
+// Publishes opportunity and execution events as JSON to Kafka, for pipelines
+// and warehouses downstream of the bot. Messages are keyed by the buy pool so
+// each route's events stay in order within a partition.
+pub struct KafkaSink {
+    producer: FutureProducer,
+    opportunities_topic: String,
+    executions_topic: String,
+}
+
+impl KafkaSink {
+    pub fn connect(brokers: &str, opportunities_topic: &str, executions_topic: &str) -> Result<Self> {
+        let producer = ClientConfig::new()
+            .set("bootstrap.servers", brokers)
+            .set("message.timeout.ms", "5000")
+            .set("linger.ms", "5")
+            .create()
+            .map_err(|e| BotError::Config(format!("kafka producer: {}", e)))?;
+        Ok(Self {
+            producer,
+            opportunities_topic: opportunities_topic.to_string(),
+            executions_topic: executions_topic.to_string(),
+        })
+    }
+
+    // Runs until both feeds close
+    pub async fn run(self, opportunities: OpportunityFeed, executions: ExecutionFeed) {
+        let opportunities = opportunities.subscribe().map(|o| {
+            (&self.opportunities_topic, format!("{:?}", o.buy_pool.address), serde_json::to_string(&o))
+        });
+        let executions = executions
+            .subscribe()
+            .map(|e| (&self.executions_topic, format!("{:?}", e.buy_pool), serde_json::to_string(&e)));
+        let mut events = stream::select(Box::pin(opportunities), Box::pin(executions));
+
+        while let Some((topic, key, payload)) = events.next().await {
+            let Ok(payload) = payload else {
+                continue;
+            };
+            let record = FutureRecord::to(topic).key(&key).payload(&payload);
+            // Never block the feed on a full producer queue; the event is dropped instead
+            if let Err((e, _)) = self.producer.send(record, Duration::ZERO).await {
+                warn!(%topic, error = %e, "⚠️ Kafka publish failed");
+            }
+        }
+    }
+}
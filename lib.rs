@@ -0,0 +1,50 @@
+// Reusable building blocks of the bot, so venues and strategies can be added
+// from outside the crate without forking it.
+
+#[cfg(test)]
+mod amm_tests;
+pub mod block_state;
+pub mod bloxroute;
+pub mod builders;
+pub mod cex;
+pub mod chain_tracker;
+pub mod chains;
+pub mod chainlink;
+pub mod coingecko;
+pub mod cow;
+pub mod dex;
+pub mod errors;
+pub mod eth_price;
+pub mod etherscan;
+pub mod executor;
+pub mod feed;
+pub mod gas;
+pub mod grpc;
+#[cfg(feature = "kafka-sink")]
+pub mod kafka_sink;
+pub mod math;
+pub mod mempool;
+pub mod models;
+pub mod multi_provider;
+pub mod nonce;
+#[cfg(feature = "postgres-sink")]
+pub mod opportunity_sink;
+pub mod oracle;
+pub mod pathfinder;
+pub mod policy;
+pub mod pool_cache;
+pub mod price;
+pub mod ranking;
+pub mod reconcile;
+pub mod redis_stream;
+pub mod registry;
+pub mod replay;
+pub mod safety;
+pub mod sandwich;
+pub mod scoring;
+pub mod signer;
+pub mod simulation;
+pub mod strategy;
+pub mod subgraph;
+pub mod thresholds;
+pub mod token_sets;
@@ -0,0 +1,90 @@
+use async_trait::async_trait;
+use ethers::types::{Address, Filter, Log, Transaction};
+use tracing::debug;
+use liquidation_bot::{
+    block_state::BlockSnapshot,
+    errors::Result,
+    math::to_f64,
+    strategy::{Action, Strategy},
+};
+
+use crate::{LiquidationBot, ETH_USD_FEED, LIQUIDATION_STRATEGY};
+
+// Liquidations attempted per event; the rest are stale by the next block anyway
+const TARGETS_PER_EVENT: usize = 10;
+
+// Aave liquidations: react to touched positions per block, oracle updates,
+// and competitor liquidation calls and pending oracle reports in the mempool.
+pub struct LiquidationStrategy {
+    bot: LiquidationBot,
+}
+
+impl LiquidationStrategy {
+    pub fn new(bot: LiquidationBot) -> Self {
+        Self { bot }
+    }
+
+    // Most urgent targets that clear the expected-value gate
+    async fn actions(&self) -> Vec<Action> {
+        let min_ev = to_f64(self.bot.thresholds.load().min_expected_value);
+        self.bot
+            .rank_targets()
+            .await
+            .into_iter()
+            .filter(|(_, ev)| *ev >= min_ev)
+            .take(TARGETS_PER_EVENT)
+            .map(|(target, _)| Action::Liquidation(target))
+            .collect()
+    }
+}
+
+#[async_trait]
+impl Strategy for LiquidationStrategy {
+    fn name(&self) -> &str {
+        LIQUIDATION_STRATEGY
+    }
+
+    fn log_filter(&self) -> Option<Filter> {
+        // Positions priced by a pull oracle move when it posts, not with Chainlink
+        if let Some(source) = &self.bot.price_source {
+            return source.update_filter();
+        }
+        let feed: Address = ETH_USD_FEED.parse().ok()?;
+        Some(
+            Filter::new()
+                .address(feed)
+                .event("AnswerUpdated(int256,uint256,uint256)"),
+        )
+    }
+
+    async fn on_block(&self, snapshot: &BlockSnapshot) -> Result<Vec<Action>> {
+        if let Some(reorg) = &snapshot.reorg {
+            if let Err(e) = self.bot.handle_reorg(reorg).await {
+                self.bot.handle_task_error("reorg", e)?;
+            }
+        }
+        self.bot.rescore_targets(snapshot.gas_price).await;
+        for user in snapshot.positions_touched.iter().copied() {
+            if let Err(e) = self.bot.refresh_position(user).await {
+                self.bot.handle_task_error("block reaction", e)?;
+            }
+        }
+        // A pull oracle with nothing to follow on chain can move any block
+        if self.bot.price_source.as_ref().is_some_and(|s| s.update_filter().is_none()) {
+            self.bot.rescan_at_risk("price poll").await?;
+        }
+        Ok(self.actions().await)
+    }
+
+    // Competitor liquidations, and Chainlink reports that will push positions under water
+    async fn on_pending_tx(&self, tx: &Transaction) -> Result<Vec<Action>> {
+        self.bot.analyze_transaction(tx.clone()).await
+    }
+
+    // An oracle update can push many positions under water at once
+    async fn on_log(&self, log: &Log) -> Result<Vec<Action>> {
+        debug!(tx = ?log.transaction_hash, "📊 Oracle update detected");
+        self.bot.rescan_at_risk("oracle rescan").await?;
+        Ok(self.actions().await)
+    }
+}
@@ -0,0 +1,56 @@
+// Liquidation targets against the expected-value gate LiquidationStrategy
+// applies before acting on them.
+//
+// Run with:
+//   cargo test --bin liquidation-bot liquidation_tests
+
+use super::*;
+
+// MIN_EXPECTED_VALUE_USD's default
+fn min_expected_value() -> f64 {
+    math::to_f64(U256::from(20) * U256::exp10(18))
+}
+
+// A USDC borrow below HF 0.95, so Aave's close factor lets all of it be repaid
+fn full_close_target() -> LiquidationTarget {
+    let usd = U256::exp10(18);
+    LiquidationTarget {
+        protocol: "AAVE_V3".to_string(),
+        user: Address::repeat_byte(0x11),
+        collateral_asset: Address::repeat_byte(0x22),
+        debt_asset: Address::repeat_byte(0x33),
+        // The whole 10,000 USDC borrow
+        debt_amount: U256::from(10_000) * U256::exp10(6),
+        health_factor: 0.9,
+        expected_profit: U256::from(435) * U256::exp10(6),
+        gas_cost: U256::from(10) * U256::exp10(6),
+        gas_price: U256::from(20) * U256::exp10(9),
+        l1_fee: U256::zero(),
+        debt_value: U256::from(10_000) * usd,
+        profit_value: U256::from(435) * usd,
+        bonus_bps: 500,
+        collateral_value: U256::from(11_000) * usd,
+    }
+}
+
+// As LiquidationStrategy ranks a target on the scan that finds it
+fn expected_value(target: &LiquidationTarget) -> f64 {
+    let scorer = OpportunityScorer::new(ScoringConfig::default());
+    let features = LiquidationBot::target_features(target);
+    scorer.observe(&features.id);
+    scorer.score(&features).expected_value
+}
+
+#[test]
+fn full_close_survives_the_gate() {
+    let target = full_close_target();
+    assert!(expected_value(&target) >= min_expected_value());
+}
+
+#[test]
+fn repaying_all_the_collateral_covers_still_scores() {
+    // The collateral ceiling binds: the repayment plus bonus takes every unit
+    let mut target = full_close_target();
+    target.collateral_value = target.debt_value * U256::from(10_500) / U256::from(10_000);
+    assert!(expected_value(&target) >= min_expected_value());
+}
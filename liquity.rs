@@ -0,0 +1,146 @@
+use async_trait::async_trait;
+use ethers::{
+    prelude::*,
+    providers::{Provider, Ws},
+    types::{Address, U256},
+};
+use std::sync::Arc;
+use tracing::info;
+use liquidation_bot::{
+    block_state::BlockSnapshot,
+    errors::Result,
+    math::{mul_div, to_f64},
+    models::TroveBatch,
+    strategy::{Action, Strategy},
+    thresholds::SharedThresholds,
+};
+
+abigen!(
+    LiquityTroveManager,
+    r#"[
+        function sortedTroves() external view returns (address)
+        function priceFeed() external view returns (address)
+        function MCR() external view returns (uint256)
+        function getCurrentICR(address borrower, uint256 price) external view returns (uint256)
+        function getEntireDebtAndColl(address borrower) external view returns (uint256 debt, uint256 coll, uint256 pendingDebtReward, uint256 pendingCollReward)
+        function checkRecoveryMode(uint256 price) external view returns (bool)
+        function getTCR(uint256 price) external view returns (uint256)
+        function batchLiquidateTroves(address[] troveArray) external
+    ]"#
+);
+
+abigen!(
+    LiquitySortedTroves,
+    r#"[
+        function getLast() external view returns (address)
+        function getPrev(address id) external view returns (address)
+    ]"#
+);
+
+abigen!(
+    LiquityPriceFeed,
+    r#"[function lastGoodPrice() external view returns (uint256)]"#
+);
+
+pub const LIQUITY_STRATEGY: &str = "liquity";
+pub const LIQUITY_TROVE_MANAGER: &str = "0xA39739EF8b0231DbFA0DcdA07d7e29faAbCf4bb2";
+// Gas per trove in a batch, plus the call itself
+pub const GAS_PER_TROVE: u64 = 150_000;
+pub const BATCH_BASE_GAS: u64 = 100_000;
+// Troves per transaction; a batch past this risks the block gas limit
+const MAX_BATCH: usize = 20;
+// Liquidators are paid the trove's gas reserve plus this share of its collateral
+const GAS_COMPENSATION: u64 = 200;
+const COLLATERAL_COMPENSATION_DIVISOR: u64 = 200;
+
+// Liquity and its forks: troves kept sorted by collateral ratio, liquidated
+// by anyone once below the minimum ratio (or below the system ratio in
+// recovery mode). The Stability Pool absorbs the debt, so nothing is repaid;
+// the caller is paid the trove's gas reserve and 0.5% of its collateral.
+pub struct LiquityStrategy {
+    provider: Arc<Provider<Ws>>,
+    thresholds: SharedThresholds,
+    trove_manager: Address,
+}
+
+impl LiquityStrategy {
+    pub fn new(provider: Arc<Provider<Ws>>, thresholds: SharedThresholds, trove_manager: Address) -> Self {
+        Self {
+            provider,
+            thresholds,
+            trove_manager,
+        }
+    }
+
+    // Riskiest troves first, stopping at the first healthy one
+    async fn liquidatable(&self, gas_price: U256) -> Result<Option<TroveBatch>> {
+        let manager = LiquityTroveManager::new(self.trove_manager, self.provider.clone());
+        let sorted = LiquitySortedTroves::new(manager.sorted_troves().call().await?, self.provider.clone());
+        let price = LiquityPriceFeed::new(manager.price_feed().call().await?, self.provider.clone())
+            .last_good_price()
+            .call()
+            .await?;
+        let mcr = manager.mcr().call().await?;
+        // In recovery mode troves under the system ratio can go too, capped at MCR worth of collateral
+        let threshold = if manager.check_recovery_mode(price).call().await? {
+            manager.get_tcr(price).call().await?
+        } else {
+            mcr
+        };
+
+        let one = U256::exp10(18);
+        let mut troves = Vec::new();
+        let mut reward = U256::zero();
+        let mut trove = sorted.get_last().call().await?;
+        while !trove.is_zero() && troves.len() < MAX_BATCH {
+            let icr = manager.get_current_icr(trove, price).call().await?;
+            if icr >= threshold {
+                break;
+            }
+            let (debt, coll, _, _) = manager.get_entire_debt_and_coll(trove).call().await?;
+            let seized = if icr >= mcr {
+                coll.min(mul_div(debt, mcr, price).unwrap_or(coll))
+            } else {
+                coll
+            };
+            // Reward in USD (LUSD at $1, price is USD per ETH with 18 decimals)
+            reward += mul_div(seized / COLLATERAL_COMPENSATION_DIVISOR, price, one).unwrap_or_default()
+                + U256::from(GAS_COMPENSATION) * one;
+            troves.push(trove);
+            trove = sorted.get_prev(trove).call().await?;
+        }
+        if troves.is_empty() {
+            return Ok(None);
+        }
+
+        let gas = U256::from(BATCH_BASE_GAS + GAS_PER_TROVE * troves.len() as u64);
+        let gas_usd = mul_div(gas * gas_price, price, one).unwrap_or_default();
+        if reward <= gas_usd || reward - gas_usd < self.thresholds.load().min_profit {
+            return Ok(None);
+        }
+        Ok(Some(TroveBatch {
+            trove_manager: self.trove_manager,
+            troves,
+            expected_profit: reward - gas_usd,
+        }))
+    }
+}
+
+#[async_trait]
+impl Strategy for LiquityStrategy {
+    fn name(&self) -> &str {
+        LIQUITY_STRATEGY
+    }
+
+    async fn on_block(&self, snapshot: &BlockSnapshot) -> Result<Vec<Action>> {
+        let Some(batch) = self.liquidatable(snapshot.gas_price).await? else {
+            return Ok(Vec::new());
+        };
+        info!(
+            troves = batch.troves.len(),
+            profit = to_f64(batch.expected_profit) / 1e18,
+            "🏺 Liquity troves under water"
+        );
+        Ok(vec![Action::TroveLiquidation(batch)])
+    }
+}
@@ -0,0 +1,70 @@
+use clap::ValueEnum;
+use opentelemetry::KeyValue;
+use opentelemetry_otlp::WithExportConfig;
+use opentelemetry_sdk::{runtime, trace, Resource};
+use tracing::{Level, Metadata};
+use tracing_subscriber::{filter::filter_fn, fmt, prelude::*, EnvFilter};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum LogFormat {
+    Text,
+    // One object per line with the event's fields and enclosing spans, for log aggregation
+    Json,
+}
+
+// Flushes exported traces when dropped
+pub struct Guard {
+    exporting: bool,
+}
+
+impl Drop for Guard {
+    fn drop(&mut self) {
+        if self.exporting {
+            opentelemetry::global::shutdown_tracer_provider();
+        }
+    }
+}
+
+// Levels come from RUST_LOG (e.g. RUST_LOG=liquidation_bot=debug), defaulting
+// to info. With OTEL_EXPORTER_OTLP_ENDPOINT set, spans are also exported over
+// OTLP/gRPC, one trace per scan cycle or execution attempt.
+pub fn init(format: LogFormat) -> anyhow::Result<Guard> {
+    let filter = EnvFilter::try_from_default_env().unwrap_or_else(|_| EnvFilter::new("info"));
+    let output = match format {
+        LogFormat::Text => fmt::layer().boxed(),
+        LogFormat::Json => fmt::layer().json().flatten_event(true).with_span_list(true).boxed(),
+    };
+
+    let endpoint = std::env::var("OTEL_EXPORTER_OTLP_ENDPOINT").ok();
+    let otel = match &endpoint {
+        Some(endpoint) => Some(
+            tracing_opentelemetry::layer()
+                .with_tracer(tracer(endpoint)?)
+                .with_filter(filter_fn(exported)),
+        ),
+        None => None,
+    };
+
+    tracing_subscriber::registry()
+        .with(output.with_filter(filter))
+        .with(otel)
+        .init();
+    Ok(Guard {
+        exporting: endpoint.is_some(),
+    })
+}
+
+fn tracer(endpoint: &str) -> anyhow::Result<trace::Tracer> {
+    let service = std::env::var("OTEL_SERVICE_NAME").unwrap_or_else(|_| "liquidation-bot".to_string());
+    Ok(opentelemetry_otlp::new_pipeline()
+        .tracing()
+        .with_exporter(opentelemetry_otlp::new_exporter().tonic().with_endpoint(endpoint))
+        .with_trace_config(trace::config().with_resource(Resource::new(vec![KeyValue::new("service.name", service)])))
+        .install_batch(runtime::Tokio)?)
+}
+
+// Our spans and events at info and above, plus the trace-level `rpc` span
+// ethers opens around every JSON-RPC request
+fn exported(meta: &Metadata) -> bool {
+    *meta.level() <= Level::INFO || (meta.is_span() && meta.name() == "rpc" && meta.target().starts_with("ethers_providers"))
+}
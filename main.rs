@@ -1,14 +1,122 @@
 use ethers::{
     prelude::*,
-    providers::{Provider, Ws, Http},
-    types::{Address, U256, H256, Transaction, BlockNumber},
+    providers::{Provider, Ws},
+    types::{transaction::eip2718::TypedTransaction, Address, U256, H256, Transaction},
     contract::abigen,
 };
-use std::{sync::Arc, time::Duration, collections::HashMap};
-use tokio::{sync::RwLock, time::interval};
+use std::{sync::Arc, time::Duration, collections::{HashMap, HashSet}, path::PathBuf};
+use tokio::{sync::{mpsc, OnceCell, RwLock}, time::interval};
 use redis::{AsyncCommands, Client as RedisClient};
-use serde::{Deserialize, Serialize};
-use anyhow::{Result, Context};
+use clap::{Parser, Subcommand};
+use tracing::{debug, error, info, instrument, warn};
+
+mod aave;
+mod allocator;
+mod backtest;
+mod balance;
+mod breaker;
+mod compound;
+mod dryrun;
+mod fork;
+mod indexer;
+mod liquidation_strategy;
+mod liquity;
+mod logging;
+mod maker;
+mod queue;
+mod monitoring;
+mod relay_stats;
+mod reporting;
+mod risk;
+mod settings;
+mod tips;
+mod treasury;
+
+#[cfg(all(test, feature = "anvil-tests"))]
+mod fork_tests;
+#[cfg(test)]
+mod liquidation_tests;
+
+use aave::AaveMarket;
+use allocator::{AllocatorConfig, CapitalAllocator, Reservation};
+use balance::{BalanceConfig, BalanceMonitor};
+use breaker::{BreakerConfig, CircuitBreaker};
+use compound::{CompoundV2, COMPOUND_V2_COMPTROLLER};
+use indexer::{PositionIndex, COMPOUND_V2_DEPLOYED_AT};
+use liquidation_bot::{
+    block_state::BlockStateService,
+    chain_tracker::{Reorg, REORG_DEPTH},
+    bloxroute::{BloxrouteClient, BloxrouteConfig},
+    builders::{BuilderRegistry, KNOWN_BUILDERS},
+    chainlink,
+    cex::{CexConfig, CexFeed},
+    chains::ChainProfile,
+    coingecko::{CoinGeckoClient, CoinGeckoConfig},
+    cow::{CowClient, CowConfig, CowOrder},
+    dex::{concurrency::DEFAULT_SCAN_CONCURRENCY, DexManager, ScanConcurrency, TokenFilter},
+    errors::{self, BotError, ExecutionError, Result, SimulationError},
+    eth_price::{EthPriceConfig, EthPriceService},
+    etherscan::{EtherscanClient, EtherscanConfig},
+    feed::{ExecutionFeed, OpportunityFeed},
+    gas::{attach_access_list, eip1559, Confidence},
+    grpc::ArbitrageService,
+    executor::ArbitrageExecutor,
+    math,
+    pool_cache::PoolStateCache,
+    price::UsdPricer,
+    reconcile::{self, DriftTracker, Reconciliation},
+    redis_stream::OpportunityStream,
+    registry::PoolRegistry,
+    safety::{ContractPolicy, ContractScreen, HoneypotDetector},
+    sandwich::{self, SandwichGuard},
+    scoring::{OpportunityFeatures, OpportunityScorer, ScoringConfig},
+    signer::{SignerSource, WalletSigner},
+    simulation::{CallSimulator, ForkSimulator, SimulationRequest, Simulator, TenderlySimulator},
+    subgraph::{SubgraphClient, SubgraphConfig},
+    models::{
+        ArbitrageOpportunity, AuctionTake, DexType, ExecutionReport, LiquidationTarget, OracleBackrun, PoolState, TroveBatch,
+    },
+    multi_provider::{MultiProvider, ProviderLimits},
+    nonce::NonceManager,
+    oracle::{PriceSource, PythSource, RedstoneSource, PYTH_MAINNET, REDSTONE_GATEWAY, REDSTONE_SERVICE},
+    policy::{PolicyRules, PolicySigner, PolicyViolation, SigningPolicy},
+    #[cfg(feature = "postgres-sink")]
+    opportunity_sink::OpportunitySink,
+    #[cfg(feature = "kafka-sink")]
+    kafka_sink::KafkaSink,
+    strategy::{arbitrage::ARBITRAGE_STRATEGY, Action, ActionExecutor, ArbitrageStrategy, CexDexStrategy, Engine},
+    thresholds::{SharedThresholds, Thresholds},
+    token_sets::TargetTokens,
+};
+use liquidation_strategy::LiquidationStrategy;
+use liquity::{LiquityStrategy, LiquityTroveManager, LIQUITY_STRATEGY, LIQUITY_TROVE_MANAGER};
+use queue::TargetQueue;
+use maker::{MakerStrategy, DAI, MAKER_DOG, MAKER_STRATEGY, TAKE_GAS as MAKER_TAKE_GAS};
+use monitoring::{metrics_server, AlertLevel, AlertManager, Incident, Metrics};
+use relay_stats::{BundleOutcome, RelayStatsTracker};
+use reporting::{DailyReport, ExecutionRecord};
+use risk::{Exposure, RiskLimits, RiskManager, RiskPermit};
+use tips::TipOptimizer;
+use treasury::{Treasury, TreasuryConfig};
+
+const LIQUIDATION_STRATEGY: &str = "liquidation";
+const LIQUIDATION_GAS: u64 = 300_000;
+// Signed size of a liquidation transaction, what a rollup's L1 data fee is charged on
+const LIQUIDATION_TX_BYTES: u64 = 420;
+const COMPOUND_V3_COMET: &str = "0xc3d688B66703497DAA19211EEdff47f25384cdc3";
+// Aave's oracle quotes USD with 8 decimals; thresholds use 18
+const ORACLE_TO_USD: u64 = 10_000_000_000;
+// Borrowers this close to liquidation are re-checked against pending oracle updates
+const WATCH_HEALTH_FACTOR: f64 = 1.1;
+// Opportunities read from the Redis stream per round trip
+const STREAM_BATCH: usize = 16;
+// Accounts per getUserAccountData multicall
+const ACCOUNT_BATCH: usize = 200;
+// Blocks after which a streamed opportunity is dropped unexecuted
+const MAX_STREAM_AGE_BLOCKS: u64 = 2;
+
+// The hot wallet's signing client, behind its signing policy
+type WalletClient = SignerMiddleware<Arc<Provider<Ws>>, PolicySigner<WalletSigner>>;
 
 // Generate contract bindings
 abigen!(
@@ -21,17 +129,10 @@ abigen!(
     "./abi/AavePool.json"
 );
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
-struct LiquidationTarget {
-    protocol: String,
-    user: Address,
-    collateral_asset: Address,
-    debt_asset: Address,
-    debt_amount: U256,
-    health_factor: f64,
-    expected_profit: U256,
-    gas_price: U256,
-}
+abigen!(
+    Erc20Balance,
+    r#"[function balanceOf(address owner) external view returns (uint256)]"#
+);
 
 #[derive(Debug, Clone)]
 struct Config {
@@ -39,33 +140,215 @@ struct Config {
     primary_rpc: String,
     backup_rpc: String,
     ws_endpoint: String,
+    // Rate limits for PRIMARY_RPC and BACKUP_RPC, in that order
+    rpc_limits: Vec<ProviderLimits>,
+    
+    // Chain the wallet signs for, and the venues and contracts deployed on it
+    chain: ChainProfile,
     
     // Contract addresses
     executor_address: Address,
-    aave_pool: Address,
+    arbitrage_executor: Option<Address>,
     compound_comet: Address,
+    compound_v2_comptroller: Address,
+    maker_dog: Address,
+    // Liquity or a fork
+    liquity_trove_manager: Address,
     
     // MEV settings
     flashbots_relay: String,
-    bloxroute_auth: String,
+    mev_relays: Vec<String>,
+    // Blocks a public transaction may stay pending before it's replaced
+    stuck_tx_blocks: u64,
+    // Bundles simulated to pay builders less than this per gas are not sent
+    min_bundle_gas_price: U256,
+    // Signing policy: contracts the wallet may call (defaults to the bot's own),
+    // caps per transaction and per day, and per-token caps in base units
+    policy_destinations: Option<Vec<Address>>,
+    policy_max_value: U256,
+    policy_max_gas_price: U256,
+    policy_max_daily_gas: U256,
+    policy_token_caps: Vec<(Address, U256)>,
+    // bloXroute mempool stream and the bundle routes that go through it
+    bloxroute: BloxrouteConfig,
+    // Public executions a sandwich could take too much of go private instead
+    sandwich: SandwichGuard,
+    // Bundle liquidations behind pending Chainlink updates
+    enable_backrun: bool,
     
-    // Thresholds
+    // Thresholds, reloadable at runtime
     min_profit_usd: U256,
+    min_expected_value: U256,
+    min_liquidity_usd: U256,
     max_gas_price: U256,
+    // Cap on the builder tip per gas of type-2 transactions
+    max_priority_fee: U256,
     health_factor_threshold: f64,
+    scan_interval: Duration,
+    
+    // Strategies
+    enable_arbitrage: bool,
+    // Take Maker Clipper auctions
+    enable_maker: bool,
+    // Liquidate Liquity troves
+    enable_liquity: bool,
+    // Longest swap loop the pathfinder searches
+    max_hops: usize,
+    // Pool reads a scan keeps in flight at most
+    scan_concurrency: usize,
+    // Best-ranked pools the arbitrage scan keeps per token pair; 0 keeps all
+    pools_per_pair: usize,
+    // Submit arbitrage transactions; otherwise they are only simulated
+    execute: bool,
+    // Skip pools with fee-on-transfer tokens rather than pricing their tax
+    exclude_taxed_tokens: bool,
+    // Tokens the arbitrage scan trades between
+    target_tokens: TargetTokens,
+    // Only scan these tokens, when set
+    token_allowlist: Option<Vec<Address>>,
+    // Never scan these tokens (scams, rebasing tokens)
+    token_denylist: Vec<Address>,
+    // Round-trip unknown tokens through the V2 router before scanning their pools
+    honeypot_check: bool,
+    // What to do with unverified and proxy tokens, checked on Etherscan
+    contract_policy: ContractPolicy,
+    // SQLite file holding discovered pools across restarts
+    registry_path: String,
+    // Postgres for opportunity history (postgres-sink feature)
+    database_url: Option<String>,
+    // Kafka for opportunity and execution events (kafka-sink feature)
+    kafka_brokers: Option<String>,
+    kafka_opportunities_topic: String,
+    kafka_executions_topic: String,
+    // Redis stream to publish opportunities to, for separate executor processes
+    opportunity_stream: Option<String>,
+    // Consumer group shared by those executors
+    stream_group: String,
+    // False when only stream consumers should execute arbitrage
+    execute_locally: bool,
+    // Serve the gRPC arbitrage API on this port
+    grpc_port: Option<u16>,
+    // Alert when realized profit trails estimates by this many bps on average
+    drift_alert_bps: f64,
+    
+    // Execution check before signing: "none", "call", "anvil" or "tenderly"
+    simulation_backend: String,
+    // Running anvil to simulate on; spawned from PRIMARY_RPC when unset
+    anvil_ws: Option<String>,
+    // Tenderly account, project and access key
+    tenderly: Option<(String, String, String)>,
+    
+    // Prices behind health factors: "aave" (the Aave oracle), "pyth" or "redstone"
+    oracle_backend: String,
+    // asset -> Pyth price id or RedStone feed id
+    oracle_feeds: Vec<(Address, String)>,
+    pyth_contract: Address,
+    // Latest off-chain Pyth prices instead of the posted ones
+    pyth_hermes: Option<String>,
+    redstone_gateway: String,
+    redstone_service: String,
     
     // Redis
     redis_url: String,
+    
+    // Where the hot wallet's key is loaded from
+    signer: SignerSource,
+    
+    // Wallet balances
+    balance: BalanceConfig,
+    
+    // When execution halts on its own
+    breaker: BreakerConfig,
+    
+    // Sources, cache TTL and staleness limit of the ETH/USD price
+    eth_price: EthPriceConfig,
+    
+    // CoinGecko key, cache TTL and request spacing
+    coingecko: CoinGeckoConfig,
+    
+    // Etherscan key, rate and response cache
+    etherscan: EtherscanConfig,
+    
+    // The Graph endpoints listing pools and borrowers at startup
+    subgraph: SubgraphConfig,
+    
+    // Limits across all strategies: trade size, flash loans, executions per block, daily gas
+    risk: RiskLimits,
+    
+    // Profit collection and sweeps to cold storage
+    treasury: TreasuryConfig,
+    
+    // Arbitrage through CoW Protocol orders when the spread can wait for a batch
+    cow: CowConfig,
+    
+    // Exchange tickers arbitrage pools are compared against
+    cex: CexConfig,
+    
+    // Where settings were read from, for reloads
+    settings: settings::Sources,
 }
 
 pub struct LiquidationBot {
     config: Config,
+    // Live copy of the config thresholds, swapped on reload
+    thresholds: SharedThresholds,
     provider: Arc<Provider<Ws>>,
-    http_provider: Arc<Provider<Http>>,
+    http_provider: Arc<Provider<MultiProvider>>,
     executor: LiquidationExecutor<Provider<Ws>>,
+    aave: Arc<AaveMarket<Provider<Ws>>>,
+    compound_v2: Arc<CompoundV2<Provider<Ws>>>,
+    // Median ETH/USD, for pricing gas
+    eth_price: Arc<EthPriceService<Provider<Ws>>>,
+    // One CoinGecko client for every off-chain price, under one rate limit
+    coingecko: Arc<CoinGeckoClient>,
+    // Contract sources and ABIs, cached on disk; None without ETHERSCAN_API_KEY
+    etherscan: Option<Arc<EtherscanClient>>,
+    subgraph: Option<Arc<SubgraphClient>>,
     redis: Arc<RedisClient>,
-    positions: Arc<RwLock<HashMap<Address, LiquidationTarget>>>,
-    wallet: LocalWallet,
+    // Every Aave account with a position, persisted in Redis
+    position_index: Arc<PositionIndex>,
+    // Every Compound V2 borrower, built once the markets are read
+    compound_v2_index: Arc<OnceCell<PositionIndex>>,
+    // Liquidatable positions, most urgent first
+    positions: Arc<RwLock<TargetQueue>>,
+    // Aave borrowers below WATCH_HEALTH_FACTOR, where one price update can tip them over
+    watchlist: Arc<RwLock<HashSet<Address>>>,
+    // Transactions sent within the last REORG_DEPTH blocks, by the head they
+    // were sent at, re-checked when the chain reorganises
+    recent_executions: Arc<RwLock<HashMap<H256, U64>>>,
+    scorer: Arc<OpportunityScorer>,
+    allocator: Arc<CapitalAllocator>,
+    // Consulted before the allocator on every execution
+    risk: Arc<RiskManager>,
+    alerts: Arc<AlertManager>,
+    // Halts execution on runaway failures or losses until re-armed
+    breaker: Arc<CircuitBreaker>,
+    metrics: Arc<Metrics>,
+    // Block builders bundles are fanned out to
+    builders: Arc<BuilderRegistry>,
+    relay_stats: Arc<RelayStatsTracker>,
+    nonces: Arc<NonceManager<WalletClient>>,
+    // Builder payment per bundle, learnt from inclusion at each tip level
+    tips: Arc<TipOptimizer>,
+    block_state: Arc<BlockStateService>,
+    dex: Arc<DexManager>,
+    pool_cache: Arc<PoolStateCache>,
+    // Actionable arbitrage and its execution, for external consumers
+    feed: OpportunityFeed,
+    executions: ExecutionFeed,
+    // Realized against estimated profit, per strategy
+    drift: Arc<HashMap<&'static str, DriftTracker>>,
+    registry: Option<Arc<PoolRegistry>>,
+    simulator: Option<Arc<dyn Simulator>>,
+    // Pull oracle pricing Aave positions, in place of the Aave oracle
+    price_source: Option<Arc<dyn PriceSource>>,
+    arbitrage: Option<Arc<ArbitrageExecutor<Provider<Ws>>>>,
+    // Places wide-spread arbitrage as CoW orders from the wallet's balances
+    cow: Option<Arc<CowClient>>,
+    // Set with BLOXROUTE_AUTH
+    bloxroute: Option<Arc<BloxrouteClient>>,
+    // Signs through the policy; messages for relays and builders use the bare key
+    wallet: PolicySigner<WalletSigner>,
 }
 
 impl LiquidationBot {
@@ -74,118 +357,746 @@ impl LiquidationBot {
         let ws = Ws::connect(&config.ws_endpoint).await?;
         let provider = Arc::new(Provider::new(ws).interval(Duration::from_millis(100)));
         
-        // HTTP provider for fallback
-        let http_provider = Arc::new(Provider::<Http>::try_from(&config.primary_rpc)?);
+        // HTTP providers for fallback, each within its own rate limit and quota
+        let endpoints: Vec<(String, ProviderLimits)> = [&config.primary_rpc, &config.backup_rpc]
+            .into_iter()
+            .cloned()
+            .zip(config.rpc_limits.iter().cloned().chain(std::iter::repeat(ProviderLimits::default())))
+            .collect();
+        // Requests spill over between endpoints, so together they set the pace
+        let http_rate: f64 = endpoints.iter().map(|(_, limits)| limits.requests_per_sec).sum();
+        let http_provider = Arc::new(Provider::new(MultiProvider::new(endpoints)?));
         
         // Load wallet
-        let wallet = std::env::var("PRIVATE_KEY")?
-            .parse::<LocalWallet>()?
-            .with_chain_id(1u64);
+        let key = config.signer.load(config.chain.chain_id).await?;
+        
+        // Every transaction the wallet signs is checked against the policy first
+        let alerts = Arc::new(AlertManager::new());
+        let (violations_tx, violations) = mpsc::unbounded_channel();
+        tokio::spawn(report_policy_violations(alerts.clone(), violations));
+        let policy = SigningPolicy::new(config.policy_rules(key.address()))?
+            .with_exposure(
+                "function liquidate(string protocol, address user, address collateralAsset, address debtAsset, uint256 debtToCover, bool useFlashLoan)",
+                Some(3),
+                4,
+            )?
+            .with_exposure(
+                "function liquidateWithTip(string protocol, address user, address collateralAsset, address debtAsset, uint256 debtToCover, bool useFlashLoan, uint256 coinbaseTip)",
+                Some(3),
+                4,
+            )?
+            .with_exposure(
+                "function executeArbitrage(address flashToken, uint256 flashAmount, (address,address,uint256,uint256,bytes)[] legs, uint256 minProfit)",
+                Some(0),
+                1,
+            )?
+            // A flash swap is exposed to both what it borrows and what it repays
+            .with_exposure(
+                "function executeFlashSwap(address pair, address borrowToken, uint256 borrowAmount, address repayToken, uint256 repayAmount, (address,address,uint256,uint256,bytes)[] legs, uint256 minProfit)",
+                Some(1),
+                2,
+            )?
+            .with_exposure(
+                "function executeFlashSwap(address pair, address borrowToken, uint256 borrowAmount, address repayToken, uint256 repayAmount, (address,address,uint256,uint256,bytes)[] legs, uint256 minProfit)",
+                Some(3),
+                4,
+            )?
+            .with_exposure(
+                "function executeV3Flash(address pool, address borrowToken, uint256 borrowAmount, (address,address,uint256,uint256,bytes)[] legs, uint256 minProfit)",
+                Some(1),
+                2,
+            )?
+            // A Maker lot costs its amount at up to the max price (ray) in DAI
+            .with_priced_exposure(
+                "function takeAuction(address clipper, uint256 id, uint256 amt, uint256 maxPrice, address gemJoin, uint24 poolFee)",
+                DAI.parse().unwrap(),
+                2,
+                3,
+                U256::exp10(27),
+            )?
+            // Treasury sweeps move the executor's balance to the owner
+            .with_function("function withdrawProfit()")?
+            .with_function("function withdrawToken(address token)")?;
+        // Nothing but the calls above may be signed for either executor
+        let mut policy = policy.with_guarded(config.executor_address).with_alerts(violations_tx);
+        if let Some(arbitrage_executor) = config.arbitrage_executor {
+            policy = policy.with_guarded(arbitrage_executor);
+        }
+        let wallet = PolicySigner::new(key.clone(), Arc::new(policy));
         
         // Initialize executor contract
         let client = Arc::new(SignerMiddleware::new(
             provider.clone(),
             wallet.clone(),
         ));
+        // Every public transaction from the wallet takes its nonce here
+        let nonces = Arc::new(NonceManager::new(client.clone(), wallet.address(), config.stuck_tx_blocks));
         let executor = LiquidationExecutor::new(config.executor_address, client);
         
         // Connect to Redis
         let redis = Arc::new(RedisClient::open(config.redis_url.as_str())?);
         
+        // Share wallet gas and capital with any other strategy on this key
+        let allocator = Arc::new(CapitalAllocator::new(AllocatorConfig::default()));
+        allocator.register(LIQUIDATION_STRATEGY);
+        if config.enable_maker {
+            allocator.register(MAKER_STRATEGY);
+        }
+        if config.enable_liquity {
+            allocator.register(LIQUITY_STRATEGY);
+        }
+        
+        // One consistent view of gas, prices and touched positions per block
+        let block_state = Arc::new(
+            BlockStateService::new(provider.clone(), config.chain.aave_pool, vec![config.chain.native_usd_feed])
+                .with_gas_model(config.chain.gas_model),
+        );
+        
+        // Builders and Flashbots stats endpoints authenticate with the searcher key
+        let builders = Arc::new(
+            BuilderRegistry::new(&config.mev_relays, key.clone()).with_simulation_endpoint(&config.flashbots_relay),
+        );
+        let relay_stats = Arc::new(RelayStatsTracker::new(
+            config.flashbots_relay.clone(),
+            builders.clone(),
+            key.clone(),
+        ));
+        
+        let mut dex = DexManager::new();
+        dex.set_token_filter(TokenFilter::new(config.token_allowlist.clone(), config.token_denylist.clone()));
+        dex.set_flash_loan_providers(config.chain.flash_loan_providers.clone());
+        if config.enable_arbitrage {
+            // Pool discovery and refreshes are request bursts, so they go through
+            // the rate-limited endpoints rather than the websocket
+            config.chain.register_venues(&mut dex, http_provider.clone());
+            allocator.register(ARBITRAGE_STRATEGY);
+            // A second's worth of the HTTP endpoints' rate limits at most; the
+            // scan backs off further on its own when throttled
+            ScanConcurrency::global().set_ceiling(config.scan_concurrency.min(http_rate as usize));
+        }
+        let dex = Arc::new(dex);
+        let registry = if config.enable_arbitrage {
+            Some(Arc::new(PoolRegistry::open(&config.registry_path).await?))
+        } else {
+            None
+        };
+        
+        let simulator: Option<Arc<dyn Simulator>> = match config.simulation_backend.as_str() {
+            "none" => None,
+            "call" => Some(Arc::new(CallSimulator::mainnet(provider.clone()))),
+            "anvil" => Some(Arc::new(match &config.anvil_ws {
+                Some(anvil_ws) => ForkSimulator::connect(provider.clone(), anvil_ws, &config.primary_rpc).await?,
+                None => ForkSimulator::spawn(provider.clone(), &config.primary_rpc).await?,
+            })),
+            "tenderly" => match &config.tenderly {
+                Some((account, project, key)) => Some(Arc::new(TenderlySimulator::new(account, project, key))),
+                None => {
+                    return Err(BotError::Config(
+                        "SIMULATION_BACKEND=tenderly needs TENDERLY_USER, TENDERLY_PROJECT and TENDERLY_ACCESS_KEY".to_string(),
+                    ))
+                }
+            },
+            other => return Err(BotError::Config(format!("unknown SIMULATION_BACKEND {}", other))),
+        };
+        
+        let price_source: Option<Arc<dyn PriceSource>> = match config.oracle_backend.as_str() {
+            "aave" => None,
+            "pyth" => {
+                let mut source = PythSource::new(provider.clone(), config.pyth_contract);
+                if let Some(hermes) = &config.pyth_hermes {
+                    source = source.with_hermes(hermes);
+                }
+                for (asset, id) in &config.oracle_feeds {
+                    let id = id
+                        .parse()
+                        .map_err(|_| BotError::Config(format!("invalid Pyth price id {}", id)))?;
+                    source = source.with_feed(*asset, id);
+                }
+                Some(Arc::new(source))
+            }
+            "redstone" => Some(Arc::new(config.oracle_feeds.iter().fold(
+                RedstoneSource::new(&config.redstone_gateway, &config.redstone_service),
+                |source, (asset, feed)| source.with_feed(*asset, feed),
+            ))),
+            other => return Err(BotError::Config(format!("unknown ORACLE_BACKEND {}", other))),
+        };
+        let mut aave = AaveMarket::new(provider.clone(), config.chain.aave_pool);
+        if let Some(source) = &price_source {
+            aave = aave.with_price_source(source.clone());
+        }
+        
+        let bloxroute = BloxrouteClient::new(config.bloxroute.clone()).map(Arc::new);
+        
+        // Without an executor contract arbitrage opportunities are only reported
+        let arbitrage = match config.arbitrage_executor {
+            Some(address) => {
+                // Bundles are signed with a separate reputation key when one is given
+                let bundle_signer = match std::env::var("FLASHBOTS_SIGNER_KEY") {
+                    Ok(key) => WalletSigner::Local(key.parse::<LocalWallet>()?),
+                    Err(_) => key.clone(),
+                };
+                let mut executor = ArbitrageExecutor::new(provider.clone(), wallet.clone(), address, dex.clone(), config.execute)
+                    .with_flashbots(provider.clone(), &config.flashbots_relay, bundle_signer)?
+                    .with_min_bundle_gas_price(config.min_bundle_gas_price)
+                    .with_sandwich_guard(config.sandwich)
+                    .with_nonce_manager(nonces.clone());
+                // The Flashbots relay always gets the bundle, since inclusion is tracked through it
+                let route = config.bloxroute.route(ARBITRAGE_STRATEGY);
+                if route.builders() {
+                    executor = executor.with_builders(builders.clone());
+                }
+                if let Some(bloxroute) = bloxroute.as_ref().filter(|_| route.bloxroute()) {
+                    executor = executor.with_bloxroute(bloxroute.clone());
+                }
+                if let Some(simulator) = &simulator {
+                    executor = executor.with_simulator(simulator.clone());
+                }
+                Some(Arc::new(executor))
+            }
+            None => None,
+        };
+        
+        let cow = if config.cow.enabled {
+            match CowClient::new(config.chain.chain_id, key.clone(), config.cow.clone()) {
+                Some(client) => Some(Arc::new(client)),
+                None => return Err(BotError::Config(format!("ENABLE_COW: CoW Protocol doesn't run on {}", config.chain.name))),
+            }
+        } else {
+            None
+        };
+        
+        let drift = [LIQUIDATION_STRATEGY, ARBITRAGE_STRATEGY]
+            .into_iter()
+            .map(|strategy| (strategy, DriftTracker::new(config.drift_alert_bps)))
+            .collect();
+        
+        let compound_v2 = Arc::new(CompoundV2::new(provider.clone(), config.compound_v2_comptroller));
+        let position_index = Arc::new(PositionIndex::new(redis.clone(), config.chain.aave_pool, config.chain.aave_pool_deployed_at));
+        let breaker = Arc::new(CircuitBreaker::new(config.breaker.clone(), redis.clone(), alerts.clone()));
+        let risk = Arc::new(RiskManager::new(config.risk.clone()));
+        let coingecko = Arc::new(CoinGeckoClient::new(&config.chain, config.coingecko.clone()));
+        let etherscan = EtherscanClient::open(config.chain.chain_id, config.etherscan.clone()).await?.map(Arc::new);
+        let mut eth_price =
+            EthPriceService::new(provider.clone(), &config.chain, config.eth_price.clone()).with_coingecko(coingecko.clone());
+        if let Some(etherscan) = &etherscan {
+            eth_price = eth_price.with_etherscan(etherscan.clone());
+        }
+        let eth_price = Arc::new(eth_price);
+        let subgraph = SubgraphClient::new(config.subgraph.clone()).map(Arc::new);
+        
         Ok(Self {
+            thresholds: config.thresholds().shared(),
             config,
             provider,
             http_provider,
             executor,
+            aave: Arc::new(aave),
+            compound_v2,
+            eth_price,
+            coingecko,
+            etherscan,
+            subgraph,
+            position_index,
+            compound_v2_index: Arc::new(OnceCell::new()),
             redis,
-            positions: Arc::new(RwLock::new(HashMap::new())),
+            positions: Arc::new(RwLock::new(TargetQueue::new())),
+            watchlist: Arc::new(RwLock::new(HashSet::new())),
+            recent_executions: Arc::new(RwLock::new(HashMap::new())),
+            scorer: Arc::new(OpportunityScorer::new(ScoringConfig::default())),
+            allocator,
+            risk,
+            alerts,
+            breaker,
+            metrics: Arc::new(Metrics::new()),
+            builders,
+            relay_stats,
+            nonces,
+            tips: Arc::new(TipOptimizer::new()),
+            block_state,
+            dex,
+            pool_cache: Arc::new(PoolStateCache::new(provider.clone())),
+            feed: OpportunityFeed::new(),
+            executions: ExecutionFeed::new(),
+            drift: Arc::new(drift),
+            registry,
+            simulator,
+            price_source,
+            arbitrage,
+            cow,
+            bloxroute,
             wallet,
         })
     }
     
     pub async fn run(&self) -> Result<()> {
-        println!("🚀 Liquidation bot starting...");
+        info!("🚀 Liquidation bot starting...");
+        
+        // Strategies see the same blocks, mempool and logs and share one executor
+        let mut engine = Engine::new(Arc::new(self.clone()));
+        match (&self.bloxroute, self.config.bloxroute.stream) {
+            (Some(bloxroute), Some(stream)) => engine.set_mempool(bloxroute.subscribe(stream)),
+            (None, Some(_)) => warn!("⚠️ BLOXROUTE_STREAM needs BLOXROUTE_AUTH, reading the node's txpool"),
+            _ => {}
+        }
+        engine.register(Arc::new(LiquidationStrategy::new(self.clone())));
+        if self.config.enable_maker {
+            let maker = MakerStrategy::new(
+                self.provider.clone(),
+                self.config
+                    .chain
+                    .uniswap_v3_quoter
+                    .ok_or_else(|| BotError::Config("ENABLE_MAKER needs UNISWAP_V3_QUOTER".to_string()))?,
+                self.thresholds.clone(),
+                self.config.maker_dog,
+            );
+            maker.load_recent().await?;
+            engine.register(Arc::new(maker));
+        }
+        if self.config.enable_liquity {
+            engine.register(Arc::new(LiquityStrategy::new(
+                self.provider.clone(),
+                self.thresholds.clone(),
+                self.config.liquity_trove_manager,
+            )));
+        }
+        if self.config.enable_arbitrage {
+            let mut strategy = ArbitrageStrategy::new(
+                self.dex.clone(),
+                self.pool_cache.clone(),
+                self.thresholds.clone(),
+            )
+            .with_max_hops(self.config.max_hops)
+            .with_pools_per_pair(self.config.pools_per_pair)
+            .with_wrapped_native(self.config.chain.wrapped_native)
+            .with_taxed_tokens_excluded(self.config.exclude_taxed_tokens)
+            .with_feed(self.feed.clone())
+            .with_scorer(self.scorer.clone())
+            .with_pricer(Arc::new(
+                UsdPricer::from_env(self.provider.clone(), &self.config.chain)?.with_coingecko(self.coingecko.clone()),
+            ))
+            .with_tokens(
+                self.config
+                    .target_tokens
+                    .resolve(self.http_provider.clone(), self.registry.as_deref())
+                    .await?,
+            );
+            if self.config.honeypot_check {
+                match self.config.chain.uniswap_v2_router {
+                    Some(router) => {
+                        let detector = HoneypotDetector::new(self.provider.clone(), router, self.config.chain.wrapped_native);
+                        strategy = strategy.with_honeypot_detector(Arc::new(detector));
+                    }
+                    None => warn!(chain = %self.config.chain.name, "⚠️ No V2 router to probe tokens through, honeypot check off"),
+                }
+            }
+            if !self.config.contract_policy.allows_all() {
+                match &self.etherscan {
+                    Some(etherscan) => {
+                        let screen = ContractScreen::new(etherscan.clone(), self.config.contract_policy)
+                            .with_trusted([self.config.chain.wrapped_native, self.config.chain.usdc]);
+                        strategy = strategy.with_contract_screen(Arc::new(screen));
+                    }
+                    None => warn!("⚠️ No ETHERSCAN_API_KEY, contract verification check off"),
+                }
+            }
+            if let Some(registry) = &self.registry {
+                strategy = strategy.with_registry(registry.clone());
+            }
+            if let Some(subgraph) = &self.subgraph {
+                strategy = strategy.with_subgraph(subgraph.clone());
+            }
+            #[cfg(feature = "postgres-sink")]
+            if let Some(url) = &self.config.database_url {
+                strategy = strategy.with_sink(Arc::new(OpportunitySink::connect(url).await?));
+            }
+            #[cfg(feature = "kafka-sink")]
+            if let Some(brokers) = &self.config.kafka_brokers {
+                let sink = KafkaSink::connect(
+                    brokers,
+                    &self.config.kafka_opportunities_topic,
+                    &self.config.kafka_executions_topic,
+                )?;
+                tokio::spawn(sink.run(self.feed.clone(), self.executions.clone()));
+            }
+            if let Some(key) = &self.config.opportunity_stream {
+                let stream = OpportunityStream::new(self.redis.clone(), key, &self.config.stream_group);
+                tokio::spawn(stream.publish(self.feed.clone()));
+            }
+            if let Some(port) = self.config.grpc_port {
+                let (results, reports) = mpsc::channel(256);
+                let service = ArbitrageService::new(self.feed.clone(), strategy.pools(), results);
+                tokio::spawn(self.clone().record_reported_executions(reports));
+                tokio::spawn(async move {
+                    info!(port, "📡 gRPC API listening");
+                    let served = tonic::transport::Server::builder()
+                        .add_service(service.into_server())
+                        .serve(([0, 0, 0, 0], port).into())
+                        .await;
+                    if let Err(e) = served {
+                        error!(error = %e, "❌ gRPC server stopped");
+                    }
+                });
+            }
+            let pools = strategy.pools();
+            engine.register(Arc::new(strategy));
+            // After the arbitrage strategy, so it sees this block's pool state
+            if self.config.cex.enabled {
+                let cex = Arc::new(CexFeed::new(self.config.cex.clone()));
+                cex.spawn();
+                engine.register(Arc::new(CexDexStrategy::new(cex, pools)));
+            }
+        }
+        
+        // A breaker tripped before a restart stays tripped
+        self.breaker.sync().await?;
         
         // Spawn concurrent tasks
-        let mempool_handle = tokio::spawn(self.clone().monitor_mempool());
+        let block_state_handle = tokio::spawn(self.block_state.clone().run());
+        if self.config.enable_arbitrage {
+            tokio::spawn(self.pool_cache.clone().run());
+            tokio::spawn(self.clone().sync_registry());
+        }
+        let engine_handle = tokio::spawn(
+            Arc::new(engine).run(self.provider.clone(), self.block_state.subscribe()),
+        );
         let positions_handle = tokio::spawn(self.clone().scan_positions());
-        let oracle_handle = tokio::spawn(self.clone().monitor_oracle_updates());
+        tokio::spawn(self.clone().backfill_positions());
+        tokio::spawn(self.clone().backfill_compound_v2_borrowers());
+        tokio::spawn(self.clone().reload_config());
         let health_handle = tokio::spawn(self.clone().health_check());
+        let balance_handle = tokio::spawn(self.clone().monitor_balances());
+        let relays_handle = tokio::spawn(self.clone().track_relays());
+        let nonces_handle = tokio::spawn(self.clone().watch_nonces());
+        tokio::spawn(self.clone().watch_breaker());
+        if self.config.treasury.enabled {
+            tokio::spawn(self.clone().manage_treasury());
+        }
+        tokio::spawn(metrics_server(
+            self.metrics.clone(),
+            self.relay_stats.clone(),
+            self.feed.clone(),
+            self.breaker.clone(),
+        ));
         
         // Wait for all tasks
         tokio::try_join!(
-            mempool_handle,
+            engine_handle,
             positions_handle,
-            oracle_handle,
-            health_handle
+            health_handle,
+            balance_handle,
+            relays_handle,
+            nonces_handle,
+            block_state_handle
         )?;
         
         Ok(())
     }
     
-    // Monitor mempool for liquidation opportunities
-    async fn monitor_mempool(self) -> Result<()> {
-        let mut stream = self.provider.watch_pending_transactions().await?;
-        
-        while let Some(tx_hash) = stream.next().await {
-            // Get transaction details
-            if let Ok(Some(tx)) = self.provider.get_transaction(tx_hash).await {
-                self.analyze_transaction(tx).await?;
+    // Keep the tracked position set fresh; execution is driven per block by LiquidationStrategy
+    async fn scan_positions(self) -> Result<()> {
+        loop {
+            // Load positions from multiple protocols
+            if let Err(e) = self.scan_aave_positions().await {
+                self.handle_task_error("aave scan", e)?;
+            }
+            if let Err(e) = self.scan_compound_positions().await {
+                self.handle_task_error("compound scan", e)?;
+            }
+            if let Err(e) = self.scan_compound_v2_positions().await {
+                self.handle_task_error("compound v2 scan", e)?;
+            }
+            
+            self.scorer.prune();
+            
+            // Read each round so a reloaded interval applies to the next scan
+            tokio::time::sleep(self.thresholds.load().scan_interval).await;
+        }
+    }
+    
+    // One-off walk over the Aave pool's history so accounts that haven't
+    // touched it lately are scanned too; restartable, progress is checkpointed.
+    // With an Aave subgraph its borrower list stands in for the walk.
+    async fn backfill_positions(self) -> Result<()> {
+        if let Some(subgraph) = self.subgraph.as_ref().filter(|s| s.has_borrowers()) {
+            match subgraph.borrowers().await {
+                Ok(borrowers) => {
+                    let added = self.position_index.add(&borrowers).await?;
+                    info!(added, "🛰️ Positions seeded from subgraph");
+                    return Ok(());
+                }
+                Err(e) => warn!(error = %e, "⚠️ Subgraph borrower listing failed, backfilling from logs"),
             }
         }
+        let head = self.http_provider.get_block_number().await?;
+        match self.position_index.backfill(&self.http_provider, head).await {
+            Ok(added) => info!(added, "🗂️ Position backfill finished"),
+            Err(e) => self.handle_task_error("position backfill", e)?,
+        }
+        Ok(())
+    }
+    
+    // The same walk over Compound V2 borrows, from the Comptroller's deployment
+    async fn backfill_compound_v2_borrowers(self) -> Result<()> {
+        let head = self.http_provider.get_block_number().await?;
+        let backfill = async { self.compound_v2_index().await?.backfill(&self.http_provider, head).await };
+        match backfill.await {
+            Ok(added) => info!(added, "🗂️ Compound V2 borrower backfill finished"),
+            Err(e) => self.handle_task_error("compound v2 backfill", e)?,
+        }
+        Ok(())
+    }
+    
+    // Score every position below the HF threshold, in queue order
+    async fn rank_targets(&self) -> Vec<(LiquidationTarget, f64)> {
+        let positions = self.positions.read().await;
         
+        positions
+            .iter()
+            .filter(|t| t.health_factor < self.thresholds.load().health_factor)
+            .map(|target| {
+                let features = Self::target_features(target);
+                self.scorer.observe(&features.id);
+                let score = self.scorer.score(&features);
+                (target.clone(), score.expected_value)
+            })
+            .collect()
+    }
+    
+    // Re-price queued targets at this block's gas and re-sort them
+    async fn rescore_targets(&self, gas_price: U256) {
+        let dropped = self.positions.write().await.rescore(gas_price, self.thresholds.load().min_profit);
+        if dropped > 0 {
+            debug!(dropped, "📉 Targets unprofitable at current gas");
+        }
+    }
+    
+    fn target_features(target: &LiquidationTarget) -> OpportunityFeatures {
+        OpportunityFeatures {
+            id: format!("{}:{:?}", target.protocol, target.user),
+            market: target.protocol.clone(),
+            // In USD, like the expected-value gate
+            net_profit: target.profit_value,
+            spread_bps: target.bonus_bps,
+            // The debt repaid against the collateral there is to seize for it
+            trade_size: target.debt_value,
+            liquidity_depth: target.collateral_value,
+            gas_price: target.gas_price,
+            // Positions are re-read every scan, so a target is never stale
+            age: Duration::ZERO,
+        }
+    }
+    
+    // Re-read one user's account and update (or drop) their tracked position
+    async fn refresh_position(&self, user: Address) -> Result<()> {
+        let account_data = self.get_aave_account_data(user).await?;
+        match self.evaluate_aave_position(user, account_data).await? {
+            Some(target) => {
+                self.positions.write().await.insert(target);
+            }
+            None => {
+                self.positions.write().await.remove(&user);
+            }
+        }
         Ok(())
     }
     
-    // Scan all positions for liquidation opportunities
-    async fn scan_positions(self) -> Result<()> {
-        let mut interval = interval(Duration::from_secs(5));
+    // Re-evaluate tracked and watched positions after prices move
+    async fn rescan_at_risk(&self, context: &str) -> Result<()> {
+        let mut users: HashSet<Address> = self.positions.read().await.users().collect();
+        users.extend(self.watchlist.read().await.iter().copied());
+        for user in users {
+            if let Err(e) = self.refresh_position(user).await {
+                self.handle_task_error(context, e)?;
+            }
+        }
+        Ok(())
+    }
+    
+    // Positions and executions read from blocks a reorg orphaned. Every
+    // tracked position is re-read at the new head, and each transaction sent
+    // since just before the fork is looked up again: one that left the chain
+    // with the orphaned blocks is back in the mempool at best.
+    async fn handle_reorg(&self, reorg: &Reorg) -> Result<()> {
+        self.metrics.reorgs_total.inc();
+        self.rescan_at_risk("reorg").await?;
         
-        loop {
-            interval.tick().await;
-            
-            // Load positions from multiple protocols
-            self.scan_aave_positions().await?;
-            self.scan_compound_positions().await?;
-            
-            // Check each position for liquidation
-            let positions = self.positions.read().await;
-            for (user, target) in positions.iter() {
-                if target.health_factor < self.config.health_factor_threshold {
-                    self.evaluate_and_execute(target.clone()).await?;
+        let exposed: Vec<(H256, U64)> = self
+            .recent_executions
+            .read()
+            .await
+            .iter()
+            .filter(|(_, sent)| **sent >= reorg.common_ancestor)
+            .map(|(tx, sent)| (*tx, *sent))
+            .collect();
+        for (tx, sent) in exposed {
+            match self.http_provider.get_transaction_receipt(tx).await? {
+                Some(receipt) => info!(
+                    ?tx,
+                    block = ?receipt.block_number,
+                    success = receipt.status == Some(U64::from(1)),
+                    "🔀 Execution still included after reorg"
+                ),
+                None => {
+                    warn!(?tx, %sent, depth = reorg.depth(), "⚠️ Execution orphaned by reorg");
+                    self.alerts
+                        .send_alert(
+                            AlertLevel::Warning,
+                            &format!("Transaction {:?} was dropped by a {}-block reorg", tx, reorg.depth()),
+                        )
+                        .await;
                 }
             }
         }
+        Ok(())
+    }
+    
+    // Gas price from the current block snapshot, falling back to RPC before the first block
+    async fn current_gas_price(&self) -> Result<U256> {
+        let snapshot = self.block_state.latest();
+        if !snapshot.is_empty() {
+            return Ok(snapshot.gas_price);
+        }
+        Ok(self.provider.get_gas_price().await?)
+    }
+    
+    // Rollup L1 data fee for a liquidation transaction (wei); zero elsewhere
+    fn liquidation_l1_fee(&self) -> U256 {
+        self.block_state.latest().l1_fee_per_byte * LIQUIDATION_TX_BYTES
     }
     
-    // Scan Aave positions
+    // Scan every indexed Aave account, after picking up accounts from new events
     async fn scan_aave_positions(&self) -> Result<()> {
-        // Query recent borrow events
-        let filter = Filter::new()
-            .address(self.config.aave_pool)
-            .event("Borrow(address,address,address,uint256,uint256,uint256,uint16)")
-            .from_block(BlockNumber::Latest - 1000);
-        
-        let logs = self.provider.get_logs(&filter).await?;
+        let head = self.http_provider.get_block_number().await?;
+        let added = self.position_index.sync(&self.http_provider, head).await?;
+        if added > 0 {
+            debug!(added, "🗂️ Indexed new Aave accounts");
+        }
         
-        for log in logs {
-            let user = Address::from(log.topics[2]);
-            
-            // Get user account data via multicall
-            let account_data = self.get_aave_account_data(user).await?;
+        let pool = AavePool::new(self.config.chain.aave_pool, self.provider.clone());
+        let users = self.position_index.users().await?;
+        for chunk in users.chunks(ACCOUNT_BATCH) {
+            let mut multicall = Multicall::new(self.provider.clone(), None)
+                .await
+                .map_err(|e| errors::ProviderError::Rpc(e.to_string()))?;
+            for user in chunk {
+                multicall.add_call(pool.get_user_account_data(*user), false);
+            }
+            let accounts: Vec<(U256, U256, U256, U256, U256, U256)> = multicall
+                .call_array()
+                .await
+                .map_err(|e| errors::ProviderError::Rpc(e.to_string()))?;
             
-            if let Some(target) = self.evaluate_aave_position(user, account_data).await? {
-                self.positions.write().await.insert(user, target);
+            let mut closed = Vec::new();
+            for (user, (total_collateral, total_debt, _, liquidation_threshold, _, health_factor)) in chunk.iter().copied().zip(accounts) {
+                if total_debt.is_zero() {
+                    closed.push(user);
+                    self.watchlist.write().await.remove(&user);
+                    continue;
+                }
+                let account_data = AccountData {
+                    total_collateral,
+                    total_debt,
+                    health_factor: math::to_f64(health_factor) / 1e18,
+                    liquidation_threshold,
+                };
+                if account_data.health_factor < WATCH_HEALTH_FACTOR {
+                    self.watchlist.write().await.insert(user);
+                } else {
+                    self.watchlist.write().await.remove(&user);
+                }
+                
+                if let Some(target) = self.evaluate_aave_position(user, account_data).await? {
+                    self.positions.write().await.insert(target);
+                }
+            }
+            // Suppliers and repaid borrowers can't be liquidated
+            self.position_index.remove(&closed).await?;
+        }
+        
+        Ok(())
+    }
+    
+    // The Compound V2 borrower index over every market but cETH
+    async fn compound_v2_index(&self) -> Result<&PositionIndex> {
+        self.compound_v2_index
+            .get_or_try_init(|| async {
+                let markets = self.compound_v2.markets().await?.iter().map(|(c_token, _)| *c_token).collect();
+                Ok::<_, BotError>(PositionIndex::compound_v2(
+                    self.redis.clone(),
+                    self.config.compound_v2_comptroller,
+                    markets,
+                    COMPOUND_V2_DEPLOYED_AT,
+                ))
+            })
+            .await
+    }
+    
+    // Scan every indexed Compound V2 borrower, after picking up new borrows
+    async fn scan_compound_v2_positions(&self) -> Result<()> {
+        let index = self.compound_v2_index().await?;
+        let head = self.http_provider.get_block_number().await?;
+        let added = index.sync(&self.http_provider, head).await?;
+        if added > 0 {
+            debug!(added, "🗂️ Indexed new Compound V2 borrowers");
+        }
+        let borrowers = index.users().await?;
+        
+        for user in borrowers {
+            if let Some(target) = self.evaluate_compound_v2_position(user).await? {
+                self.positions.write().await.insert(target);
             }
         }
         
         Ok(())
     }
     
+    // Repay the best borrow and seize the best cToken, when a shortfall makes it profitable
+    async fn evaluate_compound_v2_position(&self, user: Address) -> Result<Option<LiquidationTarget>> {
+        if self.compound_v2.shortfall(user).await?.is_zero() {
+            return Ok(None);
+        }
+        let Some(plan) = self.compound_v2.plan(user).await? else {
+            return Ok(None);
+        };
+        
+        let gas_price = self.current_gas_price().await?;
+        self.scorer.observe_gas_price(gas_price);
+        let eth_price = self.compound_v2.eth_price().await?;
+        let l1_fee = self.liquidation_l1_fee();
+        let gas_cost = (U256::from(LIQUIDATION_GAS) * gas_price + l1_fee) * eth_price / U256::exp10(18);
+        
+        if plan.gross_value <= gas_cost {
+            return Ok(None);
+        }
+        let profit_value = plan.gross_value - gas_cost;
+        if profit_value < self.thresholds.load().min_profit {
+            return Ok(None);
+        }
+        
+        Ok(Some(LiquidationTarget {
+            protocol: "COMPOUND_V2".to_string(),
+            user,
+            // The executor seizes this cToken and redeems it for the underlying
+            collateral_asset: plan.c_token_collateral,
+            debt_asset: plan.debt_asset,
+            debt_amount: plan.repay_amount,
+            // Compound reports a shortfall, not a health factor
+            health_factor: 0.0,
+            expected_profit: plan.to_debt_units(profit_value),
+            gas_cost: plan.to_debt_units(gas_cost),
+            gas_price,
+            l1_fee,
+            debt_value: plan.repay_value,
+            profit_value,
+            bonus_bps: plan.bonus_bps,
+            collateral_value: plan.collateral_value,
+        }))
+    }
+    
     // Get Aave account data
     async fn get_aave_account_data(&self, user: Address) -> Result<AccountData> {
         // Use multicall for efficiency
-        let pool = AavePool::new(self.config.aave_pool, self.provider.clone());
+        let pool = AavePool::new(self.config.chain.aave_pool, self.provider.clone());
         
         let (
             total_collateral,
@@ -199,7 +1110,8 @@ impl LiquidationBot {
         Ok(AccountData {
             total_collateral,
             total_debt,
-            health_factor: health_factor.as_u128() as f64 / 1e18,
+            // No debt reports uint256 max
+            health_factor: math::to_f64(health_factor) / 1e18,
             liquidation_threshold,
         })
     }
@@ -213,104 +1125,138 @@ impl LiquidationBot {
         if data.health_factor >= 1.0 {
             return Ok(None);
         }
-        
-        // Calculate maximum liquidation amount (50% of debt)
-        let max_liquidation = data.total_debt / 2;
+        self.aave_target(user, &HashMap::new()).await
+    }
+    
+    // Liquidation of an Aave user at current oracle prices, or with some of
+    // them replaced by a pending update
+    async fn aave_target(&self, user: Address, overrides: &HashMap<Address, U256>) -> Result<Option<LiquidationTarget>> {
+        // The reserve pair paying the most bonus for the debt we may repay,
+        // with eMode thresholds and bonuses where the user is in a category
+        let Some((position, plan)) = self.aave.plan(user, overrides).await? else {
+            return Ok(None);
+        };
+        if position.health_factor() >= 1.0 {
+            return Ok(None);
+        }
+        debug!(
+            ?user,
+            emode = position.emode,
+            isolated = position.isolated,
+            close_factor = plan.close_factor,
+            "🔎 Aave position resolved"
+        );
         
         // Get current gas price
-        let gas_price = self.provider.get_gas_price().await?;
+        let gas_price = self.current_gas_price().await?;
+        self.scorer.observe_gas_price(gas_price);
         
-        // Calculate expected profit
-        let liquidation_bonus = U256::from(500); // 5% in basis points
-        let collateral_value = max_liquidation * (10000 + liquidation_bonus) / 10000;
+        // Gas is paid in the native token; price it in the oracle's base currency.
+        // With no fresh ETH price the position waits rather than looking free of gas.
+        let weth = self.config.chain.wrapped_native;
+        let eth_price = match overrides.get(&weth) {
+            Some(price) => *price,
+            None => self.eth_price.price().await? / U256::from(ORACLE_TO_USD),
+        };
+        let l1_fee = self.liquidation_l1_fee();
+        let gas_cost = (U256::from(LIQUIDATION_GAS) * gas_price + l1_fee) * eth_price / U256::exp10(18);
         
-        // Estimate costs
-        let gas_cost = U256::from(300_000) * gas_price; // 300k gas estimate
-        let flash_loan_fee = max_liquidation * 5 / 10000; // 0.05% Aave fee
-        
-        let total_cost = max_liquidation + flash_loan_fee + gas_cost;
-        
-        if collateral_value <= total_cost {
+        if plan.gross_value <= gas_cost {
             return Ok(None);
         }
+        let profit_value = plan.gross_value - gas_cost;
         
-        let expected_profit = collateral_value - total_cost;
-        
-        if expected_profit < self.config.min_profit_usd {
+        if profit_value * U256::from(ORACLE_TO_USD) < self.thresholds.load().min_profit {
             return Ok(None);
         }
         
         Ok(Some(LiquidationTarget {
             protocol: "AAVE_V3".to_string(),
             user,
-            collateral_asset: Address::zero(), // Would need to determine actual asset
-            debt_asset: Address::zero(), // Would need to determine actual asset
-            debt_amount: max_liquidation,
-            health_factor: data.health_factor,
-            expected_profit,
+            collateral_asset: plan.collateral_asset,
+            debt_asset: plan.debt_asset,
+            debt_amount: plan.debt_to_cover,
+            // Same prices as the plan, so ranking and sizing agree
+            health_factor: position.health_factor(),
+            expected_profit: plan.to_debt_units(profit_value),
+            gas_cost: plan.to_debt_units(gas_cost),
             gas_price,
+            l1_fee,
+            debt_value: plan.debt_value() * U256::from(ORACLE_TO_USD),
+            profit_value: profit_value * U256::from(ORACLE_TO_USD),
+            bonus_bps: plan.bonus_bps,
+            collateral_value: plan.collateral_value * U256::from(ORACLE_TO_USD),
         }))
     }
     
-    // Monitor oracle price updates
-    async fn monitor_oracle_updates(self) -> Result<()> {
-        // Monitor Chainlink price feeds
-        let chainlink_feed = Address::from_str("0x5f4eC3Df9cbd43714FE2740f5E3616155c5b8419")?; // ETH/USD
-        
-        let filter = Filter::new()
-            .address(chainlink_feed)
-            .event("AnswerUpdated(int256,uint256,uint256)");
-        
-        let mut stream = self.provider.watch(&filter).await?;
-        
-        while let Some(log) = stream.next().await {
-            println!("📊 Oracle update detected: {:?}", log);
-            
-            // Immediately check positions after oracle update
-            self.scan_positions_after_oracle_update().await?;
-        }
-        
-        Ok(())
-    }
-    
-    // Quick position scan after oracle update
-    async fn scan_positions_after_oracle_update(&self) -> Result<()> {
-        let positions = self.positions.read().await.clone();
-        
-        for (_, target) in positions.iter() {
-            // Re-evaluate with new prices
-            let account_data = self.get_aave_account_data(target.user).await?;
-            
-            if account_data.health_factor < 1.0 {
-                // Execute immediately - oracle update likely made it liquidatable
-                self.execute_liquidation_flashbots(target.clone()).await?;
-            }
-        }
-        
-        Ok(())
-    }
-    
     // Evaluate and execute profitable liquidation
+    #[instrument(name = "execution", skip_all, fields(kind = "liquidation", user = ?target.user))]
     async fn evaluate_and_execute(&self, target: LiquidationTarget) -> Result<()> {
         // Double-check profitability with current gas
-        let current_gas = self.provider.get_gas_price().await?;
+        let current_gas = self.current_gas_price().await?;
         
-        if current_gas > self.config.max_gas_price {
-            println!("⚠️ Gas too high: {} gwei", current_gas.as_u64() / 1e9 as u64);
+        if current_gas > self.thresholds.load().max_gas_price {
+            warn!(gwei = current_gas.as_u64() / 1e9 as u64, "⚠️ Gas too high");
             return Ok(());
         }
         
         // Simulate transaction first
-        if self.simulate_liquidation(&target).await? {
-            // Try multiple execution strategies
-            match self.execute_liquidation_flashbots(target.clone()).await {
-                Ok(tx) => {
-                    println!("✅ Liquidation submitted via Flashbots: {:?}", tx);
-                    self.track_execution(tx).await?;
-                }
-                Err(_) => {
-                    // Fallback to regular execution
-                    self.execute_liquidation_standard(target).await?;
+        if !self.simulate_liquidation(&target).await? {
+            return Ok(());
+        }
+        
+        // Flash loans mean no working capital, only gas has to fit the strategy budget
+        let gas_cost = U256::from(LIQUIDATION_GAS) * current_gas + self.liquidation_l1_fee();
+        let Some(reservation) = self.reserve(LIQUIDATION_STRATEGY, liquidation_exposure(&target, gas_cost)) else {
+            return Ok(());
+        };
+        // Booked in wei like the gas; the receipt is reconciled in the debt asset
+        let expected_profit = I256::from_raw(profit_in_wei(&target));
+        let protocol = target.protocol.clone();
+        let debt_asset = target.debt_asset;
+        let debt_profit = target.expected_profit;
+        let gas_estimate = target.gas_cost;
+        let record = |tx, success, profit| {
+            ExecutionRecord::new(LIQUIDATION_STRATEGY, &protocol, tx, success, profit, gas_cost)
+        };
+        
+        // Try multiple execution strategies
+        match self.execute_liquidation_flashbots(target.clone(), None).await {
+            Ok(tx) => {
+                info!(bundle = ?tx, "✅ Liquidation submitted via Flashbots");
+                self.record_execution(record(Some(tx), true, expected_profit)).await;
+                // Failed bundles are not mined, so only a landed bundle costs gas
+                self.settle(reservation, gas_cost, expected_profit);
+                self.track_execution(tx).await?;
+            }
+            Err(e) if e.is_fatal() => {
+                self.settle(reservation, U256::zero(), I256::zero());
+                return Err(e);
+            }
+            // What fails bundle simulation would fail in public too
+            Err(BotError::Simulation(e)) => {
+                warn!(user = ?target.user, error = %e, "⚠️ Liquidation dropped after bundle simulation");
+                self.settle(reservation, U256::zero(), I256::zero());
+            }
+            Err(e) => {
+                warn!(error = %e, "⚠️ Flashbots submission failed, falling back to public mempool");
+                // Fallback to regular execution; a public revert still burns gas
+                match self.execute_liquidation_standard(target).await {
+                    Ok(tx) => {
+                        // Flashbots returns a bundle hash, so only public submissions have a receipt to check
+                        let estimated = I256::from_raw(debt_profit + gas_estimate);
+                        let reconciliation = self
+                            .reconcile(LIQUIDATION_STRATEGY, tx, debt_asset, self.executor.address(), estimated)
+                            .await;
+                        let executed = record(Some(tx), true, expected_profit);
+                        self.record_execution(executed.with_reconciliation(reconciliation)).await;
+                        self.settle(reservation, gas_cost, expected_profit);
+                    }
+                    Err(e) => {
+                        self.record_execution(record(None, false, -I256::from_raw(gas_cost))).await;
+                        self.settle(reservation, gas_cost, -I256::from_raw(gas_cost));
+                        return Err(e);
+                    }
                 }
             }
         }
@@ -319,8 +1265,8 @@ impl LiquidationBot {
     }
     
     // Simulate liquidation to verify profitability
+    #[instrument(name = "simulation", skip_all)]
     async fn simulate_liquidation(&self, target: &LiquidationTarget) -> Result<bool> {
-        // Use Tenderly or local fork for simulation
         let call = self.executor.calculate_expected_profit(
             target.protocol.clone(),
             target.collateral_asset,
@@ -331,89 +1277,801 @@ impl LiquidationBot {
         
         match call.call().await {
             Ok((profit, is_profitable)) => {
-                println!("📈 Expected profit: {} USD", profit.as_u128() / 1e18 as u128);
-                Ok(is_profitable)
+                info!(usd = %ethers::utils::format_ether(profit), "📈 Expected profit");
+                if !is_profitable {
+                    return Ok(false);
+                }
             }
             Err(e) => {
-                println!("❌ Simulation failed: {:?}", e);
-                Ok(false)
+                warn!(error = ?e, "❌ Simulation failed");
+                return Ok(false);
             }
         }
-    }
-    
-    // Execute via Flashbots
-    async fn execute_liquidation_flashbots(&self, target: LiquidationTarget) -> Result<H256> {
-        let flashbots_client = FlashbotsClient::new(
-            self.provider.clone(),
-            &self.config.flashbots_relay,
-        )?;
         
-        // Build liquidation transaction
+        let Some(simulator) = &self.simulator else {
+            return Ok(true);
+        };
+        // Run the actual liquidation; profit is kept by the executor in the debt asset
         let tx = self.executor.liquidate(
-            target.protocol,
+            target.protocol.clone(),
             target.user,
             target.collateral_asset,
             target.debt_asset,
             target.debt_amount,
-            true, // use flash loan
+            true,
         );
+        let request = SimulationRequest {
+            from: self.wallet.address(),
+            to: self.executor.address(),
+            data: tx.calldata().unwrap_or_default(),
+            value: U256::zero(),
+            profit: Some((target.debt_asset, self.executor.address())),
+            funding: Vec::new(),
+        };
+        match simulator.simulate(&request).await {
+            // Without a measured profit the contract's own profit checks passed
+            Ok(outcome) => match outcome.profit {
+                Some(profit) => {
+                    info!(simulator = simulator.name(), profit = %profit, gas = %outcome.gas_used, "🧪 Simulation passed");
+                    Ok(profit > I256::zero())
+                }
+                None => Ok(true),
+            },
+            Err(e) if e.is_retryable() => Err(e),
+            Err(e) => {
+                warn!(simulator = simulator.name(), error = %e, "❌ Simulation failed");
+                Ok(false)
+            }
+        }
+    }
+    
+    // Execute via every builder worth sending to, and bloXroute where the
+    // opportunity's route says so, all at once, behind `oracle_tx` when the
+    // liquidation depends on it
+    #[instrument(name = "submission", skip_all, fields(route = "flashbots"))]
+    async fn execute_liquidation_flashbots(&self, target: LiquidationTarget, oracle_tx: Option<Bytes>) -> Result<H256> {
+        let kind = if oracle_tx.is_some() { "oracle_backrun" } else { LIQUIDATION_STRATEGY };
+        // Build liquidation transaction, paying the builder out of the profit
+        let (tip, tip_bps) = self.builder_tip(&target).await;
+        let call = self.liquidation_call(&target, tip);
+        // The builder is paid through the tip, so the gas bid only needs to be ordinary
+        let mut tx = self.type2(&call.tx, Confidence::Normal)?;
+        tx.set_from(self.wallet.address());
+        self.add_access_list(&mut tx).await;
+        self.provider
+            .fill_transaction(&mut tx, None)
+            .await
+            .map_err(|e| ExecutionError::Signer(e.to_string()))?;
+        let signature = self.wallet.sign_transaction(&tx).await.map_err(BotError::from)?;
+        let tx_hash = tx.hash(&signature);
+        let target_block = self.provider.get_block_number().await? + 1;
         
-        // Create bundle with high priority
-        let bundle = BundleRequest::new()
-            .push_transaction(tx.tx)
-            .set_block(self.provider.get_block_number().await? + 1)
-            .set_min_timestamp(0)
-            .set_max_timestamp(u64::MAX);
+        let mut txs: Vec<Bytes> = oracle_tx.into_iter().collect();
+        txs.push(tx.rlp_signed(&signature));
         
-        // Send bundle
-        let result = flashbots_client.send_bundle(bundle).await?;
+        // Simulate the exact bundle first, so a revert or a bid builders ignore never goes out
+        let simulation = self.builders.simulate(&txs, target_block).await?;
+        if let Some(reverted) = simulation.first_revert() {
+            let reason = reverted.revert.clone().unwrap_or_default();
+            warn!(tx = ?reverted.hash, %reason, "❌ Bundle simulation reverted");
+            return Err(SimulationError::Reverted(reason).into());
+        }
+        if simulation.gas_price < self.config.min_bundle_gas_price {
+            warn!(gas_price = %simulation.gas_price, min = %self.config.min_bundle_gas_price, "❌ Bundle gas price too low");
+            return Err(SimulationError::Unprofitable.into());
+        }
+        let gas_fees = simulation.transactions.last().map(|t| t.gas_fees).unwrap_or_default();
+        let profit = simulated_profit(&target, gas_fees + tip);
+        if profit < self.thresholds.load().min_profit {
+            warn!(profit = math::to_f64(profit) / 1e18, gas_fees = %gas_fees, "❌ Bundle unprofitable after simulation");
+            return Err(SimulationError::Unprofitable.into());
+        }
         
-        Ok(result.bundle_hash)
+        let bundle_hash = self.submit_bundle(kind, &txs, tx_hash, target_block).await?;
+        if let Some(bps) = tip_bps {
+            self.tips.record_submission(tx_hash, bps);
+        }
+        info!(tip = %tip, bps = ?tip_bps, "💸 Builder tip");
+        Ok(bundle_hash)
     }
     
-    // Standard execution fallback
-    async fn execute_liquidation_standard(&self, target: LiquidationTarget) -> Result<H256> {
-        let tx = self.executor.liquidate(
-            target.protocol,
-            target.user,
-            target.collateral_asset,
-            target.debt_asset,
-            target.debt_amount,
-            true,
-        )
-        .gas_price(target.gas_price * 110 / 100) // 10% above base
-        .gas(500_000); // Conservative gas limit
-        
-        let pending_tx = tx.send().await?;
-        let receipt = pending_tx.await?;
+    // `tx` alone as a bundle for the next block, simulated first so one that
+    // reverts is never sent. For executions too exposed to go out publicly.
+    #[instrument(name = "submission", skip_all, fields(route = "private"))]
+    async fn send_private(&self, kind: &str, tx: &TypedTransaction) -> Result<H256> {
+        let mut tx = tx.clone();
+        tx.set_from(self.wallet.address());
+        self.provider
+            .fill_transaction(&mut tx, None)
+            .await
+            .map_err(|e| ExecutionError::Signer(e.to_string()))?;
+        let signature = self.wallet.sign_transaction(&tx).await.map_err(BotError::from)?;
+        let tx_hash = tx.hash(&signature);
+        let target_block = self.provider.get_block_number().await? + 1;
+        let txs = vec![tx.rlp_signed(&signature)];
         
-        match receipt {
-            Some(r) if r.status == Some(U64::from(1)) => {
-                println!("✅ Liquidation successful: {:?}", r.transaction_hash);
-                Ok(r.transaction_hash)
+        let simulation = self.builders.simulate(&txs, target_block).await?;
+        if let Some(reverted) = simulation.first_revert() {
+            let reason = reverted.revert.clone().unwrap_or_default();
+            warn!(tx = ?reverted.hash, %reason, "❌ Bundle simulation reverted");
+            return Err(SimulationError::Reverted(reason).into());
+        }
+        self.submit_bundle(kind, &txs, tx_hash, target_block).await
+    }
+    
+    // Send a simulated bundle wherever the route for `kind` says: the builders
+    // worth sending to, bloXroute, or both at once
+    async fn submit_bundle(&self, kind: &str, txs: &[Bytes], tx_hash: H256, target_block: U64) -> Result<H256> {
+        let route = self.config.bloxroute.route(kind);
+        let mut bundle_hash = None;
+        let mut last_error = None;
+        let relays = if route.builders() { self.relay_stats.preferred_relays() } else { Vec::new() };
+        let bloxroute = async {
+            match self.bloxroute.as_ref().filter(|_| route.bloxroute()) {
+                Some(bloxroute) => Some(bloxroute.send_bundle(txs, target_block).await),
+                None => None,
+            }
+        };
+        let (sent, bloxroute_sent) = tokio::join!(self.builders.send_bundle(&relays, txs, target_block), bloxroute);
+        match bloxroute_sent {
+            Some(Ok(hash)) => {
+                bundle_hash = Some(hash);
+            }
+            Some(Err(e)) => {
+                warn!(error = %e, "⚠️ bloXroute rejected bundle");
+                last_error = Some(e.to_string());
             }
-            _ => {
-                println!("❌ Liquidation failed");
-                Err(anyhow::anyhow!("Transaction failed"))
+            None => {}
+        }
+        for (relay, result) in sent {
+            match result {
+                Ok(hash) => {
+                    self.relay_stats.record_submission(&relay, hash, tx_hash, target_block);
+                    bundle_hash.get_or_insert(hash);
+                }
+                Err(e) => {
+                    warn!(%relay, error = %e, "⚠️ Builder rejected bundle");
+                    last_error = Some(e.to_string());
+                }
             }
         }
+        
+        bundle_hash.ok_or_else(|| {
+            ExecutionError::BundleRejected(last_error.unwrap_or_else(|| "no builders configured".to_string())).into()
+        })
     }
     
-    // Track execution results
-    async fn track_execution(&self, tx_hash: H256) -> Result<()> {
-        // Store in Redis for analysis
-        let mut conn = self.redis.get_async_connection().await?;
-        
-        let key = format!("liquidation:{}", tx_hash);
-        let _: () = conn.set_ex(key, tx_hash.to_string(), 86400).await?;
-        
-        // Increment counters
-        let _: () = conn.incr("stats:total_liquidations", 1).await?;
-        
-        Ok(())
+    // `tx` as a type-2 transaction priced from the fee estimate at `confidence`,
+    // within MAX_PRIORITY_FEE_GWEI and MAX_GAS_PRICE_GWEI
+    fn type2(&self, tx: &TypedTransaction, confidence: Confidence) -> Result<TypedTransaction> {
+        let fees = self.block_state.latest().fees;
+        let max_gas_price = self.thresholds.load().max_gas_price;
+        if fees.base_fee >= max_gas_price {
+            return Err(ExecutionError::GasTooHigh(fees.base_fee).into());
+        }
+        let priority_fee = fees.priority_fee(confidence).min(self.config.max_priority_fee);
+        let max_fee = (fees.base_fee * 2 + priority_fee).min(max_gas_price);
+        Ok(eip1559(tx, max_fee, priority_fee))
     }
     
-    // Health monitoring
+    // Without a list the transaction still goes out, just paying cold access
+    async fn add_access_list(&self, tx: &mut TypedTransaction) {
+        match attach_access_list(self.provider.as_ref(), tx).await {
+            Ok(gas_used) => debug!(gas = %gas_used, "📋 Access list attached"),
+            Err(e) => warn!(error = %e, "⚠️ Access list unavailable"),
+        }
+    }
+    
+    // The builder tip for `target` and the level it was taken at. The executor
+    // pays it from its ETH, unwrapping WETH for the rest, so a tip it can't
+    // cover would revert the liquidation; it goes out untipped instead.
+    async fn builder_tip(&self, target: &LiquidationTarget) -> (U256, Option<u64>) {
+        let (tip, bps) = self.tips.tip(profit_in_wei(target));
+        if tip.is_zero() {
+            return (tip, Some(bps));
+        }
+        let executor = self.executor.address();
+        let balances = async {
+            let eth = self.provider.get_balance(executor, None).await?;
+            let weth = Erc20Balance::new(self.config.chain.wrapped_native, self.provider.clone())
+                .balance_of(executor)
+                .call()
+                .await?;
+            Ok::<_, BotError>((eth, weth))
+        };
+        match balances.await {
+            Ok((eth, weth)) if eth.saturating_add(weth) >= tip => (tip, Some(bps)),
+            Ok((eth, weth)) => {
+                warn!(tip = %tip, eth = %eth, weth = %weth, "⚠️ Executor can't fund the builder tip, sending untipped");
+                (U256::zero(), None)
+            }
+            Err(e) => {
+                warn!(error = %e, "⚠️ Executor balance unavailable, sending untipped");
+                (U256::zero(), None)
+            }
+        }
+    }
+    
+    // Flash-loan liquidation that pays `tip` wei to block.coinbase once it succeeds
+    fn liquidation_call(&self, target: &LiquidationTarget, tip: U256) -> ContractCall<Provider<Ws>, ()> {
+        self.executor.liquidate_with_tip(
+            target.protocol.clone(),
+            target.user,
+            target.collateral_asset,
+            target.debt_asset,
+            target.debt_amount,
+            true, // use flash loan
+            tip,
+        )
+    }
+    
+    // Standard execution fallback
+    #[instrument(name = "submission", skip_all, fields(route = "mempool"))]
+    async fn execute_liquidation_standard(&self, target: LiquidationTarget) -> Result<H256> {
+        // Outbid through the builder tip rather than the gas price
+        let (tip, _) = self.builder_tip(&target).await;
+        // Public transactions race on gas too, so bid against the top of recent blocks
+        let mut tx = self.liquidation_call(&target, tip).gas(500_000); // Conservative gas limit
+        tx.tx = self.type2(&tx.tx, Confidence::Competitive)?;
+        tx.tx.set_from(self.wallet.address());
+        self.add_access_list(&mut tx.tx).await;
+        
+        let pending_tx = self.nonces.send(tx.tx).await?;
+        let receipt = pending_tx.await.map_err(BotError::from)?;
+        
+        match receipt {
+            Some(r) if r.status == Some(U64::from(1)) => {
+                info!(tx = ?r.transaction_hash, "✅ Liquidation successful");
+                Ok(r.transaction_hash)
+            }
+            Some(r) => {
+                error!(tx = ?r.transaction_hash, "❌ Liquidation failed");
+                Err(ExecutionError::reverted_on_chain(&r).into())
+            }
+            None => {
+                error!("❌ Liquidation dropped from mempool");
+                Err(ExecutionError::NotIncluded.into())
+            }
+        }
+    }
+    
+    // Liquidate right behind a pending oracle update. The position is still
+    // healthy on chain, so this can't be simulated or sent publicly; a bundle
+    // that reverts is simply not included.
+    #[instrument(name = "execution", skip_all, fields(kind = "oracle_backrun", user = ?backrun.target.user, oracle_tx = ?backrun.oracle_tx))]
+    async fn backrun_oracle_update(&self, backrun: OracleBackrun) -> Result<()> {
+        let current_gas = self.current_gas_price().await?;
+        if current_gas > self.thresholds.load().max_gas_price {
+            warn!(gwei = current_gas.as_u64() / 1e9 as u64, "⚠️ Gas too high");
+            return Ok(());
+        }
+        
+        let gas_cost = U256::from(LIQUIDATION_GAS) * current_gas + self.liquidation_l1_fee();
+        let Some(reservation) = self.reserve(LIQUIDATION_STRATEGY, liquidation_exposure(&backrun.target, gas_cost)) else {
+            return Ok(());
+        };
+        let expected_profit = I256::from_raw(profit_in_wei(&backrun.target));
+        let protocol = backrun.target.protocol.clone();
+        
+        match self.execute_liquidation_flashbots(backrun.target, Some(backrun.raw_oracle_tx)).await {
+            Ok(tx) => {
+                info!(bundle = ?tx, "✅ Oracle backrun submitted via Flashbots");
+                self.record_execution(ExecutionRecord::new(
+                    LIQUIDATION_STRATEGY,
+                    &protocol,
+                    Some(tx),
+                    true,
+                    expected_profit,
+                    gas_cost,
+                ))
+                .await;
+                self.settle(reservation, gas_cost, expected_profit);
+                self.track_execution(tx).await
+            }
+            Err(e) => {
+                self.settle(reservation, U256::zero(), I256::zero());
+                Err(e)
+            }
+        }
+    }
+    
+    // Buy a Maker auction lot through the executor, which sells the collateral
+    // for the DAI owed inside the Clipper callback
+    #[instrument(name = "execution", skip_all, fields(kind = "maker", clipper = ?take.clipper, id = %take.id))]
+    async fn take_auction(&self, take: AuctionTake) -> Result<()> {
+        let gas_price = self.current_gas_price().await?;
+        if gas_price > self.thresholds.load().max_gas_price {
+            return Ok(());
+        }
+        let gas_cost = U256::from(MAKER_TAKE_GAS) * gas_price;
+        // Paid for in DAI out of the sale, so the lot's DAI cost is the notional
+        let exposure = Exposure {
+            token: take.gem,
+            notional: math::mul_div(take.amount, take.max_price, U256::exp10(27)),
+            flash_loan: U256::zero(),
+            gas: gas_cost,
+        };
+        let Some(reservation) = self.reserve(MAKER_STRATEGY, exposure) else {
+            return Ok(());
+        };
+        let expected_profit = I256::from_raw(take.expected_profit);
+        let record = |tx, success, profit| ExecutionRecord::new(MAKER_STRATEGY, "MAKER", tx, success, profit, gas_cost);
+        
+        let call = self
+            .executor
+            .take_auction(take.clipper, take.id, take.amount, take.max_price, take.gem_join, take.pool_fee)
+            .gas_price(gas_price)
+            .gas(MAKER_TAKE_GAS);
+        let exposed = self.take_exposure(&take).await;
+        let result = async {
+            if let Some(loss) = exposed {
+                info!(loss = %loss, "🥪 Take too exposed to sandwiching, sending privately");
+                return self.send_private(MAKER_STRATEGY, &call.tx).await;
+            }
+            let receipt = self.nonces.send(call.tx.clone()).await?.await.map_err(BotError::from)?;
+            match receipt {
+                Some(r) if r.status == Some(U64::from(1)) => Ok(r.transaction_hash),
+                Some(r) => Err(ExecutionError::reverted_on_chain(&r).into()),
+                None => Err(ExecutionError::NotIncluded.into()),
+            }
+        }
+        .await;
+        
+        match result {
+            Ok(tx) => {
+                info!(?tx, "✅ Maker auction taken");
+                self.record_execution(record(Some(tx), true, expected_profit)).await;
+                self.settle(reservation, gas_cost, expected_profit);
+                self.track_execution(tx).await?;
+                Ok(())
+            }
+            Err(e) => {
+                // Someone else taking the lot first is routine, but a mined revert still costs gas
+                let spent = match &e {
+                    BotError::Execution(ExecutionError::RevertedOnChain { gas_cost, .. }) => *gas_cost,
+                    _ => U256::zero(),
+                };
+                self.settle(reservation, spent, -I256::from_raw(spent));
+                if !spent.is_zero() {
+                    self.record_execution(record(None, false, -I256::from_raw(spent))).await;
+                }
+                Err(e)
+            }
+        }
+    }
+    
+    // DAI a sandwich could take from a public take, when more than the guard
+    // allows. The executor sells the whole lot into the gem/DAI pool with the
+    // DAI owed as its floor, so all of the profit above it is exposed.
+    async fn take_exposure(&self, take: &AuctionTake) -> Option<U256> {
+        let dai: Address = DAI.parse().unwrap();
+        let handler = self.dex.handler_for(&DexType::UniswapV3).ok()?;
+        let pools = match handler.discover_pools(&[take.gem, dai]).await {
+            Ok(pools) => pools,
+            Err(e) => {
+                warn!(error = %e, "⚠️ Can't load the take's pool, sending publicly");
+                return None;
+            }
+        };
+        let pool = pools
+            .into_iter()
+            .find(|p| matches!(&p.state, PoolState::UniswapV3(state) if state.fee_pips == take.pool_fee))?;
+        let decimals = pool.other(dai)?.decimals;
+        let amount_in = take.amount / U256::exp10(18usize.saturating_sub(decimals as usize));
+        
+        let proceeds = self.dex.quote_out(&pool, take.gem, amount_in).await.ok()?;
+        let floor = proceeds.saturating_sub(take.expected_profit);
+        let risk = sandwich::sandwich(&pool, take.gem, amount_in, floor)?;
+        self.config.sandwich.too_risky(risk.loss, take.expected_profit).then_some(risk.loss)
+    }
+    
+    // Liquity pays whoever calls, so troves are liquidated straight from the wallet
+    #[instrument(name = "execution", skip_all, fields(kind = "liquity", troves = batch.troves.len()))]
+    async fn liquidate_troves(&self, batch: TroveBatch) -> Result<()> {
+        let gas_price = self.current_gas_price().await?;
+        if gas_price > self.thresholds.load().max_gas_price {
+            return Ok(());
+        }
+        let gas = liquity::BATCH_BASE_GAS + liquity::GAS_PER_TROVE * batch.troves.len() as u64;
+        let gas_cost = U256::from(gas) * gas_price;
+        // The stability pool absorbs the debt, so only gas is at stake
+        let exposure = Exposure {
+            token: batch.trove_manager,
+            notional: None,
+            flash_loan: U256::zero(),
+            gas: gas_cost,
+        };
+        let Some(reservation) = self.reserve(LIQUITY_STRATEGY, exposure) else {
+            return Ok(());
+        };
+        let expected_profit = I256::from_raw(batch.expected_profit);
+        let record = |tx, success, profit| ExecutionRecord::new(LIQUITY_STRATEGY, "LIQUITY", tx, success, profit, gas_cost);
+        
+        let manager = LiquityTroveManager::new(batch.trove_manager, self.provider.clone());
+        let call = manager.batch_liquidate_troves(batch.troves).gas_price(gas_price).gas(gas);
+        let result = async {
+            let receipt = self.nonces.send(call.tx.clone()).await?.await.map_err(BotError::from)?;
+            match receipt {
+                Some(r) if r.status == Some(U64::from(1)) => Ok(r.transaction_hash),
+                Some(r) => Err(ExecutionError::reverted_on_chain(&r).into()),
+                None => Err(ExecutionError::NotIncluded.into()),
+            }
+        }
+        .await;
+        
+        match result {
+            Ok(tx) => {
+                info!(?tx, "✅ Liquity troves liquidated");
+                self.record_execution(record(Some(tx), true, expected_profit)).await;
+                self.settle(reservation, gas_cost, expected_profit);
+                self.track_execution(tx).await?;
+                Ok(())
+            }
+            Err(e) => {
+                // Another liquidator getting there first reverts the batch
+                let spent = match &e {
+                    BotError::Execution(ExecutionError::RevertedOnChain { gas_cost, .. }) => *gas_cost,
+                    _ => U256::zero(),
+                };
+                self.settle(reservation, spent, -I256::from_raw(spent));
+                if !spent.is_zero() {
+                    self.record_execution(record(None, false, -I256::from_raw(spent))).await;
+                }
+                Err(e)
+            }
+        }
+    }
+    
+    // Flash-loaned two-pool arbitrage through the executor contract
+    #[instrument(
+        name = "execution",
+        skip_all,
+        fields(kind = "arbitrage", buy = ?opportunity.buy_pool.address, sell = ?opportunity.sell_pool.address)
+    )]
+    async fn execute_arbitrage(&self, opportunity: ArbitrageOpportunity) -> Result<()> {
+        // Spreads wide enough to outlast a batch auction go out as CoW orders
+        // when the wallet can pay for both legs; the rest are flash-loaned
+        if let Some(cow) = &self.cow {
+            let now = chrono::Utc::now().timestamp() as u64;
+            if let Some(orders) = cow.orders_for(&opportunity, now) {
+                if cow.funded(self.provider.clone(), &orders).await? {
+                    if let Err(e) = self.validate_cow_orders(&opportunity, &orders).await {
+                        debug!(buy = ?opportunity.buy_pool.address, error = %e, "⏭️ CoW orders failed validation");
+                        return Ok(());
+                    }
+                    return self.place_cow_orders(cow, &opportunity, orders).await;
+                }
+                debug!(token_in = ?opportunity.token_in, token_out = ?opportunity.token_out, "⏭️ Wallet can't fund CoW orders");
+            }
+        }
+        
+        let Some(arbitrage) = &self.arbitrage else {
+            return Ok(());
+        };
+        let gas_price = self.current_gas_price().await?;
+        if gas_price > self.thresholds.load().max_gas_price {
+            return Ok(());
+        }
+        
+        let gas_cost = opportunity.gas_cost;
+        let Some(opportunity) = self.select_flash_loan(opportunity).await? else {
+            info!("⏸️ No flash loan provider can lend the input right now");
+            return Ok(());
+        };
+        let (_, borrowed, flash_loan) = opportunity.flash_borrow();
+        let exposure = Exposure {
+            token: borrowed,
+            notional: self.usd_value(opportunity.token_in, opportunity.amount_in).await?,
+            flash_loan,
+            gas: gas_cost,
+        };
+        let Some(reservation) = self.reserve(ARBITRAGE_STRATEGY, exposure) else {
+            return Ok(());
+        };
+        
+        let route = format!("{} -> {}", opportunity.buy_pool.dex, opportunity.sell_pool.dex);
+        
+        // The contract enforces that gas is covered before the trade keeps anything
+        match arbitrage.execute(&opportunity, gas_cost, gas_price).await {
+            Ok(Some(tx)) => {
+                info!(?tx, "✅ Arbitrage executed");
+                let profit = I256::from_raw(opportunity.gross_profit) - I256::from_raw(gas_cost);
+                self.executions.publish(&ExecutionReport::new(&opportunity, Some(tx), profit, None));
+                let reconciliation = self
+                    .reconcile(
+                        ARBITRAGE_STRATEGY,
+                        tx,
+                        opportunity.token_in,
+                        arbitrage.address(),
+                        I256::from_raw(opportunity.gross_profit),
+                    )
+                    .await;
+                let executed = ExecutionRecord::new(ARBITRAGE_STRATEGY, &route, Some(tx), true, profit, gas_cost);
+                self.record_execution(executed.with_reconciliation(reconciliation)).await;
+                self.settle(reservation, gas_cost, profit);
+                self.track_execution(tx).await?;
+            }
+            Ok(None) => self.settle(reservation, U256::zero(), I256::zero()),
+            Err(e) => {
+                // A mined revert costs the gas its receipt shows; reverts caught in
+                // simulation, and trades held back from the public mempool, cost nothing
+                let spent = match &e {
+                    BotError::Execution(ExecutionError::RevertedOnChain { gas_cost, .. }) => *gas_cost,
+                    BotError::Execution(ExecutionError::Reverted(_) | ExecutionError::SandwichExposed(_)) => U256::zero(),
+                    _ if arbitrage.is_live() => gas_cost,
+                    _ => U256::zero(),
+                };
+                self.settle(reservation, spent, -I256::from_raw(spent));
+                // Only submissions that failed count; a simulation rejecting a stale spread is routine
+                if !spent.is_zero() {
+                    let loss = -I256::from_raw(spent);
+                    self.record_execution(ExecutionRecord::new(ARBITRAGE_STRATEGY, &route, None, false, loss, spent))
+                        .await;
+                }
+                self.executions.publish(&ExecutionReport::new(&opportunity, None, -I256::from_raw(spent), Some(e.to_string())));
+                return Err(e);
+            }
+        }
+        
+        Ok(())
+    }
+    
+    // Both legs of an opportunity as CoW orders; nothing is signed unless
+    // execution is on. Their profit is only known once a batch fills them.
+    async fn place_cow_orders(&self, cow: &CowClient, opportunity: &ArbitrageOpportunity, orders: [CowOrder; 2]) -> Result<()> {
+        if !self.config.execute {
+            info!(buy = ?opportunity.buy_pool.address, sell = ?opportunity.sell_pool.address, "🐮 Would place CoW orders");
+            return Ok(());
+        }
+        let exposure = Exposure {
+            token: opportunity.token_in,
+            notional: self.usd_value(opportunity.token_in, opportunity.amount_in).await?,
+            flash_loan: U256::zero(),
+            gas: U256::zero(),
+        };
+        let Some(reservation) = self.reserve(ARBITRAGE_STRATEGY, exposure) else {
+            return Ok(());
+        };
+        
+        let mut uids = Vec::with_capacity(orders.len());
+        for order in &orders {
+            match cow.place(order).await {
+                Ok(uid) => uids.push(uid),
+                Err(e) => {
+                    self.settle(reservation, U256::zero(), I256::zero());
+                    warn!(placed = ?uids, error = %e, "⚠️ CoW order rejected");
+                    // One leg alone is a directional bet, not an arbitrage
+                    if !uids.is_empty() {
+                        if let Err(cancel) = cow.cancel(&uids).await {
+                            error!(uids = ?uids, error = %cancel, "🚨 Cancelling lone CoW order failed");
+                            let message = format!("CoW order {:?} left open without its other leg: {}", uids, cancel);
+                            self.alerts.send_alert(AlertLevel::Critical, &message).await;
+                        }
+                    }
+                    return Err(e);
+                }
+            }
+        }
+        self.settle(reservation, U256::zero(), I256::zero());
+        info!(
+            buy_dex = %opportunity.buy_pool.dex,
+            sell_dex = %opportunity.sell_pool.dex,
+            amount_in = %opportunity.amount_in,
+            uids = ?uids,
+            "🐮 CoW orders placed"
+        );
+        Ok(())
+    }
+    
+    // Checks the opportunity still holds before its CoW orders are placed. The
+    // executor simulation runs whenever an executor is set up; the pools are
+    // always re-quoted on fresh state against the orders' limits, since
+    // nothing else stands between a stale quote and a signed order.
+    async fn validate_cow_orders(&self, opportunity: &ArbitrageOpportunity, orders: &[CowOrder; 2]) -> Result<()> {
+        if let Some(arbitrage) = &self.arbitrage {
+            arbitrage.validate(opportunity, opportunity.gas_cost).await?;
+        }
+        let mut pools = [opportunity.buy_pool.clone(), opportunity.sell_pool.clone()];
+        self.dex.refresh_pools(&mut pools, self.block_state.latest().number).await?;
+        let intermediate = self.dex.quote_out(&pools[0], opportunity.token_in, opportunity.amount_in).await?;
+        let out = self.dex.quote_out(&pools[1], opportunity.token_out, opportunity.intermediate_amount).await?;
+        if intermediate < orders[0].buy_amount || out < orders[1].buy_amount {
+            return Err(SimulationError::Unprofitable.into());
+        }
+        Ok(())
+    }
+    
+    // Compares what the transaction left in `account` against the pre-trade
+    // estimate, both before gas, and tracks the drift per strategy
+    async fn reconcile(
+        &self,
+        strategy: &'static str,
+        tx: H256,
+        token: Address,
+        account: Address,
+        estimated: I256,
+    ) -> Option<Reconciliation> {
+        let receipt = match self.provider.get_transaction_receipt(tx).await {
+            Ok(Some(receipt)) => receipt,
+            Ok(None) => return None,
+            Err(e) => {
+                warn!(?tx, error = %e, "⚠️ Reading receipt for reconciliation failed");
+                return None;
+            }
+        };
+        let reconciliation = Reconciliation {
+            estimated,
+            realized: reconcile::net_received(&receipt, token, account),
+        };
+        let drift = reconciliation.drift_bps();
+        info!(?tx, strategy, %estimated, realized = %reconciliation.realized, drift_bps = drift, "🧮 Profit reconciled");
+
+        if let Some(tracker) = self.drift.get(strategy) {
+            let (mean, alarm) = tracker.record(drift);
+            self.metrics.profit_drift_bps.with_label_values(&[strategy]).set(mean);
+            if alarm {
+                let message = format!("{} profit is trailing estimates by {:.0} bps on average", strategy, -mean);
+                self.alerts.send_alert(AlertLevel::Warning, &message).await;
+            }
+        }
+        Some(reconciliation)
+    }
+    
+    // Risk limits first, then the strategy's gas budget; None when either refuses
+    fn reserve(&self, strategy: &str, exposure: Exposure) -> Option<(Reservation, RiskPermit)> {
+        let permit = match self.risk.check(self.block_state.latest().number, &exposure) {
+            Ok(permit) => permit,
+            Err(violation) => {
+                info!(strategy, %violation, "🚧 Execution over risk limits, skipping");
+                return None;
+            }
+        };
+        match self.allocator.try_reserve(strategy, exposure.gas, U256::zero()) {
+            Some(reservation) => Some((reservation, permit)),
+            None => {
+                self.risk.settle(permit, U256::zero());
+                info!(strategy, "⏸️ Gas budget exhausted for this period");
+                None
+            }
+        }
+    }
+    
+    fn settle(&self, (reservation, permit): (Reservation, RiskPermit), gas_spent: U256, profit: I256) {
+        self.allocator.settle(reservation, gas_spent, profit);
+        self.risk.settle(permit, gas_spent);
+    }
+    
+    // The first provider, in FLASH_LOAN_PROVIDER order, that can fund the buy
+    // pool and holds enough to lend right now; Aave's reserve or a pool may be
+    // short of a long-tail token
+    async fn select_flash_loan(&self, opportunity: ArbitrageOpportunity) -> Result<Option<ArbitrageOpportunity>> {
+        for &provider in &self.config.chain.flash_loan_providers {
+            if !provider.supports(&opportunity.buy_pool) {
+                continue;
+            }
+            let Some(candidate) = opportunity.clone().with_flash_loan(provider) else {
+                continue;
+            };
+            let (lender, token, amount) = candidate.flash_borrow();
+            let available = match lender {
+                Some(pool) => Erc20Balance::new(token, self.provider.clone()).balance_of(pool).call().await?,
+                None => self.aave.flash_liquidity(token).await?,
+            };
+            if available < amount {
+                debug!(%provider, ?token, %amount, %available, "Flash loan provider short of liquidity, trying the next");
+                continue;
+            }
+            if candidate.gross_profit.is_zero() {
+                continue;
+            }
+            return Ok(Some(candidate));
+        }
+        Ok(None)
+    }
+    
+    // USD value (18 decimals) of an amount of a token, by the Aave oracle; None
+    // for tokens Aave doesn't list
+    async fn usd_value(&self, token: Address, amount: U256) -> Result<Option<U256>> {
+        let reserves = self.aave.reserves().await?;
+        let Some(reserve) = reserves.list.iter().find(|r| r.asset == token) else {
+            return Ok(None);
+        };
+        let price = self.aave.prices(vec![token]).await?.first().copied().unwrap_or_default();
+        Ok(math::mul_div(amount, price * U256::from(ORACLE_TO_USD), U256::exp10(reserve.decimals as usize)))
+    }
+    
+    // Every execution outcome feeds paging and the daily report
+    async fn record_execution(&self, record: ExecutionRecord) {
+        self.alerts.record_execution(record.success).await;
+        self.breaker.record(&record).await;
+        if let Err(e) = reporting::store(&self.redis, &record).await {
+            warn!(error = %e, "⚠️ Storing execution record failed");
+        }
+    }
+    
+    // Track execution results
+    async fn track_execution(&self, tx_hash: H256) -> Result<()> {
+        let head = self.block_state.latest().number;
+        {
+            let mut recent = self.recent_executions.write().await;
+            recent.retain(|_, sent| sent.as_u64() + REORG_DEPTH as u64 >= head.as_u64());
+            recent.insert(tx_hash, head);
+        }
+        
+        // Store in Redis for analysis
+        let mut conn = self.redis.get_async_connection().await?;
+        
+        let key = format!("liquidation:{}", tx_hash);
+        let _: () = conn.set_ex(key, tx_hash.to_string(), 86400).await?;
+        
+        // Increment counters
+        let _: () = conn.incr("stats:total_liquidations", 1).await?;
+        
+        Ok(())
+    }
+    
+    // Execute opportunities a scanning instance published to the Redis stream
+    pub async fn consume_opportunities(&self) -> Result<()> {
+        let Some(key) = &self.config.opportunity_stream else {
+            return Err(BotError::Config("consuming opportunities needs REDIS_OPPORTUNITY_STREAM".to_string()));
+        };
+        let stream = OpportunityStream::new(self.redis.clone(), key, &self.config.stream_group);
+        let mut conn = stream.connect().await?;
+        stream.join(&mut conn).await?;
+        let consumer = format!("executor-{}", std::process::id());
+        info!(stream = %key, group = %self.config.stream_group, %consumer, "📥 Consuming opportunities");
+        
+        loop {
+            let batch = match stream.next_batch(&mut conn, &consumer, STREAM_BATCH).await {
+                Ok(batch) => batch,
+                Err(e) => {
+                    self.handle_task_error("opportunity stream", e)?;
+                    continue;
+                }
+            };
+            if batch.is_empty() {
+                continue;
+            }
+            
+            let head = self.provider.get_block_number().await?;
+            for (id, opportunity) in batch {
+                // A spread from a few blocks back has almost certainly been taken
+                if opportunity.block_number + MAX_STREAM_AGE_BLOCKS < head {
+                    debug!(%id, block = %opportunity.block_number, "⏭️ Skipping stale opportunity");
+                } else if self.breaker.is_tripped() {
+                    debug!(%id, "⏸️ Circuit breaker tripped, skipping execution");
+                } else if let Err(e) = self.execute_arbitrage(opportunity).await {
+                    self.handle_task_error("stream execution", e)?;
+                }
+                stream.ack(&mut conn, &id).await?;
+            }
+        }
+    }
+    
+    // Executions reported by gRPC consumers
+    async fn record_reported_executions(self, mut reports: mpsc::Receiver<ExecutionReport>) -> Result<()> {
+        while let Some(report) = reports.recv().await {
+            self.executions.publish(&report);
+            match (report.success, report.tx_hash) {
+                (true, Some(tx)) => {
+                    info!(?tx, profit = %report.realized_profit, "✅ Consumer executed arbitrage");
+                    self.metrics.transactions_total.inc();
+                    if let Err(e) = self.track_execution(tx).await {
+                        self.handle_task_error("reported execution", e)?;
+                    }
+                }
+                _ => warn!(
+                    buy = ?report.buy_pool,
+                    sell = ?report.sell_pool,
+                    error = report.error.as_deref().unwrap_or("not submitted"),
+                    "⚠️ Consumer execution failed"
+                ),
+            }
+        }
+        Ok(())
+    }
+    
+    // Health monitoring
     async fn health_check(self) -> Result<()> {
         let mut interval = interval(Duration::from_secs(30));
         
@@ -421,14 +2079,22 @@ impl LiquidationBot {
             interval.tick().await;
             
             // Check RPC connectivity
-            match self.provider.get_block_number().await {
+            let ws_up = match self.provider.get_block_number().await {
                 Ok(block) => {
-                    println!("🔄 Health check - Block: {}", block);
+                    debug!(%block, "🔄 Health check");
+                    true
                 }
                 Err(e) => {
-                    println!("⚠️ RPC error, switching to backup: {:?}", e);
+                    warn!(error = ?e, "⚠️ RPC error, switching to backup");
                     // Switch to backup RPC
+                    false
                 }
+            };
+            // Page only when the websocket and every HTTP endpoint are down
+            if ws_up || self.http_provider.get_block_number().await.is_ok() {
+                self.alerts.resolve(Incident::ProvidersDown).await;
+            } else {
+                self.alerts.page(Incident::ProvidersDown, "All RPC providers are failing").await;
             }
             
             // Check Redis connectivity
@@ -438,24 +2104,315 @@ impl LiquidationBot {
         }
     }
     
-    // Analyze mempool transaction
-    async fn analyze_transaction(&self, tx: Transaction) -> Result<()> {
-        // Check if it's a liquidation transaction
-        if tx.to == Some(self.config.aave_pool) {
-            if let Some(input) = tx.input {
-                // Decode function selector (first 4 bytes)
-                let selector = &input[0..4];
-                
-                // liquidationCall selector: 0x00a718a9
-                if selector == [0x00, 0xa7, 0x18, 0xa9] {
-                    println!("🎯 Competitor liquidation detected!");
-                    // Could implement front-running logic here
+    // Re-read settings on SIGHUP or when the config file changes, and apply the
+    // thresholds and token lists. Everything else needs a restart.
+    async fn reload_config(self) -> Result<()> {
+        let mut hangup = tokio::signal::unix::signal(tokio::signal::unix::SignalKind::hangup())
+            .map_err(|e| BotError::Config(format!("installing SIGHUP handler: {}", e)))?;
+        let mut poll = interval(Duration::from_secs(10));
+        let mut modified = self.config.settings.modified();
+        
+        loop {
+            tokio::select! {
+                _ = hangup.recv() => {}
+                _ = poll.tick() => {
+                    let current = self.config.settings.modified();
+                    if current == modified {
+                        continue;
+                    }
+                    modified = current;
+                }
+            }
+            
+            match self.config.settings.load().and_then(|_| Config::from_env()) {
+                Ok(config) => {
+                    self.thresholds.store(Arc::new(config.thresholds()));
+                    self.dex.set_token_filter(TokenFilter::new(config.token_allowlist, config.token_denylist));
+                    info!("🔄 Reloaded thresholds and token lists");
+                }
+                Err(e) => warn!(error = %e, "⚠️ Config reload failed, keeping current settings"),
+            }
+        }
+    }
+    
+    // Keep enough ETH for gas and WETH for working capital
+    async fn monitor_balances(self) -> Result<()> {
+        let monitor = BalanceMonitor::new(
+            self.config.balance.clone(),
+            self.provider.clone(),
+            self.wallet.clone(),
+            self.alerts.clone(),
+        );
+        let mut interval = interval(Duration::from_secs(60));
+        
+        loop {
+            interval.tick().await;
+            
+            if let Err(e) = monitor.check().await {
+                self.handle_task_error("balance check", e)?;
+            }
+            match self.provider.get_balance(self.wallet.address(), None).await {
+                Ok(eth) => self.breaker.check_balance(eth).await,
+                Err(e) => self.handle_task_error("breaker balance check", e.into())?,
+            }
+        }
+    }
+    
+    // Collect profits and keep only working balances in the hot wallet
+    async fn manage_treasury(self) -> Result<()> {
+        let treasury = Treasury::new(
+            self.config.treasury.clone(),
+            self.config.balance.clone(),
+            self.config.policy_max_value,
+            self.provider.clone(),
+            self.wallet.clone(),
+            self.config.executor_address,
+            self.alerts.clone(),
+        );
+        let mut interval = interval(self.config.treasury.interval);
+        
+        loop {
+            interval.tick().await;
+            
+            if let Err(e) = treasury.run_once().await {
+                self.handle_task_error("treasury pass", e)?;
+            }
+        }
+    }
+    
+    // Follow halts and re-arms from the CLI, the API and other instances
+    async fn watch_breaker(self) -> Result<()> {
+        let mut interval = interval(Duration::from_secs(5));
+        
+        loop {
+            interval.tick().await;
+            
+            if let Err(e) = self.breaker.sync().await {
+                self.handle_task_error("circuit breaker sync", e)?;
+            }
+        }
+    }
+    
+    // Rebroadcast or cancel public transactions stuck for STUCK_TX_BLOCKS
+    async fn watch_nonces(self) -> Result<()> {
+        let mut interval = interval(Duration::from_secs(12));
+        
+        loop {
+            interval.tick().await;
+            
+            if let Err(e) = self.nonces.resolve_stuck().await {
+                self.handle_task_error("stuck transactions", e)?;
+            }
+        }
+    }
+    
+    // One-off walk over all factory history; restartable, progress is checkpointed
+    pub async fn backfill_pools(&self) -> Result<()> {
+        let Some(registry) = &self.registry else {
+            return Err(BotError::Config("pool backfill needs ENABLE_ARBITRAGE=true".to_string()));
+        };
+        let head = self.http_provider.get_block_number().await?;
+        let added = registry.backfill(&self.http_provider, &self.dex, head).await?;
+        info!(added, total = registry.count().await?, "🗂️ Backfill registered pools");
+        Ok(())
+    }
+    
+    // Follow factory events so pools created while running land in the registry
+    async fn sync_registry(self) -> Result<()> {
+        let Some(registry) = self.registry.clone() else {
+            return Ok(());
+        };
+        let mut interval = interval(Duration::from_secs(60));
+        
+        loop {
+            interval.tick().await;
+            
+            let block = match self.http_provider.get_block_number().await {
+                Ok(block) => block,
+                Err(e) => {
+                    self.handle_task_error("pool registry", e.into())?;
+                    continue;
                 }
+            };
+            
+            match registry.sync(&self.http_provider, &self.dex, block).await {
+                Ok(0) => {}
+                Ok(added) => info!(added, %block, "🗂️ Registered new pools"),
+                Err(e) => self.handle_task_error("pool registry", e)?,
             }
         }
+    }
+    
+    // Resolve submitted bundles and refresh searcher reputation
+    async fn track_relays(self) -> Result<()> {
+        let mut interval = interval(Duration::from_secs(12));
+        let mut ticks: u64 = 0;
         
+        loop {
+            interval.tick().await;
+            ticks += 1;
+            
+            let block = match self.provider.get_block_number().await {
+                Ok(block) => block,
+                Err(e) => {
+                    self.handle_task_error("relay tracking", e.into())?;
+                    continue;
+                }
+            };
+            
+            match self.relay_stats.poll(&self.provider, block).await {
+                Ok(resolved) => {
+                    for (tx_hash, outcome) in resolved {
+                        self.tips.record_outcome(tx_hash, outcome != BundleOutcome::Expired);
+                    }
+                }
+                Err(e) => self.handle_task_error("bundle stats", e)?,
+            }
+            
+            // Reputation moves slowly, check roughly every 10 minutes
+            if ticks % 50 == 1 {
+                if let Err(e) = self.relay_stats.refresh_reputation(block).await {
+                    self.handle_task_error("searcher reputation", e)?;
+                }
+            }
+            
+            self.metrics.update_relay_stats(&self.relay_stats);
+            if let Some(reading) = self.eth_price.last().await {
+                self.metrics.update_eth_price(&reading);
+            }
+        }
+    }
+    
+    // Log recoverable errors and keep the loop alive; only fatal errors stop the task
+    fn handle_task_error(&self, context: &str, err: BotError) -> Result<()> {
+        if err.is_fatal() {
+            error!(context, error = %err, "🔴 Fatal error");
+            return Err(err);
+        }
+
+        if err.is_retryable() {
+            warn!(context, error = %err, "⚠️ Transient error, will retry next tick");
+        } else {
+            error!(context, error = %err, "❌ Error");
+        }
+
         Ok(())
     }
+    
+    // Analyze mempool transaction
+    async fn analyze_transaction(&self, tx: Transaction) -> Result<Vec<Action>> {
+        let Some(to) = tx.to else {
+            return Ok(Vec::new());
+        };
+        
+        // Check if it's a liquidation transaction
+        if to == self.config.chain.aave_pool {
+            // liquidationCall selector: 0x00a718a9
+            if tx.input.get(0..4) == Some(&[0x00, 0xa7, 0x18, 0xa9][..]) {
+                info!(tx = ?tx.hash, "🎯 Competitor liquidation detected!");
+                self.scorer.record_competitor("AAVE_V3");
+                // Could implement front-running logic here
+            }
+            return Ok(Vec::new());
+        }
+        
+        if !self.config.enable_backrun {
+            return Ok(Vec::new());
+        }
+        match chainlink::decode_transmit(&tx.input) {
+            Some(answer) => self.predict_oracle_update(&tx, to, answer).await,
+            None => Ok(Vec::new()),
+        }
+    }
+    
+    // A Chainlink report in the mempool fixes the next price before it lands:
+    // re-check watched borrowers against it and bundle any liquidation it
+    // enables right behind the report
+    async fn predict_oracle_update(&self, tx: &Transaction, aggregator: Address, answer: I256) -> Result<Vec<Action>> {
+        let feeds = self.aave.price_feeds().await?;
+        let Some(assets) = feeds.get(&aggregator) else {
+            return Ok(Vec::new());
+        };
+        if answer <= I256::zero() {
+            return Ok(Vec::new());
+        }
+        let price = answer.into_raw();
+        info!(tx = ?tx.hash, ?aggregator, %price, assets = assets.len(), "🔮 Pending oracle update");
+        let overrides: HashMap<Address, U256> = assets.iter().map(|asset| (*asset, price)).collect();
+        
+        let users: Vec<Address> = self.watchlist.read().await.iter().copied().collect();
+        let mut actions = Vec::new();
+        for user in users {
+            // Already liquidatable; the regular path handles it
+            if self.positions.read().await.contains(&user) {
+                continue;
+            }
+            match self.aave_target(user, &overrides).await {
+                Ok(Some(target)) => {
+                    info!(?user, health_factor = target.health_factor, "🎯 Liquidatable after oracle update");
+                    actions.push(Action::OracleBackrun(OracleBackrun {
+                        oracle_tx: tx.hash,
+                        raw_oracle_tx: tx.rlp(),
+                        target,
+                    }));
+                }
+                Ok(None) => {}
+                Err(e) => self.handle_task_error("oracle prediction", e)?,
+            }
+        }
+        Ok(actions)
+    }
+}
+
+// Every strategy's actions go through the bot's execution path
+#[async_trait::async_trait]
+impl ActionExecutor for LiquidationBot {
+    async fn execute(&self, strategy: &str, action: Action) -> Result<()> {
+        // Strategies keep scanning and reporting; nothing is sent until re-armed
+        if self.breaker.is_tripped() {
+            debug!(strategy, "⏸️ Circuit breaker tripped, skipping execution");
+            return Ok(());
+        }
+        match action {
+            Action::Liquidation(target) => self.evaluate_and_execute(target).await,
+            Action::OracleBackrun(backrun) => self.backrun_oracle_update(backrun).await,
+            Action::AuctionTake(take) => self.take_auction(take).await,
+            Action::TroveLiquidation(batch) => self.liquidate_troves(batch).await,
+            Action::Arbitrage(opportunity) => {
+                // Left to the processes consuming the opportunity stream
+                if !self.config.execute_locally {
+                    return Ok(());
+                }
+                // Only report what survives simulation against pending state;
+                // execution checks again right before signing
+                if let Some(arbitrage) = &self.arbitrage {
+                    if let Err(e) = arbitrage.validate(&opportunity, opportunity.gas_cost).await {
+                        debug!(
+                            strategy,
+                            buy = ?opportunity.buy_pool.address,
+                            sell = ?opportunity.sell_pool.address,
+                            error = %e,
+                            "⏭️ Opportunity failed validation"
+                        );
+                        return Ok(());
+                    }
+                }
+                info!(
+                    strategy,
+                    buy_dex = %opportunity.buy_pool.dex,
+                    sell_dex = %opportunity.sell_pool.dex,
+                    buy = ?opportunity.buy_pool.address,
+                    sell = ?opportunity.sell_pool.address,
+                    amount_in = %opportunity.amount_in,
+                    amount_out = %opportunity.amount_out,
+                    spread_bps = opportunity.spread_bps,
+                    gas = %opportunity.gas_cost,
+                    "💱 Arbitrage opportunity"
+                );
+                self.execute_arbitrage(opportunity).await
+            }
+        }
+    }
 }
 
 // Helper structures
@@ -472,37 +2429,499 @@ impl Clone for LiquidationBot {
     fn clone(&self) -> Self {
         Self {
             config: self.config.clone(),
+            thresholds: self.thresholds.clone(),
             provider: self.provider.clone(),
             http_provider: self.http_provider.clone(),
             executor: self.executor.clone(),
+            aave: self.aave.clone(),
+            compound_v2: self.compound_v2.clone(),
+            eth_price: self.eth_price.clone(),
+            coingecko: self.coingecko.clone(),
+            etherscan: self.etherscan.clone(),
+            subgraph: self.subgraph.clone(),
             redis: self.redis.clone(),
+            position_index: self.position_index.clone(),
+            compound_v2_index: self.compound_v2_index.clone(),
             positions: self.positions.clone(),
+            watchlist: self.watchlist.clone(),
+            recent_executions: self.recent_executions.clone(),
+            scorer: self.scorer.clone(),
+            allocator: self.allocator.clone(),
+            risk: self.risk.clone(),
+            alerts: self.alerts.clone(),
+            breaker: self.breaker.clone(),
+            metrics: self.metrics.clone(),
+            builders: self.builders.clone(),
+            relay_stats: self.relay_stats.clone(),
+            nonces: self.nonces.clone(),
+            tips: self.tips.clone(),
+            block_state: self.block_state.clone(),
+            dex: self.dex.clone(),
+            pool_cache: self.pool_cache.clone(),
+            feed: self.feed.clone(),
+            executions: self.executions.clone(),
+            drift: self.drift.clone(),
+            registry: self.registry.clone(),
+            simulator: self.simulator.clone(),
+            price_source: self.price_source.clone(),
+            arbitrage: self.arbitrage.clone(),
+            cow: self.cow.clone(),
+            bloxroute: self.bloxroute.clone(),
             wallet: self.wallet.clone(),
         }
     }
 }
 
+#[derive(Parser)]
+#[command(name = "liquidation-bot")]
+struct Cli {
+    #[command(subcommand)]
+    command: Option<Command>,
+    /// Submit arbitrage transactions instead of only simulating them
+    #[arg(long, global = true)]
+    execute: bool,
+    /// TOML config file (defaults to CONFIG_FILE, then ./config.toml if present)
+    #[arg(long, global = true)]
+    config: Option<PathBuf>,
+    /// Section of the config file's [chains] table to apply
+    #[arg(long, global = true)]
+    chain: Option<String>,
+    /// Override any setting, e.g. --set MAX_HOPS=3 (repeatable)
+    #[arg(long = "set", value_name = "KEY=VALUE", global = true)]
+    overrides: Vec<String>,
+    /// Log output: human-readable text or one JSON object per line
+    #[arg(long, value_enum, default_value_t = logging::LogFormat::Text, global = true)]
+    log_format: logging::LogFormat,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Run the bot against mainnet (default)
+    Run,
+    /// Detect and execute opportunities on a local fork, reporting realized vs predicted profit
+    Dryrun {
+        #[arg(long)]
+        fork_block: u64,
+        /// Archive RPC to fork from (defaults to PRIMARY_RPC)
+        #[arg(long)]
+        fork_url: Option<String>,
+    },
+    /// Replay a block range from an archive node and report the arbitrage it would have found
+    Backtest {
+        #[arg(long)]
+        from_block: u64,
+        #[arg(long)]
+        to_block: u64,
+        /// Archive RPC to read historical state from (defaults to PRIMARY_RPC)
+        #[arg(long)]
+        archive_url: Option<String>,
+        /// Also write every RPC response to this file
+        #[arg(long, conflicts_with = "replay")]
+        record: Option<PathBuf>,
+        /// Serve RPC responses from a recording instead of the network
+        #[arg(long)]
+        replay: Option<PathBuf>,
+    },
+    /// Fill the pool registry from every factory event since deployment, then exit
+    Backfill,
+    /// Execute arbitrage another instance publishes to REDIS_OPPORTUNITY_STREAM, without scanning
+    Consume,
+    /// Roll up a day's executions into a PnL report (JSON and CSV)
+    Report {
+        /// UTC day, YYYY-MM-DD (defaults to yesterday)
+        #[arg(long)]
+        date: Option<chrono::NaiveDate>,
+        /// Directory the report files are written to
+        #[arg(long, default_value = "reports")]
+        out: PathBuf,
+        /// Also send the summary to the alert channels
+        #[arg(long)]
+        notify: bool,
+    },
+    /// Kill switch: halt execution on every running instance until re-armed
+    Halt {
+        /// Recorded with the halt and sent to the alert channels
+        #[arg(long, default_value = "operator request")]
+        reason: String,
+    },
+    /// Re-arm the circuit breaker, resuming execution on every running instance
+    Rearm,
+}
+
+impl Config {
+    fn from_env() -> anyhow::Result<Self> {
+        let chain = ChainProfile::from_env()?;
+        if std::env::var("ENABLE_TREASURY").as_deref() == Ok("true") && chain.uniswap_v2_router.is_none() {
+            anyhow::bail!("ENABLE_TREASURY needs UNISWAP_V2_ROUTER on {}", chain.name);
+        }
+        Ok(Config {
+            primary_rpc: std::env::var("PRIMARY_RPC")?,
+            backup_rpc: std::env::var("BACKUP_RPC")?,
+            ws_endpoint: std::env::var("WS_ENDPOINT")?,
+            rpc_limits: parse_rpc_limits(
+                &std::env::var("RPC_RATE_LIMITS").unwrap_or_default(),
+                &std::env::var("RPC_MONTHLY_CU").unwrap_or_default(),
+            ),
+            chain: chain.clone(),
+            executor_address: std::env::var("EXECUTOR_ADDRESS")?.parse()?,
+            arbitrage_executor: std::env::var("ARBITRAGE_EXECUTOR_ADDRESS").ok().map(|a| a.parse()).transpose()?,
+            compound_comet: address_var("COMPOUND_V3_COMET", COMPOUND_V3_COMET)?,
+            compound_v2_comptroller: address_var("COMPOUND_V2_COMPTROLLER", COMPOUND_V2_COMPTROLLER)?,
+            maker_dog: address_var("MAKER_DOG", MAKER_DOG)?,
+            liquity_trove_manager: address_var("LIQUITY_TROVE_MANAGER", LIQUITY_TROVE_MANAGER)?,
+            flashbots_relay: std::env::var("FLASHBOTS_RELAY").unwrap_or_else(|_| "https://relay.flashbots.net".to_string()),
+            mev_relays: std::env::var("MEV_RELAYS")
+                .map(|v| v.split(',').map(|r| r.trim().to_string()).collect())
+                .unwrap_or_else(|_| KNOWN_BUILDERS.iter().map(|(name, _, _)| name.to_string()).collect()),
+            stuck_tx_blocks: std::env::var("STUCK_TX_BLOCKS").ok().map(|v| v.parse()).transpose()?.unwrap_or(3),
+            min_bundle_gas_price: units_var("MIN_BUNDLE_GAS_PRICE_GWEI", "1", 9)?,
+            policy_destinations: parse_token_list("POLICY_ALLOWED_DESTINATIONS")?,
+            policy_max_value: units_var("POLICY_MAX_VALUE_ETH", "1", 18)?,
+            policy_max_gas_price: units_var("POLICY_MAX_GAS_PRICE_GWEI", "500", 9)?,
+            policy_max_daily_gas: units_var("POLICY_MAX_DAILY_GAS_ETH", "1", 18)?,
+            policy_token_caps: parse_token_caps("POLICY_TOKEN_CAPS")?,
+            bloxroute: BloxrouteConfig::from_env()?,
+            sandwich: SandwichGuard::from_env(chain.chain_id),
+            enable_backrun: std::env::var("ENABLE_BACKRUN").map(|v| v != "false").unwrap_or(true),
+            min_profit_usd: units_var("MIN_PROFIT_USD", "30", 18)?, // $30 minimum
+            min_expected_value: units_var("MIN_EXPECTED_VALUE_USD", "20", 18)?, // $20 after win probability
+            min_liquidity_usd: units_var("MIN_LIQUIDITY_USD", "10000", 18)?, // $10k per pool
+            max_gas_price: units_var("MAX_GAS_PRICE_GWEI", "100", 9)?, // 100 gwei max
+            max_priority_fee: units_var("MAX_PRIORITY_FEE_GWEI", "5", 9)?,
+            health_factor_threshold: std::env::var("HEALTH_FACTOR_THRESHOLD")
+                .ok()
+                .map(|v| v.parse())
+                .transpose()?
+                .unwrap_or(1.02),
+            scan_interval: Duration::from_secs(
+                std::env::var("SCAN_INTERVAL_SECS").ok().map(|v| v.parse()).transpose()?.unwrap_or(5),
+            ),
+            enable_arbitrage: std::env::var("ENABLE_ARBITRAGE").map(|v| v == "true").unwrap_or(true),
+            enable_maker: std::env::var("ENABLE_MAKER").map(|v| v == "true").unwrap_or(false),
+            enable_liquity: std::env::var("ENABLE_LIQUITY").map(|v| v == "true").unwrap_or(false),
+            max_hops: std::env::var("MAX_HOPS").ok().and_then(|v| v.parse().ok()).unwrap_or(4),
+            scan_concurrency: std::env::var("SCAN_CONCURRENCY")
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(DEFAULT_SCAN_CONCURRENCY),
+            pools_per_pair: std::env::var("POOLS_PER_PAIR").ok().and_then(|v| v.parse().ok()).unwrap_or(0),
+            execute: false,
+            exclude_taxed_tokens: std::env::var("EXCLUDE_TAXED_TOKENS").map(|v| v == "true").unwrap_or(false),
+            target_tokens: match read_list("TARGET_TOKENS") {
+                Some(spec) => TargetTokens::parse(&spec, &chain)?,
+                None => TargetTokens::for_chain(&chain),
+            },
+            token_allowlist: parse_token_list("TOKEN_ALLOWLIST")?,
+            token_denylist: parse_token_list("TOKEN_DENYLIST")?.unwrap_or_default(),
+            honeypot_check: std::env::var("HONEYPOT_CHECK").map(|v| v != "false").unwrap_or(true),
+            contract_policy: ContractPolicy::from_env()?,
+            registry_path: std::env::var("POOL_REGISTRY").unwrap_or_else(|_| "pools.db".to_string()),
+            database_url: std::env::var("DATABASE_URL").ok(),
+            kafka_brokers: std::env::var("KAFKA_BROKERS").ok(),
+            kafka_opportunities_topic: std::env::var("KAFKA_OPPORTUNITIES_TOPIC")
+                .unwrap_or_else(|_| "arbitrage.opportunities".to_string()),
+            kafka_executions_topic: std::env::var("KAFKA_EXECUTIONS_TOPIC")
+                .unwrap_or_else(|_| "arbitrage.executions".to_string()),
+            opportunity_stream: std::env::var("REDIS_OPPORTUNITY_STREAM").ok(),
+            stream_group: std::env::var("REDIS_STREAM_GROUP").unwrap_or_else(|_| "executors".to_string()),
+            execute_locally: std::env::var("ARBITRAGE_EXECUTE_LOCALLY").map(|v| v != "false").unwrap_or(true),
+            grpc_port: std::env::var("GRPC_PORT").ok().map(|v| v.parse()).transpose()?,
+            drift_alert_bps: std::env::var("DRIFT_ALERT_BPS").ok().map(|v| v.parse()).transpose()?.unwrap_or(500.0),
+            simulation_backend: std::env::var("SIMULATION_BACKEND").unwrap_or_else(|_| "call".to_string()),
+            anvil_ws: std::env::var("ANVIL_WS").ok(),
+            tenderly: match (
+                std::env::var("TENDERLY_USER"),
+                std::env::var("TENDERLY_PROJECT"),
+                std::env::var("TENDERLY_ACCESS_KEY"),
+            ) {
+                (Ok(account), Ok(project), Ok(key)) => Some((account, project, key)),
+                _ => None,
+            },
+            oracle_backend: std::env::var("ORACLE_BACKEND").unwrap_or_else(|_| "aave".to_string()),
+            oracle_feeds: parse_oracle_feeds("ORACLE_FEEDS")?,
+            pyth_contract: address_var("PYTH_CONTRACT", PYTH_MAINNET)?,
+            pyth_hermes: std::env::var("PYTH_HERMES_URL").ok(),
+            redstone_gateway: std::env::var("REDSTONE_GATEWAY").unwrap_or_else(|_| REDSTONE_GATEWAY.to_string()),
+            redstone_service: std::env::var("REDSTONE_SERVICE").unwrap_or_else(|_| REDSTONE_SERVICE.to_string()),
+            redis_url: std::env::var("REDIS_URL")?,
+            signer: SignerSource::from_env()?,
+            balance: BalanceConfig {
+                weth: chain.wrapped_native,
+                ..BalanceConfig::from_env()
+            },
+            breaker: BreakerConfig::from_env(),
+            eth_price: EthPriceConfig::from_env(),
+            coingecko: CoinGeckoConfig::from_env(),
+            etherscan: EtherscanConfig::from_env(),
+            subgraph: SubgraphConfig::from_env()?,
+            risk: RiskLimits {
+                max_notional: units_var("RISK_MAX_NOTIONAL_USD", "250000", 18)?,
+                max_flash_loan: parse_token_caps("RISK_MAX_FLASH_LOAN")?.into_iter().collect(),
+                max_executions_per_block: std::env::var("RISK_MAX_EXECUTIONS_PER_BLOCK")
+                    .ok()
+                    .map(|v| v.parse())
+                    .transpose()?
+                    .unwrap_or(3),
+                daily_gas_budget: units_var("RISK_DAILY_GAS_BUDGET_ETH", "0.5", 18)?,
+            },
+            treasury: TreasuryConfig {
+                enabled: std::env::var("ENABLE_TREASURY").map(|v| v == "true").unwrap_or(false),
+                interval: Duration::from_secs(
+                    std::env::var("TREASURY_INTERVAL_SECS").ok().map(|v| v.parse()).transpose()?.unwrap_or(3600),
+                ),
+                cold_wallet: std::env::var("TREASURY_COLD_ADDRESS")
+                    .ok()
+                    .map(|v| v.parse())
+                    .transpose()
+                    .map_err(|e| anyhow::anyhow!("TREASURY_COLD_ADDRESS: {}", e))?,
+                profit_tokens: parse_token_list("TREASURY_PROFIT_TOKENS")?.unwrap_or_default(),
+                consolidate_into: match std::env::var("TREASURY_CONSOLIDATE_INTO").as_deref() {
+                    Ok("usdc") => chain.usdc,
+                    Ok("weth") | Err(_) => chain.wrapped_native,
+                    Ok(other) => anyhow::bail!("TREASURY_CONSOLIDATE_INTO: expected weth or usdc, got {}", other),
+                },
+                usdc: chain.usdc,
+                router: chain.uniswap_v2_router.unwrap_or_default(),
+                slippage_bps: std::env::var("TREASURY_SLIPPAGE_BPS").ok().map(|v| v.parse()).transpose()?.unwrap_or(100),
+                sweep_threshold_weth: units_var("TREASURY_SWEEP_THRESHOLD_WETH", "1", 18)?,
+                sweep_threshold_usdc: units_var("TREASURY_SWEEP_THRESHOLD_USDC", "2000", 6)?,
+            },
+            cow: CowConfig::from_env(),
+            cex: CexConfig::from_env(&chain)?,
+            settings: settings::Sources::default(),
+        })
+    }
+    
+    // The policy's default destinations are the bot's own contracts, WETH for
+    // balance top-ups, the wallet itself for cancelling stuck transactions, and
+    // with the treasury on, the router, profit tokens, USDC and cold wallet
+    fn policy_rules(&self, wallet: Address) -> PolicyRules {
+        let treasury = &self.treasury;
+        let treasury_destinations: Vec<Address> = if treasury.enabled {
+            [treasury.router, treasury.usdc]
+                .into_iter()
+                .chain(treasury.profit_tokens.iter().copied())
+                .chain(treasury.cold_wallet)
+                .collect()
+        } else {
+            Vec::new()
+        };
+        let allowed_destinations = match &self.policy_destinations {
+            Some(destinations) => destinations.iter().copied().collect(),
+            None => [self.executor_address, self.liquity_trove_manager, self.balance.weth, wallet]
+                .into_iter()
+                .chain(self.arbitrage_executor)
+                .chain(treasury_destinations)
+                .collect(),
+        };
+        PolicyRules {
+            allowed_destinations,
+            max_value: Some(self.policy_max_value),
+            max_gas_price: Some(self.policy_max_gas_price),
+            max_daily_gas_spend: Some(self.policy_max_daily_gas),
+            token_caps: self.policy_token_caps.iter().copied().collect(),
+        }
+    }
+    
+    fn thresholds(&self) -> Thresholds {
+        Thresholds {
+            min_profit: self.min_profit_usd,
+            min_expected_value: self.min_expected_value,
+            min_liquidity: self.min_liquidity_usd,
+            max_gas_price: self.max_gas_price,
+            health_factor: self.health_factor_threshold,
+            scan_interval: self.scan_interval,
+        }
+    }
+}
+
+// Comma-separated per-endpoint values, e.g. RPC_RATE_LIMITS=25,10 RPC_MONTHLY_CU=300000000,
+fn parse_rpc_limits(rates: &str, budgets: &str) -> Vec<ProviderLimits> {
+    let rates: Vec<&str> = rates.split(',').collect();
+    let budgets: Vec<&str> = budgets.split(',').collect();
+    (0..rates.len().max(budgets.len()))
+        .map(|i| {
+            let default = ProviderLimits::default();
+            ProviderLimits {
+                requests_per_sec: rates
+                    .get(i)
+                    .and_then(|r| r.trim().parse().ok())
+                    .unwrap_or(default.requests_per_sec),
+                monthly_compute_units: budgets.get(i).and_then(|b| b.trim().parse().ok()),
+            }
+        })
+        .collect()
+}
+
+// `expected_profit` in wei, by the rate its gas estimate was converted at
+fn profit_in_wei(target: &LiquidationTarget) -> U256 {
+    let estimated_fees = U256::from(LIQUIDATION_GAS) * target.gas_price + target.l1_fee;
+    math::mul_div(target.expected_profit, estimated_fees, target.gas_cost).unwrap_or_default()
+}
+
+// `profit_value` with the estimated gas swapped for what simulation charged,
+// gas fees and builder tip (wei)
+fn simulated_profit(target: &LiquidationTarget, gas_fees: U256) -> U256 {
+    let estimated_fees = U256::from(LIQUIDATION_GAS) * target.gas_price + target.l1_fee;
+    if target.expected_profit.is_zero() || estimated_fees.is_zero() {
+        return target.profit_value;
+    }
+    let gas_value = math::mul_div(target.profit_value, target.gas_cost, target.expected_profit).unwrap_or_default();
+    let simulated_value = math::mul_div(gas_value, gas_fees, estimated_fees).unwrap_or(U256::MAX);
+    (target.profit_value + gas_value).saturating_sub(simulated_value)
+}
+
+// A flash-loaned liquidation borrows the debt it repays
+fn liquidation_exposure(target: &LiquidationTarget, gas_cost: U256) -> Exposure {
+    Exposure {
+        token: target.debt_asset,
+        notional: Some(target.debt_value),
+        flash_loan: target.debt_amount,
+        gas: gas_cost,
+    }
+}
+
+// Decimal amount in whole units, e.g. MAX_GAS_PRICE_GWEI=1.5 with 9 decimals
+fn units_var(var: &str, default: &str, decimals: u32) -> anyhow::Result<U256> {
+    let value = std::env::var(var).unwrap_or_else(|_| default.to_string());
+    Ok(ethers::utils::parse_units(value.trim(), decimals)
+        .map_err(|e| anyhow::anyhow!("{}: invalid amount {}: {}", var, value, e))?
+        .into())
+}
+
+fn address_var(var: &str, default: &str) -> anyhow::Result<Address> {
+    let value = std::env::var(var).unwrap_or_else(|_| default.to_string());
+    value.parse().map_err(|e| anyhow::anyhow!("{}: invalid address {}: {}", var, value, e))
+}
+
+// An env var holding either a comma-separated list or the path of a file with
+// one entry per line (`#` starts a comment)
+fn read_list(var: &str) -> Option<String> {
+    let value = std::env::var(var).ok()?;
+    Some(std::fs::read_to_string(&value).unwrap_or_else(|_| value.replace(',', "\n")))
+}
+
+fn parse_token_list(var: &str) -> anyhow::Result<Option<Vec<Address>>> {
+    let Some(contents) = read_list(var) else {
+        return Ok(None);
+    };
+    let addresses = contents
+        .lines()
+        .map(|line| line.split('#').next().unwrap_or_default().trim())
+        .filter(|line| !line.is_empty())
+        .map(|line| line.parse().map_err(|e| anyhow::anyhow!("{}: invalid address {}: {}", var, line, e)))
+        .collect::<anyhow::Result<Vec<Address>>>()?;
+    Ok(Some(addresses))
+}
+
+// `asset=feed` per line or comma, e.g. ORACLE_FEEDS=0xC02a…=0xff61…,0xA0b8…=0xeaa0…
+fn parse_oracle_feeds(var: &str) -> anyhow::Result<Vec<(Address, String)>> {
+    let Some(contents) = read_list(var) else {
+        return Ok(Vec::new());
+    };
+    contents
+        .lines()
+        .map(|line| line.split('#').next().unwrap_or_default().trim())
+        .filter(|line| !line.is_empty())
+        .map(|line| {
+            let (asset, feed) = line
+                .split_once('=')
+                .ok_or_else(|| anyhow::anyhow!("{}: expected asset=feed, got {}", var, line))?;
+            let asset = asset
+                .trim()
+                .parse()
+                .map_err(|e| anyhow::anyhow!("{}: invalid address {}: {}", var, asset, e))?;
+            Ok((asset, feed.trim().to_string()))
+        })
+        .collect()
+}
+
+// `token=amount` per line or comma, amounts in the token's base units,
+// e.g. POLICY_TOKEN_CAPS=0xA0b8…=500000000000
+fn parse_token_caps(var: &str) -> anyhow::Result<Vec<(Address, U256)>> {
+    parse_oracle_feeds(var)?
+        .into_iter()
+        .map(|(token, cap)| {
+            let cap = U256::from_dec_str(&cap).map_err(|e| anyhow::anyhow!("{}: invalid amount {}: {}", var, cap, e))?;
+            Ok((token, cap))
+        })
+        .collect()
+}
+
+// Every refused transaction is a bug or a compromised path, so each one alerts
+async fn report_policy_violations(alerts: Arc<AlertManager>, mut violations: mpsc::UnboundedReceiver<PolicyViolation>) {
+    while let Some(violation) = violations.recv().await {
+        alerts
+            .send_alert(AlertLevel::Critical, &format!("Signing policy refused a transaction: {}", violation))
+            .await;
+    }
+}
+
 #[tokio::main]
-async fn main() -> Result<()> {
-    // Load configuration
-    let config = Config {
-        primary_rpc: std::env::var("PRIMARY_RPC")?,
-        backup_rpc: std::env::var("BACKUP_RPC")?,
-        ws_endpoint: std::env::var("WS_ENDPOINT")?,
-        executor_address: std::env::var("EXECUTOR_ADDRESS")?.parse()?,
-        aave_pool: "0x87870Bca3F3fD6335C3F4ce8392D69350B4fA4E2".parse()?,
-        compound_comet: "0xc3d688B66703497DAA19211EEdff47f25384cdc3".parse()?,
-        flashbots_relay: "https://relay.flashbots.net".to_string(),
-        bloxroute_auth: std::env::var("BLOXROUTE_AUTH")?,
-        min_profit_usd: U256::from(30) * U256::exp10(18), // $30 minimum
-        max_gas_price: U256::from(100) * U256::exp10(9), // 100 gwei max
-        health_factor_threshold: 1.02,
-        redis_url: std::env::var("REDIS_URL")?,
+async fn main() -> anyhow::Result<()> {
+    let cli = Cli::parse();
+    // Held until exit so buffered spans are exported
+    let _logging = logging::init(cli.log_format)?;
+    
+    // Load configuration: CLI overrides, then environment, then config file
+    let sources = settings::Sources {
+        path: cli.config,
+        chain: cli.chain,
+        overrides: cli.overrides,
     };
+    sources.load()?;
+    let mut config = Config::from_env()?;
+    config.execute = cli.execute;
+    config.settings = sources;
     
-    // Initialize and run bot
-    let bot = LiquidationBot::new(config).await?;
-    bot.run().await?;
+    match cli.command.unwrap_or(Command::Run) {
+        Command::Run => {
+            // Initialize and run bot
+            let bot = LiquidationBot::new(config).await?;
+            bot.run().await?;
+        }
+        Command::Dryrun { fork_block, fork_url } => {
+            let fork_url = fork_url.unwrap_or_else(|| config.primary_rpc.clone());
+            dryrun::run(config, &fork_url, fork_block).await?;
+        }
+        Command::Backtest { from_block, to_block, archive_url, record, replay } => {
+            let archive_url = archive_url.unwrap_or_else(|| config.primary_rpc.clone());
+            let source = match (record, replay) {
+                (_, Some(path)) => backtest::Source::Replay(path),
+                (Some(path), None) => backtest::Source::Record { archive_url, path },
+                (None, None) => backtest::Source::Archive(archive_url),
+            };
+            backtest::run(config, source, from_block, to_block).await?;
+        }
+        Command::Backfill => {
+            let bot = LiquidationBot::new(config).await?;
+            bot.backfill_pools().await?;
+        }
+        Command::Consume => {
+            let bot = LiquidationBot::new(config).await?;
+            bot.consume_opportunities().await?;
+        }
+        Command::Report { date, out, notify } => {
+            let date = date.unwrap_or_else(|| chrono::Utc::now().date_naive() - chrono::Duration::days(1));
+            let redis = RedisClient::open(config.redis_url.as_str())?;
+            let report = DailyReport::build(date, &reporting::load(&redis, date).await?);
+            report.export(&out)?;
+            println!("{}", report.summary());
+            if notify {
+                AlertManager::new().send_alert(AlertLevel::Info, &report.summary()).await;
+            }
+        }
+        Command::Halt { reason } => {
+            let redis = RedisClient::open(config.redis_url.as_str())?;
+            breaker::halt(&redis, &reason).await?;
+            println!("Execution halted: {}", reason);
+        }
+        Command::Rearm => {
+            let redis = RedisClient::open(config.redis_url.as_str())?;
+            breaker::rearm(&redis).await?;
+            println!("Circuit breaker re-armed");
+        }
+    }
     
     Ok(())
-}
\ No newline at end of file
+}
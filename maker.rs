@@ -0,0 +1,249 @@
+use async_trait::async_trait;
+use ethers::{
+    contract::EthLogDecode,
+    prelude::*,
+    providers::{Provider, Ws},
+    types::{Address, Filter, Log, U256},
+};
+use std::{collections::HashMap, sync::Arc};
+use tokio::sync::RwLock;
+use tracing::{debug, info, warn};
+use liquidation_bot::{
+    block_state::BlockSnapshot,
+    dex::uniswap_v3::{Quoter, FEE_TIERS},
+    errors::{ProviderError, Result},
+    math::{mul_div, to_f64},
+    models::AuctionTake,
+    strategy::{Action, Strategy},
+    thresholds::SharedThresholds,
+};
+
+use crate::ETH_USD_FEED;
+
+abigen!(
+    MakerDog,
+    r#"[
+        event Bark(bytes32 indexed ilk, address indexed urn, uint256 ink, uint256 art, uint256 due, address clip, uint256 indexed id)
+    ]"#
+);
+
+abigen!(
+    MakerClipper,
+    r#"[
+        function getStatus(uint256 id) external view returns (bool needsRedo, uint256 price, uint256 lot, uint256 tab)
+        function ilk() external view returns (bytes32)
+    ]"#
+);
+
+abigen!(
+    MakerIlkRegistry,
+    r#"[
+        function gem(bytes32 ilk) external view returns (address)
+        function join(bytes32 ilk) external view returns (address)
+        function dec(bytes32 ilk) external view returns (uint256)
+    ]"#
+);
+
+pub const MAKER_STRATEGY: &str = "maker";
+pub const MAKER_DOG: &str = "0x135954d155898D42C90D2a57824C690e0c7BEf1B";
+const ILK_REGISTRY: &str = "0x5a464C28D19848f44199D003BeF5ecc87d090F87";
+pub const DAI: &str = "0x6B175474E89094C44Da98b954EedeAC495271d0F";
+// Take, exit, swap and join in one transaction
+pub const TAKE_GAS: u64 = 450_000;
+// Auctions reset after a few hours; Barks older than this are long settled
+const LOOKBACK_BLOCKS: u64 = 7_200;
+
+// Where a live auction stands this block
+enum Lot {
+    // Taken in full or cancelled
+    Closed,
+    Waiting,
+    Take(AuctionTake),
+}
+
+// Collateral type behind a Clipper
+#[derive(Debug, Clone)]
+struct Ilk {
+    gem: Address,
+    gem_join: Address,
+    decimals: u8,
+}
+
+// Maker vault liquidations: the Dog starts a Dutch auction on the ilk's
+// Clipper (Bark) and the price falls until someone takes the lot. A take
+// hands over the collateral before collecting DAI, so the executor sells it
+// inside the callback and needs no capital of its own.
+pub struct MakerStrategy {
+    provider: Arc<Provider<Ws>>,
+    quoter: Quoter<Provider<Ws>>,
+    thresholds: SharedThresholds,
+    dog: Address,
+    // Live auction ids per Clipper
+    auctions: RwLock<HashMap<Address, Vec<U256>>>,
+    ilks: RwLock<HashMap<Address, Ilk>>,
+}
+
+impl MakerStrategy {
+    pub fn new(provider: Arc<Provider<Ws>>, quoter: Address, thresholds: SharedThresholds, dog: Address) -> Self {
+        Self {
+            quoter: Quoter::new(provider.clone(), quoter),
+            provider,
+            thresholds,
+            dog,
+            auctions: RwLock::new(HashMap::new()),
+            ilks: RwLock::new(HashMap::new()),
+        }
+    }
+
+    // Picks up auctions started before the bot did
+    pub async fn load_recent(&self) -> Result<()> {
+        let latest = self.provider.get_block_number().await?.as_u64();
+        let filter = self.bark_filter().from_block(latest.saturating_sub(LOOKBACK_BLOCKS));
+        let logs = self
+            .provider
+            .get_logs(&filter)
+            .await
+            .map_err(|e| ProviderError::Rpc(e.to_string()))?;
+        for log in logs {
+            self.track(&log).await;
+        }
+        Ok(())
+    }
+
+    fn bark_filter(&self) -> Filter {
+        Filter::new()
+            .address(self.dog)
+            .event("Bark(bytes32,address,uint256,uint256,uint256,address,uint256)")
+    }
+
+    async fn track(&self, log: &Log) {
+        let Ok(bark) = BarkFilter::decode_log(&RawLog::from(log.clone())) else {
+            return;
+        };
+        info!(clipper = ?bark.clip, id = %bark.id, urn = ?bark.urn, "🔨 Maker auction started");
+        self.auctions.write().await.entry(bark.clip).or_default().push(bark.id);
+    }
+
+    async fn ilk(&self, clipper: Address) -> Result<Ilk> {
+        if let Some(ilk) = self.ilks.read().await.get(&clipper) {
+            return Ok(ilk.clone());
+        }
+        let name = MakerClipper::new(clipper, self.provider.clone()).ilk().call().await?;
+        let registry = MakerIlkRegistry::new(ILK_REGISTRY.parse::<Address>().unwrap(), self.provider.clone());
+        let ilk = Ilk {
+            gem: registry.gem(name).call().await?,
+            gem_join: registry.join(name).call().await?,
+            decimals: registry.dec(name).call().await?.low_u32() as u8,
+        };
+        self.ilks.write().await.insert(clipper, ilk.clone());
+        Ok(ilk)
+    }
+
+    // Whole remaining lot at the current price, if selling it covers the DAI owed and gas
+    async fn evaluate(&self, clipper: Address, id: U256, gas_dai: U256) -> Result<Lot> {
+        let (needs_redo, price, lot, tab) = MakerClipper::new(clipper, self.provider.clone())
+            .get_status(id)
+            .call()
+            .await?;
+        if lot.is_zero() {
+            return Ok(Lot::Closed);
+        }
+        // A stale auction must be reset (redo) before anyone can take it
+        if needs_redo || price.is_zero() {
+            return Ok(Lot::Waiting);
+        }
+        let ilk = self.ilk(clipper).await?;
+        let ray = U256::exp10(27);
+
+        // The Clipper stops at the tab: never pay more DAI than is owed
+        let owe = mul_div(lot, price, ray).unwrap_or_default().min(tab / ray);
+        let slice = mul_div(owe, ray, price).unwrap_or_default().min(lot);
+        let gem_amount = slice / U256::exp10(18usize.saturating_sub(ilk.decimals as usize));
+
+        let dai: Address = DAI.parse().unwrap();
+        let mut best: Option<(u32, U256)> = None;
+        for fee in FEE_TIERS {
+            let Ok(out) = self.quoter.quote_exact_input_single(ilk.gem, dai, fee, gem_amount).await else {
+                continue;
+            };
+            if best.map_or(true, |(_, b)| out > b) {
+                best = Some((fee, out));
+            }
+        }
+        let Some((pool_fee, proceeds)) = best else {
+            return Ok(Lot::Waiting);
+        };
+
+        let cost = owe + gas_dai;
+        if proceeds <= cost || proceeds - cost < self.thresholds.load().min_profit {
+            return Ok(Lot::Waiting);
+        }
+        Ok(Lot::Take(AuctionTake {
+            clipper,
+            id,
+            gem_join: ilk.gem_join,
+            gem: ilk.gem,
+            amount: slice,
+            max_price: price,
+            pool_fee,
+            expected_profit: proceeds - cost,
+        }))
+    }
+}
+
+#[async_trait]
+impl Strategy for MakerStrategy {
+    fn name(&self) -> &str {
+        MAKER_STRATEGY
+    }
+
+    fn log_filter(&self) -> Option<Filter> {
+        Some(self.bark_filter())
+    }
+
+    async fn on_log(&self, log: &Log) -> Result<Vec<Action>> {
+        self.track(log).await;
+        Ok(Vec::new())
+    }
+
+    // Auction prices fall every block, so each block re-prices every live lot
+    async fn on_block(&self, snapshot: &BlockSnapshot) -> Result<Vec<Action>> {
+        let eth_usd = ETH_USD_FEED
+            .parse::<Address>()
+            .ok()
+            .and_then(|feed| snapshot.prices.get(&feed))
+            .map(|p| (*p).max(I256::zero()).into_raw())
+            .unwrap_or_default();
+        // DAI is taken at $1; the feed has 8 decimals
+        let gas_dai = U256::from(TAKE_GAS) * snapshot.gas_price * eth_usd / U256::exp10(8);
+
+        let auctions = self.auctions.read().await.clone();
+        let mut actions = Vec::new();
+        let mut finished = Vec::new();
+        for (clipper, ids) in auctions {
+            for id in ids {
+                match self.evaluate(clipper, id, gas_dai).await {
+                    Ok(Lot::Take(take)) => {
+                        info!(?clipper, %id, profit = to_f64(take.expected_profit) / 1e18, "🏷️ Maker auction profitable");
+                        actions.push(Action::AuctionTake(take));
+                    }
+                    Ok(Lot::Waiting) => {}
+                    Ok(Lot::Closed) => finished.push((clipper, id)),
+                    Err(e) => warn!(?clipper, %id, error = %e, "⚠️ Pricing Maker auction failed"),
+                }
+            }
+        }
+
+        if !finished.is_empty() {
+            debug!(count = finished.len(), "🏁 Maker auctions closed");
+            let mut auctions = self.auctions.write().await;
+            for (clipper, id) in finished {
+                if let Some(ids) = auctions.get_mut(&clipper) {
+                    ids.retain(|i| *i != id);
+                }
+            }
+            auctions.retain(|_, ids| !ids.is_empty());
+        }
+        Ok(actions)
+    }
+}
@@ -0,0 +1,90 @@
+use ethers::types::{U256, U512};
+
+// Fixed-point helpers shared by the pricing code. Amounts stay in raw token
+// units; prices are unsigned Q128.128 (value * 2^128). Products go through
+// U512, so nothing overflows or loses precision before the final division.
+// f64 is only for logarithms and display, never for amounts.
+
+pub const BPS: u32 = 10_000;
+
+pub fn q96() -> U256 {
+    U256::one() << 96
+}
+
+pub fn q128() -> U256 {
+    U256::one() << 128
+}
+
+// floor(a * b / denominator); None on division by zero or a result above U256
+pub fn mul_div(a: U256, b: U256, denominator: U256) -> Option<U256> {
+    if denominator.is_zero() {
+        return None;
+    }
+    U256::try_from(a.full_mul(b) / U512::from(denominator)).ok()
+}
+
+// ceil(a * b / denominator)
+pub fn mul_div_up(a: U256, b: U256, denominator: U256) -> Option<U256> {
+    if denominator.is_zero() {
+        return None;
+    }
+    let product = a.full_mul(b);
+    let denominator = U512::from(denominator);
+    let mut result = product / denominator;
+    if !(product % denominator).is_zero() {
+        result += U512::one();
+    }
+    U256::try_from(result).ok()
+}
+
+pub fn div_round_up(a: U256, b: U256) -> Option<U256> {
+    if b.is_zero() {
+        return None;
+    }
+    let result = a / b;
+    Some(if (a % b).is_zero() { result } else { result + 1 })
+}
+
+// numerator / denominator as Q128
+pub fn ratio_x128(numerator: U256, denominator: U256) -> Option<U256> {
+    mul_div(numerator, q128(), denominator)
+}
+
+// 10^(decimals_out - decimals_in) as Q128, converting between raw units at 1:1
+pub fn decimals_x128(decimals_in: u8, decimals_out: u8) -> U256 {
+    if decimals_out >= decimals_in {
+        U256::exp10((decimals_out - decimals_in) as usize).saturating_mul(q128())
+    } else {
+        q128() / U256::exp10((decimals_in - decimals_out) as usize)
+    }
+}
+
+// `value` less a fee of `fee_bps`, rounded down
+pub fn less_fee(value: U256, fee_bps: u32) -> U256 {
+    mul_div(value, U256::from(BPS - fee_bps.min(BPS)), U256::from(BPS)).unwrap_or_default()
+}
+
+// part / whole in basis points, saturating at u32::MAX
+pub fn bps(part: U256, whole: U256) -> u32 {
+    match mul_div(part, U256::from(BPS), whole) {
+        Some(bps) if bps <= U256::from(u32::MAX) => bps.as_u32(),
+        Some(_) => u32::MAX,
+        None if whole.is_zero() => 0,
+        None => u32::MAX,
+    }
+}
+
+// Nearest f64, for logarithms and display; exact up to 2^53
+pub fn to_f64(value: U256) -> f64 {
+    let bits = value.bits();
+    if bits <= 128 {
+        value.as_u128() as f64
+    } else {
+        let shift = bits - 128;
+        (value >> shift).as_u128() as f64 * 2f64.powi(shift as i32)
+    }
+}
+
+pub fn x128_to_f64(value: U256) -> f64 {
+    to_f64(value) / 2f64.powi(128)
+}
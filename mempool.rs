@@ -0,0 +1,378 @@
+use ethers::{
+    abi::{self, ParamType, Token},
+    types::{Address, Transaction, U256},
+    utils::id,
+};
+
+use crate::{
+    dex::{uniswap_v3, DexManager},
+    math,
+    models::{DexPool, DexType, PoolState},
+};
+
+pub const UNISWAP_V2_ROUTER: &str = "0x7a250d5630B4cF539739dF2C5dAcb4c659F2488D";
+pub const SUSHISWAP_ROUTER: &str = "0xd9e1cE17f2641f24aE83637ab66a2cca9C378B9F";
+pub const UNISWAP_V3_ROUTER: &str = "0xE592427A0AEce92De3Edee1F18E0157C05861564";
+pub const UNISWAP_V3_ROUTER_02: &str = "0x68b3465833fb72A70ecDF485E0e4C7bD8665Fc45";
+// The Universal Router's deployments, oldest first
+pub const UNIVERSAL_ROUTERS: [&str; 3] = [
+    "0xEf1c6E67703c7BD7107eed8303Fbe6EC2554BF6B",
+    "0x3fC91A3afd70395Cd496C647d5a6B0B5a4ea2A2C",
+    "0x66a9893cC07D91D95644AEDD05D03f95e1dBA8Af",
+];
+
+// V2 Router02, and SwapRouter02's V2 leg without the deadline
+const SWAP_EXACT_TOKENS_FOR_TOKENS: &str = "swapExactTokensForTokens(uint256,uint256,address[],address,uint256)";
+const SWAP_EXACT_TOKENS_FOR_ETH: &str = "swapExactTokensForETH(uint256,uint256,address[],address,uint256)";
+const SWAP_EXACT_ETH_FOR_TOKENS: &str = "swapExactETHForTokens(uint256,address[],address,uint256)";
+const SWAP_EXACT_TOKENS_FOR_TOKENS_TAXED: &str =
+    "swapExactTokensForTokensSupportingFeeOnTransferTokens(uint256,uint256,address[],address,uint256)";
+const SWAP_EXACT_TOKENS_FOR_ETH_TAXED: &str =
+    "swapExactTokensForETHSupportingFeeOnTransferTokens(uint256,uint256,address[],address,uint256)";
+const SWAP_EXACT_ETH_FOR_TOKENS_TAXED: &str =
+    "swapExactETHForTokensSupportingFeeOnTransferTokens(uint256,address[],address,uint256)";
+const SWAP_EXACT_TOKENS_FOR_TOKENS_02: &str = "swapExactTokensForTokens(uint256,uint256,address[],address)";
+// SwapRouter takes a deadline in its params, SwapRouter02 doesn't
+const EXACT_INPUT_SINGLE: &str = "exactInputSingle((address,address,uint24,address,uint256,uint256,uint256,uint160))";
+const EXACT_INPUT: &str = "exactInput((bytes,address,uint256,uint256,uint256))";
+const EXACT_INPUT_SINGLE_02: &str = "exactInputSingle((address,address,uint24,address,uint256,uint256,uint160))";
+const EXACT_INPUT_02: &str = "exactInput((bytes,address,uint256,uint256))";
+const MULTICALL: &str = "multicall(bytes[])";
+const MULTICALL_DEADLINE: &str = "multicall(uint256,bytes[])";
+const MULTICALL_BLOCKHASH: &str = "multicall(bytes32,bytes[])";
+const EXECUTE: &str = "execute(bytes,bytes[])";
+const EXECUTE_DEADLINE: &str = "execute(bytes,bytes[],uint256)";
+
+// Universal Router commands, in the low six bits of each command byte
+const V3_SWAP_EXACT_IN: u8 = 0x00;
+const V2_SWAP_EXACT_IN: u8 = 0x08;
+const COMMAND_MASK: u8 = 0x3f;
+
+// V3 path: token (20 bytes), then fee (3) and token for every hop
+const PATH_ADDRESS: usize = 20;
+const PATH_HOP: usize = 23;
+
+// One pool a pending swap trades through
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PendingHop {
+    pub dex: DexType,
+    pub token_in: Address,
+    pub token_out: Address,
+    // V3 fee tier in pips; a V2 venue has one pair per token pair
+    pub fee_pips: Option<u32>,
+}
+
+impl PendingHop {
+    fn matches(&self, pool: &DexPool) -> bool {
+        if pool.dex != self.dex || !pool.has_token(self.token_in) || !pool.has_token(self.token_out) {
+            return false;
+        }
+        match (&pool.state, self.fee_pips) {
+            (PoolState::UniswapV3(state), Some(fee)) => state.fee_pips == fee,
+            (PoolState::ConstantProduct, None) => true,
+            _ => false,
+        }
+    }
+}
+
+// An exact-input swap decoded from router calldata. Exact-output swaps are
+// skipped: their input is only bounded, not known.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PendingSwap {
+    pub amount_in: U256,
+    pub hops: Vec<PendingHop>,
+}
+
+// Every exact-input swap in a pending transaction to one of the Uniswap
+// routers, or SushiSwap's V2 fork of Router02, in execution order
+pub fn decode_swaps(tx: &Transaction) -> Vec<PendingSwap> {
+    let mut swaps = Vec::new();
+    let Some(to) = tx.to else {
+        return swaps;
+    };
+    let is = |router: &str| router.parse::<Address>().is_ok_and(|r| r == to);
+
+    if is(SUSHISWAP_ROUTER) {
+        decode_call(&tx.input, tx.value, DexType::SushiSwap, &mut swaps);
+    } else if is(UNISWAP_V2_ROUTER) || is(UNISWAP_V3_ROUTER) || is(UNISWAP_V3_ROUTER_02) {
+        decode_call(&tx.input, tx.value, DexType::UniswapV2, &mut swaps);
+    } else if UNIVERSAL_ROUTERS.iter().any(|r| is(r)) {
+        decode_execute(&tx.input, tx.value, &mut swaps);
+    }
+    swaps
+}
+
+// One router call; multicalls are unpacked into their calls. `value` is the
+// ETH sent with the transaction, the input of the ETH-in V2 swaps.
+fn decode_call(input: &[u8], value: U256, v2: DexType, swaps: &mut Vec<PendingSwap>) {
+    if input.len() < 4 {
+        return;
+    }
+    let (selector, args) = input.split_at(4);
+    let is = |signature: &str| selector == &id(signature)[..];
+    let address_path = || ParamType::Array(Box::new(ParamType::Address));
+
+    if is(SWAP_EXACT_TOKENS_FOR_TOKENS)
+        || is(SWAP_EXACT_TOKENS_FOR_ETH)
+        || is(SWAP_EXACT_TOKENS_FOR_TOKENS_TAXED)
+        || is(SWAP_EXACT_TOKENS_FOR_ETH_TAXED)
+    {
+        let params = [
+            ParamType::Uint(256),
+            ParamType::Uint(256),
+            address_path(),
+            ParamType::Address,
+            ParamType::Uint(256),
+        ];
+        if let Ok(tokens) = abi::decode(&params, args) {
+            push_v2(uint(&tokens[0]), &tokens[2], v2, swaps);
+        }
+    } else if is(SWAP_EXACT_ETH_FOR_TOKENS) || is(SWAP_EXACT_ETH_FOR_TOKENS_TAXED) {
+        let params = [ParamType::Uint(256), address_path(), ParamType::Address, ParamType::Uint(256)];
+        if let Ok(tokens) = abi::decode(&params, args) {
+            push_v2(Some(value), &tokens[1], v2, swaps);
+        }
+    } else if is(SWAP_EXACT_TOKENS_FOR_TOKENS_02) {
+        let params = [ParamType::Uint(256), ParamType::Uint(256), address_path(), ParamType::Address];
+        if let Ok(tokens) = abi::decode(&params, args) {
+            push_v2(uint(&tokens[0]), &tokens[2], v2, swaps);
+        }
+    } else if is(EXACT_INPUT_SINGLE) || is(EXACT_INPUT_SINGLE_02) {
+        // The price limit is ignored; a swap that stops early moves the pool less
+        let mut fields = vec![
+            ParamType::Address,
+            ParamType::Address,
+            ParamType::Uint(24),
+            ParamType::Address,
+            ParamType::Uint(256),
+            ParamType::Uint(256),
+            ParamType::Uint(160),
+        ];
+        let amount_at = if is(EXACT_INPUT_SINGLE) {
+            fields.insert(4, ParamType::Uint(256));
+            5
+        } else {
+            4
+        };
+        let Some(params) = decode_tuple(fields, args) else {
+            return;
+        };
+        let (Token::Address(token_in), Token::Address(token_out), Some(fee), Some(amount_in)) =
+            (&params[0], &params[1], uint(&params[2]), uint(&params[amount_at]))
+        else {
+            return;
+        };
+        push(
+            amount_in,
+            vec![PendingHop {
+                dex: DexType::UniswapV3,
+                token_in: *token_in,
+                token_out: *token_out,
+                fee_pips: Some(fee.low_u32()),
+            }],
+            swaps,
+        );
+    } else if is(EXACT_INPUT) || is(EXACT_INPUT_02) {
+        let mut fields = vec![ParamType::Bytes, ParamType::Address, ParamType::Uint(256), ParamType::Uint(256)];
+        let amount_at = if is(EXACT_INPUT) {
+            fields.push(ParamType::Uint(256));
+            3
+        } else {
+            2
+        };
+        let Some(params) = decode_tuple(fields, args) else {
+            return;
+        };
+        if let (Token::Bytes(path), Some(amount_in)) = (&params[0], uint(&params[amount_at])) {
+            if let Some(hops) = v3_path(path) {
+                push(amount_in, hops, swaps);
+            }
+        }
+    } else if is(MULTICALL) || is(MULTICALL_DEADLINE) || is(MULTICALL_BLOCKHASH) {
+        let calls = ParamType::Array(Box::new(ParamType::Bytes));
+        let params = if is(MULTICALL) {
+            vec![calls]
+        } else if is(MULTICALL_DEADLINE) {
+            vec![ParamType::Uint(256), calls]
+        } else {
+            vec![ParamType::FixedBytes(32), calls]
+        };
+        let Some(Token::Array(calls)) = abi::decode(&params, args).ok().and_then(|t| t.into_iter().last()) else {
+            return;
+        };
+        for call in calls {
+            if let Token::Bytes(call) = call {
+                decode_call(&call, value, v2.clone(), swaps);
+            }
+        }
+    }
+}
+
+// Universal Router `execute`: one input per command byte. Only the two
+// exact-input swaps are read; wraps, permits and sweeps around them are skipped.
+fn decode_execute(input: &[u8], value: U256, swaps: &mut Vec<PendingSwap>) {
+    if input.len() < 4 {
+        return;
+    }
+    let (selector, args) = input.split_at(4);
+    let mut params = vec![ParamType::Bytes, ParamType::Array(Box::new(ParamType::Bytes))];
+    if selector == &id(EXECUTE_DEADLINE)[..] {
+        params.push(ParamType::Uint(256));
+    } else if selector != &id(EXECUTE)[..] {
+        return;
+    }
+    let Ok(tokens) = abi::decode(&params, args) else {
+        return;
+    };
+    let (Token::Bytes(commands), Token::Array(inputs)) = (&tokens[0], &tokens[1]) else {
+        return;
+    };
+
+    // The router's whole balance, which a preceding WRAP_ETH fills with the value sent
+    let contract_balance = U256::one() << 255;
+    let amount = |token: &Token| match uint(token) {
+        Some(amount) if amount == contract_balance => Some(value),
+        amount => amount,
+    };
+
+    for (command, input) in commands.iter().zip(inputs) {
+        let Token::Bytes(input) = input else {
+            continue;
+        };
+        let path = match command & COMMAND_MASK {
+            V3_SWAP_EXACT_IN => ParamType::Bytes,
+            V2_SWAP_EXACT_IN => ParamType::Array(Box::new(ParamType::Address)),
+            _ => continue,
+        };
+        let params = [ParamType::Address, ParamType::Uint(256), ParamType::Uint(256), path, ParamType::Bool];
+        let Ok(tokens) = abi::decode(&params, input) else {
+            continue;
+        };
+        match &tokens[3] {
+            Token::Bytes(path) => {
+                if let (Some(amount_in), Some(hops)) = (amount(&tokens[1]), v3_path(path)) {
+                    push(amount_in, hops, swaps);
+                }
+            }
+            path => push_v2(amount(&tokens[1]), path, DexType::UniswapV2, swaps),
+        }
+    }
+}
+
+fn push_v2(amount_in: Option<U256>, path: &Token, dex: DexType, swaps: &mut Vec<PendingSwap>) {
+    let (Some(amount_in), Token::Array(path)) = (amount_in, path) else {
+        return;
+    };
+    let path: Vec<Address> = path.iter().filter_map(|t| t.clone().into_address()).collect();
+    let hops = path
+        .windows(2)
+        .map(|pair| PendingHop {
+            dex: dex.clone(),
+            token_in: pair[0],
+            token_out: pair[1],
+            fee_pips: None,
+        })
+        .collect();
+    push(amount_in, hops, swaps);
+}
+
+// A zero input means the router pays from a balance an earlier call left it
+fn push(amount_in: U256, hops: Vec<PendingHop>, swaps: &mut Vec<PendingSwap>) {
+    if !amount_in.is_zero() && !hops.is_empty() {
+        swaps.push(PendingSwap { amount_in, hops });
+    }
+}
+
+fn decode_tuple(fields: Vec<ParamType>, args: &[u8]) -> Option<Vec<Token>> {
+    match abi::decode(&[ParamType::Tuple(fields)], args).ok()?.into_iter().next()? {
+        Token::Tuple(fields) => Some(fields),
+        _ => None,
+    }
+}
+
+fn uint(token: &Token) -> Option<U256> {
+    token.clone().into_uint()
+}
+
+// Hops of a packed V3 path
+fn v3_path(path: &[u8]) -> Option<Vec<PendingHop>> {
+    if path.len() < PATH_ADDRESS + PATH_HOP || (path.len() - PATH_ADDRESS) % PATH_HOP != 0 {
+        return None;
+    }
+    let hops = (path.len() - PATH_ADDRESS) / PATH_HOP;
+    Some(
+        (0..hops)
+            .map(|i| {
+                let at = i * PATH_HOP;
+                let fee = &path[at + PATH_ADDRESS..at + PATH_HOP];
+                PendingHop {
+                    dex: DexType::UniswapV3,
+                    token_in: Address::from_slice(&path[at..at + PATH_ADDRESS]),
+                    token_out: Address::from_slice(&path[at + PATH_HOP..at + PATH_HOP + PATH_ADDRESS]),
+                    fee_pips: Some(u32::from_be_bytes([0, fee[0], fee[1], fee[2]])),
+                }
+            })
+            .collect(),
+    )
+}
+
+// Copies of the pools `swaps` trade through, as they'll be once the swaps
+// land. Each hop is fed the previous one's output, and a pool traded twice
+// continues from its projected state. A swap stops at the first hop whose
+// pool isn't in `pools` or can't be simulated, since its output is unknown.
+pub fn project(pools: &[DexPool], swaps: &[PendingSwap]) -> Vec<DexPool> {
+    let mut projected: Vec<DexPool> = Vec::new();
+    for swap in swaps {
+        let mut amount = swap.amount_in;
+        for hop in &swap.hops {
+            let index = match projected.iter().position(|p| hop.matches(p)) {
+                Some(index) => index,
+                None => match pools.iter().find(|p| hop.matches(p)) {
+                    Some(pool) => {
+                        projected.push(pool.clone());
+                        projected.len() - 1
+                    }
+                    None => break,
+                },
+            };
+            match apply_swap(&mut projected[index], hop.token_in, amount) {
+                Some(out) => amount = out,
+                None => break,
+            }
+        }
+    }
+    projected
+}
+
+// Trade `amount_in` of `token_in` through `pool`, returning the output. Only
+// V2 pairs and V3 pools are simulated; None for other venues.
+pub(crate) fn apply_swap(pool: &mut DexPool, token_in: Address, amount_in: U256) -> Option<U256> {
+    let zero_for_one = token_in == pool.token0.address;
+    // What reaches the pool after a fee-on-transfer token's cut
+    let tax = if zero_for_one { pool.token0.transfer_tax_bps } else { pool.token1.transfer_tax_bps };
+    let amount_in = math::less_fee(amount_in, tax);
+
+    match &mut pool.state {
+        PoolState::UniswapV3(state) => {
+            let out = uniswap_v3::apply_swap_exact_in(state, zero_for_one, amount_in)?;
+            uniswap_v3::update_virtual_reserves(pool);
+            return Some(out);
+        }
+        PoolState::ConstantProduct => {}
+        _ => return None,
+    }
+
+    let (reserve_in, reserve_out) = pool.reserves_for(token_in)?;
+    let out = DexManager::calculate_output_amount(amount_in, reserve_in, reserve_out, pool.fee_bps);
+    if out.is_zero() {
+        return None;
+    }
+    if zero_for_one {
+        pool.reserve0 = reserve_in + amount_in;
+        pool.reserve1 = reserve_out - out;
+    } else {
+        pool.reserve1 = reserve_in + amount_in;
+        pool.reserve0 = reserve_out - out;
+    }
+    Some(out)
+}
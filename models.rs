@@ -0,0 +1,431 @@
+use ethers::types::{Address, Bytes, H256, I256, U256, U64};
+use serde::{Deserialize, Serialize};
+use std::{collections::BTreeMap, fmt};
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum DexType {
+    UniswapV2,
+    SushiSwap,
+    UniswapV3,
+    Curve,
+    Balancer,
+    // BNB Chain's largest venue: V2 pairs at 0.25%, V3 pools with a 0.25% tier
+    PancakeSwapV2,
+    PancakeSwapV3,
+    // Solidly forks on Optimism and Base, each with volatile and stable pools
+    Velodrome,
+    Aerodrome,
+    // Trader Joe's Liquidity Book (v2.1) on Avalanche and Arbitrum
+    TraderJoeV2,
+    // Maverick V2, whose bins move liquidity between ticks with the price
+    MaverickV2,
+    // Uniswap V4, every pool held by the PoolManager singleton
+    UniswapV4,
+    // Venues registered by library users
+    Custom(String),
+}
+
+impl fmt::Display for DexType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DexType::UniswapV2 => write!(f, "UniswapV2"),
+            DexType::SushiSwap => write!(f, "SushiSwap"),
+            DexType::UniswapV3 => write!(f, "UniswapV3"),
+            DexType::Curve => write!(f, "Curve"),
+            DexType::Balancer => write!(f, "Balancer"),
+            DexType::PancakeSwapV2 => write!(f, "PancakeSwapV2"),
+            DexType::PancakeSwapV3 => write!(f, "PancakeSwapV3"),
+            DexType::Velodrome => write!(f, "Velodrome"),
+            DexType::Aerodrome => write!(f, "Aerodrome"),
+            DexType::TraderJoeV2 => write!(f, "TraderJoeV2"),
+            DexType::MaverickV2 => write!(f, "MaverickV2"),
+            DexType::UniswapV4 => write!(f, "UniswapV4"),
+            DexType::Custom(name) => write!(f, "{}", name),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct Token {
+    pub address: Address,
+    pub symbol: String,
+    pub decimals: u8,
+    // Share withheld from every transfer by fee-on-transfer tokens, in bps
+    #[serde(default)]
+    pub transfer_tax_bps: u32,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DexPool {
+    pub address: Address,
+    pub dex: DexType,
+    pub token0: Token,
+    pub token1: Token,
+    pub reserve0: U256,
+    pub reserve1: U256,
+    // Swap fee in basis points
+    pub fee_bps: u32,
+    // Block the state was read at; zero when read at whatever was latest
+    #[serde(default)]
+    pub block: U64,
+    // Venue-specific state beyond the two reserves
+    #[serde(default)]
+    pub state: PoolState,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub enum PoolState {
+    // x*y=k, fully described by the reserves
+    #[default]
+    ConstantProduct,
+    Curve(CurveState),
+    Balancer(BalancerState),
+    UniswapV3(UniswapV3State),
+    // Solidly stable pool, x^3*y + x*y^3 = k on the reserves scaled to 18
+    // decimals; volatile Solidly pools are plain constant product
+    SolidlyStable,
+    LiquidityBook(LiquidityBookState),
+    Maverick(MaverickState),
+    UniswapV4(UniswapV4State),
+}
+
+// Stableswap pools price against every coin in the pool, not just the pair
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CurveState {
+    pub balances: Vec<U256>,
+    pub decimals: Vec<u8>,
+    // Amplification coefficient A (not A * A_PRECISION)
+    pub amplification: U256,
+    // Fee with 1e10 precision, as returned by pool.fee()
+    pub fee: U256,
+    // Coin indices of token0 and token1 within the pool
+    pub index0: usize,
+    pub index1: usize,
+}
+
+// Weighted pools price on balances and normalised weights, held by the Vault
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BalancerState {
+    pub pool_id: [u8; 32],
+    // Normalised weights (1e18 = 100%) of token0 and token1; balances are the reserves
+    pub weight0: U256,
+    pub weight1: U256,
+    // Swap fee with 1e18 precision, as returned by getSwapFeePercentage()
+    pub swap_fee: U256,
+}
+
+// Concentrated liquidity around the current price. Ticks are only known inside
+// [tick_lower_bound, tick_upper_bound]; quotes that leave that range fail.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UniswapV3State {
+    pub sqrt_price_x96: U256,
+    pub tick: i32,
+    // In-range liquidity
+    pub liquidity: u128,
+    pub tick_spacing: i32,
+    // Fee in hundredths of a bip (3000 = 0.3%)
+    pub fee_pips: u32,
+    // Initialized ticks and their liquidityNet
+    pub ticks: BTreeMap<i32, i128>,
+    pub tick_lower_bound: i32,
+    pub tick_upper_bound: i32,
+}
+
+// Constant-price bins around the active one; token0 is the pair's X and token1
+// its Y. Bins are only known as far as they were read; quotes past them fail.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LiquidityBookState {
+    pub active_id: u32,
+    pub bin_step: u16,
+    // Base plus variable fee with 1e18 precision, as of the last refresh
+    pub fee: U256,
+    // Non-empty bins read and their (reserve X, reserve Y)
+    pub bins: BTreeMap<u32, (U256, U256)>,
+}
+
+// Reserves of the ticks around the active one, in the pool's 18-decimal units;
+// token0 is the pool's A and token1 its B. Quotes past the ticks read fail.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MaverickState {
+    pub active_tick: i32,
+    pub tick_spacing: u32,
+    // Fees with 1e18 precision, which differ by swap direction
+    pub fee_a_in: U256,
+    pub fee_b_in: U256,
+    // Non-empty ticks read and their (reserve A, reserve B)
+    pub ticks: BTreeMap<i32, (U256, U256)>,
+}
+
+// A V4 pool: its key, apart from the two tokens, and V3-style state read
+// through StateView, with fee_pips the LP fee current at the last read
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UniswapV4State {
+    pub pool_id: [u8; 32],
+    // 0x800000 marks a dynamic fee, set by the hook
+    pub fee: u32,
+    pub tick_spacing: i32,
+    pub hooks: Address,
+    pub pool: UniswapV3State,
+}
+
+impl DexPool {
+    pub fn has_token(&self, token: Address) -> bool {
+        self.token0.address == token || self.token1.address == token
+    }
+
+    // The other side of the pair, if `token` is in it
+    pub fn other(&self, token: Address) -> Option<&Token> {
+        if self.token0.address == token {
+            Some(&self.token1)
+        } else if self.token1.address == token {
+            Some(&self.token0)
+        } else {
+            None
+        }
+    }
+
+    // Reserves ordered as (in, out) for a swap starting from `token_in`
+    pub fn reserves_for(&self, token_in: Address) -> Option<(U256, U256)> {
+        if self.token0.address == token_in {
+            Some((self.reserve0, self.reserve1))
+        } else if self.token1.address == token_in {
+            Some((self.reserve1, self.reserve0))
+        } else {
+            None
+        }
+    }
+
+    // Unordered key shared by every pool trading the same two tokens
+    pub fn pair_key(&self) -> (Address, Address) {
+        if self.token0.address < self.token1.address {
+            (self.token0.address, self.token1.address)
+        } else {
+            (self.token1.address, self.token0.address)
+        }
+    }
+}
+
+// Where an arbitrage's capital is borrowed from
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FlashLoanProvider {
+    // Aave V3 flash loan of the input token
+    #[default]
+    Aave,
+    // Flash swap on the buy pool itself: the V2 pair sends the intermediate
+    // token first and is repaid the input inside `uniswapV2Call`, charging
+    // nothing beyond its swap fee
+    UniswapV2Flash,
+    // `flash()` on a Uniswap V3 pool outside the trade holding the input
+    // token, for that pool's swap fee on the amount borrowed
+    UniswapV3Flash,
+}
+
+// Aave V3's flash loan premium
+const AAVE_FLASH_FEE_BPS: u64 = 5;
+
+impl FlashLoanProvider {
+    // Whether it can fund an arbitrage buying on `buy_pool`; a V3 flash also
+    // needs a lending pool
+    pub fn supports(&self, buy_pool: &DexPool) -> bool {
+        match self {
+            FlashLoanProvider::Aave | FlashLoanProvider::UniswapV3Flash => true,
+            FlashLoanProvider::UniswapV2Flash => matches!(buy_pool.dex, DexType::UniswapV2 | DexType::SushiSwap),
+        }
+    }
+}
+
+impl std::str::FromStr for FlashLoanProvider {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "aave" => Ok(FlashLoanProvider::Aave),
+            "uniswap_v2_flash" => Ok(FlashLoanProvider::UniswapV2Flash),
+            "uniswap_v3_flash" => Ok(FlashLoanProvider::UniswapV3Flash),
+            other => Err(format!("unknown flash loan provider {}", other)),
+        }
+    }
+}
+
+impl fmt::Display for FlashLoanProvider {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            FlashLoanProvider::Aave => write!(f, "aave"),
+            FlashLoanProvider::UniswapV2Flash => write!(f, "uniswap_v2_flash"),
+            FlashLoanProvider::UniswapV3Flash => write!(f, "uniswap_v3_flash"),
+        }
+    }
+}
+
+// Borrow `amount_in` of `token_in`, buy on `buy_pool`, sell back on `sell_pool`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ArbitrageOpportunity {
+    pub token_in: Address,
+    pub token_out: Address,
+    pub buy_pool: DexPool,
+    pub sell_pool: DexPool,
+    pub amount_in: U256,
+    pub intermediate_amount: U256,
+    pub amount_out: U256,
+    // Profit in `token_in` units after the flash loan fee, before gas
+    pub gross_profit: U256,
+    // Gas in wei
+    pub gas_cost: U256,
+    pub spread_bps: u32,
+    pub block_number: U64,
+    // A V2 flash swap borrows `intermediate_amount` of `token_out` from the buy
+    // pool instead, replacing the buy leg
+    #[serde(default)]
+    pub flash_loan: FlashLoanProvider,
+    // Cheapest V3 pool outside the trade holding `token_in`, that a V3 flash
+    // would borrow from
+    #[serde(default)]
+    pub flash_pool: Option<DexPool>,
+}
+
+impl ArbitrageOpportunity {
+    // The same trade funded by `provider`, with its fee repriced; None for a
+    // V3 flash without a lending pool
+    pub fn with_flash_loan(mut self, provider: FlashLoanProvider) -> Option<Self> {
+        let fee = match provider {
+            FlashLoanProvider::Aave => self.amount_in * AAVE_FLASH_FEE_BPS / 10_000,
+            FlashLoanProvider::UniswapV2Flash => U256::zero(),
+            FlashLoanProvider::UniswapV3Flash => {
+                crate::math::mul_div_up(self.amount_in, self.flash_pool.as_ref()?.fee_bps.into(), 10_000.into())?
+            }
+        };
+        self.gross_profit = self.amount_out.saturating_sub(self.amount_in + fee);
+        self.spread_bps = crate::math::bps(self.gross_profit, self.amount_in);
+        self.flash_loan = provider;
+        Some(self)
+    }
+
+    // Lender, token and amount borrowed for the trade; None for Aave, whose
+    // liquidity sits with the reserve's aToken
+    pub fn flash_borrow(&self) -> (Option<Address>, Address, U256) {
+        match self.flash_loan {
+            FlashLoanProvider::Aave => (None, self.token_in, self.amount_in),
+            FlashLoanProvider::UniswapV2Flash => (Some(self.buy_pool.address), self.token_out, self.intermediate_amount),
+            FlashLoanProvider::UniswapV3Flash => {
+                (self.flash_pool.as_ref().map(|p| p.address), self.token_in, self.amount_in)
+            }
+        }
+    }
+}
+
+// A DEX pool priced away from the centralized exchanges by more than the fees
+// on both sides: buy (or sell) the base token on the pool, hedge on the
+// exchange. Only reported; the bot doesn't trade on exchanges.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CexDexOpportunity {
+    // As configured, e.g. ETH-USDC
+    pub pair: String,
+    pub pool: DexPool,
+    // Buy the base token on the pool when true, sell it there otherwise
+    pub buy_on_dex: bool,
+    // Quote per base in whole tokens: the pool's marginal price after its fee,
+    // and the exchanges' mid
+    pub dex_price: f64,
+    pub cex_mid: f64,
+    // Gap between the two, in the trade's favour, beyond the exchange fee
+    pub edge_bps: u32,
+    pub block_number: U64,
+}
+
+// Outcome of executing an arbitrage opportunity
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ExecutionReport {
+    pub block_number: U64,
+    pub token_in: Address,
+    pub buy_pool: Address,
+    pub sell_pool: Address,
+    // None when nothing was submitted
+    pub tx_hash: Option<H256>,
+    pub success: bool,
+    // In `token_in` units after gas; negative for a loss
+    pub realized_profit: I256,
+    pub error: Option<String>,
+}
+
+impl ExecutionReport {
+    pub fn new(opportunity: &ArbitrageOpportunity, tx_hash: Option<H256>, realized_profit: I256, error: Option<String>) -> Self {
+        Self {
+            block_number: opportunity.block_number,
+            token_in: opportunity.token_in,
+            buy_pool: opportunity.buy_pool.address,
+            sell_pool: opportunity.sell_pool.address,
+            tx_hash,
+            success: error.is_none(),
+            realized_profit,
+            error,
+        }
+    }
+}
+
+// Position to liquidate through the executor contract
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LiquidationTarget {
+    pub protocol: String,
+    pub user: Address,
+    pub collateral_asset: Address,
+    pub debt_asset: Address,
+    pub debt_amount: U256,
+    pub health_factor: f64,
+    // In debt asset units, after gas
+    pub expected_profit: U256,
+    // Gas estimate in debt asset units, already taken out of `expected_profit`
+    pub gas_cost: U256,
+    pub gas_price: U256,
+    // Rollup L1 data fee in wei, on top of LIQUIDATION_GAS at `gas_price`
+    #[serde(default)]
+    pub l1_fee: U256,
+    // `debt_amount` and `expected_profit` in USD (18 decimals), comparable across assets
+    #[serde(default)]
+    pub debt_value: U256,
+    #[serde(default)]
+    pub profit_value: U256,
+    // Collateral bonus over the debt repaid, in bps
+    #[serde(default)]
+    pub bonus_bps: u32,
+    // The user's whole balance of the collateral seized, in USD (18 decimals)
+    #[serde(default)]
+    pub collateral_value: U256,
+}
+
+// A liquidation that only becomes valid once a pending oracle update lands,
+// so it is bundled right behind that transaction
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OracleBackrun {
+    pub oracle_tx: H256,
+    // Signed oracle transaction as seen in the mempool
+    pub raw_oracle_tx: Bytes,
+    pub target: LiquidationTarget,
+}
+
+// Maker Clipper auction lot to buy, paid for by selling the collateral for DAI
+// inside the take callback
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AuctionTake {
+    pub clipper: Address,
+    pub id: U256,
+    pub gem_join: Address,
+    pub gem: Address,
+    // Collateral to take, in Vat units (18 decimals)
+    pub amount: U256,
+    // Highest auction price accepted, DAI per unit in ray (27 decimals)
+    pub max_price: U256,
+    // Uniswap V3 fee tier the collateral is sold through
+    pub pool_fee: u32,
+    // In DAI, after gas
+    pub expected_profit: U256,
+}
+
+// Liquity troves to liquidate in one batchLiquidateTroves call, riskiest first
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TroveBatch {
+    pub trove_manager: Address,
+    pub troves: Vec<Address>,
+    // Gas compensation and collateral share in USD (18 decimals), after gas
+    pub expected_profit: U256,
+}
@@ -1,12 +1,27 @@
+use futures::{SinkExt, StreamExt};
 use prometheus::{
-    register_counter, register_gauge, register_histogram_vec,
-    Counter, Gauge, HistogramVec, Encoder, TextEncoder,
+    register_counter, register_gauge, register_gauge_vec, register_histogram_vec,
+    Counter, Gauge, GaugeVec, HistogramVec, Encoder, TextEncoder,
+};
+use std::{
+    collections::HashSet,
+    sync::{
+        atomic::{AtomicU32, Ordering},
+        Arc, Mutex,
+    },
 };
-use std::sync::Arc;
 use tokio::sync::RwLock;
-use warp::{Filter, Rejection, Reply};
+use warp::{
+    http::StatusCode,
+    ws::{Message, WebSocket},
+    Filter, Rejection, Reply,
+};
 use chrono::{DateTime, Utc};
 use serde::{Serialize, Deserialize};
+use tracing::{info, warn};
+
+use liquidation_bot::{eth_price::EthPriceReading, feed::OpportunityFeed, math};
+use crate::{breaker::CircuitBreaker, relay_stats::RelayStatsTracker};
 
 #[derive(Clone)]
 pub struct Metrics {
@@ -16,6 +31,9 @@ pub struct Metrics {
     pub liquidations_failed: Counter,
     pub flash_loans_total: Counter,
     pub transactions_total: Counter,
+    pub bundles_submitted: Counter,
+    pub bundles_included: Counter,
+    pub reorgs_total: Counter,
     
     // Gauges
     pub health_factor_min: Gauge,
@@ -23,6 +41,14 @@ pub struct Metrics {
     pub gas_price_gwei: Gauge,
     pub profit_usd_total: Gauge,
     pub success_rate: Gauge,
+    pub searcher_high_priority: Gauge,
+    pub bundle_revert_rate: Gauge,
+    pub relay_bundles_submitted: GaugeVec,
+    pub relay_inclusion_rate: GaugeVec,
+    pub profit_drift_bps: GaugeVec,
+    pub eth_price_usd: Gauge,
+    pub eth_price_sources: Gauge,
+    pub eth_price_age_seconds: Gauge,
     
     // Histograms
     pub liquidation_profit: HistogramVec,
@@ -79,6 +105,21 @@ impl Metrics {
             "Total number of transactions sent"
         ).unwrap();
         
+        let bundles_submitted = register_counter!(
+            "bundles_submitted",
+            "Bundles submitted, counted once however many builders they went to"
+        ).unwrap();
+        
+        let bundles_included = register_counter!(
+            "bundles_included",
+            "Bundles that landed in their target block without reverting"
+        ).unwrap();
+        
+        let reorgs_total = register_counter!(
+            "reorgs_total",
+            "Chain reorganisations seen under published blocks"
+        ).unwrap();
+        
         let health_factor_min = register_gauge!(
             "health_factor_min",
             "Minimum health factor observed"
@@ -104,6 +145,49 @@ impl Metrics {
             "Current success rate percentage"
         ).unwrap();
         
+        let searcher_high_priority = register_gauge!(
+            "searcher_high_priority",
+            "1 if Flashbots currently treats us as a high-priority searcher"
+        ).unwrap();
+        
+        let bundle_revert_rate = register_gauge!(
+            "bundle_revert_rate",
+            "Share of resolved bundles that landed with a reverted transaction"
+        ).unwrap();
+        
+        let relay_bundles_submitted = register_gauge_vec!(
+            "relay_bundles_submitted",
+            "Bundles submitted per relay",
+            &["relay"]
+        ).unwrap();
+        
+        let relay_inclusion_rate = register_gauge_vec!(
+            "relay_inclusion_rate",
+            "Share of resolved bundles sealed by a builder, per relay",
+            &["relay"]
+        ).unwrap();
+        
+        let profit_drift_bps = register_gauge_vec!(
+            "profit_drift_bps",
+            "Mean realized minus estimated profit over recent executions, in bps of the estimate",
+            &["strategy"]
+        ).unwrap();
+        
+        let eth_price_usd = register_gauge!(
+            "eth_price_usd",
+            "Median ETH/USD across the price sources"
+        ).unwrap();
+        
+        let eth_price_sources = register_gauge!(
+            "eth_price_sources",
+            "Fresh sources the last ETH/USD median was taken over"
+        ).unwrap();
+        
+        let eth_price_age_seconds = register_gauge!(
+            "eth_price_age_seconds",
+            "Age of the oldest quote behind the last ETH/USD median"
+        ).unwrap();
+        
         let liquidation_profit = register_histogram_vec!(
             "liquidation_profit",
             "Profit distribution of liquidations",
@@ -128,11 +212,22 @@ impl Metrics {
             liquidations_failed,
             flash_loans_total,
             transactions_total,
+            bundles_submitted,
+            bundles_included,
+            reorgs_total,
             health_factor_min,
             positions_monitored,
             gas_price_gwei,
             profit_usd_total,
             success_rate,
+            searcher_high_priority,
+            bundle_revert_rate,
+            relay_bundles_submitted,
+            relay_inclusion_rate,
+            profit_drift_bps,
+            eth_price_usd,
+            eth_price_sources,
+            eth_price_age_seconds,
             liquidation_profit,
             execution_time,
             gas_used,
@@ -219,6 +314,38 @@ impl Metrics {
         }
     }
     
+    // Age counts from the oldest quote, so a price stuck in the cache keeps ageing
+    pub fn update_eth_price(&self, reading: &EthPriceReading) {
+        self.eth_price_usd.set(math::to_f64(reading.price) / 1e18);
+        self.eth_price_sources.set(reading.sources as f64);
+        self.eth_price_age_seconds
+            .set((reading.oldest_quote + reading.fetched_at.elapsed()).as_secs_f64());
+    }
+    
+    pub fn update_relay_stats(&self, relays: &RelayStatsTracker) {
+        for (relay, stats) in relays.stats() {
+            self.relay_bundles_submitted
+                .with_label_values(&[&relay])
+                .set(stats.submitted as f64);
+            self.relay_inclusion_rate
+                .with_label_values(&[&relay])
+                .set(stats.inclusion_rate());
+        }
+        
+        // Counters only go up, so catch them up with the tracker's totals
+        let bundles = relays.bundles();
+        self.bundles_submitted
+            .inc_by((bundles.submitted as f64 - self.bundles_submitted.get()).max(0.0));
+        self.bundles_included
+            .inc_by((bundles.included as f64 - self.bundles_included.get()).max(0.0));
+        self.bundle_revert_rate.set(bundles.revert_rate());
+        
+        if let Some(reputation) = relays.reputation() {
+            self.searcher_high_priority
+                .set(if reputation.is_high_priority { 1.0 } else { 0.0 });
+        }
+    }
+    
     pub async fn get_daily_stats(&self) -> DailyStats {
         self.daily_stats.read().await.clone()
     }
@@ -245,7 +372,12 @@ impl DailyStats {
 }
 
 // HTTP server for Prometheus metrics
-pub async fn metrics_server(metrics: Arc<Metrics>) {
+pub async fn metrics_server(
+    metrics: Arc<Metrics>,
+    relays: Arc<RelayStatsTracker>,
+    feed: OpportunityFeed,
+    breaker: Arc<CircuitBreaker>,
+) {
     let metrics_route = warp::path!("metrics")
         .and(with_metrics(metrics))
         .and_then(metrics_handler);
@@ -253,14 +385,100 @@ pub async fn metrics_server(metrics: Arc<Metrics>) {
     let health_route = warp::path!("health")
         .map(|| warp::reply::json(&serde_json::json!({"status": "healthy"})));
     
-    let routes = metrics_route.or(health_route);
+    let relays_route = warp::path!("relays")
+        .map(move || {
+            let stats: Vec<_> = relays.stats()
+                .into_iter()
+                .map(|(relay, s)| serde_json::json!({
+                    "relay": relay,
+                    "stats": s,
+                    "inclusion_rate": s.inclusion_rate(),
+                }))
+                .collect();
+            warp::reply::json(&serde_json::json!({
+                "reputation": relays.reputation(),
+                "preferred": relays.preferred_relays(),
+                "bundles": relays.bundles(),
+                "relays": stats,
+            }))
+        });
+    
+    let status_breaker = breaker.clone();
+    let breaker_route = warp::path!("breaker")
+        .and(warp::get())
+        .map(move || warp::reply::json(&serde_json::json!({ "tripped": status_breaker.status() })));
+    
+    // POST /breaker/halt and /breaker/rearm, with `Authorization: Bearer $BREAKER_API_TOKEN`;
+    // refused outright when no token is configured
+    let api_token = std::env::var("BREAKER_API_TOKEN").ok();
+    let breaker_control_route = warp::path!("breaker" / String)
+        .and(warp::post())
+        .and(warp::header::optional::<String>("authorization"))
+        .and_then(move |action: String, auth: Option<String>| {
+            let breaker = breaker.clone();
+            let authorized = matches!(
+                (&api_token, auth.as_deref().and_then(|a| a.strip_prefix("Bearer "))),
+                (Some(token), Some(given)) if token == given
+            );
+            async move {
+                let result = match action.as_str() {
+                    _ if !authorized => Err((StatusCode::UNAUTHORIZED, "unauthorized".to_string())),
+                    "halt" => breaker.halt("via API").await.map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string())),
+                    "rearm" => breaker.rearm().await.map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string())),
+                    _ => Err((StatusCode::NOT_FOUND, format!("unknown action {}", action))),
+                };
+                let (status, body) = match result {
+                    Ok(()) => (StatusCode::OK, serde_json::json!({ "tripped": breaker.status() })),
+                    Err((status, error)) => (status, serde_json::json!({ "error": error })),
+                };
+                Ok::<_, Rejection>(warp::reply::with_status(warp::reply::json(&body), status))
+            }
+        });
+    
+    // Actionable arbitrage as it's found, as server-sent events or websocket text frames
+    let sse_feed = feed.clone();
+    let stream_route = warp::path!("opportunities" / "stream")
+        .map(move || {
+            let events = sse_feed
+                .subscribe()
+                .map(|o| warp::sse::Event::default().event("opportunity").json_data(o));
+            warp::sse::reply(warp::sse::keep_alive().stream(events))
+        });
+    
+    let ws_route = warp::path!("opportunities" / "ws")
+        .and(warp::ws())
+        .map(move |ws: warp::ws::Ws| {
+            let feed = feed.clone();
+            ws.on_upgrade(move |socket| stream_opportunities(socket, feed))
+        });
     
-    println!("📊 Metrics server listening on :9091");
+    let routes = metrics_route
+        .or(health_route)
+        .or(relays_route)
+        .or(breaker_route)
+        .or(breaker_control_route)
+        .or(stream_route)
+        .or(ws_route);
+    
+    info!(port = 9091, "📊 Metrics server listening");
     warp::serve(routes)
         .run(([0, 0, 0, 0], 9091))
         .await;
 }
 
+async fn stream_opportunities(socket: WebSocket, feed: OpportunityFeed) {
+    let (mut tx, _) = socket.split();
+    let mut opportunities = Box::pin(feed.subscribe());
+    while let Some(opportunity) = opportunities.next().await {
+        let Ok(json) = serde_json::to_string(&opportunity) else {
+            continue;
+        };
+        if tx.send(Message::text(json)).await.is_err() {
+            break;
+        }
+    }
+}
+
 fn with_metrics(
     metrics: Arc<Metrics>
 ) -> impl Filter<Extract = (Arc<Metrics>,), Error = std::convert::Infallible> + Clone {
@@ -287,6 +505,39 @@ pub struct AlertManager {
     discord_webhook: Option<String>,
     email_config: Option<EmailConfig>,
     thresholds: AlertThresholds,
+    pager: Option<Pager>,
+    // Incidents paged and not yet resolved, so a condition pages once
+    open_incidents: Mutex<HashSet<Incident>>,
+    failed_executions: AtomicU32,
+}
+
+// On-call paging for failures that need a human now
+#[derive(Clone)]
+enum Pager {
+    // Events API v2 integration key
+    PagerDuty { routing_key: String },
+    Opsgenie { api_key: String },
+}
+
+// Conditions that page. Each maps to a fixed dedup key, so repeated triggers
+// for one ongoing incident update it instead of opening new ones.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Incident {
+    ProvidersDown,
+    GasReserve,
+    ExecutionFailures,
+    CircuitBreaker,
+}
+
+impl Incident {
+    fn dedup_key(&self) -> &'static str {
+        match self {
+            Incident::ProvidersDown => "liquidation-bot/providers-down",
+            Incident::GasReserve => "liquidation-bot/gas-reserve",
+            Incident::ExecutionFailures => "liquidation-bot/execution-failures",
+            Incident::CircuitBreaker => "liquidation-bot/circuit-breaker",
+        }
+    }
 }
 
 #[derive(Clone)]
@@ -320,6 +571,15 @@ impl AlertManager {
         
         let discord_webhook = std::env::var("DISCORD_WEBHOOK").ok();
         
+        let pager = std::env::var("PAGERDUTY_ROUTING_KEY")
+            .map(|routing_key| Pager::PagerDuty { routing_key })
+            .or_else(|_| std::env::var("OPSGENIE_API_KEY").map(|api_key| Pager::Opsgenie { api_key }))
+            .ok();
+        let max_failed_consecutive = std::env::var("PAGE_AFTER_FAILED_EXECUTIONS")
+            .ok()
+            .and_then(|v| v.parse().ok())
+            .unwrap_or(5);
+        
         Self {
             telegram_bot,
             discord_webhook,
@@ -327,9 +587,94 @@ impl AlertManager {
             thresholds: AlertThresholds {
                 min_profit_usd: 30.0,
                 max_gas_gwei: 200.0,
-                max_failed_consecutive: 5,
+                max_failed_consecutive,
                 min_success_rate: 80.0,
             },
+            pager,
+            open_incidents: Mutex::new(HashSet::new()),
+            failed_executions: AtomicU32::new(0),
+        }
+    }
+    
+    // Alert and page on-call once per incident; later calls while it's open do nothing
+    pub async fn page(&self, incident: Incident, message: &str) {
+        if !self.open_incidents.lock().unwrap().insert(incident) {
+            return;
+        }
+        self.send_alert(AlertLevel::Critical, message).await;
+        if let Some(pager) = &self.pager {
+            self.send_page(pager, incident, Some(message)).await;
+        }
+    }
+    
+    // Close the incident if one is open
+    pub async fn resolve(&self, incident: Incident) {
+        if !self.open_incidents.lock().unwrap().remove(&incident) {
+            return;
+        }
+        self.send_alert(AlertLevel::Info, &format!("Resolved: {}", incident.dedup_key())).await;
+        if let Some(pager) = &self.pager {
+            self.send_page(pager, incident, None).await;
+        }
+    }
+    
+    // Pages after `max_failed_consecutive` failures in a row; a success resolves
+    pub async fn record_execution(&self, success: bool) {
+        if success {
+            self.failed_executions.store(0, Ordering::Relaxed);
+            self.resolve(Incident::ExecutionFailures).await;
+            return;
+        }
+        let failed = self.failed_executions.fetch_add(1, Ordering::Relaxed) + 1;
+        if failed >= self.thresholds.max_failed_consecutive {
+            self.page(Incident::ExecutionFailures, &format!("{} consecutive failed executions", failed))
+                .await;
+        }
+    }
+    
+    // Trigger with a message, resolve without
+    async fn send_page(&self, pager: &Pager, incident: Incident, message: Option<&str>) {
+        let request = match pager {
+            Pager::PagerDuty { routing_key } => {
+                let mut event = serde_json::json!({
+                    "routing_key": routing_key,
+                    "dedup_key": incident.dedup_key(),
+                    "event_action": if message.is_some() { "trigger" } else { "resolve" },
+                });
+                if let Some(message) = message {
+                    event["payload"] = serde_json::json!({
+                        "summary": message,
+                        "source": "liquidation-bot",
+                        "severity": "critical",
+                    });
+                }
+                reqwest::Client::new()
+                    .post("https://events.pagerduty.com/v2/enqueue")
+                    .json(&event)
+            }
+            Pager::Opsgenie { api_key } => {
+                let client = reqwest::Client::new();
+                let request = match message {
+                    Some(message) => client.post("https://api.opsgenie.com/v2/alerts").json(&serde_json::json!({
+                        "message": message,
+                        "alias": incident.dedup_key(),
+                        "priority": "P1",
+                        "source": "liquidation-bot",
+                    })),
+                    None => client
+                        .post(format!(
+                            "https://api.opsgenie.com/v2/alerts/{}/close?identifierType=alias",
+                            incident.dedup_key()
+                        ))
+                        .json(&serde_json::json!({ "source": "liquidation-bot" })),
+                };
+                request.header("Authorization", format!("GenieKey {}", api_key))
+            }
+        };
+        
+        match request.send().await.and_then(|r| r.error_for_status()) {
+            Ok(_) => {}
+            Err(e) => warn!(incident = incident.dedup_key(), error = %e, "⚠️ Paging failed"),
         }
     }
     
@@ -352,7 +697,7 @@ impl AlertManager {
         }
         
         // Log to console
-        println!("{}", formatted);
+        warn!(%level, "{}", message);
     }
     
     async fn send_telegram(&self, bot: &TelegramBot, message: &str) {
@@ -417,7 +762,7 @@ impl AlertManager {
 }
 
 #[derive(Debug, Clone)]
-enum AlertLevel {
+pub enum AlertLevel {
     Info,
     Warning,
     Critical,
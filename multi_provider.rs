@@ -0,0 +1,250 @@
+use async_trait::async_trait;
+use chrono::{Datelike, Utc};
+use ethers::providers::{Http, HttpClientError, JsonRpcClient, JsonRpcError, ProviderError, RpcError};
+use serde::{de::DeserializeOwned, Serialize};
+use std::{
+    fmt::Debug,
+    sync::{
+        atomic::{AtomicU32, AtomicU64, Ordering},
+        Mutex,
+    },
+    time::{Duration, Instant},
+};
+use thiserror::Error;
+use url::Url;
+use tracing::warn;
+
+use crate::errors::BotError;
+
+// Rounds over all endpoints before a throttled request gives up
+const MAX_ROUNDS: usize = 20;
+
+// Request rate and monthly compute-unit budget for one endpoint
+#[derive(Debug, Clone)]
+pub struct ProviderLimits {
+    pub requests_per_sec: f64,
+    // None for endpoints without a monthly quota
+    pub monthly_compute_units: Option<u64>,
+}
+
+impl Default for ProviderLimits {
+    fn default() -> Self {
+        Self {
+            requests_per_sec: 25.0,
+            monthly_compute_units: None,
+        }
+    }
+}
+
+#[derive(Debug, Error)]
+pub enum MultiProviderError {
+    #[error(transparent)]
+    Http(#[from] HttpClientError),
+
+    #[error("every provider is out of quota")]
+    Exhausted,
+}
+
+impl RpcError for MultiProviderError {
+    fn as_error_response(&self) -> Option<&JsonRpcError> {
+        match self {
+            MultiProviderError::Http(e) => e.as_error_response(),
+            MultiProviderError::Exhausted => None,
+        }
+    }
+
+    fn as_serde_error(&self) -> Option<&serde_json::Error> {
+        match self {
+            MultiProviderError::Http(e) => e.as_serde_error(),
+            MultiProviderError::Exhausted => None,
+        }
+    }
+}
+
+impl From<MultiProviderError> for ProviderError {
+    fn from(e: MultiProviderError) -> Self {
+        ProviderError::JsonRpcClientError(Box::new(e))
+    }
+}
+
+#[derive(Debug)]
+struct TokenBucket {
+    rate: f64,
+    tokens: f64,
+    last: Instant,
+}
+
+impl TokenBucket {
+    // Burst of up to one second's worth of requests
+    fn new(rate: f64) -> Self {
+        Self { rate, tokens: rate, last: Instant::now() }
+    }
+
+    fn refill(&mut self) {
+        let now = Instant::now();
+        self.tokens = (self.tokens + now.duration_since(self.last).as_secs_f64() * self.rate).min(self.rate);
+        self.last = now;
+    }
+
+    fn try_take(&mut self) -> bool {
+        self.refill();
+        if self.tokens >= 1.0 {
+            self.tokens -= 1.0;
+            true
+        } else {
+            false
+        }
+    }
+
+    // Time until the next token is available
+    fn wait(&mut self) -> Duration {
+        self.refill();
+        Duration::from_secs_f64(((1.0 - self.tokens) / self.rate).max(0.0))
+    }
+}
+
+#[derive(Debug)]
+struct Endpoint {
+    url: String,
+    client: Http,
+    limits: ProviderLimits,
+    bucket: Mutex<TokenBucket>,
+    // Compute units spent in `month`
+    spent: AtomicU64,
+    month: AtomicU32,
+}
+
+impl Endpoint {
+    fn has_budget(&self, cost: u64) -> bool {
+        let month = Utc::now().month();
+        if self.month.swap(month, Ordering::Relaxed) != month {
+            self.spent.store(0, Ordering::Relaxed);
+        }
+        match self.limits.monthly_compute_units {
+            Some(budget) => self.spent.load(Ordering::Relaxed) + cost <= budget,
+            None => true,
+        }
+    }
+}
+
+// JSON-RPC client over several HTTP endpoints in priority order. Each endpoint
+// has a token bucket and an optional monthly compute-unit budget; requests
+// spill over to the next endpoint when one is throttled, out of budget, or
+// answers with a rate-limit error.
+#[derive(Debug)]
+pub struct MultiProvider {
+    endpoints: Vec<Endpoint>,
+}
+
+impl MultiProvider {
+    pub fn new(endpoints: Vec<(String, ProviderLimits)>) -> Result<Self, BotError> {
+        let endpoints = endpoints
+            .into_iter()
+            .map(|(url, limits)| {
+                let parsed = Url::parse(&url).map_err(|e| BotError::Config(format!("invalid rpc url {}: {}", url, e)))?;
+                Ok(Endpoint {
+                    client: Http::new(parsed),
+                    bucket: Mutex::new(TokenBucket::new(limits.requests_per_sec.max(0.1))),
+                    spent: AtomicU64::new(0),
+                    month: AtomicU32::new(Utc::now().month()),
+                    url,
+                    limits,
+                })
+            })
+            .collect::<Result<Vec<_>, BotError>>()?;
+
+        if endpoints.is_empty() {
+            return Err(BotError::Config("no rpc endpoints configured".to_string()));
+        }
+        Ok(Self { endpoints })
+    }
+
+    // Compute units spent this month per endpoint
+    pub fn usage(&self) -> Vec<(String, u64)> {
+        self.endpoints
+            .iter()
+            .map(|e| (e.url.clone(), e.spent.load(Ordering::Relaxed)))
+            .collect()
+    }
+}
+
+#[async_trait]
+impl JsonRpcClient for MultiProvider {
+    type Error = MultiProviderError;
+
+    async fn request<T, R>(&self, method: &str, params: T) -> Result<R, Self::Error>
+    where
+        T: Debug + Serialize + Send + Sync,
+        R: DeserializeOwned + Send,
+    {
+        let cost = compute_units(method);
+
+        for _ in 0..MAX_ROUNDS {
+            let mut shortest_wait = None;
+            let mut last_error = None;
+
+            for endpoint in &self.endpoints {
+                if !endpoint.has_budget(cost) {
+                    continue;
+                }
+                let acquired = {
+                    let mut bucket = endpoint.bucket.lock().unwrap();
+                    let acquired = bucket.try_take();
+                    if !acquired {
+                        let wait = bucket.wait();
+                        shortest_wait = Some(shortest_wait.map_or(wait, |w: Duration| w.min(wait)));
+                    }
+                    acquired
+                };
+                if !acquired {
+                    continue;
+                }
+
+                endpoint.spent.fetch_add(cost, Ordering::Relaxed);
+                match endpoint.client.request(method, &params).await {
+                    Ok(response) => return Ok(response),
+                    Err(e) if is_rate_limited(&e) => {
+                        warn!(endpoint = %endpoint.url, "⚠️ Rate limited, spilling over");
+                        // Back off this endpoint until its bucket refills
+                        endpoint.bucket.lock().unwrap().tokens = 0.0;
+                        last_error = Some(e);
+                    }
+                    Err(e) => return Err(e.into()),
+                }
+            }
+
+            // Every endpoint with budget left is throttled; wait for the first to refill
+            match (shortest_wait, last_error) {
+                (None, None) => return Err(MultiProviderError::Exhausted),
+                (None, Some(e)) => return Err(e.into()),
+                (Some(wait), _) => tokio::time::sleep(wait.max(Duration::from_millis(10))).await,
+            }
+        }
+
+        Err(MultiProviderError::Exhausted)
+    }
+}
+
+fn is_rate_limited(e: &HttpClientError) -> bool {
+    if let HttpClientError::ReqwestError(e) = e {
+        return e.status().map(|s| s.as_u16() == 429).unwrap_or(false);
+    }
+    match e.as_error_response() {
+        Some(response) => {
+            response.code == 429 || response.code == -32005 || response.message.to_lowercase().contains("rate limit")
+        }
+        None => false,
+    }
+}
+
+// Approximate compute-unit weights, following Alchemy's published table
+fn compute_units(method: &str) -> u64 {
+    match method {
+        "eth_blockNumber" | "eth_chainId" | "net_version" => 10,
+        "eth_getBalance" | "eth_getTransactionCount" | "eth_getCode" | "eth_getStorageAt" => 19,
+        "eth_call" | "eth_estimateGas" => 26,
+        "eth_getLogs" => 75,
+        "eth_sendRawTransaction" => 250,
+        _ => 20,
+    }
+}
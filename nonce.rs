@@ -0,0 +1,180 @@
+use ethers::{
+    providers::{Middleware, PendingTransaction},
+    types::{
+        transaction::eip2718::TypedTransaction, Address, BlockNumber, Eip1559TransactionRequest, H256, U256, U64,
+    },
+};
+use std::{collections::BTreeMap, sync::Arc};
+use tokio::sync::Mutex;
+use tracing::{info, warn};
+
+use crate::errors::{ExecutionError, ProviderError, Result};
+
+// Fee increase per replacement; nodes reject replacements under +10%
+const BUMP_PCT: u64 = 125;
+// Replacements before a stuck transaction is cancelled instead
+const MAX_BUMPS: u32 = 3;
+const CANCEL_GAS: u64 = 21_000;
+
+#[derive(Debug, Clone)]
+struct PendingTx {
+    hash: H256,
+    tx: TypedTransaction,
+    sent_at: U64,
+    bumps: u32,
+}
+
+// Hands out nonces for everything the wallet sends to the public mempool and
+// keeps what's in flight. A transaction still unmined after `stuck_after`
+// blocks is rebroadcast with higher fees, then replaced by a zero-value
+// self-transfer, so one underpriced transaction can't hold up every nonce
+// behind it. Bundles take their nonce from the chain as before: they only
+// land whole, so they never leave a gap.
+pub struct NonceManager<M: Middleware> {
+    client: Arc<M>,
+    address: Address,
+    stuck_after: u64,
+    // Sent and not yet mined, by nonce; the lock also orders sends
+    pending: Mutex<BTreeMap<U256, PendingTx>>,
+}
+
+impl<M: Middleware + 'static> NonceManager<M> {
+    pub fn new(client: Arc<M>, address: Address, stuck_after: u64) -> Self {
+        Self {
+            client,
+            address,
+            stuck_after,
+            pending: Mutex::new(BTreeMap::new()),
+        }
+    }
+
+    // Send `tx` with the next free nonce and track it until it's mined
+    pub async fn send(&self, mut tx: TypedTransaction) -> Result<PendingTransaction<'_, M::Provider>> {
+        let mut pending = self.pending.lock().await;
+        let on_chain = self
+            .client
+            .get_transaction_count(self.address, Some(BlockNumber::Pending.into()))
+            .await
+            .map_err(|e| ProviderError::Rpc(e.to_string()))?;
+        let nonce = match pending.keys().next_back() {
+            Some(last) => on_chain.max(*last + 1),
+            None => on_chain,
+        };
+        tx.set_nonce(nonce);
+        tx.set_from(self.address);
+
+        let block = self.block_number().await?;
+        let sent = self
+            .client
+            .send_transaction(tx.clone(), None)
+            .await
+            .map_err(|e| ExecutionError::Reverted(e.to_string()))?;
+        pending.insert(
+            nonce,
+            PendingTx {
+                hash: *sent,
+                tx,
+                sent_at: block,
+                bumps: 0,
+            },
+        );
+        Ok(sent)
+    }
+
+    // Forget mined transactions and replace the stuck ones. Returns how many were replaced.
+    pub async fn resolve_stuck(&self) -> Result<usize> {
+        let mut pending = self.pending.lock().await;
+        if pending.is_empty() {
+            return Ok(0);
+        }
+        let mined = self
+            .client
+            .get_transaction_count(self.address, Some(BlockNumber::Latest.into()))
+            .await
+            .map_err(|e| ProviderError::Rpc(e.to_string()))?;
+        pending.retain(|nonce, _| *nonce >= mined);
+
+        let block = self.block_number().await?;
+        let mut replaced = 0;
+        for (nonce, entry) in pending.iter_mut() {
+            if block < entry.sent_at + self.stuck_after {
+                continue;
+            }
+            let replacement = if entry.bumps < MAX_BUMPS {
+                bump_fees(&entry.tx)
+            } else {
+                cancellation(&entry.tx, self.address)
+            };
+            match self.client.send_transaction(replacement.clone(), None).await {
+                Ok(sent) => {
+                    if entry.bumps < MAX_BUMPS {
+                        info!(nonce = %nonce, old = ?entry.hash, new = ?*sent, "⛽ Rebroadcast stuck transaction with higher fees");
+                    } else {
+                        warn!(nonce = %nonce, old = ?entry.hash, new = ?*sent, "🚫 Cancelled stuck transaction");
+                    }
+                    *entry = PendingTx {
+                        hash: *sent,
+                        tx: replacement,
+                        sent_at: block,
+                        bumps: entry.bumps + 1,
+                    };
+                    replaced += 1;
+                }
+                Err(e) => warn!(nonce = %nonce, error = %e, "⚠️ Stuck transaction replacement failed"),
+            }
+        }
+        Ok(replaced)
+    }
+
+    async fn block_number(&self) -> Result<U64> {
+        self.client
+            .get_block_number()
+            .await
+            .map_err(|e| ProviderError::Rpc(e.to_string()).into())
+    }
+}
+
+fn bump(fee: U256) -> U256 {
+    fee * BUMP_PCT / 100 + 1
+}
+
+fn bump_fees(tx: &TypedTransaction) -> TypedTransaction {
+    let mut tx = tx.clone();
+    match &mut tx {
+        TypedTransaction::Eip1559(inner) => {
+            inner.max_fee_per_gas = inner.max_fee_per_gas.map(bump);
+            inner.max_priority_fee_per_gas = inner.max_priority_fee_per_gas.map(bump);
+        }
+        TypedTransaction::Legacy(inner) => inner.gas_price = inner.gas_price.map(bump),
+        TypedTransaction::Eip2930(inner) => inner.tx.gas_price = inner.tx.gas_price.map(bump),
+    }
+    tx
+}
+
+// Zero-value transfer to ourselves at the same nonce, outbidding the stuck transaction
+fn cancellation(tx: &TypedTransaction, address: Address) -> TypedTransaction {
+    let (max_fee, priority_fee) = match tx {
+        TypedTransaction::Eip1559(inner) => (
+            inner.max_fee_per_gas.unwrap_or_default(),
+            inner.max_priority_fee_per_gas.unwrap_or_default(),
+        ),
+        _ => {
+            let gas_price = tx.gas_price().unwrap_or_default();
+            (gas_price, gas_price)
+        }
+    };
+    let mut request = Eip1559TransactionRequest::new()
+        .from(address)
+        .to(address)
+        .value(0)
+        .gas(CANCEL_GAS)
+        .max_fee_per_gas(bump(max_fee))
+        .max_priority_fee_per_gas(bump(priority_fee));
+    if let Some(nonce) = tx.nonce() {
+        request = request.nonce(*nonce);
+    }
+    if let Some(chain_id) = tx.chain_id() {
+        request = request.chain_id(chain_id);
+    }
+    request.into()
+}
@@ -0,0 +1,74 @@
+use sqlx::{
+    postgres::{PgPool, PgPoolOptions},
+    Postgres, QueryBuilder,
+};
+
+use crate::{errors::Result, models::ArbitrageOpportunity};
+
+// Every arbitrage opportunity the scan finds, profitable after gas or not, so
+// spreads can be analysed per pair and venue over time.
+pub struct OpportunitySink {
+    db: PgPool,
+}
+
+impl OpportunitySink {
+    pub async fn connect(url: &str) -> Result<Self> {
+        let db = PgPoolOptions::new().max_connections(2).connect(url).await?;
+
+        // Amounts are raw token units; NUMERIC holds a full uint256
+        sqlx::query(
+            "CREATE TABLE IF NOT EXISTS arbitrage_opportunities (
+                id BIGSERIAL PRIMARY KEY,
+                recorded_at TIMESTAMPTZ NOT NULL DEFAULT now(),
+                block_number BIGINT NOT NULL,
+                token_in TEXT NOT NULL,
+                token_out TEXT NOT NULL,
+                buy_dex TEXT NOT NULL,
+                buy_pool TEXT NOT NULL,
+                sell_dex TEXT NOT NULL,
+                sell_pool TEXT NOT NULL,
+                spread_bps INTEGER NOT NULL,
+                amount_in NUMERIC(78, 0) NOT NULL,
+                amount_out NUMERIC(78, 0) NOT NULL,
+                gross_profit NUMERIC(78, 0) NOT NULL,
+                gas_cost NUMERIC(78, 0) NOT NULL
+            )",
+        )
+        .execute(&db)
+        .await?;
+        sqlx::query("CREATE INDEX IF NOT EXISTS arbitrage_opportunities_block ON arbitrage_opportunities (block_number)")
+            .execute(&db)
+            .await?;
+
+        Ok(Self { db })
+    }
+
+    // One multi-row insert per block
+    pub async fn record(&self, opportunities: &[ArbitrageOpportunity]) -> Result<()> {
+        if opportunities.is_empty() {
+            return Ok(());
+        }
+
+        let mut query: QueryBuilder<Postgres> = QueryBuilder::new(
+            "INSERT INTO arbitrage_opportunities (block_number, token_in, token_out, buy_dex, buy_pool, \
+             sell_dex, sell_pool, spread_bps, amount_in, amount_out, gross_profit, gas_cost) ",
+        );
+        query.push_values(opportunities, |mut row, o| {
+            row.push_bind(o.block_number.as_u64() as i64)
+                .push_bind(format!("{:?}", o.token_in))
+                .push_bind(format!("{:?}", o.token_out))
+                .push_bind(o.buy_pool.dex.to_string())
+                .push_bind(format!("{:?}", o.buy_pool.address))
+                .push_bind(o.sell_pool.dex.to_string())
+                .push_bind(format!("{:?}", o.sell_pool.address))
+                .push_bind(o.spread_bps as i32);
+            // sqlx has no uint256 type; bind decimal text and cast
+            for amount in [o.amount_in, o.amount_out, o.gross_profit, o.gas_cost] {
+                row.push_bind(amount.to_string()).push_unseparated("::numeric");
+            }
+        });
+        query.build().execute(&self.db).await?;
+
+        Ok(())
+    }
+}
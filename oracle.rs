@@ -0,0 +1,234 @@
+use async_trait::async_trait;
+use ethers::{
+    prelude::*,
+    providers::{Provider, Ws},
+    types::{Address, Filter, H256, U256},
+};
+use serde_json::Value;
+use std::{collections::HashMap, sync::Arc};
+
+use crate::errors::{BotError, ProviderError, Result};
+
+abigen!(
+    PythOracle,
+    r#"[
+        function getPriceUnsafe(bytes32 id) external view returns ((int64,uint64,int32,uint256))
+    ]"#
+);
+
+pub const PYTH_MAINNET: &str = "0x4305FB66699C3B2702D4d05CF36551390A4c69C6";
+pub const HERMES_URL: &str = "https://hermes.pyth.network";
+pub const REDSTONE_GATEWAY: &str = "https://oracle-gateway-1.a.redstone.finance";
+pub const REDSTONE_SERVICE: &str = "redstone-primary-prod";
+// Decimals of every price a source returns, as in Chainlink USD feeds
+const PRICE_DECIMALS: i32 = 8;
+
+// Where a protocol's prices come from when it doesn't read Chainlink push
+// feeds. Prices are USD per whole token with PRICE_DECIMALS decimals, the unit
+// of the Aave oracle, so health factors come out the same either way.
+#[async_trait]
+pub trait PriceSource: Send + Sync {
+    fn name(&self) -> &str;
+
+    async fn prices(&self, assets: &[Address]) -> Result<Vec<U256>>;
+
+    // Logs emitted when a price lands on chain. Sources without one can move
+    // any block and are polled instead.
+    fn update_filter(&self) -> Option<Filter> {
+        None
+    }
+}
+
+// Pyth: prices are pulled from Hermes and posted on chain by whoever needs
+// them. The on-chain price is what a liquidation is checked against; Hermes
+// has the newer price the next post will write.
+pub struct PythSource {
+    contract: PythOracle<Provider<Ws>>,
+    // asset -> Pyth price feed id
+    feeds: HashMap<Address, H256>,
+    hermes: Option<(reqwest::Client, String)>,
+}
+
+impl PythSource {
+    pub fn new(provider: Arc<Provider<Ws>>, contract: Address) -> Self {
+        Self {
+            contract: PythOracle::new(contract, provider),
+            feeds: HashMap::new(),
+            hermes: None,
+        }
+    }
+
+    pub fn with_feed(mut self, asset: Address, price_id: H256) -> Self {
+        self.feeds.insert(asset, price_id);
+        self
+    }
+
+    // Read the latest off-chain prices instead of the posted ones
+    pub fn with_hermes(mut self, url: &str) -> Self {
+        self.hermes = Some((reqwest::Client::new(), url.trim_end_matches('/').to_string()));
+        self
+    }
+
+    fn feed(&self, asset: &Address) -> Result<H256> {
+        self.feeds
+            .get(asset)
+            .copied()
+            .ok_or_else(|| BotError::Config(format!("no Pyth price id for {:?}", asset)))
+    }
+
+    async fn hermes_prices(&self, http: &reqwest::Client, url: &str, ids: &[H256]) -> Result<Vec<U256>> {
+        let query: Vec<(&str, String)> = ids.iter().map(|id| ("ids[]", format!("{:?}", id))).collect();
+        let body: Value = http
+            .get(format!("{}/v2/updates/price/latest", url))
+            .query(&query)
+            .query(&[("parsed", "true")])
+            .send()
+            .await
+            .and_then(|r| r.error_for_status())
+            .map_err(|e| ProviderError::Rpc(format!("hermes: {}", e)))?
+            .json()
+            .await
+            .map_err(|e| ProviderError::Rpc(format!("hermes: {}", e)))?;
+
+        // Hermes returns ids without the 0x prefix, in no guaranteed order
+        let parsed: HashMap<String, &Value> = body["parsed"]
+            .as_array()
+            .map(|feeds| {
+                feeds
+                    .iter()
+                    .filter_map(|f| Some((f["id"].as_str()?.to_lowercase(), &f["price"])))
+                    .collect()
+            })
+            .unwrap_or_default();
+        ids.iter()
+            .map(|id| {
+                let price = parsed
+                    .get(&hex::encode(id.as_bytes()))
+                    .ok_or_else(|| ProviderError::Rpc(format!("hermes: no price for {:?}", id)))?;
+                let value: i64 = price["price"].as_str().and_then(|p| p.parse().ok()).unwrap_or_default();
+                let expo = price["expo"].as_i64().unwrap_or_default() as i32;
+                Ok(scale(value, expo))
+            })
+            .collect()
+    }
+}
+
+#[async_trait]
+impl PriceSource for PythSource {
+    fn name(&self) -> &str {
+        "pyth"
+    }
+
+    async fn prices(&self, assets: &[Address]) -> Result<Vec<U256>> {
+        let ids = assets.iter().map(|a| self.feed(a)).collect::<Result<Vec<H256>>>()?;
+        if let Some((http, url)) = &self.hermes {
+            return self.hermes_prices(http, url, &ids).await;
+        }
+        let mut prices = Vec::with_capacity(ids.len());
+        for id in ids {
+            let (price, _, expo, _) = self.contract.get_price_unsafe(id.0).call().await?;
+            prices.push(scale(price, expo));
+        }
+        Ok(prices)
+    }
+
+    fn update_filter(&self) -> Option<Filter> {
+        Some(
+            Filter::new()
+                .address(self.contract.address())
+                .event("PriceFeedUpdate(bytes32,uint64,int64,uint64)"),
+        )
+    }
+}
+
+// RedStone: signed price packages are fetched from a gateway and appended to
+// the calldata of the transaction that needs them, so nothing is stored or
+// emitted on chain. The gateway's median is the price the protocol will see.
+// (RedStone push feeds are Chainlink-compatible and need no adapter.)
+pub struct RedstoneSource {
+    http: reqwest::Client,
+    gateway: String,
+    service: String,
+    // asset -> RedStone data feed id, e.g. "ETH"
+    feeds: HashMap<Address, String>,
+}
+
+impl RedstoneSource {
+    pub fn new(gateway: &str, service: &str) -> Self {
+        Self {
+            http: reqwest::Client::new(),
+            gateway: gateway.trim_end_matches('/').to_string(),
+            service: service.to_string(),
+            feeds: HashMap::new(),
+        }
+    }
+
+    pub fn mainnet() -> Self {
+        Self::new(REDSTONE_GATEWAY, REDSTONE_SERVICE)
+    }
+
+    pub fn with_feed(mut self, asset: Address, feed_id: &str) -> Self {
+        self.feeds.insert(asset, feed_id.to_string());
+        self
+    }
+}
+
+#[async_trait]
+impl PriceSource for RedstoneSource {
+    fn name(&self) -> &str {
+        "redstone"
+    }
+
+    async fn prices(&self, assets: &[Address]) -> Result<Vec<U256>> {
+        let body: Value = self
+            .http
+            .get(format!("{}/data-packages/latest/{}", self.gateway, self.service))
+            .send()
+            .await
+            .and_then(|r| r.error_for_status())
+            .map_err(|e| ProviderError::Rpc(format!("redstone: {}", e)))?
+            .json()
+            .await
+            .map_err(|e| ProviderError::Rpc(format!("redstone: {}", e)))?;
+
+        assets
+            .iter()
+            .map(|asset| {
+                let feed = self
+                    .feeds
+                    .get(asset)
+                    .ok_or_else(|| BotError::Config(format!("no RedStone feed for {:?}", asset)))?;
+                // One package per signer; contracts take the median of them
+                let mut values: Vec<f64> = body[feed.as_str()]
+                    .as_array()
+                    .map(|packages| {
+                        packages
+                            .iter()
+                            .filter_map(|p| p["dataPoints"][0]["value"].as_f64())
+                            .collect()
+                    })
+                    .unwrap_or_default();
+                if values.is_empty() {
+                    return Err(ProviderError::Rpc(format!("redstone: no price for {}", feed)).into());
+                }
+                values.sort_by(|a, b| a.partial_cmp(b).unwrap_or(std::cmp::Ordering::Equal));
+                let median = values[values.len() / 2];
+                Ok(U256::from((median * 10f64.powi(PRICE_DECIMALS)).round() as u128))
+            })
+            .collect()
+    }
+}
+
+// `price * 10^expo` with PRICE_DECIMALS decimals; negative prices read as zero
+fn scale(price: i64, expo: i32) -> U256 {
+    if price <= 0 {
+        return U256::zero();
+    }
+    let price = U256::from(price as u64);
+    let shift = PRICE_DECIMALS + expo;
+    if shift >= 0 {
+        price * U256::exp10(shift as usize)
+    } else {
+        price / U256::exp10(shift.unsigned_abs() as usize)
+    }
+}
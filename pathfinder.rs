@@ -0,0 +1,228 @@
+use ethers::types::Address;
+use std::collections::HashMap;
+
+use crate::{
+    dex::{maverick::active_price_x128, solidly::stable_rate_x128, trader_joe::bin_price_x128, TokenFilter},
+    math::{decimals_x128, less_fee, mul_div, q128, ratio_x128, x128_to_f64},
+    models::{DexPool, PoolState},
+};
+
+// One leg of a cycle: swap `token_in` for `token_out` on `pool`
+#[derive(Debug, Clone)]
+pub struct Hop {
+    pub pool: DexPool,
+    pub token_in: Address,
+    pub token_out: Address,
+}
+
+// A closed loop of swaps starting and ending in `start`
+#[derive(Debug, Clone)]
+pub struct ArbitrageCycle {
+    pub start: Address,
+    pub hops: Vec<Hop>,
+    // Product of marginal rates after fees; > 1.0 means the loop is profitable at the margin
+    pub rate: f64,
+}
+
+struct Edge {
+    from: usize,
+    to: usize,
+    // -ln(marginal rate after fees)
+    weight: f64,
+    pool: usize,
+}
+
+// Finds arbitrage loops of any length up to `max_hops` by looking for negative
+// cycles in the token graph, where each pool contributes an edge per direction
+// weighted -ln(rate). The pairwise scan only sees two-pool loops.
+pub struct Pathfinder {
+    max_hops: usize,
+    filter: TokenFilter,
+}
+
+impl Pathfinder {
+    pub fn new(max_hops: usize) -> Self {
+        Self {
+            max_hops: max_hops.max(2),
+            filter: TokenFilter::default(),
+        }
+    }
+
+    // Never route through tokens the filter rejects
+    pub fn with_token_filter(mut self, filter: TokenFilter) -> Self {
+        self.filter = filter;
+        self
+    }
+
+    // Hop-bounded Bellman-Ford from each start token. Layer k holds the best
+    // path of exactly k edges, so cycles through `start` are read off directly.
+    pub fn find_cycles(&self, pools: &[DexPool], start_tokens: &[Address]) -> Vec<ArbitrageCycle> {
+        let mut index: HashMap<Address, usize> = HashMap::new();
+        let mut tokens: Vec<Address> = Vec::new();
+        let mut edges = Vec::new();
+
+        for (pool_index, pool) in pools.iter().enumerate() {
+            if !self.filter.permits_pool(pool) {
+                continue;
+            }
+            for (token_in, token_out) in [
+                (pool.token0.address, pool.token1.address),
+                (pool.token1.address, pool.token0.address),
+            ] {
+                let Some(rate) = spot_rate(pool, token_in) else { continue };
+                let from = *index.entry(token_in).or_insert_with(|| {
+                    tokens.push(token_in);
+                    tokens.len() - 1
+                });
+                let to = *index.entry(token_out).or_insert_with(|| {
+                    tokens.push(token_out);
+                    tokens.len() - 1
+                });
+                edges.push(Edge { from, to, weight: -rate.ln(), pool: pool_index });
+            }
+        }
+
+        let mut cycles = Vec::new();
+        for start in start_tokens {
+            let Some(&source) = index.get(start) else { continue };
+            cycles.extend(self.cycles_from(source, &tokens, &edges, pools));
+        }
+
+        cycles.sort_by(|a, b| b.rate.total_cmp(&a.rate));
+        cycles
+    }
+
+    fn cycles_from(
+        &self,
+        source: usize,
+        tokens: &[Address],
+        edges: &[Edge],
+        pools: &[DexPool],
+    ) -> Vec<ArbitrageCycle> {
+        let n = tokens.len();
+        // dist[k][v]: lowest weight of a k-edge walk source -> v; pred[k][v]: edge taken last
+        let mut dist = vec![vec![f64::INFINITY; n]; self.max_hops + 1];
+        let mut pred: Vec<Vec<Option<usize>>> = vec![vec![None; n]; self.max_hops + 1];
+        dist[0][source] = 0.0;
+
+        for k in 1..=self.max_hops {
+            for (edge_index, edge) in edges.iter().enumerate() {
+                // Never leave the source again once back at it
+                if k > 1 && edge.from == source {
+                    continue;
+                }
+                let candidate = dist[k - 1][edge.from] + edge.weight;
+                if candidate < dist[k][edge.to] {
+                    dist[k][edge.to] = candidate;
+                    pred[k][edge.to] = Some(edge_index);
+                }
+            }
+        }
+
+        let mut cycles = Vec::new();
+        for k in 2..=self.max_hops {
+            if dist[k][source] >= 0.0 {
+                continue;
+            }
+            if let Some(hops) = Self::walk_back(k, source, &pred, tokens, edges, pools) {
+                cycles.push(ArbitrageCycle {
+                    start: tokens[source],
+                    hops,
+                    rate: (-dist[k][source]).exp(),
+                });
+            }
+        }
+        cycles
+    }
+
+    // Rebuild the k-edge walk ending at `source`, rejecting walks that reuse a pool
+    fn walk_back(
+        k: usize,
+        source: usize,
+        pred: &[Vec<Option<usize>>],
+        tokens: &[Address],
+        edges: &[Edge],
+        pools: &[DexPool],
+    ) -> Option<Vec<Hop>> {
+        let mut hops = Vec::with_capacity(k);
+        let mut used = Vec::with_capacity(k);
+        let mut node = source;
+
+        for layer in (1..=k).rev() {
+            let edge = &edges[pred[layer][node]?];
+            if used.contains(&edge.pool) {
+                return None;
+            }
+            used.push(edge.pool);
+
+            hops.push(Hop {
+                pool: pools[edge.pool].clone(),
+                token_in: tokens[edge.from],
+                token_out: tokens[edge.to],
+            });
+            node = edge.from;
+        }
+
+        hops.reverse();
+        Some(hops)
+    }
+}
+
+// Marginal output per unit input after fees, in raw token units. Computed in
+// Q128 fixed point; only the final rate is converted for the log weights.
+pub fn spot_rate(pool: &DexPool, token_in: Address) -> Option<f64> {
+    let (reserve_in, reserve_out) = pool.reserves_for(token_in)?;
+    if reserve_in.is_zero() || reserve_out.is_zero() {
+        return None;
+    }
+
+    let rate = match &pool.state {
+        // V3 reserves are virtual, so the ratio is the current price
+        PoolState::ConstantProduct | PoolState::UniswapV3(_) | PoolState::UniswapV4(_) => ratio_x128(reserve_out, reserve_in)?,
+        PoolState::Balancer(state) => {
+            let (weight_in, weight_out) = if token_in == pool.token0.address {
+                (state.weight0, state.weight1)
+            } else {
+                (state.weight1, state.weight0)
+            };
+            mul_div(ratio_x128(reserve_out, reserve_in)?, weight_in, weight_out)?
+        }
+        // Stableswap trades near 1:1 in normalised units around the peg
+        PoolState::Curve(_) => {
+            let (decimals_in, decimals_out) = if token_in == pool.token0.address {
+                (pool.token0.decimals, pool.token1.decimals)
+            } else {
+                (pool.token1.decimals, pool.token0.decimals)
+            };
+            decimals_x128(decimals_in, decimals_out)
+        }
+        PoolState::SolidlyStable => {
+            let (decimals_in, decimals_out) = if token_in == pool.token0.address {
+                (pool.token0.decimals, pool.token1.decimals)
+            } else {
+                (pool.token1.decimals, pool.token0.decimals)
+            };
+            stable_rate_x128(reserve_in, reserve_out, decimals_in, decimals_out)?
+        }
+        // The active bin's price, quoted as Y per X
+        PoolState::LiquidityBook(state) => {
+            let price = bin_price_x128(state.active_id, state.bin_step)?;
+            if token_in == pool.token0.address {
+                price
+            } else {
+                mul_div(q128(), q128(), price)?
+            }
+        }
+        PoolState::Maverick(state) => {
+            let price = active_price_x128(state, pool.token0.decimals, pool.token1.decimals)?;
+            if token_in == pool.token0.address {
+                price
+            } else {
+                mul_div(q128(), q128(), price)?
+            }
+        }
+    };
+
+    let rate = x128_to_f64(less_fee(rate, pool.fee_bps));
+    Some(rate).filter(|r| r.is_finite() && *r > 0.0)
+}
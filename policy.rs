@@ -0,0 +1,350 @@
+use async_trait::async_trait;
+use ethers::{
+    abi::{AbiParser, Function, Token},
+    signers::Signer,
+    types::{
+        transaction::{eip2718::TypedTransaction, eip712::Eip712},
+        Address, Signature, U256,
+    },
+};
+use std::{
+    collections::{HashMap, HashSet},
+    sync::{Arc, Mutex},
+    time::{SystemTime, UNIX_EPOCH},
+};
+use thiserror::Error;
+use tokio::sync::mpsc;
+use tracing::error;
+
+use crate::{
+    errors::{BotError, ExecutionError, Result},
+    math,
+};
+
+const SECONDS_PER_DAY: u64 = 86_400;
+
+// Limits on what the hot wallet will sign. Unset limits don't apply.
+#[derive(Debug, Clone, Default)]
+pub struct PolicyRules {
+    // Contracts the wallet may call; empty allows any
+    pub allowed_destinations: HashSet<Address>,
+    pub max_value: Option<U256>,
+    // Per gas, the legacy price or the EIP-1559 max fee
+    pub max_gas_price: Option<U256>,
+    // Worst-case gas cost of everything signed per UTC day, in wei
+    pub max_daily_gas_spend: Option<U256>,
+    // Most of a token a single transaction may move or put at risk
+    pub token_caps: HashMap<Address, U256>,
+}
+
+#[derive(Debug, Clone, Error)]
+pub enum PolicyViolation {
+    #[error("destination {0:?} not allowed")]
+    Destination(Option<Address>),
+
+    #[error("value {0} above cap {1}")]
+    Value(U256, U256),
+
+    #[error("gas price {0} above cap {1}")]
+    GasPrice(U256, U256),
+
+    #[error("daily gas spend would reach {0}, cap {1}")]
+    DailyGasSpend(U256, U256),
+
+    #[error("{amount} of {token:?} above exposure cap {cap}")]
+    TokenExposure { token: Address, amount: U256, cap: U256 },
+
+    #[error("call 0x{} to {to:?} matches no policy rule", hex::encode(.selector))]
+    UnknownCall { to: Address, selector: [u8; 4] },
+}
+
+// Which token a call's exposure counts against
+#[derive(Debug, Clone, Copy)]
+enum ExposureToken {
+    // The address argument at this index
+    Arg(usize),
+    // The called contract itself
+    Callee,
+    Fixed(Address),
+}
+
+// Where a call's token exposure is read from: the amount is the argument at
+// `amount`, times the argument at `price` over its unit when priced
+#[derive(Debug, Clone)]
+struct ExposureRule {
+    function: Function,
+    token: ExposureToken,
+    amount: usize,
+    price: Option<(usize, U256)>,
+}
+
+// Checks every transaction before it's signed against PolicyRules, so a bug
+// can't send the hot wallet's funds somewhere unexpected or burn them on gas.
+// Violations are refused and reported on the alert channel.
+#[derive(Debug)]
+pub struct SigningPolicy {
+    rules: PolicyRules,
+    exposures: Vec<ExposureRule>,
+    // Selectors with a rule, the only calls guarded contracts accept
+    known: HashSet<[u8; 4]>,
+    // Contracts, like the executors, that no unregistered function may be called on
+    guarded: HashSet<Address>,
+    // (UTC day, worst-case gas signed for that day)
+    spent: Mutex<(u64, U256)>,
+    alerts: Option<mpsc::UnboundedSender<PolicyViolation>>,
+}
+
+impl SigningPolicy {
+    // ERC20 transfers and approvals count against token caps out of the box
+    pub fn new(rules: PolicyRules) -> Result<Self> {
+        Self {
+            rules,
+            exposures: Vec::new(),
+            known: HashSet::new(),
+            guarded: HashSet::new(),
+            spent: Mutex::new((0, U256::zero())),
+            alerts: None,
+        }
+        .with_exposure("function transfer(address to, uint256 amount)", None, 1)?
+        .with_exposure("function approve(address spender, uint256 amount)", None, 1)
+    }
+
+    // Count calls to `signature` against the cap of the token at argument
+    // `token` (the called contract when None), for the amount at argument `amount`
+    pub fn with_exposure(self, signature: &str, token: Option<usize>, amount: usize) -> Result<Self> {
+        let token = token.map_or(ExposureToken::Callee, ExposureToken::Arg);
+        self.with_rule(signature, token, amount, None)
+    }
+
+    // Count calls to `signature` against `token`'s cap, for the argument at
+    // `amount` times the argument at `price` over `unit`, as when buying a lot
+    // at a quoted unit price
+    pub fn with_priced_exposure(
+        self,
+        signature: &str,
+        token: Address,
+        amount: usize,
+        price: usize,
+        unit: U256,
+    ) -> Result<Self> {
+        self.with_rule(signature, ExposureToken::Fixed(token), amount, Some((price, unit)))
+    }
+
+    // Allow calls to `signature` on guarded contracts without counting any exposure
+    pub fn with_function(mut self, signature: &str) -> Result<Self> {
+        let function = parse_signature(signature)?;
+        self.known.insert(function.short_signature());
+        Ok(self)
+    }
+
+    // Refuse calls to `contract` whose function has no rule registered
+    pub fn with_guarded(mut self, contract: Address) -> Self {
+        self.guarded.insert(contract);
+        self
+    }
+
+    fn with_rule(
+        mut self,
+        signature: &str,
+        token: ExposureToken,
+        amount: usize,
+        price: Option<(usize, U256)>,
+    ) -> Result<Self> {
+        let function = parse_signature(signature)?;
+        self.known.insert(function.short_signature());
+        self.exposures.push(ExposureRule { function, token, amount, price });
+        Ok(self)
+    }
+
+    pub fn with_alerts(mut self, alerts: mpsc::UnboundedSender<PolicyViolation>) -> Self {
+        self.alerts = Some(alerts);
+        self
+    }
+
+    // Ok if `tx` may be signed; its worst-case gas cost then counts against the day
+    pub fn check(&self, tx: &TypedTransaction) -> std::result::Result<(), PolicyViolation> {
+        let result = self.evaluate(tx);
+        if let Err(violation) = &result {
+            error!(to = ?tx.to_addr(), %violation, "🛑 Transaction refused by signing policy");
+            if let Some(alerts) = &self.alerts {
+                let _ = alerts.send(violation.clone());
+            }
+        }
+        result
+    }
+
+    fn evaluate(&self, tx: &TypedTransaction) -> std::result::Result<(), PolicyViolation> {
+        let rules = &self.rules;
+        let to = tx.to_addr().copied();
+        if !rules.allowed_destinations.is_empty() && !to.map_or(false, |to| rules.allowed_destinations.contains(&to)) {
+            return Err(PolicyViolation::Destination(to));
+        }
+
+        let value = tx.value().copied().unwrap_or_default();
+        if let Some(cap) = rules.max_value {
+            if value > cap {
+                return Err(PolicyViolation::Value(value, cap));
+            }
+        }
+
+        let gas_price = match tx {
+            TypedTransaction::Eip1559(inner) => inner.max_fee_per_gas,
+            _ => tx.gas_price(),
+        }
+        .unwrap_or_default();
+        if let Some(cap) = rules.max_gas_price {
+            if gas_price > cap {
+                return Err(PolicyViolation::GasPrice(gas_price, cap));
+            }
+        }
+
+        if let (Some(to), Some(data)) = (to, tx.data()) {
+            // Plain transfers carry no calldata and pass
+            if self.guarded.contains(&to) && !data.is_empty() {
+                let selector: [u8; 4] = data.get(..4).and_then(|s| s.try_into().ok()).unwrap_or_default();
+                if !self.known.contains(&selector) {
+                    return Err(PolicyViolation::UnknownCall { to, selector });
+                }
+            }
+            for (token, amount) in self.exposure(to, data) {
+                if let Some(cap) = rules.token_caps.get(&token) {
+                    if amount > *cap {
+                        return Err(PolicyViolation::TokenExposure { token, amount, cap: *cap });
+                    }
+                }
+            }
+        }
+
+        // Checked last so refused transactions don't use up the budget
+        let cost = tx.gas().copied().unwrap_or_default().saturating_mul(gas_price);
+        let today = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_secs() / SECONDS_PER_DAY)
+            .unwrap_or_default();
+        let mut spent = self.spent.lock().unwrap();
+        if spent.0 != today {
+            *spent = (today, U256::zero());
+        }
+        let total = spent.1.saturating_add(cost);
+        if let Some(cap) = rules.max_daily_gas_spend {
+            if total > cap {
+                return Err(PolicyViolation::DailyGasSpend(total, cap));
+            }
+        }
+        spent.1 = total;
+        Ok(())
+    }
+
+    fn exposure(&self, to: Address, data: &[u8]) -> Vec<(Address, U256)> {
+        if data.len() < 4 {
+            return Vec::new();
+        }
+        self.exposures
+            .iter()
+            .filter(|rule| data[..4] == rule.function.short_signature())
+            .filter_map(|rule| {
+                let args = rule.function.decode_input(&data[4..]).ok()?;
+                let token = match rule.token {
+                    ExposureToken::Arg(i) => args.get(i)?.clone().into_address()?,
+                    ExposureToken::Callee => to,
+                    ExposureToken::Fixed(token) => token,
+                };
+                let Some(Token::Uint(amount)) = args.get(rule.amount) else {
+                    return None;
+                };
+                let amount = match rule.price {
+                    Some((i, unit)) => {
+                        let Some(Token::Uint(price)) = args.get(i) else {
+                            return None;
+                        };
+                        // Saturates rather than wraps, so an absurd price still trips the cap
+                        math::mul_div(*amount, *price, unit).unwrap_or(U256::MAX)
+                    }
+                    None => *amount,
+                };
+                Some((token, amount))
+            })
+            .collect()
+    }
+}
+
+fn parse_signature(signature: &str) -> Result<Function> {
+    AbiParser::default()
+        .parse_function(signature)
+        .map_err(|e| BotError::Config(format!("invalid policy signature {}: {}", signature, e)))
+}
+
+#[derive(Debug, Error)]
+pub enum PolicyError<E: std::error::Error> {
+    #[error("signing policy: {0}")]
+    Violation(PolicyViolation),
+
+    #[error(transparent)]
+    Signer(E),
+}
+
+impl<E: std::error::Error> From<PolicyError<E>> for BotError {
+    fn from(err: PolicyError<E>) -> Self {
+        match err {
+            PolicyError::Violation(v) => ExecutionError::Policy(v.to_string()).into(),
+            PolicyError::Signer(e) => ExecutionError::Signer(e.to_string()).into(),
+        }
+    }
+}
+
+// A signer that consults the SigningPolicy before every transaction it signs.
+// Messages and typed data pass straight through.
+#[derive(Debug, Clone)]
+pub struct PolicySigner<S> {
+    inner: S,
+    policy: Arc<SigningPolicy>,
+}
+
+impl<S> PolicySigner<S> {
+    pub fn new(inner: S, policy: Arc<SigningPolicy>) -> Self {
+        Self { inner, policy }
+    }
+
+    pub fn inner(&self) -> &S {
+        &self.inner
+    }
+}
+
+#[async_trait]
+impl<S: Signer> Signer for PolicySigner<S> {
+    type Error = PolicyError<S::Error>;
+
+    async fn sign_message<M: Send + Sync + AsRef<[u8]>>(
+        &self,
+        message: M,
+    ) -> std::result::Result<Signature, Self::Error> {
+        self.inner.sign_message(message).await.map_err(PolicyError::Signer)
+    }
+
+    async fn sign_transaction(&self, tx: &TypedTransaction) -> std::result::Result<Signature, Self::Error> {
+        self.policy.check(tx).map_err(PolicyError::Violation)?;
+        self.inner.sign_transaction(tx).await.map_err(PolicyError::Signer)
+    }
+
+    async fn sign_typed_data<T: Eip712 + Send + Sync>(
+        &self,
+        payload: &T,
+    ) -> std::result::Result<Signature, Self::Error> {
+        self.inner.sign_typed_data(payload).await.map_err(PolicyError::Signer)
+    }
+
+    fn address(&self) -> Address {
+        self.inner.address()
+    }
+
+    fn chain_id(&self) -> u64 {
+        self.inner.chain_id()
+    }
+
+    fn with_chain_id<T: Into<u64>>(self, chain_id: T) -> Self {
+        Self {
+            inner: self.inner.with_chain_id(chain_id),
+            policy: self.policy,
+        }
+    }
+}
@@ -0,0 +1,340 @@
+use dashmap::{DashMap, DashSet};
+use ethers::{
+    prelude::*,
+    providers::{Provider, Ws},
+    types::{Address, Filter, Log, H256, I256, U256, U64},
+    utils::keccak256,
+};
+use futures::stream::{self, BoxStream};
+use std::sync::Arc;
+use tokio::sync::Notify;
+
+use crate::{
+    dex::uniswap_v3::update_virtual_reserves,
+    errors::Result,
+    models::{DexPool, DexType, PoolState},
+};
+
+// Latest on-chain state of one pool, as carried by its most recent event
+#[derive(Debug, Clone)]
+pub enum CachedState {
+    Reserves { reserve0: U256, reserve1: U256 },
+    UniswapV3 { sqrt_price_x96: U256, liquidity: u128, tick: i32 },
+    // A polled pool no log has touched since it was last read
+    Unchanged,
+}
+
+#[derive(Debug, Clone)]
+struct Entry {
+    state: CachedState,
+    block: U64,
+}
+
+// Keeps V2 reserves and V3 prices current from `Sync` and `Swap` logs over the
+// websocket, so scans read memory instead of calling every pool each block.
+// Pools whose state the logs don't carry (V3 `Mint`/`Burn` move liquidity
+// between ticks; Curve, Maverick and Liquidity Book pools are polled) are
+// marked dirty by any log they emit and re-read once, not every block.
+// Pools without a cached entry (never read since tracking, dirty, or reorged
+// out) must be refreshed through their handler.
+pub struct PoolStateCache {
+    provider: Arc<Provider<Ws>>,
+    // Tracked pools and whether they are V3
+    tracked: DashMap<Address, bool>,
+    // Pools on venues whose own contract logs every change, but whose state
+    // can't be decoded from the logs
+    polled: DashSet<Address>,
+    // Signalled when `polled` grows, so its log subscription is renewed
+    polled_changed: Notify,
+    entries: DashMap<Address, Entry>,
+    // Block of the latest log that invalidated each pool's state
+    dirty: DashMap<Address, U64>,
+    // Token amounts swapped through each pool since `take_swapped` last ran
+    swapped: DashMap<Address, (U256, U256)>,
+}
+
+impl PoolStateCache {
+    pub fn new(provider: Arc<Provider<Ws>>) -> Self {
+        Self {
+            provider,
+            tracked: DashMap::new(),
+            polled: DashSet::new(),
+            polled_changed: Notify::new(),
+            entries: DashMap::new(),
+            dirty: DashMap::new(),
+            swapped: DashMap::new(),
+        }
+    }
+
+    // Start following a pool; venues without a supported event are ignored
+    pub fn track(&self, pool: &DexPool) {
+        match pool.dex {
+            DexType::UniswapV2
+            | DexType::SushiSwap
+            | DexType::PancakeSwapV2
+            | DexType::Velodrome
+            | DexType::Aerodrome => {
+                self.tracked.insert(pool.address, false);
+            }
+            DexType::UniswapV3 | DexType::PancakeSwapV3 => {
+                self.tracked.insert(pool.address, true);
+            }
+            // Balancer and V4 pools are logged by a shared vault or manager,
+            // so they are still read every block
+            DexType::Curve | DexType::MaverickV2 | DexType::TraderJoeV2 => {
+                if self.polled.insert(pool.address) {
+                    self.polled_changed.notify_one();
+                }
+            }
+            _ => {}
+        }
+    }
+
+    // Record state just read from chain at `block`, unless an event has already superseded it
+    pub fn seed(&self, pool: &DexPool, block: U64) {
+        // A log after the read means it's already stale
+        if self.dirty.get(&pool.address).is_some_and(|at| *at > block) {
+            return;
+        }
+        self.dirty.remove(&pool.address);
+        if self.polled.contains(&pool.address) {
+            self.insert_newest(pool.address, Entry { state: CachedState::Unchanged, block });
+            return;
+        }
+        if !self.tracked.contains_key(&pool.address) {
+            return;
+        }
+        let state = match &pool.state {
+            PoolState::ConstantProduct | PoolState::SolidlyStable => CachedState::Reserves {
+                reserve0: pool.reserve0,
+                reserve1: pool.reserve1,
+            },
+            PoolState::UniswapV3(v3) => CachedState::UniswapV3 {
+                sqrt_price_x96: v3.sqrt_price_x96,
+                liquidity: v3.liquidity,
+                tick: v3.tick,
+            },
+            _ => return,
+        };
+        self.insert_newest(pool.address, Entry { state, block });
+    }
+
+    pub fn get(&self, pool: Address) -> Option<CachedState> {
+        self.entries.get(&pool).map(|e| e.state.clone())
+    }
+
+    // Copy cached state into `pool` as of `block`. False when the cache can't
+    // vouch for it, including a V3 price that has moved past the ticks loaded
+    // for the pool and state already carried past `block` by a later log.
+    pub fn apply(&self, pool: &mut DexPool, block: U64) -> bool {
+        if self.dirty.contains_key(&pool.address) {
+            return false;
+        }
+        let Some(entry) = self.entries.get(&pool.address).map(|e| e.clone()) else {
+            return false;
+        };
+        if entry.block > block {
+            return false;
+        }
+
+        let applied = match (entry.state, &mut pool.state) {
+            // Left as it was last read
+            (CachedState::Unchanged, _) => true,
+            (CachedState::Reserves { reserve0, reserve1 }, PoolState::ConstantProduct | PoolState::SolidlyStable) => {
+                pool.reserve0 = reserve0;
+                pool.reserve1 = reserve1;
+                true
+            }
+            (CachedState::UniswapV3 { sqrt_price_x96, liquidity, tick }, PoolState::UniswapV3(v3)) => {
+                if tick < v3.tick_lower_bound || tick >= v3.tick_upper_bound {
+                    return false;
+                }
+                v3.sqrt_price_x96 = sqrt_price_x96;
+                v3.liquidity = liquidity;
+                v3.tick = tick;
+                update_virtual_reserves(pool);
+                true
+            }
+            _ => false,
+        };
+        if applied {
+            pool.block = block;
+        }
+        applied
+    }
+
+    // Forget state carried by events from `block` on, after a reorg orphaned
+    // those blocks; the pools are read from chain again. Returns how many.
+    pub fn invalidate_from(&self, block: U64) -> usize {
+        let before = self.entries.len();
+        self.entries.retain(|_, entry| entry.block < block);
+        before - self.entries.len()
+    }
+
+    pub async fn run(self: Arc<Self>) -> Result<()> {
+        // Subscribe by topic only so pools tracked later need no resubscription
+        let filter = Filter::new().topic0(vec![
+            sync_topic(),
+            solidly_sync_topic(),
+            v3_swap_topic(),
+            pancake_v3_swap_topic(),
+            v3_mint_topic(),
+            v3_burn_topic(),
+        ]);
+        let mut logs = self.provider.subscribe_logs(&filter).await?;
+
+        // Polled pools are followed by address, renewed as pools are added
+        loop {
+            let polled: Vec<Address> = self.polled.iter().map(|p| *p).collect();
+            let mut pool_logs: BoxStream<'_, Log> = if polled.is_empty() {
+                stream::pending().boxed()
+            } else {
+                self.provider.subscribe_logs(&Filter::new().address(polled.clone())).await?.boxed()
+            };
+            // Anything logged while resubscribing was missed; read them all once
+            for pool in &polled {
+                self.entries.remove(pool);
+            }
+
+            loop {
+                tokio::select! {
+                    log = logs.next() => match log {
+                        Some(log) => self.handle_log(&log),
+                        None => return Ok(()),
+                    },
+                    Some(log) = pool_logs.next() => self.mark_dirty(&log),
+                    _ = self.polled_changed.notified() => break,
+                }
+            }
+        }
+    }
+
+    // Amounts swapped through each pool since the last call, as (pool,
+    // token0, token1)
+    pub fn take_swapped(&self) -> Vec<(Address, U256, U256)> {
+        let pools: Vec<Address> = self.swapped.iter().map(|e| *e.key()).collect();
+        pools
+            .into_iter()
+            .filter_map(|pool| self.swapped.remove(&pool))
+            .map(|(pool, (amount0, amount1))| (pool, amount0, amount1))
+            .collect()
+    }
+
+    fn record_swap(&self, pool: Address, amount0: U256, amount1: U256) {
+        let mut swapped = self.swapped.entry(pool).or_default();
+        swapped.0 = swapped.0.saturating_add(amount0);
+        swapped.1 = swapped.1.saturating_add(amount1);
+    }
+
+    // The pool's state changed in a way only a fresh read recovers
+    fn mark_dirty(&self, log: &Log) {
+        let block = log.block_number.unwrap_or_default();
+        self.dirty
+            .entry(log.address)
+            .and_modify(|at| *at = (*at).max(block))
+            .or_insert(block);
+    }
+
+    fn handle_log(&self, log: &Log) {
+        let Some(is_v3) = self.tracked.get(&log.address).map(|t| *t) else {
+            return;
+        };
+        // A reorged-out event leaves the pool's true state unknown
+        if log.removed == Some(true) {
+            self.entries.remove(&log.address);
+            return;
+        }
+        // Liquidity added or removed over a tick range the swap state doesn't carry
+        if is_v3 && matches!(log.topics.first(), Some(t) if *t == v3_mint_topic() || *t == v3_burn_topic()) {
+            self.mark_dirty(log);
+            return;
+        }
+
+        let Some(state) = (if is_v3 { decode_v3_swap(log) } else { decode_sync(log) }) else {
+            return;
+        };
+        let block = log.block_number.unwrap_or_default();
+
+        // V3 swaps log their amounts. A V2 `Sync` moving the reserves in
+        // opposite directions was a swap of the difference; same-direction
+        // moves are liquidity.
+        match (&state, self.entries.get(&log.address).filter(|e| e.block <= block).map(|e| e.state.clone())) {
+            (CachedState::UniswapV3 { .. }, _) => {
+                let amount = |word: &[u8]| I256::from_raw(U256::from_big_endian(word)).unsigned_abs();
+                self.record_swap(log.address, amount(&log.data[0..32]), amount(&log.data[32..64]));
+            }
+            (
+                CachedState::Reserves { reserve0, reserve1 },
+                Some(CachedState::Reserves { reserve0: old0, reserve1: old1 }),
+            ) if (*reserve0 > old0) == (*reserve1 < old1) && *reserve0 != old0 && *reserve1 != old1 => {
+                let diff = |a: U256, b: U256| if a > b { a - b } else { b - a };
+                self.record_swap(log.address, diff(*reserve0, old0), diff(*reserve1, old1));
+            }
+            _ => {}
+        }
+
+        self.insert_newest(log.address, Entry { state, block });
+    }
+
+    // Logs can arrive out of order across reconnects; keep the newest
+    fn insert_newest(&self, pool: Address, entry: Entry) {
+        let stale = self.entries.get(&pool).map(|e| e.block > entry.block).unwrap_or(false);
+        if !stale {
+            self.entries.insert(pool, entry);
+        }
+    }
+}
+
+fn sync_topic() -> H256 {
+    H256::from(keccak256("Sync(uint112,uint112)"))
+}
+
+// Solidly pools keep uint256 reserves; the data layout is the same
+fn solidly_sync_topic() -> H256 {
+    H256::from(keccak256("Sync(uint256,uint256)"))
+}
+
+fn v3_swap_topic() -> H256 {
+    H256::from(keccak256("Swap(address,address,int256,int256,uint160,uint128,int24)"))
+}
+
+// PancakeSwap V3 appends the protocol fees taken to the same fields
+fn pancake_v3_swap_topic() -> H256 {
+    H256::from(keccak256("Swap(address,address,int256,int256,uint160,uint128,int24,uint128,uint128)"))
+}
+
+// The same on PancakeSwap V3
+fn v3_mint_topic() -> H256 {
+    H256::from(keccak256("Mint(address,address,int24,int24,uint128,uint256,uint256)"))
+}
+
+fn v3_burn_topic() -> H256 {
+    H256::from(keccak256("Burn(address,int24,int24,uint128,uint256,uint256)"))
+}
+
+fn decode_sync(log: &Log) -> Option<CachedState> {
+    let topic = log.topics.first()?;
+    if (*topic != sync_topic() && *topic != solidly_sync_topic()) || log.data.len() < 64 {
+        return None;
+    }
+    Some(CachedState::Reserves {
+        reserve0: U256::from_big_endian(&log.data[0..32]),
+        reserve1: U256::from_big_endian(&log.data[32..64]),
+    })
+}
+
+// Data words: amount0, amount1, sqrtPriceX96, liquidity, tick (then PancakeSwap's fees)
+fn decode_v3_swap(log: &Log) -> Option<CachedState> {
+    let topic = log.topics.first()?;
+    if (*topic != v3_swap_topic() && *topic != pancake_v3_swap_topic()) || log.data.len() < 160 {
+        return None;
+    }
+    let liquidity = U256::from_big_endian(&log.data[96..128]);
+    // int24 is sign-extended to 32 bytes; the low four bytes hold it as i32
+    let tick = i32::from_be_bytes(log.data[156..160].try_into().ok()?);
+    Some(CachedState::UniswapV3 {
+        sqrt_price_x96: U256::from_big_endian(&log.data[64..96]),
+        liquidity: liquidity.try_into().ok()?,
+        tick,
+    })
+}
@@ -0,0 +1,250 @@
+use dashmap::DashMap;
+use ethers::{
+    prelude::*,
+    types::{Address, I256, U256, U64},
+};
+use futures::future::join_all;
+use std::{
+    collections::{HashMap, HashSet},
+    sync::Arc,
+    time::Duration,
+};
+use tracing::{debug, warn};
+
+use crate::{
+    chains::ChainProfile,
+    coingecko::CoinGeckoClient,
+    errors::{BotError, Result},
+    math,
+    models::DexPool,
+};
+
+abigen!(
+    UsdFeed,
+    r#"[
+        function latestRoundData() external view returns (uint80, int256, uint256, uint256, uint80)
+        function decimals() external view returns (uint8)
+    ]"#
+);
+
+// USD feeds update at least daily; an answer older than that plus some slack
+// means the feed has stopped
+pub const DEFAULT_MAX_FEED_AGE: Duration = Duration::from_secs(25 * 3600);
+
+// Decimals of every USD price and value, as `usd_value` and MIN_PROFIT_USD use
+const USD_DECIMALS: usize = 18;
+
+// USD per whole token (18 decimals) for the tokens of one scan, with their
+// decimals so amounts can be valued
+#[derive(Debug, Clone, Default)]
+pub struct UsdPrices {
+    prices: HashMap<Address, U256>,
+    decimals: HashMap<Address, u8>,
+}
+
+impl UsdPrices {
+    pub fn price(&self, token: &Address) -> Option<U256> {
+        self.prices.get(token).copied()
+    }
+
+    // USD value (18 decimals) of `amount` of `token`; None when it has no price
+    pub fn value(&self, token: Address, amount: U256) -> Option<U256> {
+        let price = self.price(&token)?;
+        let decimals = *self.decimals.get(&token)?;
+        math::mul_div(amount, price, U256::exp10(decimals as usize))
+    }
+
+    // USD held by `pool`. A side whose token has no price is taken to be worth
+    // the other side, as it is at the pool's own price; None when neither is priced.
+    pub fn liquidity(&self, pool: &DexPool) -> Option<U256> {
+        let side0 = self.value(pool.token0.address, pool.reserve0);
+        let side1 = self.value(pool.token1.address, pool.reserve1);
+        match (side0, side1) {
+            (Some(a), Some(b)) => Some(a.saturating_add(b)),
+            (Some(side), None) | (None, Some(side)) => Some(side.saturating_mul(U256::from(2))),
+            (None, None) => None,
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.prices.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.prices.is_empty()
+    }
+}
+
+// Prices any token in USD: from its Chainlink <token>/USD feed when it has one,
+// then from CoinGecko when it's set, otherwise through the deepest pool pairing
+// it with a token already priced, which in practice is the wrapped native token
+// or USDC. USDC without a feed is taken at $1.
+pub struct UsdPricer<M> {
+    provider: Arc<M>,
+    // token -> Chainlink <token>/USD feed
+    feeds: HashMap<Address, Address>,
+    // Priced at $1 when they have no feed
+    stables: HashSet<Address>,
+    max_age: Duration,
+    // Feed decimals never change, so they're read once
+    feed_decimals: DashMap<Address, u8>,
+    // Only its cache is read while pricing, so a slow or rate-limited
+    // CoinGecko never holds up a scan
+    coingecko: Option<Arc<CoinGeckoClient>>,
+}
+
+impl<M: Middleware + 'static> UsdPricer<M> {
+    // The wrapped native token by `chain`'s feed and USDC at $1
+    pub fn new(provider: Arc<M>, chain: &ChainProfile) -> Self {
+        Self {
+            provider,
+            feeds: HashMap::from([(chain.wrapped_native, chain.native_usd_feed)]),
+            stables: HashSet::from([chain.usdc]),
+            max_age: DEFAULT_MAX_FEED_AGE,
+            feed_decimals: DashMap::new(),
+            coingecko: None,
+        }
+    }
+
+    // Plus the feeds in USD_FEEDS, `token=feed` pairs separated by commas
+    pub fn from_env(provider: Arc<M>, chain: &ChainProfile) -> Result<Self> {
+        let mut pricer = Self::new(provider, chain);
+        for entry in std::env::var("USD_FEEDS").unwrap_or_default().split(',') {
+            if entry.trim().is_empty() {
+                continue;
+            }
+            let parsed = entry
+                .split_once('=')
+                .and_then(|(token, feed)| Some((token.trim().parse().ok()?, feed.trim().parse().ok()?)));
+            let Some((token, feed)) = parsed else {
+                return Err(BotError::Config(format!("USD_FEEDS: expected token=feed, got {}", entry.trim())));
+            };
+            pricer = pricer.with_feed(token, feed);
+        }
+        Ok(pricer)
+    }
+
+    pub fn with_feed(mut self, token: Address, feed: Address) -> Self {
+        self.feeds.insert(token, feed);
+        self
+    }
+
+    pub fn with_max_age(mut self, max_age: Duration) -> Self {
+        self.max_age = max_age;
+        self
+    }
+
+    pub fn with_coingecko(mut self, coingecko: Arc<CoinGeckoClient>) -> Self {
+        self.coingecko = Some(coingecko);
+        self
+    }
+
+    // Prices for every token in `pools` as of `block`, mined at `timestamp`.
+    // Feeds that fail or have gone stale fall back to routing like any other token.
+    pub async fn prices(&self, pools: &[DexPool], block: U64, timestamp: U256) -> UsdPrices {
+        let mut prices = UsdPrices::default();
+        for pool in pools {
+            for token in [&pool.token0, &pool.token1] {
+                prices.decimals.insert(token.address, token.decimals);
+            }
+        }
+
+        let feeds = self.feeds.iter().filter(|(token, _)| prices.decimals.contains_key(token));
+        let read = join_all(feeds.map(|(token, feed)| async move {
+            (*token, self.feed_price(*feed, block, timestamp).await)
+        }))
+        .await;
+        for (token, result) in read {
+            match result {
+                Ok(Some(price)) => {
+                    prices.prices.insert(token, price);
+                }
+                Ok(None) => warn!(token = ?token, "⚠️ USD feed is stale, pricing through pools"),
+                Err(e) => warn!(token = ?token, error = %e, "⚠️ USD feed read failed, pricing through pools"),
+            }
+        }
+        for stable in &self.stables {
+            prices.prices.entry(*stable).or_insert_with(|| U256::exp10(USD_DECIMALS));
+        }
+        if let Some(coingecko) = &self.coingecko {
+            let unpriced: Vec<Address> =
+                prices.decimals.keys().filter(|t| !prices.prices.contains_key(t)).copied().collect();
+            let quoted = coingecko.token_prices_now(&unpriced);
+            prices.prices.extend(
+                quoted
+                    .into_iter()
+                    .filter(|(_, quote)| U256::from(quote.updated_at) + self.max_age.as_secs() >= timestamp)
+                    .map(|(token, quote)| (token, quote.usd)),
+            );
+        }
+
+        route(&mut prices, pools);
+        debug!(priced = prices.len(), tokens = prices.decimals.len(), "💵 Tokens priced in USD");
+        prices
+    }
+
+    // USD per whole token (18 decimals) from `feed` at `block`; None for an
+    // answer that isn't positive or is older than the max age at `timestamp`
+    async fn feed_price(&self, feed: Address, block: U64, timestamp: U256) -> Result<Option<U256>> {
+        let contract = UsdFeed::new(feed, self.provider.clone());
+        let decimals = match self.feed_decimals.get(&feed) {
+            Some(decimals) => *decimals,
+            None => {
+                let decimals = contract.decimals().call().await?;
+                self.feed_decimals.insert(feed, decimals);
+                decimals
+            }
+        };
+        let (_, answer, _, updated_at, _) = contract.latest_round_data().block(block).call().await?;
+
+        if answer <= I256::zero() || updated_at + self.max_age.as_secs() < timestamp {
+            return Ok(None);
+        }
+        Ok(Some(scale(answer.into_raw(), decimals)))
+    }
+}
+
+// A Chainlink answer with `decimals` decimals in 18
+fn scale(answer: U256, decimals: u8) -> U256 {
+    let decimals = decimals as usize;
+    if decimals <= USD_DECIMALS {
+        answer.saturating_mul(U256::exp10(USD_DECIMALS - decimals))
+    } else {
+        answer / U256::exp10(decimals - USD_DECIMALS)
+    }
+}
+
+// Price every token reachable from a priced one by its mid price in the pool
+// holding the most USD of the priced side, one hop further each pass
+fn route(prices: &mut UsdPrices, pools: &[DexPool]) {
+    loop {
+        let mut found: HashMap<Address, (U256, U256)> = HashMap::new();
+        for pool in pools {
+            for (anchor, token) in [(&pool.token0, &pool.token1), (&pool.token1, &pool.token0)] {
+                if prices.prices.contains_key(&token.address) {
+                    continue;
+                }
+                let Some((anchor_reserve, token_reserve)) = pool.reserves_for(anchor.address) else {
+                    continue;
+                };
+                let Some(depth) = prices.value(anchor.address, anchor_reserve) else {
+                    continue;
+                };
+                if token_reserve.is_zero() {
+                    continue;
+                }
+                // Value of the anchor side spread over the token side, per whole token
+                let Some(price) = math::mul_div(depth, U256::exp10(token.decimals as usize), token_reserve) else {
+                    continue;
+                };
+                if found.get(&token.address).map_or(true, |(deepest, _)| depth > *deepest) {
+                    found.insert(token.address, (depth, price));
+                }
+            }
+        }
+        if found.is_empty() {
+            return;
+        }
+        prices.prices.extend(found.into_iter().map(|(token, (_, price))| (token, price)));
+    }
+}
@@ -0,0 +1,95 @@
+use ethers::types::{Address, U256};
+use std::{
+    cmp::Reverse,
+    collections::{BTreeSet, HashMap},
+};
+
+use liquidation_bot::{math::mul_div, models::LiquidationTarget};
+
+// Health factors within one band are equally urgent, so size and profit
+// decide between them
+const URGENCY_BAND: f64 = 0.01;
+
+// Sort key, most urgent first: furthest below 1.0, then the largest debt,
+// then the most profit (both in USD)
+type Priority = (Reverse<u64>, Reverse<U256>, Reverse<U256>, Address);
+
+fn priority(target: &LiquidationTarget) -> Priority {
+    let urgency = ((1.0 - target.health_factor).max(0.0) / URGENCY_BAND) as u64;
+    (
+        Reverse(urgency),
+        Reverse(target.debt_value),
+        Reverse(target.profit_value),
+        target.user,
+    )
+}
+
+// Liquidatable positions, one per user, kept in priority order so the most
+// valuable are evaluated first when a block leaves little time
+#[derive(Debug, Clone, Default)]
+pub struct TargetQueue {
+    targets: HashMap<Address, LiquidationTarget>,
+    order: BTreeSet<Priority>,
+}
+
+impl TargetQueue {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn insert(&mut self, target: LiquidationTarget) {
+        self.remove(&target.user);
+        self.order.insert(priority(&target));
+        self.targets.insert(target.user, target);
+    }
+
+    pub fn remove(&mut self, user: &Address) -> Option<LiquidationTarget> {
+        let target = self.targets.remove(user)?;
+        self.order.remove(&priority(&target));
+        Some(target)
+    }
+
+    pub fn contains(&self, user: &Address) -> bool {
+        self.targets.contains_key(user)
+    }
+
+    pub fn users(&self) -> impl Iterator<Item = Address> + '_ {
+        self.targets.keys().copied()
+    }
+
+    // Most urgent first
+    pub fn iter(&self) -> impl Iterator<Item = &LiquidationTarget> + '_ {
+        self.order.iter().filter_map(|(_, _, _, user)| self.targets.get(user))
+    }
+
+    // Re-price every target's gas at `gas_price` and re-sort. Targets whose
+    // profit no longer clears `min_profit` (USD) are dropped until the next
+    // scan finds them again; returns how many.
+    pub fn rescore(&mut self, gas_price: U256, min_profit: U256) -> usize {
+        let targets: Vec<LiquidationTarget> = self.targets.drain().map(|(_, t)| t).collect();
+        self.order.clear();
+
+        let mut dropped = 0;
+        for mut target in targets {
+            if !target.gas_price.is_zero() && target.gas_price != gas_price {
+                let gross = target.expected_profit + target.gas_cost;
+                let gas_cost = mul_div(target.gas_cost, gas_price, target.gas_price).unwrap_or(U256::MAX);
+                if gas_cost >= gross {
+                    dropped += 1;
+                    continue;
+                }
+                let expected_profit = gross - gas_cost;
+                target.profit_value = mul_div(target.profit_value, expected_profit, target.expected_profit).unwrap_or_default();
+                target.expected_profit = expected_profit;
+                target.gas_cost = gas_cost;
+                target.gas_price = gas_price;
+            }
+            if target.profit_value < min_profit {
+                dropped += 1;
+                continue;
+            }
+            self.insert(target);
+        }
+        dropped
+    }
+}
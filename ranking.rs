@@ -0,0 +1,95 @@
+use ethers::types::{Address, U256};
+use std::collections::HashMap;
+
+use crate::{models::DexPool, price::UsdPrices};
+
+// Blocks swap volume is remembered over, about an hour on mainnet
+pub const DEFAULT_VOLUME_WINDOW: u64 = 300;
+
+// Orders pools by the USD they hold plus the USD recently swapped through
+// them, so a scan can keep to the pools that matter in each pair instead of
+// whichever the factories happened to list
+#[derive(Debug)]
+pub struct PoolRanking {
+    window: u64,
+    // USD swapped per pool, decayed by 1/window each block: about the total
+    // of the last `window` blocks
+    volume: HashMap<Address, U256>,
+}
+
+impl Default for PoolRanking {
+    fn default() -> Self {
+        Self::new(DEFAULT_VOLUME_WINDOW)
+    }
+}
+
+impl PoolRanking {
+    pub fn new(window: u64) -> Self {
+        Self {
+            window: window.max(1),
+            volume: HashMap::new(),
+        }
+    }
+
+    // Age every pool's volume by one block and add `swapped`, the token
+    // amounts each pool swapped since the last call. A swap is valued on the
+    // side worth more; one with neither token priced isn't counted.
+    pub fn record(&mut self, swapped: Vec<(Address, U256, U256)>, pools: &[DexPool], prices: &UsdPrices) {
+        let window = U256::from(self.window);
+        self.volume.retain(|_, usd| {
+            *usd -= *usd / window;
+            !usd.is_zero()
+        });
+
+        let by_address: HashMap<Address, &DexPool> = pools.iter().map(|p| (p.address, p)).collect();
+        for (address, amount0, amount1) in swapped {
+            let Some(pool) = by_address.get(&address) else {
+                continue;
+            };
+            let usd = [
+                prices.value(pool.token0.address, amount0),
+                prices.value(pool.token1.address, amount1),
+            ]
+            .into_iter()
+            .flatten()
+            .max();
+            if let Some(usd) = usd {
+                *self.volume.entry(address).or_default() += usd;
+            }
+        }
+    }
+
+    // Recent USD volume, 18 decimals
+    pub fn volume(&self, pool: &Address) -> U256 {
+        self.volume.get(pool).copied().unwrap_or_default()
+    }
+
+    // TVL plus recent volume, both in USD
+    pub fn score(&self, pool: &DexPool, prices: &UsdPrices) -> U256 {
+        prices
+            .liquidity(pool)
+            .unwrap_or_default()
+            .saturating_add(self.volume(&pool.address))
+    }
+
+    // The `per_pair` best-scoring pools of each token pair; every pool when 0
+    pub fn top_per_pair(&self, pools: Vec<DexPool>, prices: &UsdPrices, per_pair: usize) -> Vec<DexPool> {
+        if per_pair == 0 {
+            return pools;
+        }
+        let mut by_pair: HashMap<(Address, Address), Vec<(U256, DexPool)>> = HashMap::new();
+        for pool in pools {
+            by_pair
+                .entry(pool.pair_key())
+                .or_default()
+                .push((self.score(&pool, prices), pool));
+        }
+        by_pair
+            .into_values()
+            .flat_map(|mut group| {
+                group.sort_by(|a, b| b.0.cmp(&a.0));
+                group.into_iter().take(per_pair).map(|(_, pool)| pool)
+            })
+            .collect()
+    }
+}
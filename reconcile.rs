@@ -0,0 +1,93 @@
+use ethers::{
+    types::{Address, TransactionReceipt, H256, I256, U256},
+    utils::keccak256,
+};
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::VecDeque,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Mutex,
+    },
+};
+
+// Executions averaged into the drift metric
+const WINDOW: usize = 50;
+// Fewer samples than this never raise the drift alarm
+const MIN_SAMPLES: usize = 10;
+
+// Net amount of `token` a transaction moved to `account`, from its ERC20
+// Transfer logs: transfers in minus transfers out. Flash loans net out, leaving
+// what the trade actually kept.
+pub fn net_received(receipt: &TransactionReceipt, token: Address, account: Address) -> I256 {
+    let transfer = H256::from(keccak256("Transfer(address,address,uint256)"));
+    let mut net = I256::zero();
+    for log in &receipt.logs {
+        if log.address != token || log.topics.len() != 3 || log.topics[0] != transfer || log.data.len() != 32 {
+            continue;
+        }
+        let amount = I256::from_raw(U256::from_big_endian(&log.data));
+        if Address::from(log.topics[2]) == account {
+            net += amount;
+        }
+        if Address::from(log.topics[1]) == account {
+            net -= amount;
+        }
+    }
+    net
+}
+
+// Profit the evaluator predicted against what the receipt shows, both before gas
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct Reconciliation {
+    pub estimated: I256,
+    pub realized: I256,
+}
+
+impl Reconciliation {
+    pub fn delta(&self) -> I256 {
+        self.realized - self.estimated
+    }
+
+    // Delta relative to the estimate; negative when it was too optimistic
+    pub fn drift_bps(&self) -> i64 {
+        if self.estimated.is_zero() {
+            return 0;
+        }
+        i64::try_from(self.delta() * I256::from(10_000) / self.estimated.abs()).unwrap_or(i64::MIN)
+    }
+}
+
+// Rolling mean of recent drift, to catch estimates that are systematically off
+// rather than one unlucky trade
+pub struct DriftTracker {
+    // Alarm when the mean falls below minus this many bps
+    limit_bps: f64,
+    samples: Mutex<VecDeque<i64>>,
+    alarmed: AtomicBool,
+}
+
+impl DriftTracker {
+    pub fn new(limit_bps: f64) -> Self {
+        Self {
+            limit_bps,
+            samples: Mutex::new(VecDeque::with_capacity(WINDOW)),
+            alarmed: AtomicBool::new(false),
+        }
+    }
+
+    // Adds a sample and returns the mean over the window, with whether the
+    // alarm just tripped. It trips once and re-arms when the mean recovers.
+    pub fn record(&self, drift_bps: i64) -> (f64, bool) {
+        let mut samples = self.samples.lock().unwrap();
+        if samples.len() == WINDOW {
+            samples.pop_front();
+        }
+        samples.push_back(drift_bps);
+        let mean = samples.iter().sum::<i64>() as f64 / samples.len() as f64;
+
+        let over = samples.len() >= MIN_SAMPLES && mean < -self.limit_bps;
+        let was_over = self.alarmed.swap(over, Ordering::Relaxed);
+        (mean, over && !was_over)
+    }
+}
@@ -0,0 +1,101 @@
+use futures::StreamExt;
+use redis::{
+    aio::Connection,
+    streams::{StreamMaxlen, StreamReadOptions, StreamReadReply},
+    AsyncCommands, Client, RedisResult,
+};
+use std::sync::Arc;
+use tracing::warn;
+
+use crate::{errors::Result, feed::OpportunityFeed, models::ArbitrageOpportunity};
+
+// Entries kept in the stream; older ones are trimmed
+const MAX_LEN: usize = 10_000;
+const FIELD: &str = "opportunity";
+// How long a read waits for new entries
+const BLOCK_MS: usize = 5_000;
+
+// Hands opportunities from a scanning process to separate executor processes
+// through a Redis stream. Executors share a consumer group, so each
+// opportunity is delivered to exactly one of them.
+pub struct OpportunityStream {
+    client: Arc<Client>,
+    key: String,
+    group: String,
+}
+
+impl OpportunityStream {
+    pub fn new(client: Arc<Client>, key: &str, group: &str) -> Self {
+        Self {
+            client,
+            key: key.to_string(),
+            group: group.to_string(),
+        }
+    }
+
+    // Appends everything published on `feed` until it closes
+    pub async fn publish(self, feed: OpportunityFeed) -> Result<()> {
+        let mut conn = self.client.get_async_connection().await?;
+        let mut opportunities = Box::pin(feed.subscribe());
+        while let Some(opportunity) = opportunities.next().await {
+            let Ok(json) = serde_json::to_string(&opportunity) else {
+                continue;
+            };
+            let added: RedisResult<String> = conn
+                .xadd_maxlen(&self.key, StreamMaxlen::Approx(MAX_LEN), "*", &[(FIELD, json)])
+                .await;
+            if let Err(e) = added {
+                warn!(stream = %self.key, error = %e, "⚠️ Publishing opportunity failed");
+            }
+        }
+        Ok(())
+    }
+
+    // A connection for `join`, `next_batch` and `ack`. Reads block, so it
+    // shouldn't be shared with other Redis traffic.
+    pub async fn connect(&self) -> Result<Connection> {
+        Ok(self.client.get_async_connection().await?)
+    }
+
+    // Create the stream and consumer group if needed; a new group starts at
+    // new entries only
+    pub async fn join(&self, conn: &mut Connection) -> Result<()> {
+        let created: RedisResult<()> = conn.xgroup_create_mkstream(&self.key, &self.group, "$").await;
+        match created {
+            Err(e) if e.code() != Some("BUSYGROUP") => Err(e.into()),
+            _ => Ok(()),
+        }
+    }
+
+    // Entries not yet delivered to any consumer in the group, waiting briefly
+    // when there are none
+    pub async fn next_batch(
+        &self,
+        conn: &mut Connection,
+        consumer: &str,
+        count: usize,
+    ) -> Result<Vec<(String, ArbitrageOpportunity)>> {
+        let options = StreamReadOptions::default()
+            .group(&self.group, consumer)
+            .block(BLOCK_MS)
+            .count(count);
+        let reply: StreamReadReply = conn.xread_options(&[&self.key], &[">"], &options).await?;
+
+        let mut batch = Vec::new();
+        for entry in reply.keys.into_iter().flat_map(|k| k.ids) {
+            let Some(json) = entry.get::<String>(FIELD) else {
+                continue;
+            };
+            match serde_json::from_str(&json) {
+                Ok(opportunity) => batch.push((entry.id, opportunity)),
+                Err(e) => warn!(id = %entry.id, error = %e, "⚠️ Undecodable stream entry"),
+            }
+        }
+        Ok(batch)
+    }
+
+    pub async fn ack(&self, conn: &mut Connection, id: &str) -> Result<()> {
+        let _: i64 = conn.xack(&self.key, &self.group, &[id]).await?;
+        Ok(())
+    }
+}
@@ -0,0 +1,262 @@
+use ethers::{
+    prelude::*,
+    providers::{JsonRpcClient, Provider},
+    types::{Address, U256, U64},
+};
+use sqlx::{
+    sqlite::{SqliteConnectOptions, SqlitePool, SqlitePoolOptions},
+    Row,
+};
+use std::str::FromStr;
+use tracing::{info, warn};
+
+use crate::{
+    dex::{DexHandler, DexManager, TokenCache},
+    errors::{BotError, Result},
+    models::{DexPool, DexType, Token},
+};
+
+// Blocks per eth_getLogs request when following factory events
+const LOG_CHUNK: u64 = 5_000;
+
+// Pools discovered on any venue, persisted so startup doesn't re-walk factories.
+// Reserves aren't stored; they are refreshed before use.
+pub struct PoolRegistry {
+    db: SqlitePool,
+}
+
+impl PoolRegistry {
+    pub async fn open(path: &str) -> Result<Self> {
+        let options = SqliteConnectOptions::from_str(&format!("sqlite://{}", path))?.create_if_missing(true);
+        let db = SqlitePoolOptions::new().max_connections(4).connect_with(options).await?;
+
+        sqlx::query(
+            "CREATE TABLE IF NOT EXISTS pools (
+                address TEXT NOT NULL,
+                dex TEXT NOT NULL,
+                token0 TEXT NOT NULL,
+                symbol0 TEXT NOT NULL,
+                decimals0 INTEGER NOT NULL,
+                token1 TEXT NOT NULL,
+                symbol1 TEXT NOT NULL,
+                decimals1 INTEGER NOT NULL,
+                fee_bps INTEGER NOT NULL,
+                state TEXT NOT NULL,
+                -- Balancer pools hold several pairs under one address
+                PRIMARY KEY (address, token0, token1)
+            )",
+        )
+        .execute(&db)
+        .await?;
+        // Last block whose factory events have been applied, per venue
+        sqlx::query("CREATE TABLE IF NOT EXISTS sync_state (dex TEXT PRIMARY KEY, block INTEGER NOT NULL)")
+            .execute(&db)
+            .await?;
+
+        let registry = Self { db };
+        // Token metadata of registered pools doesn't need reading from chain again
+        TokenCache::global().seed(registry.tokens().await?);
+        Ok(registry)
+    }
+
+    pub async fn upsert(&self, pool: &DexPool) -> Result<()> {
+        sqlx::query(
+            "INSERT INTO pools (address, dex, token0, symbol0, decimals0, token1, symbol1, decimals1, fee_bps, state)
+             VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
+             ON CONFLICT(address, token0, token1) DO UPDATE SET fee_bps = excluded.fee_bps, state = excluded.state",
+        )
+        .bind(format!("{:?}", pool.address))
+        .bind(encode(&pool.dex)?)
+        .bind(format!("{:?}", pool.token0.address))
+        .bind(&pool.token0.symbol)
+        .bind(pool.token0.decimals as i64)
+        .bind(format!("{:?}", pool.token1.address))
+        .bind(&pool.token1.symbol)
+        .bind(pool.token1.decimals as i64)
+        .bind(pool.fee_bps as i64)
+        .bind(encode(&pool.state)?)
+        .execute(&self.db)
+        .await?;
+        Ok(())
+    }
+
+    pub async fn load_all(&self) -> Result<Vec<DexPool>> {
+        let rows = sqlx::query("SELECT * FROM pools").fetch_all(&self.db).await?;
+        let mut pools = Vec::with_capacity(rows.len());
+
+        for row in rows {
+            let token = |address: &str, symbol: &str, decimals: &str| -> Result<Token> {
+                Ok(Token {
+                    address: parse_address(row.try_get(address)?)?,
+                    symbol: row.try_get(symbol)?,
+                    decimals: row.try_get::<i64, _>(decimals)? as u8,
+                    transfer_tax_bps: 0,
+                })
+            };
+            pools.push(DexPool {
+                address: parse_address(row.try_get("address")?)?,
+                dex: decode(row.try_get("dex")?)?,
+                token0: token("token0", "symbol0", "decimals0")?,
+                token1: token("token1", "symbol1", "decimals1")?,
+                reserve0: U256::zero(),
+                reserve1: U256::zero(),
+                fee_bps: row.try_get::<i64, _>("fee_bps")? as u32,
+                block: U64::zero(),
+                state: decode(row.try_get("state")?)?,
+            });
+        }
+
+        Ok(pools)
+    }
+
+    // Every token appearing in a registered pool
+    pub async fn tokens(&self) -> Result<Vec<Token>> {
+        let rows = sqlx::query(
+            "SELECT token0 AS address, symbol0 AS symbol, decimals0 AS decimals FROM pools
+             UNION SELECT token1, symbol1, decimals1 FROM pools",
+        )
+        .fetch_all(&self.db)
+        .await?;
+
+        rows.iter()
+            .map(|row| {
+                Ok(Token {
+                    address: parse_address(row.try_get("address")?)?,
+                    symbol: row.try_get("symbol")?,
+                    decimals: row.try_get::<i64, _>("decimals")? as u8,
+                    transfer_tax_bps: 0,
+                })
+            })
+            .collect()
+    }
+
+    pub async fn count(&self) -> Result<i64> {
+        Ok(sqlx::query("SELECT COUNT(*) FROM pools").fetch_one(&self.db).await?.try_get(0)?)
+    }
+
+    pub async fn checkpoint(&self, dex: &DexType) -> Result<Option<U64>> {
+        self.read_checkpoint(&encode(dex)?).await
+    }
+
+    pub async fn set_checkpoint(&self, dex: &DexType, block: U64) -> Result<()> {
+        self.write_checkpoint(&encode(dex)?, block).await
+    }
+
+    async fn read_checkpoint(&self, key: &str) -> Result<Option<U64>> {
+        let row = sqlx::query("SELECT block FROM sync_state WHERE dex = ?")
+            .bind(key)
+            .fetch_optional(&self.db)
+            .await?;
+        Ok(row.map(|r| r.try_get::<i64, _>("block")).transpose()?.map(|b| U64::from(b as u64)))
+    }
+
+    async fn write_checkpoint(&self, key: &str, block: U64) -> Result<()> {
+        sqlx::query(
+            "INSERT INTO sync_state (dex, block) VALUES (?, ?)
+             ON CONFLICT(dex) DO UPDATE SET block = excluded.block",
+        )
+        .bind(key)
+        .bind(block.as_u64() as i64)
+        .execute(&self.db)
+        .await?;
+        Ok(())
+    }
+
+    // Apply factory events since each venue's checkpoint, up to `to_block`.
+    // A venue seen for the first time (and never backfilled) starts at
+    // `to_block`: existing pools come from discovery or `backfill`.
+    pub async fn sync<P: JsonRpcClient>(&self, provider: &Provider<P>, dex: &DexManager, to_block: U64) -> Result<usize> {
+        let mut added = 0;
+        for handler in dex.handlers() {
+            let key = encode(&handler.dex_type())?;
+            // Continue from a finished backfill rather than leave a gap after it
+            let last = match self.read_checkpoint(&key).await? {
+                Some(block) => Some(block),
+                None => self.read_checkpoint(&format!("backfill:{}", key)).await?,
+            };
+            let Some(last) = last else {
+                self.write_checkpoint(&key, to_block).await?;
+                continue;
+            };
+            let from = last + 1;
+            added += self.sync_range(provider, handler.as_ref(), &key, from, to_block).await?;
+        }
+        Ok(added)
+    }
+
+    // Every pool each factory has ever created, from its deployment block up to
+    // where live sync took over (or `to_block` if it hasn't started). Progress is
+    // checkpointed separately, so an interrupted backfill resumes where it stopped.
+    pub async fn backfill<P: JsonRpcClient>(&self, provider: &Provider<P>, dex: &DexManager, to_block: U64) -> Result<usize> {
+        let mut added = 0;
+        for handler in dex.handlers() {
+            let live = encode(&handler.dex_type())?;
+            let key = format!("backfill:{}", live);
+            let from = match self.read_checkpoint(&key).await? {
+                Some(block) => block + 1,
+                None => handler.factory_deployed_at(),
+            };
+            let to = self.read_checkpoint(&live).await?.unwrap_or(to_block).min(to_block);
+            if from > to {
+                continue;
+            }
+
+            info!(dex = %handler.dex_type(), from = %from, to = %to, "🗂️ Backfilling pools");
+            added += self.sync_range(provider, handler.as_ref(), &key, from, to).await?;
+        }
+        Ok(added)
+    }
+
+    // Chunked walk over one venue's factory events, checkpointing `key` after each chunk
+    async fn sync_range<P: JsonRpcClient>(
+        &self,
+        provider: &Provider<P>,
+        handler: &dyn DexHandler,
+        key: &str,
+        from: U64,
+        to: U64,
+    ) -> Result<usize> {
+        let Some(filter) = handler.pool_created_filter() else {
+            return Ok(0);
+        };
+        let dex = handler.dex_type();
+        let mut added = 0;
+        let mut start = from;
+
+        while start <= to {
+            let end = (start + LOG_CHUNK - 1).min(to);
+            let logs = provider.get_logs(&filter.clone().from_block(start).to_block(end)).await?;
+
+            for log in logs {
+                let Some(address) = handler.decode_pool_created(&log) else { continue };
+                match handler.load_pool(address).await {
+                    Ok(pool) => {
+                        self.upsert(&pool).await?;
+                        added += 1;
+                    }
+                    // Non-standard tokens can't be described; skip the pool, not the sync
+                    Err(e) => warn!(%dex, pool = ?address, error = %e, "⚠️ Skipping pool"),
+                }
+            }
+
+            self.write_checkpoint(key, end).await?;
+            start = end + 1;
+        }
+
+        Ok(added)
+    }
+}
+
+fn encode<T: serde::Serialize>(value: &T) -> Result<String> {
+    serde_json::to_string(value).map_err(|e| BotError::Config(format!("registry encode: {}", e)))
+}
+
+fn decode<T: serde::de::DeserializeOwned>(value: String) -> Result<T> {
+    serde_json::from_str(&value).map_err(|e| BotError::Config(format!("registry decode: {}", e)))
+}
+
+fn parse_address(value: String) -> Result<Address> {
+    value
+        .parse()
+        .map_err(|_| BotError::Config(format!("registry holds invalid address {}", value)))
+}
@@ -0,0 +1,366 @@
+use dashmap::DashMap;
+use ethers::{
+    providers::{JsonRpcClient, Middleware, Provider},
+    types::{H256, U64},
+};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::{
+    collections::{BTreeMap, HashMap},
+    sync::{Arc, RwLock},
+};
+use tracing::{info, warn};
+
+use liquidation_bot::{
+    builders::{flashbots_signature, BuilderRegistry},
+    errors::{ProviderError, Result},
+    signer::WalletSigner,
+};
+
+// Weight of the latest outcome in `recent_inclusion`, so a builder that
+// stops including us falls down the ranking within a few dozen bundles
+const RECENT_WEIGHT: f64 = 0.1;
+
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct RelayStats {
+    pub submitted: u64,
+    pub simulated: u64,
+    pub considered: u64,
+    pub sealed: u64,
+    pub failed: u64,
+    // Target blocks of ours this builder won, whether or not it included us
+    pub blocks_won: u64,
+    // Inclusion rate weighted towards the latest bundles
+    pub recent_inclusion: f64,
+}
+
+impl RelayStats {
+    pub fn inclusion_rate(&self) -> f64 {
+        let resolved = self.sealed + self.failed;
+        if resolved == 0 {
+            return 0.0;
+        }
+        self.sealed as f64 / resolved as f64
+    }
+
+    fn record_outcome(&mut self, sealed: bool) {
+        let outcome = if sealed { 1.0 } else { 0.0 };
+        if self.sealed + self.failed == 0 {
+            self.recent_inclusion = outcome;
+        } else {
+            self.recent_inclusion += RECENT_WEIGHT * (outcome - self.recent_inclusion);
+        }
+        if sealed {
+            self.sealed += 1;
+        } else {
+            self.failed += 1;
+        }
+    }
+}
+
+// What became of a bundle once its target block passed, whichever builders it went to
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BundleOutcome {
+    Included,
+    // Landed, but the transaction reverted
+    Reverted,
+    // Target block passed without it
+    Expired,
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct BundleTotals {
+    pub submitted: u64,
+    pub included: u64,
+    pub reverted: u64,
+    pub expired: u64,
+}
+
+impl BundleTotals {
+    // Share of bundles that landed with a reverted transaction, among those resolved
+    pub fn revert_rate(&self) -> f64 {
+        let resolved = self.included + self.reverted + self.expired;
+        if resolved == 0 {
+            return 0.0;
+        }
+        self.reverted as f64 / resolved as f64
+    }
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SearcherReputation {
+    pub is_high_priority: bool,
+    #[serde(default)]
+    pub all_time_validator_payments: String,
+    #[serde(default)]
+    pub all_time_gas_simulated: String,
+    #[serde(default)]
+    pub last_7d_validator_payments: String,
+    #[serde(default)]
+    pub last_1d_validator_payments: String,
+}
+
+#[derive(Debug, Clone)]
+struct PendingBundle {
+    relay: String,
+    bundle_hash: H256,
+    // The bundle's last transaction, which only lands if the bundle does
+    tx_hash: H256,
+    target_block: U64,
+}
+
+// Tracks how each builder treats our bundles and our standing with Flashbots,
+// and decides which builders are worth submitting to. A bundle counts as
+// sealed by the builder whose block it landed in.
+pub struct RelayStatsTracker {
+    http: reqwest::Client,
+    signer: WalletSigner,
+    // Relay that serves flashbots_getUserStatsV2 / getBundleStatsV2
+    stats_endpoint: String,
+    builders: Arc<BuilderRegistry>,
+    relays: Vec<String>,
+    stats: DashMap<String, RelayStats>,
+    pending: RwLock<Vec<PendingBundle>>,
+    bundles: RwLock<BundleTotals>,
+    reputation: RwLock<Option<SearcherReputation>>,
+    // Relays below this inclusion rate are dropped once they have enough samples
+    min_inclusion_rate: f64,
+    min_samples: u64,
+}
+
+impl RelayStatsTracker {
+    pub fn new(stats_endpoint: String, builders: Arc<BuilderRegistry>, signer: WalletSigner) -> Self {
+        let relays = builders.names();
+        let stats = DashMap::new();
+        for relay in &relays {
+            stats.insert(relay.clone(), RelayStats::default());
+        }
+
+        Self {
+            http: reqwest::Client::new(),
+            signer,
+            stats_endpoint,
+            builders,
+            relays,
+            stats,
+            pending: RwLock::new(Vec::new()),
+            bundles: RwLock::new(BundleTotals::default()),
+            reputation: RwLock::new(None),
+            min_inclusion_rate: 0.05,
+            min_samples: 20,
+        }
+    }
+
+    pub fn record_submission(&self, relay: &str, bundle_hash: H256, tx_hash: H256, target_block: U64) {
+        self.stats.entry(relay.to_string()).or_default().submitted += 1;
+        let mut pending = self.pending.write().unwrap();
+        // The same bundle fanned out to several builders counts once
+        if !pending.iter().any(|b| b.tx_hash == tx_hash) {
+            self.bundles.write().unwrap().submitted += 1;
+        }
+        pending.push(PendingBundle {
+            relay: relay.to_string(),
+            bundle_hash,
+            tx_hash,
+            target_block,
+        });
+    }
+
+    // Resolve bundles whose target block has passed; returns each one's
+    // transaction and what became of it
+    pub async fn poll<P: JsonRpcClient>(&self, provider: &Provider<P>, current_block: U64) -> Result<Vec<(H256, BundleOutcome)>> {
+        let due: Vec<PendingBundle> = {
+            let mut pending = self.pending.write().unwrap();
+            let (due, waiting) = pending
+                .drain(..)
+                .partition(|b| b.target_block < current_block);
+            *pending = waiting;
+            due
+        };
+
+        let mut resolved = Vec::new();
+        let mut by_block: BTreeMap<U64, Vec<PendingBundle>> = BTreeMap::new();
+        for bundle in due {
+            by_block.entry(bundle.target_block).or_default().push(bundle);
+        }
+
+        for (number, bundles) in by_block {
+            let Some(block) = provider
+                .get_block(number)
+                .await
+                .map_err(|e| ProviderError::Rpc(e.to_string()))?
+            else {
+                continue;
+            };
+            let winner = self.builders.identify(&block.extra_data).map(str::to_string);
+            if let Some(winner) = &winner {
+                self.stats.entry(winner.clone()).or_default().blocks_won += 1;
+            }
+
+            // One receipt per bundle, shared by every builder it went to
+            let mut outcomes: HashMap<H256, BundleOutcome> = HashMap::new();
+            for bundle in &bundles {
+                if outcomes.contains_key(&bundle.tx_hash) {
+                    continue;
+                }
+                let receipt = provider
+                    .get_transaction_receipt(bundle.tx_hash)
+                    .await
+                    .map_err(|e| ProviderError::Rpc(e.to_string()))?;
+                let outcome = match receipt {
+                    Some(r) if r.status == Some(U64::one()) => BundleOutcome::Included,
+                    Some(_) => BundleOutcome::Reverted,
+                    None => BundleOutcome::Expired,
+                };
+                self.record_outcome(bundle, outcome, winner.as_deref());
+                outcomes.insert(bundle.tx_hash, outcome);
+                resolved.push((bundle.tx_hash, outcome));
+            }
+
+            for bundle in bundles {
+                let included = outcomes.get(&bundle.tx_hash) != Some(&BundleOutcome::Expired);
+                // Blocks from unrecognised builders credit every builder we sent to
+                let sealed = included && winner.as_ref().map_or(true, |w| w == &bundle.relay);
+                let details = self.bundle_stats(&bundle).await;
+
+                let mut stats = self.stats.entry(bundle.relay.clone()).or_default();
+                if let Some(v) = details {
+                    if v.get("isSimulated").and_then(Value::as_bool).unwrap_or(false) {
+                        stats.simulated += 1;
+                    }
+                    if non_empty(&v, "consideredByBuildersAt") {
+                        stats.considered += 1;
+                    }
+                }
+                stats.record_outcome(sealed);
+            }
+        }
+
+        Ok(resolved)
+    }
+
+    fn record_outcome(&self, bundle: &PendingBundle, outcome: BundleOutcome, winner: Option<&str>) {
+        let mut totals = self.bundles.write().unwrap();
+        match outcome {
+            BundleOutcome::Included => {
+                totals.included += 1;
+                info!(bundle = ?bundle.bundle_hash, block = %bundle.target_block, builder = ?winner, "🎯 Bundle included");
+            }
+            BundleOutcome::Reverted => {
+                totals.reverted += 1;
+                warn!(bundle = ?bundle.bundle_hash, block = %bundle.target_block, builder = ?winner, "↩️ Bundle landed reverted");
+            }
+            BundleOutcome::Expired => totals.expired += 1,
+        }
+    }
+
+    // Simulation and builder pickup, which only the Flashbots relay reports
+    async fn bundle_stats(&self, bundle: &PendingBundle) -> Option<Value> {
+        let relay = self.builders.builders().iter().find(|b| b.name == bundle.relay)?;
+        if relay.url != self.stats_endpoint {
+            return None;
+        }
+        let result = self
+            .signed_request(
+                "flashbots_getBundleStatsV2",
+                json!([{
+                    "bundleHash": bundle.bundle_hash,
+                    "blockNumber": bundle.target_block,
+                }]),
+            )
+            .await;
+        match result {
+            Ok(v) => Some(v),
+            Err(e) => {
+                warn!(bundle = ?bundle.bundle_hash, error = %e, "⚠️ Bundle stats unavailable");
+                None
+            }
+        }
+    }
+
+    pub async fn refresh_reputation(&self, current_block: U64) -> Result<SearcherReputation> {
+        let value = self
+            .signed_request("flashbots_getUserStatsV2", json!([{ "blockNumber": current_block }]))
+            .await?;
+        let reputation: SearcherReputation = serde_json::from_value(value)
+            .map_err(|e| ProviderError::Rpc(format!("bad user stats: {}", e)))?;
+
+        *self.reputation.write().unwrap() = Some(reputation.clone());
+        Ok(reputation)
+    }
+
+    // Builders ordered by recent inclusion; poor performers are dropped once they have enough samples
+    pub fn preferred_relays(&self) -> Vec<String> {
+        let mut ranked: Vec<(String, RelayStats)> = self
+            .relays
+            .iter()
+            .map(|r| (r.clone(), self.stats.get(r).map(|s| s.clone()).unwrap_or_default()))
+            .collect();
+
+        ranked.sort_by(|a, b| {
+            b.1.recent_inclusion
+                .partial_cmp(&a.1.recent_inclusion)
+                .unwrap_or(std::cmp::Ordering::Equal)
+        });
+
+        let selected: Vec<String> = ranked
+            .iter()
+            .filter(|(_, s)| s.submitted < self.min_samples || s.recent_inclusion >= self.min_inclusion_rate)
+            .map(|(r, _)| r.clone())
+            .collect();
+
+        // Never end up with nowhere to submit
+        if selected.is_empty() {
+            ranked.into_iter().take(1).map(|(r, _)| r).collect()
+        } else {
+            selected
+        }
+    }
+
+    pub fn stats(&self) -> Vec<(String, RelayStats)> {
+        self.stats.iter().map(|e| (e.key().clone(), e.value().clone())).collect()
+    }
+
+    pub fn bundles(&self) -> BundleTotals {
+        self.bundles.read().unwrap().clone()
+    }
+
+    pub fn reputation(&self) -> Option<SearcherReputation> {
+        self.reputation.read().unwrap().clone()
+    }
+
+    // Flashbots RPC methods require X-Flashbots-Signature over the body hash
+    async fn signed_request(&self, method: &str, params: Value) -> Result<Value> {
+        let body = json!({
+            "jsonrpc": "2.0",
+            "id": 1,
+            "method": method,
+            "params": params,
+        })
+        .to_string();
+
+        let response: Value = self
+            .http
+            .post(&self.stats_endpoint)
+            .header("Content-Type", "application/json")
+            .header("X-Flashbots-Signature", flashbots_signature(&self.signer, &body).await?)
+            .body(body)
+            .send()
+            .await
+            .map_err(|e| ProviderError::Connection(e.to_string()))?
+            .json()
+            .await
+            .map_err(|e| ProviderError::Rpc(e.to_string()))?;
+
+        if let Some(err) = response.get("error") {
+            return Err(ProviderError::Rpc(err.to_string()).into());
+        }
+
+        Ok(response.get("result").cloned().unwrap_or(Value::Null))
+    }
+}
+
+fn non_empty(v: &Value, key: &str) -> bool {
+    v.get(key).and_then(Value::as_array).map(|a| !a.is_empty()).unwrap_or(false)
+}
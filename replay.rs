@@ -0,0 +1,188 @@
+use async_trait::async_trait;
+use ethers::providers::{JsonRpcClient, JsonRpcError, ProviderError, RpcError};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use serde_json::Value;
+use std::{
+    collections::{HashMap, VecDeque},
+    fmt::Debug,
+    fs::{File, OpenOptions},
+    io::{BufRead, BufReader, Write},
+    path::Path,
+    sync::Mutex,
+};
+use thiserror::Error;
+
+// One JSON-RPC exchange, stored as a line of JSON
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct Exchange {
+    method: String,
+    params: Value,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    result: Option<Value>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    error: Option<String>,
+}
+
+#[derive(Debug, Error)]
+pub enum ReplayError {
+    #[error(transparent)]
+    Provider(#[from] ProviderError),
+
+    #[error("recording: {0}")]
+    Io(#[from] std::io::Error),
+
+    #[error(transparent)]
+    Serde(#[from] serde_json::Error),
+
+    // The call failed the same way when it was recorded
+    #[error("recorded error: {0}")]
+    Recorded(String),
+
+    #[error("no recorded response for {method} {params}")]
+    Missing { method: String, params: String },
+}
+
+impl RpcError for ReplayError {
+    fn as_error_response(&self) -> Option<&JsonRpcError> {
+        match self {
+            ReplayError::Provider(e) => e.as_error_response(),
+            _ => None,
+        }
+    }
+
+    fn as_serde_error(&self) -> Option<&serde_json::Error> {
+        match self {
+            ReplayError::Provider(e) => e.as_serde_error(),
+            ReplayError::Serde(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+impl From<ReplayError> for ProviderError {
+    fn from(e: ReplayError) -> Self {
+        ProviderError::JsonRpcClientError(Box::new(e))
+    }
+}
+
+// Passes every request through to `inner` and appends the exchange to a file,
+// so a session can later be served by `Replayer` without network access
+#[derive(Debug)]
+pub struct Recorder<P> {
+    inner: P,
+    file: Mutex<File>,
+}
+
+impl<P: JsonRpcClient> Recorder<P> {
+    pub fn create(inner: P, path: impl AsRef<Path>) -> Result<Self, ReplayError> {
+        let file = OpenOptions::new().create(true).append(true).open(path)?;
+        Ok(Self { inner, file: Mutex::new(file) })
+    }
+
+    fn append(&self, exchange: &Exchange) -> Result<(), ReplayError> {
+        let mut line = serde_json::to_vec(exchange)?;
+        line.push(b'\n');
+        self.file.lock().unwrap().write_all(&line)?;
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl<P: JsonRpcClient> JsonRpcClient for Recorder<P> {
+    type Error = ReplayError;
+
+    async fn request<T, R>(&self, method: &str, params: T) -> Result<R, Self::Error>
+    where
+        T: Debug + Serialize + Send + Sync,
+        R: DeserializeOwned + Send,
+    {
+        let params = serde_json::to_value(&params)?;
+        let response = self.inner.request::<_, Value>(method, &params).await;
+
+        let mut exchange = Exchange {
+            method: method.to_string(),
+            params,
+            result: None,
+            error: None,
+        };
+        match response {
+            Ok(result) => {
+                exchange.result = Some(result.clone());
+                self.append(&exchange)?;
+                Ok(serde_json::from_value(result)?)
+            }
+            Err(e) => {
+                let e: ProviderError = e.into();
+                exchange.error = Some(e.to_string());
+                self.append(&exchange)?;
+                Err(e.into())
+            }
+        }
+    }
+}
+
+// Serves responses captured by `Recorder`. Identical requests are answered in
+// the order they were recorded, repeating the last answer once exhausted.
+#[derive(Debug)]
+pub struct Replayer {
+    responses: Mutex<HashMap<String, VecDeque<Exchange>>>,
+}
+
+impl Replayer {
+    pub fn open(path: impl AsRef<Path>) -> Result<Self, ReplayError> {
+        let mut responses: HashMap<String, VecDeque<Exchange>> = HashMap::new();
+        for line in BufReader::new(File::open(path)?).lines() {
+            let line = line?;
+            if line.trim().is_empty() {
+                continue;
+            }
+            let exchange: Exchange = serde_json::from_str(&line)?;
+            responses
+                .entry(key(&exchange.method, &exchange.params))
+                .or_default()
+                .push_back(exchange);
+        }
+        Ok(Self { responses: Mutex::new(responses) })
+    }
+}
+
+#[async_trait]
+impl JsonRpcClient for Replayer {
+    type Error = ReplayError;
+
+    async fn request<T, R>(&self, method: &str, params: T) -> Result<R, Self::Error>
+    where
+        T: Debug + Serialize + Send + Sync,
+        R: DeserializeOwned + Send,
+    {
+        let params = serde_json::to_value(&params)?;
+        let key = key(method, &params);
+
+        let (result, error) = {
+            let mut responses = self.responses.lock().unwrap();
+            let queue = responses
+                .get_mut(&key)
+                .filter(|q| !q.is_empty())
+                .ok_or_else(|| ReplayError::Missing {
+                    method: method.to_string(),
+                    params: params.to_string(),
+                })?;
+            let exchange = if queue.len() > 1 {
+                queue.pop_front().unwrap()
+            } else {
+                queue[0].clone()
+            };
+            (exchange.result, exchange.error)
+        };
+
+        match (result, error) {
+            (_, Some(error)) => Err(ReplayError::Recorded(error)),
+            (Some(result), None) => Ok(serde_json::from_value(result)?),
+            (None, None) => Ok(serde_json::from_value(Value::Null)?),
+        }
+    }
+}
+
+fn key(method: &str, params: &Value) -> String {
+    format!("{} {}", method, params)
+}
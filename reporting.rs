@@ -0,0 +1,187 @@
+use chrono::{DateTime, NaiveDate, Utc};
+use ethers::{
+    types::{H256, I256, U256},
+    utils::format_ether,
+};
+use redis::{AsyncCommands, Client as RedisClient};
+use serde::{Deserialize, Serialize};
+use std::{collections::BTreeMap, fmt::Write as _, path::Path};
+
+use liquidation_bot::{
+    errors::{BotError, Result},
+    reconcile::Reconciliation,
+};
+
+// Days of execution records kept in Redis
+const RETENTION_SECS: i64 = 30 * 86_400;
+
+// One execution attempt that reached submission
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ExecutionRecord {
+    pub timestamp: DateTime<Utc>,
+    // "liquidation" or "arbitrage"
+    pub strategy: String,
+    // Lending protocol, or "buy dex -> sell dex" for arbitrage
+    pub protocol: String,
+    pub tx_hash: Option<H256>,
+    pub success: bool,
+    // After gas, in wei; negative for a loss
+    pub profit: I256,
+    pub gas_cost: U256,
+    // Estimated against realized profit, when the receipt could be read
+    #[serde(default)]
+    pub reconciliation: Option<Reconciliation>,
+}
+
+impl ExecutionRecord {
+    pub fn new(strategy: &str, protocol: &str, tx_hash: Option<H256>, success: bool, profit: I256, gas_cost: U256) -> Self {
+        Self {
+            timestamp: Utc::now(),
+            strategy: strategy.to_string(),
+            protocol: protocol.to_string(),
+            tx_hash,
+            success,
+            profit,
+            gas_cost,
+            reconciliation: None,
+        }
+    }
+
+    pub fn with_reconciliation(mut self, reconciliation: Option<Reconciliation>) -> Self {
+        self.reconciliation = reconciliation;
+        self
+    }
+}
+
+fn day_key(date: NaiveDate) -> String {
+    format!("executions:{}", date)
+}
+
+// Appended to the list for its UTC day
+pub async fn store(redis: &RedisClient, record: &ExecutionRecord) -> Result<()> {
+    let json = serde_json::to_string(record).map_err(|e| BotError::Config(e.to_string()))?;
+    let key = day_key(record.timestamp.date_naive());
+    let mut conn = redis.get_async_connection().await?;
+    let _: () = conn.rpush(&key, json).await?;
+    let _: () = conn.expire(&key, RETENTION_SECS).await?;
+    Ok(())
+}
+
+pub async fn load(redis: &RedisClient, date: NaiveDate) -> Result<Vec<ExecutionRecord>> {
+    let mut conn = redis.get_async_connection().await?;
+    let entries: Vec<String> = conn.lrange(day_key(date), 0, -1).await?;
+    Ok(entries.iter().filter_map(|e| serde_json::from_str(e).ok()).collect())
+}
+
+#[derive(Debug, Default, Serialize)]
+pub struct Totals {
+    pub executions: usize,
+    pub successful: usize,
+    pub profit: I256,
+    pub gas_spent: U256,
+}
+
+impl Totals {
+    fn add(&mut self, record: &ExecutionRecord) {
+        self.executions += 1;
+        self.successful += record.success as usize;
+        self.profit += record.profit;
+        self.gas_spent += record.gas_cost;
+    }
+
+    pub fn success_rate(&self) -> f64 {
+        if self.executions == 0 {
+            return 0.0;
+        }
+        self.successful as f64 / self.executions as f64 * 100.0
+    }
+}
+
+// Realized PnL for one UTC day
+#[derive(Debug, Serialize)]
+pub struct DailyReport {
+    pub date: NaiveDate,
+    pub totals: Totals,
+    pub success_rate: f64,
+    pub best: Option<ExecutionRecord>,
+    pub worst: Option<ExecutionRecord>,
+    pub by_protocol: BTreeMap<String, Totals>,
+}
+
+impl DailyReport {
+    pub fn build(date: NaiveDate, records: &[ExecutionRecord]) -> Self {
+        let mut totals = Totals::default();
+        let mut by_protocol: BTreeMap<String, Totals> = BTreeMap::new();
+        for record in records {
+            totals.add(record);
+            by_protocol.entry(record.protocol.clone()).or_default().add(record);
+        }
+
+        Self {
+            date,
+            success_rate: totals.success_rate(),
+            totals,
+            best: records.iter().max_by_key(|r| r.profit).cloned(),
+            worst: records.iter().min_by_key(|r| r.profit).cloned(),
+            by_protocol,
+        }
+    }
+
+    // One row per protocol, then the day's total
+    pub fn to_csv(&self) -> String {
+        let mut csv = String::from("date,protocol,executions,successful,success_rate,profit_eth,gas_eth\n");
+        let rows = self.by_protocol.iter().map(|(p, t)| (p.as_str(), t)).chain([("total", &self.totals)]);
+        for (protocol, totals) in rows {
+            let _ = writeln!(
+                csv,
+                "{},{},{},{},{:.1},{},{}",
+                self.date,
+                protocol,
+                totals.executions,
+                totals.successful,
+                totals.success_rate(),
+                format_signed(totals.profit),
+                format_ether(totals.gas_spent),
+            );
+        }
+        csv
+    }
+
+    // Short text for the alert channels
+    pub fn summary(&self) -> String {
+        let mut text = format!(
+            "📅 Daily report {}\nExecutions: {} ({:.1}% successful)\nRealized profit: {} ETH\nGas spent: {} ETH",
+            self.date,
+            self.totals.executions,
+            self.success_rate,
+            format_signed(self.totals.profit),
+            format_ether(self.totals.gas_spent),
+        );
+        if let (Some(best), Some(worst)) = (&self.best, &self.worst) {
+            let _ = write!(
+                text,
+                "\nBest: {} ETH ({})\nWorst: {} ETH ({})",
+                format_signed(best.profit),
+                best.protocol,
+                format_signed(worst.profit),
+                worst.protocol,
+            );
+        }
+        for (protocol, totals) in &self.by_protocol {
+            let _ = write!(text, "\n• {}: {} trades, {} ETH", protocol, totals.executions, format_signed(totals.profit));
+        }
+        text
+    }
+
+    // Writes <dir>/<date>.json and <dir>/<date>.csv
+    pub fn export(&self, dir: &Path) -> std::io::Result<()> {
+        std::fs::create_dir_all(dir)?;
+        let json = serde_json::to_string_pretty(self)?;
+        std::fs::write(dir.join(format!("{}.json", self.date)), json)?;
+        std::fs::write(dir.join(format!("{}.csv", self.date)), self.to_csv())
+    }
+}
+
+fn format_signed(amount: I256) -> String {
+    ethers::utils::format_units(amount, 18).unwrap_or_else(|_| amount.to_string())
+}
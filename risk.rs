@@ -0,0 +1,141 @@
+use chrono::{NaiveDate, Utc};
+use ethers::types::{Address, U256, U64};
+use std::{collections::HashMap, sync::Mutex};
+use thiserror::Error;
+
+#[derive(Debug, Clone)]
+pub struct RiskLimits {
+    // Largest single trade, in USD (18 decimals)
+    pub max_notional: U256,
+    // Flash loans outstanding at once per token, in its base units; tokens
+    // not listed are uncapped
+    pub max_flash_loan: HashMap<Address, U256>,
+    pub max_executions_per_block: u32,
+    // Gas (wei) all executions together may spend per UTC day
+    pub daily_gas_budget: U256,
+}
+
+// What one execution puts at risk
+#[derive(Debug, Clone)]
+pub struct Exposure {
+    pub token: Address,
+    // USD (18 decimals), None when the token can't be priced
+    pub notional: Option<U256>,
+    // Borrowed for the duration of the execution, in `token` units
+    pub flash_loan: U256,
+    // Estimated gas cost in wei
+    pub gas: U256,
+}
+
+#[derive(Debug, Clone, Error)]
+pub enum RiskViolation {
+    #[error("notional {0} above limit {1}")]
+    Notional(U256, U256),
+
+    #[error("{open} of {token:?} flash-borrowed would exceed {cap}")]
+    FlashLoan { token: Address, open: U256, cap: U256 },
+
+    #[error("{0} executions already sent in block {1}")]
+    BlockExecutions(u32, U64),
+
+    #[error("daily gas budget {0} would be exceeded")]
+    DailyGas(U256),
+}
+
+// Limits handed out by `check`; must be returned via `settle`
+#[derive(Debug, Clone)]
+pub struct RiskPermit {
+    token: Address,
+    flash_loan: U256,
+    gas: U256,
+}
+
+struct RiskState {
+    block: U64,
+    executions_in_block: u32,
+    open_flash_loans: HashMap<Address, U256>,
+    day: NaiveDate,
+    gas_spent: U256,
+    gas_reserved: U256,
+}
+
+// Limits that hold across every strategy, checked before anything is signed.
+// Strategy budgets (CapitalAllocator) decide who gets to spend; these cap what
+// the wallet as a whole may do.
+pub struct RiskManager {
+    limits: RiskLimits,
+    state: Mutex<RiskState>,
+}
+
+impl RiskManager {
+    pub fn new(limits: RiskLimits) -> Self {
+        Self {
+            limits,
+            state: Mutex::new(RiskState {
+                block: U64::zero(),
+                executions_in_block: 0,
+                open_flash_loans: HashMap::new(),
+                day: Utc::now().date_naive(),
+                gas_spent: U256::zero(),
+                gas_reserved: U256::zero(),
+            }),
+        }
+    }
+
+    // Admit one execution in `block`, or say which limit it breaks
+    pub fn check(&self, block: U64, exposure: &Exposure) -> Result<RiskPermit, RiskViolation> {
+        if let Some(notional) = exposure.notional {
+            if notional > self.limits.max_notional {
+                return Err(RiskViolation::Notional(notional, self.limits.max_notional));
+            }
+        }
+
+        let mut state = self.state.lock().unwrap();
+        if block != state.block {
+            state.block = block;
+            state.executions_in_block = 0;
+        }
+        if state.executions_in_block >= self.limits.max_executions_per_block {
+            return Err(RiskViolation::BlockExecutions(state.executions_in_block, block));
+        }
+
+        let open = state.open_flash_loans.get(&exposure.token).copied().unwrap_or_default() + exposure.flash_loan;
+        if let Some(cap) = self.limits.max_flash_loan.get(&exposure.token) {
+            if open > *cap {
+                return Err(RiskViolation::FlashLoan {
+                    token: exposure.token,
+                    open,
+                    cap: *cap,
+                });
+            }
+        }
+
+        let today = Utc::now().date_naive();
+        if today != state.day {
+            state.day = today;
+            state.gas_spent = U256::zero();
+        }
+        if state.gas_spent + state.gas_reserved + exposure.gas > self.limits.daily_gas_budget {
+            return Err(RiskViolation::DailyGas(self.limits.daily_gas_budget));
+        }
+
+        state.executions_in_block += 1;
+        state.open_flash_loans.insert(exposure.token, open);
+        state.gas_reserved += exposure.gas;
+        Ok(RiskPermit {
+            token: exposure.token,
+            flash_loan: exposure.flash_loan,
+            gas: exposure.gas,
+        })
+    }
+
+    // Release a permit and book the gas actually spent
+    pub fn settle(&self, permit: RiskPermit, gas_spent: U256) {
+        let mut state = self.state.lock().unwrap();
+        if let Some(open) = state.open_flash_loans.get_mut(&permit.token) {
+            *open = open.saturating_sub(permit.flash_loan);
+        }
+        state.gas_reserved = state.gas_reserved.saturating_sub(permit.gas);
+        state.gas_spent += gas_spent;
+    }
+}
@@ -0,0 +1,324 @@
+use dashmap::DashMap;
+use ethers::{
+    abi::{self, Token as AbiToken},
+    prelude::*,
+    providers::{call_raw::RawCall, spoof, RpcError},
+    types::{transaction::eip2718::TypedTransaction, Address, Bytes, U256},
+};
+use std::{collections::HashSet, str::FromStr, sync::Arc};
+use tracing::warn;
+
+use crate::{
+    errors::{BotError, Result},
+    etherscan::EtherscanClient,
+    models::DexPool,
+    simulation::decode_revert,
+};
+
+abigen!(
+    UniswapV2Router,
+    r#"[
+        function getAmountsOut(uint256 amountIn, address[] path) external view returns (uint256[])
+        function swapExactETHForTokensSupportingFeeOnTransferTokens(uint256 amountOutMin, address[] path, address to, uint256 deadline) external payable
+        function swapExactTokensForETHSupportingFeeOnTransferTokens(uint256 amountIn, uint256 amountOutMin, address[] path, address to, uint256 deadline) external
+    ]"#
+);
+
+abigen!(
+    Erc20Approve,
+    r#"[function approve(address spender, uint256 amount) external returns (bool)]"#
+);
+
+pub const UNISWAP_V2_ROUTER: &str = "0x7a250d5630B4cF539739dF2C5dAcb4c659F2488D";
+const WETH: &str = "0xC02aaA39b223FE8D0A0e5C4F27eAD9083C756Cc2";
+// Round trips buy with this much ETH (0.1)
+const PROBE_WEI: u64 = 100_000_000_000_000_000;
+
+// Runtime code for a throwaway account that runs a batch of calls from its own
+// address, reverting with the first failure's data and otherwise returning the
+// last call's output. Calldata is a sequence of
+// abi.encode(target, value, data.length) ++ data records.
+const BATCH_CALLER: &str = "0x60005b368110156032578060400135808260600160003760006000826000856020013586355af115603d57016060016002565b3d600060003e3d6000f35b3d600060003e3d6000fd";
+// Where the batch caller is placed for a probe
+const PROBE_ACCOUNT: &str = "0x000000000000000000000000000000000000f00d";
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TokenVerdict {
+    Sellable,
+    // Buying or selling back reverted; carries the reason
+    Honeypot(String),
+    // No WETH pair on the router to test through
+    Untested,
+}
+
+// Screens tokens before their pools are scanned: buys a little through the
+// Uniswap V2 router and sells half of it straight back, all inside one
+// eth_call. Tokens that can be bought but not sold would otherwise surface as
+// large, unrealisable spreads.
+pub struct HoneypotDetector<M> {
+    provider: Arc<M>,
+    router: Address,
+    weth: Address,
+    verdicts: DashMap<Address, TokenVerdict>,
+}
+
+impl<M: Middleware + 'static> HoneypotDetector<M> {
+    pub fn new(provider: Arc<M>, router: Address, weth: Address) -> Self {
+        Self {
+            provider,
+            router,
+            weth,
+            verdicts: DashMap::new(),
+        }
+    }
+
+    pub fn mainnet(provider: Arc<M>) -> Self {
+        Self::new(provider, UNISWAP_V2_ROUTER.parse().unwrap(), WETH.parse().unwrap())
+    }
+
+    // Cached per process; a token's transfer rules rarely change
+    pub async fn check(&self, token: Address) -> Result<TokenVerdict> {
+        if token == self.weth {
+            return Ok(TokenVerdict::Sellable);
+        }
+        if let Some(verdict) = self.verdicts.get(&token) {
+            return Ok(verdict.clone());
+        }
+
+        let verdict = self.round_trip(token).await?;
+        if let TokenVerdict::Honeypot(reason) = &verdict {
+            warn!(?token, %reason, "🍯 Token can't be traded back");
+        }
+        self.verdicts.insert(token, verdict.clone());
+        Ok(verdict)
+    }
+
+    // False only for pools holding a confirmed honeypot; probe failures don't
+    // hide a pool
+    pub async fn is_tradeable(&self, pool: &DexPool) -> bool {
+        for token in [pool.token0.address, pool.token1.address] {
+            match self.check(token).await {
+                Ok(TokenVerdict::Honeypot(_)) => return false,
+                Ok(_) => {}
+                Err(e) => warn!(?token, error = %e, "⚠️ Honeypot check failed"),
+            }
+        }
+        true
+    }
+
+    async fn round_trip(&self, token: Address) -> Result<TokenVerdict> {
+        let router = UniswapV2Router::new(self.router, self.provider.clone());
+        let probe = U256::from(PROBE_WEI);
+        let account: Address = PROBE_ACCOUNT.parse().unwrap();
+
+        let Ok(amounts) = router.get_amounts_out(probe, vec![self.weth, token]).call().await else {
+            return Ok(TokenVerdict::Untested);
+        };
+        // Half the quoted output still covers buy taxes of up to 50%
+        let sell_amount = amounts.last().copied().unwrap_or_default() / 2;
+        if sell_amount.is_zero() {
+            return Ok(TokenVerdict::Untested);
+        }
+
+        let buy = router
+            .swap_exact_eth_for_tokens_supporting_fee_on_transfer_tokens(
+                U256::zero(),
+                vec![self.weth, token],
+                account,
+                U256::MAX,
+            )
+            .calldata()
+            .unwrap_or_default();
+        let approve = Erc20Approve::new(token, self.provider.clone())
+            .approve(self.router, U256::MAX)
+            .calldata()
+            .unwrap_or_default();
+        let sell = router
+            .swap_exact_tokens_for_eth_supporting_fee_on_transfer_tokens(
+                sell_amount,
+                U256::zero(),
+                vec![token, self.weth],
+                account,
+                U256::MAX,
+            )
+            .calldata()
+            .unwrap_or_default();
+
+        let mut state = spoof::state();
+        state.account(account).code(BATCH_CALLER.parse::<Bytes>().unwrap()).balance(probe);
+        let tx: TypedTransaction = TransactionRequest::new()
+            .to(account)
+            .data(batch(&[
+                (self.router, probe, buy),
+                (token, U256::zero(), approve),
+                (self.router, U256::zero(), sell),
+            ]))
+            .into();
+
+        match self.provider.provider().call_raw(&tx).state(&state).await {
+            Ok(_) => Ok(TokenVerdict::Sellable),
+            Err(e) => match e.as_error_response() {
+                Some(response) => {
+                    let reason = response
+                        .as_revert_data()
+                        .map(|d| decode_revert(&d))
+                        .unwrap_or_else(|| response.message.clone());
+                    Ok(TokenVerdict::Honeypot(reason))
+                }
+                None => Err(e.into()),
+            },
+        }
+    }
+}
+
+// What to do with a pool whose token fails a contract check
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ContractAction {
+    Allow,
+    // Trade it, but log the token once
+    Warn,
+    Skip,
+}
+
+impl FromStr for ContractAction {
+    type Err = String;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s.trim() {
+            "allow" => Ok(ContractAction::Allow),
+            "warn" => Ok(ContractAction::Warn),
+            "skip" => Ok(ContractAction::Skip),
+            other => Err(format!("unknown action {}, expected allow, warn or skip", other)),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ContractPolicy {
+    // Source not verified on Etherscan
+    pub unverified: ContractAction,
+    // Upgradeable proxy, whose rules can change under an open position
+    pub proxy: ContractAction,
+}
+
+impl Default for ContractPolicy {
+    fn default() -> Self {
+        Self {
+            unverified: ContractAction::Skip,
+            proxy: ContractAction::Warn,
+        }
+    }
+}
+
+impl ContractPolicy {
+    pub fn from_env() -> Result<Self> {
+        let action = |name: &str, default: ContractAction| match std::env::var(name) {
+            Ok(value) => value.parse().map_err(|e| BotError::Config(format!("{}: {}", name, e))),
+            Err(_) => Ok(default),
+        };
+        let defaults = Self::default();
+        Ok(Self {
+            unverified: action("UNVERIFIED_TOKENS", defaults.unverified)?,
+            proxy: action("PROXY_TOKENS", defaults.proxy)?,
+        })
+    }
+
+    // Nothing would be skipped or logged
+    pub fn allows_all(&self) -> bool {
+        self.unverified == ContractAction::Allow && self.proxy == ContractAction::Allow
+    }
+}
+
+// Screens tokens by their Etherscan source before their pools are scanned:
+// unverified contracts can hide anything, and a proxy's logic can be swapped
+// out. Trusted tokens, such as the wrapped native token and the chain's
+// stablecoins, which are often proxies themselves, aren't checked.
+pub struct ContractScreen {
+    etherscan: Arc<EtherscanClient>,
+    policy: ContractPolicy,
+    trusted: HashSet<Address>,
+    // Tokens already judged this process, so each is logged once
+    verdicts: DashMap<Address, bool>,
+}
+
+impl ContractScreen {
+    pub fn new(etherscan: Arc<EtherscanClient>, policy: ContractPolicy) -> Self {
+        Self {
+            etherscan,
+            policy,
+            trusted: HashSet::new(),
+            verdicts: DashMap::new(),
+        }
+    }
+
+    pub fn with_trusted(mut self, tokens: impl IntoIterator<Item = Address>) -> Self {
+        self.trusted.extend(tokens);
+        self
+    }
+
+    // Whether `token` may be traded under the policy. A failed lookup doesn't
+    // hide the token and is asked again next time.
+    pub async fn check(&self, token: Address) -> bool {
+        if self.trusted.contains(&token) || self.policy.allows_all() {
+            return true;
+        }
+        if let Some(verdict) = self.verdicts.get(&token) {
+            return *verdict;
+        }
+
+        let info = match self.etherscan.contract(token).await {
+            Ok(info) => info,
+            Err(e) => {
+                warn!(?token, error = %e, "⚠️ Contract verification check failed");
+                return true;
+            }
+        };
+        let mut tradeable = true;
+        if !info.verified {
+            match self.policy.unverified {
+                ContractAction::Skip => {
+                    warn!(?token, "📜 Token source isn't verified, skipping its pools");
+                    tradeable = false;
+                }
+                ContractAction::Warn => warn!(?token, "📜 Token source isn't verified"),
+                ContractAction::Allow => {}
+            }
+        }
+        if info.proxy && tradeable {
+            match self.policy.proxy {
+                ContractAction::Skip => {
+                    warn!(
+                        ?token,
+                        implementation = ?info.implementation,
+                        "📜 Token is an upgradeable proxy, skipping its pools"
+                    );
+                    tradeable = false;
+                }
+                ContractAction::Warn => {
+                    warn!(?token, implementation = ?info.implementation, "📜 Token is an upgradeable proxy")
+                }
+                ContractAction::Allow => {}
+            }
+        }
+        self.verdicts.insert(token, tradeable);
+        tradeable
+    }
+
+    // False for pools holding a token the policy skips
+    pub async fn is_tradeable(&self, pool: &DexPool) -> bool {
+        self.check(pool.token0.address).await && self.check(pool.token1.address).await
+    }
+}
+
+fn batch(calls: &[(Address, U256, Bytes)]) -> Bytes {
+    let mut out = Vec::new();
+    for (target, value, data) in calls {
+        out.extend(abi::encode(&[
+            AbiToken::Address(*target),
+            AbiToken::Uint(*value),
+            AbiToken::Uint(U256::from(data.len())),
+        ]));
+        out.extend_from_slice(data);
+    }
+    out.into()
+}
@@ -0,0 +1,140 @@
+use ethers::types::{Address, U256};
+
+use crate::{
+    math,
+    mempool::apply_swap,
+    models::{ArbitrageOpportunity, DexPool},
+};
+
+// Halvings when sizing the front-run; the range starts at 16x the pool's reserve
+const SEARCH_STEPS: usize = 128;
+
+// Who can see a public transaction before it's included
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Visibility {
+    // Rollups ordering transactions first come, first served, without a
+    // mempool anyone can watch
+    Sequencer,
+    PublicMempool,
+}
+
+impl Visibility {
+    pub fn public_on(chain_id: u64) -> Self {
+        match chain_id {
+            10 | 8453 | 42161 => Visibility::Sequencer,
+            _ => Visibility::PublicMempool,
+        }
+    }
+}
+
+// What the best sandwich around one of our swaps would take
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct SandwichRisk {
+    // Output we'd lose, in the swap's output token
+    pub loss: U256,
+    // The sandwicher's gain before gas, in the swap's input token
+    pub attacker_profit: U256,
+    // `loss` as a share of the output without the sandwich
+    pub loss_bps: u32,
+}
+
+// The sandwich around selling `amount_in` of `token_in` into `pool` with an
+// output floor of `min_out`. The front-run is the largest the floor lets
+// through, which is the sandwicher's best unless the pool's fee on its two
+// swaps eats the gain. A shallow pool or a loose floor both widen it. None for
+// venues that can't be simulated.
+pub fn sandwich(pool: &DexPool, token_in: Address, amount_in: U256, min_out: U256) -> Option<SandwichRisk> {
+    let token_out = pool.other(token_in)?.address;
+    let clean = apply_swap(&mut pool.clone(), token_in, amount_in)?;
+
+    // Our output behind a front-run of `size`, and what the back-run returns
+    let run = |size: U256| -> Option<(U256, U256)> {
+        let mut pool = pool.clone();
+        let bought = apply_swap(&mut pool, token_in, size)?;
+        let ours = apply_swap(&mut pool, token_in, amount_in)?;
+        let sold = apply_swap(&mut pool, token_out, bought)?;
+        Some((ours, sold))
+    };
+
+    let (reserve_in, _) = pool.reserves_for(token_in)?;
+    let mut low = U256::zero();
+    let mut high = reserve_in.saturating_mul(U256::from(16));
+    for _ in 0..SEARCH_STEPS {
+        if high - low <= U256::one() {
+            break;
+        }
+        let mid = low + (high - low) / 2;
+        match run(mid) {
+            Some((ours, _)) if ours >= min_out => low = mid,
+            _ => high = mid,
+        }
+    }
+    if low.is_zero() {
+        return Some(SandwichRisk::default());
+    }
+
+    let (ours, sold) = run(low)?;
+    if sold <= low {
+        return Some(SandwichRisk::default());
+    }
+    let loss = clean.saturating_sub(ours);
+    Some(SandwichRisk {
+        loss,
+        attacker_profit: sold - low,
+        loss_bps: math::mul_div(loss, U256::from(math::BPS), clean).map_or(math::BPS, |bps| bps.low_u32()),
+    })
+}
+
+// What a sandwich around either leg of `opportunity` would take, in its input
+// token, with each leg's floor `slippage_bps` under its quote as the executor
+// sets it. A searcher takes the better leg; the buy leg's loss is carried into
+// the input token at the sell leg's rate.
+pub fn arbitrage_loss(opportunity: &ArbitrageOpportunity, slippage_bps: u32) -> U256 {
+    let buy = sandwich(
+        &opportunity.buy_pool,
+        opportunity.token_in,
+        opportunity.amount_in,
+        math::less_fee(opportunity.intermediate_amount, slippage_bps),
+    )
+    .and_then(|r| math::mul_div(r.loss, opportunity.amount_out, opportunity.intermediate_amount))
+    .unwrap_or_default();
+    let sell = sandwich(
+        &opportunity.sell_pool,
+        opportunity.token_out,
+        opportunity.intermediate_amount,
+        math::less_fee(opportunity.amount_out, slippage_bps),
+    )
+    .map(|r| r.loss)
+    .unwrap_or_default();
+    buy.max(sell)
+}
+
+// Keeps executions a sandwich could take a large share of out of the public
+// mempool. Bundles are never seen before inclusion, so only public
+// submissions are checked.
+#[derive(Debug, Clone, Copy)]
+pub struct SandwichGuard {
+    pub visibility: Visibility,
+    // Largest share of the expected profit a public execution may expose
+    pub max_loss_share_bps: u32,
+}
+
+impl SandwichGuard {
+    pub fn from_env(chain_id: u64) -> Self {
+        Self {
+            visibility: Visibility::public_on(chain_id),
+            max_loss_share_bps: std::env::var("SANDWICH_MAX_LOSS_SHARE_BPS")
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(1_000),
+        }
+    }
+
+    // Whether sending publicly could lose more than the allowed share of
+    // `profit`; `loss` is in the same token
+    pub fn too_risky(&self, loss: U256, profit: U256) -> bool {
+        self.visibility == Visibility::PublicMempool
+            && !loss.is_zero()
+            && loss.full_mul(U256::from(math::BPS)) > profit.full_mul(U256::from(self.max_loss_share_bps))
+    }
+}
@@ -0,0 +1,223 @@
+use dashmap::DashMap;
+use ethers::types::U256;
+use std::{
+    sync::RwLock,
+    time::{Duration, Instant},
+};
+
+use crate::math::to_f64;
+
+// Inputs the scorer needs for one candidate, regardless of strategy
+#[derive(Debug, Clone)]
+pub struct OpportunityFeatures {
+    // Stable identifier for the opportunity (pool pair, position) used to track persistence
+    pub id: String,
+    // Market the opportunity competes in (protocol, pair) used to track competitor activity
+    pub market: String,
+    pub net_profit: U256,
+    pub spread_bps: u32,
+    pub trade_size: U256,
+    pub liquidity_depth: U256,
+    pub gas_price: U256,
+    // Time since the opportunity was detected; the longer it waits, the more
+    // likely someone else takes it first
+    pub age: Duration,
+}
+
+#[derive(Debug, Clone)]
+pub struct ScoringConfig {
+    // Observations after which an opportunity counts as fully persistent
+    pub persistence_saturation: u32,
+    // Gap after which an opportunity is treated as newly appeared
+    pub persistence_reset: Duration,
+    // How long a competitor sighting keeps counting against a market
+    pub competitor_window: Duration,
+    // Win probability lost per competitor sighting in the window
+    pub competitor_weight: f64,
+    // Smoothing for the gas baseline (0..1, higher reacts faster)
+    pub gas_ema_alpha: f64,
+    // Spreads below this are treated as noise and discounted
+    pub min_spread_bps: u32,
+    // Age at which the odds of landing have halved
+    pub latency_half_life: Duration,
+}
+
+impl Default for ScoringConfig {
+    fn default() -> Self {
+        Self {
+            persistence_saturation: 3,
+            persistence_reset: Duration::from_secs(60),
+            competitor_window: Duration::from_secs(600),
+            competitor_weight: 0.25,
+            gas_ema_alpha: 0.1,
+            min_spread_bps: 10,
+            // Half a mainnet slot
+            latency_half_life: Duration::from_secs(6),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct ScoreBreakdown {
+    pub expected_value: f64,
+    pub success_probability: f64,
+    pub persistence: f64,
+    pub competition: f64,
+    pub depth: f64,
+    pub gas_regime: f64,
+    pub spread: f64,
+    pub latency: f64,
+}
+
+#[derive(Debug)]
+struct PersistenceState {
+    observations: u32,
+    last_seen: Instant,
+}
+
+pub struct OpportunityScorer {
+    config: ScoringConfig,
+    persistence: DashMap<String, PersistenceState>,
+    competitors: DashMap<String, Vec<Instant>>,
+    gas_baseline: RwLock<Option<f64>>,
+}
+
+impl OpportunityScorer {
+    pub fn new(config: ScoringConfig) -> Self {
+        Self {
+            config,
+            persistence: DashMap::new(),
+            competitors: DashMap::new(),
+            gas_baseline: RwLock::new(None),
+        }
+    }
+
+    // Record that the opportunity was seen again this tick
+    pub fn observe(&self, id: &str) {
+        let now = Instant::now();
+        let mut entry = self
+            .persistence
+            .entry(id.to_string())
+            .or_insert(PersistenceState { observations: 0, last_seen: now });
+
+        if now.duration_since(entry.last_seen) > self.config.persistence_reset {
+            entry.observations = 0;
+        }
+        entry.observations += 1;
+        entry.last_seen = now;
+    }
+
+    // Record a competing searcher acting in the same market
+    pub fn record_competitor(&self, market: &str) {
+        let now = Instant::now();
+        let window = self.config.competitor_window;
+        let mut sightings = self.competitors.entry(market.to_string()).or_default();
+        sightings.retain(|t| now.duration_since(*t) <= window);
+        sightings.push(now);
+    }
+
+    // Feed the current network gas price into the baseline
+    pub fn observe_gas_price(&self, gas_price: U256) {
+        let gwei = to_f64(gas_price) / 1e9;
+        let mut baseline = self.gas_baseline.write().unwrap();
+        *baseline = Some(match *baseline {
+            Some(prev) => prev + self.config.gas_ema_alpha * (gwei - prev),
+            None => gwei,
+        });
+    }
+
+    pub fn score(&self, features: &OpportunityFeatures) -> ScoreBreakdown {
+        let persistence = self.persistence_factor(&features.id);
+        let competition = self.competition_factor(&features.market);
+        let depth = depth_factor(features.trade_size, features.liquidity_depth);
+        let gas_regime = self.gas_regime_factor(features.gas_price);
+        let spread = self.spread_factor(features.spread_bps);
+        let latency = self.latency_factor(features.age);
+
+        // Odds of the execution landing; expected value is profit weighted by them
+        let success_probability = persistence * competition * depth * gas_regime * spread * latency;
+
+        ScoreBreakdown {
+            expected_value: to_f64(features.net_profit) * success_probability,
+            success_probability,
+            persistence,
+            competition,
+            depth,
+            gas_regime,
+            spread,
+            latency,
+        }
+    }
+
+    // Drop history for opportunities that have not been seen for a while
+    pub fn prune(&self) {
+        let now = Instant::now();
+        let reset = self.config.persistence_reset;
+        let window = self.config.competitor_window;
+
+        self.persistence.retain(|_, s| now.duration_since(s.last_seen) <= reset);
+        self.competitors.retain(|_, v| {
+            v.retain(|t| now.duration_since(*t) <= window);
+            !v.is_empty()
+        });
+    }
+
+    // A spread seen once may be a read-skew artifact; repeated sightings make it credible
+    fn persistence_factor(&self, id: &str) -> f64 {
+        let observations = self.persistence.get(id).map(|s| s.observations).unwrap_or(0);
+        let saturation = self.config.persistence_saturation.max(1) as f64;
+        0.5 + 0.5 * (observations as f64 / saturation).min(1.0)
+    }
+
+    fn competition_factor(&self, market: &str) -> f64 {
+        let now = Instant::now();
+        let recent = self
+            .competitors
+            .get(market)
+            .map(|v| {
+                v.iter()
+                    .filter(|t| now.duration_since(**t) <= self.config.competitor_window)
+                    .count()
+            })
+            .unwrap_or(0);
+        1.0 / (1.0 + self.config.competitor_weight * recent as f64)
+    }
+
+    // Above-baseline gas means more contention for block space and worse odds
+    fn gas_regime_factor(&self, gas_price: U256) -> f64 {
+        let baseline = match *self.gas_baseline.read().unwrap() {
+            Some(b) if b > 0.0 => b,
+            _ => return 1.0,
+        };
+        let ratio = (to_f64(gas_price) / 1e9) / baseline;
+        if ratio <= 1.0 {
+            1.0
+        } else {
+            1.0 / ratio.sqrt()
+        }
+    }
+
+    fn spread_factor(&self, spread_bps: u32) -> f64 {
+        let min = self.config.min_spread_bps.max(1) as f64;
+        (spread_bps as f64 / (2.0 * min)).min(1.0)
+    }
+
+    fn latency_factor(&self, age: Duration) -> f64 {
+        let half_life = self.config.latency_half_life.as_secs_f64();
+        if half_life <= 0.0 {
+            return 1.0;
+        }
+        0.5f64.powf(age.as_secs_f64() / half_life)
+    }
+}
+
+// Larger trades relative to available depth suffer more slippage and reverts.
+// Discounted like a constant-product price impact, so even a trade the size of
+// the depth (a liquidation repaying all it may) keeps half its value.
+fn depth_factor(trade_size: U256, depth: U256) -> f64 {
+    if depth.is_zero() {
+        return 0.0;
+    }
+    let depth = to_f64(depth);
+    depth / (depth + to_f64(trade_size))
+}
@@ -0,0 +1,135 @@
+use anyhow::{anyhow, bail, Context};
+use std::{
+    collections::{BTreeMap, HashSet},
+    path::{Path, PathBuf},
+    sync::Mutex,
+    time::SystemTime,
+};
+use toml::{Table, Value};
+
+// Read when neither --config nor CONFIG_FILE names a file
+const DEFAULT_FILE: &str = "config.toml";
+
+// Keys whose environment value came from the file, and may be replaced by a reload
+static FROM_FILE: Mutex<Option<HashSet<String>>> = Mutex::new(None);
+
+// Layered configuration. Every setting is named by its environment variable,
+// and the first source that sets it wins:
+//   1. `--set KEY=VALUE` on the command line
+//   2. the environment
+//   3. the `[chains.<CHAIN>]` section of the config file
+//   4. the rest of the config file
+// File keys may be lower case; tables other than `chains` only group keys.
+// Values are copied into the process environment, so `Config::from_env` and
+// everything else reading settings sees the merged result.
+#[derive(Debug, Clone, Default)]
+pub struct Sources {
+    pub path: Option<PathBuf>,
+    pub chain: Option<String>,
+    pub overrides: Vec<String>,
+}
+
+impl Sources {
+    // The config file in use, if any
+    pub fn file(&self) -> Option<PathBuf> {
+        self.path
+            .clone()
+            .or_else(|| std::env::var_os("CONFIG_FILE").map(Into::into))
+            .or_else(|| Path::new(DEFAULT_FILE).exists().then(|| DEFAULT_FILE.into()))
+    }
+
+    pub fn modified(&self) -> Option<SystemTime> {
+        std::fs::metadata(self.file()?).ok()?.modified().ok()
+    }
+
+    // Safe to call again: values taken from the file are refreshed, values
+    // from the real environment are left alone
+    pub fn load(&self) -> anyhow::Result<()> {
+        let file = match self.file() {
+            Some(path) => {
+                let contents =
+                    std::fs::read_to_string(&path).with_context(|| format!("reading config {}", path.display()))?;
+                Some(contents.parse::<Table>().with_context(|| format!("parsing config {}", path.display()))?)
+            }
+            None => None,
+        };
+
+        let mut overrides: BTreeMap<String, String> = self
+            .overrides
+            .iter()
+            .map(|o| {
+                o.split_once('=')
+                    .map(|(k, v)| (k.trim().to_uppercase(), v.to_string()))
+                    .ok_or_else(|| anyhow!("--set expects KEY=VALUE, got {}", o))
+            })
+            .collect::<anyhow::Result<_>>()?;
+        if let Some(chain) = &self.chain {
+            overrides.insert("CHAIN".to_string(), chain.clone());
+        }
+
+        let mut values = BTreeMap::new();
+        if let Some(mut file) = file {
+            let chains = file.remove("chains");
+            flatten(&file, &mut values);
+
+            let chain = overrides
+                .get("CHAIN")
+                .cloned()
+                .or_else(|| std::env::var("CHAIN").ok().filter(|_| !from_file("CHAIN")))
+                .or_else(|| values.get("CHAIN").cloned());
+            if let (Some(chain), Some(chains)) = (chain, chains) {
+                match chains.get(&chain) {
+                    Some(Value::Table(section)) => flatten(section, &mut values),
+                    _ => bail!("config has no [chains.{}] section", chain),
+                }
+            }
+        }
+
+        let mut injected = FROM_FILE.lock().unwrap();
+        let previous = injected.take().unwrap_or_default();
+        // Keys dropped from the file since the last load
+        for key in previous.iter().filter(|k| !values.contains_key(*k)) {
+            std::env::remove_var(key);
+        }
+        let mut current = HashSet::new();
+        for (key, value) in values {
+            if std::env::var_os(&key).is_none() || previous.contains(&key) {
+                std::env::set_var(&key, value);
+                current.insert(key);
+            }
+        }
+        *injected = Some(current);
+        drop(injected);
+
+        for (key, value) in overrides {
+            std::env::set_var(key, value);
+        }
+        Ok(())
+    }
+}
+
+fn from_file(key: &str) -> bool {
+    FROM_FILE.lock().unwrap().as_ref().map_or(false, |keys| keys.contains(key))
+}
+
+fn flatten(table: &Table, out: &mut BTreeMap<String, String>) {
+    for (key, value) in table {
+        let text = match value {
+            Value::Table(inner) => {
+                flatten(inner, out);
+                continue;
+            }
+            Value::String(s) => s.clone(),
+            Value::Array(items) => items
+                .iter()
+                .map(|i| match i {
+                    Value::String(s) => s.clone(),
+                    other => other.to_string(),
+                })
+                .collect::<Vec<_>>()
+                .join(","),
+            other => other.to_string(),
+        };
+        out.insert(key.to_uppercase(), text);
+    }
+}
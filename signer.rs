@@ -0,0 +1,162 @@
+use async_trait::async_trait;
+use ethers::{
+    signers::{AwsSigner, AwsSignerError, LocalWallet, Signer, WalletError},
+    types::{
+        transaction::{eip2718::TypedTransaction, eip712::Eip712},
+        Address, Signature,
+    },
+};
+use rusoto_core::Region;
+use rusoto_kms::KmsClient;
+use std::{path::PathBuf, str::FromStr};
+use thiserror::Error;
+
+use crate::errors::{BotError, ExecutionError, Result};
+
+// Where the hot wallet's key comes from, chosen with SIGNER
+#[derive(Debug, Clone)]
+pub enum SignerSource {
+    // Hex private key in the named environment variable
+    Env(String),
+    // Encrypted JSON keystore (geth, foundry `cast wallet import`). The
+    // passphrase is read from `passphrase_file`, or prompted for on the terminal.
+    Keystore {
+        path: PathBuf,
+        passphrase_file: Option<PathBuf>,
+    },
+    // secp256k1 key in AWS KMS, by ARN; the key never leaves KMS and every
+    // signature is a KMS Sign call, authorised by the host's AWS credentials
+    Kms { key_arn: String },
+}
+
+impl SignerSource {
+    pub fn from_env() -> Result<Self> {
+        match std::env::var("SIGNER").unwrap_or_else(|_| "env".to_string()).as_str() {
+            "env" => Ok(SignerSource::Env("PRIVATE_KEY".to_string())),
+            "keystore" => Ok(SignerSource::Keystore {
+                path: std::env::var("KEYSTORE_PATH")
+                    .map_err(|_| BotError::Config("SIGNER=keystore needs KEYSTORE_PATH".to_string()))?
+                    .into(),
+                passphrase_file: std::env::var("KEYSTORE_PASSPHRASE_FILE").ok().map(PathBuf::from),
+            }),
+            "kms" => Ok(SignerSource::Kms {
+                key_arn: std::env::var("KMS_KEY_ARN")
+                    .map_err(|_| BotError::Config("SIGNER=kms needs KMS_KEY_ARN".to_string()))?,
+            }),
+            other => Err(BotError::Config(format!("unknown SIGNER {}", other))),
+        }
+    }
+
+    pub async fn load(&self, chain_id: u64) -> Result<WalletSigner> {
+        let wallet = match self {
+            SignerSource::Env(var) => std::env::var(var)?.parse::<LocalWallet>()?,
+            SignerSource::Keystore { path, passphrase_file } => {
+                let passphrase = match passphrase_file {
+                    Some(file) => std::fs::read_to_string(file)
+                        .map_err(|e| BotError::Config(format!("passphrase file {}: {}", file.display(), e)))?
+                        .trim_end_matches(['\r', '\n'])
+                        .to_string(),
+                    None => rpassword::prompt_password(format!("Passphrase for {}: ", path.display()))
+                        .map_err(|e| BotError::Config(format!("passphrase prompt: {}", e)))?,
+                };
+                // Scrypt is deliberately slow; keep it off the runtime threads
+                let path = path.clone();
+                tokio::task::spawn_blocking(move || LocalWallet::decrypt_keystore(&path, passphrase)).await??
+            }
+            SignerSource::Kms { key_arn } => {
+                // arn:aws:kms:<region>:<account>:key/<id>
+                let region = key_arn
+                    .split(':')
+                    .nth(3)
+                    .and_then(|r| Region::from_str(r).ok())
+                    .ok_or_else(|| BotError::Config(format!("KMS_KEY_ARN {} has no valid region", key_arn)))?;
+                // Reads the public key once, so a missing key or permission fails at startup
+                let kms = AwsSigner::new(KmsClient::new(region), key_arn.clone(), chain_id).await?;
+                return Ok(WalletSigner::Kms(kms));
+            }
+        };
+        Ok(WalletSigner::Local(wallet.with_chain_id(chain_id)))
+    }
+}
+
+// The hot wallet, whichever backend holds its key. Everything that signs takes
+// this, so adding a backend means a variant here and in SignerSource.
+#[derive(Debug, Clone)]
+pub enum WalletSigner {
+    Local(LocalWallet),
+    Kms(AwsSigner),
+}
+
+#[derive(Debug, Error)]
+pub enum WalletSignerError {
+    #[error(transparent)]
+    Local(#[from] WalletError),
+
+    #[error(transparent)]
+    Kms(#[from] AwsSignerError),
+}
+
+impl From<AwsSignerError> for BotError {
+    fn from(err: AwsSignerError) -> Self {
+        BotError::Config(format!("KMS signer: {}", err))
+    }
+}
+
+impl From<WalletSignerError> for BotError {
+    fn from(err: WalletSignerError) -> Self {
+        ExecutionError::Signer(err.to_string()).into()
+    }
+}
+
+#[async_trait]
+impl Signer for WalletSigner {
+    type Error = WalletSignerError;
+
+    async fn sign_message<M: Send + Sync + AsRef<[u8]>>(
+        &self,
+        message: M,
+    ) -> std::result::Result<Signature, Self::Error> {
+        match self {
+            WalletSigner::Local(wallet) => Ok(wallet.sign_message(message).await?),
+            WalletSigner::Kms(kms) => Ok(kms.sign_message(message).await?),
+        }
+    }
+
+    async fn sign_transaction(&self, tx: &TypedTransaction) -> std::result::Result<Signature, Self::Error> {
+        match self {
+            WalletSigner::Local(wallet) => Ok(wallet.sign_transaction(tx).await?),
+            WalletSigner::Kms(kms) => Ok(kms.sign_transaction(tx).await?),
+        }
+    }
+
+    async fn sign_typed_data<T: Eip712 + Send + Sync>(
+        &self,
+        payload: &T,
+    ) -> std::result::Result<Signature, Self::Error> {
+        match self {
+            WalletSigner::Local(wallet) => Ok(wallet.sign_typed_data(payload).await?),
+            WalletSigner::Kms(kms) => Ok(kms.sign_typed_data(payload).await?),
+        }
+    }
+
+    fn address(&self) -> Address {
+        match self {
+            WalletSigner::Local(wallet) => wallet.address(),
+            WalletSigner::Kms(kms) => kms.address(),
+        }
+    }
+
+    fn chain_id(&self) -> u64 {
+        match self {
+            WalletSigner::Local(wallet) => wallet.chain_id(),
+            WalletSigner::Kms(kms) => kms.chain_id(),
+        }
+    }
+
+    fn with_chain_id<T: Into<u64>>(self, chain_id: T) -> Self {
+        match self {
+            WalletSigner::Local(wallet) => WalletSigner::Local(wallet.with_chain_id(chain_id)),
+            WalletSigner::Kms(kms) => WalletSigner::Kms(kms.with_chain_id(chain_id)),
+        }
+    }
+}
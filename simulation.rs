@@ -0,0 +1,442 @@
+use async_trait::async_trait;
+use ethers::{
+    abi::{self, ParamType, Token},
+    prelude::*,
+    providers::{call_raw::RawCall, spoof, Provider, RpcError, Ws},
+    types::{transaction::eip2718::TypedTransaction, Address, BlockNumber, Bytes, H256, I256, U256, U64},
+    utils::{keccak256, Anvil, AnvilInstance},
+};
+use serde_json::{json, Value};
+use std::{collections::HashMap, sync::Arc, time::Duration};
+use tokio::sync::Mutex;
+
+use crate::errors::{Result, SimulationError};
+
+abigen!(
+    Erc20Balance,
+    r#"[function balanceOf(address owner) external view returns (uint256)]"#
+);
+
+// Error(string) and Panic(uint256) selectors
+const ERROR_SELECTOR: [u8; 4] = [0x08, 0xc3, 0x79, 0xa0];
+const PANIC_SELECTOR: [u8; 4] = [0x4e, 0x48, 0x7b, 0x71];
+
+// A transaction to try before it is signed for mainnet
+#[derive(Debug, Clone)]
+pub struct SimulationRequest {
+    pub from: Address,
+    pub to: Address,
+    pub data: Bytes,
+    pub value: U256,
+    // (token, holder) whose balance change is the trade's profit
+    pub profit: Option<(Address, Address)>,
+    // Token balances credited to `from`, and approved to `to`, before the call
+    pub funding: Vec<(Address, U256)>,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct SimulationOutcome {
+    // Zero when the backend can't measure it
+    pub gas_used: U256,
+    // Balance change of the profit token, when requested and measurable
+    pub profit: Option<I256>,
+    // Token transfers made by the transaction, for backends that report them
+    pub asset_changes: Vec<AssetChange>,
+}
+
+#[derive(Debug, Clone)]
+pub struct AssetChange {
+    pub token: Address,
+    pub from: Address,
+    pub to: Address,
+    pub amount: U256,
+}
+
+// Somewhere a transaction can be executed against current mainnet state
+// without consequences. A revert comes back as SimulationError::Reverted with
+// the decoded reason.
+#[async_trait]
+pub trait Simulator: Send + Sync {
+    fn name(&self) -> &str;
+
+    async fn simulate(&self, request: &SimulationRequest) -> Result<SimulationOutcome>;
+
+    // Transactions executed in order on top of each other's state
+    async fn simulate_bundle(&self, requests: &[SimulationRequest]) -> Result<Vec<SimulationOutcome>> {
+        match requests {
+            [request] => Ok(vec![self.simulate(request).await?]),
+            _ => Err(SimulationError::Backend(format!("{} can't simulate bundles", self.name())).into()),
+        }
+    }
+}
+
+const TENDERLY_API: &str = "https://api.tenderly.co/api/v1";
+
+// Tenderly's simulation API: gas used, token transfers and, on revert, the
+// error and the call that raised it. Each request is a paid API call.
+pub struct TenderlySimulator {
+    http: reqwest::Client,
+    endpoint: String,
+    access_key: String,
+    network_id: String,
+}
+
+impl TenderlySimulator {
+    pub fn new(account: &str, project: &str, access_key: &str) -> Self {
+        Self {
+            http: reqwest::Client::new(),
+            endpoint: format!("{}/account/{}/project/{}", TENDERLY_API, account, project),
+            access_key: access_key.to_string(),
+            network_id: "1".to_string(),
+        }
+    }
+
+    fn body(&self, request: &SimulationRequest) -> Value {
+        json!({
+            "network_id": self.network_id,
+            "from": request.from,
+            "to": request.to,
+            "input": request.data,
+            "value": request.value.to_string(),
+            "gas": 8_000_000,
+            "save": false,
+            "simulation_type": "full",
+        })
+    }
+
+    async fn post(&self, path: &str, body: Value) -> Result<Value> {
+        let response = self
+            .http
+            .post(format!("{}/{}", self.endpoint, path))
+            .header("X-Access-Key", &self.access_key)
+            .json(&body)
+            .send()
+            .await
+            .map_err(|e| SimulationError::Backend(format!("tenderly: {}", e)))?;
+
+        let status = response.status();
+        let body: Value = response
+            .json()
+            .await
+            .map_err(|e| SimulationError::Backend(format!("tenderly: {}", e)))?;
+        if !status.is_success() {
+            return Err(SimulationError::Backend(format!("tenderly {}: {}", status, body["error"])).into());
+        }
+        Ok(body)
+    }
+}
+
+#[async_trait]
+impl Simulator for TenderlySimulator {
+    fn name(&self) -> &str {
+        "tenderly"
+    }
+
+    async fn simulate(&self, request: &SimulationRequest) -> Result<SimulationOutcome> {
+        let response = self.post("simulate", self.body(request)).await?;
+        tenderly_outcome(&response["transaction"], request)
+    }
+
+    async fn simulate_bundle(&self, requests: &[SimulationRequest]) -> Result<Vec<SimulationOutcome>> {
+        let simulations: Vec<Value> = requests.iter().map(|r| self.body(r)).collect();
+        let response = self.post("simulate-bundle", json!({ "simulations": simulations })).await?;
+
+        let results = response["simulation_results"].as_array().cloned().unwrap_or_default();
+        if results.len() != requests.len() {
+            return Err(SimulationError::Backend("tenderly returned a partial bundle".to_string()).into());
+        }
+        results
+            .iter()
+            .zip(requests)
+            .map(|(result, request)| tenderly_outcome(&result["transaction"], request))
+            .collect()
+    }
+}
+
+fn tenderly_outcome(tx: &Value, request: &SimulationRequest) -> Result<SimulationOutcome> {
+    let info = &tx["transaction_info"];
+    if !tx["status"].as_bool().unwrap_or(false) {
+        let error = tx["error_message"].as_str().unwrap_or("reverted");
+        // Deepest call that failed, e.g. "reverted in 0xabc…::swap"
+        let origin = info["stack_trace"]
+            .as_array()
+            .and_then(|frames| frames.first())
+            .map(|frame| {
+                let contract = frame["contract"].as_str().unwrap_or("?");
+                let function = frame["name"].as_str().unwrap_or("?");
+                format!(" in {}::{}", contract, function)
+            })
+            .unwrap_or_default();
+        return Err(SimulationError::Reverted(format!("{}{}", error, origin)).into());
+    }
+
+    let asset_changes: Vec<AssetChange> = info["asset_changes"]
+        .as_array()
+        .map(|changes| changes.iter().filter_map(asset_change).collect())
+        .unwrap_or_default();
+    let profit = request.profit.map(|(token, holder)| {
+        asset_changes
+            .iter()
+            .filter(|c| c.token == token)
+            .fold(I256::zero(), |net, c| {
+                let amount = I256::from_raw(c.amount);
+                match (c.to == holder, c.from == holder) {
+                    (true, false) => net + amount,
+                    (false, true) => net - amount,
+                    _ => net,
+                }
+            })
+    });
+
+    Ok(SimulationOutcome {
+        gas_used: U256::from(tx["gas_used"].as_u64().unwrap_or_default()),
+        profit,
+        asset_changes,
+    })
+}
+
+fn asset_change(change: &Value) -> Option<AssetChange> {
+    Some(AssetChange {
+        token: change["token_info"]["contract_address"].as_str()?.parse().ok()?,
+        from: change["from"].as_str()?.parse().ok()?,
+        to: change["to"].as_str()?.parse().ok()?,
+        amount: U256::from_dec_str(change["raw_amount"].as_str()?).ok()?,
+    })
+}
+
+// Storage slots of the `balanceOf` and `allowance` mappings of well-known tokens
+const MAINNET_TOKEN_SLOTS: &[(&str, u64, u64)] = &[
+    ("0xC02aaA39b223FE8D0A0e5C4F27eAD9083C756Cc2", 3, 4),  // WETH
+    ("0xA0b86991c6218b36c1d19D4a2e9Eb0cE3606eB48", 9, 10), // USDC
+    ("0xdAC17F958D2ee523a2206206994597C13D831ec7", 2, 5),  // USDT
+    ("0x6B175474E89094C44Da98b954EedeAC495271d0F", 2, 3),  // DAI
+    ("0x2260FAC5E5542a773Aa44fBCfeDf7C193bc2C599", 0, 2),  // WBTC
+];
+
+// eth_call against the pending block with state overrides: the sender gets
+// ETH for gas and any requested token balances and approvals. Cheap enough for
+// every opportunity, but it can't observe balances after the call, so profit
+// is only enforced by the contract's own checks.
+pub struct CallSimulator {
+    provider: Arc<Provider<Ws>>,
+    // token -> (balance slot, allowance slot)
+    slots: HashMap<Address, (u64, u64)>,
+}
+
+impl CallSimulator {
+    pub fn new(provider: Arc<Provider<Ws>>) -> Self {
+        Self { provider, slots: HashMap::new() }
+    }
+
+    pub fn mainnet(provider: Arc<Provider<Ws>>) -> Self {
+        MAINNET_TOKEN_SLOTS.iter().fold(Self::new(provider), |simulator, (token, balance, allowance)| {
+            simulator.with_token_slots(token.parse().unwrap(), *balance, *allowance)
+        })
+    }
+
+    // Where `token` keeps its balance and allowance mappings, for funding overrides
+    pub fn with_token_slots(mut self, token: Address, balance_slot: u64, allowance_slot: u64) -> Self {
+        self.slots.insert(token, (balance_slot, allowance_slot));
+        self
+    }
+
+    fn overrides(&self, request: &SimulationRequest) -> Result<spoof::State> {
+        let mut state = spoof::state();
+        state.account(request.from).balance(U256::exp10(24));
+
+        for (token, amount) in &request.funding {
+            let (balance_slot, allowance_slot) = self.slots.get(token).ok_or_else(|| {
+                SimulationError::Backend(format!("no storage layout known for {:?}", token))
+            })?;
+            let value = H256::from_uint(amount);
+            let balance_key = mapping_slot(request.from, U256::from(*balance_slot));
+            let allowance_key = mapping_slot(request.to, allowance_slot_of(request.from, *allowance_slot));
+            state.account(*token).store(balance_key, value).store(allowance_key, value);
+        }
+        Ok(state)
+    }
+}
+
+#[async_trait]
+impl Simulator for CallSimulator {
+    fn name(&self) -> &str {
+        "eth_call"
+    }
+
+    async fn simulate(&self, request: &SimulationRequest) -> Result<SimulationOutcome> {
+        let tx: TypedTransaction = TransactionRequest::new()
+            .from(request.from)
+            .to(request.to)
+            .data(request.data.clone())
+            .value(request.value)
+            .into();
+        let state = self.overrides(request)?;
+
+        match self.provider.call_raw(&tx).block(BlockNumber::Pending.into()).state(&state).await {
+            Ok(_) => Ok(SimulationOutcome::default()),
+            Err(e) => Err(SimulationError::Reverted(revert_reason(&e)).into()),
+        }
+    }
+}
+
+// Executes on a local anvil fork, reset to the upstream head whenever it moves.
+// Each simulation runs inside an EVM snapshot that is reverted afterwards.
+pub struct ForkSimulator {
+    provider: Arc<Provider<Ws>>,
+    upstream: Arc<Provider<Ws>>,
+    upstream_url: String,
+    // Head the fork was last reset to; also serialises simulations
+    forked_at: Mutex<U64>,
+    // Kept alive for a fork we spawned ourselves
+    _anvil: Option<AnvilInstance>,
+}
+
+impl ForkSimulator {
+    // Spawn anvil forking `upstream_url` (an archive-capable HTTP endpoint)
+    pub async fn spawn(upstream: Arc<Provider<Ws>>, upstream_url: &str) -> Result<Self> {
+        let anvil = Anvil::new().fork(upstream_url).spawn();
+        let mut simulator = Self::connect(upstream, &anvil.ws_endpoint(), upstream_url).await?;
+        simulator._anvil = Some(anvil);
+        Ok(simulator)
+    }
+
+    // Use an anvil already running at `anvil_ws`
+    pub async fn connect(upstream: Arc<Provider<Ws>>, anvil_ws: &str, upstream_url: &str) -> Result<Self> {
+        let ws = Ws::connect(anvil_ws).await?;
+        Ok(Self {
+            provider: Arc::new(Provider::new(ws).interval(Duration::from_millis(10))),
+            upstream,
+            upstream_url: upstream_url.to_string(),
+            forked_at: Mutex::new(U64::zero()),
+            _anvil: None,
+        })
+    }
+
+    async fn balance(&self, token: Address, holder: Address) -> Result<U256> {
+        Ok(Erc20Balance::new(token, self.provider.clone()).balance_of(holder).call().await?)
+    }
+
+    async fn rpc<T: serde::Serialize + std::fmt::Debug + Send + Sync, R: serde::de::DeserializeOwned + Send>(
+        &self,
+        method: &str,
+        params: T,
+    ) -> Result<R> {
+        self.provider
+            .request(method, params)
+            .await
+            .map_err(|e| SimulationError::Backend(format!("{}: {}", method, e)).into())
+    }
+
+    async fn run(&self, request: &SimulationRequest) -> Result<SimulationOutcome> {
+        if !request.funding.is_empty() {
+            return Err(SimulationError::Backend("token funding is only supported by eth_call simulation".to_string()).into());
+        }
+        let tx: TypedTransaction = TransactionRequest::new()
+            .from(request.from)
+            .to(request.to)
+            .data(request.data.clone())
+            .value(request.value)
+            .into();
+
+        // eth_call first: a revert there carries the reason, a mined revert doesn't
+        if let Err(e) = self.provider.call(&tx, None).await {
+            return Err(SimulationError::Reverted(revert_reason(&e)).into());
+        }
+
+        let before = match request.profit {
+            Some((token, holder)) => Some(self.balance(token, holder).await?),
+            None => None,
+        };
+
+        self.rpc::<_, ()>("anvil_impersonateAccount", [request.from]).await?;
+        let receipt = self
+            .provider
+            .send_transaction(tx, None)
+            .await
+            .map_err(|e| SimulationError::Backend(e.to_string()))?
+            .await
+            .map_err(|e| SimulationError::Backend(e.to_string()))?
+            .ok_or_else(|| SimulationError::Backend("fork did not mine the transaction".to_string()))?;
+        if receipt.status != Some(U64::from(1)) {
+            return Err(SimulationError::Reverted("reverted when mined".to_string()).into());
+        }
+
+        let profit = match (request.profit, before) {
+            (Some((token, holder)), Some(before)) => {
+                let after = self.balance(token, holder).await?;
+                Some(I256::from_raw(after) - I256::from_raw(before))
+            }
+            _ => None,
+        };
+
+        Ok(SimulationOutcome {
+            gas_used: receipt.gas_used.unwrap_or_default(),
+            profit,
+            asset_changes: Vec::new(),
+        })
+    }
+}
+
+#[async_trait]
+impl Simulator for ForkSimulator {
+    fn name(&self) -> &str {
+        "anvil"
+    }
+
+    async fn simulate(&self, request: &SimulationRequest) -> Result<SimulationOutcome> {
+        let mut forked_at = self.forked_at.lock().await;
+
+        let head = self.upstream.get_block_number().await?;
+        if head != *forked_at {
+            let reset = serde_json::json!([{
+                "forking": { "jsonRpcUrl": self.upstream_url, "blockNumber": head.as_u64() }
+            }]);
+            self.rpc::<_, ()>("anvil_reset", reset).await?;
+            *forked_at = head;
+        }
+
+        let snapshot: U256 = self.rpc("evm_snapshot", ()).await?;
+        let outcome = self.run(request).await;
+        self.rpc::<_, bool>("evm_revert", [snapshot]).await?;
+
+        outcome
+    }
+}
+
+// keccak256(key . slot), the storage slot of `mapping[key]`
+fn mapping_slot(key: Address, slot: U256) -> H256 {
+    H256::from(keccak256(abi::encode(&[Token::Address(key), Token::Uint(slot)])))
+}
+
+// Slot of the inner `allowance[owner]` mapping
+fn allowance_slot_of(owner: Address, slot: u64) -> U256 {
+    U256::from_big_endian(mapping_slot(owner, U256::from(slot)).as_bytes())
+}
+
+fn revert_reason(e: &ProviderError) -> String {
+    match e.as_error_response().and_then(|r| r.as_revert_data()) {
+        Some(data) => decode_revert(&data),
+        None => e.to_string(),
+    }
+}
+
+// Human-readable revert reason from raw revert data
+pub fn decode_revert(data: &[u8]) -> String {
+    if data.len() >= 4 {
+        let (selector, body) = data.split_at(4);
+        if selector == ERROR_SELECTOR {
+            if let Ok(tokens) = abi::decode(&[ParamType::String], body) {
+                if let Some(reason) = tokens.into_iter().next().and_then(|t| t.into_string()) {
+                    return reason;
+                }
+            }
+        } else if selector == PANIC_SELECTOR && body.len() >= 32 {
+            return format!("panic 0x{:02x}", U256::from_big_endian(&body[..32]));
+        }
+    }
+    if data.is_empty() {
+        "reverted without reason".to_string()
+    } else {
+        format!("custom error 0x{}", hex::encode(data))
+    }
+}
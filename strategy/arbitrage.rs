@@ -0,0 +1,560 @@
+use async_trait::async_trait;
+use ethers::{
+    providers::{Provider, Ws},
+    types::{Address, Transaction, U256, U64},
+};
+use std::{
+    collections::HashSet,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc, Mutex,
+    },
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+use tokio::sync::RwLock;
+use tracing::{debug, info, warn};
+
+use super::{Action, Strategy};
+use crate::{
+    block_state::BlockSnapshot,
+    dex::{concurrency::read_concurrently, DexManager},
+    errors::Result,
+    feed::OpportunityFeed,
+    mempool::{self, PendingSwap},
+    models::{ArbitrageOpportunity, DexPool},
+    pathfinder::Pathfinder,
+    pool_cache::PoolStateCache,
+    price::{UsdPricer, UsdPrices},
+    ranking::PoolRanking,
+    registry::PoolRegistry,
+    safety::{ContractScreen, HoneypotDetector},
+    scoring::{OpportunityFeatures, OpportunityScorer},
+    subgraph::SubgraphClient,
+    thresholds::SharedThresholds,
+    token_sets::{TargetTokens, WETH},
+};
+#[cfg(feature = "postgres-sink")]
+use crate::opportunity_sink::OpportunitySink;
+
+pub const ARBITRAGE_STRATEGY: &str = "arbitrage";
+
+const DEFAULT_MAX_HOPS: usize = 4;
+// Size used to re-quote pathfinder cycles
+const CYCLE_PROBE_AMOUNT: u64 = 1_000_000_000_000_000_000;
+// Blocks between picking up pools the registry sync has added (~1 hour)
+const REGISTRY_RELOAD_BLOCKS: u64 = 300;
+
+// Opportunities worth executing. Profit and gas are only comparable when the
+// loop starts and ends in WETH, or whatever wraps the chain's gas token. The
+// profit left after gas is valued in USD against `min_profit`; without a WETH
+// price only a zero minimum can be cleared.
+pub fn actionable(
+    opportunities: Vec<ArbitrageOpportunity>,
+    weth: Address,
+    min_profit: U256,
+    prices: &UsdPrices,
+) -> Vec<ArbitrageOpportunity> {
+    opportunities
+        .into_iter()
+        .filter(|o| o.token_in == weth && o.gross_profit > o.gas_cost)
+        .filter(|o| {
+            min_profit.is_zero()
+                || prices
+                    .value(weth, o.gross_profit - o.gas_cost)
+                    .is_some_and(|usd| usd >= min_profit)
+        })
+        .collect()
+}
+
+// Pools deep enough to trade through, by the USD both sides hold. A pool whose
+// tokens have no price only passes a zero minimum.
+pub fn liquid(pools: Vec<DexPool>, prices: &UsdPrices, min_liquidity: U256) -> Vec<DexPool> {
+    if min_liquidity.is_zero() {
+        return pools;
+    }
+    pools
+        .into_iter()
+        .filter(|p| prices.liquidity(p).is_some_and(|usd| usd >= min_liquidity))
+        .collect()
+}
+
+// Market a token pair's opportunities compete in, for the scorer
+fn market((token_a, token_b): (Address, Address)) -> String {
+    format!("{:?}/{:?}", token_a, token_b)
+}
+
+// What the scorer weighs an opportunity on, `age` after it was detected
+fn features(opportunity: &ArbitrageOpportunity, gas_price: U256, age: Duration) -> OpportunityFeatures {
+    OpportunityFeatures {
+        id: format!(
+            "{:?}:{:?}:{:?}",
+            opportunity.buy_pool.address, opportunity.sell_pool.address, opportunity.token_in
+        ),
+        market: market(opportunity.buy_pool.pair_key()),
+        net_profit: opportunity.gross_profit.saturating_sub(opportunity.gas_cost),
+        spread_bps: opportunity.spread_bps,
+        trade_size: opportunity.amount_in,
+        // The shallower pool bounds how much the trade can move before reverting
+        liquidity_depth: [&opportunity.buy_pool, &opportunity.sell_pool]
+            .iter()
+            .filter_map(|p| p.reserves_for(opportunity.token_in).map(|(reserve, _)| reserve))
+            .min()
+            .unwrap_or_default(),
+        gas_price,
+        age,
+    }
+}
+
+// Cross-DEX arbitrage over the target token set: two-pool opportunities are
+// executed, longer cycles from the pathfinder are reported
+pub struct ArbitrageStrategy {
+    dex: Arc<DexManager>,
+    pathfinder: Pathfinder,
+    cache: Arc<PoolStateCache>,
+    tokens: Vec<Address>,
+    // Loops start and end in it
+    weth: Address,
+    // Minimum profit after gas, among others; reloadable
+    thresholds: SharedThresholds,
+    pools: Arc<RwLock<Vec<DexPool>>>,
+    registry: Option<Arc<PoolRegistry>>,
+    // Block the pool list was last loaded at
+    loaded_at: AtomicU64,
+    // Gas prices that pending swaps are priced against
+    last_block: RwLock<BlockSnapshot>,
+    // Drop pools holding fee-on-transfer tokens instead of pricing the tax in
+    exclude_taxed: bool,
+    honeypots: Option<Arc<HoneypotDetector<Provider<Ws>>>>,
+    contracts: Option<Arc<ContractScreen>>,
+    feed: Option<OpportunityFeed>,
+    // Orders opportunities by expected value instead of profit alone
+    scorer: Option<Arc<OpportunityScorer>>,
+    // Values profits in USD for the minimum-profit check
+    pricer: Option<Arc<UsdPricer<Provider<Ws>>>>,
+    // From the last block, for pending swaps
+    prices: RwLock<UsdPrices>,
+    ranking: Mutex<PoolRanking>,
+    // Lists pools faster than factory discovery
+    subgraph: Option<Arc<SubgraphClient>>,
+    // Best-ranked pools scanned per token pair; 0 scans them all
+    pools_per_pair: usize,
+    #[cfg(feature = "postgres-sink")]
+    sink: Option<Arc<OpportunitySink>>,
+}
+
+impl ArbitrageStrategy {
+    pub fn new(dex: Arc<DexManager>, cache: Arc<PoolStateCache>, thresholds: SharedThresholds) -> Self {
+        Self {
+            dex,
+            pathfinder: Pathfinder::new(DEFAULT_MAX_HOPS),
+            cache,
+            tokens: TargetTokens::default().fixed,
+            weth: WETH.parse().unwrap(),
+            thresholds,
+            pools: Arc::new(RwLock::new(Vec::new())),
+            registry: None,
+            loaded_at: AtomicU64::new(0),
+            last_block: RwLock::new(BlockSnapshot::default()),
+            exclude_taxed: false,
+            honeypots: None,
+            contracts: None,
+            feed: None,
+            scorer: None,
+            pricer: None,
+            prices: RwLock::new(UsdPrices::default()),
+            ranking: Mutex::new(PoolRanking::default()),
+            subgraph: None,
+            pools_per_pair: 0,
+            #[cfg(feature = "postgres-sink")]
+            sink: None,
+        }
+    }
+
+    // Load pools from the persistent registry instead of walking factories on every start
+    pub fn with_registry(mut self, registry: Arc<PoolRegistry>) -> Self {
+        self.registry = Some(registry);
+        self
+    }
+
+    pub fn with_taxed_tokens_excluded(mut self, exclude: bool) -> Self {
+        self.exclude_taxed = exclude;
+        self
+    }
+
+    // Tokens to trade between, from `TargetTokens::resolve`
+    pub fn with_tokens(mut self, tokens: Vec<Address>) -> Self {
+        self.tokens = tokens;
+        self
+    }
+
+    // The chain's wrapped native token, when not on mainnet
+    pub fn with_wrapped_native(mut self, weth: Address) -> Self {
+        self.weth = weth;
+        self
+    }
+
+    // Round-trip every token before its pools are scanned
+    pub fn with_honeypot_detector(mut self, detector: Arc<HoneypotDetector<Provider<Ws>>>) -> Self {
+        self.honeypots = Some(detector);
+        self
+    }
+
+    // Check every token's Etherscan source before its pools are scanned
+    pub fn with_contract_screen(mut self, screen: Arc<ContractScreen>) -> Self {
+        self.contracts = Some(screen);
+        self
+    }
+
+    // Publish each actionable opportunity as soon as it's found
+    pub fn with_feed(mut self, feed: OpportunityFeed) -> Self {
+        self.feed = Some(feed);
+        self
+    }
+
+    // Rank opportunities by profit times the odds of landing, so the executor's
+    // budgets go to the trades most likely to be included
+    pub fn with_scorer(mut self, scorer: Arc<OpportunityScorer>) -> Self {
+        self.scorer = Some(scorer);
+        self
+    }
+
+    pub fn with_pricer(mut self, pricer: Arc<UsdPricer<Provider<Ws>>>) -> Self {
+        self.pricer = Some(pricer);
+        self
+    }
+
+    pub fn with_max_hops(mut self, max_hops: usize) -> Self {
+        self.pathfinder = Pathfinder::new(max_hops);
+        self
+    }
+
+    pub fn with_subgraph(mut self, subgraph: Arc<SubgraphClient>) -> Self {
+        self.subgraph = Some(subgraph);
+        self
+    }
+
+    // Scan only the `n` pools of each pair with the most TVL and recent volume
+    pub fn with_pools_per_pair(mut self, n: usize) -> Self {
+        self.pools_per_pair = n;
+        self
+    }
+
+    #[cfg(feature = "postgres-sink")]
+    pub fn with_sink(mut self, sink: Arc<OpportunitySink>) -> Self {
+        self.sink = Some(sink);
+        self
+    }
+
+    // Pools the scan tracks, refreshed every block
+    pub fn pools(&self) -> Arc<RwLock<Vec<DexPool>>> {
+        self.pools.clone()
+    }
+
+    // Written in the background so a slow database never delays a block
+    #[cfg(feature = "postgres-sink")]
+    fn record(&self, opportunities: &[ArbitrageOpportunity]) {
+        if let Some(sink) = self.sink.clone() {
+            let opportunities = opportunities.to_vec();
+            tokio::spawn(async move {
+                if let Err(e) = sink.record(&opportunities).await {
+                    warn!(error = %e, "⚠️ Recording opportunities failed");
+                }
+            });
+        }
+    }
+
+    // Highest expected value first. Without a scorer the scan's order, by
+    // gross profit, is kept.
+    fn rank(&self, opportunities: Vec<ArbitrageOpportunity>, snapshot: &BlockSnapshot) -> Vec<ArbitrageOpportunity> {
+        let Some(scorer) = &self.scorer else {
+            return opportunities;
+        };
+        // Time into the slot since the block was produced; the later a trade
+        // is found, the less of it is left to reach a builder
+        let now = SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or_default().as_secs();
+        let age = Duration::from_secs(now.saturating_sub(snapshot.timestamp.low_u64()));
+
+        let mut scored: Vec<(f64, ArbitrageOpportunity)> = opportunities
+            .into_iter()
+            .map(|opportunity| {
+                let features = features(&opportunity, snapshot.gas_price, age);
+                scorer.observe(&features.id);
+                (scorer.score(&features).expected_value, opportunity)
+            })
+            .collect();
+        scored.sort_by(|a, b| b.0.total_cmp(&a.0));
+        scored.into_iter().map(|(_, opportunity)| opportunity).collect()
+    }
+
+    // A router swap that starts and ends in the same token is another
+    // searcher's loop; each pair it trades counts as contested
+    fn record_competitors(&self, swaps: &[PendingSwap]) {
+        let Some(scorer) = &self.scorer else {
+            return;
+        };
+        for swap in swaps {
+            let (Some(first), Some(last)) = (swap.hops.first(), swap.hops.last()) else {
+                continue;
+            };
+            if swap.hops.len() < 2 || first.token_in != last.token_out {
+                continue;
+            }
+            for hop in &swap.hops {
+                let pair = if hop.token_in < hop.token_out {
+                    (hop.token_in, hop.token_out)
+                } else {
+                    (hop.token_out, hop.token_in)
+                };
+                scorer.record_competitor(&market(pair));
+            }
+        }
+    }
+
+    // Multi-hop loops the pairwise scan can't see, confirmed by quoting each leg
+    async fn report_cycles(&self, pools: &[DexPool], weth: Address) {
+        let probe = U256::from(CYCLE_PROBE_AMOUNT);
+        for cycle in self.pathfinder.find_cycles(pools, &[weth]) {
+            if cycle.hops.len() < 3 {
+                continue;
+            }
+            match self.dex.quote_path(&cycle.hops, probe).await {
+                Ok(out) if out > probe => info!(
+                    hops = cycle.hops.len(),
+                    out = %out,
+                    rate = cycle.rate,
+                    "🔁 Profitable cycle for 1 WETH"
+                ),
+                Ok(_) => {}
+                Err(e) => warn!(error = %e, "⚠️ Cycle quote failed"),
+            }
+        }
+    }
+
+    // Two-pool loops a pending router swap opens, priced against the tracked
+    // pools as the swap will leave them. Only the pairs it trades are scanned,
+    // and only loops through a pool it moves are kept.
+    async fn backruns(&self, swaps: &[PendingSwap]) -> Vec<ArbitrageOpportunity> {
+        if swaps.is_empty() {
+            return Vec::new();
+        }
+        let snapshot = self.last_block.read().await.clone();
+        if snapshot.is_empty() {
+            return Vec::new();
+        }
+
+        let pools = self.pools.read().await;
+        let projected = mempool::project(&pools, swaps);
+        if projected.is_empty() {
+            return Vec::new();
+        }
+        let same = |a: &DexPool, b: &DexPool| {
+            a.address == b.address && a.token0.address == b.token0.address && a.token1.address == b.token1.address
+        };
+        let pairs: HashSet<_> = projected.iter().map(|p| p.pair_key()).collect();
+        let mut candidates: Vec<DexPool> = pools
+            .iter()
+            .filter(|p| pairs.contains(&p.pair_key()) && self.dex.permits(p))
+            .filter(|p| !projected.iter().any(|moved| same(p, moved)))
+            .cloned()
+            .collect();
+        drop(pools);
+        candidates.extend(projected.iter().cloned());
+
+        let prices = self.prices.read().await.clone();
+        let candidates = liquid(candidates, &prices, self.thresholds.load().min_liquidity);
+        let opportunities = self
+            .dex
+            .find_arbitrage_opportunities(&candidates, snapshot.gas_price, snapshot.l1_fee_per_byte, snapshot.number)
+            .await;
+        actionable(opportunities, self.weth, self.thresholds.load().min_profit, &prices)
+            .into_iter()
+            .filter(|o| projected.iter().any(|moved| same(&o.buy_pool, moved) || same(&o.sell_pool, moved)))
+            .collect()
+    }
+
+    async fn ensure_pools(&self, block: U64) {
+        let loaded = !self.pools.read().await.is_empty();
+        let due = block.as_u64() >= self.loaded_at.load(Ordering::Relaxed) + REGISTRY_RELOAD_BLOCKS;
+        if loaded && (self.registry.is_none() || !due) {
+            return;
+        }
+        self.loaded_at.store(block.as_u64(), Ordering::Relaxed);
+
+        let mut found = match &self.registry {
+            Some(registry) => match self.load_registered(registry).await {
+                Ok(pools) => pools,
+                Err(e) => {
+                    warn!(error = %e, "⚠️ Pool registry unavailable");
+                    if loaded {
+                        return;
+                    }
+                    self.discover().await
+                }
+            },
+            None => self.discover().await,
+        };
+        // Registered pools bypass discovery, so apply the token filter here too
+        found.retain(|p| self.dex.permits(p));
+        self.dex.apply_transfer_taxes(&mut found).await;
+        if self.exclude_taxed {
+            found.retain(|p| p.token0.transfer_tax_bps == 0 && p.token1.transfer_tax_bps == 0);
+        }
+        if let Some(screen) = &self.contracts {
+            let mut tradeable = Vec::with_capacity(found.len());
+            for pool in found {
+                if screen.is_tradeable(&pool).await {
+                    tradeable.push(pool);
+                }
+            }
+            found = tradeable;
+        }
+        if let Some(detector) = &self.honeypots {
+            let mut tradeable = Vec::with_capacity(found.len());
+            for pool in found {
+                if detector.is_tradeable(&pool).await {
+                    tradeable.push(pool);
+                }
+            }
+            found = tradeable;
+        }
+
+        // Keep pools already loaded, with their refreshed state; only add new ones
+        let mut pools = self.pools.write().await;
+        let before = pools.len();
+        for pool in found {
+            let known = pools.iter().any(|p| {
+                p.address == pool.address && p.token0.address == pool.token0.address && p.token1.address == pool.token1.address
+            });
+            if !known {
+                // Event-driven state for the venues that emit it; the rest are polled
+                self.cache.track(&pool);
+                pools.push(pool);
+            }
+        }
+        if pools.len() != before {
+            info!(pools = pools.len(), "🏊 Arbitrage pools loaded");
+        }
+    }
+
+    // Pools between target tokens from the subgraphs when configured, falling
+    // back to walking the factories when they fail or list nothing
+    async fn discover(&self) -> Vec<DexPool> {
+        if let Some(subgraph) = self.subgraph.as_ref().filter(|s| s.has_pools()) {
+            match subgraph.pools(&self.tokens).await {
+                Ok(listed) if !listed.is_empty() => {
+                    let pools = self.dex.load_pools(&listed).await;
+                    if !pools.is_empty() {
+                        return pools;
+                    }
+                }
+                Ok(_) => {}
+                Err(e) => warn!(error = %e, "⚠️ Subgraph pool listing failed, walking factories"),
+            }
+        }
+        self.dex.get_all_pools(&self.tokens).await
+    }
+
+    // Registered pools between target tokens; an empty registry is seeded by discovery
+    async fn load_registered(&self, registry: &PoolRegistry) -> Result<Vec<DexPool>> {
+        if registry.count().await? == 0 {
+            let pools = self.discover().await;
+            for pool in &pools {
+                registry.upsert(pool).await?;
+            }
+            return Ok(pools);
+        }
+
+        Ok(registry
+            .load_all()
+            .await?
+            .into_iter()
+            .filter(|p| self.tokens.contains(&p.token0.address) && self.tokens.contains(&p.token1.address))
+            .collect())
+    }
+}
+
+#[async_trait]
+impl Strategy for ArbitrageStrategy {
+    fn name(&self) -> &str {
+        ARBITRAGE_STRATEGY
+    }
+
+    async fn on_block(&self, snapshot: &BlockSnapshot) -> Result<Vec<Action>> {
+        self.ensure_pools(snapshot.number).await;
+        *self.last_block.write().await = snapshot.clone();
+        if let Some(reorg) = &snapshot.reorg {
+            let dropped = self.cache.invalidate_from(reorg.first_orphaned());
+            info!(dropped, from = %reorg.first_orphaned(), "🔀 Pool state from orphaned blocks dropped");
+        }
+
+        let mut stored = self.pools.write().await;
+        // Only pools touched since they were last read, or that the cache
+        // can't follow, are read from chain, concurrently and all at this block
+        let stale = stored
+            .iter_mut()
+            .filter_map(|pool| (!self.cache.apply(pool, snapshot.number)).then_some(pool));
+        let refreshed = read_concurrently(stale, |pool| async move {
+            let result = self.dex.refresh_pool(pool, snapshot.number).await;
+            match &result {
+                Ok(()) => self.cache.seed(pool, snapshot.number),
+                Err(e) => warn!(pool = ?pool.address, error = %e, "⚠️ Refresh failed"),
+            }
+            result
+        })
+        .await
+        .len();
+        debug!(refreshed, pools = stored.len(), "♻️ Pools re-read");
+        // The token filter can be reloaded, so apply it to every scan. Pools
+        // whose refresh failed still hold an older block and sit this one out.
+        let pools: Vec<DexPool> = stored
+            .iter()
+            .filter(|p| p.block == snapshot.number && self.dex.permits(p))
+            .cloned()
+            .collect();
+        // The scan works on the copy; readers such as the gRPC pool listing
+        // shouldn't wait out its round-trips
+        drop(stored);
+        let prices = match &self.pricer {
+            Some(pricer) => pricer.prices(&pools, snapshot.number, snapshot.timestamp).await,
+            None => UsdPrices::default(),
+        };
+        let thresholds = self.thresholds.load_full();
+        self.ranking.lock().unwrap().record(self.cache.take_swapped(), &pools, &prices);
+        let pools = liquid(pools, &prices, thresholds.min_liquidity);
+        let pools = self.ranking.lock().unwrap().top_per_pair(pools, &prices, self.pools_per_pair);
+
+        self.report_cycles(&pools, self.weth).await;
+
+        let opportunities = self
+            .dex
+            .find_arbitrage_opportunities(&pools, snapshot.gas_price, snapshot.l1_fee_per_byte, snapshot.number)
+            .await;
+        #[cfg(feature = "postgres-sink")]
+        self.record(&opportunities);
+
+        let opportunities = actionable(opportunities, self.weth, thresholds.min_profit, &prices);
+        *self.prices.write().await = prices;
+        let opportunities = self.rank(opportunities, snapshot);
+        if let Some(feed) = &self.feed {
+            opportunities.iter().for_each(|o| feed.publish(o));
+        }
+        Ok(opportunities.into_iter().map(Action::Arbitrage).collect())
+    }
+
+    // Backruns are reported, not executed: they only pay behind the pending
+    // swap, in the same bundle, which the flash-loan executor doesn't build
+    async fn on_pending_tx(&self, tx: &Transaction) -> Result<Vec<Action>> {
+        let swaps = mempool::decode_swaps(tx);
+        self.record_competitors(&swaps);
+        for opportunity in self.backruns(&swaps).await {
+            info!(
+                tx = ?tx.hash,
+                buy = ?opportunity.buy_pool.address,
+                sell = ?opportunity.sell_pool.address,
+                profit = %opportunity.gross_profit,
+                "🐋 Backrun opportunity behind pending swap"
+            );
+        }
+        Ok(Vec::new())
+    }
+}
@@ -0,0 +1,64 @@
+use async_trait::async_trait;
+use std::sync::Arc;
+use tokio::sync::RwLock;
+use tracing::info;
+
+use super::{Action, Strategy};
+use crate::{
+    block_state::BlockSnapshot,
+    cex::CexFeed,
+    errors::Result,
+    feed::CexDexFeed,
+    models::DexPool,
+};
+
+pub const CEX_DEX_STRATEGY: &str = "cex_dex";
+
+// Flags pools the arbitrage scan tracks whose price has moved away from the
+// exchanges. Nothing is executed: the exchange leg is left to whoever
+// consumes the feed.
+pub struct CexDexStrategy {
+    cex: Arc<CexFeed>,
+    // The arbitrage strategy's pools, refreshed by it each block
+    pools: Arc<RwLock<Vec<DexPool>>>,
+    feed: Option<CexDexFeed>,
+}
+
+impl CexDexStrategy {
+    pub fn new(cex: Arc<CexFeed>, pools: Arc<RwLock<Vec<DexPool>>>) -> Self {
+        Self { cex, pools, feed: None }
+    }
+
+    // Publish each deviation as it's found
+    pub fn with_feed(mut self, feed: CexDexFeed) -> Self {
+        self.feed = Some(feed);
+        self
+    }
+}
+
+#[async_trait]
+impl Strategy for CexDexStrategy {
+    fn name(&self) -> &str {
+        CEX_DEX_STRATEGY
+    }
+
+    async fn on_block(&self, snapshot: &BlockSnapshot) -> Result<Vec<Action>> {
+        let pools = self.pools.read().await;
+        for opportunity in self.cex.deviations(&pools, snapshot.number) {
+            info!(
+                pair = %opportunity.pair,
+                dex = %opportunity.pool.dex,
+                pool = ?opportunity.pool.address,
+                buy_on_dex = opportunity.buy_on_dex,
+                dex_price = opportunity.dex_price,
+                cex_mid = opportunity.cex_mid,
+                edge_bps = opportunity.edge_bps,
+                "🏦 DEX pool off the exchange price"
+            );
+            if let Some(feed) = &self.feed {
+                feed.publish(&opportunity);
+            }
+        }
+        Ok(Vec::new())
+    }
+}
@@ -0,0 +1,184 @@
+use async_trait::async_trait;
+use ethers::{
+    prelude::*,
+    providers::{Provider, Ws},
+    types::{Filter, Log, Transaction},
+};
+use futures::stream::{self, BoxStream};
+use std::sync::{Arc, Mutex};
+use tokio::sync::{mpsc, watch};
+use tracing::{error, info, info_span, warn, Instrument};
+
+use crate::{
+    block_state::BlockSnapshot,
+    errors::Result,
+    models::{ArbitrageOpportunity, AuctionTake, LiquidationTarget, OracleBackrun, TroveBatch},
+};
+
+pub mod arbitrage;
+pub mod cex_dex;
+
+pub use arbitrage::ArbitrageStrategy;
+pub use cex_dex::CexDexStrategy;
+
+// What a strategy wants done; the engine hands these to the shared executor
+#[derive(Debug, Clone)]
+pub enum Action {
+    Arbitrage(ArbitrageOpportunity),
+    Liquidation(LiquidationTarget),
+    OracleBackrun(OracleBackrun),
+    AuctionTake(AuctionTake),
+    TroveLiquidation(TroveBatch),
+}
+
+// One independent source of opportunities. Every hook defaults to doing nothing
+// so a strategy only implements the events it cares about.
+#[async_trait]
+pub trait Strategy: Send + Sync {
+    fn name(&self) -> &str;
+
+    // Logs this strategy wants delivered to `on_log`
+    fn log_filter(&self) -> Option<Filter> {
+        None
+    }
+
+    async fn on_block(&self, _snapshot: &BlockSnapshot) -> Result<Vec<Action>> {
+        Ok(Vec::new())
+    }
+
+    async fn on_pending_tx(&self, _tx: &Transaction) -> Result<Vec<Action>> {
+        Ok(Vec::new())
+    }
+
+    async fn on_log(&self, _log: &Log) -> Result<Vec<Action>> {
+        Ok(Vec::new())
+    }
+}
+
+// Turns actions into transactions; shared by every strategy
+#[async_trait]
+pub trait ActionExecutor: Send + Sync {
+    async fn execute(&self, strategy: &str, action: Action) -> Result<()>;
+}
+
+pub struct Engine {
+    strategies: Vec<Arc<dyn Strategy>>,
+    executor: Arc<dyn ActionExecutor>,
+    // Pending transactions from outside the node, taken when the engine starts
+    mempool: Mutex<Option<mpsc::Receiver<Transaction>>>,
+}
+
+impl Engine {
+    pub fn new(executor: Arc<dyn ActionExecutor>) -> Self {
+        Self {
+            strategies: Vec::new(),
+            executor,
+            mempool: Mutex::new(None),
+        }
+    }
+
+    pub fn register(&mut self, strategy: Arc<dyn Strategy>) {
+        info!(strategy = strategy.name(), "🧩 Strategy registered");
+        self.strategies.push(strategy);
+    }
+
+    // Read pending transactions from `txs`, a bloXroute stream say, instead of
+    // subscribing to the node's txpool
+    pub fn set_mempool(&mut self, txs: mpsc::Receiver<Transaction>) {
+        *self.mempool.get_mut().unwrap() = Some(txs);
+    }
+
+    // Fan block snapshots, pending transactions and subscribed logs out to every strategy
+    pub async fn run(
+        self: Arc<Self>,
+        provider: Arc<Provider<Ws>>,
+        mut snapshots: watch::Receiver<Arc<BlockSnapshot>>,
+    ) -> Result<()> {
+        let external = self.mempool.lock().unwrap().take();
+        let mut pending: BoxStream<'_, Transaction> = match external {
+            Some(mut txs) => stream::poll_fn(move |cx| txs.poll_recv(cx)).boxed(),
+            // The node only announces hashes, so each is fetched
+            None => {
+                let fetcher = provider.clone();
+                provider
+                    .subscribe_pending_txs()
+                    .await?
+                    .filter_map(move |hash| {
+                        let provider = fetcher.clone();
+                        async move { provider.get_transaction(hash).await.ok().flatten() }
+                    })
+                    .boxed()
+            }
+        };
+
+        let mut log_streams: Vec<BoxStream<'_, (usize, Log)>> = Vec::new();
+        for (index, strategy) in self.strategies.iter().enumerate() {
+            if let Some(filter) = strategy.log_filter() {
+                let logs = provider.subscribe_logs(&filter).await?;
+                log_streams.push(logs.map(move |log| (index, log)).boxed());
+            }
+        }
+        let mut logs = stream::select_all(log_streams);
+
+        loop {
+            tokio::select! {
+                changed = snapshots.changed() => {
+                    if changed.is_err() {
+                        break;
+                    }
+                    let snapshot = snapshots.borrow_and_update().clone();
+                    for strategy in &self.strategies {
+                        let span = info_span!("scan", strategy = strategy.name(), block = %snapshot.number);
+                        async {
+                            let actions = strategy.on_block(&snapshot).await;
+                            self.dispatch(strategy.as_ref(), "block", actions).await
+                        }
+                        .instrument(span)
+                        .await?;
+                    }
+                }
+                Some(tx) = pending.next() => {
+                    for strategy in &self.strategies {
+                        let actions = strategy.on_pending_tx(&tx).await;
+                        self.dispatch(strategy.as_ref(), "pending tx", actions).await?;
+                    }
+                }
+                Some((index, log)) = logs.next() => {
+                    let strategy = &self.strategies[index];
+                    let actions = strategy.on_log(&log).await;
+                    self.dispatch(strategy.as_ref(), "log", actions).await?;
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    // Errors from one strategy or action never stop the others unless fatal
+    async fn dispatch(
+        &self,
+        strategy: &dyn Strategy,
+        event: &str,
+        actions: Result<Vec<Action>>,
+    ) -> Result<()> {
+        let actions = match actions {
+            Ok(actions) => actions,
+            Err(e) if e.is_fatal() => return Err(e),
+            Err(e) => {
+                warn!(strategy = strategy.name(), event, error = %e, "⚠️ Strategy failed handling event");
+                return Ok(());
+            }
+        };
+
+        for action in actions {
+            if let Err(e) = self.executor.execute(strategy.name(), action).await {
+                if e.is_fatal() {
+                    return Err(e);
+                }
+                error!(strategy = strategy.name(), error = %e, "❌ Action failed");
+            }
+        }
+
+        Ok(())
+    }
+}
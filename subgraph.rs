@@ -0,0 +1,200 @@
+use ethers::types::Address;
+use serde_json::{json, Value};
+use std::{str::FromStr, time::Duration};
+use tracing::info;
+
+use crate::{
+    errors::{BotError, ProviderError, Result},
+    models::DexType,
+};
+
+// Most entities The Graph returns per query
+const PAGE_SIZE: usize = 1_000;
+// Furthest `skip` The Graph accepts
+const MAX_SKIP: usize = 5_000;
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(30);
+
+// What pool lists are sorted by
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum PoolOrder {
+    #[default]
+    Tvl,
+    Volume,
+}
+
+impl FromStr for PoolOrder {
+    type Err = String;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s.trim() {
+            "tvl" => Ok(PoolOrder::Tvl),
+            "volume" => Ok(PoolOrder::Volume),
+            other => Err(format!("unknown pool order {}, expected tvl or volume", other)),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct SubgraphConfig {
+    // Pool subgraph per venue: Uniswap V2's schema for V2 and SushiSwap,
+    // Uniswap V3's for V3
+    pub pools: Vec<(DexType, String)>,
+    // Aave V3 subgraph, for borrowers
+    pub aave: Option<String>,
+    pub order: PoolOrder,
+    // Pools taken from each venue's subgraph
+    pub top_pools: usize,
+}
+
+impl SubgraphConfig {
+    pub fn from_env() -> Result<Self> {
+        let url = |name: &str| std::env::var(name).ok().filter(|u| !u.trim().is_empty());
+        let pools = [
+            (DexType::UniswapV2, "SUBGRAPH_UNISWAP_V2"),
+            (DexType::SushiSwap, "SUBGRAPH_SUSHISWAP"),
+            (DexType::UniswapV3, "SUBGRAPH_UNISWAP_V3"),
+        ]
+        .into_iter()
+        .filter_map(|(dex, name)| Some((dex, url(name)?)))
+        .collect();
+        let order = match url("SUBGRAPH_POOL_ORDER") {
+            Some(order) => order.parse().map_err(|e| BotError::Config(format!("SUBGRAPH_POOL_ORDER: {}", e)))?,
+            None => PoolOrder::default(),
+        };
+
+        Ok(Self {
+            pools,
+            aave: url("SUBGRAPH_AAVE"),
+            order,
+            top_pools: std::env::var("SUBGRAPH_TOP_POOLS")
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(500),
+        })
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.pools.is_empty() && self.aave.is_none()
+    }
+}
+
+// The Graph as a fast start: pool lists from the DEX subgraphs and borrowers
+// from Aave's, instead of walking factories and the pool's whole log history.
+// Subgraphs lag the chain and can be wrong, so what they return is only a
+// list of addresses; state is still read on chain.
+pub struct SubgraphClient {
+    http: reqwest::Client,
+    config: SubgraphConfig,
+}
+
+impl SubgraphClient {
+    // None when no subgraph is configured
+    pub fn new(config: SubgraphConfig) -> Option<Self> {
+        if config.is_empty() {
+            return None;
+        }
+        Some(Self {
+            http: reqwest::Client::builder().timeout(REQUEST_TIMEOUT).build().unwrap_or_default(),
+            config,
+        })
+    }
+
+    pub fn has_pools(&self) -> bool {
+        !self.config.pools.is_empty()
+    }
+
+    pub fn has_borrowers(&self) -> bool {
+        self.config.aave.is_some()
+    }
+
+    // The top pools between `tokens` on every configured venue, in the
+    // configured order
+    pub async fn pools(&self, tokens: &[Address]) -> Result<Vec<(DexType, Address)>> {
+        let tokens: Vec<String> = tokens.iter().map(|t| format!("{:?}", t)).collect();
+        let mut found = Vec::new();
+        for (dex, url) in &self.config.pools {
+            let (entity, tvl) = match dex {
+                DexType::UniswapV3 => ("pools", "totalValueLockedUSD"),
+                _ => ("pairs", "reserveUSD"),
+            };
+            let order_by = match self.config.order {
+                PoolOrder::Tvl => tvl,
+                PoolOrder::Volume => "volumeUSD",
+            };
+            let query = format!(
+                "query($first: Int!, $skip: Int!, $tokens: [String!]) {{ \
+                   {entity}(first: $first, skip: $skip, orderBy: {order_by}, orderDirection: desc, \
+                     where: {{ token0_in: $tokens, token1_in: $tokens }}) {{ id }} }}"
+            );
+
+            let mut pools = Vec::new();
+            while pools.len() < self.config.top_pools && pools.len() <= MAX_SKIP {
+                let first = PAGE_SIZE.min(self.config.top_pools - pools.len());
+                let data = self
+                    .query(url, &query, json!({"first": first, "skip": pools.len(), "tokens": tokens}))
+                    .await?;
+                let page = ids(&data[entity]);
+                let done = page.len() < first;
+                pools.extend(page);
+                if done {
+                    break;
+                }
+            }
+            info!(dex = %dex, pools = pools.len(), "🛰️ Pools listed by subgraph");
+            found.extend(pools.into_iter().map(|pool| (dex.clone(), pool)));
+        }
+        Ok(found)
+    }
+
+    // Every account with an open borrow on the Aave pool
+    pub async fn borrowers(&self) -> Result<Vec<Address>> {
+        let Some(url) = &self.config.aave else {
+            return Ok(Vec::new());
+        };
+        let query = "query($first: Int!, $last: String!) { \
+                       users(first: $first, orderBy: id, orderDirection: asc, \
+                         where: { id_gt: $last, borrowedReservesCount_gt: 0 }) { id } }";
+
+        // Paged by id, since `skip` stops at 5000
+        let mut borrowers: Vec<Address> = Vec::new();
+        loop {
+            let last = borrowers.last().map(|b| format!("{:?}", b)).unwrap_or_default();
+            let data = self.query(url, query, json!({"first": PAGE_SIZE, "last": last})).await?;
+            let page = ids(&data["users"]);
+            let done = page.len() < PAGE_SIZE;
+            borrowers.extend(page);
+            if done {
+                break;
+            }
+        }
+        info!(borrowers = borrowers.len(), "🛰️ Borrowers listed by subgraph");
+        Ok(borrowers)
+    }
+
+    async fn query(&self, url: &str, query: &str, variables: Value) -> Result<Value> {
+        let response: Value = self
+            .http
+            .post(url)
+            .json(&json!({"query": query, "variables": variables}))
+            .send()
+            .await
+            .and_then(|r| r.error_for_status())
+            .map_err(|e| ProviderError::Rpc(format!("subgraph: {}", e)))?
+            .json()
+            .await
+            .map_err(|e| ProviderError::Rpc(format!("subgraph: {}", e)))?;
+
+        if let Some(errors) = response.get("errors") {
+            return Err(ProviderError::Rpc(format!("subgraph: {}", errors)).into());
+        }
+        Ok(response["data"].clone())
+    }
+}
+
+// Entity ids that parse as addresses
+fn ids(entities: &Value) -> Vec<Address> {
+    entities
+        .as_array()
+        .map(|list| list.iter().filter_map(|e| e["id"].as_str()?.parse().ok()).collect())
+        .unwrap_or_default()
+}
@@ -0,0 +1,29 @@
+use arc_swap::ArcSwap;
+use ethers::types::U256;
+use std::{sync::Arc, time::Duration};
+
+// Limits an operator can tighten or loosen while the bot runs. Readers take a
+// snapshot with `load()` per decision, so a reload applies from the next one
+// without restarting tasks or dropping warm caches.
+#[derive(Debug, Clone)]
+pub struct Thresholds {
+    // Minimum profit after gas, in wei
+    pub min_profit: U256,
+    // Minimum liquidation profit after win probability, in wei
+    pub min_expected_value: U256,
+    // Pools holding less USD than this (18 decimals) are left out of arbitrage scans
+    pub min_liquidity: U256,
+    pub max_gas_price: U256,
+    // Positions below this health factor are watched
+    pub health_factor: f64,
+    // Between liquidation position scans
+    pub scan_interval: Duration,
+}
+
+pub type SharedThresholds = Arc<ArcSwap<Thresholds>>;
+
+impl Thresholds {
+    pub fn shared(self) -> SharedThresholds {
+        Arc::new(ArcSwap::from_pointee(self))
+    }
+}
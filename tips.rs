@@ -0,0 +1,81 @@
+use dashmap::DashMap;
+use ethers::types::{H256, U256};
+use std::collections::HashMap;
+use std::sync::Mutex;
+
+// Builder payments tried, as shares of expected profit in basis points
+const TIP_LEVELS_BPS: [u64; 9] = [500, 1_000, 2_000, 3_000, 4_000, 5_000, 6_000, 7_000, 9_000];
+// Pseudo-observations behind the prior at each level; real outcomes outweigh
+// it after a handful of bundles
+const PRIOR_WEIGHT: f64 = 4.0;
+
+#[derive(Debug, Clone, Copy, Default)]
+struct LevelStats {
+    submitted: u64,
+    included: u64,
+}
+
+// Picks what to pay the block builder out of each bundle's profit. A bigger
+// tip wins more blocks but keeps less, so the tip chosen maximises
+// P(inclusion | tip) * (1 - tip), with P learnt from how bundles at each level
+// fared. Until there is data, inclusion is assumed to grow in line with the tip.
+pub struct TipOptimizer {
+    levels: DashMap<u64, LevelStats>,
+    // Our transaction in each unresolved bundle -> its tip level
+    pending: Mutex<HashMap<H256, u64>>,
+}
+
+impl TipOptimizer {
+    pub fn new() -> Self {
+        Self {
+            levels: TIP_LEVELS_BPS.iter().map(|bps| (*bps, LevelStats::default())).collect(),
+            pending: Mutex::new(HashMap::new()),
+        }
+    }
+
+    // Posterior inclusion probability at each level, made non-decreasing in
+    // the tip: paying more never makes a bundle less attractive
+    pub fn inclusion_probabilities(&self) -> Vec<(u64, f64)> {
+        let mut floor: f64 = 0.0;
+        TIP_LEVELS_BPS
+            .iter()
+            .map(|bps| {
+                let stats = self.levels.get(bps).map(|s| *s).unwrap_or_default();
+                let prior = *bps as f64 / 10_000.0;
+                let p = (stats.included as f64 + PRIOR_WEIGHT * prior) / (stats.submitted as f64 + PRIOR_WEIGHT);
+                floor = floor.max(p);
+                (*bps, floor)
+            })
+            .collect()
+    }
+
+    // Level with the highest expected share of profit kept
+    pub fn optimal_bps(&self) -> u64 {
+        self.inclusion_probabilities()
+            .into_iter()
+            .map(|(bps, p)| (bps, p * (1.0 - bps as f64 / 10_000.0)))
+            .max_by(|a, b| a.1.partial_cmp(&b.1).unwrap_or(std::cmp::Ordering::Equal))
+            .map(|(bps, _)| bps)
+            .unwrap_or(TIP_LEVELS_BPS[0])
+    }
+
+    // Builder payment for a bundle expected to make `profit` wei, and the level it was taken at
+    pub fn tip(&self, profit: U256) -> (U256, u64) {
+        let bps = self.optimal_bps();
+        (profit * U256::from(bps) / U256::from(10_000), bps)
+    }
+
+    pub fn record_submission(&self, tx_hash: H256, bps: u64) {
+        self.pending.lock().unwrap().insert(tx_hash, bps);
+        self.levels.entry(bps).or_default().submitted += 1;
+    }
+
+    pub fn record_outcome(&self, tx_hash: H256, included: bool) {
+        let Some(bps) = self.pending.lock().unwrap().remove(&tx_hash) else {
+            return;
+        };
+        if included {
+            self.levels.entry(bps).or_default().included += 1;
+        }
+    }
+}
@@ -0,0 +1,157 @@
+use ethers::{prelude::*, types::Address};
+use std::{collections::HashMap, sync::Arc};
+use tracing::warn;
+
+use crate::{
+    chains::ChainProfile,
+    dex::uniswap_v2::UniswapV2Pair,
+    errors::{BotError, ProviderError, Result},
+    models::DexType,
+    registry::PoolRegistry,
+};
+
+pub const WETH: &str = "0xC02aaA39b223FE8D0A0e5C4F27eAD9083C756Cc2";
+
+// Pairs whose reserves are read per multicall when ranking by TVL
+const RESERVES_BATCH: usize = 500;
+
+// Tokens the arbitrage scan trades between, from TARGET_TOKENS: addresses and
+// preset names ("bluechips", "stables", "top<N>-by-tvl") separated by commas
+// or newlines.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TargetTokens {
+    // Listed addresses and static presets
+    pub fixed: Vec<Address>,
+    // Also the N tokens with the most wrapped native liquidity in registered V2 pairs
+    pub top_by_tvl: Option<usize>,
+    // WETH, or the chain's wrapped native token, that pairs are ranked against
+    pub native: Address,
+}
+
+impl Default for TargetTokens {
+    fn default() -> Self {
+        Self::for_chain(&ChainProfile::mainnet())
+    }
+}
+
+impl TargetTokens {
+    // The chain's bluechips
+    pub fn for_chain(chain: &ChainProfile) -> Self {
+        Self {
+            fixed: chain.bluechips.clone(),
+            top_by_tvl: None,
+            native: chain.wrapped_native,
+        }
+    }
+
+    // Presets expand to `chain`'s tokens
+    pub fn parse(spec: &str, chain: &ChainProfile) -> Result<Self> {
+        let mut tokens = Self {
+            fixed: Vec::new(),
+            top_by_tvl: None,
+            native: chain.wrapped_native,
+        };
+        let entries = spec
+            .split([',', '\n'])
+            .map(|e| e.split('#').next().unwrap_or_default().trim())
+            .filter(|e| !e.is_empty());
+
+        for entry in entries {
+            let found = match entry {
+                "bluechips" => chain.bluechips.clone(),
+                "stables" => chain.stables.clone(),
+                _ => match top_by_tvl(entry) {
+                    Some(n) => {
+                        tokens.top_by_tvl = Some(n);
+                        continue;
+                    }
+                    None => vec![entry
+                        .parse()
+                        .map_err(|_| BotError::Config(format!("unknown target token or preset {}", entry)))?],
+                },
+            };
+            for token in found {
+                if !tokens.fixed.contains(&token) {
+                    tokens.fixed.push(token);
+                }
+            }
+        }
+
+        if tokens.fixed.is_empty() && tokens.top_by_tvl.is_none() {
+            return Err(BotError::Config("TARGET_TOKENS selects no tokens".to_string()));
+        }
+        Ok(tokens)
+    }
+
+    // The full token list. Ranking by TVL needs the pool registry; without it
+    // only the fixed tokens are returned.
+    pub async fn resolve<M: Middleware + 'static>(
+        &self,
+        provider: Arc<M>,
+        registry: Option<&PoolRegistry>,
+    ) -> Result<Vec<Address>> {
+        let mut tokens = self.fixed.clone();
+        let Some(n) = self.top_by_tvl else {
+            return Ok(tokens);
+        };
+        let Some(registry) = registry else {
+            warn!("⚠️ top{}-by-tvl needs the pool registry; using listed tokens only", n);
+            return Ok(tokens);
+        };
+
+        for token in rank_by_native_liquidity(provider, registry, self.native).await?.into_iter().take(n) {
+            if !tokens.contains(&token) {
+                tokens.push(token);
+            }
+        }
+        Ok(tokens)
+    }
+}
+
+// "top100-by-tvl" -> 100
+fn top_by_tvl(entry: &str) -> Option<usize> {
+    entry.strip_prefix("top")?.strip_suffix("-by-tvl")?.parse().ok()
+}
+
+// Tokens by the WETH (or wrapped native token) held in their registered
+// Uniswap V2, SushiSwap and PancakeSwap pairs, largest first. Half of a V2
+// pair's value sits on each side, so this ranks the same as pair TVL. WETH
+// itself always comes first.
+async fn rank_by_native_liquidity<M: Middleware + 'static>(
+    provider: Arc<M>,
+    registry: &PoolRegistry,
+    weth: Address,
+) -> Result<Vec<Address>> {
+    let pairs: Vec<_> = registry
+        .load_all()
+        .await?
+        .into_iter()
+        .filter(|p| matches!(p.dex, DexType::UniswapV2 | DexType::SushiSwap | DexType::PancakeSwapV2))
+        .filter_map(|p| p.other(weth).map(|t| (p.address, p.token0.address == weth, t.address)))
+        .collect();
+
+    let mut liquidity: HashMap<Address, U256> = HashMap::new();
+    for batch in pairs.chunks(RESERVES_BATCH) {
+        let mut multicall = Multicall::new(provider.clone(), None)
+            .await
+            .map_err(|e| ProviderError::Rpc(e.to_string()))?;
+        for (pair, _, _) in batch {
+            multicall.add_call(UniswapV2Pair::new(*pair, provider.clone()).get_reserves(), false);
+        }
+        let reserves: Vec<(u128, u128, u32)> = match multicall.call_array().await {
+            Ok(reserves) => reserves,
+            Err(e) => {
+                warn!(pairs = batch.len(), error = %e, "⚠️ Reading pair reserves failed");
+                continue;
+            }
+        };
+        for ((_, weth_is_token0, token), (reserve0, reserve1, _)) in batch.iter().zip(reserves) {
+            let weth_reserve = if *weth_is_token0 { reserve0 } else { reserve1 };
+            *liquidity.entry(*token).or_default() += U256::from(weth_reserve);
+        }
+    }
+
+    let mut ranked: Vec<_> = liquidity.into_iter().collect();
+    ranked.sort_by(|a, b| b.1.cmp(&a.1));
+    Ok(std::iter::once(weth).chain(ranked.into_iter().map(|(token, _)| token)).collect())
+}
@@ -0,0 +1,225 @@
+use ethers::{
+    prelude::*,
+    providers::{Provider, Ws},
+    types::{Address, U256},
+};
+use std::{sync::Arc, time::Duration};
+use tracing::{debug, info, warn};
+
+use liquidation_bot::{policy::PolicySigner, signer::WalletSigner};
+
+use crate::{
+    balance::{BalanceConfig, Weth},
+    errors::{ExecutionError, Result},
+    monitoring::{AlertLevel, AlertManager},
+};
+
+abigen!(
+    TreasuryToken,
+    r#"[
+        function balanceOf(address owner) external view returns (uint256)
+        function approve(address spender, uint256 amount) external returns (bool)
+        function transfer(address to, uint256 amount) external returns (bool)
+    ]"#
+);
+
+abigen!(
+    TreasuryRouter,
+    r#"[
+        function getAmountsOut(uint256 amountIn, address[] path) external view returns (uint256[])
+        function swapExactTokensForTokens(uint256 amountIn, uint256 amountOutMin, address[] path, address to, uint256 deadline) external returns (uint256[])
+    ]"#
+);
+
+// Owner-only withdrawals on the liquidation executor, where profits accrue
+abigen!(
+    ProfitVault,
+    r#"[
+        function owner() external view returns (address)
+        function withdrawProfit() external
+        function withdrawToken(address token) external
+    ]"#
+);
+
+type Client = SignerMiddleware<Arc<Provider<Ws>>, PolicySigner<WalletSigner>>;
+
+// Stray ETH below this isn't worth the gas to wrap
+const MIN_WRAP_WEI: u64 = 10_000_000_000_000_000;
+const SWAP_DEADLINE_SECS: u64 = 300;
+
+#[derive(Debug, Clone)]
+pub struct TreasuryConfig {
+    pub enabled: bool,
+    pub interval: Duration,
+    // Cold wallet surplus is swept to; nothing leaves the hot wallet when unset
+    pub cold_wallet: Option<Address>,
+    // Tokens profits arrive in, sold into `consolidate_into`
+    pub profit_tokens: Vec<Address>,
+    // WETH or USDC
+    pub consolidate_into: Address,
+    pub usdc: Address,
+    pub router: Address,
+    pub slippage_bps: u64,
+    // Surplus over the working balances before a sweep is worth sending
+    pub sweep_threshold_weth: U256,
+    pub sweep_threshold_usdc: U256,
+}
+
+// Moves profits out of the hot wallet: collects them from the executor, sells
+// them into WETH or USDC, wraps ETH above the gas float, and sweeps whatever
+// exceeds the working balances to the cold wallet. Every transaction still
+// goes through the signing policy, so the router, the profit tokens and the
+// cold wallet have to be allowed destinations.
+pub struct Treasury {
+    config: TreasuryConfig,
+    // Gas float and WETH working capital are kept back
+    balance: BalanceConfig,
+    // Largest wrap per pass, so a deposit stays under the policy's value cap
+    max_wrap: U256,
+    provider: Arc<Provider<Ws>>,
+    client: Arc<Client>,
+    wallet: Address,
+    executor: Address,
+    alerts: Arc<AlertManager>,
+}
+
+impl Treasury {
+    pub fn new(
+        config: TreasuryConfig,
+        balance: BalanceConfig,
+        max_wrap: U256,
+        provider: Arc<Provider<Ws>>,
+        wallet: PolicySigner<WalletSigner>,
+        executor: Address,
+        alerts: Arc<AlertManager>,
+    ) -> Self {
+        let address = wallet.address();
+        let client = Arc::new(SignerMiddleware::new(provider.clone(), wallet));
+
+        Self {
+            config,
+            balance,
+            max_wrap,
+            provider,
+            client,
+            wallet: address,
+            executor,
+            alerts,
+        }
+    }
+
+    // One pass: collect, consolidate, wrap, sweep
+    pub async fn run_once(&self) -> Result<()> {
+        self.collect().await?;
+        self.consolidate().await?;
+        self.wrap().await?;
+        self.sweep().await
+    }
+
+    // Withdraw what the executor holds, when the wallet owns it
+    async fn collect(&self) -> Result<()> {
+        let vault = ProfitVault::new(self.executor, self.client.clone());
+        if vault.owner().call().await? != self.wallet {
+            debug!(executor = ?self.executor, "Wallet doesn't own the executor, profits stay there");
+            return Ok(());
+        }
+
+        let tokens = self
+            .config
+            .profit_tokens
+            .iter()
+            .copied()
+            .chain([self.balance.weth, self.config.usdc]);
+        for token in tokens {
+            let held = TreasuryToken::new(token, self.client.clone()).balance_of(self.executor).call().await?;
+            if !held.is_zero() {
+                vault.withdraw_token(token).send().await?.await?;
+                info!(?token, amount = %held, "📥 Collected profit from executor");
+            }
+        }
+        if !self.provider.get_balance(self.executor, None).await?.is_zero() {
+            vault.withdraw_profit().send().await?.await?;
+            info!("📥 Collected ETH profit from executor");
+        }
+        Ok(())
+    }
+
+    // Sell each profit token into the consolidation token through the V2 router
+    async fn consolidate(&self) -> Result<()> {
+        let router = TreasuryRouter::new(self.config.router, self.client.clone());
+        let target = self.config.consolidate_into;
+
+        for &token in &self.config.profit_tokens {
+            if token == target || token == self.balance.weth {
+                continue;
+            }
+            let erc20 = TreasuryToken::new(token, self.client.clone());
+            let amount = erc20.balance_of(self.wallet).call().await?;
+            if amount.is_zero() {
+                continue;
+            }
+            let path = if target == self.balance.weth {
+                vec![token, target]
+            } else {
+                vec![token, self.balance.weth, target]
+            };
+            // No route is a listing problem, not a reason to stop the pass
+            let Ok(amounts) = router.get_amounts_out(amount, path.clone()).call().await else {
+                warn!(?token, "⚠️ No router path to consolidate profit token");
+                continue;
+            };
+            let min_out = amounts.last().copied().unwrap_or_default() * (10_000 - self.config.slippage_bps) / 10_000;
+
+            // Exact approvals, so the policy's token caps see the real amount
+            erc20.approve(self.config.router, amount).send().await?.await?;
+            let deadline = U256::from(chrono::Utc::now().timestamp() as u64 + SWAP_DEADLINE_SECS);
+            router
+                .swap_exact_tokens_for_tokens(amount, min_out, path, self.wallet, deadline)
+                .send()
+                .await?
+                .await?
+                .filter(|r| r.status == Some(U64::from(1)))
+                .ok_or_else(|| ExecutionError::Reverted(format!("consolidating {:?}", token)))?;
+            info!(?token, %amount, "🔄 Consolidated profit token");
+        }
+        Ok(())
+    }
+
+    // ETH above the gas float becomes WETH, to be swept with the rest
+    async fn wrap(&self) -> Result<()> {
+        let eth = self.provider.get_balance(self.wallet, None).await?;
+        let surplus = eth.saturating_sub(self.balance.target_eth);
+        if surplus < U256::from(MIN_WRAP_WEI) {
+            return Ok(());
+        }
+        let amount = surplus.min(self.max_wrap);
+        Weth::new(self.balance.weth, self.client.clone()).deposit().value(amount).send().await?.await?;
+        info!(amount = %ethers::utils::format_ether(amount), "🔄 Wrapped surplus ETH");
+        Ok(())
+    }
+
+    async fn sweep(&self) -> Result<()> {
+        let Some(cold) = self.config.cold_wallet else {
+            return Ok(());
+        };
+        let sweeps = [
+            (self.balance.weth, self.balance.target_weth, self.config.sweep_threshold_weth, "WETH", 18),
+            (self.config.usdc, U256::zero(), self.config.sweep_threshold_usdc, "USDC", 6),
+        ];
+
+        for (token, keep, threshold, symbol, decimals) in sweeps {
+            let erc20 = TreasuryToken::new(token, self.client.clone());
+            let surplus = erc20.balance_of(self.wallet).call().await?.saturating_sub(keep);
+            if surplus.is_zero() || surplus < threshold {
+                continue;
+            }
+            erc20.transfer(cold, surplus).send().await?.await?;
+            let amount = ethers::utils::format_units(surplus, decimals as u32).unwrap_or_default();
+            info!(%symbol, %amount, ?cold, "🏦 Swept surplus to cold wallet");
+            self.alerts
+                .send_alert(AlertLevel::Info, &format!("Swept {} {} to cold wallet {:?}", amount, symbol, cold))
+                .await;
+        }
+        Ok(())
+    }
+}